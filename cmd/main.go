@@ -5,24 +5,35 @@ import (
 	"fmt"
 	"log"
 	"os"
+	"os/signal"
 	"path/filepath"
 	"strings"
+	"syscall"
 	"time"
 
 	"github.com/iBreaker/llm-gateway/internal/app"
+	"github.com/iBreaker/llm-gateway/internal/backup"
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/migrate"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
 	"github.com/iBreaker/llm-gateway/pkg/debug"
 	"github.com/iBreaker/llm-gateway/pkg/logger"
 	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
 )
 
+// version 构建版本，由 -ldflags "-X main.version=..." 注入
+var version = "dev"
+
 func main() {
 	// 初始化日志系统，从环境变量检测调试模式
 	logger.EnableDebugFromEnv()
 
-	// 设置默认配置文件路径
-	configPath := "./config.yaml"
-	if home, err := os.UserHomeDir(); err == nil {
-		configPath = filepath.Join(home, ".llm-gateway", "config.yaml")
+	// 确定配置文件路径，取出命令之前的全局参数
+	args, configPath, err := configPathFromArgs(os.Args)
+	if err != nil {
+		log.Printf("错误: %v\n", err)
+		os.Exit(1)
 	}
 
 	// 初始化应用程序
@@ -34,25 +45,72 @@ func main() {
 
 	// 初始化调试模式（从配置或环境变量）
 	if config := application.Config.Get(); config != nil {
-		// 根据配置的日志级别设置 logger 模块的级别
-		switch config.Logging.Level {
+		// 根据配置的日志级别设置 logger 模块的级别，未配置时使用运行环境配置档的默认值
+		logLevel := application.Config.Profile().ResolveLogLevel(config)
+		switch logLevel {
 		case "debug":
 			logger.SetDebugLevel()
+		case "warn":
+			if !logger.IsDebugEnabled() {
+				logger.SetLevel(logger.WarnLevel)
+			}
+		case "error":
+			if !logger.IsDebugEnabled() {
+				logger.SetLevel(logger.ErrorLevel)
+			}
 		}
 
-		// 启用 trace 调试功能
-		if err := debug.EnableFromConfig(config.Logging.Level, config.Logging.File); err != nil {
+		logger.SetFormat(config.Logging.Format)
+
+		// 启用 trace 调试功能（调试模式或 logging.trace.enabled），按路由采样
+		if err := debug.EnableFromConfig(logLevel, config.Logging.File); err != nil {
 			log.Printf("启用调试模式失败: %v\n", err)
 		}
+		if config.Logging.Trace.Enabled {
+			if err := debug.Enable(); err != nil {
+				log.Printf("启用请求跟踪失败: %v\n", err)
+			}
+		}
+		debug.ConfigureSampling(&config.Logging.Trace)
 	}
 
 	// 运行CLI
-	if err := runCLI(os.Args, application); err != nil {
+	if err := runCLI(args, application); err != nil {
 		log.Printf("错误: %v\n", err)
 		os.Exit(1)
 	}
 }
 
+// configPathFromArgs 取出命令之前的全局参数 --config <path>（或 --config=<path>），返回剩余的参数和配置文件路径。
+// 没有 --config 时使用环境变量 LLM_GATEWAY_CONFIG，都没有时使用 ~/.llm-gateway/config.yaml
+func configPathFromArgs(args []string) ([]string, string, error) {
+	configPath := os.Getenv(config.ConfigPathEnvVar)
+	if configPath == "" {
+		configPath = "./config.yaml"
+		if home, err := os.UserHomeDir(); err == nil {
+			configPath = filepath.Join(home, ".llm-gateway", "config.yaml")
+		}
+	}
+
+	for i := 1; i < len(args); i++ {
+		switch arg := args[i]; {
+		case arg == "--config" || arg == "-config":
+			if i+1 >= len(args) || args[i+1] == "" {
+				return nil, "", fmt.Errorf("--config 需要配置文件路径")
+			}
+			i++
+			configPath = args[i]
+		case strings.HasPrefix(arg, "--config="):
+			if configPath = strings.TrimPrefix(arg, "--config="); configPath == "" {
+				return nil, "", fmt.Errorf("--config 需要配置文件路径")
+			}
+		default:
+			return append([]string{args[0]}, args[i:]...), configPath, nil
+		}
+	}
+	return args[:1], configPath, nil
+}
+
 func runCLI(args []string, app *app.Application) error {
 	if len(args) < 2 {
 		printUsage()
@@ -60,13 +118,13 @@ func runCLI(args []string, app *app.Application) error {
 	}
 
 	command := args[1]
-	
+
 	// Handle help flags
 	if command == "help" || command == "--help" || command == "-h" {
 		printUsage()
 		return nil
 	}
-	
+
 	switch command {
 	case "apikey":
 		return handleAPIKey(args[2:], app)
@@ -82,6 +140,10 @@ func runCLI(args []string, app *app.Application) error {
 		return handleHealthCheck(args[2:], app)
 	case "env":
 		return handleEnvironment(args[2:], app)
+	case "import":
+		return handleImport(args[2:], app)
+	case "backup":
+		return handleBackup(args[2:], app)
 	default:
 		fmt.Printf("未知命令: %s\n\n", command)
 		printUsage()
@@ -93,7 +155,10 @@ func printUsage() {
 	fmt.Println("LLM Gateway - Anthropic API Proxy")
 	fmt.Println()
 	fmt.Println("用法:")
-	fmt.Println("  llm-gateway <command> [arguments]")
+	fmt.Println("  llm-gateway [--config <path>] <command> [arguments]")
+	fmt.Println()
+	fmt.Println("全局参数:")
+	fmt.Println("  --config   配置文件路径（默认 ~/.llm-gateway/config.yaml，也可以用 LLM_GATEWAY_CONFIG 指定）")
 	fmt.Println()
 	fmt.Println("可用命令:")
 	fmt.Println("  apikey     Gateway API Key管理")
@@ -103,6 +168,8 @@ func printUsage() {
 	fmt.Println("  env        环境变量管理")
 	fmt.Println("  status     显示系统状态")
 	fmt.Println("  health     健康检查")
+	fmt.Println("  import     从LiteLLM/one-api导入配置")
+	fmt.Println("  backup     备份与恢复网关数据")
 	fmt.Println()
 	fmt.Println("使用 'llm-gateway <command> --help' 查看命令的详细帮助")
 }
@@ -149,7 +216,8 @@ func printAPIKeyUsage() {
 func handleAPIKeyAdd(args []string, app *app.Application) error {
 	fs := flag.NewFlagSet("apikey add", flag.ContinueOnError)
 	name := fs.String("name", "", "API Key名称")
-	permissions := fs.String("permissions", "read,write", "权限列表，逗号分隔")
+	permissions := fs.String("permissions", "read,write", "权限列表，逗号分隔（read、write、admin、trace）")
+	scopes := fs.String("scopes", "", "作用域列表，逗号分隔，如 provider:anthropic,model:claude-3-haiku-*")
 
 	if err := fs.Parse(args); err != nil {
 		return err
@@ -171,21 +239,48 @@ func handleAPIKeyAdd(args []string, app *app.Application) error {
 			perms = append(perms, types.PermissionWrite)
 		case "admin":
 			perms = append(perms, types.PermissionAdmin)
+		case "trace":
+			perms = append(perms, types.PermissionTrace)
 		default:
 			return fmt.Errorf("无效的权限: %s", perm)
 		}
 	}
 
+	// 解析作用域
+	var scopeList []string
+	for _, scope := range strings.Split(*scopes, ",") {
+		scope = strings.TrimSpace(scope)
+		if scope == "" {
+			continue
+		}
+		if _, _, ok := types.ParseScope(scope); !ok {
+			return fmt.Errorf("无效的作用域: %s", scope)
+		}
+		scopeList = append(scopeList, scope)
+	}
+
 	// 创建API Key
 	key, rawKey, err := app.GatewayKeyMgr.CreateKey(*name, perms)
 	if err != nil {
 		return fmt.Errorf("创建API Key失败: %w", err)
 	}
+	if len(scopeList) > 0 {
+		err := app.Config.UpdateGatewayKey(key.ID, func(gatewayKey *types.GatewayAPIKey) error {
+			gatewayKey.Scopes = scopeList
+			return nil
+		})
+		if err != nil {
+			return fmt.Errorf("设置API Key作用域失败: %w", err)
+		}
+	}
 
 	fmt.Printf("成功创建Gateway API Key:\n")
 	fmt.Printf("  ID: %s\n", key.ID)
 	fmt.Printf("  名称: %s\n", key.Name)
 	fmt.Printf("  权限: %v\n", perms)
+	if len(scopeList) > 0 {
+		fmt.Printf("  作用域: %v\n", scopeList)
+	}
 	fmt.Printf("  密钥: %s\n", rawKey)
 	fmt.Printf("  状态: %s\n", key.Status)
 	fmt.Println()
@@ -208,6 +303,9 @@ func handleAPIKeyList(args []string, app *app.Application) error {
 		fmt.Printf("ID: %s\n", key.ID)
 		fmt.Printf("  名称: %s\n", key.Name)
 		fmt.Printf("  权限: %v\n", key.Permissions)
+		if len(key.Scopes) > 0 {
+			fmt.Printf("  作用域: %v\n", key.Scopes)
+		}
 		fmt.Printf("  状态: %s\n", key.Status)
 		fmt.Printf("  创建时间: %s\n", key.CreatedAt.Format("2006-01-02 15:04:05"))
 
@@ -345,9 +443,13 @@ func handleUpstreamAdd(args []string, app *app.Application) error {
 	fs := flag.NewFlagSet("upstream add", flag.ContinueOnError)
 	accountType := fs.String("type", "", "账号类型 (api-key, oauth)")
 	name := fs.String("name", "", "账号名称")
-	provider := fs.String("provider", "", "提供商 (anthropic, openai, google, azure, qwen)")
-	baseURL := fs.String("base-url", "", "自定义API端点URL (可选)")
-	apiKey := fs.String("key", "", "API密钥 (type=api-key时必需)")
+	provider := fs.String("provider", "", "提供商 (anthropic, openai, google, azure, bedrock, openai-compatible, qwen)")
+	baseURL := fs.String("base-url", "", "自定义API端点URL (provider=openai-compatible时必需，不含/v1)")
+	apiKey := fs.String("key", "", "API密钥 (type=api-key时必需，bedrock和openai-compatible除外)")
+	awsAccessKeyID := fs.String("aws-access-key-id", "", "AWS Access Key ID (provider=bedrock时必需)")
+	awsSecretAccessKey := fs.String("aws-secret-access-key", "", "AWS Secret Access Key (provider=bedrock时必需)")
+	awsRegion := fs.String("aws-region", "", "AWS区域，如 us-east-1 (provider=bedrock时必需)")
+	skipVerify := fs.Bool("skip-verify", false, "跳过添加前的凭证验证")
 
 	if err := fs.Parse(args); err != nil {
 		return err
@@ -365,7 +467,7 @@ func handleUpstreamAdd(args []string, app *app.Application) error {
 	switch *accountType {
 	case "api-key":
 		upstreamType = types.UpstreamTypeAPIKey
-		if *apiKey == "" {
+		if *apiKey == "" && *provider != "bedrock" && *provider != "openai-compatible" {
 			return fmt.Errorf("API Key类型账号缺少参数: --key")
 		}
 	case "oauth":
@@ -391,10 +493,23 @@ func handleUpstreamAdd(args []string, app *app.Application) error {
 		providerType = types.ProviderGoogle
 	case "azure":
 		providerType = types.ProviderAzure
+	case "bedrock":
+		providerType = types.ProviderBedrock
+		if upstreamType != types.UpstreamTypeAPIKey {
+			return fmt.Errorf("bedrock 只支持 api-key 类型账号")
+		}
+		if *awsAccessKeyID == "" || *awsSecretAccessKey == "" || *awsRegion == "" {
+			return fmt.Errorf("bedrock 账号缺少参数: --aws-access-key-id、--aws-secret-access-key 和 --aws-region")
+		}
+	case "openai-compatible":
+		providerType = types.ProviderOpenAICompatible
+		if *baseURL == "" {
+			return fmt.Errorf("openai-compatible 账号缺少参数: --base-url（如 http://localhost:11434）")
+		}
 	case "qwen":
 		providerType = types.ProviderQwen
 	default:
-		return fmt.Errorf("无效的提供商: %s (支持: anthropic, openai, google, azure, qwen)", *provider)
+		return fmt.Errorf("无效的提供商: %s (支持: anthropic, openai, google, azure, bedrock, openai-compatible, qwen)", *provider)
 	}
 
 	// 创建上游账号
@@ -410,8 +525,26 @@ func handleUpstreamAdd(args []string, app *app.Application) error {
 	if upstreamType == types.UpstreamTypeAPIKey {
 		account.APIKey = *apiKey
 	}
+	if providerType == types.ProviderBedrock {
+		account.AWS = &types.AWSCredentials{
+			AccessKeyID:     *awsAccessKeyID,
+			SecretAccessKey: *awsSecretAccessKey,
+			Region:          *awsRegion,
+		}
+	}
 	// OAuth账号不需要设置client credentials，使用固定配置
 
+	// 添加前验证凭证：上游拒绝凭证时不添加，其他失败时添加为不健康账号
+	var verification *upstream.HealthResult
+	if !*skipVerify {
+		if verification = app.HealthService.Verify(account); verification != nil {
+			if verification.CredentialRejected() {
+				return fmt.Errorf("上游拒绝了凭证: %s（确认无误可使用 --skip-verify 跳过验证）", verification.Error)
+			}
+			upstream.ApplyHealthResult(account, verification)
+		}
+	}
+
 	// 添加账号
 	if err := app.UpstreamMgr.AddAccount(account); err != nil {
 		return fmt.Errorf("添加上游账号失败: %w", err)
@@ -423,6 +556,9 @@ func handleUpstreamAdd(args []string, app *app.Application) error {
 	fmt.Printf("  类型: %s\n", account.Type)
 	fmt.Printf("  提供商: %s\n", account.Provider)
 	fmt.Printf("  状态: %s\n", account.Status)
+	if verification != nil && !verification.Healthy {
+		fmt.Printf("⚠️  凭证验证未通过，账号已标记为不健康: %s\n", verification.Error)
+	}
 
 	// 如果是OAuth账号，启动交互式授权流程
 	if upstreamType == types.UpstreamTypeOAuth {
@@ -501,14 +637,14 @@ func handleUpstreamShow(args []string, app *app.Application) error {
 	}
 
 	if account.Type == types.UpstreamTypeAPIKey {
-		fmt.Printf("API Key: %s***\n", account.APIKey[:8])
+		fmt.Printf("API Key: %s***\n", utils.TruncateUTF8(account.APIKey, 8))
 	} else {
 		fmt.Printf("Client ID: %s\n", account.ClientID)
 		if account.ExpiresAt != nil {
 			fmt.Printf("Token过期时间: %s\n", account.ExpiresAt.Format("2006-01-02 15:04:05"))
 		}
 		if account.AccessToken != "" {
-			fmt.Printf("Access Token: %s***\n", account.AccessToken[:8])
+			fmt.Printf("Access Token: %s***\n", utils.TruncateUTF8(account.AccessToken, 8))
 		}
 	}
 
@@ -615,11 +751,27 @@ func printServerUsage() {
 func handleServerStart(args []string, app *app.Application) error {
 	fmt.Printf("启动LLM Gateway HTTP服务器...\n")
 
+	// 按运行环境配置档校验配置，未通过时拒绝启动
+	profile := app.Config.Profile()
+	if err := app.Config.ValidateProfile(); err != nil {
+		return fmt.Errorf("配置未通过 %s 环境校验: %w", profile.Name, err)
+	}
+
 	// 显示服务器配置信息
 	config := app.Config.Get()
+	fmt.Printf("运行环境: %s\n", profile.Name)
 	fmt.Printf("监听地址: %s:%d\n", config.Server.Host, config.Server.Port)
 	fmt.Printf("请求超时: %d秒\n", config.Server.Timeout)
 
+	// 按配置调整Go运行时参数并显示生效的配置
+	runtimeInfo, err := app.Config.ApplyRuntime()
+	if err != nil {
+		return err
+	}
+	fmt.Printf("版本: %s\n", version)
+	fmt.Printf("运行时: %s\n", runtimeInfo)
+	app.HTTPServer.SetVersion(version)
+
 	// 显示统计信息
 	gatewayKeys := app.GatewayKeyMgr.ListKeys()
 	upstreamAccounts := app.UpstreamMgr.ListAccounts()
@@ -636,10 +788,32 @@ func handleServerStart(args []string, app *app.Application) error {
 	fmt.Printf("  活跃上游账号: %d个\n", activeUpstreams)
 	fmt.Println()
 
-	// 启动HTTP服务器 (这会阻塞)
+	// 启动后台任务（SLO监控等）
+	app.StartBackgroundServices()
+
+	// 启动HTTP服务器，收到 SIGINT/SIGTERM 后优雅关闭
 	fmt.Println("服务器启动中，按 Ctrl+C 停止...")
-	if err := app.HTTPServer.Start(); err != nil {
+	serverErr := make(chan error, 1)
+	go func() {
+		serverErr <- app.HTTPServer.Start()
+	}()
+
+	signals := make(chan os.Signal, 1)
+	signal.Notify(signals, os.Interrupt, syscall.SIGTERM)
+	defer signal.Stop(signals)
+
+	select {
+	case err := <-serverErr:
+		app.StopBackgroundServices()
 		return fmt.Errorf("启动服务器失败: %w", err)
+	case sig := <-signals:
+		fmt.Printf("\n收到信号 %s，停止接收新请求并等待进行中的请求结束（再次按 Ctrl+C 强制退出）...\n", sig)
+		go func() {
+			<-signals
+			fmt.Println("强制退出")
+			os.Exit(1)
+		}()
+		app.Shutdown()
 	}
 
 	return nil
@@ -650,6 +824,8 @@ func handleServerStatus(args []string, app *app.Application) error {
 
 	fmt.Println("LLM Gateway 服务器状态:")
 	fmt.Printf("配置文件: %s\n", app.Config.GetConfigPath())
+	profile := app.Config.Profile()
+	fmt.Printf("运行环境: %s (容忍不安全凭证: %v)\n", profile.Name, profile.AllowInsecureSecrets)
 	fmt.Printf("监听地址: %s:%d\n", config.Server.Host, config.Server.Port)
 	fmt.Printf("请求超时: %d秒\n", config.Server.Timeout)
 
@@ -802,8 +978,30 @@ func handleSystemStatus(args []string, app *app.Application) error {
 	return nil
 }
 
+// handleHealthCheck 探测上游账号（未指定ID时探测所有未禁用的账号）
 func handleHealthCheck(args []string, app *app.Application) error {
-	fmt.Println("健康检查功能待实现")
+	var results []*upstream.HealthResult
+	if len(args) > 0 {
+		results = app.HealthService.CheckMany(args)
+	} else {
+		results = app.HealthService.CheckAll()
+	}
+
+	if len(results) == 0 {
+		fmt.Println("没有需要检查的上游账号")
+		return nil
+	}
+
+	healthy := 0
+	for _, result := range results {
+		if result.Healthy {
+			healthy++
+			fmt.Printf("✅ %s (%s, %s) %dms\n", result.UpstreamID, result.Name, result.Provider, result.LatencyMs)
+		} else {
+			fmt.Printf("❌ %s (%s, %s) %s\n", result.UpstreamID, result.Name, result.Provider, result.Error)
+		}
+	}
+	fmt.Printf("\n健康: %d/%d\n", healthy, len(results))
 	return nil
 }
 
@@ -884,6 +1082,210 @@ func startInteractiveOAuth(app *app.Application, upstreamID string) error {
 	}
 }
 
+// ===== Import 命令处理器 =====
+
+func handleImport(args []string, app *app.Application) error {
+	fs := flag.NewFlagSet("import", flag.ContinueOnError)
+	from := fs.String("from", "", "导入来源: litellm 或 one-api")
+	file := fs.String("file", "", "LiteLLM config.yaml 或 one-api 导出的JSON文件")
+	dryRun := fs.Bool("dry-run", false, "只显示导入计划，不写入配置")
+
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	if *from == "" || *file == "" {
+		return fmt.Errorf("缺少必要参数: --from 和 --file")
+	}
+
+	data, err := os.ReadFile(*file)
+	if err != nil {
+		return fmt.Errorf("读取文件失败: %w", err)
+	}
+
+	var plan *migrate.Plan
+	switch *from {
+	case migrate.SourceLiteLLM:
+		plan, err = migrate.ParseLiteLLM(data, os.Getenv)
+	case migrate.SourceOneAPI:
+		plan, err = migrate.ParseOneAPI(data)
+	default:
+		return fmt.Errorf("不支持的导入来源: %s", *from)
+	}
+	if err != nil {
+		return err
+	}
+
+	fmt.Printf("导入计划 (%s):\n", plan.Source)
+	fmt.Printf("  上游账号: %d个\n", len(plan.Accounts))
+	for _, account := range plan.Accounts {
+		fmt.Printf("    %s (%s, %s)\n", account.ID, account.Name, account.Provider)
+	}
+	fmt.Printf("  模型路由: %d条\n", len(plan.ModelRoutes))
+	for _, route := range plan.ModelRoutes {
+		fmt.Printf("    %s -> %s/%s\n", route.SourceModel, route.TargetProvider, route.TargetModel)
+	}
+	fmt.Printf("  路由规则: %d条\n", len(plan.RoutingRules))
+	for _, rule := range plan.RoutingRules {
+		fmt.Printf("    %s -> %s %v\n", rule.Pattern, rule.Provider, rule.UpstreamIDs)
+	}
+	fmt.Printf("  API Key: %d个\n", len(plan.Keys))
+
+	if len(plan.Warnings) > 0 {
+		fmt.Printf("\n⚠️  以下内容无法转换:\n")
+		for _, warning := range plan.Warnings {
+			fmt.Printf("  - %s\n", warning)
+		}
+	}
+
+	if *dryRun {
+		return nil
+	}
+
+	result := migrate.Apply(plan, app.Config, app.UpstreamMgr, app.GatewayKeyMgr)
+	fmt.Printf("\n✅ 已导入: 上游账号 %d个, 模型路由 %d条, 路由规则 %d条, API Key %d个\n",
+		result.Accounts, result.ModelRoutes, result.RoutingRules, result.Keys)
+	if len(result.Failures) > 0 {
+		fmt.Printf("❌ 导入失败:\n")
+		for _, failure := range result.Failures {
+			fmt.Printf("  - %s\n", failure)
+		}
+	}
+	return nil
+}
+
+// ===== Backup 命令处理器 =====
+
+func handleBackup(args []string, app *app.Application) error {
+	if len(args) == 0 {
+		printBackupUsage()
+		return nil
+	}
+
+	subcommand := args[0]
+	switch subcommand {
+	case "run":
+		return handleBackupRun(args[1:], app)
+	case "list":
+		return handleBackupList(args[1:], app)
+	case "restore":
+		return handleBackupRestore(args[1:], app)
+	default:
+		fmt.Printf("未知的backup子命令: %s\n\n", subcommand)
+		printBackupUsage()
+		return fmt.Errorf("未知的backup子命令: %s", subcommand)
+	}
+}
+
+func printBackupUsage() {
+	fmt.Println("用法: llm-gateway backup <subcommand>")
+	fmt.Println("描述: 备份与恢复网关数据（Gateway Key、上游账号、模型路由、路由规则、公告）")
+	fmt.Println()
+	fmt.Println("子命令:")
+	fmt.Println("  run        立即备份到对象存储")
+	fmt.Println("  list       列出对象存储中的备份")
+	fmt.Println("  restore    从备份恢复（会替换当前的网关数据，请先停止服务器）")
+	fmt.Println()
+	fmt.Println("示例:")
+	fmt.Println("  llm-gateway backup run")
+	fmt.Println("  llm-gateway backup list")
+	fmt.Println("  llm-gateway backup restore --key=backups/llm-gateway-20240620T120000Z.yaml")
+	fmt.Println("  llm-gateway backup restore --file=./llm-gateway-20240620T120000Z.yaml --dry-run")
+}
+
+func handleBackupRun(args []string, app *app.Application) error {
+	service, err := backup.NewService(&app.Config.Get().Backup, app.Config)
+	if err != nil {
+		return err
+	}
+
+	key, err := service.Run(time.Now())
+	if err != nil {
+		return err
+	}
+	fmt.Printf("✅ 备份完成: %s\n", key)
+	return nil
+}
+
+func handleBackupList(args []string, app *app.Application) error {
+	service, err := backup.NewService(&app.Config.Get().Backup, app.Config)
+	if err != nil {
+		return err
+	}
+
+	backups, err := service.List()
+	if err != nil {
+		return err
+	}
+	if len(backups) == 0 {
+		fmt.Println("没有备份")
+		return nil
+	}
+
+	fmt.Printf("%-60s %-10s %s\n", "KEY", "SIZE", "LAST MODIFIED")
+	for _, object := range backups {
+		fmt.Printf("%-60s %-10d %s\n", object.Key, object.Size, object.LastModified.Local().Format("2006-01-02 15:04:05"))
+	}
+	return nil
+}
+
+func handleBackupRestore(args []string, app *app.Application) error {
+	fs := flag.NewFlagSet("backup restore", flag.ContinueOnError)
+	key := fs.String("key", "", "对象存储中的备份键")
+	file := fs.String("file", "", "本地备份文件")
+	dryRun := fs.Bool("dry-run", false, "只显示备份内容，不写入配置")
+
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	if (*key == "") == (*file == "") {
+		return fmt.Errorf("需要且只能指定一个参数: --key 或 --file")
+	}
+
+	backupConfig := &app.Config.Get().Backup
+	var snapshot *backup.Snapshot
+	var accounts []types.UpstreamAccount
+	if *key != "" {
+		service, err := backup.NewService(backupConfig, app.Config)
+		if err != nil {
+			return err
+		}
+		if snapshot, accounts, err = service.Fetch(*key); err != nil {
+			return err
+		}
+	} else {
+		data, err := os.ReadFile(*file)
+		if err != nil {
+			return fmt.Errorf("读取文件失败: %w", err)
+		}
+		passphrase := backup.Passphrase(backupConfig)
+		if passphrase == "" {
+			return fmt.Errorf("未配置备份口令（backup.passphrase 或环境变量 %s）", backup.PassphraseEnv)
+		}
+		if snapshot, accounts, err = backup.Open(data, passphrase); err != nil {
+			return err
+		}
+	}
+
+	fmt.Printf("备份时间: %s\n", snapshot.CreatedAt.Local().Format("2006-01-02 15:04:05"))
+	fmt.Printf("  API Key: %d个\n", len(snapshot.GatewayKeys))
+	fmt.Printf("  上游账号: %d个\n", len(accounts))
+	fmt.Printf("  模型路由: %d条\n", len(snapshot.ModelRoutes.Routes))
+	fmt.Printf("  路由规则: %d条\n", len(snapshot.RoutingRules))
+	fmt.Printf("  公告: %d条\n", len(snapshot.Announcements))
+
+	if *dryRun {
+		return nil
+	}
+
+	if err := backup.Restore(app.Config, snapshot, accounts); err != nil {
+		return err
+	}
+	fmt.Println("\n✅ 恢复完成，重启服务器后生效")
+	return nil
+}
+
 // ===== Environment 命令处理器 =====
 
 func handleEnvironment(args []string, app *app.Application) error {
@@ -940,6 +1342,15 @@ func handleEnvList(args []string, app *app.Application) error {
 	fmt.Printf("  HTTPS_PROXY: %s\n", os.Getenv("HTTPS_PROXY"))
 	fmt.Printf("  NO_PROXY:    %s\n", os.Getenv("NO_PROXY"))
 
+	// 环境变量覆盖的配置项（不显示值，可能是密钥）
+	if overrides := app.Config.EnvOverrides(); len(overrides) > 0 {
+		fmt.Println()
+		fmt.Println("环境变量覆盖的配置项:")
+		for _, override := range overrides {
+			fmt.Printf("  %-40s %s\n", override.EnvVar, override.Key)
+		}
+	}
+
 	return nil
 }
 
@@ -1,12 +1,27 @@
 package app
 
 import (
+	"context"
+	"fmt"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/audit"
+	"github.com/iBreaker/llm-gateway/internal/backup"
+	"github.com/iBreaker/llm-gateway/internal/canary"
 	"github.com/iBreaker/llm-gateway/internal/client"
 	"github.com/iBreaker/llm-gateway/internal/config"
 	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/hygiene"
+	"github.com/iBreaker/llm-gateway/internal/notify"
+	"github.com/iBreaker/llm-gateway/internal/pricing"
+	"github.com/iBreaker/llm-gateway/internal/ratelimit"
+	"github.com/iBreaker/llm-gateway/internal/redis"
 	"github.com/iBreaker/llm-gateway/internal/router"
 	"github.com/iBreaker/llm-gateway/internal/server"
+	"github.com/iBreaker/llm-gateway/internal/stats"
 	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
 )
 
 // Application 应用程序上下文
@@ -15,15 +30,38 @@ type Application struct {
 	GatewayKeyMgr *client.GatewayKeyManager
 	UpstreamMgr   *upstream.UpstreamManager
 	OAuthMgr      *upstream.OAuthManager
+	TokenRefresh  *upstream.TokenRefreshService
+	HealthService *upstream.HealthService
+	HealthChecks  *upstream.HealthScheduler
 	Router        *router.RequestRouter
+	Autopilot     *router.Autopilot
 	Converter     *converter.Manager
+	Recorder      *stats.Recorder
+	UsageWAL      *stats.WAL // 未启用使用记录持久化时为nil
+	Rollups       *stats.Rollups
+	SLOMonitor    *stats.SLOMonitor
+	Hygiene       *hygiene.Monitor
+	Audit         *audit.Log
+	Notifier      *notify.Service
+	Canaries      *canary.Runner
+	Backup        *backup.Service       // 未启用定时备份时为nil
+	BreakerSync   *upstream.BreakerSync // 未使用 Redis 共享状态时为nil
+	Redis         *redis.Client         // 未使用 Redis 共享状态时为nil
+	ConfigWatch   *config.Watcher       // 未配置 server.config_watch_seconds 时为nil
 	HTTPServer    *server.HTTPServer
 }
 
 // NewApplication 创建新的应用程序实例
 func NewApplication(configPath string) (*Application, error) {
+	// 根据 GATEWAY_ENV 选择运行环境配置档
+	profile, err := config.ProfileFromEnv()
+	if err != nil {
+		return nil, err
+	}
+
 	// 初始化配置管理器
 	configMgr := config.NewConfigManager(configPath)
+	configMgr.SetProfile(profile)
 
 	// 加载配置
 	cfg, err := configMgr.Load()
@@ -31,25 +69,132 @@ func NewApplication(configPath string) (*Application, error) {
 		return nil, err
 	}
 
+	// 应用配置档决定的CORS策略
+	server.ConfigureCORS(profile.ResolveCORSOrigins(cfg))
+
+	// 自定义模型价格覆盖内置价格表
+	pricing.SetCustomPrices(cfg.Pricing.Models)
+
 	// 初始化各个组件，使用ConfigManager作为数据层
 	gatewayKeyMgr := client.NewGatewayKeyManager(configMgr)
 	upstreamMgr := upstream.NewUpstreamManager(configMgr)
+	upstreamMgr.Providers().ApplySettings(cfg.Providers)
+	if err := upstreamMgr.Breakers().LoadHistory(cfg.HealthCheck.HistoryDir); err != nil {
+		logger.Warn("加载熔断记录失败: %v", err)
+	}
+	upstreamMgr.Breakers().Configure(func() *types.CircuitBreakerConfig { return &configMgr.Get().HealthCheck.CircuitBreaker })
+	upstreamMgr.Overloads().Configure(&cfg.Proxy.OverloadBackoff)
+
+	// 多实例部署时限流令牌桶、熔断器和过载退避状态保存在 Redis 中共享
+	var redisClient *redis.Client
+	var rateLimiter ratelimit.WindowLimiter
+	var breakerSync *upstream.BreakerSync
+	if cfg.SharedState.Backend == "redis" {
+		if redisClient, err = redis.NewClient(&cfg.SharedState.Redis); err != nil {
+			return nil, err
+		}
+		if err := redisClient.Ping(); err != nil {
+			logger.Warn("Redis 暂时不可用，恢复前限流使用本实例的计数: %v", err)
+		}
+		rateLimiter = ratelimit.NewRedisLimiter(redisClient)
+		upstreamMgr.Breakers().SetStore(upstream.NewRedisBreakerStore(redisClient))
+		upstreamMgr.Overloads().SetStore(upstream.NewRedisOverloadStore(redisClient))
+		breakerSync = upstream.NewBreakerSync(upstreamMgr.Breakers(), upstreamMgr.Overloads(), time.Duration(cfg.SharedState.SyncIntervalSeconds)*time.Second)
+	}
 	oauthMgr := upstream.NewOAuthManager(upstreamMgr)
+	tokenRefresh := upstream.NewTokenRefreshService(oauthMgr, time.Minute)
+	healthSettings := func() *types.HealthCheckConfig { return &configMgr.Get().HealthCheck }
+	healthService := upstream.NewHealthService(upstreamMgr, healthSettings)
+	healthScheduler := upstream.NewHealthScheduler(healthService, healthSettings)
 	converter := converter.NewManager()
+	recorder := stats.NewRecorder(0)
 
-	// 设置路由器策略
-	requestRouter := router.NewRequestRouter(upstreamMgr, router.StrategyHealthFirst)
+	// 恢复上次运行的使用记录后再订阅，新记录由后台任务追加到日志
+	var usageWAL *stats.WAL
+	if cfg.UsageWAL.Enabled {
+		usageWAL = stats.NewWAL(&cfg.UsageWAL, 0)
+		if replayed, err := usageWAL.Replay(recorder); err != nil {
+			logger.Warn("恢复使用记录失败: %v", err)
+		} else if replayed > 0 {
+			logger.Info("已恢复 %d 条使用记录", replayed)
+		}
+		recorder.Subscribe(usageWAL.Append)
+	}
+	rollups := stats.NewRollups(recorder, time.Minute)
+	sloMonitor := stats.NewSLOMonitor(recorder, func() *types.SLOConfig { return &configMgr.Get().SLO }, time.Minute)
+	hygieneMonitor := hygiene.NewMonitor(configMgr, func() *types.HygieneConfig { return &configMgr.Get().Hygiene }, time.Hour)
+	auditLog := audit.NewLog(&cfg.Audit)
+	notifier := notify.NewService(func() *types.NotificationConfig { return &configMgr.Get().Notifications }, recorder, upstreamMgr, time.Minute)
+	notifier.SetRateLimitSource(upstreamMgr.RateLimits())
+	// SLO错误预算燃烧过快时发送告警
+	sloMonitor.SetAlertHandler(func(alert stats.SLOAlert) {
+		notifier.Alert(notify.EventSLOBurnRate,
+			fmt.Sprintf("SLO error budget for %s %s is burning at %.1fx (threshold %.1fx)", alert.Dimension, alert.ID, alert.BurnRate, alert.Threshold),
+			map[string]interface{}{"dimension": alert.Dimension, "id": alert.ID, "burn_rate": alert.BurnRate, "threshold": alert.Threshold},
+			alert.FiredAt)
+	})
+	canaries := canary.NewRunner(func() *types.CanaryConfig { return &configMgr.Get().Canaries }, upstreamMgr, converter, healthService, notifier)
+
+	// 账号凭证连续被拒绝而被自动停用时发送告警并写入审计日志
+	upstreamMgr.ConfigureAutoDisable(cfg.HealthCheck.AuthFailureThreshold, func(account *types.UpstreamAccount, failures int, now time.Time) {
+		notifier.Alert(notify.EventAccountDisabled,
+			fmt.Sprintf("Upstream account %s (%s) was disabled after %d consecutive credential rejections", account.Name, account.ID, failures),
+			map[string]interface{}{"upstream_id": account.ID, "provider": account.Provider, "failures": failures, "reason": account.DisabledReason},
+			now)
+		auditLog.RecordEvent(&audit.Entry{
+			Timestamp:  now,
+			UpstreamID: account.ID,
+			Provider:   account.Provider,
+			Event:      audit.EventAccountAutoDisabled,
+			Message:    account.DisabledReason,
+		})
+	})
+
+	var backupService *backup.Service
+	if cfg.Backup.Enabled {
+		if backupService, err = backup.NewService(&cfg.Backup, configMgr); err != nil {
+			logger.Warn("定时备份未启动: %v", err)
+		}
+	}
+
+	// 设置路由器策略，自动切换在延迟异常或流量突增时临时改用其他策略
+	requestRouter := router.NewRequestRouter(upstreamMgr, router.ConfiguredStrategy(&cfg.Routing))
+	requestRouter.SetRoutingRuleSource(configMgr)
+	autopilot := router.NewAutopilot(requestRouter, recorder, func() *types.RoutingConfig { return &configMgr.Get().Routing })
 
 	// 创建HTTP服务器
-	httpServer := server.NewServer(cfg, gatewayKeyMgr, upstreamMgr, requestRouter, converter, configMgr, oauthMgr)
+	httpServer := server.NewServer(cfg, gatewayKeyMgr, upstreamMgr, requestRouter, converter, configMgr, oauthMgr, healthService, recorder, auditLog, notifier, canaries, usageWAL, rollups, autopilot, rateLimiter)
+
+	// 配置文件被外部修改后自动重新加载（也可以通过 POST /api/v1/config/reload 手动触发）
+	configWatch := config.NewWatcher(configMgr, time.Duration(cfg.Server.ConfigWatchSeconds)*time.Second, func() {
+		if _, err := httpServer.ReloadConfig(); err != nil {
+			logger.Error("重新加载配置失败: %v", err)
+		}
+	})
 
 	app := &Application{
 		Config:        configMgr,
 		GatewayKeyMgr: gatewayKeyMgr,
 		UpstreamMgr:   upstreamMgr,
 		OAuthMgr:      oauthMgr,
+		TokenRefresh:  tokenRefresh,
+		HealthService: healthService,
+		HealthChecks:  healthScheduler,
 		Router:        requestRouter,
+		Autopilot:     autopilot,
 		Converter:     converter,
+		Recorder:      recorder,
+		UsageWAL:      usageWAL,
+		Rollups:       rollups,
+		SLOMonitor:    sloMonitor,
+		Hygiene:       hygieneMonitor,
+		Audit:         auditLog,
+		Notifier:      notifier,
+		Canaries:      canaries,
+		Backup:        backupService,
+		BreakerSync:   breakerSync,
+		Redis:         redisClient,
+		ConfigWatch:   configWatch,
 		HTTPServer:    httpServer,
 	}
 
@@ -57,3 +202,61 @@ func NewApplication(configPath string) (*Application, error) {
 
 	return app, nil
 }
+
+// StartBackgroundServices 启动服务器运行期间的后台任务
+func (a *Application) StartBackgroundServices() {
+	a.SLOMonitor.Start()
+	a.Hygiene.Start()
+	a.HealthChecks.Start()
+	a.TokenRefresh.Start()
+	a.Audit.Start()
+	a.Notifier.Start()
+	a.Canaries.Start()
+	a.Backup.Start()
+	a.UsageWAL.Start()
+	a.Rollups.Start()
+	a.Autopilot.Start()
+	a.BreakerSync.Start()
+	a.ConfigWatch.Start()
+}
+
+// defaultDrainTimeout 未配置 server.drain_timeout_seconds 时的排空等待时间
+const defaultDrainTimeout = 30 * time.Second
+
+// Shutdown 优雅关闭：先停止接收新的代理请求并等待进行中的请求（包括流式响应）和统计写入完成，
+// 超过 server.drain_timeout_seconds 后强制关闭剩余连接，然后停止后台任务
+func (a *Application) Shutdown() {
+	timeout := time.Duration(a.Config.Get().Server.DrainTimeoutSeconds) * time.Second
+	if timeout <= 0 {
+		timeout = defaultDrainTimeout
+	}
+
+	ctx, cancel := context.WithTimeout(context.Background(), timeout)
+	defer cancel()
+
+	interrupted := a.HTTPServer.Drain(ctx)
+	a.StopBackgroundServices()
+	a.Redis.Close()
+	if interrupted > 0 {
+		logger.Warn("服务器已关闭，%d 个请求被中断", interrupted)
+	} else {
+		logger.Info("服务器已关闭")
+	}
+}
+
+// StopBackgroundServices 停止后台任务
+func (a *Application) StopBackgroundServices() {
+	a.SLOMonitor.Stop()
+	a.Hygiene.Stop()
+	a.HealthChecks.Stop()
+	a.TokenRefresh.Stop()
+	a.Audit.Stop()
+	a.Notifier.Stop()
+	a.Canaries.Stop()
+	a.Backup.Stop()
+	a.UsageWAL.Stop()
+	a.Rollups.Stop()
+	a.Autopilot.Stop()
+	a.BreakerSync.Stop()
+	a.ConfigWatch.Stop()
+}
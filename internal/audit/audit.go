@@ -0,0 +1,520 @@
+package audit
+
+import (
+	"bufio"
+	"bytes"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"hash"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 请求体/响应体的保存方式
+const (
+	BodyModeFull = "full" // 脱敏后保存，超过 max_body_bytes 截断
+	BodyModeHash = "hash" // 只保存SHA-256和长度
+)
+
+// EventAccountAutoDisabled 上游账号因凭证连续被拒绝而被自动停用
+const EventAccountAutoDisabled = "account_auto_disabled"
+
+// dayLayout 审计文件按UTC日期分文件
+const dayLayout = "2006-01-02"
+
+// Body 请求体或响应体的审计内容
+type Body struct {
+	Size      int    `json:"size"`
+	SHA256    string `json:"sha256"` // 完整原始内容的哈希，可用于核对而无需保存原文
+	Content   string `json:"content,omitempty"`
+	Truncated bool   `json:"truncated,omitempty"`
+
+	// Chunks 开启去重时 Content 按块保存在块存储中，这里是按顺序排列的块哈希；查询时拼接回 Content
+	Chunks []string `json:"chunks,omitempty"`
+
+	// ObjectKey 完整内容（最多 max_object_bytes）在对象存储中的位置，URL 在查询时生成
+	ObjectKey string `json:"object_key,omitempty"`
+	URL       string `json:"url,omitempty"`
+}
+
+// Entry 一次代理请求的审计记录，或网关自身触发的事件（Event 不为空，没有请求体/响应体）
+type Entry struct {
+	RequestID         string         `json:"request_id"`
+	Timestamp         time.Time      `json:"timestamp"`
+	GatewayKeyID      string         `json:"gateway_key_id"`
+	UpstreamID        string         `json:"upstream_id,omitempty"`
+	UpstreamRequestID string         `json:"upstream_request_id,omitempty"` // 上游返回的请求ID，用于向提供商提交工单
+	Provider          types.Provider `json:"provider,omitempty"`
+	Model             string         `json:"model,omitempty"`
+	Endpoint          string         `json:"endpoint"`
+	StatusCode        int            `json:"status_code"`
+	LatencyMs         int64          `json:"latency_ms"`
+	Request           *Body          `json:"request"`
+	Response          *Body          `json:"response"`
+	Event             string         `json:"event,omitempty"`   // 网关事件类型，如 account_auto_disabled
+	Message           string         `json:"message,omitempty"` // 事件说明
+}
+
+// Filter 审计记录查询条件，零值字段表示不过滤
+type Filter struct {
+	Since        time.Time
+	Until        time.Time
+	GatewayKeyID string
+	RequestID    string
+	Limit        int
+}
+
+// Match 检查记录是否满足过滤条件
+func (f *Filter) Match(entry *Entry) bool {
+	if !f.Since.IsZero() && entry.Timestamp.Before(f.Since) {
+		return false
+	}
+	if !f.Until.IsZero() && !entry.Timestamp.Before(f.Until) {
+		return false
+	}
+	if f.GatewayKeyID != "" && entry.GatewayKeyID != f.GatewayKeyID {
+		return false
+	}
+	if f.RequestID != "" && entry.RequestID != f.RequestID {
+		return false
+	}
+	return true
+}
+
+// Capture 增量记录请求体/响应体：对完整内容计算哈希，只缓存前 limit 字节
+type Capture struct {
+	hash   hash.Hash
+	size   int
+	buffer bytes.Buffer
+	limit  int
+}
+
+// Write 写入内容
+func (c *Capture) Write(p []byte) (int, error) {
+	c.hash.Write(p)
+	c.size += len(p)
+	if remaining := c.limit - c.buffer.Len(); remaining > 0 {
+		if len(p) > remaining {
+			c.buffer.Write(p[:remaining])
+		} else {
+			c.buffer.Write(p)
+		}
+	}
+	return len(p), nil
+}
+
+// Log 审计日志，每天一个JSON Lines文件，超过保留期的文件由后台任务删除
+type Log struct {
+	config   *types.AuditConfig
+	redactor *Redactor
+	tier     *objectTier // 未配置对象存储时为nil
+	chunks   *chunkStore // 未开启内容去重时为nil
+	stopCh   chan struct{}
+	mutex    sync.Mutex // 保护文件写入和后台任务状态
+}
+
+// NewLog 创建审计日志
+func NewLog(config *types.AuditConfig) *Log {
+	l := &Log{
+		config:   config,
+		redactor: NewRedactor(config.RedactFields, config.KeepPII),
+		tier:     newObjectTier(config.ObjectStore),
+	}
+	if config.Dedup.Enabled {
+		l.chunks = &chunkStore{dir: filepath.Join(l.dir(), "chunks")}
+	}
+	return l
+}
+
+// ShouldAudit 检查是否需要审计该Gateway Key的请求
+func (l *Log) ShouldAudit(keyID string) bool {
+	if l == nil || !l.config.Enabled {
+		return false
+	}
+	if len(l.config.KeyIDs) == 0 {
+		return true
+	}
+	for _, id := range l.config.KeyIDs {
+		if id == keyID {
+			return true
+		}
+	}
+	return false
+}
+
+// NewCapture 创建请求体/响应体的记录器
+func (l *Log) NewCapture() *Capture {
+	limit := 0
+	if l.config.BodyMode != BodyModeHash {
+		limit = l.config.MaxBodyBytes
+		if l.tier != nil && l.tier.config.MaxObjectBytes > limit {
+			limit = l.tier.config.MaxObjectBytes
+		}
+	}
+	return &Capture{hash: sha256.New(), limit: limit}
+}
+
+// Record 脱敏后写入一条审计记录（写文件并可能上传对象存储，调用方应在goroutine中调用）
+func (l *Log) Record(entry *Entry, request, response *Capture) {
+	entry.Request = l.body(entry, "request", request)
+	entry.Response = l.body(entry, "response", response)
+
+	data, err := json.Marshal(entry)
+	if err != nil {
+		logger.Warn("序列化审计记录失败: %v", err)
+		return
+	}
+
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if err := l.appendLine(entry.Timestamp, data); err != nil {
+		logger.Warn("写入审计记录失败: %s: %v", entry.RequestID, err)
+	}
+}
+
+// RecordEvent 写入一条网关事件记录，不受 key_ids 限制，审计未启用时忽略
+func (l *Log) RecordEvent(entry *Entry) {
+	if l == nil || !l.config.Enabled {
+		return
+	}
+	data, err := json.Marshal(entry)
+	if err != nil {
+		logger.Warn("序列化审计事件失败: %v", err)
+		return
+	}
+
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if err := l.appendLine(entry.Timestamp, data); err != nil {
+		logger.Warn("写入审计事件失败: %s: %v", entry.Event, err)
+	}
+}
+
+// body 根据保存方式生成审计内容，超过 max_body_bytes 的内容在配置了对象存储时完整上传
+func (l *Log) body(entry *Entry, kind string, capture *Capture) *Body {
+	if capture == nil {
+		return nil
+	}
+	body := &Body{
+		Size:   capture.size,
+		SHA256: hex.EncodeToString(capture.hash.Sum(nil)),
+	}
+	if l.config.BodyMode == BodyModeHash || capture.size == 0 {
+		return body
+	}
+
+	captured := capture.buffer.Bytes()
+	inline := captured
+	if len(inline) > l.config.MaxBodyBytes {
+		inline = inline[:l.config.MaxBodyBytes]
+	}
+	body.Content = l.redactor.Redact(inline)
+	body.Truncated = capture.size > len(inline)
+
+	if l.tier != nil && len(captured) > len(inline) {
+		key, err := l.tier.upload(entry, kind, l.redactor.Redact(captured))
+		if err != nil {
+			logger.Warn("上传审计内容到对象存储失败，只保留本地预览: %s: %v", entry.RequestID, err)
+		} else {
+			body.ObjectKey = key
+		}
+	}
+
+	// 开启去重时较长的内容按块保存，重复的系统提示词和对话前缀只保存一份
+	if l.chunks != nil && len(body.Content) >= l.dedupMinBytes() {
+		hashes, err := l.chunks.store(body.Content, time.Now())
+		if err != nil {
+			logger.Warn("审计内容去重保存失败，改为内联保存: %s: %v", entry.RequestID, err)
+		} else {
+			body.Chunks = hashes
+			body.Content = ""
+		}
+	}
+	return body
+}
+
+// dedupMinBytes 按块保存的最小内容长度，更短的内容直接内联保存
+func (l *Log) dedupMinBytes() int {
+	if l.config.Dedup.MinBytes > 0 {
+		return l.config.Dedup.MinBytes
+	}
+	return defaultDedupMinBytes
+}
+
+// appendLine 追加一行到记录日期对应的文件（调用方持有锁）
+func (l *Log) appendLine(timestamp time.Time, data []byte) error {
+	dir := l.dir()
+	if err := os.MkdirAll(dir, 0700); err != nil {
+		return fmt.Errorf("创建审计日志目录失败: %w", err)
+	}
+
+	path := filepath.Join(dir, timestamp.UTC().Format(dayLayout)+".jsonl")
+	file, err := os.OpenFile(path, os.O_CREATE|os.O_WRONLY|os.O_APPEND, 0600)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = file.Close() }()
+
+	_, err = file.Write(append(data, '\n'))
+	return err
+}
+
+// Query 查询审计记录，按时间从新到旧返回（最多 Limit 条，Limit<=0 时不限制）
+func (l *Log) Query(filter Filter) ([]*Entry, error) {
+	days, err := l.days()
+	if err != nil {
+		return nil, err
+	}
+
+	result := make([]*Entry, 0)
+	for i := len(days) - 1; i >= 0; i-- {
+		day := days[i]
+		// 跳过整天都不在查询范围内的文件
+		if !filter.Since.IsZero() && day.AddDate(0, 0, 1).Before(filter.Since) {
+			break
+		}
+		if !filter.Until.IsZero() && !day.Before(filter.Until) {
+			continue
+		}
+
+		entries, err := l.readDay(day)
+		if err != nil {
+			return nil, err
+		}
+		for j := len(entries) - 1; j >= 0; j-- {
+			if !filter.Match(entries[j]) {
+				continue
+			}
+			result = append(result, entries[j])
+			if filter.Limit > 0 && len(result) >= filter.Limit {
+				return l.withDownloadURLs(l.withContent(result)), nil
+			}
+		}
+	}
+	return l.withDownloadURLs(l.withContent(result)), nil
+}
+
+// withContent 把按块保存的内容拼接回 Content，调用方看到的记录与未去重时相同。
+// 去重关闭后已有的按块记录仍然可以读取
+func (l *Log) withContent(entries []*Entry) []*Entry {
+	chunks := l.chunks
+	if chunks == nil {
+		chunks = &chunkStore{dir: filepath.Join(l.dir(), "chunks")}
+	}
+	for _, entry := range entries {
+		for _, body := range []*Body{entry.Request, entry.Response} {
+			if body == nil || len(body.Chunks) == 0 {
+				continue
+			}
+			content, err := chunks.load(body.Chunks)
+			if err != nil {
+				logger.Warn("还原审计内容失败: %s: %v", entry.RequestID, err)
+				continue
+			}
+			body.Content = content
+			body.Chunks = nil
+		}
+	}
+	return entries
+}
+
+// withDownloadURLs 为保存在对象存储中的内容生成预签名下载链接
+func (l *Log) withDownloadURLs(entries []*Entry) []*Entry {
+	if l.tier == nil {
+		return entries
+	}
+	for _, entry := range entries {
+		for _, body := range []*Body{entry.Request, entry.Response} {
+			if body != nil && body.ObjectKey != "" {
+				body.URL = l.tier.presign(body.ObjectKey)
+			}
+		}
+	}
+	return entries
+}
+
+// readDay 读取某天的全部审计记录
+func (l *Log) readDay(day time.Time) ([]*Entry, error) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	file, err := os.Open(filepath.Join(l.dir(), day.Format(dayLayout)+".jsonl"))
+	if err != nil {
+		if os.IsNotExist(err) {
+			return nil, nil
+		}
+		return nil, fmt.Errorf("读取审计日志失败: %w", err)
+	}
+	defer func() { _ = file.Close() }()
+
+	var entries []*Entry
+	scanner := bufio.NewScanner(file)
+	scanner.Buffer(make([]byte, 64*1024), 64*1024*1024)
+	for scanner.Scan() {
+		var entry Entry
+		if err := json.Unmarshal(scanner.Bytes(), &entry); err != nil {
+			continue // 跳过写入中断产生的不完整行
+		}
+		entries = append(entries, &entry)
+	}
+	return entries, scanner.Err()
+}
+
+// days 列出已有审计文件的日期（从旧到新）
+func (l *Log) days() ([]time.Time, error) {
+	files, err := os.ReadDir(l.dir())
+	if err != nil {
+		if os.IsNotExist(err) {
+			return nil, nil
+		}
+		return nil, fmt.Errorf("读取审计日志目录失败: %w", err)
+	}
+
+	var days []time.Time
+	for _, file := range files {
+		name := file.Name()
+		if file.IsDir() || !strings.HasSuffix(name, ".jsonl") {
+			continue
+		}
+		day, err := time.Parse(dayLayout, strings.TrimSuffix(name, ".jsonl"))
+		if err != nil {
+			continue
+		}
+		days = append(days, day)
+	}
+	sort.Slice(days, func(i, j int) bool { return days[i].Before(days[j]) })
+	return days, nil
+}
+
+// Purge 删除超过保留期的审计文件，返回删除的文件数
+func (l *Log) Purge(now time.Time) int {
+	if l.config.RetentionDays <= 0 {
+		return 0
+	}
+
+	days, err := l.days()
+	if err != nil {
+		logger.Warn("清理审计日志失败: %v", err)
+		return 0
+	}
+
+	cutoff := now.UTC().AddDate(0, 0, -l.config.RetentionDays)
+	removed := 0
+	for _, day := range days {
+		// 文件中最新的记录也已超过保留期才删除
+		if !day.AddDate(0, 0, 1).Before(cutoff) {
+			continue
+		}
+
+		l.mutex.Lock()
+		err := os.Remove(filepath.Join(l.dir(), day.Format(dayLayout)+".jsonl"))
+		l.mutex.Unlock()
+		if err != nil {
+			logger.Warn("删除过期审计日志失败: %v", err)
+			continue
+		}
+		removed++
+	}
+
+	l.sweepChunks(now)
+	return removed
+}
+
+// sweepChunks 删除已经没有审计记录引用的内容块（包括去重关闭前写入的块）
+func (l *Log) sweepChunks(now time.Time) {
+	store := &chunkStore{dir: filepath.Join(l.dir(), "chunks")}
+	if _, err := os.Stat(store.dir); err != nil {
+		return
+	}
+
+	days, err := l.days()
+	if err != nil {
+		logger.Warn("清理审计内容块失败: %v", err)
+		return
+	}
+	referenced := make(map[string]bool)
+	for _, day := range days {
+		entries, err := l.readDay(day)
+		if err != nil {
+			logger.Warn("清理审计内容块失败: %v", err)
+			return
+		}
+		for _, entry := range entries {
+			for _, body := range []*Body{entry.Request, entry.Response} {
+				if body == nil {
+					continue
+				}
+				for _, hash := range body.Chunks {
+					referenced[hash] = true
+				}
+			}
+		}
+	}
+
+	if removed := store.sweep(referenced, now); removed > 0 {
+		logger.Info("清理了%d个不再被引用的审计内容块", removed)
+	}
+}
+
+// dir 审计日志目录，未配置时使用 ~/.llm-gateway/audit
+func (l *Log) dir() string {
+	if l.config.Dir != "" {
+		return l.config.Dir
+	}
+	homeDir, err := os.UserHomeDir()
+	if err != nil {
+		return filepath.Join(".llm-gateway", "audit")
+	}
+	return filepath.Join(homeDir, ".llm-gateway", "audit")
+}
+
+// Start 启动过期审计日志的定期清理（启动时立即清理一次），配置了对象存储时同时写入生命周期规则
+func (l *Log) Start() {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if l.stopCh != nil {
+		return
+	}
+	l.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(time.Hour)
+		defer ticker.Stop()
+
+		if l.tier != nil {
+			l.tier.applyLifecycle()
+		}
+		l.Purge(time.Now())
+		for {
+			select {
+			case <-ticker.C:
+				l.Purge(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(l.stopCh)
+}
+
+// Stop 停止定期清理
+func (l *Log) Stop() {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if l.stopCh != nil {
+		close(l.stopCh)
+		l.stopCh = nil
+	}
+}
@@ -0,0 +1,281 @@
+package audit
+
+import (
+	"fmt"
+	"math/rand"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestRedactor(t *testing.T) {
+	redactor := NewRedactor([]string{"user_id"}, false)
+
+	body := `{"model":"gpt-4o","api_key":"abc","metadata":{"user_id":"u-42"},` +
+		`"messages":[{"role":"user","content":"mail me at alice@example.com, key sk-abcdefghijklmnopqrstu"}]}`
+	redacted := redactor.Redact([]byte(body))
+	for _, leaked := range []string{"abc\"", "u-42", "alice@example.com", "sk-abcdefghijklmnopqrstu"} {
+		if strings.Contains(redacted, leaked) {
+			t.Errorf("redacted body still contains %q: %s", leaked, redacted)
+		}
+	}
+	for _, expected := range []string{`"api_key":"[REDACTED]"`, "[EMAIL]", "[API_KEY]", `"model":"gpt-4o"`} {
+		if !strings.Contains(redacted, expected) {
+			t.Errorf("redacted body should contain %q: %s", expected, redacted)
+		}
+	}
+
+	// 截断的JSON或SSE流按文本脱敏
+	truncated := redactor.Redact([]byte(`{"refresh_token": "rt-secret", "content": "call +1 415 555 0100`))
+	if strings.Contains(truncated, "rt-secret") || strings.Contains(truncated, "555") {
+		t.Errorf("truncated body not redacted: %s", truncated)
+	}
+
+	// keep_pii 只保留个人信息，凭证仍然脱敏
+	kept := NewRedactor(nil, true).Redact([]byte(`"alice@example.com Bearer abcdefghijklmnopqrstuvwxyz"`))
+	if !strings.Contains(kept, "alice@example.com") || strings.Contains(kept, "abcdefghijklmnopqrstuvwxyz") {
+		t.Errorf("keep_pii result = %s", kept)
+	}
+}
+
+func TestLog_RecordAndQuery(t *testing.T) {
+	config := &types.AuditConfig{
+		Enabled:       true,
+		Dir:           t.TempDir(),
+		BodyMode:      BodyModeFull,
+		MaxBodyBytes:  16,
+		RetentionDays: 7,
+		KeyIDs:        []string{"key-a", "key-b"},
+	}
+	log := NewLog(config)
+
+	if !log.ShouldAudit("key-a") || log.ShouldAudit("key-c") {
+		t.Fatal("ShouldAudit should follow key_ids")
+	}
+
+	now := time.Now()
+	for i, keyID := range []string{"key-a", "key-b", "key-a"} {
+		request := log.NewCapture()
+		_, _ = request.Write([]byte(`{"password":"hunter2"}`))
+		response := log.NewCapture()
+		_, _ = response.Write([]byte(`{"ok":true}`))
+
+		log.Record(&Entry{
+			RequestID:    "req-" + string(rune('1'+i)),
+			Timestamp:    now.Add(time.Duration(i) * time.Second),
+			GatewayKeyID: keyID,
+			StatusCode:   200,
+		}, request, response)
+	}
+
+	entries, err := log.Query(Filter{GatewayKeyID: "key-a"})
+	if err != nil {
+		t.Fatalf("Query() error = %v", err)
+	}
+	if len(entries) != 2 || entries[0].RequestID != "req-3" || entries[1].RequestID != "req-1" {
+		t.Fatalf("Query() should return key-a entries newest first, got %+v", entries)
+	}
+
+	// 请求体超过 max_body_bytes 被截断，哈希基于完整内容
+	request := entries[0].Request
+	if !request.Truncated || request.Size != len(`{"password":"hunter2"}`) || len(request.SHA256) != 64 {
+		t.Errorf("request body = %+v", request)
+	}
+	if strings.Contains(request.Content, "hun") {
+		t.Errorf("password should be redacted even when truncated: %s", request.Content)
+	}
+	if entries[0].Response.Content != `{"ok":true}` || entries[0].Response.Truncated {
+		t.Errorf("response body = %+v", entries[0].Response)
+	}
+
+	limited, _ := log.Query(Filter{Limit: 1})
+	if len(limited) != 1 || limited[0].RequestID != "req-3" {
+		t.Errorf("Query(limit=1) = %+v", limited)
+	}
+
+	// 网关事件不受 key_ids 限制
+	log.RecordEvent(&Entry{Timestamp: now.Add(time.Minute), UpstreamID: "acc-1", Event: EventAccountAutoDisabled, Message: "revoked"})
+	latest, _ := log.Query(Filter{Limit: 1})
+	if len(latest) != 1 || latest[0].Event != EventAccountAutoDisabled || latest[0].UpstreamID != "acc-1" || latest[0].Request != nil {
+		t.Errorf("Query() after RecordEvent = %+v", latest)
+	}
+
+	// hash 模式只保存哈希和长度
+	config.BodyMode = BodyModeHash
+	capture := log.NewCapture()
+	_, _ = capture.Write([]byte("secret payload"))
+	if body := log.body(&Entry{}, "request", capture); body.Content != "" || body.Size != 14 || body.SHA256 == "" {
+		t.Errorf("hash mode body = %+v", body)
+	}
+}
+
+func TestLog_Purge(t *testing.T) {
+	dir := t.TempDir()
+	log := NewLog(&types.AuditConfig{Dir: dir, RetentionDays: 7})
+
+	now := time.Date(2024, 6, 20, 12, 0, 0, 0, time.UTC)
+	for _, day := range []string{"2024-06-01", "2024-06-12", "2024-06-13", "2024-06-20"} {
+		if err := os.WriteFile(filepath.Join(dir, day+".jsonl"), []byte("{}\n"), 0600); err != nil {
+			t.Fatal(err)
+		}
+	}
+
+	if removed := log.Purge(now); removed != 2 {
+		t.Errorf("Purge() removed %d files, want 2", removed)
+	}
+	if _, err := os.Stat(filepath.Join(dir, "2024-06-13.jsonl")); err != nil {
+		t.Errorf("file within retention should be kept: %v", err)
+	}
+}
+
+// promptText 生成不含凭证和个人信息的随机文本
+func promptText(seed int64, words int) string {
+	vocabulary := []string{"gateway", "model", "request", "stream", "token", "route", "upstream", "account", "cache", "retry", "answer", "question"}
+	rng := rand.New(rand.NewSource(seed))
+	parts := make([]string, words)
+	for i := range parts {
+		parts[i] = vocabulary[rng.Intn(len(vocabulary))]
+	}
+	return strings.Join(parts, " ")
+}
+
+// countChunks 统计块存储中的块文件数
+func countChunks(t *testing.T, dir string) int {
+	t.Helper()
+	count := 0
+	_ = filepath.Walk(filepath.Join(dir, "chunks"), func(path string, info os.FileInfo, err error) error {
+		if err == nil && !info.IsDir() {
+			count++
+		}
+		return nil
+	})
+	return count
+}
+
+func TestLog_Dedup(t *testing.T) {
+	dir := t.TempDir()
+	log := NewLog(&types.AuditConfig{
+		Dir:           dir,
+		BodyMode:      BodyModeFull,
+		MaxBodyBytes:  64 * 1024,
+		RetentionDays: 7,
+		Dedup:         types.AuditDedupConfig{Enabled: true},
+	})
+
+	// 两个请求共用同一个很长的系统提示词，用户消息不同
+	system := promptText(1, 2000)
+	old := time.Date(2024, 6, 1, 12, 0, 0, 0, time.UTC)
+	now := time.Now()
+	want := make(map[string]string)
+	referenced := 0
+	for i, timestamp := range []time.Time{old, now} {
+		requestID := fmt.Sprintf("req-%d", i+1)
+		body := fmt.Sprintf(`{"system":%q,"messages":[{"role":"user","content":%q}]}`, system, promptText(int64(i+2), 300))
+		want[requestID] = log.redactor.Redact([]byte(body))
+		referenced += len(splitChunks([]byte(want[requestID])))
+
+		request := log.NewCapture()
+		_, _ = request.Write([]byte(body))
+		response := log.NewCapture()
+		_, _ = response.Write([]byte(`{"ok":true}`))
+		log.Record(&Entry{RequestID: requestID, Timestamp: timestamp}, request, response)
+	}
+
+	// 共同的内容只保存一份
+	stored := countChunks(t, dir)
+	if stored == 0 || stored >= referenced {
+		t.Fatalf("stored %d chunks for %d referenced, want shared chunks stored once", stored, referenced)
+	}
+
+	// 查询时透明地拼接回原始内容，短内容仍然内联保存
+	entries, err := log.Query(Filter{})
+	if err != nil || len(entries) != 2 {
+		t.Fatalf("Query() = %v, %v", entries, err)
+	}
+	for _, entry := range entries {
+		if entry.Request.Content != want[entry.RequestID] || entry.Request.Chunks != nil {
+			t.Errorf("%s request content was not reassembled", entry.RequestID)
+		}
+		if entry.Response.Content != `{"ok":true}` {
+			t.Errorf("%s response = %+v", entry.RequestID, entry.Response)
+		}
+	}
+
+	// 过期记录删除后只清理不再被引用的块，仍在保留期内的记录可以完整读取
+	if removed := log.Purge(now.Add(2 * chunkGracePeriod)); removed != 1 {
+		t.Fatalf("Purge() removed %d files, want 1", removed)
+	}
+	if remaining := countChunks(t, dir); remaining >= stored || remaining != len(splitChunks([]byte(want["req-2"]))) {
+		t.Errorf("%d chunks left after purge, want only the chunks of req-2", remaining)
+	}
+	entries, _ = log.Query(Filter{})
+	if len(entries) != 1 || entries[0].Request.Content != want["req-2"] {
+		t.Errorf("Query() after purge = %+v", entries)
+	}
+}
+
+// stubObjectStore 记录上传内容的对象存储
+type stubObjectStore struct {
+	objects map[string]string
+	rules   map[string]int
+}
+
+func (s *stubObjectStore) PutObject(key string, data []byte, contentType string) error {
+	s.objects[key] = string(data)
+	return nil
+}
+
+func (s *stubObjectStore) PresignGet(key string, expires time.Duration) string {
+	return "https://objects.example.com/" + key + "?expires=" + expires.String()
+}
+
+func (s *stubObjectStore) PutExpirationRule(ruleID, prefix string, days int) error {
+	s.rules[prefix] = days
+	return nil
+}
+
+func TestLog_ObjectTier(t *testing.T) {
+	storeConfig := &types.AuditObjectStoreConfig{Prefix: "audit/", ExpirationDays: 90, MaxObjectBytes: 1024, PresignSeconds: 60}
+	log := NewLog(&types.AuditConfig{Dir: t.TempDir(), BodyMode: BodyModeFull, MaxBodyBytes: 16, ObjectStore: storeConfig})
+	store := &stubObjectStore{objects: map[string]string{}, rules: map[string]int{}}
+	log.tier = &objectTier{store: store, config: storeConfig, uploads: make(chan struct{}, 1)}
+
+	request := log.NewCapture()
+	_, _ = request.Write([]byte(`{"password":"hunter2","messages":[{"role":"user","content":"a long prompt"}]}`))
+	response := log.NewCapture()
+	_, _ = response.Write([]byte(`{"ok":true}`))
+	log.Record(&Entry{
+		RequestID: "req-1",
+		Timestamp: time.Date(2024, 6, 20, 12, 0, 0, 0, time.UTC),
+	}, request, response)
+
+	// 超过 max_body_bytes 的内容完整上传（已脱敏），本地只保留预览；小内容不上传
+	uploaded, ok := store.objects["audit/2024-06-20/req-1/request"]
+	if !ok || len(store.objects) != 1 {
+		t.Fatalf("uploaded objects = %v", store.objects)
+	}
+	if strings.Contains(uploaded, "hunter2") || !strings.Contains(uploaded, "a long prompt") {
+		t.Errorf("uploaded object = %s", uploaded)
+	}
+
+	entries, err := log.Query(Filter{})
+	if err != nil || len(entries) != 1 {
+		t.Fatalf("Query() = %v, %v", entries, err)
+	}
+	body := entries[0].Request
+	if !body.Truncated || body.ObjectKey != "audit/2024-06-20/req-1/request" || !strings.Contains(body.URL, "expires=1m0s") {
+		t.Errorf("request body = %+v", body)
+	}
+	if entries[0].Response.ObjectKey != "" || entries[0].Response.URL != "" {
+		t.Errorf("response body should stay inline: %+v", entries[0].Response)
+	}
+
+	log.tier.applyLifecycle()
+	if store.rules["audit/"] != 90 {
+		t.Errorf("lifecycle rules = %v", store.rules)
+	}
+}
@@ -0,0 +1,183 @@
+package audit
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+)
+
+// 内容去重的分块参数：按内容定义分块（gear 滚动哈希），块边界只取决于附近的内容，
+// 重复的系统提示词和共同的对话前缀在不同请求中切出相同的块
+const (
+	defaultDedupMinBytes = 1024                // 未配置 min_bytes 时直接内联保存的最大长度
+	minChunkBytes        = 512                 // 块的最小长度
+	maxChunkBytes        = 16 * 1024           // 块的最大长度
+	chunkMask            = uint64(0x7FF) << 53 // 哈希最高11位为0时切分，平均约2KB一个块
+
+	// chunkGracePeriod 清理时保留最近写入或复用的块，避免删除尚未写入审计记录的块
+	chunkGracePeriod = time.Hour
+)
+
+// gearTable 每个字节值对应的随机数，固定生成以保证块边界在重启后不变
+var gearTable = newGearTable()
+
+// newGearTable 用 splitmix64 生成 gear 哈希表
+func newGearTable() [256]uint64 {
+	var table [256]uint64
+	seed := uint64(0)
+	for i := range table {
+		seed += 0x9E3779B97F4A7C15
+		z := seed
+		z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9
+		z = (z ^ (z >> 27)) * 0x94D049BB133111EB
+		table[i] = z ^ (z >> 31)
+	}
+	return table
+}
+
+// splitChunks 把内容切分为长度在 minChunkBytes 到 maxChunkBytes 之间的块（最后一块可能更短）
+func splitChunks(data []byte) [][]byte {
+	var chunks [][]byte
+	for len(data) > 0 {
+		n := chunkLength(data)
+		chunks = append(chunks, data[:n])
+		data = data[n:]
+	}
+	return chunks
+}
+
+// chunkLength 返回从 data 开头切出的块的长度
+func chunkLength(data []byte) int {
+	if len(data) <= minChunkBytes {
+		return len(data)
+	}
+	limit := len(data)
+	if limit > maxChunkBytes {
+		limit = maxChunkBytes
+	}
+
+	var hash uint64
+	for i := 0; i < limit; i++ {
+		hash = hash<<1 + gearTable[data[i]]
+		if i >= minChunkBytes && hash&chunkMask == 0 {
+			return i + 1
+		}
+	}
+	return limit
+}
+
+// chunkStore 内容寻址的块存储：<dir>/<SHA-256前2位>/<SHA-256>，相同内容只保存一份
+type chunkStore struct {
+	dir string
+}
+
+// path 块文件的路径
+func (s *chunkStore) path(hash string) string {
+	return filepath.Join(s.dir, hash[:2], hash)
+}
+
+// store 切分并保存内容，返回按顺序排列的块哈希
+func (s *chunkStore) store(content string, now time.Time) ([]string, error) {
+	chunks := splitChunks([]byte(content))
+	hashes := make([]string, 0, len(chunks))
+	for _, chunk := range chunks {
+		hash, err := s.put(chunk, now)
+		if err != nil {
+			return nil, err
+		}
+		hashes = append(hashes, hash)
+	}
+	return hashes, nil
+}
+
+// put 保存一个块，已存在时只更新修改时间（清理任务据此保留即将被新记录引用的块）
+func (s *chunkStore) put(chunk []byte, now time.Time) (string, error) {
+	sum := sha256.Sum256(chunk)
+	hash := hex.EncodeToString(sum[:])
+	path := s.path(hash)
+	if _, err := os.Stat(path); err == nil {
+		return hash, os.Chtimes(path, now, now)
+	}
+
+	dir := filepath.Dir(path)
+	if err := os.MkdirAll(dir, 0700); err != nil {
+		return "", fmt.Errorf("创建审计内容块目录失败: %w", err)
+	}
+	// 先写临时文件再重命名，并发写入同一个块时读取方不会看到不完整的内容
+	tmp, err := os.CreateTemp(dir, hash+".tmp*")
+	if err != nil {
+		return "", err
+	}
+	if _, err := tmp.Write(chunk); err != nil {
+		_ = tmp.Close()
+		_ = os.Remove(tmp.Name())
+		return "", err
+	}
+	if err := tmp.Close(); err != nil {
+		_ = os.Remove(tmp.Name())
+		return "", err
+	}
+	if err := os.Rename(tmp.Name(), path); err != nil {
+		_ = os.Remove(tmp.Name())
+		return "", err
+	}
+	return hash, nil
+}
+
+// load 按顺序读取块并拼接为原始内容
+func (s *chunkStore) load(hashes []string) (string, error) {
+	var builder strings.Builder
+	for _, hash := range hashes {
+		if len(hash) != sha256.Size*2 {
+			return "", fmt.Errorf("无效的审计内容块: %s", hash)
+		}
+		data, err := os.ReadFile(s.path(hash))
+		if err != nil {
+			return "", fmt.Errorf("读取审计内容块失败: %w", err)
+		}
+		builder.Write(data)
+	}
+	return builder.String(), nil
+}
+
+// sweep 删除没有被任何审计记录引用、且超过保留宽限期未被使用的块，返回删除的块数
+func (s *chunkStore) sweep(referenced map[string]bool, now time.Time) int {
+	prefixes, err := os.ReadDir(s.dir)
+	if err != nil {
+		if !os.IsNotExist(err) {
+			logger.Warn("读取审计内容块目录失败: %v", err)
+		}
+		return 0
+	}
+
+	cutoff := now.Add(-chunkGracePeriod)
+	removed := 0
+	for _, prefix := range prefixes {
+		if !prefix.IsDir() {
+			continue
+		}
+		files, err := os.ReadDir(filepath.Join(s.dir, prefix.Name()))
+		if err != nil {
+			continue
+		}
+		for _, file := range files {
+			if referenced[file.Name()] {
+				continue
+			}
+			info, err := file.Info()
+			if err != nil || !info.ModTime().Before(cutoff) {
+				continue
+			}
+			if err := os.Remove(filepath.Join(s.dir, prefix.Name(), file.Name())); err == nil {
+				removed++
+			}
+		}
+	}
+	return removed
+}
@@ -0,0 +1,119 @@
+package audit
+
+import (
+	"encoding/json"
+	"regexp"
+	"strings"
+)
+
+// redactedValue 被脱敏字段的替换值
+const redactedValue = "[REDACTED]"
+
+// defaultRedactFields 始终脱敏的JSON字段名（不区分大小写）
+var defaultRedactFields = []string{
+	"api_key", "apikey", "x-api-key", "authorization", "password", "secret",
+	"client_secret", "access_token", "refresh_token", "id_token", "token",
+}
+
+// credentialPatterns 文本中的凭证（始终替换）
+var credentialPatterns = []struct {
+	pattern     *regexp.Regexp
+	replacement string
+}{
+	{regexp.MustCompile(`\b(?:sk|pk|rk)-[A-Za-z0-9_\-]{16,}`), "[API_KEY]"},
+	{regexp.MustCompile(`(?i)\bBearer\s+[A-Za-z0-9._\-]{16,}`), "Bearer [REDACTED]"},
+}
+
+// piiPatterns 文本中的个人信息（keep_pii 为 false 时替换）
+var piiPatterns = []struct {
+	pattern     *regexp.Regexp
+	replacement string
+}{
+	{regexp.MustCompile(`[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}`), "[EMAIL]"},
+	{regexp.MustCompile(`\b\d{4}[ \-]?\d{4}[ \-]?\d{4}[ \-]?\d{1,4}\b`), "[CARD]"},
+	{regexp.MustCompile(`\+\d{1,3}[ \-]?\d{2,4}[ \-]?\d{3,4}[ \-]?\d{3,4}\b`), "[PHONE]"},
+	{regexp.MustCompile(`\b1[3-9]\d{9}\b`), "[PHONE]"},
+}
+
+// Redactor 对请求体/响应体脱敏
+type Redactor struct {
+	fields    map[string]bool
+	fieldText *regexp.Regexp // JSON无法解析（截断或SSE）时按文本匹配字段，值被截断时一直匹配到末尾
+	keepPII   bool
+}
+
+// NewRedactor 创建脱敏器，extraFields 为内置字段之外需要脱敏的字段名
+func NewRedactor(extraFields []string, keepPII bool) *Redactor {
+	fields := make(map[string]bool)
+	var names []string
+	for _, field := range append(append([]string{}, defaultRedactFields...), extraFields...) {
+		field = strings.ToLower(strings.TrimSpace(field))
+		if field == "" || fields[field] {
+			continue
+		}
+		fields[field] = true
+		names = append(names, regexp.QuoteMeta(field))
+	}
+
+	return &Redactor{
+		fields:    fields,
+		fieldText: regexp.MustCompile(`(?i)"(` + strings.Join(names, "|") + `)"\s*:\s*"(?:[^"\\]|\\.)*(?:"|$)`),
+		keepPII:   keepPII,
+	}
+}
+
+// Redact 脱敏内容：合法JSON按字段结构处理，其他内容（截断的JSON、SSE流）按文本处理
+func (r *Redactor) Redact(content []byte) string {
+	var value interface{}
+	if err := json.Unmarshal(content, &value); err == nil {
+		if data, err := json.Marshal(r.redactValue(value)); err == nil {
+			return string(data)
+		}
+	}
+
+	text := r.fieldText.ReplaceAllString(string(content), `"$1":"`+redactedValue+`"`)
+	return r.redactText(text)
+}
+
+// redactValue 递归脱敏JSON值
+func (r *Redactor) redactValue(value interface{}) interface{} {
+	switch v := value.(type) {
+	case map[string]interface{}:
+		for key, item := range v {
+			if r.fields[strings.ToLower(key)] {
+				v[key] = redactedValue
+			} else {
+				v[key] = r.redactValue(item)
+			}
+		}
+		return v
+	case []interface{}:
+		for i, item := range v {
+			v[i] = r.redactValue(item)
+		}
+		return v
+	case string:
+		return r.redactText(v)
+	default:
+		return v
+	}
+}
+
+// redactText 替换文本中的凭证和个人信息
+func (r *Redactor) redactText(text string) string {
+	for _, p := range credentialPatterns {
+		text = p.pattern.ReplaceAllString(text, p.replacement)
+	}
+	if !r.keepPII {
+		text = MaskPII(text)
+	}
+	return text
+}
+
+// MaskPII 替换文本中的个人信息（邮箱、银行卡号、电话号码）
+func MaskPII(text string) string {
+	for _, p := range piiPatterns {
+		text = p.pattern.ReplaceAllString(text, p.replacement)
+	}
+	return text
+}
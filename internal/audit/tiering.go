@@ -0,0 +1,88 @@
+package audit
+
+import (
+	"encoding/json"
+	"fmt"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/objectstore"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 对象存储分层参数
+const (
+	lifecycleRuleID     = "llm-gateway-audit"
+	maxConcurrentUpload = 4
+	uploadAttempts      = 3
+)
+
+// objectStore 审计内容使用的对象存储操作（由 objectstore.Client 实现）
+type objectStore interface {
+	PutObject(key string, data []byte, contentType string) error
+	PresignGet(key string, expires time.Duration) string
+	PutExpirationRule(ruleID, prefix string, days int) error
+}
+
+// objectTier 将超过本地保存上限的完整内容上传到对象存储
+type objectTier struct {
+	store   objectStore
+	config  *types.AuditObjectStoreConfig
+	uploads chan struct{} // 限制同时进行的上传数
+}
+
+// newObjectTier 根据配置创建对象存储分层，未配置或配置无效时返回nil
+func newObjectTier(config *types.AuditObjectStoreConfig) *objectTier {
+	if config == nil || config.Endpoint == "" {
+		return nil
+	}
+	client, err := objectstore.NewClient(&config.ObjectStoreConfig)
+	if err != nil {
+		logger.Warn("审计日志对象存储配置无效，内容只保存在本地: %v", err)
+		return nil
+	}
+	return &objectTier{store: client, config: config, uploads: make(chan struct{}, maxConcurrentUpload)}
+}
+
+// objectKey 对象键：<prefix><日期>/<请求ID>/<request|response>
+func (t *objectTier) objectKey(entry *Entry, kind string) string {
+	return fmt.Sprintf("%s%s/%s/%s", t.config.Prefix, entry.Timestamp.UTC().Format(dayLayout), entry.RequestID, kind)
+}
+
+// upload 上传内容（失败时重试），返回对象键
+func (t *objectTier) upload(entry *Entry, kind, content string) (string, error) {
+	t.uploads <- struct{}{}
+	defer func() { <-t.uploads }()
+
+	contentType := "text/plain; charset=utf-8"
+	if json.Valid([]byte(content)) {
+		contentType = "application/json"
+	}
+
+	key := t.objectKey(entry, kind)
+	var err error
+	for attempt := 1; attempt <= uploadAttempts; attempt++ {
+		if err = t.store.PutObject(key, []byte(content), contentType); err == nil {
+			return key, nil
+		}
+		if attempt < uploadAttempts {
+			time.Sleep(time.Duration(attempt) * time.Second)
+		}
+	}
+	return "", err
+}
+
+// presign 生成下载链接
+func (t *objectTier) presign(key string) string {
+	return t.store.PresignGet(key, time.Duration(t.config.PresignSeconds)*time.Second)
+}
+
+// applyLifecycle 为审计前缀写入过期规则
+func (t *objectTier) applyLifecycle() {
+	if t.config.ExpirationDays <= 0 {
+		return
+	}
+	if err := t.store.PutExpirationRule(lifecycleRuleID, t.config.Prefix, t.config.ExpirationDays); err != nil {
+		logger.Warn("设置审计日志对象存储生命周期规则失败: %v", err)
+	}
+}
@@ -0,0 +1,209 @@
+package backup
+
+import (
+	"crypto/aes"
+	"crypto/cipher"
+	"crypto/rand"
+	"encoding/base64"
+	"fmt"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+	yaml "gopkg.in/yaml.v2"
+)
+
+// 备份格式参数
+const (
+	snapshotVersion  = 2 // 版本1只加密上游账号，其他数据可以被篡改而不被发现
+	kdfName          = "pbkdf2-sha256"
+	kdfIterations    = 210000
+	maxKDFIterations = 10 * kdfIterations // 备份中的迭代次数超过它时拒绝解密，避免被篡改的备份耗尽CPU
+	saltSize         = 16
+	keySize          = 32 // AES-256
+)
+
+// Snapshot 备份中的网关数据（上游账号由 Open 单独返回）
+type Snapshot struct {
+	CreatedAt     time.Time                                 `yaml:"created_at"`
+	GatewayKeys   []types.GatewayAPIKey                     `yaml:"gateway_keys"`
+	ModelRoutes   types.ModelRouteConfig                    `yaml:"model_routes"`
+	RoutingRules  []types.RoutingRule                       `yaml:"routing_rules,omitempty"`
+	Providers     map[types.Provider]types.ProviderSettings `yaml:"providers,omitempty"`
+	Announcements []types.Announcement                      `yaml:"announcements,omitempty"`
+}
+
+// payload 加密保存的备份内容：整份数据由 AES-GCM 认证，任何一部分被修改都无法解密
+type payload struct {
+	Snapshot         `yaml:",inline"`
+	UpstreamAccounts []types.UpstreamAccount `yaml:"upstream_accounts"`
+}
+
+// envelope 备份文件：只有版本号是明文
+type envelope struct {
+	Version int    `yaml:"version"`
+	Payload Sealed `yaml:"payload"`
+}
+
+// Sealed 口令加密的数据（PBKDF2-SHA256 派生密钥，AES-256-GCM 加密）
+type Sealed struct {
+	KDF        string `yaml:"kdf"`
+	Iterations int    `yaml:"iterations"`
+	Salt       string `yaml:"salt"`  // base64
+	Nonce      string `yaml:"nonce"` // base64
+	Ciphertext string `yaml:"ciphertext"`
+}
+
+// Create 从当前配置生成备份文件
+func Create(cfg *types.Config, passphrase string, now time.Time) ([]byte, error) {
+	if passphrase == "" {
+		return nil, fmt.Errorf("备份口令不能为空")
+	}
+
+	plaintext, err := yaml.Marshal(&payload{
+		Snapshot: Snapshot{
+			CreatedAt:     now.UTC(),
+			GatewayKeys:   cfg.GatewayKeys,
+			ModelRoutes:   cfg.ModelRoutes,
+			RoutingRules:  cfg.RoutingRules,
+			Providers:     cfg.Providers,
+			Announcements: cfg.Announcements,
+		},
+		UpstreamAccounts: cfg.UpstreamAccounts,
+	})
+	if err != nil {
+		return nil, fmt.Errorf("序列化备份失败: %w", err)
+	}
+	sealed, err := seal(plaintext, passphrase)
+	if err != nil {
+		return nil, err
+	}
+
+	data, err := yaml.Marshal(&envelope{Version: snapshotVersion, Payload: *sealed})
+	if err != nil {
+		return nil, fmt.Errorf("序列化备份失败: %w", err)
+	}
+	return data, nil
+}
+
+// Open 解析并解密备份文件
+func Open(data []byte, passphrase string) (*Snapshot, []types.UpstreamAccount, error) {
+	var file envelope
+	if err := yaml.Unmarshal(data, &file); err != nil {
+		return nil, nil, fmt.Errorf("解析备份失败: %w", err)
+	}
+	if file.Version != snapshotVersion {
+		return nil, nil, fmt.Errorf("不支持的备份版本: %d", file.Version)
+	}
+
+	plaintext, err := open(&file.Payload, passphrase)
+	if err != nil {
+		return nil, nil, err
+	}
+	var content payload
+	if err := yaml.Unmarshal(plaintext, &content); err != nil {
+		return nil, nil, fmt.Errorf("解析备份内容失败: %w", err)
+	}
+	return &content.Snapshot, content.UpstreamAccounts, nil
+}
+
+// Restore 用备份内容替换配置中的网关数据（服务器、日志等运行设置保持不变）
+// 配置文件由 ConfigManager 直接写入，恢复前应停止正在运行的服务器
+func Restore(configMgr *config.ConfigManager, snapshot *Snapshot, accounts []types.UpstreamAccount) error {
+	current := configMgr.Get()
+	if current == nil {
+		return fmt.Errorf("配置未加载")
+	}
+
+	restored := *current
+	restored.GatewayKeys = snapshot.GatewayKeys
+	restored.UpstreamAccounts = accounts
+	restored.ModelRoutes = snapshot.ModelRoutes
+	restored.RoutingRules = snapshot.RoutingRules
+	restored.Providers = snapshot.Providers
+	restored.Announcements = snapshot.Announcements
+	if restored.GatewayKeys == nil {
+		restored.GatewayKeys = []types.GatewayAPIKey{}
+	}
+	if restored.UpstreamAccounts == nil {
+		restored.UpstreamAccounts = []types.UpstreamAccount{}
+	}
+
+	if err := configMgr.Save(&restored); err != nil {
+		return fmt.Errorf("写入恢复的配置失败: %w", err)
+	}
+	return nil
+}
+
+// seal 用口令加密数据
+func seal(plaintext []byte, passphrase string) (*Sealed, error) {
+	salt := make([]byte, saltSize)
+	if _, err := rand.Read(salt); err != nil {
+		return nil, fmt.Errorf("生成随机数失败: %w", err)
+	}
+	gcm, err := newGCM(passphrase, salt, kdfIterations)
+	if err != nil {
+		return nil, err
+	}
+	nonce := make([]byte, gcm.NonceSize())
+	if _, err := rand.Read(nonce); err != nil {
+		return nil, fmt.Errorf("生成随机数失败: %w", err)
+	}
+
+	return &Sealed{
+		KDF:        kdfName,
+		Iterations: kdfIterations,
+		Salt:       base64.StdEncoding.EncodeToString(salt),
+		Nonce:      base64.StdEncoding.EncodeToString(nonce),
+		Ciphertext: base64.StdEncoding.EncodeToString(gcm.Seal(nil, nonce, plaintext, nil)),
+	}, nil
+}
+
+// open 用口令解密数据
+func open(sealed *Sealed, passphrase string) ([]byte, error) {
+	if sealed.KDF != kdfName {
+		return nil, fmt.Errorf("不支持的密钥派生方式: %s", sealed.KDF)
+	}
+	if sealed.Iterations <= 0 || sealed.Iterations > maxKDFIterations {
+		return nil, fmt.Errorf("无效的密钥派生迭代次数: %d（最多 %d）", sealed.Iterations, maxKDFIterations)
+	}
+	salt, err := base64.StdEncoding.DecodeString(sealed.Salt)
+	if err != nil {
+		return nil, fmt.Errorf("无效的备份 salt: %w", err)
+	}
+	nonce, err := base64.StdEncoding.DecodeString(sealed.Nonce)
+	if err != nil {
+		return nil, fmt.Errorf("无效的备份 nonce: %w", err)
+	}
+	ciphertext, err := base64.StdEncoding.DecodeString(sealed.Ciphertext)
+	if err != nil {
+		return nil, fmt.Errorf("无效的备份密文: %w", err)
+	}
+
+	gcm, err := newGCM(passphrase, salt, sealed.Iterations)
+	if err != nil {
+		return nil, err
+	}
+	if len(nonce) != gcm.NonceSize() {
+		return nil, fmt.Errorf("无效的备份 nonce 长度: %d", len(nonce))
+	}
+	plaintext, err := gcm.Open(nil, nonce, ciphertext, nil)
+	if err != nil {
+		return nil, fmt.Errorf("解密备份失败，口令错误或备份已损坏")
+	}
+	return plaintext, nil
+}
+
+// newGCM 由口令派生密钥并创建 AES-GCM
+func newGCM(passphrase string, salt []byte, iterations int) (cipher.AEAD, error) {
+	block, err := aes.NewCipher(utils.PBKDF2SHA256([]byte(passphrase), salt, iterations, keySize))
+	if err != nil {
+		return nil, fmt.Errorf("创建加密器失败: %w", err)
+	}
+	gcm, err := cipher.NewGCM(block)
+	if err != nil {
+		return nil, fmt.Errorf("创建加密器失败: %w", err)
+	}
+	return gcm, nil
+}
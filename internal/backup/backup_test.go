@@ -0,0 +1,196 @@
+package backup
+
+import (
+	"encoding/base64"
+	"path/filepath"
+	"sort"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/objectstore"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	yaml "gopkg.in/yaml.v2"
+)
+
+func TestCreateAndOpen(t *testing.T) {
+	cfg := &types.Config{
+		GatewayKeys: []types.GatewayAPIKey{{ID: "key-1", Name: "ci", KeyHash: "hash-1", Status: "active"}},
+		UpstreamAccounts: []types.UpstreamAccount{{
+			ID: "acc-1", Name: "main", Type: types.UpstreamTypeAPIKey, Provider: types.ProviderAnthropic,
+			Status: "active", APIKey: "sk-ant-super-secret",
+		}},
+		RoutingRules: []types.RoutingRule{{ID: "rule-1", Pattern: "claude-*", Provider: types.ProviderAnthropic, Enabled: true}},
+	}
+	now := time.Date(2024, 6, 20, 12, 0, 0, 0, time.UTC)
+
+	data, err := Create(cfg, "correct horse", now)
+	if err != nil {
+		t.Fatalf("Create() error = %v", err)
+	}
+	for _, secret := range []string{"sk-ant-super-secret", "hash-1", "claude-*"} {
+		if strings.Contains(string(data), secret) {
+			t.Errorf("backup contains %q in plaintext", secret)
+		}
+	}
+
+	snapshot, accounts, err := Open(data, "correct horse")
+	if err != nil {
+		t.Fatalf("Open() error = %v", err)
+	}
+	if !snapshot.CreatedAt.Equal(now) || len(snapshot.GatewayKeys) != 1 || len(snapshot.RoutingRules) != 1 {
+		t.Errorf("snapshot = %+v", snapshot)
+	}
+	if len(accounts) != 1 || accounts[0].APIKey != "sk-ant-super-secret" {
+		t.Errorf("accounts = %+v", accounts)
+	}
+
+	if _, _, err := Open(data, "wrong"); err == nil {
+		t.Error("Open() with wrong passphrase should fail")
+	}
+	if _, err := Create(cfg, "", now); err == nil {
+		t.Error("Create() without passphrase should fail")
+	}
+}
+
+func TestOpen_RejectsTampering(t *testing.T) {
+	cfg := &types.Config{GatewayKeys: []types.GatewayAPIKey{{ID: "key-1", KeyHash: "hash-1", Status: "active"}}}
+	data, err := Create(cfg, "correct horse", time.Now())
+	if err != nil {
+		t.Fatalf("Create() error = %v", err)
+	}
+	var file envelope
+	if err := yaml.Unmarshal(data, &file); err != nil {
+		t.Fatal(err)
+	}
+
+	tests := []struct {
+		name   string
+		modify func(file *envelope)
+	}{
+		{name: "ciphertext", modify: func(file *envelope) {
+			ciphertext, _ := base64.StdEncoding.DecodeString(file.Payload.Ciphertext)
+			ciphertext[0] ^= 1
+			file.Payload.Ciphertext = base64.StdEncoding.EncodeToString(ciphertext)
+		}},
+		{name: "old version", modify: func(file *envelope) { file.Version = 1 }},
+		{name: "huge iterations", modify: func(file *envelope) { file.Payload.Iterations = 1 << 30 }},
+		{name: "zero iterations", modify: func(file *envelope) { file.Payload.Iterations = 0 }},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			tampered := file
+			tt.modify(&tampered)
+			data, err := yaml.Marshal(&tampered)
+			if err != nil {
+				t.Fatal(err)
+			}
+			if _, _, err := Open(data, "correct horse"); err == nil {
+				t.Error("Open() accepted a tampered backup")
+			}
+		})
+	}
+}
+
+func TestRestore(t *testing.T) {
+	configMgr := config.NewConfigManager(filepath.Join(t.TempDir(), "config.yaml"))
+	if _, err := configMgr.Load(); err != nil {
+		t.Fatal(err)
+	}
+	port := configMgr.Get().Server.Port
+
+	snapshot := &Snapshot{GatewayKeys: []types.GatewayAPIKey{{ID: "key-1", KeyHash: "hash-1", Status: "active"}}}
+	accounts := []types.UpstreamAccount{{ID: "acc-1", Type: types.UpstreamTypeAPIKey, Provider: types.ProviderOpenAI, APIKey: "sk-1"}}
+	if err := Restore(configMgr, snapshot, accounts); err != nil {
+		t.Fatalf("Restore() error = %v", err)
+	}
+
+	reloaded, err := configMgr.Reload()
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(reloaded.GatewayKeys) != 1 || len(reloaded.UpstreamAccounts) != 1 || reloaded.UpstreamAccounts[0].APIKey != "sk-1" {
+		t.Errorf("restored config = %+v", reloaded)
+	}
+	if reloaded.Server.Port != port {
+		t.Errorf("server settings should be kept, port = %d", reloaded.Server.Port)
+	}
+}
+
+// memoryStore 内存对象存储
+type memoryStore struct {
+	objects map[string][]byte
+}
+
+func (m *memoryStore) PutObject(key string, data []byte, contentType string) error {
+	m.objects[key] = data
+	return nil
+}
+
+func (m *memoryStore) GetObject(key string) ([]byte, error) {
+	return m.objects[key], nil
+}
+
+func (m *memoryStore) DeleteObject(key string) error {
+	delete(m.objects, key)
+	return nil
+}
+
+func (m *memoryStore) ListObjects(prefix string) ([]objectstore.Object, error) {
+	var objects []objectstore.Object
+	for key, data := range m.objects {
+		if strings.HasPrefix(key, prefix) {
+			objects = append(objects, objectstore.Object{Key: key, Size: int64(len(data))})
+		}
+	}
+	return objects, nil
+}
+
+func TestService_RunAndPrune(t *testing.T) {
+	configMgr := config.NewConfigManager(filepath.Join(t.TempDir(), "config.yaml"))
+	if _, err := configMgr.Load(); err != nil {
+		t.Fatal(err)
+	}
+	memory := &memoryStore{objects: map[string][]byte{
+		"backups/llm-gateway-20240501T000000Z.yaml": []byte("old"),
+		"backups/llm-gateway-20240610T000000Z.yaml": []byte("recent"),
+		"backups/unrelated.txt":                     []byte("keep"),
+	}}
+	service := &Service{
+		config:     &types.BackupConfig{IntervalHours: 24, RetentionDays: 30, Prefix: "backups/"},
+		configMgr:  configMgr,
+		store:      memory,
+		passphrase: "secret",
+	}
+
+	now := time.Date(2024, 6, 20, 12, 0, 0, 0, time.UTC)
+	if !service.due(now) {
+		t.Error("backup should be due when the latest one is 10 days old")
+	}
+
+	key, err := service.Run(now)
+	if err != nil {
+		t.Fatalf("Run() error = %v", err)
+	}
+	if key != "backups/llm-gateway-20240620T120000Z.yaml" {
+		t.Errorf("Run() key = %s", key)
+	}
+
+	var keys []string
+	for key := range memory.objects {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+	want := []string{"backups/llm-gateway-20240610T000000Z.yaml", "backups/llm-gateway-20240620T120000Z.yaml", "backups/unrelated.txt"}
+	if strings.Join(keys, ",") != strings.Join(want, ",") {
+		t.Errorf("objects after prune = %v, want %v", keys, want)
+	}
+
+	if _, _, err := service.Fetch(key); err != nil {
+		t.Errorf("Fetch() error = %v", err)
+	}
+	if service.due(now.Add(time.Hour)) {
+		t.Error("backup should not be due right after a run")
+	}
+}
@@ -0,0 +1,224 @@
+package backup
+
+import (
+	"fmt"
+	"os"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/objectstore"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// PassphraseEnv 未在配置中设置口令时读取的环境变量
+const PassphraseEnv = "LLM_GATEWAY_BACKUP_PASSPHRASE"
+
+// 备份对象命名：<prefix>llm-gateway-20060102T150405Z.yaml
+const (
+	objectNamePrefix = "llm-gateway-"
+	objectNameSuffix = ".yaml"
+	objectTimeLayout = "20060102T150405Z"
+)
+
+// store 备份使用的对象存储操作（由 objectstore.Client 实现）
+type store interface {
+	PutObject(key string, data []byte, contentType string) error
+	GetObject(key string) ([]byte, error)
+	DeleteObject(key string) error
+	ListObjects(prefix string) ([]objectstore.Object, error)
+}
+
+// Service 定时把网关数据备份到对象存储，并按保留天数清理旧备份
+type Service struct {
+	config     *types.BackupConfig
+	configMgr  *config.ConfigManager
+	store      store
+	passphrase string
+	stopCh     chan struct{}
+	mutex      sync.Mutex // 保护后台任务状态
+}
+
+// Passphrase 备份口令：优先使用配置，其次读取环境变量
+func Passphrase(cfg *types.BackupConfig) string {
+	if cfg.Passphrase != "" {
+		return cfg.Passphrase
+	}
+	return os.Getenv(PassphraseEnv)
+}
+
+// NewService 创建备份服务，对象存储或口令未配置时返回错误
+func NewService(cfg *types.BackupConfig, configMgr *config.ConfigManager) (*Service, error) {
+	client, err := objectstore.NewClient(&cfg.ObjectStore)
+	if err != nil {
+		return nil, err
+	}
+
+	passphrase := Passphrase(cfg)
+	if passphrase == "" {
+		return nil, fmt.Errorf("未配置备份口令（backup.passphrase 或环境变量 %s）", PassphraseEnv)
+	}
+
+	return &Service{
+		config:     cfg,
+		configMgr:  configMgr,
+		store:      client,
+		passphrase: passphrase,
+	}, nil
+}
+
+// Run 立即执行一次备份，返回备份的对象键；成功后清理过期备份
+func (s *Service) Run(now time.Time) (string, error) {
+	cfg := s.configMgr.Get()
+	if cfg == nil {
+		return "", fmt.Errorf("配置未加载")
+	}
+	data, err := Create(cfg, s.passphrase, now)
+	if err != nil {
+		return "", err
+	}
+
+	key := s.config.Prefix + objectNamePrefix + now.UTC().Format(objectTimeLayout) + objectNameSuffix
+	if err := s.store.PutObject(key, data, "application/yaml"); err != nil {
+		return "", fmt.Errorf("上传备份失败: %w", err)
+	}
+
+	if removed, err := s.Prune(now); err != nil {
+		logger.Warn("清理过期备份失败: %v", err)
+	} else if removed > 0 {
+		logger.Info("已清理 %d 份过期备份", removed)
+	}
+	return key, nil
+}
+
+// List 列出全部备份，按时间从新到旧
+func (s *Service) List() ([]objectstore.Object, error) {
+	objects, err := s.store.ListObjects(s.config.Prefix)
+	if err != nil {
+		return nil, fmt.Errorf("列出备份失败: %w", err)
+	}
+
+	var backups []objectstore.Object
+	for _, object := range objects {
+		name := strings.TrimPrefix(object.Key, s.config.Prefix)
+		if strings.HasPrefix(name, objectNamePrefix) && strings.HasSuffix(name, objectNameSuffix) {
+			backups = append(backups, object)
+		}
+	}
+	// 对象名中的时间戳定长，按键排序即按时间排序
+	sort.Slice(backups, func(i, j int) bool {
+		return backups[i].Key > backups[j].Key
+	})
+	return backups, nil
+}
+
+// Prune 删除超过保留天数的备份，最新一份始终保留
+func (s *Service) Prune(now time.Time) (int, error) {
+	backups, err := s.List()
+	if err != nil {
+		return 0, err
+	}
+
+	cutoff := now.Add(-time.Duration(s.config.RetentionDays) * 24 * time.Hour)
+	removed := 0
+	for i, object := range backups {
+		if i == 0 || !backupTime(object).Before(cutoff) {
+			continue
+		}
+		if err := s.store.DeleteObject(object.Key); err != nil {
+			return removed, fmt.Errorf("删除备份 %s 失败: %w", object.Key, err)
+		}
+		removed++
+	}
+	return removed, nil
+}
+
+// Fetch 下载并解密一份备份
+func (s *Service) Fetch(key string) (*Snapshot, []types.UpstreamAccount, error) {
+	data, err := s.store.GetObject(key)
+	if err != nil {
+		return nil, nil, fmt.Errorf("下载备份失败: %w", err)
+	}
+	return Open(data, s.passphrase)
+}
+
+// backupTime 备份时间：优先使用对象名中的时间戳
+func backupTime(object objectstore.Object) time.Time {
+	name := object.Key[strings.LastIndex(object.Key, "/")+1:]
+	name = strings.TrimSuffix(strings.TrimPrefix(name, objectNamePrefix), objectNameSuffix)
+	if t, err := time.Parse(objectTimeLayout, name); err == nil {
+		return t
+	}
+	return object.LastModified
+}
+
+// due 距离最近一次备份是否已超过备份间隔
+func (s *Service) due(now time.Time) bool {
+	backups, err := s.List()
+	if err != nil {
+		logger.Warn("检查最近一次备份失败: %v", err)
+		return true
+	}
+	interval := time.Duration(s.config.IntervalHours) * time.Hour
+	return len(backups) == 0 || now.Sub(backupTime(backups[0])) >= interval
+}
+
+// runScheduled 执行一次定时备份
+func (s *Service) runScheduled(now time.Time) {
+	key, err := s.Run(now)
+	if err != nil {
+		logger.Error("定时备份失败: %v", err)
+		return
+	}
+	logger.Info("定时备份完成: %s", key)
+}
+
+// Start 启动定时备份（启动时若距上次备份已超过间隔则立即备份一次）
+func (s *Service) Start() {
+	if s == nil {
+		return
+	}
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		return
+	}
+	s.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(time.Duration(s.config.IntervalHours) * time.Hour)
+		defer ticker.Stop()
+
+		if s.due(time.Now()) {
+			s.runScheduled(time.Now())
+		}
+		for {
+			select {
+			case <-ticker.C:
+				s.runScheduled(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(s.stopCh)
+
+	logger.Info("定时备份已启动，间隔: %d小时，保留: %d天", s.config.IntervalHours, s.config.RetentionDays)
+}
+
+// Stop 停止定时备份
+func (s *Service) Stop() {
+	if s == nil {
+		return
+	}
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		close(s.stopCh)
+		s.stopCh = nil
+	}
+}
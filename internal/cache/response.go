@@ -0,0 +1,183 @@
+package cache
+
+import (
+	"container/list"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	defaultTTL        = 5 * time.Minute
+	defaultMaxEntries = 1000
+)
+
+// Entry 缓存的客户端响应
+type Entry struct {
+	Body        []byte
+	ContentType string
+	StoredAt    time.Time
+}
+
+// ResponseCache 非流式响应的精确匹配缓存（内存，按TTL过期，超过容量时淘汰最久未使用的条目）
+type ResponseCache struct {
+	mutex      sync.Mutex
+	ttl        time.Duration
+	maxEntries int
+	entries    map[string]*list.Element
+	order      *list.List // 最近使用的在前
+	hits       int64
+	misses     int64
+	now        func() time.Time
+}
+
+// Stats 响应缓存的容量和命中情况（从启动开始累计）
+type Stats struct {
+	Entries    int     `json:"entries"`
+	MaxEntries int     `json:"max_entries"`
+	TTLSeconds int     `json:"ttl_seconds"`
+	Hits       int64   `json:"hits"`
+	Misses     int64   `json:"misses"`
+	HitRate    float64 `json:"hit_rate"`
+}
+
+// element 链表中保存的条目
+type element struct {
+	key   string
+	entry *Entry
+}
+
+// NewResponseCache 根据配置创建响应缓存，未启用时返回nil
+func NewResponseCache(config *types.ResponseCacheConfig) *ResponseCache {
+	if config == nil || !config.Enabled {
+		return nil
+	}
+
+	ttl, maxEntries := settings(config)
+	return &ResponseCache{
+		ttl:        ttl,
+		maxEntries: maxEntries,
+		entries:    make(map[string]*list.Element),
+		order:      list.New(),
+		now:        time.Now,
+	}
+}
+
+// settings 返回配置的TTL和容量，未配置时使用默认值
+func settings(config *types.ResponseCacheConfig) (time.Duration, int) {
+	ttl := defaultTTL
+	if config.TTLSeconds > 0 {
+		ttl = time.Duration(config.TTLSeconds) * time.Second
+	}
+	maxEntries := defaultMaxEntries
+	if config.MaxEntries > 0 {
+		maxEntries = config.MaxEntries
+	}
+	return ttl, maxEntries
+}
+
+// Configure 按新配置调整TTL和容量，保留已缓存的条目，超过新容量时淘汰最久未使用的条目
+func (c *ResponseCache) Configure(config *types.ResponseCacheConfig) {
+	ttl, maxEntries := settings(config)
+
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+
+	c.ttl = ttl
+	c.maxEntries = maxEntries
+	c.evictLocked()
+}
+
+// Key 计算缓存键：Gateway Key、提供商、客户端端点与格式，加上规范化后的请求（模型路由之后的统一格式）
+func Key(keyID string, provider types.Provider, endpoint, format string, request *types.UnifiedRequest) (string, error) {
+	data, err := json.Marshal(request)
+	if err != nil {
+		return "", fmt.Errorf("序列化请求失败: %w", err)
+	}
+
+	hash := sha256.New()
+	for _, part := range []string{keyID, string(provider), endpoint, format, request.Model} {
+		hash.Write([]byte(part))
+		hash.Write([]byte{0})
+	}
+	hash.Write(data)
+	return hex.EncodeToString(hash.Sum(nil)), nil
+}
+
+// Get 查找未过期的缓存条目
+func (c *ResponseCache) Get(key string) (*Entry, bool) {
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+
+	elem, exists := c.entries[key]
+	if !exists {
+		c.misses++
+		return nil, false
+	}
+	item := elem.Value.(*element)
+	if c.now().Sub(item.entry.StoredAt) >= c.ttl {
+		c.order.Remove(elem)
+		delete(c.entries, key)
+		c.misses++
+		return nil, false
+	}
+
+	c.hits++
+	c.order.MoveToFront(elem)
+	return item.entry, true
+}
+
+// Set 保存响应，超过容量时淘汰最久未使用的条目
+func (c *ResponseCache) Set(key string, body []byte, contentType string) {
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+
+	entry := &Entry{Body: body, ContentType: contentType, StoredAt: c.now()}
+	if elem, exists := c.entries[key]; exists {
+		elem.Value.(*element).entry = entry
+		c.order.MoveToFront(elem)
+		return
+	}
+
+	c.entries[key] = c.order.PushFront(&element{key: key, entry: entry})
+	c.evictLocked()
+}
+
+// evictLocked 超过容量时淘汰最久未使用的条目（调用方持有锁）
+func (c *ResponseCache) evictLocked() {
+	for c.order.Len() > c.maxEntries {
+		oldest := c.order.Back()
+		c.order.Remove(oldest)
+		delete(c.entries, oldest.Value.(*element).key)
+	}
+}
+
+// Len 返回当前缓存的条目数（包含尚未清理的过期条目）
+func (c *ResponseCache) Len() int {
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+	return c.order.Len()
+}
+
+// Stats 返回缓存的容量和命中情况
+func (c *ResponseCache) Stats() Stats {
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+
+	stats := Stats{
+		Entries:    c.order.Len(),
+		MaxEntries: c.maxEntries,
+		TTLSeconds: int(c.ttl / time.Second),
+		Hits:       c.hits,
+		Misses:     c.misses,
+	}
+	if total := c.hits + c.misses; total > 0 {
+		stats.HitRate = float64(c.hits) / float64(total)
+	}
+	return stats
+}
@@ -0,0 +1,96 @@
+package cache
+
+import (
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestResponseCache_GetSet(t *testing.T) {
+	if NewResponseCache(&types.ResponseCacheConfig{}) != nil {
+		t.Fatal("disabled config should not create a cache")
+	}
+
+	cache := NewResponseCache(&types.ResponseCacheConfig{Enabled: true, TTLSeconds: 60, MaxEntries: 2})
+	now := time.Date(2024, 1, 1, 10, 0, 0, 0, time.UTC)
+	cache.now = func() time.Time { return now }
+
+	cache.Set("a", []byte(`{"id":"a"}`), "application/json")
+	entry, ok := cache.Get("a")
+	if !ok || string(entry.Body) != `{"id":"a"}` || entry.ContentType != "application/json" {
+		t.Fatalf("Get(a) = %+v, %v", entry, ok)
+	}
+
+	// 超过容量时淘汰最久未使用的条目（a 刚被读取过，淘汰 b）
+	cache.Set("b", []byte("b"), "application/json")
+	cache.Get("a")
+	cache.Set("c", []byte("c"), "application/json")
+	if _, ok := cache.Get("b"); ok {
+		t.Error("least recently used entry should be evicted")
+	}
+	if cache.Len() != 2 {
+		t.Errorf("Len() = %d, want 2", cache.Len())
+	}
+
+	// 过期后不再命中
+	now = now.Add(time.Minute)
+	if _, ok := cache.Get("a"); ok {
+		t.Error("expired entry should not be returned")
+	}
+
+	if stats := cache.Stats(); stats.Hits != 2 || stats.Misses != 2 || stats.HitRate != 0.5 || stats.MaxEntries != 2 {
+		t.Errorf("Stats() = %+v", stats)
+	}
+}
+
+func TestResponseCache_Configure(t *testing.T) {
+	cache := NewResponseCache(&types.ResponseCacheConfig{Enabled: true, MaxEntries: 3})
+	for _, key := range []string{"a", "b", "c"} {
+		cache.Set(key, []byte(key), "application/json")
+	}
+
+	// 缩小容量时保留最近使用的条目，TTL 立即按新值计算
+	cache.Configure(&types.ResponseCacheConfig{Enabled: true, TTLSeconds: 30, MaxEntries: 2})
+	if _, ok := cache.Get("a"); ok {
+		t.Error("oldest entry should be evicted after shrinking")
+	}
+	if _, ok := cache.Get("c"); !ok {
+		t.Error("recent entry should survive a reconfigure")
+	}
+	if stats := cache.Stats(); stats.MaxEntries != 2 || stats.TTLSeconds != 30 || stats.Entries != 2 {
+		t.Errorf("Stats() = %+v, want 2 entries with ttl 30s", stats)
+	}
+}
+
+func TestKey(t *testing.T) {
+	request := func(content string) *types.UnifiedRequest {
+		return &types.UnifiedRequest{
+			Model:    "gpt-4o",
+			Messages: []types.Message{{Role: "user", Content: content}},
+		}
+	}
+
+	base, err := Key("key-a", types.ProviderOpenAI, "/v1/chat/completions", "openai", request("hi"))
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	// 与请求无关的内部字段不影响缓存键
+	same := request("hi")
+	same.UpstreamID = "upstream-1"
+	if key, _ := Key("key-a", types.ProviderOpenAI, "/v1/chat/completions", "openai", same); key != base {
+		t.Error("identical requests should share a key")
+	}
+
+	for name, key := range map[string]func() (string, error){
+		"prompt":   func() (string, error) { return Key("key-a", types.ProviderOpenAI, "/v1/chat/completions", "openai", request("hello")) },
+		"gateway":  func() (string, error) { return Key("key-b", types.ProviderOpenAI, "/v1/chat/completions", "openai", request("hi")) },
+		"provider": func() (string, error) { return Key("key-a", types.ProviderAnthropic, "/v1/chat/completions", "openai", request("hi")) },
+		"format":   func() (string, error) { return Key("key-a", types.ProviderOpenAI, "/v1/messages", "anthropic", request("hi")) },
+	} {
+		if got, _ := key(); got == base {
+			t.Errorf("changing %s should change the key", name)
+		}
+	}
+}
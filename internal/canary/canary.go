@@ -0,0 +1,374 @@
+package canary
+
+import (
+	"bytes"
+	"fmt"
+	"io"
+	"net/http"
+	"regexp"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/notify"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+const (
+	defaultInterval  = 15 * time.Minute
+	defaultTimeout   = 30 * time.Second
+	defaultMaxTokens = 32
+	maxOutputBytes   = 512 // 结果中保留的输出长度
+	canaryEndpoint   = "/v1/chat/completions"
+)
+
+// Result 一次合成探针的结果
+type Result struct {
+	Canary     string         `json:"canary"`
+	UpstreamID string         `json:"upstream_id"`
+	Name       string         `json:"name"`
+	Provider   types.Provider `json:"provider"`
+	Model      string         `json:"model"`
+	Passed     bool           `json:"passed"`
+	StatusCode int            `json:"status_code,omitempty"`
+	LatencyMs  int64          `json:"latency_ms"`
+	Output     string         `json:"output,omitempty"` // 模型输出（截断）
+	Error      string         `json:"error,omitempty"`  // 请求错误或断言失败原因
+	CheckedAt  time.Time      `json:"checked_at"`
+}
+
+// Upstreams 合成探针使用的上游账号能力
+type Upstreams interface {
+	ListActiveAccounts(provider types.Provider) []*types.UpstreamAccount
+	GetAccount(upstreamID string) (*types.UpstreamAccount, error)
+	GetAuthHeaders(upstreamID string) (map[string]string, error)
+	UpstreamURL(account *types.UpstreamAccount, path string, request *types.UnifiedRequest) string
+	UpstreamBody(account *types.UpstreamAccount, body []byte) ([]byte, error)
+	SignRequest(account *types.UpstreamAccount, req *http.Request, body []byte) error
+}
+
+// HealthRecorder 接收合成探针产生的健康信号
+type HealthRecorder interface {
+	Record(result *upstream.HealthResult)
+}
+
+// Alerter 接收合成探针失败告警
+type Alerter interface {
+	Alert(eventType, message string, details map[string]interface{}, now time.Time)
+}
+
+// Runner 定期向各提供商的账号发送很小的提示词并断言输出（子串或正则），
+// 结果作为健康信号记录到账号，连续失败的第一次触发告警，恢复后才会再次告警
+type Runner struct {
+	settings  func() *types.CanaryConfig // 每次运行时读取探针列表，重新加载配置后下一轮生效
+	upstreams Upstreams
+	converter *converter.Manager
+	health    HealthRecorder // 可为nil
+	alerter   Alerter        // 可为nil
+	client    *http.Client
+
+	results map[string]*Result // 探针名称/账号ID -> 最近一次结果
+	failing map[string]bool    // 正在失败的探针/账号，恢复前不重复告警
+
+	stopCh chan struct{}
+	mutex  sync.Mutex
+}
+
+// NewRunner 创建合成探针运行器，settings 返回当前的探针配置（超时在创建时确定）
+func NewRunner(settings func() *types.CanaryConfig, upstreams Upstreams, converter *converter.Manager, health HealthRecorder, alerter Alerter) *Runner {
+	timeout := time.Duration(settings().TimeoutSeconds) * time.Second
+	if timeout <= 0 {
+		timeout = defaultTimeout
+	}
+
+	return &Runner{
+		settings:  settings,
+		upstreams: upstreams,
+		converter: converter,
+		health:    health,
+		alerter:   alerter,
+		client:    &http.Client{Timeout: timeout},
+		results:   make(map[string]*Result),
+		failing:   make(map[string]bool),
+	}
+}
+
+// Start 启动后台运行，未启用时不启动
+func (r *Runner) Start() {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	config := r.settings()
+	if r.stopCh != nil || !config.Enabled || len(config.Checks) == 0 {
+		return
+	}
+	r.stopCh = make(chan struct{})
+
+	interval := defaultInterval
+	if config.IntervalSeconds > 0 {
+		interval = time.Duration(config.IntervalSeconds) * time.Second
+	}
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				r.RunOnce()
+			case <-stopCh:
+				return
+			}
+		}
+	}(r.stopCh)
+}
+
+// Stop 停止后台运行
+func (r *Runner) Stop() {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	if r.stopCh != nil {
+		close(r.stopCh)
+		r.stopCh = nil
+	}
+}
+
+// RunOnce 依次运行所有探针，返回本轮结果
+func (r *Runner) RunOnce() []*Result {
+	var results []*Result
+	for _, check := range r.settings().Checks {
+		for _, account := range r.accounts(check) {
+			result := r.run(check, account)
+			r.record(result)
+			results = append(results, result)
+		}
+	}
+	return results
+}
+
+// Results 返回每个探针/账号最近一次的结果，按探针名称和账号ID排序
+func (r *Runner) Results() []*Result {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	results := make([]*Result, 0, len(r.results))
+	for _, result := range r.results {
+		results = append(results, result)
+	}
+	sort.Slice(results, func(i, j int) bool {
+		if results[i].Canary != results[j].Canary {
+			return results[i].Canary < results[j].Canary
+		}
+		return results[i].UpstreamID < results[j].UpstreamID
+	})
+	return results
+}
+
+// accounts 返回探针要探测的账号：指定的账号（跳过不存在或已禁用的），或该提供商的所有活跃账号
+func (r *Runner) accounts(check types.CanaryCheck) []*types.UpstreamAccount {
+	if len(check.UpstreamIDs) == 0 {
+		return r.upstreams.ListActiveAccounts(check.Provider)
+	}
+
+	var accounts []*types.UpstreamAccount
+	for _, id := range check.UpstreamIDs {
+		account, err := r.upstreams.GetAccount(id)
+		if err != nil || account.Status == "disabled" {
+			continue
+		}
+		accounts = append(accounts, account)
+	}
+	return accounts
+}
+
+// run 向账号发送探针请求并检查输出
+func (r *Runner) run(check types.CanaryCheck, account *types.UpstreamAccount) *Result {
+	result := &Result{
+		Canary:     check.Name,
+		UpstreamID: account.ID,
+		Name:       account.Name,
+		Provider:   account.Provider,
+		Model:      check.Model,
+		CheckedAt:  time.Now(),
+	}
+
+	maxTokens := check.MaxTokens
+	if maxTokens <= 0 {
+		maxTokens = defaultMaxTokens
+	}
+	stream := false
+	request := &types.UnifiedRequest{
+		Model:     check.Model,
+		Messages:  []types.Message{{Role: "user", Content: check.Prompt}},
+		MaxTokens: maxTokens,
+		Stream:    &stream,
+	}
+
+	responseBody, statusCode, latency, err := r.send(account, request)
+	result.StatusCode = statusCode
+	result.LatencyMs = latency.Milliseconds()
+	if err != nil {
+		result.Error = err.Error()
+		return result
+	}
+
+	response, err := r.converter.ParseUpstreamResponse(responseBody, account.Provider)
+	if err != nil {
+		result.Error = fmt.Sprintf("invalid response: %v", err)
+		return result
+	}
+
+	output := ""
+	if len(response.Choices) > 0 {
+		output = messageText(response.Choices[0].Message.Content)
+	}
+	result.Output = utils.TruncateUTF8(output, maxOutputBytes)
+	result.Error = Evaluate(check, output)
+	result.Passed = result.Error == ""
+	return result
+}
+
+// send 构建并发送上游请求，非200状态码作为错误返回
+func (r *Runner) send(account *types.UpstreamAccount, request *types.UnifiedRequest) ([]byte, int, time.Duration, error) {
+	path, err := r.converter.GetUpstreamPath(account.Provider, canaryEndpoint)
+	if err != nil {
+		return nil, 0, 0, err
+	}
+	body, err := r.converter.BuildUpstreamRequest(request, account.Provider)
+	if err != nil {
+		return nil, 0, 0, fmt.Errorf("failed to build request: %w", err)
+	}
+	if body, err = r.upstreams.UpstreamBody(account, body); err != nil {
+		return nil, 0, 0, fmt.Errorf("failed to build request: %w", err)
+	}
+
+	req, err := http.NewRequest(http.MethodPost, r.upstreams.UpstreamURL(account, converter.ExpandUpstreamPath(path, request), request), bytes.NewReader(body))
+	if err != nil {
+		return nil, 0, 0, err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if account.Provider == types.ProviderAnthropic {
+		req.Header.Set("User-Agent", "claude-cli/1.0.56 (external, cli)")
+	} else {
+		req.Header.Set("User-Agent", "LLM-Gateway/1.0")
+	}
+	authHeaders, err := r.upstreams.GetAuthHeaders(account.ID)
+	if err != nil {
+		return nil, 0, 0, fmt.Errorf("failed to get auth headers: %w", err)
+	}
+	for key, value := range authHeaders {
+		req.Header.Set(key, value)
+	}
+	if err := r.upstreams.SignRequest(account, req, body); err != nil {
+		return nil, 0, 0, fmt.Errorf("failed to sign request: %w", err)
+	}
+
+	start := time.Now()
+	resp, err := r.client.Do(req)
+	if err != nil {
+		return nil, 0, time.Since(start), err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	responseBody, err := io.ReadAll(resp.Body)
+	latency := time.Since(start)
+	if err != nil {
+		return nil, resp.StatusCode, latency, fmt.Errorf("failed to read response: %w", err)
+	}
+	if resp.StatusCode != http.StatusOK {
+		return nil, resp.StatusCode, latency, fmt.Errorf("status %d: %s", resp.StatusCode, utils.TruncateUTF8(string(responseBody), maxOutputBytes))
+	}
+	return responseBody, resp.StatusCode, latency, nil
+}
+
+// record 保存结果，写入健康信号，首次失败时告警
+func (r *Runner) record(result *Result) {
+	key := result.Canary + "/" + result.UpstreamID
+
+	r.mutex.Lock()
+	r.results[key] = result
+	wasFailing := r.failing[key]
+	r.failing[key] = !result.Passed
+	r.mutex.Unlock()
+
+	if r.health != nil {
+		healthResult := &upstream.HealthResult{
+			UpstreamID: result.UpstreamID,
+			Name:       result.Name,
+			Provider:   result.Provider,
+			Healthy:    result.Passed,
+			Probed:     true,
+			StatusCode: result.StatusCode,
+			LatencyMs:  result.LatencyMs,
+			Canary:     result.Canary,
+			CheckedAt:  result.CheckedAt,
+		}
+		if !result.Passed {
+			healthResult.Error = fmt.Sprintf("canary %s: %s", result.Canary, result.Error)
+		}
+		r.health.Record(healthResult)
+	}
+
+	switch {
+	case result.Passed && wasFailing:
+		logger.Info("合成探针 %s 在上游账号 %s 上已恢复", result.Canary, result.UpstreamID)
+	case !result.Passed && !wasFailing:
+		logger.Warn("合成探针 %s 在上游账号 %s 上失败: %s", result.Canary, result.UpstreamID, result.Error)
+		if r.alerter != nil {
+			r.alerter.Alert(notify.EventCanaryFailure,
+				fmt.Sprintf("Canary %s failed on upstream account %s (%s): %s", result.Canary, result.Name, result.UpstreamID, result.Error),
+				map[string]interface{}{"canary": result.Canary, "upstream_id": result.UpstreamID, "provider": result.Provider, "model": result.Model, "status_code": result.StatusCode, "error": result.Error, "output": result.Output},
+				result.CheckedAt)
+		}
+	}
+}
+
+// Evaluate 检查模型输出是否满足探针断言，返回失败原因，通过时返回空字符串。
+// 空输出总是失败，用于发现返回200但内容为空的静默故障
+func Evaluate(check types.CanaryCheck, output string) string {
+	if strings.TrimSpace(output) == "" {
+		return "empty content"
+	}
+	if check.ExpectContains != "" && !strings.Contains(strings.ToLower(output), strings.ToLower(check.ExpectContains)) {
+		return fmt.Sprintf("output does not contain %q", check.ExpectContains)
+	}
+	if check.ExpectRegex != "" {
+		pattern, err := regexp.Compile(check.ExpectRegex)
+		if err != nil {
+			return fmt.Sprintf("invalid expect_regex: %v", err)
+		}
+		if !pattern.MatchString(output) {
+			return fmt.Sprintf("output does not match %q", check.ExpectRegex)
+		}
+	}
+	return ""
+}
+
+// messageText 提取响应消息中的文本：字符串，或文本内容块数组
+func messageText(content interface{}) string {
+	switch content := content.(type) {
+	case string:
+		return content
+	case []interface{}:
+		var texts []string
+		for _, item := range content {
+			block, ok := item.(map[string]interface{})
+			if !ok || block["type"] != "text" {
+				continue
+			}
+			if text, ok := block["text"].(string); ok {
+				texts = append(texts, text)
+			}
+		}
+		return strings.Join(texts, "\n")
+	default:
+		return ""
+	}
+}
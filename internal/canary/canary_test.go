@@ -0,0 +1,155 @@
+package canary
+
+import (
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+type stubUpstreams struct {
+	accounts []*types.UpstreamAccount
+}
+
+func (s *stubUpstreams) ListActiveAccounts(provider types.Provider) []*types.UpstreamAccount {
+	var accounts []*types.UpstreamAccount
+	for _, account := range s.accounts {
+		if account.Provider == provider && account.Status == "active" {
+			accounts = append(accounts, account)
+		}
+	}
+	return accounts
+}
+
+func (s *stubUpstreams) GetAccount(upstreamID string) (*types.UpstreamAccount, error) {
+	for _, account := range s.accounts {
+		if account.ID == upstreamID {
+			return account, nil
+		}
+	}
+	return nil, fmt.Errorf("account not found: %s", upstreamID)
+}
+
+func (s *stubUpstreams) GetAuthHeaders(upstreamID string) (map[string]string, error) {
+	account, err := s.GetAccount(upstreamID)
+	if err != nil {
+		return nil, err
+	}
+	return map[string]string{"Authorization": "Bearer " + account.APIKey}, nil
+}
+
+func (s *stubUpstreams) UpstreamURL(account *types.UpstreamAccount, path string, request *types.UnifiedRequest) string {
+	return account.BaseURL + path
+}
+
+func (s *stubUpstreams) UpstreamBody(account *types.UpstreamAccount, body []byte) ([]byte, error) {
+	return body, nil
+}
+
+func (s *stubUpstreams) SignRequest(account *types.UpstreamAccount, req *http.Request, body []byte) error {
+	return nil
+}
+
+type stubHealth struct {
+	results []*upstream.HealthResult
+}
+
+func (s *stubHealth) Record(result *upstream.HealthResult) {
+	s.results = append(s.results, result)
+}
+
+type stubAlerter struct {
+	messages []string
+}
+
+func (s *stubAlerter) Alert(eventType, message string, details map[string]interface{}, now time.Time) {
+	s.messages = append(s.messages, eventType+": "+message)
+}
+
+func TestEvaluate(t *testing.T) {
+	tests := []struct {
+		name   string
+		check  types.CanaryCheck
+		output string
+		want   string
+	}{
+		{"empty", types.CanaryCheck{}, "  \n", "empty content"},
+		{"no_assertions", types.CanaryCheck{}, "anything", ""},
+		{"contains_case_insensitive", types.CanaryCheck{ExpectContains: "pong"}, "PONG!", ""},
+		{"contains_missing", types.CanaryCheck{ExpectContains: "pong"}, "ping", "does not contain"},
+		{"regex_match", types.CanaryCheck{ExpectRegex: `\b4\b`}, "2 + 2 = 4", ""},
+		{"regex_mismatch", types.CanaryCheck{ExpectRegex: `^\d+$`}, "four", "does not match"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := Evaluate(tt.check, tt.output)
+			if (tt.want == "") != (got == "") || !strings.Contains(got, tt.want) {
+				t.Errorf("Evaluate() = %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestRunner_RunOnce(t *testing.T) {
+	content := "pong"
+	upstreamServer := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.Method != http.MethodPost || r.URL.Path != "/v1/chat/completions" {
+			t.Errorf("unexpected canary request: %s %s", r.Method, r.URL.Path)
+		}
+		if r.Header.Get("Authorization") == "Bearer sk-bad" {
+			w.WriteHeader(http.StatusInternalServerError)
+			_, _ = w.Write([]byte(`{"error":"boom"}`))
+			return
+		}
+		_, _ = fmt.Fprintf(w, `{"id":"c1","object":"chat.completion","model":"gpt-4o-mini","choices":[{"index":0,"message":{"role":"assistant","content":%q},"finish_reason":"stop"}]}`, content)
+	}))
+	defer upstreamServer.Close()
+
+	upstreams := &stubUpstreams{accounts: []*types.UpstreamAccount{
+		{ID: "good", Name: "primary", Provider: types.ProviderOpenAI, BaseURL: upstreamServer.URL, APIKey: "sk-good", Status: "active"},
+		{ID: "bad", Name: "secondary", Provider: types.ProviderOpenAI, BaseURL: upstreamServer.URL, APIKey: "sk-bad", Status: "active"},
+		{ID: "off", Name: "disabled", Provider: types.ProviderOpenAI, BaseURL: upstreamServer.URL, APIKey: "sk-good", Status: "disabled"},
+	}}
+	config := &types.CanaryConfig{Checks: []types.CanaryCheck{
+		{Name: "ping", Provider: types.ProviderOpenAI, Model: "gpt-4o-mini", Prompt: "Reply with pong", ExpectContains: "pong"},
+	}}
+	health := &stubHealth{}
+	alerter := &stubAlerter{}
+	runner := NewRunner(func() *types.CanaryConfig { return config }, upstreams, converter.NewManager(), health, alerter)
+
+	results := runner.RunOnce()
+	if len(results) != 2 {
+		t.Fatalf("len(results) = %d, want 2", len(results))
+	}
+	if good := results[0]; good.UpstreamID != "good" || !good.Passed || good.Output != "pong" {
+		t.Errorf("good result = %+v", good)
+	}
+	if bad := results[1]; bad.UpstreamID != "bad" || bad.Passed || bad.StatusCode != http.StatusInternalServerError {
+		t.Errorf("bad result = %+v", bad)
+	}
+	if len(health.results) != 2 || !health.results[0].Healthy || health.results[1].Healthy || health.results[1].Canary != "ping" {
+		t.Errorf("health signals = %+v", health.results)
+	}
+	if len(alerter.messages) != 1 || !strings.Contains(alerter.messages[0], "bad") {
+		t.Errorf("alerts = %v, want one for the failing account", alerter.messages)
+	}
+
+	// 上游返回200但内容为空：正常账号开始失败并告警，已在失败的账号不重复告警
+	content = ""
+	runner.RunOnce()
+	if len(alerter.messages) != 2 || !strings.Contains(alerter.messages[1], "empty content") {
+		t.Errorf("alerts = %v, want a second alert for empty content", alerter.messages)
+	}
+
+	latest := runner.Results()
+	if len(latest) != 2 || latest[0].UpstreamID != "bad" || latest[1].Passed {
+		t.Errorf("Results() = %+v", latest)
+	}
+}
@@ -2,17 +2,19 @@ package client
 
 import (
 	"crypto/rand"
-	"crypto/sha256"
 	"encoding/hex"
 	"fmt"
+	"strings"
+	"sync"
 	"time"
 
 	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
 )
 
 // ConfigManager 配置管理器接口
 type ConfigManager interface {
-	CreateGatewayKey(key *types.GatewayAPIKey) error
+	CreateGatewayKeyLimited(key *types.GatewayAPIKey, limit int) error
 	GetGatewayKey(keyID string) (*types.GatewayAPIKey, error)
 	ListGatewayKeys() []*types.GatewayAPIKey
 	UpdateGatewayKey(keyID string, updater func(*types.GatewayAPIKey) error) error
@@ -22,38 +24,100 @@ type ConfigManager interface {
 // GatewayKeyManager Gateway API Key业务管理器
 type GatewayKeyManager struct {
 	configMgr ConfigManager
+
+	// modelScopes 每个Key编译后的模型作用域，作用域修改后重新编译
+	modelScopes map[string]*compiledScopes
+	scopeMutex  sync.Mutex
+}
+
+// compiledScopes 编译结果和编译时的作用域（用于检测修改）
+type compiledScopes struct {
+	signature string
+	scopes    *types.ModelScopes
 }
 
 // NewGatewayKeyManager 创建新的Gateway Key管理器
 func NewGatewayKeyManager(configMgr ConfigManager) *GatewayKeyManager {
 	return &GatewayKeyManager{
-		configMgr: configMgr,
+		configMgr:   configMgr,
+		modelScopes: make(map[string]*compiledScopes),
 	}
 }
 
+// KeyOptions 创建Key时一并保存的属性，Key保存前就已设置好，不会出现属性不完整的中间状态
+type KeyOptions struct {
+	Scopes          []string
+	OrgID           string
+	CreatedBy       string
+	Sandbox         bool
+	PendingApproval bool // 自助创建的Key等待 admin 批准，创建时即为停用状态
+	MaxPerCreator   int  // CreatedBy 已创建的Key达到该数量时拒绝创建，0表示不限制
+}
+
 // CreateKey 创建新的Gateway API Key（业务逻辑）
 func (m *GatewayKeyManager) CreateKey(name string, permissions []types.Permission) (*types.GatewayAPIKey, string, error) {
+	return m.CreateKeyWithOptions(name, permissions, KeyOptions{})
+}
+
+// CreateKeyWithOptions 创建带有作用域、组织等属性的Gateway API Key；
+// 超过 MaxPerCreator 时返回的错误包装 config.ErrKeyLimitReached
+func (m *GatewayKeyManager) CreateKeyWithOptions(name string, permissions []types.Permission, options KeyOptions) (*types.GatewayAPIKey, string, error) {
 	// 生成原始key
 	rawKey, err := generateRandomKey(32)
 	if err != nil {
 		return nil, "", fmt.Errorf("生成密钥失败: %w", err)
 	}
 
+	key, err := m.saveKey(name, rawKey, permissions, options)
+	if err != nil {
+		return nil, "", err
+	}
+	return key, rawKey, nil
+}
+
+// ImportKey 导入已有的原始密钥（从其他网关迁移时保持客户端密钥不变）
+func (m *GatewayKeyManager) ImportKey(name, rawKey string, permissions []types.Permission) (*types.GatewayAPIKey, error) {
+	if rawKey == "" {
+		return nil, fmt.Errorf("密钥不能为空")
+	}
+
+	keyHash := hashKey(rawKey)
+	for _, key := range m.configMgr.ListGatewayKeys() {
+		if utils.SecureEqual(key.KeyHash, keyHash) {
+			return nil, fmt.Errorf("密钥已存在: %s", key.ID)
+		}
+	}
+
+	return m.saveKey(name, rawKey, permissions, KeyOptions{})
+}
+
+// saveKey 根据原始密钥创建并保存Gateway API Key
+func (m *GatewayKeyManager) saveKey(name, rawKey string, permissions []types.Permission, options KeyOptions) (*types.GatewayAPIKey, error) {
 	// 计算hash
 	keyHash := hashKey(rawKey)
 
 	// 生成ID
 	keyID := generateID("gw")
 
+	status := "active"
+	if options.PendingApproval {
+		status = "disabled"
+	}
+
 	// 创建key对象
 	key := &types.GatewayAPIKey{
-		ID:          keyID,
-		Name:        name,
-		KeyHash:     keyHash,
-		Permissions: permissions,
-		Status:      "active",
-		CreatedAt:   time.Now(),
-		UpdatedAt:   time.Now(),
+		ID:              keyID,
+		Name:            name,
+		KeyHash:         keyHash,
+		Permissions:     permissions,
+		Scopes:          options.Scopes,
+		OrgID:           options.OrgID,
+		Status:          status,
+		CreatedBy:       options.CreatedBy,
+		PendingApproval: options.PendingApproval,
+		Sandbox:         options.Sandbox,
+		CreatedAt:       time.Now(),
+		UpdatedAt:       time.Now(),
 		Usage: &types.KeyUsageStats{
 			TotalRequests:      0,
 			SuccessfulRequests: 0,
@@ -63,11 +127,11 @@ func (m *GatewayKeyManager) CreateKey(name string, permissions []types.Permissio
 	}
 
 	// 通过ConfigManager保存
-	if err := m.configMgr.CreateGatewayKey(key); err != nil {
-		return nil, "", fmt.Errorf("保存密钥失败: %w", err)
+	if err := m.configMgr.CreateGatewayKeyLimited(key, options.MaxPerCreator); err != nil {
+		return nil, fmt.Errorf("保存密钥失败: %w", err)
 	}
 
-	return key, rawKey, nil
+	return key, nil
 }
 
 // ValidateKey 验证Gateway API Key（业务逻辑）
@@ -78,7 +142,7 @@ func (m *GatewayKeyManager) ValidateKey(rawKey string) (*types.GatewayAPIKey, er
 	keys := m.configMgr.ListGatewayKeys()
 
 	for _, key := range keys {
-		if key.KeyHash == keyHash && key.Status == "active" {
+		if utils.SecureEqual(key.KeyHash, keyHash) && key.Status == "active" {
 			return key, nil
 		}
 	}
@@ -96,6 +160,21 @@ func (m *GatewayKeyManager) ListKeys() []*types.GatewayAPIKey {
 	return m.configMgr.ListGatewayKeys()
 }
 
+// ModelScopes 返回Key编译后的模型作用域，按Key缓存，作用域修改后自动重新编译
+func (m *GatewayKeyManager) ModelScopes(key *types.GatewayAPIKey) *types.ModelScopes {
+	signature := strings.Join(key.Scopes, "\n")
+
+	m.scopeMutex.Lock()
+	defer m.scopeMutex.Unlock()
+
+	if cached, ok := m.modelScopes[key.ID]; ok && cached.signature == signature {
+		return cached.scopes
+	}
+	compiled := types.CompileModelScopes(key.Scopes)
+	m.modelScopes[key.ID] = &compiledScopes{signature: signature, scopes: compiled}
+	return compiled
+}
+
 // DeleteKey 删除Gateway API Key
 func (m *GatewayKeyManager) DeleteKey(keyID string) error {
 	return m.configMgr.DeleteGatewayKey(keyID)
@@ -146,10 +225,9 @@ func generateRandomKey(length int) (string, error) {
 	return hex.EncodeToString(bytes), nil
 }
 
-// hashKey 计算密钥hash
+// hashKey 计算密钥hash（SHA-256，小写十六进制）
 func hashKey(key string) string {
-	hash := sha256.Sum256([]byte(key))
-	return hex.EncodeToString(hash[:])
+	return utils.SHA256Hex(key)
 }
 
 // generateID 生成唯一ID
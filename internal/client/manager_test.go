@@ -1,6 +1,7 @@
 package client
 
 import (
+	"errors"
 	"fmt"
 	"testing"
 	"time"
@@ -8,6 +9,9 @@ import (
 	"github.com/iBreaker/llm-gateway/pkg/types"
 )
 
+// errKeyLimit 模拟配置管理器在创建者达到数量上限时返回的错误
+var errKeyLimit = errors.New("key limit reached")
+
 // MockConfigManager 实现ConfigManager接口用于测试
 type MockConfigManager struct {
 	keys map[string]*types.GatewayAPIKey
@@ -19,7 +23,16 @@ func NewMockConfigManager() *MockConfigManager {
 	}
 }
 
-func (m *MockConfigManager) CreateGatewayKey(key *types.GatewayAPIKey) error {
+func (m *MockConfigManager) CreateGatewayKeyLimited(key *types.GatewayAPIKey, limit int) error {
+	count := 0
+	for _, existing := range m.keys {
+		if existing.CreatedBy == key.CreatedBy {
+			count++
+		}
+	}
+	if limit > 0 && count >= limit {
+		return errKeyLimit
+	}
 	m.keys[key.ID] = key
 	return nil
 }
@@ -167,6 +180,11 @@ func TestGatewayKeyManager_ValidateKey(t *testing.T) {
 			rawKey:  "",
 			wantErr: true,
 		},
+		{
+			name:    "stored_hash_as_key",
+			rawKey:  key.KeyHash,
+			wantErr: true,
+		},
 	}
 
 	for _, tt := range tests {
@@ -190,6 +208,55 @@ func TestGatewayKeyManager_ValidateKey(t *testing.T) {
 	}
 }
 
+func TestGatewayKeyManager_ImportKey(t *testing.T) {
+	configMgr := NewMockConfigManager()
+	mgr := NewGatewayKeyManager(configMgr)
+
+	key, err := mgr.ImportKey("migrated", "sk-existing-client-key", []types.Permission{types.PermissionRead})
+	if err != nil {
+		t.Fatalf("ImportKey() error = %v", err)
+	}
+
+	// 导入后客户端可以继续使用原来的密钥
+	validated, err := mgr.ValidateKey("sk-existing-client-key")
+	if err != nil || validated.ID != key.ID {
+		t.Errorf("ValidateKey() = %v, %v, want imported key", validated, err)
+	}
+
+	if _, err := mgr.ImportKey("duplicate", "sk-existing-client-key", []types.Permission{types.PermissionRead}); err == nil {
+		t.Error("ImportKey() should reject an existing key")
+	}
+	if _, err := mgr.ImportKey("empty", "", []types.Permission{types.PermissionRead}); err == nil {
+		t.Error("ImportKey() should reject an empty key")
+	}
+}
+
+func TestGatewayKeyManager_CreateKeyWithOptions(t *testing.T) {
+	configMgr := NewMockConfigManager()
+	mgr := NewGatewayKeyManager(configMgr)
+	options := KeyOptions{CreatedBy: "web:alice", PendingApproval: true, MaxPerCreator: 1}
+
+	// 待审批的Key保存时就是停用状态，批准前不能使用
+	key, rawKey, err := mgr.CreateKeyWithOptions("pending", []types.Permission{types.PermissionRead}, options)
+	if err != nil {
+		t.Fatalf("CreateKeyWithOptions() error = %v", err)
+	}
+	saved := configMgr.keys[key.ID]
+	if saved.Status != "disabled" || !saved.PendingApproval || saved.CreatedBy != "web:alice" {
+		t.Errorf("saved key = %+v, want disabled and pending approval", saved)
+	}
+	if _, err := mgr.ValidateKey(rawKey); err == nil {
+		t.Error("ValidateKey() accepted a key waiting for approval")
+	}
+
+	if _, _, err := mgr.CreateKeyWithOptions("second", []types.Permission{types.PermissionRead}, options); !errors.Is(err, errKeyLimit) {
+		t.Errorf("CreateKeyWithOptions() error = %v, want the key limit error", err)
+	}
+	if len(configMgr.keys) != 1 {
+		t.Errorf("saved %d keys, want 1", len(configMgr.keys))
+	}
+}
+
 func TestGatewayKeyManager_ValidateKey_DisabledKey(t *testing.T) {
 	configMgr := NewMockConfigManager()
 	mgr := NewGatewayKeyManager(configMgr)
@@ -332,3 +399,38 @@ func TestGatewayKeyManager_UpdateKeyUsage(t *testing.T) {
 		t.Errorf("Usage.AvgLatency = %f, want %f", updatedKey.Usage.AvgLatency, expectedAvg)
 	}
 }
+
+func TestGatewayKeyManager_ModelScopes(t *testing.T) {
+	mgr := NewGatewayKeyManager(NewMockConfigManager())
+	key := &types.GatewayAPIKey{
+		ID:     "key",
+		Scopes: []string{"provider:anthropic", "provider:openai", "model:claude-3-*", "model:openai/*", "model:gpt-4"},
+	}
+
+	tests := []struct {
+		provider types.Provider
+		model    string
+		want     bool
+	}{
+		{types.ProviderAnthropic, "claude-3-haiku-20240307", true},
+		{types.ProviderAnthropic, "claude-sonnet-4-20250514", false},
+		{types.ProviderOpenAI, "o3-mini", true}, // 提供商前缀匹配该提供商的所有模型
+		{types.ProviderQwen, "gpt-4", true},
+		{types.ProviderQwen, "gpt-4o", false},
+		{types.ProviderAnthropic, "", false},
+	}
+	for _, tt := range tests {
+		if got := mgr.ModelScopes(key).Allows(tt.provider, tt.model); got != tt.want {
+			t.Errorf("Allows(%s, %q) = %v, want %v", tt.provider, tt.model, got, tt.want)
+		}
+	}
+
+	// 作用域修改后重新编译，不使用缓存的结果
+	key.Scopes = []string{"endpoint:/v1/messages"}
+	if !mgr.ModelScopes(key).Allows(types.ProviderAnthropic, "claude-sonnet-4-20250514") {
+		t.Error("a key without model scopes should allow any model")
+	}
+	if _, _, ok := types.ParseScope("model:claude-*-sonnet"); ok {
+		t.Error("ParseScope() should reject a wildcard that is not at the end")
+	}
+}
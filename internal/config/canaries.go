@@ -0,0 +1,39 @@
+package config
+
+import (
+	"fmt"
+	"regexp"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validateCanaries 验证合成探针配置
+func validateCanaries(config *types.CanaryConfig) error {
+	if config.IntervalSeconds < 0 || config.TimeoutSeconds < 0 {
+		return fmt.Errorf("canaries 的运行间隔和超时时间不能为负数")
+	}
+
+	names := make(map[string]bool, len(config.Checks))
+	for i, check := range config.Checks {
+		if check.Name == "" {
+			return fmt.Errorf("合成探针 %d: 名称不能为空", i)
+		}
+		if names[check.Name] {
+			return fmt.Errorf("合成探针名称重复: %s", check.Name)
+		}
+		names[check.Name] = true
+
+		if check.Provider == "" || check.Model == "" || check.Prompt == "" {
+			return fmt.Errorf("合成探针 %s: provider、model 和 prompt 不能为空", check.Name)
+		}
+		if check.MaxTokens < 0 {
+			return fmt.Errorf("合成探针 %s: max_tokens 不能为负数", check.Name)
+		}
+		if check.ExpectRegex != "" {
+			if _, err := regexp.Compile(check.ExpectRegex); err != nil {
+				return fmt.Errorf("合成探针 %s: 无效的 expect_regex: %w", check.Name, err)
+			}
+		}
+	}
+	return nil
+}
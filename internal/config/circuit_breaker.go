@@ -0,0 +1,53 @@
+package config
+
+import (
+	"fmt"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validateCircuitBreaker 验证熔断器参数，field 为出错时提示的配置项
+func validateCircuitBreaker(field string, config *types.CircuitBreakerConfig) error {
+	if config.FailureThreshold < 0 {
+		return fmt.Errorf("无效的 %s.failure_threshold: %d（0表示使用默认值）", field, config.FailureThreshold)
+	}
+	if config.OpenSeconds < 0 {
+		return fmt.Errorf("无效的 %s.open_seconds: %d（0表示使用默认值）", field, config.OpenSeconds)
+	}
+	return nil
+}
+
+// SetCircuitBreakerConfig 验证并保存全局熔断器参数（熔断器下次判定时使用新参数）
+func (m *ConfigManager) SetCircuitBreakerConfig(breaker types.CircuitBreakerConfig) error {
+	if err := validateCircuitBreaker("health_check.circuit_breaker", &breaker); err != nil {
+		return err
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+	next.HealthCheck.CircuitBreaker = breaker
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// SetUpstreamCircuitBreaker 验证并保存账号的熔断器参数覆盖，breaker 为nil时恢复使用全局参数
+func (m *ConfigManager) SetUpstreamCircuitBreaker(accountID string, breaker *types.CircuitBreakerConfig) error {
+	if breaker != nil {
+		if err := validateCircuitBreaker("circuit_breaker", breaker); err != nil {
+			return err
+		}
+		copied := *breaker
+		breaker = &copied
+	}
+
+	return m.UpdateUpstreamAccount(accountID, func(account *types.UpstreamAccount) error {
+		account.CircuitBreaker = breaker
+		return nil
+	})
+}
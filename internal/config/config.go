@@ -11,18 +11,53 @@ import (
 	yaml "gopkg.in/yaml.v2"
 )
 
+// MinServiceTokenSecretLength 服务账号令牌签名密钥的最小长度
+const MinServiceTokenSecretLength = 32
+
 // ConfigManager 配置管理器
 type ConfigManager struct {
 	configPath string
 	config     *types.Config
+	profile    *Profile
+	overrides  []appliedOverride // 生效的环境变量覆盖，保存时不写入配置文件
+	modTime    time.Time         // 上次加载或保存时配置文件的修改时间，用于检测外部修改
+	dismissals *announcementDismissals
 	mutex      sync.RWMutex
 }
 
 // NewConfigManager 创建新的配置管理器
 func NewConfigManager(configPath string) *ConfigManager {
+	profile, _ := LookupProfile("dev")
 	return &ConfigManager{
 		configPath: configPath,
+		profile:    profile,
+		dismissals: newAnnouncementDismissals(configPath),
+	}
+}
+
+// SetProfile 设置运行环境配置档
+func (m *ConfigManager) SetProfile(profile *Profile) {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+	m.profile = profile
+}
+
+// Profile 获取运行环境配置档
+func (m *ConfigManager) Profile() *Profile {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+	return m.profile
+}
+
+// ValidateProfile 按运行环境配置档校验当前配置
+func (m *ConfigManager) ValidateProfile() error {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
 	}
+	return m.profile.Validate(m.config)
 }
 
 // Load 加载配置文件
@@ -39,29 +74,55 @@ func (m *ConfigManager) loadUnsafe() (*types.Config, error) {
 		// 如果配置文件不存在，创建默认配置
 		if os.IsNotExist(err) {
 			config := m.createDefaultConfig()
+			m.overrides = nil
 			if err := m.saveUnsafe(config); err != nil {
 				return nil, fmt.Errorf("创建默认配置文件失败: %w", err)
 			}
+			if m.overrides, err = applyEnvOverrides(config, lookupEnv); err != nil {
+				return nil, err
+			}
 			m.config = config
 			return config, nil
 		}
 		return nil, fmt.Errorf("读取配置文件失败: %w", err)
 	}
 
+	config, overrides, err := m.parseConfig(data)
+	if err != nil {
+		return nil, err
+	}
+	m.config = config
+	m.overrides = overrides
+	m.recordModTime()
+
+	// 应用环境变量配置
+	m.applyEnvironmentConfig(config)
+
+	return config, nil
+}
+
+// parseConfig 解析配置文件内容，应用环境变量覆盖并设置默认值
+func (m *ConfigManager) parseConfig(data []byte) (*types.Config, []appliedOverride, error) {
 	var config types.Config
 	if err := yaml.Unmarshal(data, &config); err != nil {
-		return nil, fmt.Errorf("解析配置文件失败: %w", err)
+		return nil, nil, fmt.Errorf("解析配置文件失败: %w", err)
 	}
 
-	m.config = &config
+	// 环境变量覆盖配置文件中的设置
+	overrides, err := applyEnvOverrides(&config, lookupEnv)
+	if err != nil {
+		return nil, nil, err
+	}
 
 	// 设置默认值（向后兼容）
 	m.setDefaultValues(&config)
 
-	// 应用环境变量配置
-	m.applyEnvironmentConfig(&config)
+	// 预先解析路由规则的时间段和时区，匹配请求时不再解析
+	for i := range config.RoutingRules {
+		config.RoutingRules[i].Prepare()
+	}
 
-	return &config, nil
+	return &config, overrides, nil
 }
 
 // Save 保存配置到文件
@@ -71,9 +132,9 @@ func (m *ConfigManager) Save(config *types.Config) error {
 	return m.saveUnsafe(config)
 }
 
-// saveUnsafe 不加锁的保存方法（内部使用）
+// saveUnsafe 不加锁的保存方法（内部使用），被环境变量覆盖的配置项写入文件中的原值
 func (m *ConfigManager) saveUnsafe(config *types.Config) error {
-	data, err := yaml.Marshal(config)
+	data, err := yaml.Marshal(fileView(config, m.overrides))
 	if err != nil {
 		return fmt.Errorf("序列化配置失败: %w", err)
 	}
@@ -90,10 +151,55 @@ func (m *ConfigManager) saveUnsafe(config *types.Config) error {
 	}
 
 	m.config = config
+	m.recordModTime()
 	return nil
 }
 
-// Get 获取当前配置
+// cloneConfig 复制配置用于修改：修改在副本上进行，保存成功后整体替换当前配置。
+// 通过 Get() 取得的配置此后不再被修改，调用方可以不加锁读取
+func cloneConfig(config *types.Config) *types.Config {
+	next := *config
+	next.GatewayKeys = make([]types.GatewayAPIKey, len(config.GatewayKeys))
+	for i, key := range config.GatewayKeys {
+		if key.Usage != nil {
+			usage := *key.Usage
+			key.Usage = &usage
+		}
+		next.GatewayKeys[i] = key
+	}
+	next.UpstreamAccounts = make([]types.UpstreamAccount, len(config.UpstreamAccounts))
+	for i, account := range config.UpstreamAccounts {
+		if account.Usage != nil {
+			usage := *account.Usage
+			account.Usage = &usage
+		}
+		next.UpstreamAccounts[i] = account
+	}
+	next.Announcements = append([]types.Announcement(nil), config.Announcements...)
+	next.RoutingRules = append([]types.RoutingRule(nil), config.RoutingRules...)
+	next.Organizations = make([]types.Organization, len(config.Organizations))
+	for i, org := range config.Organizations {
+		next.Organizations[i] = *copyOrganization(org)
+	}
+	// 模型路由重新构造，查找索引按新的规则列表重建
+	next.ModelRoutes = types.ModelRouteConfig{
+		Routes:          append([]types.ModelRoute(nil), config.ModelRoutes.Routes...),
+		DefaultBehavior: config.ModelRoutes.DefaultBehavior,
+		EnableLogging:   config.ModelRoutes.EnableLogging,
+	}
+	next.Server.Web.Users = append([]types.WebUser(nil), config.Server.Web.Users...)
+	next.Server.Web.ServiceAccounts = append([]types.ServiceAccount(nil), config.Server.Web.ServiceAccounts...)
+	if config.Providers != nil {
+		next.Providers = make(map[types.Provider]types.ProviderSettings, len(config.Providers))
+		for provider, settings := range config.Providers {
+			next.Providers[provider] = settings
+		}
+	}
+	return &next
+}
+
+// Get 获取当前配置。返回的配置不会被原地修改（修改和重新加载都替换为新的配置），
+// 需要最新设置的组件应在每次使用时调用 Get()，而不是保存其中字段的指针
 func (m *ConfigManager) Get() *types.Config {
 	m.mutex.RLock()
 	defer m.mutex.RUnlock()
@@ -108,13 +214,39 @@ func (m *ConfigManager) Validate() error {
 
 	// 验证服务器配置
 	if m.config.Server.Port <= 0 || m.config.Server.Port > 65535 {
-		return fmt.Errorf("无效的端口号: %d", m.config.Server.Port)
+		return fmt.Errorf("无效的端口号: %d（server.port 的范围为 1-65535）", m.config.Server.Port)
 	}
 
 	if m.config.Server.Host == "" {
 		return fmt.Errorf("服务器地址不能为空")
 	}
 
+	if m.config.Server.DrainTimeoutSeconds < 0 {
+		return fmt.Errorf("server.drain_timeout_seconds 不能为负数")
+	}
+
+	if m.config.Server.ConfigWatchSeconds < 0 {
+		return fmt.Errorf("server.config_watch_seconds 不能为负数")
+	}
+
+	if err := validateWebUsers(m.config.Server.Web.Users); err != nil {
+		return err
+	}
+	if err := validateServiceAccounts(m.config.Server.Web.ServiceAccounts); err != nil {
+		return err
+	}
+	if m.config.Server.Web.ServiceTokenTTLSeconds < 0 {
+		return fmt.Errorf("server.web.service_token_ttl_seconds 不能为负数")
+	}
+	if secret := m.config.Server.Web.ServiceTokenSecret; secret != "" && len(secret) < MinServiceTokenSecretLength {
+		return fmt.Errorf("server.web.service_token_secret 只有 %d 个字符，至少需要 %d 个（可以用 openssl rand -hex 32 生成，或留空在每次启动时随机生成）", len(secret), MinServiceTokenSecretLength)
+	}
+
+	// 验证熔断器参数
+	if err := validateCircuitBreaker("health_check.circuit_breaker", &m.config.HealthCheck.CircuitBreaker); err != nil {
+		return err
+	}
+
 	// 验证上游账号配置
 	for i, account := range m.config.UpstreamAccounts {
 		if err := m.validateUpstreamAccount(&account, i); err != nil {
@@ -129,6 +261,113 @@ func (m *ConfigManager) Validate() error {
 		}
 	}
 
+	// 验证组织及Key、上游账号的归属
+	if err := validateOrganizations(m.config); err != nil {
+		return err
+	}
+
+	// 验证路由规则
+	for i := range m.config.RoutingRules {
+		rule := &m.config.RoutingRules[i]
+		if err := rule.Validate(); err != nil {
+			return fmt.Errorf("路由规则[%d] %s: %w", i, rule.ID, err)
+		}
+	}
+
+	// 验证负载均衡策略
+	if err := validateRouting(&m.config.Routing); err != nil {
+		return err
+	}
+
+	// 验证多实例共享状态配置
+	if err := validateSharedState(&m.config.SharedState); err != nil {
+		return err
+	}
+
+	// 验证自助创建Key的设置
+	if err := validateKeyCreation(&m.config.Server.Web.KeyCreation); err != nil {
+		return err
+	}
+
+	// 验证请求体大小上限
+	if m.config.Proxy.MaxRequestBytes < 0 {
+		return fmt.Errorf("proxy.max_request_bytes 不能为负数")
+	}
+
+	// 验证请求排队配置
+	if queue := m.config.Proxy.Queue; queue.MaxSize < 0 || queue.MaxWaitSeconds < 0 {
+		return fmt.Errorf("proxy.queue 的 max_size 和 max_wait_seconds 不能为负数")
+	}
+
+	// 验证上下文超限删减配置
+	if trim := m.config.Proxy.ContextTrim; trim.KeepRecentMessages < 0 || trim.TargetRatio < 0 || trim.TargetRatio >= 1 {
+		return fmt.Errorf("proxy.context_trim 的 keep_recent_messages 不能为负数，target_ratio 必须在0到1之间")
+	}
+
+	// 验证请求/响应转换器配置
+	if err := validateTransforms(m.config.Proxy.Transforms); err != nil {
+		return err
+	}
+
+	// 验证内容审核配置
+	if err := validateModeration(&m.config.Proxy.Moderation); err != nil {
+		return err
+	}
+
+	// 验证审计日志配置
+	if mode := m.config.Audit.BodyMode; mode != "" && mode != "full" && mode != "hash" {
+		return fmt.Errorf("无效的审计日志 body_mode: %s（可选 full、hash）", mode)
+	}
+	if m.config.Audit.Dedup.MinBytes < 0 {
+		return fmt.Errorf("audit.dedup.min_bytes 不能为负数")
+	}
+
+	// 验证日志格式
+	if format := m.config.Logging.Format; format != "" && format != "text" && format != "json" {
+		return fmt.Errorf("无效的日志格式: %s（可选 text、json）", format)
+	}
+
+	// 验证请求跟踪采样率
+	trace := m.config.Logging.Trace
+	if trace.SampleRate != nil && (*trace.SampleRate < 0 || *trace.SampleRate > 1) {
+		return fmt.Errorf("无效的跟踪采样率: %v（范围 0-1）", *trace.SampleRate)
+	}
+	for prefix, rate := range trace.Routes {
+		if rate < 0 || rate > 1 {
+			return fmt.Errorf("无效的跟踪采样率 %s: %v（范围 0-1）", prefix, rate)
+		}
+	}
+
+	// 验证运行时调优配置
+	if err := validateRuntime(&m.config.Runtime); err != nil {
+		return err
+	}
+
+	// 验证自定义模型价格
+	if err := validatePricing(&m.config.Pricing); err != nil {
+		return err
+	}
+
+	// 验证告警通知配置
+	if err := validateNotifications(&m.config.Notifications); err != nil {
+		return err
+	}
+
+	// 验证合成探针配置
+	if err := validateCanaries(&m.config.Canaries); err != nil {
+		return err
+	}
+
+	// 验证定时备份配置
+	if m.config.Backup.Enabled && (m.config.Backup.ObjectStore.Endpoint == "" || m.config.Backup.ObjectStore.Bucket == "") {
+		return fmt.Errorf("启用定时备份时必须配置 backup.object_store 的 endpoint 和 bucket")
+	}
+
+	// 验证使用记录持久化配置
+	if m.config.UsageWAL.FlushIntervalSeconds < 0 {
+		return fmt.Errorf("usage_wal.flush_interval_seconds 不能为负数")
+	}
+
 	return nil
 }
 
@@ -148,7 +387,8 @@ func (m *ConfigManager) validateUpstreamAccount(account *types.UpstreamAccount,
 
 	switch account.Type {
 	case types.UpstreamTypeAPIKey:
-		if account.APIKey == "" {
+		// Bedrock 使用AWS凭证签名，本地的 OpenAI 兼容后端通常不需要认证
+		if account.APIKey == "" && account.Provider != types.ProviderBedrock && account.Provider != types.ProviderOpenAICompatible {
 			return fmt.Errorf("上游账号[%d] API Key不能为空", index)
 		}
 	case types.UpstreamTypeOAuth:
@@ -162,6 +402,41 @@ func (m *ConfigManager) validateUpstreamAccount(account *types.UpstreamAccount,
 		return fmt.Errorf("上游账号[%d] 不支持的账号类型: %s", index, account.Type)
 	}
 
+	if account.Provider == types.ProviderAzure && account.BaseURL == "" {
+		return fmt.Errorf("上游账号[%d] Azure OpenAI 账号必须配置 base_url（如 https://your-resource.openai.azure.com）", index)
+	}
+	if account.Provider == types.ProviderOpenAICompatible && account.BaseURL == "" {
+		return fmt.Errorf("上游账号[%d] OpenAI 兼容账号必须配置 base_url（如 http://localhost:11434）", index)
+	}
+	if account.Provider == types.ProviderBedrock {
+		if account.Type != types.UpstreamTypeAPIKey {
+			return fmt.Errorf("上游账号[%d] Bedrock 账号只支持 api_key 类型", index)
+		}
+		if account.AWS == nil || account.AWS.AccessKeyID == "" || account.AWS.SecretAccessKey == "" || account.AWS.Region == "" {
+			return fmt.Errorf("上游账号[%d] Bedrock 账号必须配置 aws.access_key_id、aws.secret_access_key 和 aws.region", index)
+		}
+	}
+	for model, deployment := range account.Deployments {
+		if model == "" || deployment == "" {
+			return fmt.Errorf("上游账号[%d] 部署映射的模型名和部署名不能为空", index)
+		}
+	}
+
+	if account.APIVersion != "" && !types.ValidAPIVersion(account.APIVersion) {
+		return fmt.Errorf("上游账号[%d] 无效的API版本: %s", index, account.APIVersion)
+	}
+	if account.Weight != nil && (*account.Weight < 0 || *account.Weight > types.MaxUpstreamWeight) {
+		return fmt.Errorf("上游账号[%d] 权重必须在0到%d之间", index, types.MaxUpstreamWeight)
+	}
+	if account.Priority < 0 {
+		return fmt.Errorf("上游账号[%d] 优先级不能为负数", index)
+	}
+	if account.CircuitBreaker != nil {
+		if err := validateCircuitBreaker(fmt.Sprintf("upstream_accounts[%d].circuit_breaker", index), account.CircuitBreaker); err != nil {
+			return err
+		}
+	}
+
 	return nil
 }
 
@@ -183,6 +458,36 @@ func (m *ConfigManager) validateGatewayKey(key *types.GatewayAPIKey, index int)
 		return fmt.Errorf("gateway API Key[%d] 权限不能为空", index)
 	}
 
+	for _, scope := range key.Scopes {
+		if _, _, ok := types.ParseScope(scope); !ok {
+			return fmt.Errorf("gateway API Key[%d] 无效的作用域: %s（格式为 provider:、model: 或 endpoint: 加值，通配符 * 只能在末尾）", index, scope)
+		}
+	}
+
+	for _, app := range key.Apps {
+		if !types.ValidClientAppName(app) {
+			return fmt.Errorf("gateway API Key[%d] 无效的应用名称: %s", index, app)
+		}
+	}
+
+	for _, tag := range key.Tags {
+		if tag == "" {
+			return fmt.Errorf("gateway API Key[%d] 标签不能为空", index)
+		}
+	}
+
+	for _, rule := range key.AllowedIPs {
+		if _, ok := types.ParseIPRule(rule); !ok {
+			return fmt.Errorf("gateway API Key[%d] 无效的IP地址或CIDR: %s", index, rule)
+		}
+	}
+
+	for _, origin := range key.AllowedOrigins {
+		if _, ok := types.NormalizeOrigin(origin); !ok {
+			return fmt.Errorf("gateway API Key[%d] 无效的来源: %s（格式为 https://host[:port]）", index, origin)
+		}
+	}
+
 	return nil
 }
 
@@ -191,7 +496,74 @@ func (m *ConfigManager) setDefaultValues(config *types.Config) {
 	// Web 配置默认值
 	if config.Server.Web.Password == "" {
 		config.Server.Web.Enabled = true
-		config.Server.Web.Password = "admin123"
+		config.Server.Web.Password = defaultWebPassword
+	}
+
+	// SLO 配置默认值
+	defaultSLO := defaultSLOConfig()
+	if config.SLO.LatencyThresholdMs <= 0 {
+		config.SLO.LatencyThresholdMs = defaultSLO.LatencyThresholdMs
+	}
+	if config.SLO.AvailabilityTarget <= 0 || config.SLO.AvailabilityTarget >= 1 {
+		config.SLO.AvailabilityTarget = defaultSLO.AvailabilityTarget
+	}
+	if config.SLO.WindowHours <= 0 {
+		config.SLO.WindowHours = defaultSLO.WindowHours
+	}
+	if config.SLO.BurnRateWindowMinutes <= 0 {
+		config.SLO.BurnRateWindowMinutes = defaultSLO.BurnRateWindowMinutes
+	}
+	if config.SLO.BurnRateAlertThreshold <= 0 {
+		config.SLO.BurnRateAlertThreshold = defaultSLO.BurnRateAlertThreshold
+	}
+
+	// 闲置凭证检测默认值
+	defaultHygiene := defaultHygieneConfig()
+	if config.Hygiene.IdleDays <= 0 {
+		config.Hygiene.IdleDays = defaultHygiene.IdleDays
+	}
+	if config.Hygiene.GraceDays <= 0 {
+		config.Hygiene.GraceDays = defaultHygiene.GraceDays
+	}
+
+	// 健康探测默认值
+	if config.HealthCheck.TimeoutSeconds <= 0 {
+		config.HealthCheck.TimeoutSeconds = defaultHealthCheckTimeoutSeconds
+	}
+
+	// 审计日志默认值
+	defaultAudit := defaultAuditConfig()
+	if config.Audit.BodyMode == "" {
+		config.Audit.BodyMode = defaultAudit.BodyMode
+	}
+	if config.Audit.MaxBodyBytes <= 0 {
+		config.Audit.MaxBodyBytes = defaultAudit.MaxBodyBytes
+	}
+	if config.Audit.RetentionDays <= 0 {
+		config.Audit.RetentionDays = defaultAudit.RetentionDays
+	}
+	if store := config.Audit.ObjectStore; store != nil {
+		if store.Prefix == "" {
+			store.Prefix = "audit/"
+		}
+		if store.MaxObjectBytes <= 0 {
+			store.MaxObjectBytes = 8 * 1024 * 1024
+		}
+		if store.PresignSeconds <= 0 {
+			store.PresignSeconds = 900
+		}
+	}
+
+	// 定时备份默认值
+	defaultBackup := defaultBackupConfig()
+	if config.Backup.IntervalHours <= 0 {
+		config.Backup.IntervalHours = defaultBackup.IntervalHours
+	}
+	if config.Backup.RetentionDays <= 0 {
+		config.Backup.RetentionDays = defaultBackup.RetentionDays
+	}
+	if config.Backup.Prefix == "" {
+		config.Backup.Prefix = defaultBackup.Prefix
 	}
 }
 
@@ -204,7 +576,7 @@ func (m *ConfigManager) createDefaultConfig() *types.Config {
 			Timeout: 30,
 			Web: types.WebConfig{
 				Enabled:  true,
-				Password: "admin123", // 默认密码，建议首次启动后修改
+				Password: defaultWebPassword, // 默认密码，建议首次启动后修改
 			},
 		},
 		Proxy: types.ProxyConfig{
@@ -217,6 +589,13 @@ func (m *ConfigManager) createDefaultConfig() *types.Config {
 		},
 		GatewayKeys:      []types.GatewayAPIKey{},
 		UpstreamAccounts: []types.UpstreamAccount{},
+		SLO:              defaultSLOConfig(),
+		Hygiene:          defaultHygieneConfig(),
+		HealthCheck: types.HealthCheckConfig{
+			TimeoutSeconds: defaultHealthCheckTimeoutSeconds,
+		},
+		Audit:  defaultAuditConfig(),
+		Backup: defaultBackupConfig(),
 		Logging: types.LoggingConfig{
 			Level:  "info",
 			Format: "json",
@@ -230,34 +609,160 @@ func (m *ConfigManager) createDefaultConfig() *types.Config {
 	}
 }
 
-// Reload 重新加载配置
+// defaultSLOConfig 默认SLO目标
+func defaultSLOConfig() types.SLOConfig {
+	return types.SLOConfig{
+		LatencyThresholdMs:     2000, // Apdex T=2秒
+		AvailabilityTarget:     0.99, // 99%可用性
+		WindowHours:            24 * 7,
+		BurnRateWindowMinutes:  60,
+		BurnRateAlertThreshold: 14.4, // 1小时内消耗约2%的周错误预算
+	}
+}
+
+// defaultHealthCheckTimeoutSeconds 默认健康探测超时（秒）
+const defaultHealthCheckTimeoutSeconds = 10
+
+// defaultHygieneConfig 默认闲置凭证检测配置（默认只报告不禁用）
+func defaultHygieneConfig() types.HygieneConfig {
+	return types.HygieneConfig{
+		IdleDays:  30,
+		GraceDays: 7,
+	}
+}
+
+// defaultAuditConfig 默认审计日志配置（默认关闭）
+func defaultAuditConfig() types.AuditConfig {
+	return types.AuditConfig{
+		BodyMode:      "full",
+		MaxBodyBytes:  16 * 1024,
+		RetentionDays: 30,
+	}
+}
+
+// defaultBackupConfig 默认定时备份配置（默认关闭）
+func defaultBackupConfig() types.BackupConfig {
+	return types.BackupConfig{
+		IntervalHours: 24,
+		RetentionDays: 30,
+		Prefix:        "backups/",
+	}
+}
+
+// Reload 重新读取配置文件，验证通过后整体替换当前配置，之后的 Get() 返回新配置；
+// 文件无法解析或验证失败时保留当前配置
 func (m *ConfigManager) Reload() (*types.Config, error) {
-	return m.Load()
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return m.loadUnsafe()
+	}
+
+	data, err := os.ReadFile(m.configPath)
+	if err != nil {
+		return nil, fmt.Errorf("读取配置文件失败: %w", err)
+	}
+	// 失败时同样记录修改时间，同一份无效的文件不会被监视器反复加载
+	m.recordModTime()
+	config, overrides, err := m.parseConfig(data)
+	if err != nil {
+		return nil, err
+	}
+
+	candidate := &ConfigManager{configPath: m.configPath, config: config}
+	err = candidate.Validate()
+	if err == nil && m.profile != nil {
+		err = m.profile.Validate(config)
+	}
+	if err != nil {
+		return nil, fmt.Errorf("新配置验证失败，保留当前配置: %w", err)
+	}
+
+	keepHealthState(m.config, config)
+	m.config = config
+	m.overrides = overrides
+	m.applyEnvironmentConfig(config)
+	return config, nil
+}
+
+// keepHealthState 把内存中较新的健康探测结果带到重新加载的配置里，探测结果不写配置文件，重新加载时不能丢失
+func keepHealthState(current, next *types.Config) {
+	checked := make(map[string]*types.UpstreamAccount, len(current.UpstreamAccounts))
+	for i := range current.UpstreamAccounts {
+		if current.UpstreamAccounts[i].LastHealthCheck != nil {
+			checked[current.UpstreamAccounts[i].ID] = &current.UpstreamAccounts[i]
+		}
+	}
+
+	for i := range next.UpstreamAccounts {
+		account := &next.UpstreamAccounts[i]
+		previous, exists := checked[account.ID]
+		if !exists || (account.LastHealthCheck != nil && !previous.LastHealthCheck.After(*account.LastHealthCheck)) {
+			continue
+		}
+		account.LastHealthCheck = previous.LastHealthCheck
+		account.HealthStatus = previous.HealthStatus
+		account.HealthLatencyMs = previous.HealthLatencyMs
+		account.HealthError = previous.HealthError
+		if previous.Models != nil {
+			account.Models = previous.Models
+		}
+	}
+}
+
+// FileChanged 判断配置文件在上次加载或保存之后是否被外部修改（网关自己保存配置不算修改）
+func (m *ConfigManager) FileChanged() bool {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	info, err := os.Stat(m.configPath)
+	if err != nil {
+		return false
+	}
+	return !info.ModTime().Equal(m.modTime)
+}
+
+// recordModTime 记录配置文件当前的修改时间（调用方持有锁）
+func (m *ConfigManager) recordModTime() {
+	if info, err := os.Stat(m.configPath); err == nil {
+		m.modTime = info.ModTime()
+	}
 }
 
 // ===== Gateway API Keys CRUD =====
 
 // CreateGatewayKey 创建Gateway API Key
 func (m *ConfigManager) CreateGatewayKey(key *types.GatewayAPIKey) error {
+	return m.CreateGatewayKeyLimited(key, 0)
+}
+
+// CreateGatewayKeyLimited 创建Gateway API Key，key.CreatedBy 已创建的Key达到 limit 个时返回 ErrKeyLimitReached。
+// 检查数量和保存在同一把锁下完成，并发的自助创建不会超过上限；limit 为0时不限制
+func (m *ConfigManager) CreateGatewayKeyLimited(key *types.GatewayAPIKey, limit int) error {
 	m.mutex.Lock()
 	defer m.mutex.Unlock()
 
 	if m.config == nil {
 		return fmt.Errorf("配置未加载")
 	}
+	if limit > 0 && countKeysCreatedBy(m.config, key.CreatedBy) >= limit {
+		return ErrKeyLimitReached
+	}
+	next := cloneConfig(m.config)
 
 	// 检查ID是否已存在
-	for _, existingKey := range m.config.GatewayKeys {
+	for _, existingKey := range next.GatewayKeys {
 		if existingKey.ID == key.ID {
 			return fmt.Errorf("gateway API Key ID已存在: %s", key.ID)
 		}
 	}
 
 	// 添加到配置
-	m.config.GatewayKeys = append(m.config.GatewayKeys, *key)
+	next.GatewayKeys = append(next.GatewayKeys, *key)
 
 	// 自动保存到文件
-	return m.saveUnsafe(m.config)
+	return m.saveUnsafe(next)
 }
 
 // GetGatewayKey 获取指定的Gateway API Key
@@ -306,16 +811,17 @@ func (m *ConfigManager) UpdateGatewayKey(keyID string, updater func(*types.Gatew
 	if m.config == nil {
 		return fmt.Errorf("配置未加载")
 	}
+	next := cloneConfig(m.config)
 
-	for i, key := range m.config.GatewayKeys {
+	for i, key := range next.GatewayKeys {
 		if key.ID == keyID {
 			// 应用更新函数
-			if err := updater(&m.config.GatewayKeys[i]); err != nil {
+			if err := updater(&next.GatewayKeys[i]); err != nil {
 				return err
 			}
 
 			// 自动保存到文件
-			return m.saveUnsafe(m.config)
+			return m.saveUnsafe(next)
 		}
 	}
 
@@ -330,14 +836,15 @@ func (m *ConfigManager) DeleteGatewayKey(keyID string) error {
 	if m.config == nil {
 		return fmt.Errorf("配置未加载")
 	}
+	next := cloneConfig(m.config)
 
-	for i, key := range m.config.GatewayKeys {
+	for i, key := range next.GatewayKeys {
 		if key.ID == keyID {
 			// 从切片中删除
-			m.config.GatewayKeys = append(m.config.GatewayKeys[:i], m.config.GatewayKeys[i+1:]...)
+			next.GatewayKeys = append(next.GatewayKeys[:i], next.GatewayKeys[i+1:]...)
 
 			// 自动保存到文件
-			return m.saveUnsafe(m.config)
+			return m.saveUnsafe(next)
 		}
 	}
 
@@ -354,19 +861,20 @@ func (m *ConfigManager) CreateUpstreamAccount(account *types.UpstreamAccount) er
 	if m.config == nil {
 		return fmt.Errorf("配置未加载")
 	}
+	next := cloneConfig(m.config)
 
 	// 检查ID是否已存在
-	for _, existingAccount := range m.config.UpstreamAccounts {
+	for _, existingAccount := range next.UpstreamAccounts {
 		if existingAccount.ID == account.ID {
 			return fmt.Errorf("上游账号ID已存在: %s", account.ID)
 		}
 	}
 
 	// 添加到配置
-	m.config.UpstreamAccounts = append(m.config.UpstreamAccounts, *account)
+	next.UpstreamAccounts = append(next.UpstreamAccounts, *account)
 
 	// 自动保存到文件
-	return m.saveUnsafe(m.config)
+	return m.saveUnsafe(next)
 }
 
 // GetUpstreamAccount 获取指定的上游账号
@@ -449,16 +957,41 @@ func (m *ConfigManager) UpdateUpstreamAccount(accountID string, updater func(*ty
 	if m.config == nil {
 		return fmt.Errorf("配置未加载")
 	}
+	next := cloneConfig(m.config)
 
-	for i, account := range m.config.UpstreamAccounts {
+	for i, account := range next.UpstreamAccounts {
 		if account.ID == accountID {
 			// 应用更新函数
-			if err := updater(&m.config.UpstreamAccounts[i]); err != nil {
+			if err := updater(&next.UpstreamAccounts[i]); err != nil {
 				return err
 			}
 
 			// 自动保存到文件
-			return m.saveUnsafe(m.config)
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("上游账号不存在: %s", accountID)
+}
+
+// UpdateUpstreamAccountState 更新上游账号的运行时状态（如健康探测结果）。
+// 只更新内存中的配置，不写配置文件
+func (m *ConfigManager) UpdateUpstreamAccountState(accountID string, updater func(*types.UpstreamAccount) error) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, account := range next.UpstreamAccounts {
+		if account.ID == accountID {
+			if err := updater(&next.UpstreamAccounts[i]); err != nil {
+				return err
+			}
+			m.config = next
+			return nil
 		}
 	}
 
@@ -473,20 +1006,289 @@ func (m *ConfigManager) DeleteUpstreamAccount(accountID string) error {
 	if m.config == nil {
 		return fmt.Errorf("配置未加载")
 	}
+	next := cloneConfig(m.config)
 
-	for i, account := range m.config.UpstreamAccounts {
+	for i, account := range next.UpstreamAccounts {
 		if account.ID == accountID {
 			// 从切片中删除
-			m.config.UpstreamAccounts = append(m.config.UpstreamAccounts[:i], m.config.UpstreamAccounts[i+1:]...)
+			next.UpstreamAccounts = append(next.UpstreamAccounts[:i], next.UpstreamAccounts[i+1:]...)
 
 			// 自动保存到文件
-			return m.saveUnsafe(m.config)
+			return m.saveUnsafe(next)
 		}
 	}
 
 	return fmt.Errorf("上游账号不存在: %s", accountID)
 }
 
+// ===== Announcements CRUD =====
+
+// CreateAnnouncement 创建公告
+func (m *ConfigManager) CreateAnnouncement(announcement *types.Announcement) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for _, existing := range next.Announcements {
+		if existing.ID == announcement.ID {
+			return fmt.Errorf("公告ID已存在: %s", announcement.ID)
+		}
+	}
+
+	next.Announcements = append(next.Announcements, *announcement)
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// ListAnnouncements 列出所有公告
+func (m *ConfigManager) ListAnnouncements() []*types.Announcement {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return []*types.Announcement{}
+	}
+
+	// 返回副本避免外部修改内部数据
+	announcements := make([]*types.Announcement, len(m.config.Announcements))
+	for i, announcement := range m.config.Announcements {
+		announcementCopy := announcement
+		announcementCopy.DismissedBy = m.dismissals.list(announcement.ID)
+		announcements[i] = &announcementCopy
+	}
+
+	return announcements
+}
+
+// UpdateAnnouncement 更新公告
+func (m *ConfigManager) UpdateAnnouncement(id string, updater func(*types.Announcement) error) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, announcement := range next.Announcements {
+		if announcement.ID == id {
+			if err := updater(&next.Announcements[i]); err != nil {
+				return err
+			}
+
+			// 自动保存到文件
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("公告不存在: %s", id)
+}
+
+// DeleteAnnouncement 删除公告
+func (m *ConfigManager) DeleteAnnouncement(id string) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, announcement := range next.Announcements {
+		if announcement.ID == id {
+			next.Announcements = append(next.Announcements[:i], next.Announcements[i+1:]...)
+
+			// 自动保存到文件
+			if err := m.saveUnsafe(next); err != nil {
+				return err
+			}
+			return m.dismissals.remove(id)
+		}
+	}
+
+	return fmt.Errorf("公告不存在: %s", id)
+}
+
+// DismissAnnouncement 为指定用户关闭公告，关闭记录保存在单独的文件中，不重写配置文件
+func (m *ConfigManager) DismissAnnouncement(id, userID string) error {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	for _, announcement := range m.config.Announcements {
+		if announcement.ID == id {
+			return m.dismissals.add(id, userID)
+		}
+	}
+
+	return fmt.Errorf("公告不存在: %s", id)
+}
+
+// ===== Routing Rules CRUD =====
+
+// CreateRoutingRule 创建路由规则
+func (m *ConfigManager) CreateRoutingRule(rule *types.RoutingRule) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for _, existing := range next.RoutingRules {
+		if existing.ID == rule.ID {
+			return fmt.Errorf("路由规则ID已存在: %s", rule.ID)
+		}
+	}
+
+	next.RoutingRules = append(next.RoutingRules, *rule)
+	next.RoutingRules[len(next.RoutingRules)-1].Prepare()
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// ListRoutingRules 列出所有路由规则
+func (m *ConfigManager) ListRoutingRules() []*types.RoutingRule {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return []*types.RoutingRule{}
+	}
+
+	// 返回副本避免外部修改内部数据
+	rules := make([]*types.RoutingRule, len(m.config.RoutingRules))
+	for i, rule := range m.config.RoutingRules {
+		ruleCopy := rule
+		ruleCopy.UpstreamIDs = append([]string(nil), rule.UpstreamIDs...)
+		ruleCopy.KeyTags = append([]string(nil), rule.KeyTags...)
+		rules[i] = &ruleCopy
+	}
+
+	return rules
+}
+
+// UpdateRoutingRule 更新路由规则
+func (m *ConfigManager) UpdateRoutingRule(id string, updater func(*types.RoutingRule) error) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, rule := range next.RoutingRules {
+		if rule.ID == id {
+			if err := updater(&next.RoutingRules[i]); err != nil {
+				return err
+			}
+			next.RoutingRules[i].Prepare()
+
+			// 自动保存到文件
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("路由规则不存在: %s", id)
+}
+
+// DeleteRoutingRule 删除路由规则
+func (m *ConfigManager) DeleteRoutingRule(id string) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, rule := range next.RoutingRules {
+		if rule.ID == id {
+			next.RoutingRules = append(next.RoutingRules[:i], next.RoutingRules[i+1:]...)
+
+			// 自动保存到文件
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("路由规则不存在: %s", id)
+}
+
+// ===== Global Model Routes =====
+
+// CreateModelRoute 添加全局模型路由
+func (m *ConfigManager) CreateModelRoute(route *types.ModelRoute) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for _, existing := range next.ModelRoutes.Routes {
+		if existing.ID == route.ID {
+			return fmt.Errorf("模型路由ID已存在: %s", route.ID)
+		}
+	}
+
+	next.ModelRoutes.Routes = append(next.ModelRoutes.Routes, *route)
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// SetModelRoutes 替换全局模型路由配置并保存（代理下个请求使用新规则）
+func (m *ConfigManager) SetModelRoutes(routes types.ModelRouteConfig) error {
+	if err := routes.Validate(); err != nil {
+		return err
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	next.ModelRoutes = routes
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// SetProviderEnabled 设置提供商启用状态并保存
+func (m *ConfigManager) SetProviderEnabled(provider types.Provider, enabled bool) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	if next.Providers == nil {
+		next.Providers = make(map[types.Provider]types.ProviderSettings)
+	}
+	settings := next.Providers[provider]
+	settings.Enabled = &enabled
+	next.Providers[provider] = settings
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
 // GetConfigPath 获取配置文件路径
 func (m *ConfigManager) GetConfigPath() string {
 	return m.configPath
@@ -1,12 +1,19 @@
 package config
 
 import (
+	"errors"
+	"fmt"
 	"os"
 	"path/filepath"
+	"runtime"
+	"runtime/debug"
+	"strings"
+	"sync"
 	"testing"
 	"time"
 
 	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
 )
 
 func TestConfigManager_LoadDefaultConfig(t *testing.T) {
@@ -225,6 +232,34 @@ func TestConfigManager_Validate(t *testing.T) {
 			wantErr: true,
 			errMsg:  "服务器地址不能为空",
 		},
+		{
+			name: "negative_drain_timeout",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:                "localhost",
+					Port:                8080,
+					Timeout:             30,
+					DrainTimeoutSeconds: -1,
+				},
+			},
+			wantErr: true,
+			errMsg:  "drain_timeout_seconds 不能为负数",
+		},
+		{
+			name: "invalid_log_format",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Logging: types.LoggingConfig{
+					Format: "xml",
+				},
+			},
+			wantErr: true,
+			errMsg:  "无效的日志格式",
+		},
 		{
 			name: "gateway_key_missing_id",
 			config: &types.Config{
@@ -261,6 +296,69 @@ func TestConfigManager_Validate(t *testing.T) {
 			wantErr: true,
 			errMsg:  "名称不能为空",
 		},
+		{
+			name: "gateway_key_invalid_scope",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				GatewayKeys: []types.GatewayAPIKey{
+					{
+						ID:          "test-key",
+						Name:        "Test Key",
+						KeyHash:     "hash",
+						Permissions: []types.Permission{types.PermissionRead},
+						Scopes:      []string{"provider:anthropic", "team:ml"},
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "无效的作用域: team:ml",
+		},
+		{
+			name: "gateway_key_invalid_app",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				GatewayKeys: []types.GatewayAPIKey{
+					{
+						ID:          "test-key",
+						Name:        "Test Key",
+						KeyHash:     "hash",
+						Permissions: []types.Permission{types.PermissionRead},
+						Apps:        []string{"billing-bot", "ci@1.0"},
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "无效的应用名称: ci@1.0",
+		},
+		{
+			name: "gateway_key_invalid_allowed_ip",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				GatewayKeys: []types.GatewayAPIKey{
+					{
+						ID:          "test-key",
+						Name:        "Test Key",
+						KeyHash:     "hash",
+						Permissions: []types.Permission{types.PermissionRead},
+						AllowedIPs:  []string{"10.0.0.0/8", "192.168.1.300"},
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "无效的IP地址或CIDR: 192.168.1.300",
+		},
 		{
 			name: "upstream_api_key_missing_key",
 			config: &types.Config{
@@ -282,6 +380,148 @@ func TestConfigManager_Validate(t *testing.T) {
 			wantErr: true,
 			errMsg:  "API Key不能为空",
 		},
+		{
+			name: "upstream_invalid_api_version",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				UpstreamAccounts: []types.UpstreamAccount{
+					{
+						ID:         "test-upstream",
+						Name:       "Test Upstream",
+						Type:       types.UpstreamTypeAPIKey,
+						Provider:   types.ProviderAnthropic,
+						APIKey:     "sk-ant-test",
+						APIVersion: "2023-06-01; beta", // 含非法字符
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "无效的API版本",
+		},
+		{
+			name: "upstream_negative_priority",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				UpstreamAccounts: []types.UpstreamAccount{
+					{
+						ID:       "test-upstream",
+						Name:     "Test Upstream",
+						Type:     types.UpstreamTypeAPIKey,
+						Provider: types.ProviderAnthropic,
+						APIKey:   "sk-ant-test",
+						Priority: -1,
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "优先级不能为负数",
+		},
+		{
+			name: "upstream_azure_missing_base_url",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				UpstreamAccounts: []types.UpstreamAccount{
+					{
+						ID:       "test-upstream",
+						Name:     "Test Upstream",
+						Type:     types.UpstreamTypeAPIKey,
+						Provider: types.ProviderAzure,
+						APIKey:   "azure-key",
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "必须配置 base_url",
+		},
+		{
+			name: "upstream_bedrock_missing_region",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				UpstreamAccounts: []types.UpstreamAccount{
+					{
+						ID:       "test-upstream",
+						Name:     "Test Upstream",
+						Type:     types.UpstreamTypeAPIKey,
+						Provider: types.ProviderBedrock,
+						AWS:      &types.AWSCredentials{AccessKeyID: "AKID", SecretAccessKey: "secret"},
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "aws.region",
+		},
+		{
+			name: "upstream_openai_compatible_missing_base_url",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				UpstreamAccounts: []types.UpstreamAccount{
+					{
+						ID:       "test-upstream",
+						Name:     "Test Upstream",
+						Type:     types.UpstreamTypeAPIKey,
+						Provider: types.ProviderOpenAICompatible,
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "OpenAI 兼容账号必须配置 base_url",
+		},
+		{
+			name: "transform_strips_protected_field",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Proxy: types.ProxyConfig{
+					Transforms: []types.TransformConfig{
+						{Name: "strip", Type: "strip_fields", Fields: []string{"logprobs", "model"}},
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "不能删除参数 model",
+		},
+		{
+			name: "moderation_rule_without_match",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Proxy: types.ProxyConfig{
+					Moderation: types.ModerationConfig{
+						Policies: []types.ModerationPolicyConfig{
+							{Name: "default", Rules: []types.ModerationRuleConfig{{Code: "secrets"}}},
+						},
+					},
+				},
+			},
+			wantErr: true,
+			errMsg:  "规则 secrets 至少需要一个关键词或正则表达式",
+		},
 		{
 			name: "upstream_oauth_missing_client_id",
 			config: &types.Config{
@@ -304,6 +544,103 @@ func TestConfigManager_Validate(t *testing.T) {
 			wantErr: true,
 			errMsg:  "Client ID不能为空",
 		},
+		{
+			name: "runtime_negative_max_procs",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Runtime: types.RuntimeConfig{MaxProcs: -2},
+			},
+			wantErr: true,
+			errMsg:  "runtime.max_procs",
+		},
+		{
+			name: "proxy_negative_max_request_bytes",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Proxy: types.ProxyConfig{MaxRequestBytes: -1},
+			},
+			wantErr: true,
+			errMsg:  "proxy.max_request_bytes",
+		},
+		{
+			name: "usage_wal_negative_flush_interval",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				UsageWAL: types.UsageWALConfig{Enabled: true, FlushIntervalSeconds: -1},
+			},
+			wantErr: true,
+			errMsg:  "usage_wal.flush_interval_seconds",
+		},
+		{
+			name: "notification_webhook_invalid_url",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Notifications: types.NotificationConfig{
+					Webhooks: []types.WebhookConfig{{Name: "ops", URL: "ftp://example.com/hook"}},
+				},
+			},
+			wantErr: true,
+			errMsg:  "无效的URL",
+		},
+		{
+			name: "short_service_token_secret",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+					Web:     types.WebConfig{ServiceTokenSecret: "too-short"},
+				},
+			},
+			wantErr: true,
+			errMsg:  "至少需要 32 个",
+		},
+		{
+			name: "notification_unknown_error_class",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Notifications: types.NotificationConfig{
+					ErrorClassRules: []types.ErrorClassRule{{Class: "billing", Threshold: 0.2}},
+				},
+			},
+			wantErr: true,
+			errMsg:  "未知的错误分类",
+		},
+		{
+			name: "canary_invalid_regex",
+			config: &types.Config{
+				Server: types.ServerConfig{
+					Host:    "localhost",
+					Port:    8080,
+					Timeout: 30,
+				},
+				Canaries: types.CanaryConfig{
+					Checks: []types.CanaryCheck{{Name: "ping", Provider: types.ProviderOpenAI, Model: "gpt-4o-mini", Prompt: "Reply with pong", ExpectRegex: "(pong"}},
+				},
+			},
+			wantErr: true,
+			errMsg:  "expect_regex",
+		},
 	}
 
 	for _, tt := range tests {
@@ -329,19 +666,63 @@ func TestConfigManager_Validate(t *testing.T) {
 	}
 }
 
-func TestConfigManager_Reload(t *testing.T) {
-	// 创建临时目录
-	tempDir := t.TempDir()
-	configPath := filepath.Join(tempDir, "test_config.yaml")
+func TestConfigManager_ApplyRuntime(t *testing.T) {
+	previousProcs := runtime.GOMAXPROCS(0)
+	previousGC := debug.SetGCPercent(100)
+	previousStored := gcPercent.Load()
+	defer func() {
+		runtime.GOMAXPROCS(previousProcs)
+		debug.SetGCPercent(previousGC)
+		gcPercent.Store(previousStored)
+	}()
 
-	mgr := NewConfigManager(configPath)
+	percent := 150
+	mgr := NewConfigManager(filepath.Join(t.TempDir(), "config.yaml"))
+	mgr.config = &types.Config{Runtime: types.RuntimeConfig{MaxProcs: 1, GCPercent: &percent}}
 
-	// 创建初始配置
-	initialConfig := &types.Config{
-		Server: types.ServerConfig{
-			Host: "localhost",
-			Port: 8080,
-		},
+	info, err := mgr.ApplyRuntime()
+	if err != nil {
+		t.Fatalf("ApplyRuntime() error = %v", err)
+	}
+	if info.MaxProcs != 1 || info.GCPercent != 150 {
+		t.Errorf("ApplyRuntime() = %+v, want max_procs 1 and gc_percent 150", info)
+	}
+	// 读取运行时配置不会改动GC设置
+	if got := CurrentRuntime().GCPercent; got != 150 {
+		t.Errorf("CurrentRuntime().GCPercent = %d, want 150", got)
+	}
+	if got := debug.SetGCPercent(150); got != 150 {
+		t.Errorf("GC percent = %d after CurrentRuntime(), want 150", got)
+	}
+
+	percent = -5
+	if _, err := mgr.ApplyRuntime(); err == nil {
+		t.Error("ApplyRuntime() should reject gc_percent below -1")
+	}
+}
+
+func TestEnvGCPercent(t *testing.T) {
+	tests := map[string]int{"": 100, "off": -1, "50": 50, "abc": 100}
+	for value, want := range tests {
+		if got := envGCPercent(value); got != want {
+			t.Errorf("envGCPercent(%q) = %d, want %d", value, got, want)
+		}
+	}
+}
+
+func TestConfigManager_Reload(t *testing.T) {
+	// 创建临时目录
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+
+	// 创建初始配置
+	initialConfig := &types.Config{
+		Server: types.ServerConfig{
+			Host: "localhost",
+			Port: 8080,
+		},
 	}
 	_ = mgr.Save(initialConfig)
 
@@ -378,6 +759,52 @@ func TestConfigManager_Reload(t *testing.T) {
 	}
 }
 
+func TestConfigManager_UpdateUpstreamAccountState(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	if err := mgr.CreateUpstreamAccount(&types.UpstreamAccount{ID: "up-1", Name: "up", Type: types.UpstreamTypeAPIKey, Provider: types.ProviderAnthropic, APIKey: "sk-ant"}); err != nil {
+		t.Fatalf("CreateUpstreamAccount() error = %v", err)
+	}
+	saved, err := os.ReadFile(configPath)
+	if err != nil {
+		t.Fatalf("ReadFile() error = %v", err)
+	}
+
+	// 运行时状态只更新内存，不写配置文件
+	checkedAt := time.Now()
+	err = mgr.UpdateUpstreamAccountState("up-1", func(account *types.UpstreamAccount) error {
+		account.LastHealthCheck = &checkedAt
+		account.HealthStatus = "unhealthy"
+		account.HealthError = "timeout"
+		return nil
+	})
+	if err != nil {
+		t.Fatalf("UpdateUpstreamAccountState() error = %v", err)
+	}
+	if account, _ := mgr.GetUpstreamAccount("up-1"); account.HealthStatus != "unhealthy" {
+		t.Errorf("HealthStatus = %q, want unhealthy", account.HealthStatus)
+	}
+	if data, _ := os.ReadFile(configPath); string(data) != string(saved) {
+		t.Error("UpdateUpstreamAccountState() should not rewrite the config file")
+	}
+	if err := mgr.UpdateUpstreamAccountState("missing", func(*types.UpstreamAccount) error { return nil }); err == nil {
+		t.Error("UpdateUpstreamAccountState() should fail for an unknown account")
+	}
+
+	// 重新加载配置文件时保留内存中的探测结果
+	if _, err := mgr.Reload(); err != nil {
+		t.Fatalf("Reload() error = %v", err)
+	}
+	if account, _ := mgr.GetUpstreamAccount("up-1"); account.HealthStatus != "unhealthy" || account.HealthError != "timeout" {
+		t.Errorf("health state after Reload() = %+v", account)
+	}
+}
+
 func TestConfigManager_GetConfigPath(t *testing.T) {
 	configPath := "/tmp/test_config.yaml"
 	mgr := NewConfigManager(configPath)
@@ -407,6 +834,516 @@ func TestConfigManager_Get(t *testing.T) {
 	}
 }
 
+func TestConfigManager_Announcements(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	announcement := &types.Announcement{
+		ID:      "announcement-1",
+		Title:   "Maintenance",
+		Message: "Scheduled maintenance tonight",
+		Level:   "warning",
+		Enabled: true,
+	}
+	if err := mgr.CreateAnnouncement(announcement); err != nil {
+		t.Fatalf("CreateAnnouncement() error = %v", err)
+	}
+	if err := mgr.CreateAnnouncement(announcement); err == nil {
+		t.Error("CreateAnnouncement() should reject duplicate IDs")
+	}
+
+	// 重复关闭只记录一次，关闭记录不写配置文件
+	saved, err := os.ReadFile(configPath)
+	if err != nil {
+		t.Fatalf("ReadFile() error = %v", err)
+	}
+	for i := 0; i < 2; i++ {
+		if err := mgr.DismissAnnouncement("announcement-1", "gw_key"); err != nil {
+			t.Fatalf("DismissAnnouncement() error = %v", err)
+		}
+	}
+	if data, _ := os.ReadFile(configPath); string(data) != string(saved) {
+		t.Error("DismissAnnouncement() should not rewrite the config file")
+	}
+
+	// 重新加载后关闭状态仍然保留
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	announcements := reloaded.ListAnnouncements()
+	if len(announcements) != 1 {
+		t.Fatalf("ListAnnouncements() len = %d, want 1", len(announcements))
+	}
+	if len(announcements[0].DismissedBy) != 1 || !announcements[0].IsDismissedBy("gw_key") {
+		t.Errorf("DismissedBy = %v, want [gw_key]", announcements[0].DismissedBy)
+	}
+	if announcements[0].IsDismissedBy("other_key") {
+		t.Error("announcement should not be dismissed for other users")
+	}
+
+	if err := reloaded.DeleteAnnouncement("announcement-1"); err != nil {
+		t.Fatalf("DeleteAnnouncement() error = %v", err)
+	}
+	if err := reloaded.DismissAnnouncement("announcement-1", "gw_key"); err == nil {
+		t.Error("DismissAnnouncement() should fail for deleted announcement")
+	}
+
+	// 删除公告时一并删除关闭记录，同ID的新公告不会被视为已关闭
+	if err := reloaded.CreateAnnouncement(announcement); err != nil {
+		t.Fatalf("CreateAnnouncement() error = %v", err)
+	}
+	if announcements := reloaded.ListAnnouncements(); announcements[0].IsDismissedBy("gw_key") {
+		t.Error("recreated announcement should not keep old dismissals")
+	}
+}
+
+func TestConfigManager_RoutingRules(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	rules := []*types.RoutingRule{
+		{ID: "rule-gpt", Pattern: "gpt-4*", Provider: types.ProviderOpenAI, UpstreamIDs: []string{"openai-1"}, Priority: 10, Enabled: true},
+		{ID: "rule-claude", Pattern: "claude-*", Provider: types.ProviderAnthropic, Priority: 20, Enabled: true},
+	}
+	for _, rule := range rules {
+		if err := mgr.CreateRoutingRule(rule); err != nil {
+			t.Fatalf("CreateRoutingRule() error = %v", err)
+		}
+	}
+	if err := mgr.CreateRoutingRule(rules[0]); err == nil {
+		t.Error("CreateRoutingRule() should reject duplicate IDs")
+	}
+
+	// 重新加载后规则仍然保留，并按优先级匹配
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	loaded := reloaded.ListRoutingRules()
+	if len(loaded) != 2 {
+		t.Fatalf("ListRoutingRules() len = %d, want 2", len(loaded))
+	}
+	if rule := types.MatchRoutingRule(loaded, "GPT-4o"); rule == nil || rule.ID != "rule-gpt" || !rule.InPool("openai-1") || rule.InPool("openai-2") {
+		t.Errorf("MatchRoutingRule(GPT-4o) = %+v, want rule-gpt with pool [openai-1]", rule)
+	}
+
+	err := reloaded.UpdateRoutingRule("rule-gpt", func(rule *types.RoutingRule) error {
+		rule.Enabled = false
+		return nil
+	})
+	if err != nil {
+		t.Fatalf("UpdateRoutingRule() error = %v", err)
+	}
+	if rule := types.MatchRoutingRule(reloaded.ListRoutingRules(), "gpt-4o"); rule != nil {
+		t.Errorf("disabled rule should not match, got %+v", rule)
+	}
+
+	if err := reloaded.DeleteRoutingRule("rule-claude"); err != nil {
+		t.Fatalf("DeleteRoutingRule() error = %v", err)
+	}
+	if err := reloaded.DeleteRoutingRule("rule-claude"); err == nil {
+		t.Error("DeleteRoutingRule() should fail for missing rule")
+	}
+}
+
+func TestConfigManager_SetModelRoutes(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	previous := mgr.Get()
+
+	routes := types.ModelRouteConfig{Routes: []types.ModelRoute{
+		{ID: "fast", SourceModel: "fast", TargetModel: "gpt-4o-mini", TargetProvider: types.ProviderOpenAI, Enabled: true},
+		{ID: "smart", SourceModel: "smart-*", TargetModel: "claude-3-5-sonnet-20241022", TargetProvider: types.ProviderAnthropic, Priority: 5, Enabled: true},
+	}}
+	if err := mgr.SetModelRoutes(routes); err != nil {
+		t.Fatalf("SetModelRoutes() error = %v", err)
+	}
+	if len(previous.ModelRoutes.Routes) != 0 {
+		t.Errorf("SetModelRoutes() modified the previous snapshot: %+v", previous.ModelRoutes.Routes)
+	}
+
+	// 重新加载后别名仍然保留，并按规则匹配
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	ctx := reloaded.Get().ModelRoutes.CreateContextWithKey("smart-v2", nil)
+	if ctx == nil || ctx.TargetModel != "claude-3-5-sonnet-20241022" || ctx.TargetProvider != types.ProviderAnthropic || ctx.RouteRuleID != "smart" {
+		t.Errorf("CreateContextWithKey(smart-v2) = %+v, want the smart alias", ctx)
+	}
+
+	// 无效的路由不保存
+	invalid := types.ModelRouteConfig{Routes: []types.ModelRoute{{ID: "broken", SourceModel: "broken", TargetProvider: types.ProviderOpenAI}}}
+	if err := mgr.SetModelRoutes(invalid); err == nil {
+		t.Error("SetModelRoutes() should reject a route without a target model")
+	}
+	if got := mgr.Get().ModelRoutes.Routes; len(got) != 2 {
+		t.Errorf("routes after a rejected update = %+v, want the previous 2 kept", got)
+	}
+}
+
+func TestConfigManager_WebUsers(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	config, err := mgr.Load()
+	if err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	if err := mgr.CreateWebUser("alice", types.RoleViewer, "alice-secret"); err != nil {
+		t.Fatalf("CreateWebUser() error = %v", err)
+	}
+	if err := mgr.CreateWebUser("alice", types.RoleViewer, "other"); err == nil {
+		t.Error("CreateWebUser() should reject duplicate usernames")
+	}
+	if err := mgr.CreateWebUser(BuiltinAdminUser, types.RoleViewer, "secret"); err == nil {
+		t.Error("CreateWebUser() should reject the built-in admin username")
+	}
+	if err := mgr.CreateWebUser("bob", "superuser", "secret"); err == nil {
+		t.Error("CreateWebUser() should reject unknown roles")
+	}
+
+	// 密码只保存加盐哈希，重新加载后仍可登录
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	users := reloaded.ListWebUsers()
+	if len(users) != 1 || users[0].PasswordHash == "alice-secret" || users[0].Salt == "" {
+		t.Fatalf("ListWebUsers() = %+v", users)
+	}
+	if role, ok := reloaded.AuthenticateWebUser("alice", "alice-secret"); !ok || role != types.RoleViewer {
+		t.Errorf("AuthenticateWebUser(alice) = %s, %v", role, ok)
+	}
+	if _, ok := reloaded.AuthenticateWebUser("alice", "wrong"); ok {
+		t.Error("AuthenticateWebUser() should reject a wrong password")
+	}
+	if role, ok := reloaded.AuthenticateWebUser("", config.Server.Web.Password); !ok || role != types.RoleAdmin {
+		t.Errorf("built-in admin login = %s, %v", role, ok)
+	}
+
+	if err := reloaded.UpdateWebUser("alice", types.RoleOperator, "new-secret"); err != nil {
+		t.Fatalf("UpdateWebUser() error = %v", err)
+	}
+	if role, ok := reloaded.AuthenticateWebUser("alice", "new-secret"); !ok || role != types.RoleOperator {
+		t.Errorf("after update = %s, %v", role, ok)
+	}
+
+	if err := reloaded.DeleteWebUser("alice"); err != nil {
+		t.Fatalf("DeleteWebUser() error = %v", err)
+	}
+	if _, ok := reloaded.AuthenticateWebUser("alice", "new-secret"); ok {
+		t.Error("deleted user should not log in")
+	}
+}
+
+func TestConfigManager_WebUserPasswordHash(t *testing.T) {
+	configPath := filepath.Join(t.TempDir(), "test_config.yaml")
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	// 新密码使用 PBKDF2，迭代次数与哈希一起保存
+	if err := mgr.CreateWebUser("alice", types.RoleViewer, "alice-secret"); err != nil {
+		t.Fatalf("CreateWebUser() error = %v", err)
+	}
+	alice := mgr.ListWebUsers()[0]
+	if alice.PasswordIterations != passwordIterations || alice.PasswordHash == utils.SHA256Hex(alice.Salt+"alice-secret") {
+		t.Errorf("new user hash = %+v, want PBKDF2 with %d iterations", alice, passwordIterations)
+	}
+
+	// 早期版本的 SHA-256 哈希仍可登录，登录成功后升级为 PBKDF2
+	config := mgr.Get()
+	next := *config
+	next.Server.Web.Users = []types.WebUser{{Username: "bob", Role: types.RoleOperator, Salt: "legacy-salt", PasswordHash: utils.SHA256Hex("legacy-salt" + "bob-secret")}}
+	if err := mgr.Save(&next); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	if _, ok := mgr.AuthenticateWebUser("bob", "wrong"); ok {
+		t.Error("AuthenticateWebUser() should reject a wrong password for a legacy hash")
+	}
+	if mgr.ListWebUsers()[0].PasswordIterations != 0 {
+		t.Error("a failed login should not upgrade the password hash")
+	}
+	if role, ok := mgr.AuthenticateWebUser("bob", "bob-secret"); !ok || role != types.RoleOperator {
+		t.Fatalf("AuthenticateWebUser(bob) = %s, %v, want the legacy hash accepted", role, ok)
+	}
+	bob := mgr.ListWebUsers()[0]
+	if bob.PasswordIterations != passwordIterations || bob.Salt == "legacy-salt" {
+		t.Errorf("legacy hash was not upgraded: %+v", bob)
+	}
+	if _, ok := mgr.AuthenticateWebUser("bob", "bob-secret"); !ok {
+		t.Error("AuthenticateWebUser() rejected the password after the upgrade")
+	}
+}
+
+func TestConfigManager_Organizations(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	if err := mgr.CreateWebUser("alice", types.RoleOperator, "alice-secret"); err != nil {
+		t.Fatalf("CreateWebUser() error = %v", err)
+	}
+
+	if err := mgr.CreateOrganization(&types.Organization{ID: "org-ml", Name: "ML", Members: []string{"alice", BuiltinAdminUser}}); err != nil {
+		t.Fatalf("CreateOrganization() error = %v", err)
+	}
+	if err := mgr.CreateOrganization(&types.Organization{ID: "org-ml", Name: "Duplicate"}); err == nil {
+		t.Error("CreateOrganization() should reject duplicate IDs")
+	}
+	if err := mgr.CreateOrganization(&types.Organization{ID: "org-x", Name: "X", Members: []string{"mallory"}}); err == nil {
+		t.Error("CreateOrganization() should reject members that are not web users")
+	}
+
+	if err := mgr.CreateGatewayKey(&types.GatewayAPIKey{ID: "key-ml", Name: "ml", KeyHash: "hash", Permissions: []types.Permission{"read"}, Status: "active", OrgID: "org-ml"}); err != nil {
+		t.Fatalf("CreateGatewayKey() error = %v", err)
+	}
+	if err := mgr.DeleteOrganization("org-ml"); err == nil {
+		t.Error("DeleteOrganization() should fail while a key belongs to the organization")
+	}
+
+	// 删除Web用户时从组织成员中移除，重新加载后配置仍然有效
+	if err := mgr.DeleteWebUser("alice"); err != nil {
+		t.Fatalf("DeleteWebUser() error = %v", err)
+	}
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	if err := validateOrganizations(reloaded.Get()); err != nil {
+		t.Fatalf("validateOrganizations() error = %v", err)
+	}
+	org, err := reloaded.GetOrganization("org-ml")
+	if err != nil || org.HasMember("alice") || !org.HasMember(BuiltinAdminUser) {
+		t.Fatalf("GetOrganization() = %+v, %v", org, err)
+	}
+
+	err = reloaded.UpdateOrganization("org-ml", func(org *types.Organization) error {
+		org.Name = ""
+		return nil
+	})
+	if err == nil {
+		t.Error("UpdateOrganization() should reject an empty name")
+	}
+	if orgs := reloaded.ListOrganizations(); len(orgs) != 1 || orgs[0].Name != "ML" {
+		t.Errorf("ListOrganizations() = %+v, rejected update should not be applied", orgs)
+	}
+
+	if err := reloaded.DeleteGatewayKey("key-ml"); err != nil {
+		t.Fatalf("DeleteGatewayKey() error = %v", err)
+	}
+	if err := reloaded.DeleteOrganization("org-ml"); err != nil {
+		t.Fatalf("DeleteOrganization() error = %v", err)
+	}
+}
+
+func TestConfigManager_CircuitBreaker(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	cfg, err := mgr.Load()
+	if err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	if err := mgr.CreateUpstreamAccount(&types.UpstreamAccount{ID: "up-1", Name: "up", Type: types.UpstreamTypeAPIKey, Provider: types.ProviderAnthropic, APIKey: "sk-ant"}); err != nil {
+		t.Fatalf("CreateUpstreamAccount() error = %v", err)
+	}
+
+	// 全局参数保存后替换当前配置，之前取得的配置保持不变
+	if err := mgr.SetCircuitBreakerConfig(types.CircuitBreakerConfig{FailureThreshold: 3, OpenSeconds: 60}); err != nil {
+		t.Fatalf("SetCircuitBreakerConfig() error = %v", err)
+	}
+	if breaker := mgr.Get().HealthCheck.CircuitBreaker; breaker.FailureThreshold != 3 || breaker.OpenSeconds != 60 {
+		t.Errorf("circuit breaker config = %+v, want the new values", breaker)
+	}
+	if cfg.HealthCheck.CircuitBreaker.FailureThreshold == 3 {
+		t.Error("SetCircuitBreakerConfig() modified the previous configuration in place")
+	}
+	if err := mgr.SetCircuitBreakerConfig(types.CircuitBreakerConfig{FailureThreshold: -1}); err == nil {
+		t.Error("SetCircuitBreakerConfig() should reject negative values")
+	}
+
+	if err := mgr.SetUpstreamCircuitBreaker("up-1", &types.CircuitBreakerConfig{FailureThreshold: 1}); err != nil {
+		t.Fatalf("SetUpstreamCircuitBreaker() error = %v", err)
+	}
+	if err := mgr.SetUpstreamCircuitBreaker("up-1", &types.CircuitBreakerConfig{OpenSeconds: -5}); err == nil {
+		t.Error("SetUpstreamCircuitBreaker() should reject negative values")
+	}
+
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	account, _ := reloaded.GetUpstreamAccount("up-1")
+	if account.CircuitBreaker == nil || account.CircuitBreaker.FailureThreshold != 1 || reloaded.Get().HealthCheck.CircuitBreaker.OpenSeconds != 60 {
+		t.Errorf("circuit breaker settings were not saved: account = %+v", account.CircuitBreaker)
+	}
+
+	if err := mgr.SetUpstreamCircuitBreaker("up-1", nil); err != nil {
+		t.Fatalf("SetUpstreamCircuitBreaker(nil) error = %v", err)
+	}
+	if account, _ := mgr.GetUpstreamAccount("up-1"); account.CircuitBreaker != nil {
+		t.Errorf("override = %+v, want removed", account.CircuitBreaker)
+	}
+}
+
+func TestConfigManager_KeyCreation(t *testing.T) {
+	configPath := filepath.Join(t.TempDir(), "test_config.yaml")
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	if err := mgr.SetKeyCreationConfig(types.KeyCreationConfig{AllowOperators: true, MaxKeysPerUser: -1}); err == nil {
+		t.Error("SetKeyCreationConfig() should reject a negative limit")
+	}
+	if err := mgr.SetKeyCreationConfig(types.KeyCreationConfig{AllowOperators: true, MaxKeysPerUser: 2, RequireApproval: true}); err != nil {
+		t.Fatalf("SetKeyCreationConfig() error = %v", err)
+	}
+
+	// 环境变量优先，无效值时使用配置
+	settings := &mgr.Get().Server.Web.KeyCreation
+	t.Setenv(MaxKeysPerUserEnvVar, "5")
+	if got := MaxKeysPerUser(settings); got != 5 {
+		t.Errorf("MaxKeysPerUser() = %d, want the environment value 5", got)
+	}
+	t.Setenv(MaxKeysPerUserEnvVar, "many")
+	if got := MaxKeysPerUser(settings); got != 2 {
+		t.Errorf("MaxKeysPerUser() = %d, want the configured value 2", got)
+	}
+
+	keys := []*types.GatewayAPIKey{
+		{ID: "key-1", Name: "one", KeyHash: "hash-1", Permissions: []types.Permission{"read"}, Status: "disabled", CreatedBy: "web:alice", PendingApproval: true},
+		{ID: "key-2", Name: "two", KeyHash: "hash-2", Permissions: []types.Permission{"read"}, Status: "active", CreatedBy: "web:alice"},
+		{ID: "key-3", Name: "three", KeyHash: "hash-3", Permissions: []types.Permission{"read"}, Status: "active", CreatedBy: "web:bob"},
+	}
+	for _, key := range keys {
+		if err := mgr.CreateGatewayKey(key); err != nil {
+			t.Fatalf("CreateGatewayKey() error = %v", err)
+		}
+	}
+	if got := mgr.CountKeysCreatedBy("web:alice"); got != 2 {
+		t.Errorf("CountKeysCreatedBy(alice) = %d, want 2", got)
+	}
+
+	now := time.Date(2024, 6, 1, 12, 0, 0, 0, time.UTC)
+	if err := mgr.ApproveGatewayKey("key-2", "web:admin", now); err == nil {
+		t.Error("ApproveGatewayKey() should reject keys that are not pending")
+	}
+	if err := mgr.ApproveGatewayKey("key-1", "web:admin", now); err != nil {
+		t.Fatalf("ApproveGatewayKey() error = %v", err)
+	}
+
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	key, _ := reloaded.GetGatewayKey("key-1")
+	if key.Status != "active" || key.PendingApproval || key.ApprovedBy != "web:admin" || key.ApprovedAt == nil || !key.ApprovedAt.Equal(now) {
+		t.Errorf("approved key = %+v", key)
+	}
+	if !reloaded.Get().Server.Web.KeyCreation.RequireApproval {
+		t.Error("key creation settings were not saved")
+	}
+}
+
+func TestConfigManager_CreateGatewayKeyLimited(t *testing.T) {
+	mgr := NewConfigManager(filepath.Join(t.TempDir(), "test_config.yaml"))
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	// 并发创建时数量检查和保存是原子的，不会超过上限
+	const limit = 2
+	var wg sync.WaitGroup
+	for i := 0; i < 8; i++ {
+		wg.Add(1)
+		go func(i int) {
+			defer wg.Done()
+			key := &types.GatewayAPIKey{ID: fmt.Sprintf("key-%d", i), Name: "burst", KeyHash: fmt.Sprintf("hash-%d", i), Status: "active", CreatedBy: "web:alice"}
+			if err := mgr.CreateGatewayKeyLimited(key, limit); err != nil && !errors.Is(err, ErrKeyLimitReached) {
+				t.Errorf("CreateGatewayKeyLimited() error = %v", err)
+			}
+		}(i)
+	}
+	wg.Wait()
+
+	if got := mgr.CountKeysCreatedBy("web:alice"); got != limit {
+		t.Errorf("CountKeysCreatedBy(alice) = %d, want %d", got, limit)
+	}
+	// 上限只针对同一个创建者
+	if err := mgr.CreateGatewayKeyLimited(&types.GatewayAPIKey{ID: "key-bob", Name: "bob", KeyHash: "hash-bob", Status: "active", CreatedBy: "web:bob"}, limit); err != nil {
+		t.Errorf("CreateGatewayKeyLimited(bob) error = %v", err)
+	}
+}
+
+func TestConfigManager_ServiceAccounts(t *testing.T) {
+	tempDir := t.TempDir()
+	configPath := filepath.Join(tempDir, "test_config.yaml")
+
+	mgr := NewConfigManager(configPath)
+	if _, err := mgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	if _, _, err := mgr.CreateServiceAccount("ci", "superuser"); err == nil {
+		t.Error("CreateServiceAccount() should reject unknown roles")
+	}
+	account, secret, err := mgr.CreateServiceAccount("ci", types.RoleOperator)
+	if err != nil {
+		t.Fatalf("CreateServiceAccount() error = %v", err)
+	}
+	if !strings.HasPrefix(account.ClientID, "sa_") || secret == "" || account.SecretHash == secret {
+		t.Fatalf("CreateServiceAccount() = %+v, %q", account, secret)
+	}
+
+	// 密钥只保存加盐哈希，重新加载后仍可换取令牌
+	reloaded := NewConfigManager(configPath)
+	if _, err := reloaded.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	if role, ok := reloaded.AuthenticateServiceAccount(account.ClientID, secret); !ok || role != types.RoleOperator {
+		t.Errorf("AuthenticateServiceAccount() = %s, %v", role, ok)
+	}
+	if _, ok := reloaded.AuthenticateServiceAccount(account.ClientID, "wrong"); ok {
+		t.Error("AuthenticateServiceAccount() should reject a wrong secret")
+	}
+
+	if err := reloaded.DeleteServiceAccount(account.ClientID); err != nil {
+		t.Fatalf("DeleteServiceAccount() error = %v", err)
+	}
+	if _, ok := reloaded.ServiceAccountRole(account.ClientID); ok {
+		t.Error("deleted service account should not have a role")
+	}
+	if err := reloaded.DeleteServiceAccount(account.ClientID); err == nil {
+		t.Error("DeleteServiceAccount() should fail for a missing account")
+	}
+}
+
 // contains 检查字符串是否包含子字符串
 func contains(s, substr string) bool {
 	return len(s) >= len(substr) &&
@@ -426,3 +1363,50 @@ func indexOfSubstring(s, substr string) int {
 	}
 	return -1
 }
+
+func TestProfile_Validate(t *testing.T) {
+	if _, err := LookupProfile("qa"); err == nil {
+		t.Error("LookupProfile(qa) should fail for unknown environment")
+	}
+
+	dev, err := LookupProfile("")
+	if err != nil || dev.Name != "dev" {
+		t.Fatalf("LookupProfile(\"\") = %v, %v, want dev", dev, err)
+	}
+
+	prod, err := LookupProfile("production")
+	if err != nil || prod.Name != "prod" {
+		t.Fatalf("LookupProfile(production) = %v, %v, want prod", prod, err)
+	}
+
+	config := &types.Config{
+		Server: types.ServerConfig{
+			Web: types.WebConfig{Enabled: true, Password: defaultWebPassword},
+		},
+	}
+
+	// dev 容忍默认密码
+	if err := dev.Validate(config); err != nil {
+		t.Errorf("dev.Validate() error = %v", err)
+	}
+
+	// prod 拒绝默认密码
+	if err := prod.Validate(config); err == nil {
+		t.Error("prod.Validate() should reject the default web password")
+	}
+
+	// prod 拒绝 CORS 通配符
+	config.Server.Web.Password = "a-much-longer-password"
+	config.Server.CORSAllowedOrigins = []string{"*"}
+	if err := prod.Validate(config); err == nil {
+		t.Error("prod.Validate() should reject wildcard CORS origins")
+	}
+
+	config.Server.CORSAllowedOrigins = []string{"https://console.example.com"}
+	if err := prod.Validate(config); err != nil {
+		t.Errorf("prod.Validate() error = %v", err)
+	}
+	if level := prod.ResolveLogLevel(config); level != "info" {
+		t.Errorf("prod.ResolveLogLevel() = %s, want info", level)
+	}
+}
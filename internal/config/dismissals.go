@@ -0,0 +1,111 @@
+package config
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+)
+
+// announcementDismissals 用户关闭公告的记录，保存在配置文件旁的单独文件中，
+// 用户关闭公告不会重写配置文件
+type announcementDismissals struct {
+	path   string
+	loaded bool
+	users  map[string][]string // 公告ID -> 已关闭的用户（Web用户名或Gateway Key ID）
+	mutex  sync.Mutex
+}
+
+// dismissalsFile 关闭记录文件名，与配置文件位于同一目录
+const dismissalsFile = "announcement_dismissals.json"
+
+func newAnnouncementDismissals(configPath string) *announcementDismissals {
+	return &announcementDismissals{path: filepath.Join(filepath.Dir(configPath), dismissalsFile)}
+}
+
+// loadUnsafe 首次使用时从文件读取关闭记录（调用方持有锁）
+func (d *announcementDismissals) loadUnsafe() error {
+	if d.loaded {
+		return nil
+	}
+
+	users := make(map[string][]string)
+	data, err := os.ReadFile(d.path)
+	if err != nil && !os.IsNotExist(err) {
+		return fmt.Errorf("读取公告关闭记录失败: %w", err)
+	}
+	if err == nil {
+		if err := json.Unmarshal(data, &users); err != nil {
+			return fmt.Errorf("解析公告关闭记录失败: %w", err)
+		}
+	}
+
+	d.users = users
+	d.loaded = true
+	return nil
+}
+
+// saveUnsafe 将关闭记录写入文件（调用方持有锁）
+func (d *announcementDismissals) saveUnsafe() error {
+	data, err := json.Marshal(d.users)
+	if err != nil {
+		return fmt.Errorf("序列化公告关闭记录失败: %w", err)
+	}
+
+	if dir := filepath.Dir(d.path); dir != "." {
+		if err := os.MkdirAll(dir, 0755); err != nil {
+			return fmt.Errorf("创建配置目录失败: %w", err)
+		}
+	}
+	if err := os.WriteFile(d.path, data, 0600); err != nil {
+		return fmt.Errorf("写入公告关闭记录失败: %w", err)
+	}
+	return nil
+}
+
+// add 记录用户关闭公告，重复关闭只记录一次
+func (d *announcementDismissals) add(id, userID string) error {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	if err := d.loadUnsafe(); err != nil {
+		return err
+	}
+	for _, existing := range d.users[id] {
+		if existing == userID {
+			return nil
+		}
+	}
+	d.users[id] = append(d.users[id], userID)
+	return d.saveUnsafe()
+}
+
+// remove 删除公告的所有关闭记录
+func (d *announcementDismissals) remove(id string) error {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	if err := d.loadUnsafe(); err != nil {
+		return err
+	}
+	if _, exists := d.users[id]; !exists {
+		return nil
+	}
+	delete(d.users, id)
+	return d.saveUnsafe()
+}
+
+// list 返回已关闭公告的用户，读取失败时视为没有关闭记录
+func (d *announcementDismissals) list(id string) []string {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	if err := d.loadUnsafe(); err != nil {
+		logger.Warn("%v", err)
+		return nil
+	}
+	return append([]string(nil), d.users[id]...)
+}
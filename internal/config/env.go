@@ -0,0 +1,162 @@
+package config
+
+import (
+	"fmt"
+	"os"
+	"strconv"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// ConfigPathEnvVar 指定配置文件路径的环境变量，命令行的 --config 优先
+const ConfigPathEnvVar = "LLM_GATEWAY_CONFIG"
+
+// EnvPrefix 覆盖配置文件设置的环境变量前缀
+const EnvPrefix = "LLM_GATEWAY_"
+
+// envOverride 一个可以用环境变量覆盖的配置项
+type envOverride struct {
+	name string // 环境变量名（不含前缀）
+	key  string // 配置文件中的路径，用于错误信息
+	get  func(config *types.Config) string
+	set  func(config *types.Config, value string) error
+}
+
+// envOverrides 支持环境变量覆盖的配置项：部署时常按环境注入的地址、端口、超时、日志和密钥
+var envOverrides = []envOverride{
+	stringOverride("SERVER_HOST", "server.host", func(c *types.Config) *string { return &c.Server.Host }),
+	intOverride("SERVER_PORT", "server.port", func(c *types.Config) *int { return &c.Server.Port }),
+	intOverride("SERVER_TIMEOUT_SECONDS", "server.timeout_seconds", func(c *types.Config) *int { return &c.Server.Timeout }),
+	boolOverride("WEB_ENABLED", "server.web.enabled", func(c *types.Config) *bool { return &c.Server.Web.Enabled }),
+	stringOverride("WEB_PASSWORD", "server.web.password", func(c *types.Config) *string { return &c.Server.Web.Password }),
+	stringOverride("SERVICE_TOKEN_SECRET", "server.web.service_token_secret", func(c *types.Config) *string { return &c.Server.Web.ServiceTokenSecret }),
+	intOverride("PROXY_REQUEST_TIMEOUT_SECONDS", "proxy.request_timeout_seconds", func(c *types.Config) *int { return &c.Proxy.RequestTimeout }),
+	intOverride("PROXY_STREAM_TIMEOUT_SECONDS", "proxy.stream_timeout_seconds", func(c *types.Config) *int { return &c.Proxy.StreamTimeout }),
+	{
+		name: "PROXY_MAX_REQUEST_BYTES",
+		key:  "proxy.max_request_bytes",
+		get:  func(c *types.Config) string { return strconv.FormatInt(c.Proxy.MaxRequestBytes, 10) },
+		set: func(c *types.Config, value string) error {
+			parsed, err := strconv.ParseInt(value, 10, 64)
+			if err != nil {
+				return fmt.Errorf("需要整数")
+			}
+			c.Proxy.MaxRequestBytes = parsed
+			return nil
+		},
+	},
+	stringOverride("SHARED_STATE_BACKEND", "shared_state.backend", func(c *types.Config) *string { return &c.SharedState.Backend }),
+	stringOverride("REDIS_ADDRESS", "shared_state.redis.address", func(c *types.Config) *string { return &c.SharedState.Redis.Address }),
+	stringOverride("REDIS_PASSWORD", "shared_state.redis.password", func(c *types.Config) *string { return &c.SharedState.Redis.Password }),
+	stringOverride("LOG_LEVEL", "logging.level", func(c *types.Config) *string { return &c.Logging.Level }),
+	stringOverride("LOG_FORMAT", "logging.format", func(c *types.Config) *string { return &c.Logging.Format }),
+}
+
+// stringOverride 字符串配置项
+func stringOverride(name, key string, field func(*types.Config) *string) envOverride {
+	return envOverride{
+		name: name,
+		key:  key,
+		get:  func(c *types.Config) string { return *field(c) },
+		set: func(c *types.Config, value string) error {
+			*field(c) = value
+			return nil
+		},
+	}
+}
+
+// intOverride 整数配置项
+func intOverride(name, key string, field func(*types.Config) *int) envOverride {
+	return envOverride{
+		name: name,
+		key:  key,
+		get:  func(c *types.Config) string { return strconv.Itoa(*field(c)) },
+		set: func(c *types.Config, value string) error {
+			parsed, err := strconv.Atoi(value)
+			if err != nil {
+				return fmt.Errorf("需要整数")
+			}
+			*field(c) = parsed
+			return nil
+		},
+	}
+}
+
+// boolOverride 布尔配置项
+func boolOverride(name, key string, field func(*types.Config) *bool) envOverride {
+	return envOverride{
+		name: name,
+		key:  key,
+		get:  func(c *types.Config) string { return strconv.FormatBool(*field(c)) },
+		set: func(c *types.Config, value string) error {
+			parsed, err := strconv.ParseBool(value)
+			if err != nil {
+				return fmt.Errorf("需要 true 或 false")
+			}
+			*field(c) = parsed
+			return nil
+		},
+	}
+}
+
+// appliedOverride 已生效的环境变量覆盖，保存配置文件时写回文件中的原值
+type appliedOverride struct {
+	override  *envOverride
+	fileValue string
+}
+
+// EnvOverride 生效的环境变量覆盖（不含值，值可能是密钥）
+type EnvOverride struct {
+	EnvVar string `json:"env_var"`
+	Key    string `json:"key"`
+}
+
+// applyEnvOverrides 用环境变量覆盖配置项，环境变量优先于配置文件；值无法解析时返回指出变量名和配置项的错误
+func applyEnvOverrides(config *types.Config, lookup func(string) (string, bool)) ([]appliedOverride, error) {
+	var applied []appliedOverride
+	for i := range envOverrides {
+		override := &envOverrides[i]
+		value, ok := lookup(EnvPrefix + override.name)
+		if !ok {
+			continue
+		}
+		value = strings.TrimSpace(value)
+		fileValue := override.get(config)
+		if err := override.set(config, value); err != nil {
+			return nil, fmt.Errorf("环境变量 %s%s 的值 %q 无效（覆盖 %s）: %w", EnvPrefix, override.name, value, override.key, err)
+		}
+		applied = append(applied, appliedOverride{override: override, fileValue: fileValue})
+	}
+	return applied, nil
+}
+
+// fileView 返回写入配置文件的配置：被环境变量覆盖的配置项恢复为文件中的原值，环境变量的值不会被持久化
+func fileView(config *types.Config, applied []appliedOverride) *types.Config {
+	if len(applied) == 0 {
+		return config
+	}
+	copied := *config
+	for _, item := range applied {
+		_ = item.override.set(&copied, item.fileValue)
+	}
+	return &copied
+}
+
+// EnvOverrides 返回当前生效的环境变量覆盖
+func (m *ConfigManager) EnvOverrides() []EnvOverride {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	result := make([]EnvOverride, 0, len(m.overrides))
+	for _, item := range m.overrides {
+		result = append(result, EnvOverride{EnvVar: EnvPrefix + item.override.name, Key: item.override.key})
+	}
+	return result
+}
+
+// lookupEnv 读取环境变量，值为空时视为未设置
+func lookupEnv(name string) (string, bool) {
+	value, ok := os.LookupEnv(name)
+	return value, ok && strings.TrimSpace(value) != ""
+}
@@ -0,0 +1,49 @@
+package config
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestConfigManager_EnvOverrides(t *testing.T) {
+	configPath := filepath.Join(t.TempDir(), "config.yaml")
+	mgr := NewConfigManager(configPath)
+	_ = mgr.Save(&types.Config{Server: types.ServerConfig{Host: "localhost", Port: 8080, Web: types.WebConfig{Password: "from-file"}}})
+
+	t.Setenv("LLM_GATEWAY_SERVER_PORT", "9090")
+	t.Setenv("LLM_GATEWAY_WEB_PASSWORD", "from-env")
+	t.Setenv("LLM_GATEWAY_LOG_LEVEL", "")
+
+	config, err := mgr.Load()
+	if err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	if config.Server.Port != 9090 || config.Server.Web.Password != "from-env" || config.Server.Host != "localhost" {
+		t.Errorf("server = %+v, want port and password from the environment", config.Server)
+	}
+	if overrides := mgr.EnvOverrides(); len(overrides) != 2 || overrides[0].Key != "server.port" {
+		t.Errorf("EnvOverrides() = %+v, want server.port and server.web.password", overrides)
+	}
+
+	// 保存时写回文件中的原值，环境变量的值（可能是密钥）不会落盘
+	if err := mgr.Save(config); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	data, _ := os.ReadFile(configPath)
+	if strings.Contains(string(data), "from-env") || !strings.Contains(string(data), "port: 8080") {
+		t.Errorf("saved file contains overridden values:\n%s", data)
+	}
+	if config.Server.Port != 9090 {
+		t.Errorf("in-memory port = %d after Save(), want 9090", config.Server.Port)
+	}
+
+	// 无法解析的值指出变量名和配置项
+	t.Setenv("LLM_GATEWAY_SERVER_PORT", "eighty")
+	if _, err := mgr.Load(); err == nil || !strings.Contains(err.Error(), "LLM_GATEWAY_SERVER_PORT") || !strings.Contains(err.Error(), "server.port") {
+		t.Errorf("Load() error = %v, want the variable and key named", err)
+	}
+}
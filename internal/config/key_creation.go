@@ -0,0 +1,91 @@
+package config
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// MaxKeysPerUserEnvVar 覆盖 server.web.key_creation.max_keys_per_user 的环境变量
+const MaxKeysPerUserEnvVar = "MAX_KEYS_PER_USER"
+
+// ErrKeyLimitReached 创建者的Key数量已达到自助创建的上限
+var ErrKeyLimitReached = errors.New("已达到每个用户可以创建的Key数量上限")
+
+// validateKeyCreation 验证自助创建Key的设置
+func validateKeyCreation(config *types.KeyCreationConfig) error {
+	if config.MaxKeysPerUser < 0 {
+		return fmt.Errorf("无效的 server.web.key_creation.max_keys_per_user: %d（0表示不限制）", config.MaxKeysPerUser)
+	}
+	return nil
+}
+
+// MaxKeysPerUser 返回每个用户最多创建的Key数量，设置了 MAX_KEYS_PER_USER 时以环境变量为准，0表示不限制
+func MaxKeysPerUser(config *types.KeyCreationConfig) int {
+	if value := strings.TrimSpace(os.Getenv(MaxKeysPerUserEnvVar)); value != "" {
+		if limit, err := strconv.Atoi(value); err == nil && limit >= 0 {
+			return limit
+		}
+	}
+	return config.MaxKeysPerUser
+}
+
+// SetKeyCreationConfig 验证并保存自助创建Key的设置
+func (m *ConfigManager) SetKeyCreationConfig(settings types.KeyCreationConfig) error {
+	if err := validateKeyCreation(&settings); err != nil {
+		return err
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+	next.Server.Web.KeyCreation = settings
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// CountKeysCreatedBy 统计用户创建的Key数量（包括停用和待审批的Key）
+func (m *ConfigManager) CountKeysCreatedBy(user string) int {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return 0
+	}
+	return countKeysCreatedBy(m.config, user)
+}
+
+// countKeysCreatedBy 统计配置中由 user 创建的Key数量
+func countKeysCreatedBy(config *types.Config, user string) int {
+	count := 0
+	for _, key := range config.GatewayKeys {
+		if key.CreatedBy == user {
+			count++
+		}
+	}
+	return count
+}
+
+// ApproveGatewayKey 批准待审批的Key：启用并记录批准人
+func (m *ConfigManager) ApproveGatewayKey(keyID, approver string, now time.Time) error {
+	return m.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		if !key.PendingApproval {
+			return fmt.Errorf("该Key不在待审批状态: %s", keyID)
+		}
+		key.PendingApproval = false
+		key.Status = "active"
+		key.ApprovedBy = approver
+		key.ApprovedAt = &now
+		return nil
+	})
+}
@@ -0,0 +1,49 @@
+package config
+
+import (
+	"fmt"
+	"regexp"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validateModeration 验证内容审核配置
+func validateModeration(moderation *types.ModerationConfig) error {
+	names := make(map[string]bool, len(moderation.Policies))
+	for i, policy := range moderation.Policies {
+		if policy.Name == "" {
+			return fmt.Errorf("proxy.moderation.policies[%d]: 名称不能为空", i)
+		}
+		if names[policy.Name] {
+			return fmt.Errorf("proxy.moderation.policies 中重复的名称: %s", policy.Name)
+		}
+		names[policy.Name] = true
+
+		if len(policy.Rules) == 0 && !policy.UseModel {
+			return fmt.Errorf("审核策略 %s: 至少需要一条规则或开启 use_model", policy.Name)
+		}
+		for j, rule := range policy.Rules {
+			if rule.Code == "" {
+				return fmt.Errorf("审核策略 %s: rules[%d] 的 code 不能为空", policy.Name, j)
+			}
+			if len(rule.Blocklist) == 0 && len(rule.Patterns) == 0 {
+				return fmt.Errorf("审核策略 %s: 规则 %s 至少需要一个关键词或正则表达式", policy.Name, rule.Code)
+			}
+			for _, word := range rule.Blocklist {
+				if word == "" {
+					return fmt.Errorf("审核策略 %s: 规则 %s 的关键词不能为空", policy.Name, rule.Code)
+				}
+			}
+			for _, pattern := range rule.Patterns {
+				if _, err := regexp.Compile(pattern); err != nil {
+					return fmt.Errorf("审核策略 %s: 无效的正则表达式 %q: %w", policy.Name, pattern, err)
+				}
+			}
+		}
+	}
+
+	if moderation.Model.TimeoutSeconds < 0 {
+		return fmt.Errorf("proxy.moderation.model.timeout_seconds 不能为负数")
+	}
+	return nil
+}
@@ -0,0 +1,98 @@
+package config
+
+import (
+	"fmt"
+	"net/url"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// notificationEvents 可订阅的通知事件类型
+var notificationEvents = map[string]bool{
+	"cost_threshold":       true,
+	"error_rate":           true,
+	"health_change":        true,
+	"canary_failure":       true,
+	"quota_warning":        true,
+	"account_disabled":     true,
+	"key_pending_approval": true,
+	"error_class_rate":     true,
+}
+
+// validateNotifications 验证告警通知配置
+func validateNotifications(config *types.NotificationConfig) error {
+	if config.DailyCostUSD < 0 || config.KeyDailyCostUSD < 0 {
+		return fmt.Errorf("notifications 的费用阈值不能为负数")
+	}
+	if config.ErrorRateThreshold < 0 || config.ErrorRateThreshold > 1 {
+		return fmt.Errorf("无效的 notifications.error_rate_threshold: %v（范围 0-1）", config.ErrorRateThreshold)
+	}
+	if config.ErrorRateWindowMinutes < 0 || config.ErrorRateMinRequests < 0 || config.MaxAttempts < 0 {
+		return fmt.Errorf("notifications 的窗口、最小请求数和最大尝试次数不能为负数")
+	}
+	for _, threshold := range config.QuotaWarningThresholds {
+		if threshold <= 0 || threshold > 1 {
+			return fmt.Errorf("无效的 notifications.quota_warning_thresholds: %v（范围 0-1）", threshold)
+		}
+	}
+	for i, rule := range config.ErrorClassRules {
+		class, ok := types.ParseErrorClass(string(rule.Class))
+		if !ok {
+			return fmt.Errorf("notifications.error_class_rules[%d]: 未知的错误分类 %q", i, rule.Class)
+		}
+		config.ErrorClassRules[i].Class = class
+		if rule.Threshold <= 0 || rule.Threshold > 1 {
+			return fmt.Errorf("notifications.error_class_rules[%d]: 无效的 threshold %v（范围 0-1）", i, rule.Threshold)
+		}
+		if rule.WindowMinutes < 0 || rule.MinRequests < 0 {
+			return fmt.Errorf("notifications.error_class_rules[%d]: 窗口和最小请求数不能为负数", i)
+		}
+	}
+
+	names := make(map[string]bool, len(config.Webhooks))
+	for i, webhook := range config.Webhooks {
+		if webhook.Name == "" {
+			return fmt.Errorf("通知Webhook %d: 名称不能为空", i)
+		}
+		if names[webhook.Name] {
+			return fmt.Errorf("通知Webhook名称重复: %s", webhook.Name)
+		}
+		names[webhook.Name] = true
+
+		parsed, err := url.Parse(webhook.URL)
+		if err != nil || (parsed.Scheme != "http" && parsed.Scheme != "https") || parsed.Host == "" {
+			return fmt.Errorf("通知Webhook %s: 无效的URL %q", webhook.Name, webhook.URL)
+		}
+		if webhook.Format != "" && webhook.Format != "generic" && webhook.Format != "slack" {
+			return fmt.Errorf("通知Webhook %s: 不支持的格式 %s（generic 或 slack）", webhook.Name, webhook.Format)
+		}
+		for _, event := range webhook.Events {
+			if !notificationEvents[event] {
+				return fmt.Errorf("通知Webhook %s: 未知的事件类型 %s", webhook.Name, event)
+			}
+		}
+	}
+	return nil
+}
+
+// SetNotificationConfig 验证并保存告警通知配置（通知服务下次检查时使用新配置）
+func (m *ConfigManager) SetNotificationConfig(notifications types.NotificationConfig) error {
+	if err := validateNotifications(&notifications); err != nil {
+		return err
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	// 投递记录目录只能通过配置文件修改
+	notifications.Dir = next.Notifications.Dir
+	next.Notifications = notifications
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
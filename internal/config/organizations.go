@@ -0,0 +1,195 @@
+package config
+
+import (
+	"fmt"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validateOrganizations 验证组织配置：ID唯一、成员是已有的Web用户，Key和上游账号引用的组织必须存在
+func validateOrganizations(config *types.Config) error {
+	users := map[string]bool{BuiltinAdminUser: true}
+	for _, user := range config.Server.Web.Users {
+		users[user.Username] = true
+	}
+
+	ids := make(map[string]bool, len(config.Organizations))
+	for i, org := range config.Organizations {
+		if org.ID == "" {
+			return fmt.Errorf("organizations[%d]: ID不能为空", i)
+		}
+		if ids[org.ID] {
+			return fmt.Errorf("organizations 中重复的ID: %s", org.ID)
+		}
+		ids[org.ID] = true
+		if org.Name == "" {
+			return fmt.Errorf("组织 %s: 名称不能为空", org.ID)
+		}
+
+		members := make(map[string]bool, len(org.Members))
+		for _, member := range org.Members {
+			if !users[member] {
+				return fmt.Errorf("组织 %s: 成员 %s 不是Web用户", org.ID, member)
+			}
+			if members[member] {
+				return fmt.Errorf("组织 %s: 重复的成员 %s", org.ID, member)
+			}
+			members[member] = true
+		}
+	}
+
+	for _, key := range config.GatewayKeys {
+		if key.OrgID != "" && !ids[key.OrgID] {
+			return fmt.Errorf("gateway API Key %s: 组织不存在: %s", key.ID, key.OrgID)
+		}
+	}
+	for _, account := range config.UpstreamAccounts {
+		if account.OrgID != "" && !ids[account.OrgID] {
+			return fmt.Errorf("上游账号 %s: 组织不存在: %s", account.ID, account.OrgID)
+		}
+	}
+	return nil
+}
+
+// copyOrganization 复制组织，避免外部修改内部数据
+func copyOrganization(org types.Organization) *types.Organization {
+	org.Members = append([]string{}, org.Members...)
+	return &org
+}
+
+// ===== Organizations CRUD =====
+
+// CreateOrganization 创建组织
+func (m *ConfigManager) CreateOrganization(org *types.Organization) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	next.Organizations = append(next.Organizations, *copyOrganization(*org))
+	if err := validateOrganizations(next); err != nil {
+		return err
+	}
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// GetOrganization 获取组织
+func (m *ConfigManager) GetOrganization(id string) (*types.Organization, error) {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return nil, fmt.Errorf("配置未加载")
+	}
+
+	for _, org := range m.config.Organizations {
+		if org.ID == id {
+			return copyOrganization(org), nil
+		}
+	}
+	return nil, fmt.Errorf("组织不存在: %s", id)
+}
+
+// ListOrganizations 列出所有组织
+func (m *ConfigManager) ListOrganizations() []*types.Organization {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return []*types.Organization{}
+	}
+
+	orgs := make([]*types.Organization, len(m.config.Organizations))
+	for i, org := range m.config.Organizations {
+		orgs[i] = copyOrganization(org)
+	}
+	return orgs
+}
+
+// UpdateOrganization 更新组织（名称、成员），更新后重新验证
+func (m *ConfigManager) UpdateOrganization(id string, updater func(*types.Organization) error) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, org := range next.Organizations {
+		if org.ID != id {
+			continue
+		}
+		updated := copyOrganization(org)
+		if err := updater(updated); err != nil {
+			return err
+		}
+		updated.ID = id
+
+		next.Organizations[i] = *updated
+		if err := validateOrganizations(next); err != nil {
+			return err
+		}
+
+		// 自动保存到文件
+		return m.saveUnsafe(next)
+	}
+
+	return fmt.Errorf("组织不存在: %s", id)
+}
+
+// DeleteOrganization 删除组织，仍有Key或上游账号归属该组织时拒绝删除
+func (m *ConfigManager) DeleteOrganization(id string) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	keys, accounts := 0, 0
+	for _, key := range next.GatewayKeys {
+		if key.OrgID == id {
+			keys++
+		}
+	}
+	for _, account := range next.UpstreamAccounts {
+		if account.OrgID == id {
+			accounts++
+		}
+	}
+	if keys > 0 || accounts > 0 {
+		return fmt.Errorf("组织 %s 仍有 %d 个Gateway API Key和 %d 个上游账号", id, keys, accounts)
+	}
+
+	for i, org := range next.Organizations {
+		if org.ID == id {
+			next.Organizations = append(next.Organizations[:i], next.Organizations[i+1:]...)
+
+			// 自动保存到文件
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("组织不存在: %s", id)
+}
+
+// removeOrganizationMember 从配置副本的所有组织中移除用户
+func removeOrganizationMember(config *types.Config, username string) {
+	for i := range config.Organizations {
+		org := &config.Organizations[i]
+		members := make([]string, 0, len(org.Members))
+		for _, member := range org.Members {
+			if member != username {
+				members = append(members, member)
+			}
+		}
+		org.Members = members
+	}
+}
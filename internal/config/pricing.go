@@ -0,0 +1,47 @@
+package config
+
+import (
+	"fmt"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validatePricing 验证自定义模型价格
+func validatePricing(config *types.PricingConfig) error {
+	seen := make(map[string]bool, len(config.Models))
+	for i, model := range config.Models {
+		if model.Model == "" {
+			return fmt.Errorf("pricing.models[%d]: 模型名不能为空", i)
+		}
+		if model.Input < 0 || model.Output < 0 || model.CacheRead < 0 || model.CacheWrite < 0 {
+			return fmt.Errorf("pricing.models[%d] %s: 价格不能为负数", i, model.Model)
+		}
+
+		key := string(model.Provider) + "/" + model.Model
+		if seen[key] {
+			return fmt.Errorf("pricing.models 中重复的价格: %s", key)
+		}
+		seen[key] = true
+	}
+	return nil
+}
+
+// SetPricing 验证并保存自定义模型价格（调用方负责让计费模块使用新价格）
+func (m *ConfigManager) SetPricing(pricing types.PricingConfig) error {
+	if err := validatePricing(&pricing); err != nil {
+		return err
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	next.Pricing = pricing
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
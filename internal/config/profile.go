@@ -0,0 +1,114 @@
+package config
+
+import (
+	"fmt"
+	"os"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// ProfileEnvVar 选择运行环境配置档的环境变量
+const ProfileEnvVar = "GATEWAY_ENV"
+
+// defaultWebPassword 默认的Web管理密码
+const defaultWebPassword = "admin123"
+
+// Profile 运行环境配置档（dev/staging/prod），决定各环境下的默认值与安全约束
+type Profile struct {
+	Name                 string   `json:"name"`
+	LogLevel             string   `json:"log_level"`              // 配置文件未指定日志级别时使用
+	CORSAllowedOrigins   []string `json:"cors_allowed_origins"`   // 配置文件未指定时使用，空表示不允许跨域
+	AllowInsecureSecrets bool     `json:"allow_insecure_secrets"` // 是否容忍默认密码等不安全凭证
+}
+
+// builtinProfiles 内置配置档
+var builtinProfiles = map[string]Profile{
+	"dev": {
+		Name:                 "dev",
+		LogLevel:             "debug",
+		CORSAllowedOrigins:   []string{"*"},
+		AllowInsecureSecrets: true,
+	},
+	"staging": {
+		Name:                 "staging",
+		LogLevel:             "info",
+		CORSAllowedOrigins:   []string{"*"},
+		AllowInsecureSecrets: false,
+	},
+	"prod": {
+		Name:                 "prod",
+		LogLevel:             "info",
+		CORSAllowedOrigins:   nil,
+		AllowInsecureSecrets: false,
+	},
+}
+
+// profileAliases 配置档别名
+var profileAliases = map[string]string{
+	"":            "dev",
+	"development": "dev",
+	"stage":       "staging",
+	"production":  "prod",
+}
+
+// LookupProfile 按名称查找配置档
+func LookupProfile(name string) (*Profile, error) {
+	name = strings.ToLower(strings.TrimSpace(name))
+	if alias, ok := profileAliases[name]; ok {
+		name = alias
+	}
+
+	profile, ok := builtinProfiles[name]
+	if !ok {
+		return nil, fmt.Errorf("未知的运行环境: %s（可选 dev、staging、prod）", name)
+	}
+	return &profile, nil
+}
+
+// ProfileFromEnv 根据 GATEWAY_ENV 选择配置档，未设置时使用 dev
+func ProfileFromEnv() (*Profile, error) {
+	return LookupProfile(os.Getenv(ProfileEnvVar))
+}
+
+// ResolveLogLevel 获取生效的日志级别（配置文件优先）
+func (p *Profile) ResolveLogLevel(config *types.Config) string {
+	if config.Logging.Level != "" {
+		return config.Logging.Level
+	}
+	return p.LogLevel
+}
+
+// ResolveCORSOrigins 获取生效的CORS来源列表（配置文件优先）
+func (p *Profile) ResolveCORSOrigins(config *types.Config) []string {
+	if config.Server.CORSAllowedOrigins != nil {
+		return config.Server.CORSAllowedOrigins
+	}
+	return p.CORSAllowedOrigins
+}
+
+// Validate 启动时按配置档校验配置
+func (p *Profile) Validate(config *types.Config) error {
+	if p.AllowInsecureSecrets {
+		return nil
+	}
+
+	if config.Server.Web.Enabled {
+		if config.Server.Web.Password == defaultWebPassword {
+			return fmt.Errorf("%s 环境不允许使用默认的Web管理密码，请修改 server.web.password", p.Name)
+		}
+		if len(config.Server.Web.Password) < 12 {
+			return fmt.Errorf("%s 环境要求Web管理密码至少12位", p.Name)
+		}
+	}
+
+	if p.Name == "prod" {
+		for _, origin := range p.ResolveCORSOrigins(config) {
+			if origin == "*" {
+				return fmt.Errorf("prod 环境不允许 CORS 通配符来源，请在 server.cors_allowed_origins 中列出具体域名")
+			}
+		}
+	}
+
+	return nil
+}
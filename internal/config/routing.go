@@ -0,0 +1,25 @@
+package config
+
+import (
+	"fmt"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validateRouting 验证负载均衡策略和自动切换参数
+func validateRouting(config *types.RoutingConfig) error {
+	switch config.Strategy {
+	case "", "round_robin", "random", "health_first", "fastest", "least_connections":
+	default:
+		return fmt.Errorf("无效的 routing.strategy: %s", config.Strategy)
+	}
+
+	autopilot := &config.Autopilot
+	if autopilot.IntervalSeconds < 0 || autopilot.WindowMinutes < 0 || autopilot.LatencyThresholdMs < 0 || autopilot.MinHoldSeconds < 0 {
+		return fmt.Errorf("routing.autopilot 的 interval_seconds、window_minutes、latency_threshold_ms 和 min_hold_seconds 不能为负数")
+	}
+	if autopilot.SpikeFactor != 0 && autopilot.SpikeFactor <= 1 {
+		return fmt.Errorf("无效的 routing.autopilot.spike_factor: %g（必须大于1，0表示使用默认值）", autopilot.SpikeFactor)
+	}
+	return nil
+}
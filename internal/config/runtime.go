@@ -0,0 +1,104 @@
+package config
+
+import (
+	"fmt"
+	"math"
+	"os"
+	"runtime"
+	"runtime/debug"
+	"strconv"
+	"sync/atomic"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// RuntimeInfo 当前生效的Go运行时配置
+type RuntimeInfo struct {
+	GoVersion     string `json:"go_version"`
+	NumCPU        int    `json:"num_cpu"`
+	MaxProcs      int    `json:"max_procs"`
+	GCPercent     int    `json:"gc_percent"`      // -1 表示GC已关闭
+	MemoryLimitMB int    `json:"memory_limit_mb"` // 0 表示未设置上限
+}
+
+// gcPercent 当前生效的GC百分比。Go只能通过 SetGCPercent 的返回值读取旧值，读取也要写入一次，
+// 因此启动时按 GOGC 环境变量得到初始值，之后由 ApplyRuntime 在设置时更新
+var gcPercent atomic.Int32
+
+func init() {
+	gcPercent.Store(int32(envGCPercent(os.Getenv("GOGC"))))
+}
+
+// envGCPercent 按运行时的规则解析 GOGC：off 表示关闭GC（-1），无法解析时为默认值100
+func envGCPercent(value string) int {
+	if value == "off" {
+		return -1
+	}
+	if percent, err := strconv.Atoi(value); err == nil {
+		return percent
+	}
+	return 100
+}
+
+// validateRuntime 验证运行时调优配置
+func validateRuntime(config *types.RuntimeConfig) error {
+	if config.MaxProcs < 0 {
+		return fmt.Errorf("无效的 runtime.max_procs: %d（0表示不修改）", config.MaxProcs)
+	}
+	if config.GCPercent != nil && *config.GCPercent < -1 {
+		return fmt.Errorf("无效的 runtime.gc_percent: %d（-1表示关闭GC）", *config.GCPercent)
+	}
+	if config.MemoryLimitMB < 0 {
+		return fmt.Errorf("无效的 runtime.memory_limit_mb: %d（0表示不修改）", config.MemoryLimitMB)
+	}
+	return nil
+}
+
+// ApplyRuntime 按 runtime 配置调整Go运行时参数，返回调整后生效的配置
+func (m *ConfigManager) ApplyRuntime() (RuntimeInfo, error) {
+	config := &m.Get().Runtime
+	if err := validateRuntime(config); err != nil {
+		return CurrentRuntime(), err
+	}
+
+	if config.MaxProcs > 0 {
+		runtime.GOMAXPROCS(config.MaxProcs)
+	}
+	if config.GCPercent != nil {
+		debug.SetGCPercent(*config.GCPercent)
+		gcPercent.Store(int32(*config.GCPercent))
+	}
+	if config.MemoryLimitMB > 0 {
+		debug.SetMemoryLimit(int64(config.MemoryLimitMB) << 20)
+	}
+	return CurrentRuntime(), nil
+}
+
+// CurrentRuntime 读取当前生效的Go运行时配置
+func CurrentRuntime() RuntimeInfo {
+	memoryLimitMB := 0
+	if limit := debug.SetMemoryLimit(-1); limit != math.MaxInt64 {
+		memoryLimitMB = int(limit >> 20)
+	}
+
+	return RuntimeInfo{
+		GoVersion:     runtime.Version(),
+		NumCPU:        runtime.NumCPU(),
+		MaxProcs:      runtime.GOMAXPROCS(0),
+		GCPercent:     int(gcPercent.Load()),
+		MemoryLimitMB: memoryLimitMB,
+	}
+}
+
+// String 返回便于日志输出的运行时配置摘要
+func (info RuntimeInfo) String() string {
+	gc := fmt.Sprintf("%d%%", info.GCPercent)
+	if info.GCPercent < 0 {
+		gc = "off"
+	}
+	memoryLimit := "none"
+	if info.MemoryLimitMB > 0 {
+		memoryLimit = fmt.Sprintf("%dMB", info.MemoryLimitMB)
+	}
+	return fmt.Sprintf("%s, GOMAXPROCS=%d/%d CPU, GOGC=%s, GOMEMLIMIT=%s", info.GoVersion, info.MaxProcs, info.NumCPU, gc, memoryLimit)
+}
@@ -0,0 +1,147 @@
+package config
+
+import (
+	"crypto/rand"
+	"encoding/base64"
+	"encoding/hex"
+	"fmt"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// validateServiceAccounts 验证服务账号列表
+func validateServiceAccounts(accounts []types.ServiceAccount) error {
+	seen := make(map[string]bool, len(accounts))
+	for i, account := range accounts {
+		if account.ClientID == "" {
+			return fmt.Errorf("server.web.service_accounts[%d]: client_id 不能为空", i)
+		}
+		if seen[account.ClientID] {
+			return fmt.Errorf("server.web.service_accounts 中重复的 client_id: %s", account.ClientID)
+		}
+		seen[account.ClientID] = true
+
+		if types.RoleRank(account.Role) == 0 {
+			return fmt.Errorf("服务账号 %s 的角色无效: %s（可选 admin、operator、viewer）", account.ClientID, account.Role)
+		}
+		if account.SecretHash == "" || account.Salt == "" {
+			return fmt.Errorf("服务账号 %s 未设置密钥", account.ClientID)
+		}
+	}
+	return nil
+}
+
+// AuthenticateServiceAccount 验证 client_id 和 client_secret，返回服务账号的角色
+func (m *ConfigManager) AuthenticateServiceAccount(clientID, secret string) (string, bool) {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil || clientID == "" {
+		return "", false
+	}
+	for _, account := range m.config.Server.Web.ServiceAccounts {
+		if account.ClientID == clientID {
+			return account.Role, utils.SecureEqual(hashSecret(account.Salt, secret), account.SecretHash)
+		}
+	}
+	return "", false
+}
+
+// ServiceAccountRole 返回服务账号当前的角色，账号已删除时返回 ok=false（用于校验已签发的令牌）
+func (m *ConfigManager) ServiceAccountRole(clientID string) (string, bool) {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return "", false
+	}
+	for _, account := range m.config.Server.Web.ServiceAccounts {
+		if account.ClientID == clientID {
+			return account.Role, true
+		}
+	}
+	return "", false
+}
+
+// ListServiceAccounts 列出服务账号
+func (m *ConfigManager) ListServiceAccounts() []types.ServiceAccount {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return []types.ServiceAccount{}
+	}
+	return append([]types.ServiceAccount{}, m.config.Server.Web.ServiceAccounts...)
+}
+
+// CreateServiceAccount 创建服务账号，返回生成的账号和 client_secret（只在创建时返回，配置中只保存哈希）
+func (m *ConfigManager) CreateServiceAccount(name, role string) (*types.ServiceAccount, string, error) {
+	if types.RoleRank(role) == 0 {
+		return nil, "", fmt.Errorf("角色无效: %s（可选 admin、operator、viewer）", role)
+	}
+	idBytes := make([]byte, 8)
+	secretBytes := make([]byte, 32)
+	if _, err := rand.Read(idBytes); err != nil {
+		return nil, "", fmt.Errorf("生成 client_id 失败: %w", err)
+	}
+	if _, err := rand.Read(secretBytes); err != nil {
+		return nil, "", fmt.Errorf("生成 client_secret 失败: %w", err)
+	}
+	salt, err := newSalt()
+	if err != nil {
+		return nil, "", err
+	}
+	secret := base64.RawURLEncoding.EncodeToString(secretBytes)
+	account := types.ServiceAccount{
+		ClientID:   "sa_" + hex.EncodeToString(idBytes),
+		Name:       name,
+		Role:       role,
+		SecretHash: hashSecret(salt, secret),
+		Salt:       salt,
+		CreatedAt:  time.Now(),
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return nil, "", fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	accounts := append(append([]types.ServiceAccount{}, next.Server.Web.ServiceAccounts...), account)
+	if err := validateServiceAccounts(accounts); err != nil {
+		return nil, "", err
+	}
+	next.Server.Web.ServiceAccounts = accounts
+
+	// 自动保存到文件
+	if err := m.saveUnsafe(next); err != nil {
+		return nil, "", err
+	}
+	return &account, secret, nil
+}
+
+// DeleteServiceAccount 删除服务账号，已签发的令牌随之失效
+func (m *ConfigManager) DeleteServiceAccount(clientID string) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, account := range next.Server.Web.ServiceAccounts {
+		if account.ClientID == clientID {
+			next.Server.Web.ServiceAccounts = append(next.Server.Web.ServiceAccounts[:i], next.Server.Web.ServiceAccounts[i+1:]...)
+
+			// 自动保存到文件
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("服务账号不存在: %s", clientID)
+}
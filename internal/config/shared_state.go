@@ -0,0 +1,25 @@
+package config
+
+import (
+	"fmt"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// validateSharedState 验证多实例共享状态配置
+func validateSharedState(config *types.SharedStateConfig) error {
+	switch config.Backend {
+	case "", "memory":
+	case "redis":
+		if config.Redis.Address == "" {
+			return fmt.Errorf("shared_state.backend 为 redis 时必须配置 shared_state.redis.address")
+		}
+	default:
+		return fmt.Errorf("无效的 shared_state.backend: %s（可选 memory 或 redis）", config.Backend)
+	}
+
+	if config.Redis.DB < 0 || config.Redis.TimeoutMs < 0 || config.SyncIntervalSeconds < 0 {
+		return fmt.Errorf("shared_state 的 redis.db、redis.timeout_ms 和 sync_interval_seconds 不能为负数")
+	}
+	return nil
+}
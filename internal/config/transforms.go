@@ -0,0 +1,61 @@
+package config
+
+import (
+	"fmt"
+	"regexp"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// protectedRequestFields 请求转换器不能删除的参数
+var protectedRequestFields = map[string]bool{"model": true, "messages": true, "stream": true, "max_tokens": true}
+
+// validateTransforms 验证请求/响应转换器配置
+func validateTransforms(transforms []types.TransformConfig) error {
+	names := make(map[string]bool, len(transforms))
+	for i, transform := range transforms {
+		if transform.Name == "" {
+			return fmt.Errorf("proxy.transforms[%d]: 名称不能为空", i)
+		}
+		if names[transform.Name] {
+			return fmt.Errorf("proxy.transforms 中重复的名称: %s", transform.Name)
+		}
+		names[transform.Name] = true
+
+		switch transform.Type {
+		case "system_prompt":
+			if transform.Prompt == "" {
+				return fmt.Errorf("转换器 %s: prompt 不能为空", transform.Name)
+			}
+			if transform.Position != "" && transform.Position != "prepend" && transform.Position != "append" {
+				return fmt.Errorf("转换器 %s: 无效的 position: %s（可选 prepend、append）", transform.Name, transform.Position)
+			}
+		case "strip_fields":
+			if len(transform.Fields) == 0 {
+				return fmt.Errorf("转换器 %s: fields 不能为空", transform.Name)
+			}
+			for _, field := range transform.Fields {
+				if protectedRequestFields[field] {
+					return fmt.Errorf("转换器 %s: 不能删除参数 %s", transform.Name, field)
+				}
+			}
+		case "mask_pii":
+		case "redact":
+			if len(transform.Patterns) == 0 {
+				return fmt.Errorf("转换器 %s: patterns 不能为空", transform.Name)
+			}
+			for _, pattern := range transform.Patterns {
+				if _, err := regexp.Compile(pattern); err != nil {
+					return fmt.Errorf("转换器 %s: 无效的正则表达式 %q: %w", transform.Name, pattern, err)
+				}
+			}
+		default:
+			return fmt.Errorf("转换器 %s: 无效的类型: %s（可选 system_prompt、strip_fields、mask_pii、redact）", transform.Name, transform.Type)
+		}
+
+		if transform.Apply != "" && transform.Apply != "request" && transform.Apply != "response" && transform.Apply != "both" {
+			return fmt.Errorf("转换器 %s: 无效的 apply: %s（可选 request、response、both）", transform.Name, transform.Apply)
+		}
+	}
+	return nil
+}
@@ -0,0 +1,210 @@
+package config
+
+import (
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+const (
+	// BuiltinAdminUser 内置管理员用户名，密码为 server.web.password
+	BuiltinAdminUser = "admin"
+
+	// passwordIterations 新设置的密码使用的 PBKDF2 迭代次数，与哈希一起保存在用户上，调整后不影响已有密码
+	passwordIterations = 210000
+)
+
+// validateWebUsers 验证Web用户列表
+func validateWebUsers(users []types.WebUser) error {
+	seen := make(map[string]bool, len(users))
+	for i, user := range users {
+		if user.Username == "" {
+			return fmt.Errorf("server.web.users[%d]: 用户名不能为空", i)
+		}
+		if user.Username == BuiltinAdminUser {
+			return fmt.Errorf("server.web.users[%d]: 用户名 %s 保留给内置管理员", i, BuiltinAdminUser)
+		}
+		if seen[user.Username] {
+			return fmt.Errorf("server.web.users 中重复的用户名: %s", user.Username)
+		}
+		seen[user.Username] = true
+
+		if types.RoleRank(user.Role) == 0 {
+			return fmt.Errorf("用户 %s 的角色无效: %s（可选 admin、operator、viewer）", user.Username, user.Role)
+		}
+		if user.PasswordHash == "" || user.Salt == "" {
+			return fmt.Errorf("用户 %s 未设置密码", user.Username)
+		}
+	}
+	return nil
+}
+
+// AuthenticateWebUser 验证用户名和密码，返回用户角色；用户名为空或 admin 时验证内置管理员密码。
+// 早期版本保存的 SHA-256 密码哈希在验证成功后升级为 PBKDF2
+func (m *ConfigManager) AuthenticateWebUser(username, password string) (string, bool) {
+	config := m.Get()
+	if config == nil {
+		return "", false
+	}
+
+	if username == "" || username == BuiltinAdminUser {
+		return types.RoleAdmin, utils.SecureEqual(password, config.Server.Web.Password)
+	}
+	for _, user := range config.Server.Web.Users {
+		if user.Username != username {
+			continue
+		}
+		if !utils.SecureEqual(hashPassword(user.Salt, password, user.PasswordIterations), user.PasswordHash) {
+			return user.Role, false
+		}
+		if user.PasswordIterations <= 0 {
+			if err := m.UpdateWebUser(username, "", password); err != nil {
+				logger.Warn("升级用户 %s 的密码哈希失败: %v", username, err)
+			}
+		}
+		return user.Role, true
+	}
+	return "", false
+}
+
+// ListWebUsers 列出Web用户（不含内置管理员）
+func (m *ConfigManager) ListWebUsers() []types.WebUser {
+	m.mutex.RLock()
+	defer m.mutex.RUnlock()
+
+	if m.config == nil {
+		return []types.WebUser{}
+	}
+	return append([]types.WebUser{}, m.config.Server.Web.Users...)
+}
+
+// CreateWebUser 创建Web用户
+func (m *ConfigManager) CreateWebUser(username, role, password string) error {
+	if password == "" {
+		return fmt.Errorf("密码不能为空")
+	}
+	salt, err := newSalt()
+	if err != nil {
+		return err
+	}
+	user := types.WebUser{
+		Username:           username,
+		Role:               role,
+		PasswordHash:       hashPassword(salt, password, passwordIterations),
+		Salt:               salt,
+		PasswordIterations: passwordIterations,
+		CreatedAt:          time.Now(),
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	users := append(append([]types.WebUser{}, next.Server.Web.Users...), user)
+	if err := validateWebUsers(users); err != nil {
+		return err
+	}
+	next.Server.Web.Users = users
+
+	// 自动保存到文件
+	return m.saveUnsafe(next)
+}
+
+// UpdateWebUser 修改Web用户的角色或密码，空字符串表示不修改
+func (m *ConfigManager) UpdateWebUser(username, role, password string) error {
+	if role != "" && types.RoleRank(role) == 0 {
+		return fmt.Errorf("角色无效: %s（可选 admin、operator、viewer）", role)
+	}
+	var salt, passwordHash string
+	if password != "" {
+		var err error
+		if salt, err = newSalt(); err != nil {
+			return err
+		}
+		passwordHash = hashPassword(salt, password, passwordIterations)
+	}
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i := range next.Server.Web.Users {
+		user := &next.Server.Web.Users[i]
+		if user.Username != username {
+			continue
+		}
+		if role != "" {
+			user.Role = role
+		}
+		if password != "" {
+			user.Salt = salt
+			user.PasswordHash = passwordHash
+			user.PasswordIterations = passwordIterations
+		}
+
+		// 自动保存到文件
+		return m.saveUnsafe(next)
+	}
+
+	return fmt.Errorf("用户不存在: %s", username)
+}
+
+// DeleteWebUser 删除Web用户
+func (m *ConfigManager) DeleteWebUser(username string) error {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.config == nil {
+		return fmt.Errorf("配置未加载")
+	}
+	next := cloneConfig(m.config)
+
+	for i, user := range next.Server.Web.Users {
+		if user.Username == username {
+			next.Server.Web.Users = append(next.Server.Web.Users[:i], next.Server.Web.Users[i+1:]...)
+			removeOrganizationMember(next, username)
+
+			// 自动保存到文件
+			return m.saveUnsafe(next)
+		}
+	}
+
+	return fmt.Errorf("用户不存在: %s", username)
+}
+
+// hashPassword 计算加盐的密码哈希：iterations 大于0时为 PBKDF2-HMAC-SHA256，
+// 为0时是早期版本使用的 SHA-256(salt + 密码)，只用于验证尚未升级的密码
+func hashPassword(salt, password string, iterations int) string {
+	if iterations <= 0 {
+		return utils.SHA256Hex(salt + password)
+	}
+	return hex.EncodeToString(utils.PBKDF2SHA256([]byte(password), []byte(salt), iterations, sha256.Size))
+}
+
+// hashSecret 计算加盐的随机密钥哈希，密钥由网关随机生成（256位），不需要慢哈希
+func hashSecret(salt, secret string) string {
+	return utils.SHA256Hex(salt + secret)
+}
+
+// newSalt 生成随机盐
+func newSalt() (string, error) {
+	bytes := make([]byte, 16)
+	if _, err := rand.Read(bytes); err != nil {
+		return "", fmt.Errorf("生成密码盐失败: %w", err)
+	}
+	return hex.EncodeToString(bytes), nil
+}
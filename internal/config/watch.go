@@ -0,0 +1,78 @@
+package config
+
+import (
+	"sync"
+	"time"
+)
+
+// Watcher 定期检查配置文件是否被外部修改，修改后调用 onChange 重新加载并应用配置
+type Watcher struct {
+	manager  *ConfigManager
+	interval time.Duration
+	onChange func()
+	stopCh   chan struct{}
+	mutex    sync.Mutex
+}
+
+// NewWatcher 创建配置文件监视器，interval 不大于0时返回nil（不监视）
+func NewWatcher(manager *ConfigManager, interval time.Duration, onChange func()) *Watcher {
+	if interval <= 0 {
+		return nil
+	}
+	return &Watcher{
+		manager:  manager,
+		interval: interval,
+		onChange: onChange,
+	}
+}
+
+// Start 启动后台检查
+func (w *Watcher) Start() {
+	if w == nil {
+		return
+	}
+	w.mutex.Lock()
+	defer w.mutex.Unlock()
+
+	if w.stopCh != nil {
+		return
+	}
+	w.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(w.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				w.Check()
+			case <-stopCh:
+				return
+			}
+		}
+	}(w.stopCh)
+}
+
+// Stop 停止后台检查
+func (w *Watcher) Stop() {
+	if w == nil {
+		return
+	}
+	w.mutex.Lock()
+	defer w.mutex.Unlock()
+
+	if w.stopCh != nil {
+		close(w.stopCh)
+		w.stopCh = nil
+	}
+}
+
+// Check 配置文件被外部修改时调用 onChange，返回是否检测到修改
+func (w *Watcher) Check() bool {
+	if !w.manager.FileChanged() {
+		return false
+	}
+	w.onChange()
+	return true
+}
@@ -0,0 +1,85 @@
+package config
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// writeExternally 模拟在网关之外编辑配置文件，修改时间推后以免与上次保存的时间相同
+func writeExternally(t *testing.T, configPath string, config *types.Config, offset time.Duration) {
+	t.Helper()
+	if err := NewConfigManager(configPath).Save(config); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	modTime := time.Now().Add(offset)
+	if err := os.Chtimes(configPath, modTime, modTime); err != nil {
+		t.Fatalf("Chtimes() error = %v", err)
+	}
+}
+
+func TestWatcher_ReloadsModifiedFile(t *testing.T) {
+	configPath := filepath.Join(t.TempDir(), "config.yaml")
+	mgr := NewConfigManager(configPath)
+	_ = mgr.Save(&types.Config{Server: types.ServerConfig{Host: "localhost", Port: 8080}})
+	config, err := mgr.Load()
+	if err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	previous := mgr.Get()
+
+	reloads := 0
+	watcher := NewWatcher(mgr, time.Minute, func() {
+		reloads++
+		if _, err := mgr.Reload(); err != nil {
+			t.Logf("Reload() error = %v", err)
+		}
+	})
+
+	// 网关自己保存配置不触发重新加载
+	if err := mgr.Save(config); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	if watcher.Check() {
+		t.Error("Check() = true after the gateway saved the file itself")
+	}
+
+	// 外部修改后整体替换配置，之前取得的快照保持不变
+	writeExternally(t, configPath, &types.Config{
+		Server:  types.ServerConfig{Host: "localhost", Port: 8080},
+		Routing: types.RoutingConfig{Strategy: "least_connections"},
+	}, time.Second)
+	if !watcher.Check() || reloads != 1 {
+		t.Fatalf("Check() did not reload the modified file")
+	}
+	current := mgr.Get()
+	if current.Routing.Strategy != "least_connections" {
+		t.Errorf("routing strategy = %q, want least_connections", current.Routing.Strategy)
+	}
+	if current == previous || previous.Routing.Strategy != "" {
+		t.Errorf("Reload() modified the previous snapshot in place, strategy = %q", previous.Routing.Strategy)
+	}
+
+	// 无效的新配置不生效，同一份文件也不会被反复加载
+	writeExternally(t, configPath, &types.Config{
+		Server:  types.ServerConfig{Host: "localhost", Port: 8080},
+		Routing: types.RoutingConfig{Strategy: "busiest"},
+	}, 2*time.Second)
+	if _, err := mgr.Reload(); err == nil || !strings.Contains(err.Error(), "routing.strategy") {
+		t.Errorf("Reload() error = %v, want the invalid strategy rejected", err)
+	}
+	if mgr.Get() != current {
+		t.Errorf("routing strategy = %q after a failed reload, want the previous configuration kept", mgr.Get().Routing.Strategy)
+	}
+	if watcher.Check() {
+		t.Error("Check() = true for a file that already failed to reload")
+	}
+
+	if NewWatcher(mgr, 0, nil) != nil {
+		t.Error("NewWatcher() with a zero interval should disable watching")
+	}
+}
@@ -8,8 +8,12 @@ import (
 
 	"github.com/iBreaker/llm-gateway/pkg/logger"
 	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
 )
 
+// maxLogBodyBytes 调试日志中保留的请求体长度
+const maxLogBodyBytes = 4096
+
 // AnthropicConverter Anthropic格式转换器工厂
 type AnthropicConverter struct{}
 
@@ -17,6 +21,8 @@ type AnthropicConverter struct{}
 type AnthropicStreamConverter struct {
 	messageStartSent      bool
 	contentBlockStartSent bool
+	messageDeltaSent      bool
+	stopReason            string // 解析时记录 message_delta 中的结束原因，由 message_stop 携带
 }
 
 // NewAnthropicConverter 创建Anthropic转换器
@@ -37,7 +43,7 @@ func (c *AnthropicConverter) GetUpstreamPath(clientEndpoint string) string {
 
 // ParseRequest 解析Anthropic请求到内部格式
 func (c *AnthropicConverter) ParseRequest(data []byte) (*types.UnifiedRequest, error) {
-	logger.Debug("解析Anthropic请求: %s", string(data))
+	logger.Debug("解析Anthropic请求: %s", utils.Excerpt(data, maxLogBodyBytes))
 
 	var req types.AnthropicRequest
 	if err := json.Unmarshal(data, &req); err != nil {
@@ -146,7 +152,12 @@ func (c *AnthropicConverter) BuildRequest(request *types.UnifiedRequest) ([]byte
 		Temperature: request.Temperature,
 		Stream:      request.Stream,
 		Tools:       convertedTools,
-		// 注意：故意不设置ToolChoice字段 - Anthropic默认为auto行为
+	}
+	// 没有工具时 Anthropic 拒绝 tool_choice
+	if len(convertedTools) > 0 {
+		if choice := toolChoiceToAnthropic(request.ToolChoice); choice != nil {
+			req.ToolChoice = choice
+		}
 	}
 
 	// 设置系统字段，并确保Claude Code身份在最前面
@@ -641,11 +652,15 @@ func (c *AnthropicConverter) convertTools(tools []map[string]interface{}) []map[
 		// 如果是OpenAI格式，转换为Anthropic格式
 		if tool["type"] == "function" {
 			if function, ok := tool["function"].(map[string]interface{}); ok {
+				// Anthropic 要求 input_schema，拒绝值为null的 description
 				anthropicTool := map[string]interface{}{
-					"name":        function["name"],
-					"description": function["description"],
+					"name":         function["name"],
+					"input_schema": map[string]interface{}{"type": "object", "properties": map[string]interface{}{}},
 				}
-				if parameters, ok := function["parameters"]; ok {
+				if description, ok := function["description"].(string); ok && description != "" {
+					anthropicTool["description"] = description
+				}
+				if parameters, ok := function["parameters"]; ok && parameters != nil {
 					anthropicTool["input_schema"] = parameters
 				}
 				converted = append(converted, anthropicTool)
@@ -706,11 +721,19 @@ func (c *AnthropicConverter) convertContent(content interface{}, toolCalls []map
 	for _, toolCall := range toolCalls {
 		if toolCall != nil {
 			if funcData, ok := toolCall["function"].(map[string]interface{}); ok {
+				// OpenAI格式的arguments是JSON字符串，Anthropic的input必须是对象
+				input := funcData["arguments"]
+				if arguments, ok := input.(string); ok {
+					var decoded interface{}
+					if err := json.Unmarshal([]byte(arguments), &decoded); err == nil {
+						input = decoded
+					}
+				}
 				blocks = append(blocks, types.AnthropicContentBlock{
 					Type:  "tool_use",
 					ID:    fmt.Sprintf("%v", toolCall["id"]),
 					Name:  fmt.Sprintf("%v", funcData["name"]),
-					Input: funcData["arguments"],
+					Input: input,
 				})
 			}
 		}
@@ -725,32 +748,12 @@ func (c *AnthropicConverter) convertContent(content interface{}, toolCalls []map
 
 // convertStopReason 转换Anthropic停止原因到标准格式
 func (c *AnthropicConverter) convertStopReason(stopReason string) string {
-	switch stopReason {
-	case "end_turn":
-		return "stop"
-	case "max_tokens":
-		return "length"
-	case "stop_sequence":
-		return "stop"
-	case "tool_use":
-		return "tool_calls"
-	default:
-		return "stop"
-	}
+	return openAIFinishReason(stopReason)
 }
 
 // convertFinishReason 转换标准格式到Anthropic停止原因
 func (c *AnthropicConverter) convertFinishReason(finishReason string) string {
-	switch finishReason {
-	case "stop":
-		return "end_turn"
-	case "length":
-		return "max_tokens"
-	case "tool_calls":
-		return "tool_use"
-	default:
-		return "end_turn"
-	}
+	return anthropicStopReason(finishReason)
 }
 
 // NewStreamConverter 创建新的流式转换器实例
@@ -778,6 +781,28 @@ func (sc *AnthropicStreamConverter) ParseStreamEvent(eventType string, data []by
 			}}, nil
 		}
 
+	case "content_block_start":
+		// 只需要工具调用的ID和名称，文本块由目标格式按需生成
+		if block, ok := eventData["content_block"].(map[string]interface{}); ok && getString(block["type"]) == "tool_use" {
+			index, _ := eventData["index"].(float64)
+			return []*UnifiedStreamEvent{{
+				Type: StreamEventContentStart,
+				Content: &UnifiedStreamContent{
+					Type:     "tool_use",
+					ToolID:   getString(block["id"]),
+					ToolName: getString(block["name"]),
+					Index:    int(index),
+				},
+			}}, nil
+		}
+
+	case "content_block_stop":
+		index, _ := eventData["index"].(float64)
+		return []*UnifiedStreamEvent{{
+			Type:    StreamEventContentStop,
+			Content: &UnifiedStreamContent{Index: int(index)},
+		}}, nil
+
 	case "content_block_delta":
 		if delta, ok := eventData["delta"].(map[string]interface{}); ok {
 			index, _ := eventData["index"].(float64)
@@ -811,10 +836,16 @@ func (sc *AnthropicStreamConverter) ParseStreamEvent(eventType string, data []by
 			}
 		}
 
+	case "message_delta":
+		if delta, ok := eventData["delta"].(map[string]interface{}); ok {
+			sc.stopReason = getString(delta["stop_reason"])
+		}
+
 	case "message_stop":
 		return []*UnifiedStreamEvent{{
-			Type:   StreamEventMessageStop,
-			IsDone: false, // 不设置IsDone，让[DONE]来触发结束
+			Type:         StreamEventMessageStop,
+			IsDone:       false, // 不设置IsDone，让[DONE]来触发结束
+			FinishReason: NormalizeFinishReason(sc.stopReason),
 		}}, nil
 	}
 
@@ -939,6 +970,25 @@ func (sc *AnthropicStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent)
 			IsDone:    false,
 		}, nil
 
+	case StreamEventMessageDelta:
+		messageDelta := map[string]interface{}{
+			"type": "message_delta",
+			"delta": map[string]interface{}{
+				"stop_reason":   anthropicStopReason(event.FinishReason),
+				"stop_sequence": nil,
+			},
+			"usage": map[string]interface{}{
+				"output_tokens": event.Usage["output_tokens"],
+			},
+		}
+
+		return &StreamChunk{
+			EventType: "message_delta",
+			Data:      messageDelta,
+			Tokens:    0,
+			IsDone:    false,
+		}, nil
+
 	case StreamEventMessageStop:
 		messageStop := map[string]interface{}{
 			"type": "message_stop",
@@ -959,6 +1009,11 @@ func (sc *AnthropicStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent)
 func (sc *AnthropicStreamConverter) NeedPreEvents(event *UnifiedStreamEvent) []*UnifiedStreamEvent {
 	var events []*UnifiedStreamEvent
 
+	// 源格式自带message_start时不再自动生成
+	if event.Type == StreamEventMessageStart {
+		sc.messageStartSent = true
+	}
+
 	// Anthropic需要严格的事件顺序
 	if event.Type == StreamEventContentDelta || event.Type == StreamEventContentStart {
 		// 如果还没发送message_start，需要先发送
@@ -1003,6 +1058,16 @@ func (sc *AnthropicStreamConverter) NeedPreEvents(event *UnifiedStreamEvent) []*
 		sc.contentBlockStartSent = false
 	}
 
+	// message_stop之前先发送携带结束原因的message_delta
+	if event.Type == StreamEventMessageStop && !sc.messageDeltaSent {
+		sc.messageDeltaSent = true
+		events = append(events, &UnifiedStreamEvent{
+			Type:         StreamEventMessageDelta,
+			FinishReason: event.FinishReason,
+			Usage:        event.Usage,
+		})
+	}
+
 	return events
 }
 
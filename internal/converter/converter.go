@@ -67,6 +67,7 @@ const (
 	StreamEventContentDelta
 	StreamEventContentStop
 	StreamEventMessageStop
+	StreamEventMessageDelta // 消息级的结束原因和用量，只有Anthropic格式单独输出（message_delta）
 )
 
 // UnifiedStreamContent 统一流式内容
@@ -87,6 +88,9 @@ type UnifiedStreamEvent struct {
 	Model     string                `json:"model,omitempty"`
 	Usage     map[string]int        `json:"usage,omitempty"`
 	IsDone    bool                  `json:"is_done"`
+
+	// FinishReason 规范化的结束原因（见 FinishReason* 常量），由 MessageStop 事件携带，源格式未提供时为空
+	FinishReason string `json:"finish_reason,omitempty"`
 }
 
 // StreamChunk 流式数据块 (保持向后兼容)
@@ -130,6 +134,7 @@ func NewConverterRegistry() ConverterRegistry {
 	// 注册内置转换器
 	registry.Register(FormatOpenAI, NewOpenAIConverter())
 	registry.Register(FormatAnthropic, NewAnthropicConverter())
+	registry.Register(FormatGemini, NewGeminiConverter())
 
 	return registry
 }
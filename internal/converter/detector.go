@@ -17,6 +17,9 @@ func (d *FormatDetector) Detect(requestBody []byte, endpoint string) Format {
 	if endpoint == "/v1/chat/completions" {
 		return FormatOpenAI
 	}
+	if endpoint == GeminiEndpoint {
+		return FormatGemini
+	}
 
 	// 解析JSON数据
 	var data map[string]interface{}
@@ -24,6 +27,11 @@ func (d *FormatDetector) Detect(requestBody []byte, endpoint string) Format {
 		return FormatUnknown
 	}
 
+	// 检查Gemini特有字段
+	if _, hasContents := data["contents"]; hasContents {
+		return FormatGemini
+	}
+
 	// 检查Anthropic特有字段
 	if _, hasSystem := data["system"]; hasSystem {
 		return FormatAnthropic
@@ -0,0 +1,238 @@
+package converter
+
+import (
+	"encoding/base64"
+	"encoding/binary"
+	"encoding/json"
+	"fmt"
+	"math"
+	"sort"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// EmbeddingsEndpoint OpenAI 格式的嵌入端点，所有提供商的嵌入都通过它访问
+const EmbeddingsEndpoint = "/v1/embeddings"
+
+// 上游的嵌入路径
+const (
+	openAIEmbeddingsPath      = "/v1/embeddings"
+	geminiEmbedContentPath    = "/v1beta/models/{model}:embedContent"
+	geminiBatchEmbeddingsPath = "/v1beta/models/{model}:batchEmbedContents"
+)
+
+// 嵌入向量的返回格式
+const (
+	EmbeddingEncodingFloat  = "float"
+	EmbeddingEncodingBase64 = "base64" // float32 小端序后 base64 编码，与 OpenAI 相同
+)
+
+// EmbeddingRequest 客户端的嵌入请求（OpenAI 格式），字符串形式的 input 统一为只有一项的列表
+type EmbeddingRequest struct {
+	Model          string
+	Input          []string
+	Dimensions     int    // 输出向量的维度，0表示使用模型的默认维度
+	EncodingFormat string // float（默认）或 base64
+	User           string
+}
+
+// EmbeddingResult 上游返回的嵌入向量，按输入顺序排列
+type EmbeddingResult struct {
+	Embeddings   [][]float64
+	Model        string // 上游返回的模型名，可能为空
+	InputTokens  int
+	UsagePresent bool // 上游返回了token用量（Gemini 不返回，需要估算）
+}
+
+// EmbeddingsSupported 提供商是否支持嵌入：OpenAI 格式的提供商和 Gemini 支持，Anthropic（包括 Bedrock）没有嵌入接口
+func EmbeddingsSupported(provider types.Provider) bool {
+	switch provider {
+	case types.ProviderAnthropic, types.ProviderBedrock:
+		return false
+	}
+	return true
+}
+
+// ParseEmbeddingRequest 解析 OpenAI 格式的嵌入请求，input 只支持字符串或字符串数组
+func ParseEmbeddingRequest(data []byte) (*EmbeddingRequest, error) {
+	var raw struct {
+		Model          string          `json:"model"`
+		Input          json.RawMessage `json:"input"`
+		Dimensions     int             `json:"dimensions"`
+		EncodingFormat string          `json:"encoding_format"`
+		User           string          `json:"user"`
+	}
+	if err := json.Unmarshal(data, &raw); err != nil {
+		return nil, fmt.Errorf("解析嵌入请求失败: %w", err)
+	}
+	if raw.Model == "" {
+		return nil, fmt.Errorf("缺少 model")
+	}
+	if raw.Dimensions < 0 {
+		return nil, fmt.Errorf("无效的 dimensions: %d", raw.Dimensions)
+	}
+	switch raw.EncodingFormat {
+	case "", EmbeddingEncodingFloat, EmbeddingEncodingBase64:
+	default:
+		return nil, fmt.Errorf("不支持的 encoding_format: %s（float 或 base64）", raw.EncodingFormat)
+	}
+
+	var input []string
+	var single string
+	if err := json.Unmarshal(raw.Input, &single); err == nil {
+		input = []string{single}
+	} else if err := json.Unmarshal(raw.Input, &input); err != nil {
+		return nil, fmt.Errorf("input 必须是字符串或字符串数组（不支持token数组）")
+	}
+	if len(input) == 0 {
+		return nil, fmt.Errorf("缺少 input")
+	}
+	for i, text := range input {
+		if text == "" {
+			return nil, fmt.Errorf("input 的第%d项为空", i+1)
+		}
+	}
+
+	return &EmbeddingRequest{
+		Model:          raw.Model,
+		Input:          input,
+		Dimensions:     raw.Dimensions,
+		EncodingFormat: raw.EncodingFormat,
+		User:           raw.User,
+	}, nil
+}
+
+// BuildEmbeddingRequest 构建发送给上游的嵌入请求，返回上游路径（Gemini 的路径含 {model} 模板）和请求体。
+// 上游始终返回浮点数向量，客户端要求 base64 时由 BuildEmbeddingResponse 编码
+func BuildEmbeddingRequest(request *EmbeddingRequest, provider types.Provider) (string, []byte, error) {
+	if !EmbeddingsSupported(provider) {
+		return "", nil, fmt.Errorf("提供商 %s 不支持嵌入", provider)
+	}
+
+	if provider != types.ProviderGoogle {
+		body := map[string]interface{}{
+			"model":           request.Model,
+			"input":           request.Input,
+			"encoding_format": EmbeddingEncodingFloat,
+		}
+		if request.Dimensions > 0 {
+			body["dimensions"] = request.Dimensions
+		}
+		if request.User != "" {
+			body["user"] = request.User
+		}
+		data, err := json.Marshal(body)
+		return openAIEmbeddingsPath, data, err
+	}
+
+	// Gemini：单个输入使用 embedContent，多个输入使用 batchEmbedContents
+	contents := make([]map[string]interface{}, len(request.Input))
+	for i, text := range request.Input {
+		content := map[string]interface{}{
+			"content": map[string]interface{}{
+				"parts": []map[string]string{{"text": text}},
+			},
+		}
+		if request.Dimensions > 0 {
+			content["outputDimensionality"] = request.Dimensions
+		}
+		contents[i] = content
+	}
+	if len(contents) == 1 {
+		data, err := json.Marshal(contents[0])
+		return geminiEmbedContentPath, data, err
+	}
+	for _, content := range contents {
+		content["model"] = "models/" + request.Model
+	}
+	data, err := json.Marshal(map[string]interface{}{"requests": contents})
+	return geminiBatchEmbeddingsPath, data, err
+}
+
+// ParseEmbeddingResponse 解析上游的嵌入响应，检查向量数量与输入数量一致
+func ParseEmbeddingResponse(data []byte, provider types.Provider, inputs int) (*EmbeddingResult, error) {
+	result := &EmbeddingResult{}
+
+	if provider == types.ProviderGoogle {
+		var resp struct {
+			Embedding *struct {
+				Values []float64 `json:"values"`
+			} `json:"embedding"`
+			Embeddings []struct {
+				Values []float64 `json:"values"`
+			} `json:"embeddings"`
+		}
+		if err := json.Unmarshal(data, &resp); err != nil {
+			return nil, fmt.Errorf("解析Gemini嵌入响应失败: %w", err)
+		}
+		if resp.Embedding != nil {
+			result.Embeddings = append(result.Embeddings, resp.Embedding.Values)
+		}
+		for _, embedding := range resp.Embeddings {
+			result.Embeddings = append(result.Embeddings, embedding.Values)
+		}
+	} else {
+		var resp struct {
+			Model string `json:"model"`
+			Data  []struct {
+				Index     int       `json:"index"`
+				Embedding []float64 `json:"embedding"`
+			} `json:"data"`
+			Usage *struct {
+				PromptTokens int `json:"prompt_tokens"`
+			} `json:"usage"`
+		}
+		if err := json.Unmarshal(data, &resp); err != nil {
+			return nil, fmt.Errorf("解析嵌入响应失败: %w", err)
+		}
+		sort.SliceStable(resp.Data, func(i, j int) bool { return resp.Data[i].Index < resp.Data[j].Index })
+		for _, item := range resp.Data {
+			result.Embeddings = append(result.Embeddings, item.Embedding)
+		}
+		result.Model = resp.Model
+		if resp.Usage != nil {
+			result.InputTokens = resp.Usage.PromptTokens
+			result.UsagePresent = true
+		}
+	}
+
+	if len(result.Embeddings) != inputs {
+		return nil, fmt.Errorf("上游返回了%d个向量，请求有%d个输入", len(result.Embeddings), inputs)
+	}
+	return result, nil
+}
+
+// BuildEmbeddingResponse 构建返回给客户端的 OpenAI 格式嵌入响应
+func BuildEmbeddingResponse(result *EmbeddingResult, model, encodingFormat string) ([]byte, error) {
+	data := make([]map[string]interface{}, len(result.Embeddings))
+	for i, values := range result.Embeddings {
+		var embedding interface{} = values
+		if encodingFormat == EmbeddingEncodingBase64 {
+			embedding = encodeEmbedding(values)
+		}
+		data[i] = map[string]interface{}{
+			"object":    "embedding",
+			"index":     i,
+			"embedding": embedding,
+		}
+	}
+
+	return json.Marshal(map[string]interface{}{
+		"object": "list",
+		"data":   data,
+		"model":  model,
+		"usage": map[string]int{
+			"prompt_tokens": result.InputTokens,
+			"total_tokens":  result.InputTokens,
+		},
+	})
+}
+
+// encodeEmbedding 把向量编码为 float32 小端序字节的 base64
+func encodeEmbedding(values []float64) string {
+	buf := make([]byte, 4*len(values))
+	for i, value := range values {
+		binary.LittleEndian.PutUint32(buf[4*i:], math.Float32bits(float32(value)))
+	}
+	return base64.StdEncoding.EncodeToString(buf)
+}
@@ -0,0 +1,121 @@
+package converter
+
+import (
+	"encoding/base64"
+	"encoding/binary"
+	"encoding/json"
+	"math"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestParseEmbeddingRequest(t *testing.T) {
+	req, err := ParseEmbeddingRequest([]byte(`{"model": "text-embedding-3-small", "input": "hello", "dimensions": 256}`))
+	if err != nil {
+		t.Fatalf("ParseEmbeddingRequest() error = %v", err)
+	}
+	if len(req.Input) != 1 || req.Input[0] != "hello" || req.Dimensions != 256 {
+		t.Errorf("request = %+v", req)
+	}
+
+	req, err = ParseEmbeddingRequest([]byte(`{"model": "m", "input": ["a", "b"], "encoding_format": "base64"}`))
+	if err != nil || len(req.Input) != 2 || req.EncodingFormat != EmbeddingEncodingBase64 {
+		t.Errorf("request = %+v, error = %v", req, err)
+	}
+
+	for _, body := range []string{
+		`{"input": "hello"}`,
+		`{"model": "m", "input": []}`,
+		`{"model": "m", "input": [[1, 2, 3]]}`,
+		`{"model": "m", "input": ["a", ""]}`,
+		`{"model": "m", "input": "a", "encoding_format": "int8"}`,
+	} {
+		if _, err := ParseEmbeddingRequest([]byte(body)); err == nil {
+			t.Errorf("ParseEmbeddingRequest(%s) should fail", body)
+		}
+	}
+}
+
+func TestBuildEmbeddingRequest(t *testing.T) {
+	req := &EmbeddingRequest{Model: "text-embedding-004", Input: []string{"hello"}, Dimensions: 8}
+
+	path, body, err := BuildEmbeddingRequest(req, types.ProviderGoogle)
+	if err != nil || path != "/v1beta/models/{model}:embedContent" {
+		t.Fatalf("BuildEmbeddingRequest() = %s, %v", path, err)
+	}
+	if !strings.Contains(string(body), `"parts":[{"text":"hello"}]`) || !strings.Contains(string(body), `"outputDimensionality":8`) {
+		t.Errorf("embedContent body = %s", body)
+	}
+
+	req.Input = []string{"a", "b"}
+	path, body, _ = BuildEmbeddingRequest(req, types.ProviderGoogle)
+	var batch struct {
+		Requests []struct {
+			Model string `json:"model"`
+		} `json:"requests"`
+	}
+	if err := json.Unmarshal(body, &batch); err != nil || path != "/v1beta/models/{model}:batchEmbedContents" || len(batch.Requests) != 2 || batch.Requests[1].Model != "models/text-embedding-004" {
+		t.Errorf("batchEmbedContents = %s %s", path, body)
+	}
+
+	path, body, _ = BuildEmbeddingRequest(req, types.ProviderAzure)
+	if path != "/v1/embeddings" || !strings.Contains(string(body), `"input":["a","b"]`) || !strings.Contains(string(body), `"encoding_format":"float"`) {
+		t.Errorf("OpenAI request = %s %s", path, body)
+	}
+
+	if _, _, err := BuildEmbeddingRequest(req, types.ProviderAnthropic); err == nil {
+		t.Error("BuildEmbeddingRequest() should reject Anthropic")
+	}
+}
+
+func TestEmbeddingResponse(t *testing.T) {
+	// OpenAI 的结果按 index 排序
+	result, err := ParseEmbeddingResponse([]byte(`{
+		"model": "text-embedding-3-small",
+		"data": [{"index": 1, "embedding": [0.5]}, {"index": 0, "embedding": [0.25]}],
+		"usage": {"prompt_tokens": 7, "total_tokens": 7}
+	}`), types.ProviderOpenAI, 2)
+	if err != nil {
+		t.Fatalf("ParseEmbeddingResponse() error = %v", err)
+	}
+	if result.Embeddings[0][0] != 0.25 || !result.UsagePresent || result.InputTokens != 7 {
+		t.Errorf("result = %+v", result)
+	}
+
+	// Gemini 不返回用量
+	result, err = ParseEmbeddingResponse([]byte(`{"embeddings": [{"values": [1, 2]}, {"values": [3, 4]}]}`), types.ProviderGoogle, 2)
+	if err != nil || len(result.Embeddings) != 2 || result.UsagePresent {
+		t.Fatalf("Gemini result = %+v, %v", result, err)
+	}
+	if _, err := ParseEmbeddingResponse([]byte(`{"embedding": {"values": [1]}}`), types.ProviderGoogle, 2); err == nil {
+		t.Error("ParseEmbeddingResponse() should reject a count mismatch")
+	}
+
+	result.InputTokens = 4
+	body, err := BuildEmbeddingResponse(result, "text-embedding-004", EmbeddingEncodingBase64)
+	if err != nil {
+		t.Fatalf("BuildEmbeddingResponse() error = %v", err)
+	}
+	var resp struct {
+		Object string `json:"object"`
+		Data   []struct {
+			Index     int    `json:"index"`
+			Embedding string `json:"embedding"`
+		} `json:"data"`
+		Usage struct {
+			PromptTokens int `json:"prompt_tokens"`
+		} `json:"usage"`
+	}
+	if err := json.Unmarshal(body, &resp); err != nil {
+		t.Fatalf("response = %s: %v", body, err)
+	}
+	if resp.Object != "list" || len(resp.Data) != 2 || resp.Data[1].Index != 1 || resp.Usage.PromptTokens != 4 {
+		t.Errorf("response = %s", body)
+	}
+	raw, _ := base64.StdEncoding.DecodeString(resp.Data[1].Embedding)
+	if len(raw) != 8 || math.Float32frombits(binary.LittleEndian.Uint32(raw[4:])) != 4 {
+		t.Errorf("base64 embedding = %v", raw)
+	}
+}
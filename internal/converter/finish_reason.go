@@ -0,0 +1,49 @@
+package converter
+
+// 规范化的结束原因（取值与OpenAI的 finish_reason 一致），记录在使用记录中，转换响应格式时再映射为目标格式的取值
+const (
+	FinishReasonStop          = "stop"           // 正常结束或命中停止序列
+	FinishReasonLength        = "length"         // 达到最大输出token数或上下文长度
+	FinishReasonToolCalls     = "tool_calls"     // 模型请求调用工具
+	FinishReasonContentFilter = "content_filter" // 被提供商的安全策略拦截或模型拒绝回答
+)
+
+// NormalizeFinishReason 将各提供商的结束原因（Anthropic stop_reason、OpenAI finish_reason、Gemini finishReason）
+// 映射为规范取值。空值返回空字符串，无法识别的取值按正常结束处理
+func NormalizeFinishReason(reason string) string {
+	switch reason {
+	case "":
+		return ""
+	case "length", "max_tokens", "model_context_window_exceeded", "MAX_TOKENS":
+		return FinishReasonLength
+	case "tool_calls", "function_call", "tool_use":
+		return FinishReasonToolCalls
+	case "content_filter", "refusal", "SAFETY", "RECITATION", "BLOCKLIST", "PROHIBITED_CONTENT", "SPII", "IMAGE_SAFETY":
+		return FinishReasonContentFilter
+	default:
+		// stop、end_turn、stop_sequence、pause_turn、STOP 等
+		return FinishReasonStop
+	}
+}
+
+// anthropicStopReason 将结束原因映射为Anthropic的 stop_reason
+func anthropicStopReason(reason string) string {
+	switch NormalizeFinishReason(reason) {
+	case FinishReasonLength:
+		return "max_tokens"
+	case FinishReasonToolCalls:
+		return "tool_use"
+	case FinishReasonContentFilter:
+		return "refusal"
+	default:
+		return "end_turn"
+	}
+}
+
+// openAIFinishReason 将结束原因映射为OpenAI的 finish_reason，未知时按正常结束处理
+func openAIFinishReason(reason string) string {
+	if normalized := NormalizeFinishReason(reason); normalized != "" {
+		return normalized
+	}
+	return FinishReasonStop
+}
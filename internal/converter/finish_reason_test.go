@@ -0,0 +1,70 @@
+package converter
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestNormalizeFinishReason(t *testing.T) {
+	tests := map[string]string{
+		"end_turn":       FinishReasonStop,
+		"stop_sequence":  FinishReasonStop,
+		"STOP":           FinishReasonStop,
+		"max_tokens":     FinishReasonLength,
+		"MAX_TOKENS":     FinishReasonLength,
+		"tool_use":       FinishReasonToolCalls,
+		"function_call":  FinishReasonToolCalls,
+		"refusal":        FinishReasonContentFilter,
+		"RECITATION":     FinishReasonContentFilter,
+		"content_filter": FinishReasonContentFilter,
+		"":               "",
+	}
+	for reason, want := range tests {
+		if got := NormalizeFinishReason(reason); got != want {
+			t.Errorf("NormalizeFinishReason(%q) = %q, want %q", reason, got, want)
+		}
+	}
+}
+
+func TestStreamFinishReason_AnthropicToOpenAI(t *testing.T) {
+	stream := "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude-3-5-sonnet\",\"stop_reason\":null}}\n\n" +
+		"event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n" +
+		"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n" +
+		"event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n" +
+		"event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"max_tokens\",\"stop_sequence\":null},\"usage\":{\"output_tokens\":1}}\n\n" +
+		"event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"
+
+	writer := &recordingWriter{}
+	if err := NewManager().ProcessStream(strings.NewReader(stream), types.ProviderAnthropic, FormatOpenAI, writer); err != nil {
+		t.Fatalf("ProcessStream() error = %v", err)
+	}
+
+	last := writer.chunks[len(writer.chunks)-1].Data.(map[string]interface{})
+	choice := last["choices"].([]interface{})[0].(map[string]interface{})
+	if choice["finish_reason"] != FinishReasonLength {
+		t.Errorf("finish_reason = %v, want length", choice["finish_reason"])
+	}
+}
+
+func TestStreamFinishReason_OpenAIToAnthropic(t *testing.T) {
+	stream := "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"content_filter\"}]}\n\n" +
+		"data: [DONE]\n\n"
+
+	writer := &recordingWriter{}
+	if err := NewManager().ProcessStream(strings.NewReader(stream), types.ProviderOpenAI, FormatAnthropic, writer); err != nil {
+		t.Fatalf("ProcessStream() error = %v", err)
+	}
+
+	var stopReason interface{}
+	for _, chunk := range writer.chunks {
+		if chunk.EventType == "message_delta" {
+			stopReason = chunk.Data.(map[string]interface{})["delta"].(map[string]interface{})["stop_reason"]
+		}
+	}
+	if stopReason != "refusal" {
+		t.Errorf("message_delta stop_reason = %v, want refusal", stopReason)
+	}
+}
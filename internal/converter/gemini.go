@@ -0,0 +1,782 @@
+package converter
+
+import (
+	"encoding/json"
+	"fmt"
+	"net/url"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// GeminiEndpoint Gemini原生入口（/v1beta/models/{model}:generateContent）在网关内部使用的客户端端点，
+// 用于格式检测、路径规则和使用统计
+const GeminiEndpoint = "/v1beta/models:generateContent"
+
+// geminiUpstreamPath Gemini上游路径模板，{model} 在构建上游请求时替换
+const geminiUpstreamPath = "/v1beta/models/{model}:generateContent"
+
+// GeminiConverter Gemini格式转换器工厂
+type GeminiConverter struct{}
+
+// GeminiStreamConverter Gemini流式转换器（有状态）
+type GeminiStreamConverter struct {
+	// 解析上游Gemini流
+	messageStarted bool
+	textStarted    bool
+	index          int // 下一个内容块的索引
+	toolCalls      int
+
+	// 构建返回给客户端的Gemini流
+	modelVersion string
+	responseID   string
+	toolPending  bool
+	toolName     string
+	toolInput    strings.Builder
+}
+
+// NewGeminiConverter 创建Gemini转换器
+func NewGeminiConverter() *GeminiConverter {
+	return &GeminiConverter{}
+}
+
+// GetFormat 获取转换器支持的格式
+func (c *GeminiConverter) GetFormat() Format {
+	return FormatGemini
+}
+
+// GetUpstreamPath 根据客户端端点获取上游路径（模型名和流式后缀由 ExpandUpstreamPath 填入）
+func (c *GeminiConverter) GetUpstreamPath(clientEndpoint string) string {
+	return geminiUpstreamPath
+}
+
+// ExpandUpstreamPath 把上游路径模板中的模型名替换为实际模型，流式请求改用 streamGenerateContent?alt=sse
+// 不含模板的路径原样返回
+func ExpandUpstreamPath(path string, request *types.UnifiedRequest) string {
+	if !strings.Contains(path, "{model}") {
+		return path
+	}
+	path = strings.Replace(path, "{model}", url.PathEscape(request.Model), 1)
+	if request.Stream != nil && *request.Stream {
+		path = strings.Replace(path, ":generateContent", ":streamGenerateContent", 1) + "?alt=sse"
+	}
+	return path
+}
+
+// ParseRequest 解析Gemini请求到内部格式
+func (c *GeminiConverter) ParseRequest(data []byte) (*types.UnifiedRequest, error) {
+	var req types.GeminiRequest
+	if err := json.Unmarshal(data, &req); err != nil {
+		return nil, fmt.Errorf("解析Gemini请求失败: %w", err)
+	}
+
+	var messages []types.Message
+	if req.SystemInstruction != nil {
+		if system := geminiText(req.SystemInstruction.Parts); system != "" {
+			messages = append(messages, types.Message{Role: "system", Content: system})
+		}
+	}
+
+	// Gemini的函数调用没有ID，按函数名把结果与调用配对
+	callIDs := make(map[string][]string)
+	callCount := 0
+
+	for _, content := range req.Contents {
+		var parts []types.GeminiPart
+		var toolCalls []map[string]interface{}
+
+		for _, part := range content.Parts {
+			switch {
+			case part.FunctionCall != nil:
+				callCount++
+				id := fmt.Sprintf("call_%d", callCount)
+				callIDs[part.FunctionCall.Name] = append(callIDs[part.FunctionCall.Name], id)
+				arguments, _ := json.Marshal(part.FunctionCall.Args)
+				toolCalls = append(toolCalls, map[string]interface{}{
+					"id":   id,
+					"type": "function",
+					"function": map[string]interface{}{
+						"name":      part.FunctionCall.Name,
+						"arguments": string(arguments),
+					},
+				})
+			case part.FunctionResponse != nil:
+				name := part.FunctionResponse.Name
+				id := "call_" + name
+				if queue := callIDs[name]; len(queue) > 0 {
+					id, callIDs[name] = queue[0], queue[1:]
+				}
+				result, _ := json.Marshal(part.FunctionResponse.Response)
+				messages = append(messages, types.Message{
+					Role:       "tool",
+					Content:    string(result),
+					ToolCallID: &id,
+					Name:       &name,
+				})
+			default:
+				parts = append(parts, part)
+			}
+		}
+
+		role := "user"
+		if content.Role == "model" {
+			role = "assistant"
+		}
+		if len(parts) == 0 && len(toolCalls) == 0 {
+			continue
+		}
+		msg := types.Message{Role: role, ToolCalls: toolCalls}
+		if len(parts) > 0 {
+			msg.Content = geminiPartsToContent(parts)
+		}
+		messages = append(messages, msg)
+	}
+
+	request := &types.UnifiedRequest{
+		Model:          req.Model,
+		Messages:       messages,
+		Stream:         req.Stream,
+		Tools:          geminiToolsToOpenAI(req.Tools),
+		ToolChoice:     geminiToolChoice(req.ToolConfig),
+		OriginalFormat: string(FormatGemini),
+	}
+	if config := req.GenerationConfig; config != nil {
+		request.MaxTokens = config.MaxOutputTokens
+		request.TopP = config.TopP
+		if config.Temperature != nil {
+			request.Temperature = *config.Temperature
+		}
+	}
+	return request, nil
+}
+
+// BuildRequest 构建发送给上游Gemini的请求（模型名和是否流式体现在请求路径中）
+func (c *GeminiConverter) BuildRequest(request *types.UnifiedRequest) ([]byte, error) {
+	req := types.GeminiRequest{
+		Tools:      openAIToolsToGemini(request.Tools),
+		ToolConfig: toolChoiceToGemini(request.ToolChoice),
+	}
+
+	// 记录工具调用ID对应的函数名，tool消息可能只带ID
+	toolNames := make(map[string]string)
+	var systemParts []types.GeminiPart
+
+	for _, msg := range request.Messages {
+		switch msg.Role {
+		case "system":
+			if text := contentToText(msg.Content); text != "" {
+				systemParts = append(systemParts, types.GeminiPart{Text: text})
+			}
+		case "tool":
+			name := ""
+			if msg.Name != nil {
+				name = *msg.Name
+			} else if msg.ToolCallID != nil {
+				name = toolNames[*msg.ToolCallID]
+			}
+			req.Contents = appendGeminiContent(req.Contents, "user", types.GeminiPart{
+				FunctionResponse: &types.GeminiFunctionResponse{Name: name, Response: toolResultObject(msg.Content)},
+			})
+		case "assistant":
+			parts := contentToGeminiParts(msg.Content)
+			for _, toolCall := range msg.ToolCalls {
+				function, _ := toolCall["function"].(map[string]interface{})
+				name := getString(function["name"])
+				toolNames[getString(toolCall["id"])] = name
+				parts = append(parts, types.GeminiPart{
+					FunctionCall: &types.GeminiFunctionCall{Name: name, Args: toolArguments(function["arguments"])},
+				})
+			}
+			req.Contents = appendGeminiContent(req.Contents, "model", parts...)
+		default:
+			req.Contents = appendGeminiContent(req.Contents, "user", contentToGeminiParts(msg.Content)...)
+		}
+	}
+
+	if len(systemParts) > 0 {
+		req.SystemInstruction = &types.GeminiContent{Parts: systemParts}
+	}
+
+	config := &types.GeminiGenerationConfig{MaxOutputTokens: request.MaxTokens, TopP: request.TopP}
+	if request.Temperature != 0 {
+		temperature := request.Temperature
+		config.Temperature = &temperature
+	}
+	if config.MaxOutputTokens > 0 || config.TopP != nil || config.Temperature != nil {
+		req.GenerationConfig = config
+	}
+
+	return json.Marshal(req)
+}
+
+// ParseResponse 解析Gemini上游响应到内部格式
+func (c *GeminiConverter) ParseResponse(data []byte) (*types.UnifiedResponse, error) {
+	var resp types.GeminiResponse
+	if err := json.Unmarshal(data, &resp); err != nil {
+		return nil, fmt.Errorf("解析Gemini响应失败: %w", err)
+	}
+
+	message := types.Message{Role: "assistant"}
+	finishReason := "stop"
+	if len(resp.Candidates) > 0 {
+		candidate := resp.Candidates[0]
+		var text strings.Builder
+		for i, part := range candidate.Content.Parts {
+			if part.FunctionCall != nil {
+				arguments, _ := json.Marshal(part.FunctionCall.Args)
+				message.ToolCalls = append(message.ToolCalls, map[string]interface{}{
+					"id":   fmt.Sprintf("call_%d", i+1),
+					"type": "function",
+					"function": map[string]interface{}{
+						"name":      part.FunctionCall.Name,
+						"arguments": string(arguments),
+					},
+				})
+				continue
+			}
+			text.WriteString(part.Text)
+		}
+		if text.Len() > 0 || len(message.ToolCalls) == 0 {
+			message.Content = text.String()
+		}
+		finishReason = convertGeminiFinishReason(candidate.FinishReason, len(message.ToolCalls) > 0)
+	}
+
+	response := &types.UnifiedResponse{
+		ID:      resp.ResponseID,
+		Object:  "chat.completion",
+		Created: time.Now().Unix(),
+		Model:   resp.ModelVersion,
+		Choices: []types.ResponseChoice{{Index: 0, Message: message, FinishReason: finishReason}},
+	}
+	if resp.UsageMetadata != nil {
+		response.Usage = types.ResponseUsage{
+			PromptTokens:     resp.UsageMetadata.PromptTokenCount,
+			CompletionTokens: resp.UsageMetadata.CandidatesTokenCount,
+			TotalTokens:      resp.UsageMetadata.PromptTokenCount + resp.UsageMetadata.CandidatesTokenCount,
+		}
+	}
+	return response, nil
+}
+
+// BuildResponse 构建返回给客户端的Gemini格式响应
+func (c *GeminiConverter) BuildResponse(response *types.UnifiedResponse) ([]byte, error) {
+	resp := types.GeminiResponse{
+		ModelVersion: response.Model,
+		ResponseID:   response.ID,
+		UsageMetadata: &types.GeminiUsageMetadata{
+			PromptTokenCount:     response.Usage.PromptTokens,
+			CandidatesTokenCount: response.Usage.CompletionTokens,
+			TotalTokenCount:      response.Usage.PromptTokens + response.Usage.CompletionTokens,
+		},
+	}
+
+	candidate := types.GeminiCandidate{
+		Content:      types.GeminiContent{Role: "model", Parts: []types.GeminiPart{}},
+		FinishReason: "STOP",
+	}
+	if len(response.Choices) > 0 {
+		choice := response.Choices[0]
+		candidate.FinishReason = convertFinishReasonToGemini(choice.FinishReason)
+
+		// Anthropic响应的内容块中已包含tool_use，此时不再重复添加tool_calls
+		blocks, isBlocks := choice.Message.Content.([]interface{})
+		candidate.Content.Parts = append(candidate.Content.Parts, contentToGeminiParts(choice.Message.Content)...)
+		if !isBlocks || !hasToolUseBlock(blocks) {
+			for _, toolCall := range choice.Message.ToolCalls {
+				function, _ := toolCall["function"].(map[string]interface{})
+				candidate.Content.Parts = append(candidate.Content.Parts, types.GeminiPart{
+					FunctionCall: &types.GeminiFunctionCall{Name: getString(function["name"]), Args: toolArguments(function["arguments"])},
+				})
+			}
+		}
+	}
+	resp.Candidates = []types.GeminiCandidate{candidate}
+
+	return json.Marshal(resp)
+}
+
+// ValidateRequest 验证Gemini请求格式
+func (c *GeminiConverter) ValidateRequest(data []byte) error {
+	var req types.GeminiRequest
+	if err := json.Unmarshal(data, &req); err != nil {
+		return fmt.Errorf("无效的Gemini请求格式: %w", err)
+	}
+
+	if len(req.Contents) == 0 {
+		return fmt.Errorf("缺少必需字段: contents")
+	}
+
+	return nil
+}
+
+// geminiText 拼接各部分中的文本
+func geminiText(parts []types.GeminiPart) string {
+	var texts []string
+	for _, part := range parts {
+		if part.Text != "" {
+			texts = append(texts, part.Text)
+		}
+	}
+	return strings.Join(texts, "\n")
+}
+
+// geminiPartsToContent 把Gemini内容部分转换为内部消息内容：纯文本为字符串，含图片时为OpenAI格式的内容数组
+func geminiPartsToContent(parts []types.GeminiPart) interface{} {
+	hasInlineData := false
+	for _, part := range parts {
+		if part.InlineData != nil {
+			hasInlineData = true
+		}
+	}
+	if !hasInlineData {
+		var text strings.Builder
+		for _, part := range parts {
+			text.WriteString(part.Text)
+		}
+		return text.String()
+	}
+
+	var content []interface{}
+	for _, part := range parts {
+		if part.InlineData != nil {
+			content = append(content, map[string]interface{}{
+				"type": "image_url",
+				"image_url": map[string]interface{}{
+					"url": "data:" + part.InlineData.MimeType + ";base64," + part.InlineData.Data,
+				},
+			})
+		} else if part.Text != "" {
+			content = append(content, map[string]interface{}{"type": "text", "text": part.Text})
+		}
+	}
+	return content
+}
+
+// contentToGeminiParts 把内部消息内容（字符串、OpenAI内容数组或Anthropic内容块）转换为Gemini内容部分
+func contentToGeminiParts(content interface{}) []types.GeminiPart {
+	switch v := content.(type) {
+	case string:
+		if v == "" {
+			return nil
+		}
+		return []types.GeminiPart{{Text: v}}
+	case []interface{}:
+		var parts []types.GeminiPart
+		for _, item := range v {
+			block, ok := item.(map[string]interface{})
+			if !ok {
+				continue
+			}
+			switch block["type"] {
+			case "text":
+				if text := getString(block["text"]); text != "" {
+					parts = append(parts, types.GeminiPart{Text: text})
+				}
+			case "image_url":
+				imageURL, _ := block["image_url"].(map[string]interface{})
+				if data := parseDataURL(getString(imageURL["url"])); data != nil {
+					parts = append(parts, types.GeminiPart{InlineData: data})
+				}
+			case "image":
+				source, _ := block["source"].(map[string]interface{})
+				if getString(source["type"]) == "base64" {
+					parts = append(parts, types.GeminiPart{InlineData: &types.GeminiInlineData{
+						MimeType: getString(source["media_type"]),
+						Data:     getString(source["data"]),
+					}})
+				}
+			case "tool_use":
+				parts = append(parts, types.GeminiPart{FunctionCall: &types.GeminiFunctionCall{
+					Name: getString(block["name"]),
+					Args: toolArguments(block["input"]),
+				}})
+			}
+		}
+		return parts
+	default:
+		return nil
+	}
+}
+
+// parseDataURL 解析 data:<mime>;base64,<data> 形式的图片，其他URL返回nil（Gemini不支持远程图片URL）
+func parseDataURL(dataURL string) *types.GeminiInlineData {
+	if !strings.HasPrefix(dataURL, "data:") {
+		return nil
+	}
+	header, data, ok := strings.Cut(strings.TrimPrefix(dataURL, "data:"), ",")
+	if !ok || !strings.HasSuffix(header, ";base64") {
+		return nil
+	}
+	return &types.GeminiInlineData{MimeType: strings.TrimSuffix(header, ";base64"), Data: data}
+}
+
+// contentToText 提取消息内容中的文本
+func contentToText(content interface{}) string {
+	var texts []string
+	for _, part := range contentToGeminiParts(content) {
+		if part.Text != "" {
+			texts = append(texts, part.Text)
+		}
+	}
+	return strings.Join(texts, "\n")
+}
+
+// appendGeminiContent 追加一轮内容，与上一轮角色相同时合并（Gemini要求user/model交替）
+func appendGeminiContent(contents []types.GeminiContent, role string, parts ...types.GeminiPart) []types.GeminiContent {
+	if len(parts) == 0 {
+		return contents
+	}
+	if n := len(contents); n > 0 && contents[n-1].Role == role {
+		contents[n-1].Parts = append(contents[n-1].Parts, parts...)
+		return contents
+	}
+	return append(contents, types.GeminiContent{Role: role, Parts: parts})
+}
+
+// toolArguments 把工具调用参数（JSON字符串或对象）转换为Gemini的args对象
+func toolArguments(arguments interface{}) map[string]interface{} {
+	switch v := arguments.(type) {
+	case map[string]interface{}:
+		return v
+	case string:
+		var args map[string]interface{}
+		if err := json.Unmarshal([]byte(v), &args); err == nil {
+			return args
+		}
+	}
+	return map[string]interface{}{}
+}
+
+// toolResultObject 把工具结果转换为Gemini要求的对象：JSON对象原样使用，其他内容放在 content 字段
+func toolResultObject(content interface{}) map[string]interface{} {
+	text := contentToText(content)
+	var object map[string]interface{}
+	if err := json.Unmarshal([]byte(text), &object); err == nil && object != nil {
+		return object
+	}
+	return map[string]interface{}{"content": text}
+}
+
+// hasToolUseBlock 内容块中是否包含tool_use
+func hasToolUseBlock(blocks []interface{}) bool {
+	for _, item := range blocks {
+		if block, ok := item.(map[string]interface{}); ok && block["type"] == "tool_use" {
+			return true
+		}
+	}
+	return false
+}
+
+// geminiToolsToOpenAI 把Gemini函数声明转换为OpenAI工具格式（内部统一格式）
+func geminiToolsToOpenAI(tools []types.GeminiTool) []map[string]interface{} {
+	var converted []map[string]interface{}
+	for _, tool := range tools {
+		for _, declaration := range tool.FunctionDeclarations {
+			function := map[string]interface{}{
+				"name":        declaration.Name,
+				"description": declaration.Description,
+			}
+			if declaration.Parameters != nil {
+				function["parameters"] = declaration.Parameters
+			}
+			converted = append(converted, map[string]interface{}{"type": "function", "function": function})
+		}
+	}
+	return converted
+}
+
+// openAIToolsToGemini 把OpenAI或Anthropic格式的工具转换为Gemini函数声明
+func openAIToolsToGemini(tools []map[string]interface{}) []types.GeminiTool {
+	var declarations []types.GeminiFunctionDeclaration
+	for _, tool := range tools {
+		var declaration types.GeminiFunctionDeclaration
+		if function, ok := tool["function"].(map[string]interface{}); ok {
+			declaration = types.GeminiFunctionDeclaration{
+				Name:        getString(function["name"]),
+				Description: getString(function["description"]),
+				Parameters:  cleanGeminiSchema(function["parameters"]),
+			}
+		} else if name := getString(tool["name"]); name != "" {
+			declaration = types.GeminiFunctionDeclaration{
+				Name:        name,
+				Description: getString(tool["description"]),
+				Parameters:  cleanGeminiSchema(tool["input_schema"]),
+			}
+		} else {
+			continue
+		}
+		declarations = append(declarations, declaration)
+	}
+	if len(declarations) == 0 {
+		return nil
+	}
+	return []types.GeminiTool{{FunctionDeclarations: declarations}}
+}
+
+// cleanGeminiSchema 移除Gemini不接受的JSON Schema关键字
+func cleanGeminiSchema(schema interface{}) interface{} {
+	switch v := schema.(type) {
+	case map[string]interface{}:
+		cleaned := make(map[string]interface{}, len(v))
+		for key, value := range v {
+			if key == "$schema" || key == "additionalProperties" {
+				continue
+			}
+			cleaned[key] = cleanGeminiSchema(value)
+		}
+		return cleaned
+	case []interface{}:
+		cleaned := make([]interface{}, len(v))
+		for i, value := range v {
+			cleaned[i] = cleanGeminiSchema(value)
+		}
+		return cleaned
+	default:
+		return schema
+	}
+}
+
+// geminiToolChoice 把Gemini函数调用模式转换为OpenAI的tool_choice
+func geminiToolChoice(config *types.GeminiToolConfig) interface{} {
+	if config == nil || config.FunctionCallingConfig == nil {
+		return nil
+	}
+	calling := config.FunctionCallingConfig
+	switch strings.ToUpper(calling.Mode) {
+	case "NONE":
+		return "none"
+	case "ANY":
+		if len(calling.AllowedFunctionNames) == 1 {
+			return map[string]interface{}{
+				"type":     "function",
+				"function": map[string]interface{}{"name": calling.AllowedFunctionNames[0]},
+			}
+		}
+		return "required"
+	default:
+		return nil
+	}
+}
+
+// toolChoiceToGemini 把OpenAI或Anthropic格式的tool_choice转换为Gemini函数调用配置
+func toolChoiceToGemini(choice interface{}) *types.GeminiToolConfig {
+	calling := &types.GeminiFunctionCallingConfig{}
+	switch v := choice.(type) {
+	case string:
+		switch v {
+		case "none":
+			calling.Mode = "NONE"
+		case "required", "any":
+			calling.Mode = "ANY"
+		default:
+			return nil
+		}
+	case map[string]interface{}:
+		switch getString(v["type"]) {
+		case "function":
+			function, _ := v["function"].(map[string]interface{})
+			calling.Mode = "ANY"
+			calling.AllowedFunctionNames = []string{getString(function["name"])}
+		case "tool":
+			calling.Mode = "ANY"
+			calling.AllowedFunctionNames = []string{getString(v["name"])}
+		case "any":
+			calling.Mode = "ANY"
+		case "none":
+			calling.Mode = "NONE"
+		default:
+			return nil
+		}
+	default:
+		return nil
+	}
+	return &types.GeminiToolConfig{FunctionCallingConfig: calling}
+}
+
+// convertGeminiFinishReason 转换Gemini结束原因到标准格式，输出了函数调用时视为工具调用
+func convertGeminiFinishReason(finishReason string, hasToolCalls bool) string {
+	if hasToolCalls {
+		return FinishReasonToolCalls
+	}
+	return openAIFinishReason(finishReason)
+}
+
+// convertFinishReasonToGemini 转换标准格式到Gemini结束原因
+func convertFinishReasonToGemini(finishReason string) string {
+	switch NormalizeFinishReason(finishReason) {
+	case FinishReasonLength:
+		return "MAX_TOKENS"
+	case FinishReasonContentFilter:
+		return "SAFETY"
+	default:
+		return "STOP"
+	}
+}
+
+// NewStreamConverter 创建新的流式转换器实例
+func (c *GeminiConverter) NewStreamConverter() StreamConverter {
+	return &GeminiStreamConverter{}
+}
+
+// ParseStreamEvent 解析Gemini流式分块（alt=sse，每个data行是一个完整的响应分块）到统一内部格式
+func (sc *GeminiStreamConverter) ParseStreamEvent(eventType string, data []byte) ([]*UnifiedStreamEvent, error) {
+	var chunk types.GeminiResponse
+	if err := json.Unmarshal(data, &chunk); err != nil {
+		return nil, fmt.Errorf("解析事件数据失败: %w", err)
+	}
+
+	var events []*UnifiedStreamEvent
+	if !sc.messageStarted {
+		sc.messageStarted = true
+		events = append(events, &UnifiedStreamEvent{
+			Type:      StreamEventMessageStart,
+			MessageID: chunk.ResponseID,
+			Model:     chunk.ModelVersion,
+		})
+	}
+	if len(chunk.Candidates) == 0 {
+		return events, nil
+	}
+
+	candidate := chunk.Candidates[0]
+	for _, part := range candidate.Content.Parts {
+		if part.FunctionCall != nil {
+			events = append(events, sc.closeText()...)
+			sc.toolCalls++
+			arguments, _ := json.Marshal(part.FunctionCall.Args)
+			events = append(events,
+				&UnifiedStreamEvent{Type: StreamEventContentStart, Content: &UnifiedStreamContent{
+					Type: "tool_use", ToolID: fmt.Sprintf("call_%d", sc.toolCalls), ToolName: part.FunctionCall.Name, Index: sc.index,
+				}},
+				&UnifiedStreamEvent{Type: StreamEventContentDelta, Content: &UnifiedStreamContent{
+					Type: "tool_use", ToolInput: string(arguments), Index: sc.index,
+				}},
+				&UnifiedStreamEvent{Type: StreamEventContentStop, Content: &UnifiedStreamContent{Index: sc.index}},
+			)
+			sc.index++
+			continue
+		}
+		if part.Text == "" {
+			continue
+		}
+		if !sc.textStarted {
+			sc.textStarted = true
+			events = append(events, &UnifiedStreamEvent{Type: StreamEventContentStart, Content: &UnifiedStreamContent{Type: "text", Index: sc.index}})
+		}
+		events = append(events, &UnifiedStreamEvent{Type: StreamEventContentDelta, Content: &UnifiedStreamContent{Type: "text", Text: part.Text, Index: sc.index}})
+	}
+
+	if candidate.FinishReason != "" {
+		events = append(events, sc.closeText()...)
+		stop := &UnifiedStreamEvent{Type: StreamEventMessageStop, FinishReason: convertGeminiFinishReason(candidate.FinishReason, sc.toolCalls > 0)}
+		if chunk.UsageMetadata != nil {
+			stop.Usage = map[string]int{
+				"input_tokens":  chunk.UsageMetadata.PromptTokenCount,
+				"output_tokens": chunk.UsageMetadata.CandidatesTokenCount,
+			}
+		}
+		events = append(events, stop)
+	}
+	return events, nil
+}
+
+// closeText 结束正在输出的文本块
+func (sc *GeminiStreamConverter) closeText() []*UnifiedStreamEvent {
+	if !sc.textStarted {
+		return nil
+	}
+	sc.textStarted = false
+	index := sc.index
+	sc.index++
+	return []*UnifiedStreamEvent{{Type: StreamEventContentStop, Content: &UnifiedStreamContent{Index: index}}}
+}
+
+// BuildStreamEvent 从统一内部格式构建Gemini流式分块
+// 工具调用参数在内容块结束时整体输出（Gemini的functionCall不支持增量参数）
+func (sc *GeminiStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent) (*StreamChunk, error) {
+	switch event.Type {
+	case StreamEventMessageStart:
+		sc.modelVersion = event.Model
+		sc.responseID = event.MessageID
+
+	case StreamEventContentStart:
+		if event.Content != nil && event.Content.Type == "tool_use" {
+			sc.toolPending = true
+			sc.toolName = event.Content.ToolName
+			sc.toolInput.Reset()
+		}
+
+	case StreamEventContentDelta:
+		if event.Content == nil {
+			return nil, nil
+		}
+		if event.Content.Type == "tool_use" {
+			sc.toolPending = true
+			sc.toolInput.WriteString(event.Content.ToolInput)
+			return nil, nil
+		}
+		if event.Content.Text != "" {
+			return sc.chunk([]types.GeminiPart{{Text: event.Content.Text}}, "", nil), nil
+		}
+
+	case StreamEventContentStop:
+		if part := sc.flushTool(); part != nil {
+			return sc.chunk([]types.GeminiPart{*part}, "", nil), nil
+		}
+
+	case StreamEventMessageStop:
+		parts := []types.GeminiPart{}
+		if part := sc.flushTool(); part != nil {
+			parts = append(parts, *part)
+		}
+		var usage *types.GeminiUsageMetadata
+		if event.Usage != nil {
+			usage = &types.GeminiUsageMetadata{
+				PromptTokenCount:     event.Usage["input_tokens"],
+				CandidatesTokenCount: event.Usage["output_tokens"],
+				TotalTokenCount:      event.Usage["input_tokens"] + event.Usage["output_tokens"],
+			}
+		}
+		return sc.chunk(parts, convertFinishReasonToGemini(event.FinishReason), usage), nil
+	}
+
+	return nil, nil
+}
+
+// flushTool 输出已累积的工具调用
+func (sc *GeminiStreamConverter) flushTool() *types.GeminiPart {
+	if !sc.toolPending {
+		return nil
+	}
+	sc.toolPending = false
+	part := &types.GeminiPart{FunctionCall: &types.GeminiFunctionCall{
+		Name: sc.toolName,
+		Args: toolArguments(sc.toolInput.String()),
+	}}
+	sc.toolName = ""
+	sc.toolInput.Reset()
+	return part
+}
+
+// chunk 构建一个Gemini响应分块
+func (sc *GeminiStreamConverter) chunk(parts []types.GeminiPart, finishReason string, usage *types.GeminiUsageMetadata) *StreamChunk {
+	return &StreamChunk{
+		Data: &types.GeminiResponse{
+			Candidates: []types.GeminiCandidate{{
+				Content:      types.GeminiContent{Role: "model", Parts: parts},
+				FinishReason: finishReason,
+			}},
+			UsageMetadata: usage,
+			ModelVersion:  sc.modelVersion,
+			ResponseID:    sc.responseID,
+		},
+	}
+}
+
+// NeedPreEvents 返回需要自动生成的前置事件
+func (sc *GeminiStreamConverter) NeedPreEvents(event *UnifiedStreamEvent) []*UnifiedStreamEvent {
+	// Gemini的每个分块都是独立的完整响应，不需要前置事件
+	return nil
+}
@@ -0,0 +1,253 @@
+package converter
+
+import (
+	"encoding/json"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestGeminiConverter_ParseRequest(t *testing.T) {
+	body := `{
+		"model": "gemini-2.0-flash",
+		"stream": true,
+		"systemInstruction": {"parts": [{"text": "Be brief."}]},
+		"contents": [
+			{"role": "user", "parts": [{"text": "Weather in Paris?"}]},
+			{"role": "model", "parts": [{"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}]},
+			{"role": "user", "parts": [{"functionResponse": {"name": "get_weather", "response": {"temp": 21}}}]}
+		],
+		"generationConfig": {"maxOutputTokens": 256, "temperature": 0.2},
+		"tools": [{"functionDeclarations": [{"name": "get_weather", "parameters": {"type": "object"}}]}],
+		"toolConfig": {"functionCallingConfig": {"mode": "NONE"}}
+	}`
+
+	req, err := NewGeminiConverter().ParseRequest([]byte(body))
+	if err != nil {
+		t.Fatalf("ParseRequest() error = %v", err)
+	}
+	if req.Model != "gemini-2.0-flash" || req.Stream == nil || !*req.Stream {
+		t.Errorf("model/stream = %q/%v", req.Model, req.Stream)
+	}
+	if req.MaxTokens != 256 || req.Temperature != 0.2 || req.ToolChoice != "none" {
+		t.Errorf("generation config = %d/%v/%v", req.MaxTokens, req.Temperature, req.ToolChoice)
+	}
+	if len(req.Messages) != 4 {
+		t.Fatalf("messages = %+v", req.Messages)
+	}
+	if req.Messages[0].Role != "system" || req.Messages[0].Content != "Be brief." {
+		t.Errorf("system message = %+v", req.Messages[0])
+	}
+
+	call := req.Messages[2]
+	if call.Role != "assistant" || len(call.ToolCalls) != 1 {
+		t.Fatalf("tool call message = %+v", call)
+	}
+	function := call.ToolCalls[0]["function"].(map[string]interface{})
+	if function["name"] != "get_weather" || function["arguments"] != `{"city":"Paris"}` {
+		t.Errorf("tool call = %+v", function)
+	}
+
+	result := req.Messages[3]
+	if result.Role != "tool" || result.ToolCallID == nil || *result.ToolCallID != call.ToolCalls[0]["id"] {
+		t.Errorf("tool result should reference the call id, got %+v", result)
+	}
+	if len(req.Tools) != 1 || req.Tools[0]["type"] != "function" {
+		t.Errorf("tools = %+v", req.Tools)
+	}
+}
+
+func TestGeminiConverter_BuildRequest(t *testing.T) {
+	callID := "call_abc"
+	toolName := "get_weather"
+	request := &types.UnifiedRequest{
+		Model:     "gemini-2.0-flash",
+		MaxTokens: 100,
+		Messages: []types.Message{
+			{Role: "system", Content: "Be brief."},
+			{Role: "user", Content: "Weather in Paris?"},
+			{Role: "assistant", ToolCalls: []map[string]interface{}{{
+				"id":       callID,
+				"type":     "function",
+				"function": map[string]interface{}{"name": toolName, "arguments": `{"city":"Paris"}`},
+			}}},
+			{Role: "tool", Content: "21 degrees", ToolCallID: &callID},
+			{Role: "user", Content: "Thanks"},
+		},
+		Tools: []map[string]interface{}{{
+			"name":         toolName,
+			"input_schema": map[string]interface{}{"type": "object", "additionalProperties": false},
+		}},
+		ToolChoice: "required",
+	}
+
+	data, err := NewGeminiConverter().BuildRequest(request)
+	if err != nil {
+		t.Fatalf("BuildRequest() error = %v", err)
+	}
+	var req types.GeminiRequest
+	if err := json.Unmarshal(data, &req); err != nil {
+		t.Fatal(err)
+	}
+
+	if req.Model != "" || req.Stream != nil {
+		t.Error("model and stream belong in the upstream path, not the body")
+	}
+	if req.SystemInstruction == nil || req.SystemInstruction.Parts[0].Text != "Be brief." {
+		t.Errorf("systemInstruction = %+v", req.SystemInstruction)
+	}
+	// 工具结果与随后的用户消息合并为同一轮user内容
+	if len(req.Contents) != 3 {
+		t.Fatalf("contents = %+v", req.Contents)
+	}
+	if call := req.Contents[1].Parts[0].FunctionCall; req.Contents[1].Role != "model" || call == nil || call.Args["city"] != "Paris" {
+		t.Errorf("model turn = %+v", req.Contents[1])
+	}
+	last := req.Contents[2]
+	if last.Role != "user" || len(last.Parts) != 2 || last.Parts[0].FunctionResponse == nil {
+		t.Fatalf("last turn = %+v", last)
+	}
+	if response := last.Parts[0].FunctionResponse; response.Name != toolName || response.Response["content"] != "21 degrees" {
+		t.Errorf("functionResponse = %+v", response)
+	}
+	if req.GenerationConfig == nil || req.GenerationConfig.MaxOutputTokens != 100 {
+		t.Errorf("generationConfig = %+v", req.GenerationConfig)
+	}
+	schema := req.Tools[0].FunctionDeclarations[0].Parameters.(map[string]interface{})
+	if _, exists := schema["additionalProperties"]; exists {
+		t.Error("unsupported schema keywords should be removed")
+	}
+	if req.ToolConfig == nil || req.ToolConfig.FunctionCallingConfig.Mode != "ANY" {
+		t.Errorf("toolConfig = %+v", req.ToolConfig)
+	}
+}
+
+func TestGeminiConverter_Responses(t *testing.T) {
+	manager := NewManager()
+	gemini := `{
+		"candidates": [{"content": {"role": "model", "parts": [{"text": "It is sunny."}]}, "finishReason": "MAX_TOKENS", "index": 0}],
+		"usageMetadata": {"promptTokenCount": 12, "candidatesTokenCount": 4, "totalTokenCount": 16},
+		"modelVersion": "gemini-2.0-flash"
+	}`
+
+	data, err := manager.ConvertResponse(FormatGemini, FormatOpenAI, []byte(gemini))
+	if err != nil {
+		t.Fatalf("ConvertResponse(gemini->openai) error = %v", err)
+	}
+	var openai types.UnifiedResponse
+	if err := json.Unmarshal(data, &openai); err != nil {
+		t.Fatal(err)
+	}
+	if openai.Choices[0].Message.Content != "It is sunny." || openai.Choices[0].FinishReason != "length" || openai.Usage.PromptTokens != 12 {
+		t.Errorf("openai response = %s", data)
+	}
+
+	anthropic := `{"id":"msg_1","type":"message","role":"assistant","model":"claude-3-5-sonnet",
+		"content":[{"type":"text","text":"Checking."},{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{"city":"Paris"}}],
+		"stop_reason":"tool_use","usage":{"input_tokens":5,"output_tokens":7}}`
+	data, err = manager.ConvertResponse(FormatAnthropic, FormatGemini, []byte(anthropic))
+	if err != nil {
+		t.Fatalf("ConvertResponse(anthropic->gemini) error = %v", err)
+	}
+	var resp types.GeminiResponse
+	if err := json.Unmarshal(data, &resp); err != nil {
+		t.Fatal(err)
+	}
+	parts := resp.Candidates[0].Content.Parts
+	if len(parts) != 2 || parts[0].Text != "Checking." || parts[1].FunctionCall == nil || parts[1].FunctionCall.Args["city"] != "Paris" {
+		t.Errorf("gemini parts = %s", data)
+	}
+	if resp.UsageMetadata.PromptTokenCount != 5 || resp.UsageMetadata.CandidatesTokenCount != 7 {
+		t.Errorf("usageMetadata = %+v", resp.UsageMetadata)
+	}
+}
+
+// recordingWriter 记录流式写入结果
+type recordingWriter struct {
+	chunks []*StreamChunk
+	done   int
+}
+
+func (w *recordingWriter) WriteChunk(chunk *StreamChunk) error {
+	w.chunks = append(w.chunks, chunk)
+	return nil
+}
+
+func (w *recordingWriter) WriteDone() error {
+	w.done++
+	return nil
+}
+
+func TestGeminiStream_ToAnthropic(t *testing.T) {
+	stream := "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hel\"}]}}],\"modelVersion\":\"gemini-2.0-flash\"}\n\n" +
+		"data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"lo\"}]},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":3,\"candidatesTokenCount\":2}}\n\n"
+
+	writer := &recordingWriter{}
+	if err := NewManager().ProcessStream(strings.NewReader(stream), types.ProviderGoogle, FormatAnthropic, writer); err != nil {
+		t.Fatalf("ProcessStream() error = %v", err)
+	}
+
+	var events []string
+	for _, chunk := range writer.chunks {
+		events = append(events, chunk.EventType)
+	}
+	want := "message_start,content_block_start,content_block_delta,content_block_delta,content_block_stop,message_delta,message_stop"
+	if strings.Join(events, ",") != want {
+		t.Errorf("events = %v, want %s", events, want)
+	}
+	messageDelta := writer.chunks[len(writer.chunks)-2].Data.(map[string]interface{})
+	if reason := messageDelta["delta"].(map[string]interface{})["stop_reason"]; reason != "end_turn" {
+		t.Errorf("stop_reason = %v, want end_turn", reason)
+	}
+	if writer.done != 1 {
+		t.Errorf("WriteDone called %d times, want 1 (Gemini streams end at EOF)", writer.done)
+	}
+}
+
+func TestGeminiStream_FromOpenAIToolCall(t *testing.T) {
+	stream := "data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"get_weather\",\"arguments\":\"{\\\"city\\\":\"}}]}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"Paris\\\"}\"}}]}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n" +
+		"data: [DONE]\n\n"
+
+	writer := &recordingWriter{}
+	if err := NewManager().ProcessStream(strings.NewReader(stream), types.ProviderOpenAI, FormatGemini, writer); err != nil {
+		t.Fatalf("ProcessStream() error = %v", err)
+	}
+
+	var calls []*types.GeminiFunctionCall
+	for _, chunk := range writer.chunks {
+		resp, ok := chunk.Data.(*types.GeminiResponse)
+		if !ok {
+			t.Fatalf("chunk data = %T, want *types.GeminiResponse", chunk.Data)
+		}
+		for _, part := range resp.Candidates[0].Content.Parts {
+			if part.FunctionCall != nil {
+				calls = append(calls, part.FunctionCall)
+			}
+		}
+	}
+	if len(calls) != 1 || calls[0].Name != "get_weather" || calls[0].Args["city"] != "Paris" {
+		t.Errorf("function calls = %+v", calls)
+	}
+}
+
+func TestExpandUpstreamPath(t *testing.T) {
+	stream := true
+	tests := []struct {
+		path    string
+		request *types.UnifiedRequest
+		want    string
+	}{
+		{geminiUpstreamPath, &types.UnifiedRequest{Model: "gemini-2.0-flash"}, "/v1beta/models/gemini-2.0-flash:generateContent"},
+		{geminiUpstreamPath, &types.UnifiedRequest{Model: "gemini-2.0-flash", Stream: &stream}, "/v1beta/models/gemini-2.0-flash:streamGenerateContent?alt=sse"},
+		{"/v1/messages", &types.UnifiedRequest{Model: "claude-3-5-sonnet", Stream: &stream}, "/v1/messages"},
+	}
+
+	for _, tt := range tests {
+		if got := ExpandUpstreamPath(tt.path, tt.request); got != tt.want {
+			t.Errorf("ExpandUpstreamPath(%q) = %q, want %q", tt.path, got, tt.want)
+		}
+	}
+}
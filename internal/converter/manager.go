@@ -1,6 +1,8 @@
 package converter
 
 import (
+	"bytes"
+	"encoding/json"
 	"fmt"
 	"io"
 
@@ -51,6 +53,7 @@ func (m *Manager) ParseRequestWithModelRoute(requestBody []byte, endpoint string
 	if err != nil {
 		return nil, format, err
 	}
+	request.ExtraParams = extractExtraParams(requestBody, format)
 
 	// 如果有模型路由配置，替换模型名称
 	if modelRouteContext != nil && modelRouteContext.HasModelRoute() {
@@ -74,7 +77,11 @@ func (m *Manager) BuildUpstreamRequest(request *types.UnifiedRequest, provider t
 		return nil, fmt.Errorf("获取上游转换器失败: %w", err)
 	}
 
-	return converter.BuildRequest(request)
+	body, err := converter.BuildRequest(request)
+	if err != nil {
+		return nil, err
+	}
+	return mergeExtraParams(body, request.ExtraParams)
 }
 
 // ParseUpstreamResponse 解析上游响应
@@ -181,6 +188,35 @@ func (m *Manager) ProcessStreamWithModelRoute(reader io.Reader, provider types.P
 	return m.crossConverter.ConvertStream(upstreamFormat, clientFormat, reader, writer)
 }
 
+// ProviderFormat 返回提供商上游使用的格式
+func (m *Manager) ProviderFormat(provider types.Provider) Format {
+	return m.getProviderFormat(provider)
+}
+
+// IsPassthrough 客户端格式与上游提供商格式一致时，流式响应可以原样透传
+func (m *Manager) IsPassthrough(provider types.Provider, clientFormat Format) bool {
+	return m.getProviderFormat(provider) == clientFormat
+}
+
+// PassthroughModelRewriter 透传模式下恢复原始模型名的行改写函数，无模型路由时返回nil
+func (m *Manager) PassthroughModelRewriter(modelRouteContext *types.ModelRouteContext) func(line []byte) []byte {
+	if modelRouteContext == nil || !modelRouteContext.HasModelRoute() {
+		return nil
+	}
+
+	targetModel, _ := json.Marshal(modelRouteContext.TargetModel)
+	originalModel, _ := json.Marshal(modelRouteContext.OriginalModel)
+	target := append([]byte(`"model":`), targetModel...)
+	original := append([]byte(`"model":`), originalModel...)
+
+	return func(line []byte) []byte {
+		if !bytes.Contains(line, target) {
+			return nil
+		}
+		return bytes.ReplaceAll(line, target, original)
+	}
+}
+
 // InjectSystemPrompt 注入系统提示词
 func (m *Manager) InjectSystemPrompt(request *types.UnifiedRequest, provider types.Provider, upstreamType types.UpstreamType) {
 	// Anthropic转换器会自动处理Claude Code身份注入
@@ -293,6 +329,14 @@ func (w *modelReplaceStreamWriter) replaceModelInChunk(chunk *StreamChunk) {
 		return
 	}
 
+	// Gemini分块的模型名在modelVersion字段
+	if response, ok := chunk.Data.(*types.GeminiResponse); ok {
+		if response.ModelVersion == w.modelRouteContext.TargetModel {
+			response.ModelVersion = w.modelRouteContext.OriginalModel
+		}
+		return
+	}
+
 	// 如果Data是map类型，尝试替换model字段（兜底处理）
 	if dataMap, ok := chunk.Data.(map[string]interface{}); ok {
 		if modelField, exists := dataMap["model"]; exists {
@@ -306,10 +350,12 @@ func (w *modelReplaceStreamWriter) replaceModelInChunk(chunk *StreamChunk) {
 // getProviderFormat 根据提供商获取对应格式
 func (m *Manager) getProviderFormat(provider types.Provider) Format {
 	switch provider {
-	case types.ProviderAnthropic:
+	case types.ProviderAnthropic, types.ProviderBedrock: // Bedrock 上的 Anthropic 模型使用 Messages 格式
 		return FormatAnthropic
-	case types.ProviderOpenAI:
+	case types.ProviderOpenAI, types.ProviderOpenAICompatible:
 		return FormatOpenAI
+	case types.ProviderGoogle:
+		return FormatGemini
 	default:
 		return FormatOpenAI // 默认OpenAI格式，Qwen等也使用此格式
 	}
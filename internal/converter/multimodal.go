@@ -0,0 +1,227 @@
+package converter
+
+import (
+	"encoding/base64"
+	"fmt"
+	"net/http"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// ImageError 请求中的图片不符合目标提供商的要求（格式、大小、数量或不支持图片URL）
+type ImageError struct {
+	Index  int // 消息序号（从0开始）
+	Reason string
+}
+
+func (e *ImageError) Error() string {
+	return fmt.Sprintf("第 %d 条消息中的图片无效: %s", e.Index, e.Reason)
+}
+
+// imageLimits 提供商对图片输入的限制
+type imageLimits struct {
+	mimeTypes     []string
+	maxBytes      int  // 单张图片解码后的大小上限
+	maxTotalBytes int  // 请求中所有内嵌图片的总大小上限，0表示不限制
+	maxImages     int  // 单个请求的图片数量上限
+	remoteURL     bool // 是否支持远程图片URL（由提供商下载）
+}
+
+const megabyte = 1024 * 1024
+
+// imageLimitsFor 按提供商返回图片输入的限制（取自各提供商的公开文档）
+func imageLimitsFor(provider types.Provider, upstreamFormat Format) imageLimits {
+	switch {
+	case provider == types.ProviderBedrock:
+		return imageLimits{mimeTypes: []string{"image/jpeg", "image/png", "image/gif", "image/webp"}, maxBytes: 3750 * 1024, maxImages: 20}
+	case upstreamFormat == FormatAnthropic:
+		return imageLimits{mimeTypes: []string{"image/jpeg", "image/png", "image/gif", "image/webp"}, maxBytes: 5 * megabyte, maxTotalBytes: 32 * megabyte, maxImages: 100, remoteURL: true}
+	case upstreamFormat == FormatGemini:
+		return imageLimits{mimeTypes: []string{"image/jpeg", "image/png", "image/webp", "image/heic", "image/heif"}, maxBytes: 20 * megabyte, maxTotalBytes: 20 * megabyte, maxImages: 3000}
+	default:
+		return imageLimits{mimeTypes: []string{"image/jpeg", "image/png", "image/gif", "image/webp"}, maxBytes: 20 * megabyte, maxTotalBytes: 50 * megabyte, maxImages: 500, remoteURL: true}
+	}
+}
+
+// imageInput 从内容块中解析出的图片：内嵌图片有 MimeType 和 Data（base64），远程图片只有 URL
+type imageInput struct {
+	MimeType string
+	Data     string
+	URL      string
+	Detail   string // OpenAI 的 detail 参数，只在转发给 OpenAI 格式时保留
+}
+
+// NormalizeImages 校验消息中的图片（OpenAI 的 image_url 块和 Anthropic 的 image 块）是否符合目标提供商的
+// 格式、大小和数量限制，并转换为上游格式的图片块，避免不兼容的图片到达上游才被拒绝
+func NormalizeImages(request *types.UnifiedRequest, provider types.Provider, upstreamFormat Format) error {
+	limits := imageLimitsFor(provider, upstreamFormat)
+	images, totalBytes := 0, 0
+
+	for i, msg := range request.Messages {
+		blocks, ok := msg.Content.([]interface{})
+		if !ok {
+			continue
+		}
+
+		var converted []interface{}
+		for j, item := range blocks {
+			block, ok := item.(map[string]interface{})
+			if !ok {
+				continue
+			}
+			image, isImage, err := parseImageBlock(block)
+			if !isImage {
+				continue
+			}
+			if err != nil {
+				return &ImageError{Index: i, Reason: err.Error()}
+			}
+			if msg.Role != "user" {
+				return &ImageError{Index: i, Reason: fmt.Sprintf("图片只能出现在 user 消息中，实际为 %s", msg.Role)}
+			}
+
+			images++
+			if images > limits.maxImages {
+				return &ImageError{Index: i, Reason: fmt.Sprintf("图片数量超过 %s 的上限 %d", provider, limits.maxImages)}
+			}
+			size, err := validateImage(image, provider, limits)
+			if err != nil {
+				return &ImageError{Index: i, Reason: err.Error()}
+			}
+			totalBytes += size
+			if limits.maxTotalBytes > 0 && totalBytes > limits.maxTotalBytes {
+				return &ImageError{Index: i, Reason: fmt.Sprintf("内嵌图片总大小超过 %s 的上限 %dMB", provider, limits.maxTotalBytes/megabyte)}
+			}
+
+			// 只复制一次内容数组，不修改解析出的原始请求内容
+			if converted == nil {
+				converted = make([]interface{}, len(blocks))
+				copy(converted, blocks)
+			}
+			converted[j] = buildImageBlock(image, upstreamFormat)
+		}
+		if converted != nil {
+			request.Messages[i].Content = converted
+		}
+	}
+	return nil
+}
+
+// parseImageBlock 解析 OpenAI 的 image_url 块或 Anthropic 的 image 块，不是图片块时 isImage 为false
+func parseImageBlock(block map[string]interface{}) (image imageInput, isImage bool, err error) {
+	switch block["type"] {
+	case "image_url":
+		imageURL, _ := block["image_url"].(map[string]interface{})
+		url := getString(imageURL["url"])
+		if url == "" {
+			// 部分客户端直接把URL字符串作为 image_url
+			url = getString(block["image_url"])
+		}
+		image.Detail = getString(imageURL["detail"])
+		if url == "" {
+			return image, true, fmt.Errorf("image_url 缺少 url")
+		}
+		if !strings.HasPrefix(url, "data:") {
+			image.URL = url
+			return image, true, nil
+		}
+		header, data, ok := strings.Cut(strings.TrimPrefix(url, "data:"), ",")
+		if !ok || !strings.HasSuffix(header, ";base64") {
+			return image, true, fmt.Errorf("data URL 必须为 data:<类型>;base64,<数据> 格式")
+		}
+		image.MimeType = normalizeMimeType(strings.TrimSuffix(header, ";base64"))
+		image.Data = data
+		return image, true, nil
+	case "image":
+		source, _ := block["source"].(map[string]interface{})
+		switch getString(source["type"]) {
+		case "base64":
+			image.MimeType = normalizeMimeType(getString(source["media_type"]))
+			image.Data = getString(source["data"])
+			return image, true, nil
+		case "url":
+			image.URL = getString(source["url"])
+			if image.URL == "" {
+				return image, true, fmt.Errorf("图片来源缺少 url")
+			}
+			return image, true, nil
+		}
+		return image, true, fmt.Errorf("不支持的图片来源类型 %q（base64 或 url）", getString(source["type"]))
+	}
+	return image, false, nil
+}
+
+// normalizeMimeType 统一图片类型的大小写和 image/jpg 别名
+func normalizeMimeType(mimeType string) string {
+	mimeType = strings.ToLower(strings.TrimSpace(mimeType))
+	if mimeType == "image/jpg" {
+		return "image/jpeg"
+	}
+	return mimeType
+}
+
+// validateImage 检查图片的类型、大小和来源是否被提供商支持，返回内嵌图片解码后的大小
+func validateImage(image imageInput, provider types.Provider, limits imageLimits) (int, error) {
+	if image.URL != "" {
+		if !strings.HasPrefix(image.URL, "https://") && !strings.HasPrefix(image.URL, "http://") {
+			return 0, fmt.Errorf("图片URL必须为 http(s) 地址")
+		}
+		if !limits.remoteURL {
+			return 0, fmt.Errorf("%s 不支持图片URL，请使用 base64 内嵌图片", provider)
+		}
+		return 0, nil
+	}
+
+	supported := false
+	for _, allowed := range limits.mimeTypes {
+		if image.MimeType == allowed {
+			supported = true
+		}
+	}
+	if !supported {
+		return 0, fmt.Errorf("%s 不支持图片类型 %q（支持 %s）", provider, image.MimeType, strings.Join(limits.mimeTypes, "、"))
+	}
+
+	data, err := base64.StdEncoding.DecodeString(image.Data)
+	if err != nil {
+		return 0, fmt.Errorf("图片数据不是有效的 base64: %v", err)
+	}
+	if len(data) == 0 {
+		return 0, fmt.Errorf("图片数据为空")
+	}
+	if len(data) > limits.maxBytes {
+		return 0, fmt.Errorf("图片大小 %.1fMB 超过 %s 的上限 %.1fMB", float64(len(data))/megabyte, provider, float64(limits.maxBytes)/megabyte)
+	}
+
+	// 声明的类型与内容不符时上游通常会拒绝，能识别的格式提前检查
+	if detected := http.DetectContentType(data); strings.HasPrefix(detected, "image/") && detected != image.MimeType {
+		return 0, fmt.Errorf("声明的图片类型 %s 与内容 %s 不符", image.MimeType, detected)
+	}
+	return len(data), nil
+}
+
+// buildImageBlock 构建上游格式的图片块：Anthropic 为 image 块，其他格式为 OpenAI 的 image_url 块（Gemini 转换器从中读取内嵌图片）
+func buildImageBlock(image imageInput, upstreamFormat Format) map[string]interface{} {
+	if upstreamFormat == FormatAnthropic {
+		source := map[string]interface{}{"type": "url", "url": image.URL}
+		if image.URL == "" {
+			source = map[string]interface{}{
+				"type":       "base64",
+				"media_type": image.MimeType,
+				"data":       image.Data,
+			}
+		}
+		return map[string]interface{}{"type": "image", "source": source}
+	}
+
+	url := image.URL
+	if url == "" {
+		url = "data:" + image.MimeType + ";base64," + image.Data
+	}
+	imageURL := map[string]interface{}{"url": url}
+	if image.Detail != "" && upstreamFormat == FormatOpenAI {
+		imageURL["detail"] = image.Detail
+	}
+	return map[string]interface{}{"type": "image_url", "image_url": imageURL}
+}
@@ -0,0 +1,105 @@
+package converter
+
+import (
+	"encoding/base64"
+	"errors"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// pngData 最小的PNG文件头，足够 http.DetectContentType 识别
+var pngData = base64.StdEncoding.EncodeToString([]byte("\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"))
+
+func imageRequest(blocks ...interface{}) *types.UnifiedRequest {
+	return &types.UnifiedRequest{
+		Messages: []types.Message{{Role: "user", Content: append([]interface{}{map[string]interface{}{"type": "text", "text": "What is this?"}}, blocks...)}},
+	}
+}
+
+func openAIImage(url string) map[string]interface{} {
+	return map[string]interface{}{"type": "image_url", "image_url": map[string]interface{}{"url": url, "detail": "low"}}
+}
+
+func anthropicImage(mediaType, data string) map[string]interface{} {
+	return map[string]interface{}{"type": "image", "source": map[string]interface{}{"type": "base64", "media_type": mediaType, "data": data}}
+}
+
+func TestNormalizeImages_Convert(t *testing.T) {
+	// OpenAI 的 data URL 转发给 Anthropic 时转换为 base64 图片块
+	request := imageRequest(openAIImage("data:image/png;base64," + pngData))
+	if err := NormalizeImages(request, types.ProviderAnthropic, FormatAnthropic); err != nil {
+		t.Fatalf("NormalizeImages() error = %v", err)
+	}
+	block := request.Messages[0].Content.([]interface{})[1].(map[string]interface{})
+	source, _ := block["source"].(map[string]interface{})
+	if block["type"] != "image" || source["type"] != "base64" || source["media_type"] != "image/png" || source["data"] != pngData {
+		t.Errorf("Anthropic block = %v", block)
+	}
+
+	// 远程URL转换为 Anthropic 的 url 来源
+	request = imageRequest(openAIImage("https://example.com/cat.png"))
+	if err := NormalizeImages(request, types.ProviderAnthropic, FormatAnthropic); err != nil {
+		t.Fatalf("NormalizeImages() error = %v", err)
+	}
+	block = request.Messages[0].Content.([]interface{})[1].(map[string]interface{})
+	if source, _ := block["source"].(map[string]interface{}); source["type"] != "url" || source["url"] != "https://example.com/cat.png" {
+		t.Errorf("Anthropic url block = %v", block)
+	}
+
+	// Anthropic 的图片块转发给 OpenAI 时转换为 data URL，image/jpg 规范化为 image/jpeg
+	jpeg := base64.StdEncoding.EncodeToString([]byte("\xff\xd8\xff\xe0\x00\x10JFIF"))
+	request = imageRequest(anthropicImage("image/jpg", jpeg))
+	if err := NormalizeImages(request, types.ProviderOpenAI, FormatOpenAI); err != nil {
+		t.Fatalf("NormalizeImages() error = %v", err)
+	}
+	block = request.Messages[0].Content.([]interface{})[1].(map[string]interface{})
+	if imageURL, _ := block["image_url"].(map[string]interface{}); block["type"] != "image_url" || imageURL["url"] != "data:image/jpeg;base64,"+jpeg {
+		t.Errorf("OpenAI block = %v", block)
+	}
+}
+
+func TestNormalizeImages_Validation(t *testing.T) {
+	tests := []struct {
+		name     string
+		provider types.Provider
+		format   Format
+		request  *types.UnifiedRequest
+		reason   string
+	}{
+		{"gemini rejects urls", types.ProviderGoogle, FormatGemini, imageRequest(openAIImage("https://example.com/cat.png")), "不支持图片URL"},
+		{"bedrock rejects urls", types.ProviderBedrock, FormatAnthropic, imageRequest(openAIImage("https://example.com/cat.png")), "不支持图片URL"},
+		{"unsupported type", types.ProviderAnthropic, FormatAnthropic, imageRequest(anthropicImage("image/bmp", pngData)), "不支持图片类型"},
+		{"invalid base64", types.ProviderOpenAI, FormatOpenAI, imageRequest(openAIImage("data:image/png;base64,not base64!")), "base64"},
+		{"type mismatch", types.ProviderOpenAI, FormatOpenAI, imageRequest(anthropicImage("image/jpeg", pngData)), "不符"},
+		{"not a data url", types.ProviderOpenAI, FormatOpenAI, imageRequest(openAIImage("data:image/png,raw")), "data URL"},
+		{"too large", types.ProviderAnthropic, FormatAnthropic, imageRequest(anthropicImage("image/png", base64.StdEncoding.EncodeToString(make([]byte, 6*megabyte)))), "超过"},
+		{"assistant image", types.ProviderOpenAI, FormatOpenAI, &types.UnifiedRequest{Messages: []types.Message{
+			{Role: "user", Content: "Draw a cat"},
+			{Role: "assistant", Content: []interface{}{openAIImage("https://example.com/cat.png")}},
+		}}, "user"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			err := NormalizeImages(tt.request, tt.provider, tt.format)
+			var imageErr *ImageError
+			if !errors.As(err, &imageErr) || !strings.Contains(imageErr.Reason, tt.reason) {
+				t.Errorf("NormalizeImages() error = %v, want reason containing %q", err, tt.reason)
+			}
+		})
+	}
+}
+
+func TestNormalizeImages_Count(t *testing.T) {
+	blocks := make([]interface{}, 21)
+	for i := range blocks {
+		blocks[i] = anthropicImage("image/png", pngData)
+	}
+	if err := NormalizeImages(imageRequest(blocks...), types.ProviderBedrock, FormatAnthropic); err == nil || !strings.Contains(err.Error(), "数量") {
+		t.Errorf("NormalizeImages() error = %v, want image count error", err)
+	}
+	if err := NormalizeImages(imageRequest(blocks...), types.ProviderAnthropic, FormatAnthropic); err != nil {
+		t.Errorf("NormalizeImages() error = %v, Anthropic allows 100 images", err)
+	}
+}
@@ -0,0 +1,170 @@
+package converter
+
+import (
+	"fmt"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// NormalizeOptions 请求消息规范化选项
+type NormalizeOptions struct {
+	// MergeConsecutive 自动合并连续的同角色user/assistant消息
+	MergeConsecutive bool
+	// MaxMessages 消息数量上限，0表示不限制
+	MaxMessages int
+}
+
+// NormalizeRequest 在构建上游请求之前规范化消息，校验目标提供商的角色顺序约束和图片限制
+func (m *Manager) NormalizeRequest(request *types.UnifiedRequest, provider types.Provider, opts NormalizeOptions) error {
+	upstreamFormat := m.getProviderFormat(provider)
+	if err := normalizeMessages(request, upstreamFormat, opts); err != nil {
+		return err
+	}
+	return NormalizeImages(request, provider, upstreamFormat)
+}
+
+// normalizeMessages 规范化消息列表
+func normalizeMessages(request *types.UnifiedRequest, upstreamFormat Format, opts NormalizeOptions) error {
+	if len(request.Messages) == 0 {
+		return fmt.Errorf("消息列表不能为空")
+	}
+
+	if opts.MaxMessages > 0 && len(request.Messages) > opts.MaxMessages {
+		return fmt.Errorf("消息数量 %d 超过上限 %d", len(request.Messages), opts.MaxMessages)
+	}
+
+	for i, msg := range request.Messages {
+		switch msg.Role {
+		case "system", "user", "assistant", "tool":
+		default:
+			return fmt.Errorf("第 %d 条消息的角色 %q 无效", i, msg.Role)
+		}
+	}
+
+	if opts.MergeConsecutive {
+		request.Messages = mergeConsecutiveMessages(request.Messages)
+	}
+
+	switch upstreamFormat {
+	case FormatAnthropic:
+		return validateAnthropicRoleOrder(request.Messages)
+	case FormatOpenAI:
+		return validateOpenAIRoleOrder(request.Messages)
+	}
+	return nil
+}
+
+// mergeConsecutiveMessages 合并连续的同角色消息（不处理system、tool和包含工具调用的消息）
+func mergeConsecutiveMessages(messages []types.Message) []types.Message {
+	merged := make([]types.Message, 0, len(messages))
+	for _, msg := range messages {
+		if n := len(merged); n > 0 && canMergeMessages(merged[n-1], msg) {
+			merged[n-1].Content = mergeContent(merged[n-1].Content, msg.Content)
+			continue
+		}
+		merged = append(merged, msg)
+	}
+	return merged
+}
+
+// canMergeMessages 检查两条消息是否可以合并
+func canMergeMessages(prev, next types.Message) bool {
+	if prev.Role != next.Role || (prev.Role != "user" && prev.Role != "assistant") {
+		return false
+	}
+	if prev.ToolCalls != nil || next.ToolCalls != nil {
+		return false
+	}
+	return isMergeableContent(prev.Content) && isMergeableContent(next.Content)
+}
+
+// isMergeableContent 只合并字符串和内容块数组
+func isMergeableContent(content interface{}) bool {
+	switch content.(type) {
+	case string, []interface{}:
+		return true
+	}
+	return false
+}
+
+// mergeContent 合并两段消息内容，字符串之间用空行连接，否则统一转换为内容块数组
+func mergeContent(a, b interface{}) interface{} {
+	aText, aIsString := a.(string)
+	bText, bIsString := b.(string)
+	if aIsString && bIsString {
+		if aText == "" {
+			return bText
+		}
+		if bText == "" {
+			return aText
+		}
+		return aText + "\n\n" + bText
+	}
+
+	blocks := toContentBlocks(a)
+	return append(blocks, toContentBlocks(b)...)
+}
+
+// toContentBlocks 将消息内容转换为内容块数组
+func toContentBlocks(content interface{}) []interface{} {
+	switch v := content.(type) {
+	case string:
+		if v == "" {
+			return nil
+		}
+		return []interface{}{map[string]interface{}{"type": "text", "text": v}}
+	case []interface{}:
+		blocks := make([]interface{}, len(v))
+		copy(blocks, v)
+		return blocks
+	}
+	return nil
+}
+
+// validateAnthropicRoleOrder Anthropic要求对话以user开始，且user/assistant交替出现
+func validateAnthropicRoleOrder(messages []types.Message) error {
+	prevRole := ""
+	prevOriginalRole := "" // 上一条非system消息的原始角色（system消息可能出现在对话中间）
+	for i, msg := range messages {
+		if msg.Role == "system" {
+			continue
+		}
+
+		// tool消息在Anthropic中以user角色的tool_result发送
+		role := msg.Role
+		if role == "tool" {
+			role = "user"
+		}
+
+		if prevRole == "" && role != "user" {
+			return fmt.Errorf("上游为Anthropic时第一条对话消息必须为user，实际为 %s（第 %d 条）", msg.Role, i)
+		}
+		if role == prevRole && msg.Role != "tool" && prevOriginalRole != "tool" {
+			return fmt.Errorf("上游为Anthropic时不支持连续的 %s 消息（第 %d 条），可开启 merge_consecutive_messages 自动合并", msg.Role, i)
+		}
+		prevRole = role
+		prevOriginalRole = msg.Role
+	}
+
+	if prevRole == "" {
+		return fmt.Errorf("至少需要一条非system消息")
+	}
+	return nil
+}
+
+// validateOpenAIRoleOrder OpenAI要求tool消息紧跟在带tool_calls的assistant消息（或其他tool消息）之后
+func validateOpenAIRoleOrder(messages []types.Message) error {
+	for i, msg := range messages {
+		if msg.Role != "tool" {
+			continue
+		}
+		if i == 0 {
+			return fmt.Errorf("第 %d 条tool消息之前缺少带tool_calls的assistant消息", i)
+		}
+		prev := messages[i-1]
+		if prev.Role != "tool" && (prev.Role != "assistant" || prev.ToolCalls == nil) {
+			return fmt.Errorf("第 %d 条tool消息之前缺少带tool_calls的assistant消息", i)
+		}
+	}
+	return nil
+}
@@ -0,0 +1,153 @@
+package converter
+
+import (
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestNormalizeMessages_MergeConsecutive(t *testing.T) {
+	request := &types.UnifiedRequest{
+		Messages: []types.Message{
+			{Role: "system", Content: "You are helpful"},
+			{Role: "user", Content: "Hello"},
+			{Role: "user", Content: "Are you there?"},
+			{Role: "assistant", Content: "Yes"},
+			{Role: "user", Content: []interface{}{map[string]interface{}{"type": "text", "text": "First"}}},
+			{Role: "user", Content: "Second"},
+		},
+	}
+
+	err := normalizeMessages(request, FormatAnthropic, NormalizeOptions{MergeConsecutive: true})
+	if err != nil {
+		t.Fatalf("规范化失败: %v", err)
+	}
+
+	if len(request.Messages) != 4 {
+		t.Fatalf("期望合并后4条消息, 实际 %d", len(request.Messages))
+	}
+	if request.Messages[1].Content != "Hello\n\nAre you there?" {
+		t.Errorf("字符串内容合并错误: %v", request.Messages[1].Content)
+	}
+
+	blocks, ok := request.Messages[3].Content.([]interface{})
+	if !ok || len(blocks) != 2 {
+		t.Fatalf("期望合并为2个内容块, 实际 %v", request.Messages[3].Content)
+	}
+	if text := blocks[1].(map[string]interface{})["text"]; text != "Second" {
+		t.Errorf("内容块合并错误: %v", text)
+	}
+}
+
+func TestNormalizeMessages_AnthropicRoleOrder(t *testing.T) {
+	tests := []struct {
+		name     string
+		messages []types.Message
+		opts     NormalizeOptions
+		wantErr  bool
+	}{
+		{
+			name:     "assistant first",
+			messages: []types.Message{{Role: "assistant", Content: "Hi"}},
+			wantErr:  true,
+		},
+		{
+			name: "consecutive user without merge",
+			messages: []types.Message{
+				{Role: "user", Content: "a"},
+				{Role: "user", Content: "b"},
+			},
+			wantErr: true,
+		},
+		{
+			name: "consecutive user with merge",
+			messages: []types.Message{
+				{Role: "user", Content: "a"},
+				{Role: "user", Content: "b"},
+			},
+			opts: NormalizeOptions{MergeConsecutive: true},
+		},
+		{
+			name: "tool result followed by user",
+			messages: []types.Message{
+				{Role: "user", Content: "weather?"},
+				{Role: "assistant", ToolCalls: []map[string]interface{}{{"id": "call_1"}}},
+				{Role: "tool", Content: "sunny"},
+				{Role: "user", Content: "thanks"},
+			},
+		},
+		{
+			// 中间的system消息不影响对tool结果之后user消息的判断
+			name: "system between tool result and user",
+			messages: []types.Message{
+				{Role: "user", Content: "weather?"},
+				{Role: "assistant", ToolCalls: []map[string]interface{}{{"id": "call_1"}}},
+				{Role: "tool", Content: "sunny"},
+				{Role: "system", Content: "answer briefly"},
+				{Role: "user", Content: "thanks"},
+			},
+		},
+		{
+			name: "system between consecutive users",
+			messages: []types.Message{
+				{Role: "user", Content: "a"},
+				{Role: "system", Content: "x"},
+				{Role: "user", Content: "b"},
+			},
+			wantErr: true,
+		},
+		{
+			name:     "only system",
+			messages: []types.Message{{Role: "system", Content: "x"}},
+			wantErr:  true,
+		},
+		{
+			name:     "invalid role",
+			messages: []types.Message{{Role: "robot", Content: "x"}},
+			wantErr:  true,
+		},
+		{
+			name: "too many messages",
+			messages: []types.Message{
+				{Role: "user", Content: "a"},
+				{Role: "assistant", Content: "b"},
+				{Role: "user", Content: "c"},
+			},
+			opts:    NormalizeOptions{MaxMessages: 2},
+			wantErr: true,
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			request := &types.UnifiedRequest{Messages: tt.messages}
+			err := normalizeMessages(request, FormatAnthropic, tt.opts)
+			if (err != nil) != tt.wantErr {
+				t.Errorf("期望错误=%v, 实际 %v", tt.wantErr, err)
+			}
+		})
+	}
+}
+
+func TestNormalizeMessages_OpenAIToolOrder(t *testing.T) {
+	request := &types.UnifiedRequest{
+		Messages: []types.Message{
+			{Role: "user", Content: "weather?"},
+			{Role: "tool", Content: "sunny"},
+		},
+	}
+	if err := normalizeMessages(request, FormatOpenAI, NormalizeOptions{}); err == nil {
+		t.Error("缺少tool_calls的tool消息应当返回错误")
+	}
+
+	// OpenAI允许连续的user消息，不合并时也不报错
+	request = &types.UnifiedRequest{
+		Messages: []types.Message{
+			{Role: "user", Content: "a"},
+			{Role: "user", Content: "b"},
+		},
+	}
+	if err := normalizeMessages(request, FormatOpenAI, NormalizeOptions{}); err != nil {
+		t.Errorf("不应返回错误: %v", err)
+	}
+}
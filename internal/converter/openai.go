@@ -12,7 +12,15 @@ type OpenAIConverter struct{}
 
 // OpenAIStreamConverter OpenAI流式转换器（有状态）
 type OpenAIStreamConverter struct {
-	// 目前OpenAI转换器不需要保存状态，保留结构体以便将来扩展
+	// 解析上游OpenAI流
+	blockOpen  bool
+	blockType  string      // 当前内容块的类型：text 或 tool_use
+	blockIndex int         // 当前内容块的索引
+	nextIndex  int         // 下一个内容块的索引
+	toolBlocks map[int]int // tool_calls 的 index -> 内容块索引
+
+	// 构建返回给客户端的OpenAI流
+	toolIndexes map[int]int // 内容块索引 -> tool_calls 的 index
 }
 
 // NewOpenAIConverter 创建OpenAI转换器
@@ -61,7 +69,9 @@ func (c *OpenAIConverter) BuildRequest(request *types.UnifiedRequest) ([]byte, e
 		Stream:      request.Stream,
 		TopP:        request.TopP,
 		Tools:       c.convertTools(request.Tools),
-		ToolChoice:  request.ToolChoice,
+	}
+	if len(req.Tools) > 0 {
+		req.ToolChoice = toolChoiceToOpenAI(request.ToolChoice)
 	}
 
 	return json.Marshal(req)
@@ -73,6 +83,9 @@ func (c *OpenAIConverter) ParseResponse(data []byte) (*types.UnifiedResponse, er
 	if err := json.Unmarshal(data, &resp); err != nil {
 		return nil, fmt.Errorf("解析OpenAI响应失败: %w", err)
 	}
+	for i := range resp.Choices {
+		resp.Choices[i].FinishReason = NormalizeFinishReason(resp.Choices[i].FinishReason)
+	}
 
 	// 处理可能的额外字段
 	var rawResp map[string]interface{}
@@ -167,13 +180,16 @@ func (c *OpenAIConverter) convertTools(tools []map[string]interface{}) []map[str
 		// 如果是Anthropic格式，转换为OpenAI格式
 		if _, hasName := tool["name"]; hasName {
 			if _, hasInputSchema := tool["input_schema"]; hasInputSchema {
+				function := map[string]interface{}{
+					"name":       tool["name"],
+					"parameters": tool["input_schema"],
+				}
+				if description, ok := tool["description"].(string); ok && description != "" {
+					function["description"] = description
+				}
 				openaiTool := map[string]interface{}{
-					"type": "function",
-					"function": map[string]interface{}{
-						"name":        tool["name"],
-						"description": tool["description"],
-						"parameters":  tool["input_schema"],
-					},
+					"type":     "function",
+					"function": function,
 				}
 				converted = append(converted, openaiTool)
 			} else {
@@ -193,7 +209,8 @@ func (c *OpenAIConverter) NewStreamConverter() StreamConverter {
 	return &OpenAIStreamConverter{}
 }
 
-// ParseStreamEvent 解析OpenAI流式事件到统一内部格式
+// ParseStreamEvent 解析OpenAI流式事件到统一内部格式。
+// 文本和每个工具调用分别对应一个内容块，并行的工具调用按 tool_calls 的 index 区分，切换内容块时先结束上一个
 func (sc *OpenAIStreamConverter) ParseStreamEvent(eventType string, data []byte) ([]*UnifiedStreamEvent, error) {
 	var eventData map[string]interface{}
 	if err := json.Unmarshal(data, &eventData); err != nil {
@@ -201,107 +218,164 @@ func (sc *OpenAIStreamConverter) ParseStreamEvent(eventType string, data []byte)
 	}
 
 	// OpenAI格式没有命名事件，需要从数据结构判断事件类型
-	if choices, ok := eventData["choices"].([]interface{}); ok && len(choices) > 0 {
-		choice := choices[0].(map[string]interface{})
+	choices, ok := eventData["choices"].([]interface{})
+	if !ok || len(choices) == 0 {
+		return nil, nil // 跳过不识别的事件（如只携带usage的分块）
+	}
+	choice, _ := choices[0].(map[string]interface{})
+	delta, ok := choice["delta"].(map[string]interface{})
+	if !ok {
+		return nil, nil
+	}
+
+	var events []*UnifiedStreamEvent
 
-		// 检查是否有delta
-		if delta, ok := choice["delta"].(map[string]interface{}); ok {
-			// 检查finish_reason确定是否结束
-			if finishReason, ok := choice["finish_reason"]; ok && finishReason != nil {
-				events := []*UnifiedStreamEvent{}
+	// 处理内容增量
+	if content, ok := delta["content"].(string); ok && content != "" {
+		if sc.blockOpen && sc.blockType != "text" {
+			events = append(events, sc.closeBlock())
+		}
+		if !sc.blockOpen {
+			sc.openBlock("text")
+		}
+		events = append(events, &UnifiedStreamEvent{
+			Type: StreamEventContentDelta,
+			Content: &UnifiedStreamContent{
+				Type:  "text",
+				Text:  content,
+				Index: sc.blockIndex,
+			},
+		})
+	}
 
-				// 先发送ContentStop（无论是工具调用还是普通文本）
+	// 处理工具调用增量：首次出现的调用携带ID和函数名，之后只有参数片段
+	if toolCalls, ok := delta["tool_calls"].([]interface{}); ok {
+		for i, item := range toolCalls {
+			toolCall, ok := item.(map[string]interface{})
+			if !ok {
+				continue
+			}
+			position := i
+			if index, ok := toolCall["index"].(float64); ok {
+				position = int(index)
+			}
+			function, _ := toolCall["function"].(map[string]interface{})
+
+			index, seen := sc.toolBlocks[position]
+			if !seen {
+				if sc.blockOpen {
+					events = append(events, sc.closeBlock())
+				}
+				index = sc.openBlock("tool_use")
+				if sc.toolBlocks == nil {
+					sc.toolBlocks = make(map[int]int)
+				}
+				sc.toolBlocks[position] = index
 				events = append(events, &UnifiedStreamEvent{
-					Type: StreamEventContentStop,
+					Type: StreamEventContentStart,
 					Content: &UnifiedStreamContent{
-						Index: 0,
+						Type:     "tool_use",
+						ToolID:   getString(toolCall["id"]),
+						ToolName: getString(function["name"]),
+						Index:    index,
 					},
 				})
-
-				// 然后发送MessageStop（不设置IsDone，让[DONE]来触发结束）
-				events = append(events, &UnifiedStreamEvent{
-					Type:   StreamEventMessageStop,
-					IsDone: false,
-				})
-
-				return events, nil
 			}
 
-			// 处理内容增量
-			if content, ok := delta["content"].(string); ok && content != "" {
-				return []*UnifiedStreamEvent{{
+			if arguments := getString(function["arguments"]); arguments != "" {
+				events = append(events, &UnifiedStreamEvent{
 					Type: StreamEventContentDelta,
 					Content: &UnifiedStreamContent{
-						Type:  "text",
-						Text:  content,
-						Index: 0,
+						Type:      "tool_use",
+						ToolInput: arguments,
+						Index:     index,
 					},
-				}}, nil
+				})
 			}
+		}
+	}
 
-			// 处理工具调用增量
-			if toolCalls, ok := delta["tool_calls"].([]interface{}); ok && len(toolCalls) > 0 {
-				toolCall := toolCalls[0].(map[string]interface{})
-
-				// 提取工具调用ID（如果存在）
-				toolID, _ := toolCall["id"].(string)
-
-				if function, ok := toolCall["function"].(map[string]interface{}); ok {
-					// 提取工具名称（第一次调用时会有）
-					toolName, _ := function["name"].(string)
-					arguments, _ := function["arguments"].(string)
-
-					// 如果有工具名称，说明这是第一个chunk，需要生成ContentStart事件
-					if toolName != "" {
-						events := []*UnifiedStreamEvent{
-							{
-								Type: StreamEventContentStart,
-								Content: &UnifiedStreamContent{
-									Type:     "tool_use",
-									ToolID:   toolID,
-									ToolName: toolName,
-									Index:    0,
-								},
-							},
-						}
+	// finish_reason 结束当前内容块和消息（不设置IsDone，让[DONE]来触发结束）
+	if finishReason, ok := choice["finish_reason"].(string); ok && finishReason != "" {
+		if sc.blockOpen {
+			events = append(events, sc.closeBlock())
+		}
+		events = append(events, &UnifiedStreamEvent{
+			Type:         StreamEventMessageStop,
+			IsDone:       false,
+			FinishReason: NormalizeFinishReason(finishReason),
+		})
+	}
 
-						// 如果同时有arguments，也生成ContentDelta事件
-						if arguments != "" {
-							events = append(events, &UnifiedStreamEvent{
-								Type: StreamEventContentDelta,
-								Content: &UnifiedStreamContent{
-									Type:      "tool_use",
-									ToolInput: arguments,
-									Index:     0,
-								},
-							})
-						}
+	return events, nil
+}
 
-						return events, nil
-					}
+// openBlock 开始一个新的内容块，返回其索引
+func (sc *OpenAIStreamConverter) openBlock(blockType string) int {
+	sc.blockOpen = true
+	sc.blockType = blockType
+	sc.blockIndex = sc.nextIndex
+	sc.nextIndex++
+	return sc.blockIndex
+}
 
-					// 只有arguments的增量更新
-					if arguments != "" {
-						return []*UnifiedStreamEvent{{
-							Type: StreamEventContentDelta,
-							Content: &UnifiedStreamContent{
-								Type:      "tool_use",
-								ToolInput: arguments,
-								Index:     0,
-							},
-						}}, nil
-					}
-				}
-			}
-		}
+// closeBlock 结束当前内容块
+func (sc *OpenAIStreamConverter) closeBlock() *UnifiedStreamEvent {
+	sc.blockOpen = false
+	return &UnifiedStreamEvent{
+		Type:    StreamEventContentStop,
+		Content: &UnifiedStreamContent{Index: sc.blockIndex},
 	}
+}
 
-	return nil, nil // 跳过不识别的事件
+// toolCallIndex 返回内容块对应的 tool_calls 序号：OpenAI 的工具调用从0开始编号，不计入文本块
+func (sc *OpenAIStreamConverter) toolCallIndex(blockIndex int) int {
+	if sc.toolIndexes == nil {
+		sc.toolIndexes = make(map[int]int)
+	}
+	index, ok := sc.toolIndexes[blockIndex]
+	if !ok {
+		index = len(sc.toolIndexes)
+		sc.toolIndexes[blockIndex] = index
+	}
+	return index
 }
 
 // BuildStreamEvent 从统一内部格式构建OpenAI流式事件
 func (sc *OpenAIStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent) (*StreamChunk, error) {
 	switch event.Type {
+	case StreamEventContentStart:
+		// 工具调用的首个分块携带ID和函数名
+		if event.Content != nil && event.Content.Type == "tool_use" {
+			openAIData := map[string]interface{}{
+				"choices": []interface{}{
+					map[string]interface{}{
+						"index": 0,
+						"delta": map[string]interface{}{
+							"tool_calls": []interface{}{
+								map[string]interface{}{
+									"index": sc.toolCallIndex(event.Content.Index),
+									"id":    event.Content.ToolID,
+									"type":  "function",
+									"function": map[string]interface{}{
+										"name":      event.Content.ToolName,
+										"arguments": "",
+									},
+								},
+							},
+						},
+					},
+				},
+			}
+
+			return &StreamChunk{
+				EventType: "",
+				Data:      openAIData,
+				Tokens:    0,
+				IsDone:    false,
+			}, nil
+		}
+
 	case StreamEventContentDelta:
 		if event.Content != nil {
 			var delta map[string]interface{}
@@ -315,7 +389,7 @@ func (sc *OpenAIStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent) (*S
 				delta = map[string]interface{}{
 					"tool_calls": []interface{}{
 						map[string]interface{}{
-							"index": event.Content.Index,
+							"index": sc.toolCallIndex(event.Content.Index),
 							"function": map[string]interface{}{
 								"arguments": event.Content.ToolInput,
 							},
@@ -327,7 +401,7 @@ func (sc *OpenAIStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent) (*S
 			openAIData := map[string]interface{}{
 				"choices": []interface{}{
 					map[string]interface{}{
-						"index": event.Content.Index,
+						"index": 0,
 						"delta": delta,
 					},
 				},
@@ -342,7 +416,7 @@ func (sc *OpenAIStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent) (*S
 		}
 
 	case StreamEventMessageStop:
-		finishReason := "stop"
+		finishReason := openAIFinishReason(event.FinishReason)
 		openAIData := map[string]interface{}{
 			"choices": []interface{}{
 				map[string]interface{}{
@@ -353,11 +427,12 @@ func (sc *OpenAIStreamConverter) BuildStreamEvent(event *UnifiedStreamEvent) (*S
 			},
 		}
 
+		// 携带finish_reason的分块需要写给客户端，[DONE]由流结束时的WriteDone输出
 		return &StreamChunk{
 			EventType: "",
 			Data:      openAIData,
 			Tokens:    0,
-			IsDone:    true,
+			IsDone:    false,
 		}, nil
 	}
 
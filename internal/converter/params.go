@@ -0,0 +1,119 @@
+package converter
+
+import (
+	"encoding/json"
+	"fmt"
+	"sort"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 额外参数过滤模式
+const (
+	ParamModeAllowlist   = "allowlist"   // 只转发目标提供商允许列表中的额外参数（默认）
+	ParamModePassthrough = "passthrough" // 原样转发所有额外参数
+)
+
+// ParamFilter 客户端请求中转换器未解析的顶层参数（额外参数）的过滤规则
+type ParamFilter struct {
+	// Passthrough 不过滤，所有额外参数都转发给上游
+	Passthrough bool
+	// Allow 按提供商替换内置的允许列表
+	Allow map[types.Provider][]string
+}
+
+// knownParams 各客户端格式中由转换器解析的顶层字段，其余字段作为额外参数保留
+var knownParams = map[Format]map[string]bool{
+	FormatOpenAI: {
+		"model": true, "messages": true, "max_tokens": true, "temperature": true,
+		"stream": true, "top_p": true, "tools": true, "tool_choice": true,
+	},
+	FormatAnthropic: {
+		"model": true, "messages": true, "max_tokens": true, "temperature": true,
+		"stream": true, "system": true, "metadata": true, "tools": true, "tool_choice": true,
+	},
+	FormatGemini: {
+		"model": true, "stream": true, "contents": true, "systemInstruction": true,
+		"generationConfig": true, "tools": true, "toolConfig": true,
+	},
+}
+
+// defaultAllowedParams 按上游格式内置的额外参数允许列表，只包含该提供商API支持的参数
+var defaultAllowedParams = map[Format][]string{
+	FormatOpenAI: {
+		"stop", "presence_penalty", "frequency_penalty", "seed", "n", "user", "response_format",
+		"logit_bias", "logprobs", "top_logprobs", "parallel_tool_calls", "stream_options",
+		"max_completion_tokens", "reasoning_effort", "service_tier",
+	},
+	FormatAnthropic: {
+		"stop_sequences", "top_k", "top_p", "thinking", "service_tier",
+	},
+	FormatGemini: {
+		"safetySettings", "cachedContent",
+	},
+}
+
+// extractExtraParams 提取请求中转换器未解析的顶层参数
+func extractExtraParams(data []byte, format Format) map[string]json.RawMessage {
+	var fields map[string]json.RawMessage
+	if err := json.Unmarshal(data, &fields); err != nil {
+		return nil
+	}
+
+	known := knownParams[format]
+	var extras map[string]json.RawMessage
+	for name, value := range fields {
+		if known[name] {
+			continue
+		}
+		if extras == nil {
+			extras = make(map[string]json.RawMessage)
+		}
+		extras[name] = value
+	}
+	return extras
+}
+
+// FilterParams 按目标提供商的允许列表删除请求中的额外参数，返回被删除的参数名（已排序）
+func (m *Manager) FilterParams(request *types.UnifiedRequest, provider types.Provider, filter ParamFilter) []string {
+	if filter.Passthrough || len(request.ExtraParams) == 0 {
+		return nil
+	}
+
+	allowList, ok := filter.Allow[provider]
+	if !ok {
+		allowList = defaultAllowedParams[m.getProviderFormat(provider)]
+	}
+	allowed := make(map[string]bool, len(allowList))
+	for _, name := range allowList {
+		allowed[name] = true
+	}
+
+	var stripped []string
+	for name := range request.ExtraParams {
+		if !allowed[name] {
+			stripped = append(stripped, name)
+			delete(request.ExtraParams, name)
+		}
+	}
+	sort.Strings(stripped)
+	return stripped
+}
+
+// mergeExtraParams 将额外参数写入转换后的上游请求体，不覆盖转换器已生成的字段
+func mergeExtraParams(body []byte, extras map[string]json.RawMessage) ([]byte, error) {
+	if len(extras) == 0 {
+		return body, nil
+	}
+
+	var fields map[string]json.RawMessage
+	if err := json.Unmarshal(body, &fields); err != nil {
+		return nil, fmt.Errorf("合并额外参数失败: %w", err)
+	}
+	for name, value := range extras {
+		if _, exists := fields[name]; !exists {
+			fields[name] = value
+		}
+	}
+	return json.Marshal(fields)
+}
@@ -0,0 +1,67 @@
+package converter
+
+import (
+	"encoding/json"
+	"reflect"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestExtraParams_FilterAndMerge(t *testing.T) {
+	body := []byte(`{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}],"seed":7,"top_k":40,"response_format":{"type":"json_object"}}`)
+
+	manager := NewManager()
+	request, _, err := manager.ParseRequest(body, "/v1/chat/completions")
+	if err != nil {
+		t.Fatalf("解析请求失败: %v", err)
+	}
+	if len(request.ExtraParams) != 3 {
+		t.Fatalf("期望3个额外参数, 实际 %v", request.ExtraParams)
+	}
+
+	// 转发给Anthropic时只保留Anthropic支持的参数
+	stripped := manager.FilterParams(request, types.ProviderAnthropic, ParamFilter{})
+	if !reflect.DeepEqual(stripped, []string{"response_format", "seed"}) {
+		t.Errorf("删除的参数 = %v", stripped)
+	}
+
+	upstream, err := manager.BuildUpstreamRequest(request, types.ProviderAnthropic)
+	if err != nil {
+		t.Fatalf("构建上游请求失败: %v", err)
+	}
+	var fields map[string]interface{}
+	if err := json.Unmarshal(upstream, &fields); err != nil {
+		t.Fatalf("上游请求不是JSON对象: %v", err)
+	}
+	if fields["top_k"] != float64(40) {
+		t.Errorf("top_k 应被转发, 实际 %v", fields["top_k"])
+	}
+	if _, exists := fields["seed"]; exists {
+		t.Error("seed 不应转发给Anthropic")
+	}
+	if fields["model"] != "gpt-4o" {
+		t.Errorf("额外参数不应覆盖转换器生成的字段: model = %v", fields["model"])
+	}
+}
+
+func TestFilterParams_Modes(t *testing.T) {
+	newRequest := func() *types.UnifiedRequest {
+		return &types.UnifiedRequest{ExtraParams: map[string]json.RawMessage{
+			"seed":        json.RawMessage(`1`),
+			"custom_flag": json.RawMessage(`true`),
+		}}
+	}
+	manager := NewManager()
+
+	request := newRequest()
+	if stripped := manager.FilterParams(request, types.ProviderOpenAI, ParamFilter{Passthrough: true}); stripped != nil || len(request.ExtraParams) != 2 {
+		t.Errorf("passthrough 模式不应删除参数: %v", stripped)
+	}
+
+	request = newRequest()
+	stripped := manager.FilterParams(request, types.ProviderOpenAI, ParamFilter{Allow: map[types.Provider][]string{types.ProviderOpenAI: {"custom_flag"}}})
+	if !reflect.DeepEqual(stripped, []string{"seed"}) || request.ExtraParams["custom_flag"] == nil {
+		t.Errorf("配置的允许列表应替换内置列表: 删除 %v, 保留 %v", stripped, request.ExtraParams)
+	}
+}
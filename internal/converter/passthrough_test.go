@@ -0,0 +1,66 @@
+package converter
+
+import (
+	"bytes"
+	"os"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestForwardSSEStream_PreservesStream(t *testing.T) {
+	data, err := os.ReadFile("testdata/stream/stream_anthropic_basic.txt")
+	if err != nil {
+		t.Fatalf("读取测试数据失败: %v", err)
+	}
+
+	var out bytes.Buffer
+	flushes := 0
+	doneCalls := 0
+	err = ForwardSSEStream(bytes.NewReader(data), &out, func() { flushes++ }, PassthroughOptions{
+		OnDone: func() { doneCalls++ },
+	})
+	if err != nil {
+		t.Fatalf("透传失败: %v", err)
+	}
+
+	if out.String() != string(data) {
+		t.Error("透传模式不应修改流内容")
+	}
+	if flushes == 0 {
+		t.Error("应在事件边界刷新")
+	}
+	if doneCalls != 1 {
+		t.Errorf("OnDone应调用一次, 实际 %d", doneCalls)
+	}
+}
+
+func TestForwardSSEStream_OnDoneBeforeDoneMarker(t *testing.T) {
+	stream := "data: {\"id\":\"1\",\"model\":\"gpt-4-turbo\"}\n\ndata: [DONE]\n\n"
+
+	manager := NewManager()
+	rewrite := manager.PassthroughModelRewriter(&types.ModelRouteContext{
+		Enabled:        true,
+		OriginalModel:  "gpt-4",
+		TargetModel:    "gpt-4-turbo",
+		TargetProvider: types.ProviderOpenAI,
+	})
+	if rewrite == nil {
+		t.Fatal("存在模型路由时应返回改写函数")
+	}
+
+	var out bytes.Buffer
+	err := ForwardSSEStream(strings.NewReader(stream), &out, func() {}, PassthroughOptions{
+		Rewrite: rewrite,
+		OnDone:  func() { out.WriteString("event: gateway_usage\ndata: {}\n\n") },
+	})
+	if err != nil {
+		t.Fatalf("透传失败: %v", err)
+	}
+
+	expected := "data: {\"id\":\"1\",\"model\":\"gpt-4\"}\n\nevent: gateway_usage\ndata: {}\n\ndata: [DONE]\n\n"
+	if out.String() != expected {
+		t.Errorf("输出不符合预期:\n%s", out.String())
+	}
+}
@@ -2,6 +2,7 @@ package converter
 
 import (
 	"bufio"
+	"bytes"
 	"io"
 	"strings"
 )
@@ -65,7 +66,15 @@ func ProcessSSEStream(reader io.Reader, converter Converter, writer StreamWriter
 		}
 	}
 
-	return scanner.Err()
+	if err := scanner.Err(); err != nil {
+		return err
+	}
+
+	// Gemini流没有结束标记，读到流末尾即结束
+	if converter.GetFormat() == FormatGemini {
+		return writer.WriteDone()
+	}
+	return nil
 }
 
 // processSSEEvent 处理单个SSE事件
@@ -99,3 +108,60 @@ func processSSEEvent(eventType string, data []byte, streamConverter StreamConver
 
 	return nil
 }
+
+// PassthroughOptions 透传模式选项
+type PassthroughOptions struct {
+	// Rewrite 可选：改写单行内容（如恢复模型路由前的模型名），返回nil表示不修改
+	Rewrite func(line []byte) []byte
+
+	// OnDone 可选：在转发 data: [DONE] 之前（或上游没有[DONE]时在流结束时）调用一次
+	OnDone func()
+}
+
+// ForwardSSEStream 透传SSE流：客户端格式与上游一致时不解析重组，
+// 按行原样转发，保留原始事件名和分块边界，并在每个事件结束时刷新
+func ForwardSSEStream(reader io.Reader, writer io.Writer, flush func(), opts PassthroughOptions) error {
+	bufReader := bufio.NewReader(reader)
+	doneCalled := false
+	callDone := func() {
+		if !doneCalled && opts.OnDone != nil {
+			doneCalled = true
+			opts.OnDone()
+		}
+	}
+
+	for {
+		line, readErr := bufReader.ReadBytes('\n')
+		if len(line) > 0 {
+			trimmed := bytes.TrimSpace(line)
+			if bytes.Equal(trimmed, []byte("data: [DONE]")) {
+				callDone()
+			}
+
+			if opts.Rewrite != nil && len(trimmed) > 0 {
+				if rewritten := opts.Rewrite(line); rewritten != nil {
+					line = rewritten
+				}
+			}
+
+			if _, err := writer.Write(line); err != nil {
+				return err
+			}
+
+			// 空行表示一个SSE事件结束
+			if len(trimmed) == 0 {
+				flush()
+			}
+		}
+
+		if readErr == io.EOF {
+			flush()
+			callDone()
+			flush()
+			return nil
+		}
+		if readErr != nil {
+			return readErr
+		}
+	}
+}
@@ -0,0 +1,231 @@
+package converter
+
+import (
+	"bytes"
+	"encoding/json"
+	"io"
+	"time"
+)
+
+// StreamUsage 从上游响应中提取的token用量
+type StreamUsage struct {
+	InputTokens      int `json:"input_tokens"`
+	OutputTokens     int `json:"output_tokens"`
+	CacheReadTokens  int `json:"cache_read_tokens,omitempty"`  // 命中提示词缓存的输入token
+	CacheWriteTokens int `json:"cache_write_tokens,omitempty"` // 写入提示词缓存的输入token（Anthropic）
+
+	// FinishReason 规范化的结束原因（见 FinishReason* 常量），上游未返回时为空
+	FinishReason string `json:"finish_reason,omitempty"`
+
+	// cacheReadIncluded InputTokens 是否已包含 CacheReadTokens（OpenAI、Gemini包含，Anthropic不包含）
+	cacheReadIncluded bool
+}
+
+// UncachedInputTokens 返回按正常输入价格计费的输入token数
+func (u StreamUsage) UncachedInputTokens() int {
+	if u.cacheReadIncluded && u.InputTokens >= u.CacheReadTokens {
+		return u.InputTokens - u.CacheReadTokens
+	}
+	return u.InputTokens
+}
+
+// streamUsageFields 兼容Anthropic、OpenAI与Gemini的usage字段
+type streamUsageFields struct {
+	InputTokens              int `json:"input_tokens"`
+	OutputTokens             int `json:"output_tokens"`
+	CacheReadInputTokens     int `json:"cache_read_input_tokens"`
+	CacheCreationInputTokens int `json:"cache_creation_input_tokens"`
+	PromptTokens             int `json:"prompt_tokens"`
+	CompletionTokens         int `json:"completion_tokens"`
+	PromptTokensDetails      *struct {
+		CachedTokens int `json:"cached_tokens"`
+	} `json:"prompt_tokens_details"`
+	PromptTokenCount        int `json:"promptTokenCount"`
+	CandidatesTokenCount    int `json:"candidatesTokenCount"`
+	CachedContentTokenCount int `json:"cachedContentTokenCount"`
+}
+
+// streamUsagePayload 可能携带usage的流式事件
+// Anthropic: message_start.message.usage / message_delta.usage
+// OpenAI: 最后一个chunk的usage（需要 stream_options.include_usage）
+// Gemini: 每个分块的usageMetadata（累计值）
+type streamUsagePayload struct {
+	Usage   *streamUsageFields `json:"usage"`
+	Message *struct {
+		Usage *streamUsageFields `json:"usage"`
+	} `json:"message"`
+	UsageMetadata *streamUsageFields `json:"usageMetadata"`
+}
+
+// finishReasonPayload 可能携带结束原因的响应或流式事件
+// Anthropic: stop_reason（非流式）/ message_delta.delta.stop_reason
+// OpenAI: choices[].finish_reason
+// Gemini: candidates[].finishReason
+type finishReasonPayload struct {
+	StopReason string `json:"stop_reason"`
+	Delta      *struct {
+		StopReason string `json:"stop_reason"`
+	} `json:"delta"`
+	Choices []struct {
+		FinishReason string `json:"finish_reason"`
+	} `json:"choices"`
+	Candidates []struct {
+		FinishReason string `json:"finishReason"`
+	} `json:"candidates"`
+}
+
+// UsageCaptureReader 在读取上游SSE流的同时提取token用量和结束原因，不修改流内容
+// 同时记录首个data事件到达的时间，用于计算首token延迟
+type UsageCaptureReader struct {
+	reader       io.Reader
+	pending      []byte
+	usage        StreamUsage
+	firstDataAt  time.Time
+	now          func() time.Time
+	functionCall bool // Gemini输出过functionCall，结束原因按工具调用记录
+}
+
+// NewUsageCaptureReader 创建用量捕获读取器
+func NewUsageCaptureReader(reader io.Reader) *UsageCaptureReader {
+	return &UsageCaptureReader{reader: reader, now: time.Now}
+}
+
+// Read 实现io.Reader
+func (r *UsageCaptureReader) Read(p []byte) (int, error) {
+	n, err := r.reader.Read(p)
+	if n > 0 {
+		r.pending = append(r.pending, p[:n]...)
+		r.consumeLines()
+	}
+	return n, err
+}
+
+// Usage 返回目前为止捕获的用量
+func (r *UsageCaptureReader) Usage() StreamUsage {
+	return r.usage
+}
+
+// FirstDataAt 返回首个data事件到达的时间，尚未收到时为零值
+func (r *UsageCaptureReader) FirstDataAt() time.Time {
+	return r.firstDataAt
+}
+
+// consumeLines 处理缓冲区中的完整行
+func (r *UsageCaptureReader) consumeLines() {
+	for {
+		idx := bytes.IndexByte(r.pending, '\n')
+		if idx < 0 {
+			return
+		}
+		line := bytes.TrimSpace(r.pending[:idx])
+		r.pending = r.pending[idx+1:]
+
+		if bytes.HasPrefix(line, []byte("data:")) {
+			data := bytes.TrimSpace(line[5:])
+			if r.firstDataAt.IsZero() && len(data) > 0 && !bytes.Equal(data, []byte("[DONE]")) {
+				r.firstDataAt = r.now()
+			}
+			r.observe(data)
+		}
+	}
+}
+
+// observe 解析单个data行中的usage和结束原因
+func (r *UsageCaptureReader) observe(data []byte) {
+	r.observeFinishReason(data)
+	if !bytes.Contains(data, []byte(`"usage`)) {
+		return
+	}
+
+	var payload streamUsagePayload
+	if err := json.Unmarshal(data, &payload); err != nil {
+		return
+	}
+
+	if payload.Message != nil && payload.Message.Usage != nil {
+		r.merge(payload.Message.Usage)
+	}
+	if payload.Usage != nil {
+		r.merge(payload.Usage)
+	}
+	if payload.UsageMetadata != nil {
+		r.merge(payload.UsageMetadata)
+	}
+}
+
+// observeFinishReason 解析单个data行中的结束原因，非空值覆盖
+func (r *UsageCaptureReader) observeFinishReason(data []byte) {
+	if bytes.Contains(data, []byte(`"functionCall"`)) {
+		r.functionCall = true
+	}
+	if !bytes.Contains(data, []byte(`"stop_reason"`)) && !bytes.Contains(data, []byte(`"finish_reason"`)) && !bytes.Contains(data, []byte(`"finishReason"`)) {
+		return
+	}
+
+	var payload finishReasonPayload
+	if err := json.Unmarshal(data, &payload); err != nil {
+		return
+	}
+
+	reason := NormalizeFinishReason(payload.StopReason)
+	if payload.Delta != nil && payload.Delta.StopReason != "" {
+		reason = NormalizeFinishReason(payload.Delta.StopReason)
+	}
+	if len(payload.Choices) > 0 && payload.Choices[0].FinishReason != "" {
+		reason = NormalizeFinishReason(payload.Choices[0].FinishReason)
+	}
+	if len(payload.Candidates) > 0 && payload.Candidates[0].FinishReason != "" {
+		reason = convertGeminiFinishReason(payload.Candidates[0].FinishReason, r.functionCall)
+	}
+	if reason != "" {
+		r.usage.FinishReason = reason
+	}
+}
+
+// merge 合并用量，非零值覆盖（Anthropic的output_tokens为累计值）
+func (r *UsageCaptureReader) merge(fields *streamUsageFields) {
+	if fields.InputTokens > 0 {
+		r.usage.InputTokens = fields.InputTokens
+	}
+	if fields.PromptTokens > 0 {
+		r.usage.InputTokens = fields.PromptTokens
+	}
+	if fields.OutputTokens > 0 {
+		r.usage.OutputTokens = fields.OutputTokens
+	}
+	if fields.CompletionTokens > 0 {
+		r.usage.OutputTokens = fields.CompletionTokens
+	}
+	if fields.PromptTokenCount > 0 {
+		r.usage.InputTokens = fields.PromptTokenCount
+	}
+	if fields.CandidatesTokenCount > 0 {
+		r.usage.OutputTokens = fields.CandidatesTokenCount
+	}
+	r.usage.mergeCache(fields)
+}
+
+// mergeCache 合并提示词缓存用量，非零值覆盖
+func (u *StreamUsage) mergeCache(fields *streamUsageFields) {
+	if fields.CacheReadInputTokens > 0 {
+		u.CacheReadTokens = fields.CacheReadInputTokens
+	}
+	if fields.CacheCreationInputTokens > 0 {
+		u.CacheWriteTokens = fields.CacheCreationInputTokens
+	}
+	if fields.PromptTokensDetails != nil && fields.PromptTokensDetails.CachedTokens > 0 {
+		u.CacheReadTokens = fields.PromptTokensDetails.CachedTokens
+		u.cacheReadIncluded = true
+	}
+	if fields.CachedContentTokenCount > 0 {
+		u.CacheReadTokens = fields.CachedContentTokenCount
+		u.cacheReadIncluded = true
+	}
+}
+
+// ParseUsage 从非流式上游响应体中提取token用量（Anthropic/OpenAI的usage，Gemini的usageMetadata）
+func ParseUsage(body []byte) StreamUsage {
+	reader := &UsageCaptureReader{}
+	reader.observe(body)
+	return reader.usage
+}
@@ -0,0 +1,121 @@
+package converter
+
+import (
+	"io"
+	"os"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestUsageCaptureReader_Anthropic(t *testing.T) {
+	data, err := os.ReadFile("testdata/stream/stream_anthropic_basic.txt")
+	if err != nil {
+		t.Fatalf("读取测试数据失败: %v", err)
+	}
+
+	reader := NewUsageCaptureReader(strings.NewReader(string(data)))
+	forwarded, err := io.ReadAll(reader)
+	if err != nil {
+		t.Fatalf("读取流失败: %v", err)
+	}
+
+	if string(forwarded) != string(data) {
+		t.Error("用量捕获不应修改流内容")
+	}
+
+	usage := reader.Usage()
+	if usage.InputTokens != 25 || usage.OutputTokens != 15 || usage.FinishReason != FinishReasonStop {
+		t.Errorf("期望 input=25 output=15 finish_reason=stop, 实际 %+v", usage)
+	}
+}
+
+func TestUsageCaptureReader_OpenAI(t *testing.T) {
+	stream := "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"}}]}\n\n" +
+		"data: {\"choices\":[],\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":3,\"total_tokens\":15}}\n\n" +
+		"data: [DONE]\n\n"
+
+	// 使用小缓冲区读取，验证跨Read调用的行拼接
+	reader := NewUsageCaptureReader(strings.NewReader(stream))
+	buf := make([]byte, 7)
+	for {
+		if _, err := reader.Read(buf); err != nil {
+			break
+		}
+	}
+
+	usage := reader.Usage()
+	if usage.InputTokens != 12 || usage.OutputTokens != 3 {
+		t.Errorf("期望 input=12 output=3, 实际 %+v", usage)
+	}
+}
+
+func TestUsageCaptureReader_FirstDataAt(t *testing.T) {
+	stream := ": keep-alive\n\n" +
+		"event: message_start\n" +
+		"data: {\"type\":\"message_start\"}\n\n" +
+		"data: {\"type\":\"content_block_delta\"}\n\n"
+
+	reader := NewUsageCaptureReader(strings.NewReader(stream))
+	if !reader.FirstDataAt().IsZero() {
+		t.Fatal("读取前不应有首个事件时间")
+	}
+
+	base := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	calls := 0
+	reader.now = func() time.Time {
+		calls++
+		return base.Add(time.Duration(calls) * time.Second)
+	}
+	if _, err := io.ReadAll(reader); err != nil {
+		t.Fatalf("读取流失败: %v", err)
+	}
+
+	if calls != 1 {
+		t.Errorf("只应在首个data事件时记录时间, 实际记录 %d 次", calls)
+	}
+	if got := reader.FirstDataAt(); !got.Equal(base.Add(time.Second)) {
+		t.Errorf("首个事件时间 = %v", got)
+	}
+}
+
+func TestParseUsage_CacheTokens(t *testing.T) {
+	// Anthropic: input_tokens 不包含缓存token
+	usage := ParseUsage([]byte(`{"id":"msg_1","usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":200,"cache_creation_input_tokens":50}}`))
+	if usage.InputTokens != 10 || usage.CacheReadTokens != 200 || usage.CacheWriteTokens != 50 || usage.UncachedInputTokens() != 10 {
+		t.Errorf("Anthropic用量解析错误: %+v", usage)
+	}
+
+	// OpenAI: prompt_tokens 包含缓存命中的token
+	usage = ParseUsage([]byte(`{"id":"chatcmpl-1","usage":{"prompt_tokens":1200,"completion_tokens":8,"prompt_tokens_details":{"cached_tokens":1024}}}`))
+	if usage.InputTokens != 1200 || usage.CacheReadTokens != 1024 || usage.UncachedInputTokens() != 176 {
+		t.Errorf("OpenAI用量解析错误: %+v", usage)
+	}
+
+	// Gemini: promptTokenCount 包含缓存内容的token
+	usage = ParseUsage([]byte(`{"candidates":[],"usageMetadata":{"promptTokenCount":300,"candidatesTokenCount":20,"cachedContentTokenCount":100}}`))
+	if usage.InputTokens != 300 || usage.OutputTokens != 20 || usage.UncachedInputTokens() != 200 {
+		t.Errorf("Gemini用量解析错误: %+v", usage)
+	}
+}
+
+func TestParseUsage_FinishReason(t *testing.T) {
+	tests := []struct {
+		name string
+		body string
+		want string
+	}{
+		{"anthropic", `{"id":"msg_1","stop_reason":"max_tokens","usage":{"input_tokens":1,"output_tokens":2}}`, FinishReasonLength},
+		{"anthropic_refusal", `{"id":"msg_1","stop_reason":"refusal"}`, FinishReasonContentFilter},
+		{"openai", `{"choices":[{"index":0,"message":{"role":"assistant"},"finish_reason":"tool_calls"}]}`, FinishReasonToolCalls},
+		{"openai_function_call", `{"choices":[{"index":0,"finish_reason":"function_call"}]}`, FinishReasonToolCalls},
+		{"gemini", `{"candidates":[{"content":{"parts":[{"text":"hi"}]},"finishReason":"SAFETY"}]}`, FinishReasonContentFilter},
+		{"gemini_function_call", `{"candidates":[{"content":{"parts":[{"functionCall":{"name":"f"}}]},"finishReason":"STOP"}]}`, FinishReasonToolCalls},
+		{"none", `{"choices":[{"index":0,"finish_reason":null}]}`, ""},
+	}
+	for _, tt := range tests {
+		if got := ParseUsage([]byte(tt.body)).FinishReason; got != tt.want {
+			t.Errorf("%s: finish_reason = %q, want %q", tt.name, got, tt.want)
+		}
+	}
+}
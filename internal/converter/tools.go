@@ -0,0 +1,58 @@
+package converter
+
+// toolChoiceToAnthropic 把OpenAI或Anthropic格式的tool_choice转换为Anthropic格式，无法识别或为空时返回nil（Anthropic默认为auto）
+//
+//	OpenAI "auto" / "required" / "none" / {"type":"function","function":{"name":"x"}}
+//	Anthropic {"type":"auto"} / {"type":"any"} / {"type":"none"} / {"type":"tool","name":"x"}
+func toolChoiceToAnthropic(choice interface{}) map[string]interface{} {
+	switch v := choice.(type) {
+	case string:
+		switch v {
+		case "auto":
+			return map[string]interface{}{"type": "auto"}
+		case "required", "any":
+			return map[string]interface{}{"type": "any"}
+		case "none":
+			return map[string]interface{}{"type": "none"}
+		}
+	case map[string]interface{}:
+		switch getString(v["type"]) {
+		case "function":
+			function, _ := v["function"].(map[string]interface{})
+			if name := getString(function["name"]); name != "" {
+				return map[string]interface{}{"type": "tool", "name": name}
+			}
+		case "auto", "any", "none", "tool":
+			return v
+		}
+	}
+	return nil
+}
+
+// toolChoiceToOpenAI 把OpenAI或Anthropic格式的tool_choice转换为OpenAI格式，无法识别或为空时返回nil（OpenAI默认为auto）
+func toolChoiceToOpenAI(choice interface{}) interface{} {
+	switch v := choice.(type) {
+	case string:
+		switch v {
+		case "auto", "required", "none":
+			return v
+		case "any":
+			return "required"
+		}
+	case map[string]interface{}:
+		switch getString(v["type"]) {
+		case "function":
+			return v
+		case "tool":
+			return map[string]interface{}{
+				"type":     "function",
+				"function": map[string]interface{}{"name": getString(v["name"])},
+			}
+		case "any":
+			return "required"
+		case "auto", "none":
+			return getString(v["type"])
+		}
+	}
+	return nil
+}
@@ -0,0 +1,190 @@
+package converter
+
+import (
+	"encoding/json"
+	"fmt"
+	"reflect"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestToolChoiceMapping(t *testing.T) {
+	tests := []struct {
+		name      string
+		choice    interface{}
+		anthropic map[string]interface{}
+		openai    interface{}
+	}{
+		{"openai auto", "auto", map[string]interface{}{"type": "auto"}, "auto"},
+		{"openai required", "required", map[string]interface{}{"type": "any"}, "required"},
+		{"openai none", "none", map[string]interface{}{"type": "none"}, "none"},
+		{
+			"openai function",
+			map[string]interface{}{"type": "function", "function": map[string]interface{}{"name": "get_weather"}},
+			map[string]interface{}{"type": "tool", "name": "get_weather"},
+			map[string]interface{}{"type": "function", "function": map[string]interface{}{"name": "get_weather"}},
+		},
+		{"anthropic any", map[string]interface{}{"type": "any"}, map[string]interface{}{"type": "any"}, "required"},
+		{
+			"anthropic tool",
+			map[string]interface{}{"type": "tool", "name": "get_weather"},
+			map[string]interface{}{"type": "tool", "name": "get_weather"},
+			map[string]interface{}{"type": "function", "function": map[string]interface{}{"name": "get_weather"}},
+		},
+		{"unset", nil, nil, nil},
+		{"unknown", "sometimes", nil, nil},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := toolChoiceToAnthropic(tt.choice); !reflect.DeepEqual(got, tt.anthropic) {
+				t.Errorf("toolChoiceToAnthropic() = %v, want %v", got, tt.anthropic)
+			}
+			if got := toolChoiceToOpenAI(tt.choice); !reflect.DeepEqual(got, tt.openai) {
+				t.Errorf("toolChoiceToOpenAI() = %v, want %v", got, tt.openai)
+			}
+		})
+	}
+}
+
+func TestBuildRequest_Tools(t *testing.T) {
+	manager := NewManager()
+
+	// OpenAI 函数 -> Anthropic 工具，缺少参数和描述时补全 input_schema、省略 description
+	openAIReq := []byte(`{"model":"claude-3-5-sonnet","messages":[{"role":"user","content":"hi"}],
+		"tools":[{"type":"function","function":{"name":"now"}}],
+		"tool_choice":{"type":"function","function":{"name":"now"}}}`)
+	unified, _, err := manager.ParseRequest(openAIReq, "")
+	if err != nil {
+		t.Fatalf("ParseRequest() error = %v", err)
+	}
+	body, err := manager.BuildUpstreamRequest(unified, types.ProviderAnthropic)
+	if err != nil {
+		t.Fatalf("BuildUpstreamRequest() error = %v", err)
+	}
+	var anthropicReq map[string]interface{}
+	_ = json.Unmarshal(body, &anthropicReq)
+	tool := anthropicReq["tools"].([]interface{})[0].(map[string]interface{})
+	if _, ok := tool["description"]; ok || tool["input_schema"] == nil {
+		t.Errorf("tool = %v, want input_schema and no description", tool)
+	}
+	if choice := anthropicReq["tool_choice"]; !reflect.DeepEqual(choice, map[string]interface{}{"type": "tool", "name": "now"}) {
+		t.Errorf("tool_choice = %v", choice)
+	}
+
+	// Anthropic 工具 -> OpenAI 函数
+	anthropicBody := []byte(`{"model":"gpt-4o","max_tokens":100,"messages":[{"role":"user","content":"hi"}],
+		"tools":[{"name":"get_weather","description":"Weather","input_schema":{"type":"object","properties":{"city":{"type":"string"}}}}],
+		"tool_choice":{"type":"any"}}`)
+	unified, _, err = manager.ParseRequest(anthropicBody, "")
+	if err != nil {
+		t.Fatalf("ParseRequest() error = %v", err)
+	}
+	body, err = manager.BuildUpstreamRequest(unified, types.ProviderOpenAI)
+	if err != nil {
+		t.Fatalf("BuildUpstreamRequest() error = %v", err)
+	}
+	var openAIBody map[string]interface{}
+	_ = json.Unmarshal(body, &openAIBody)
+	function := openAIBody["tools"].([]interface{})[0].(map[string]interface{})["function"].(map[string]interface{})
+	if function["name"] != "get_weather" || function["parameters"] == nil {
+		t.Errorf("function = %v", function)
+	}
+	if openAIBody["tool_choice"] != "required" {
+		t.Errorf("tool_choice = %v, want required", openAIBody["tool_choice"])
+	}
+
+	// 没有工具时不转发 tool_choice
+	unified.Tools = nil
+	body, _ = manager.BuildUpstreamRequest(unified, types.ProviderAnthropic)
+	if strings.Contains(string(body), "tool_choice") {
+		t.Errorf("request without tools has tool_choice: %s", body)
+	}
+}
+
+func TestStreamToolCalls_OpenAIToAnthropic(t *testing.T) {
+	stream := "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Checking.\"}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"get_weather\",\"arguments\":\"\"}}]}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"city\\\":\\\"Paris\\\"}\"}}]}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"call_2\",\"function\":{\"name\":\"get_time\",\"arguments\":\"{}\"}}]}}]}\n\n" +
+		"data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n" +
+		"data: [DONE]\n\n"
+
+	writer := &recordingWriter{}
+	if err := NewManager().ProcessStream(strings.NewReader(stream), types.ProviderOpenAI, FormatAnthropic, writer); err != nil {
+		t.Fatalf("ProcessStream() error = %v", err)
+	}
+
+	var events []string
+	inputs := make(map[interface{}]string)
+	var stopReason interface{}
+	for _, chunk := range writer.chunks {
+		data, _ := chunk.Data.(map[string]interface{})
+		if index, ok := data["index"]; ok {
+			events = append(events, fmt.Sprintf("%s:%v", chunk.EventType, index))
+		} else {
+			events = append(events, chunk.EventType)
+		}
+		if delta, ok := data["delta"].(map[string]interface{}); ok {
+			if partial, ok := delta["partial_json"].(string); ok {
+				inputs[data["index"]] += partial
+			}
+			if chunk.EventType == "message_delta" {
+				stopReason = delta["stop_reason"]
+			}
+		}
+	}
+
+	// 文本和两个并行的工具调用各占一个内容块，切换时先结束上一个
+	want := "message_start,content_block_start:0,content_block_delta:0,content_block_stop:0," +
+		"content_block_start:1,content_block_delta:1,content_block_stop:1," +
+		"content_block_start:2,content_block_delta:2,content_block_stop:2,message_delta,message_stop"
+	if got := strings.Join(events, ","); got != want {
+		t.Errorf("events = %s\nwant %s", got, want)
+	}
+	if inputs[1] != `{"city":"Paris"}` || inputs[2] != "{}" {
+		t.Errorf("tool inputs = %v", inputs)
+	}
+	if stopReason != "tool_use" {
+		t.Errorf("stop_reason = %v, want tool_use", stopReason)
+	}
+}
+
+func TestStreamToolCalls_AnthropicToOpenAI(t *testing.T) {
+	stream := "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude-3-5-sonnet\"}}\n\n" +
+		"event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n" +
+		"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Checking.\"}}\n\n" +
+		"event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n" +
+		"event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"get_weather\",\"input\":{}}}\n\n" +
+		"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"city\\\":\\\"Paris\\\"}\"}}\n\n" +
+		"event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":1}\n\n" +
+		"event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":5}}\n\n" +
+		"event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"
+
+	writer := &recordingWriter{}
+	if err := NewManager().ProcessStream(strings.NewReader(stream), types.ProviderAnthropic, FormatOpenAI, writer); err != nil {
+		t.Fatalf("ProcessStream() error = %v", err)
+	}
+
+	// 工具调用的 index 从0开始编号，不计入前面的文本块
+	var calls []map[string]interface{}
+	for _, chunk := range writer.chunks {
+		data, _ := chunk.Data.(map[string]interface{})
+		choices, _ := data["choices"].([]interface{})
+		if len(choices) == 0 {
+			continue
+		}
+		choice := choices[0].(map[string]interface{})
+		if choice["index"] != 0 {
+			t.Errorf("choice index = %v, want 0", choice["index"])
+		}
+		delta, _ := choice["delta"].(map[string]interface{})
+		if toolCalls, ok := delta["tool_calls"].([]interface{}); ok {
+			calls = append(calls, toolCalls[0].(map[string]interface{}))
+		}
+	}
+	if len(calls) != 2 || calls[0]["index"] != 0 || calls[0]["id"] != "toolu_1" || calls[1]["index"] != 0 {
+		t.Errorf("tool_calls = %v", calls)
+	}
+}
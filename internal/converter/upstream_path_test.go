@@ -49,6 +49,12 @@ func TestConverterUpstreamPaths(t *testing.T) {
 			clientEndpoint: "/v1/completions",
 			expectedPath:   "/v1/messages",
 		},
+		{
+			name:           "Gemini Converter - generateContent",
+			converter:      NewGeminiConverter(),
+			clientEndpoint: GeminiEndpoint,
+			expectedPath:   "/v1beta/models/{model}:generateContent",
+		},
 	}
 
 	for _, tt := range tests {
@@ -85,6 +91,13 @@ func TestManagerUpstreamPath(t *testing.T) {
 			expectedPath:   "/v1/chat/completions",
 			expectError:    false,
 		},
+		{
+			name:           "Bedrock Provider (Anthropic格式)",
+			provider:       types.ProviderBedrock,
+			clientEndpoint: "/v1/chat/completions",
+			expectedPath:   "/v1/messages",
+			expectError:    false,
+		},
 		{
 			name:           "Qwen Provider (默认OpenAI格式)",
 			provider:       types.ProviderQwen,
@@ -93,10 +106,10 @@ func TestManagerUpstreamPath(t *testing.T) {
 			expectError:    false,
 		},
 		{
-			name:           "Google Provider (默认OpenAI格式)",
+			name:           "Google Provider (Gemini原生格式)",
 			provider:       types.ProviderGoogle,
 			clientEndpoint: "/v1/chat/completions",
-			expectedPath:   "/v1/chat/completions",
+			expectedPath:   "/v1beta/models/{model}:generateContent",
 			expectError:    false,
 		},
 	}
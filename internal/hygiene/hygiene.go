@@ -0,0 +1,226 @@
+package hygiene
+
+import (
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 凭证类型
+const (
+	KindGatewayKey      = "gateway_key"
+	KindUpstreamAccount = "upstream_account"
+)
+
+// Finding 一个闲置的凭证
+type Finding struct {
+	Kind       string    `json:"kind"`
+	ID         string    `json:"id"`
+	Name       string    `json:"name"`
+	Status     string    `json:"status"`
+	LastUsedAt time.Time `json:"last_used_at"` // 从未使用时为创建时间
+	IdleDays   int       `json:"idle_days"`
+	FlaggedAt  time.Time `json:"flagged_at"` // 达到闲置阈值的时间
+
+	// DisableAt 宽限期结束、将被自动禁用的时间，未开启自动禁用时为空
+	DisableAt *time.Time `json:"disable_at,omitempty"`
+}
+
+// Report 闲置凭证报告
+type Report struct {
+	GeneratedAt time.Time  `json:"generated_at"`
+	IdleDays    int        `json:"idle_days"`
+	AutoDisable bool       `json:"auto_disable"`
+	GraceDays   int        `json:"grace_days"`
+	Findings    []*Finding `json:"findings"`
+}
+
+// Store 凭证数据来源（由ConfigManager实现）
+type Store interface {
+	ListGatewayKeys() []*types.GatewayAPIKey
+	ListUpstreamAccounts() []*types.UpstreamAccount
+	UpdateGatewayKey(keyID string, updater func(*types.GatewayAPIKey) error) error
+	UpdateUpstreamAccount(accountID string, updater func(*types.UpstreamAccount) error) error
+}
+
+// BuildReport 找出超过 IdleDays 天未使用的未禁用凭证，按闲置时间从长到短排序
+func BuildReport(keys []*types.GatewayAPIKey, accounts []*types.UpstreamAccount, cfg types.HygieneConfig, now time.Time) *Report {
+	report := &Report{
+		GeneratedAt: now,
+		IdleDays:    cfg.IdleDays,
+		AutoDisable: cfg.AutoDisable,
+		GraceDays:   cfg.GraceDays,
+		Findings:    []*Finding{},
+	}
+
+	add := func(kind, id, name, status string, lastUsed time.Time) {
+		if status == "disabled" {
+			return
+		}
+		flaggedAt := lastUsed.AddDate(0, 0, cfg.IdleDays)
+		if now.Before(flaggedAt) {
+			return
+		}
+
+		finding := &Finding{
+			Kind:       kind,
+			ID:         id,
+			Name:       name,
+			Status:     status,
+			LastUsedAt: lastUsed,
+			IdleDays:   int(now.Sub(lastUsed).Hours() / 24),
+			FlaggedAt:  flaggedAt,
+		}
+		if cfg.AutoDisable {
+			disableAt := flaggedAt.AddDate(0, 0, cfg.GraceDays)
+			finding.DisableAt = &disableAt
+		}
+		report.Findings = append(report.Findings, finding)
+	}
+
+	for _, key := range keys {
+		var usage time.Time
+		if key.Usage != nil {
+			usage = key.Usage.LastUsedAt
+		}
+		add(KindGatewayKey, key.ID, key.Name, key.Status, lastUsedAt(usage, key.CreatedAt))
+	}
+	for _, account := range accounts {
+		var usage time.Time
+		if account.Usage != nil {
+			usage = account.Usage.LastUsedAt
+		}
+		add(KindUpstreamAccount, account.ID, account.Name, account.Status, lastUsedAt(usage, account.CreatedAt))
+	}
+
+	sort.SliceStable(report.Findings, func(i, j int) bool {
+		return report.Findings[i].LastUsedAt.Before(report.Findings[j].LastUsedAt)
+	})
+	return report
+}
+
+// lastUsedAt 取最后使用时间和创建时间中较晚的一个（从未使用的凭证从创建时开始计算闲置）
+func lastUsedAt(lastUsed, createdAt time.Time) time.Time {
+	if lastUsed.After(createdAt) {
+		return lastUsed
+	}
+	return createdAt
+}
+
+// Monitor 定期检测闲置凭证，新发现时告警，开启自动禁用时在宽限期后禁用
+type Monitor struct {
+	store    Store
+	settings func() *types.HygieneConfig // 每次检测时读取，修改或重新加载配置后立即生效
+	interval time.Duration
+	flagged  map[string]bool
+	stopCh   chan struct{}
+	mutex    sync.Mutex
+}
+
+// NewMonitor 创建闲置凭证检测器，settings 返回当前的检测配置
+func NewMonitor(store Store, settings func() *types.HygieneConfig, interval time.Duration) *Monitor {
+	if interval <= 0 {
+		interval = time.Hour
+	}
+	return &Monitor{
+		store:    store,
+		settings: settings,
+		interval: interval,
+		flagged:  make(map[string]bool),
+	}
+}
+
+// Start 启动后台检测
+func (m *Monitor) Start() {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.stopCh != nil {
+		return
+	}
+	m.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(m.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				m.Evaluate(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(m.stopCh)
+}
+
+// Stop 停止后台检测
+func (m *Monitor) Stop() {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.stopCh != nil {
+		close(m.stopCh)
+		m.stopCh = nil
+	}
+}
+
+// Evaluate 执行一次检测，返回本次自动禁用的凭证
+func (m *Monitor) Evaluate(now time.Time) []*Finding {
+	cfg := *m.settings()
+	report := BuildReport(m.store.ListGatewayKeys(), m.store.ListUpstreamAccounts(), cfg, now)
+
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	var disabled []*Finding
+	current := make(map[string]bool)
+	for _, finding := range report.Findings {
+		flagKey := finding.Kind + ":" + finding.ID
+
+		if finding.DisableAt != nil && !now.Before(*finding.DisableAt) {
+			if err := m.disable(finding, now); err != nil {
+				logger.Error("自动禁用闲置凭证失败: %s=%s: %v", finding.Kind, finding.ID, err)
+				current[flagKey] = true
+				continue
+			}
+			logger.Warn("已自动禁用闲置凭证: %s=%s (%s) 已 %d 天未使用", finding.Kind, finding.ID, finding.Name, finding.IdleDays)
+			disabled = append(disabled, finding)
+			continue
+		}
+
+		current[flagKey] = true
+		if !m.flagged[flagKey] {
+			if finding.DisableAt != nil {
+				logger.Warn("发现闲置凭证: %s=%s (%s) 已 %d 天未使用，将于 %s 自动禁用", finding.Kind, finding.ID, finding.Name, finding.IdleDays, finding.DisableAt.Format(time.RFC3339))
+			} else {
+				logger.Warn("发现闲置凭证: %s=%s (%s) 已 %d 天未使用", finding.Kind, finding.ID, finding.Name, finding.IdleDays)
+			}
+		}
+	}
+	m.flagged = current
+
+	return disabled
+}
+
+// disable 禁用闲置凭证
+func (m *Monitor) disable(finding *Finding, now time.Time) error {
+	switch finding.Kind {
+	case KindGatewayKey:
+		return m.store.UpdateGatewayKey(finding.ID, func(key *types.GatewayAPIKey) error {
+			key.Status = "disabled"
+			key.UpdatedAt = now
+			return nil
+		})
+	default:
+		return m.store.UpdateUpstreamAccount(finding.ID, func(account *types.UpstreamAccount) error {
+			account.Status = "disabled"
+			account.UpdatedAt = now
+			return nil
+		})
+	}
+}
@@ -0,0 +1,118 @@
+package hygiene
+
+import (
+	"fmt"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// mockStore 内存中的凭证数据
+type mockStore struct {
+	keys     []*types.GatewayAPIKey
+	accounts []*types.UpstreamAccount
+}
+
+func (s *mockStore) ListGatewayKeys() []*types.GatewayAPIKey {
+	return s.keys
+}
+
+func (s *mockStore) ListUpstreamAccounts() []*types.UpstreamAccount {
+	return s.accounts
+}
+
+func (s *mockStore) UpdateGatewayKey(keyID string, updater func(*types.GatewayAPIKey) error) error {
+	for _, key := range s.keys {
+		if key.ID == keyID {
+			return updater(key)
+		}
+	}
+	return fmt.Errorf("key not found: %s", keyID)
+}
+
+func (s *mockStore) UpdateUpstreamAccount(accountID string, updater func(*types.UpstreamAccount) error) error {
+	for _, account := range s.accounts {
+		if account.ID == accountID {
+			return updater(account)
+		}
+	}
+	return fmt.Errorf("account not found: %s", accountID)
+}
+
+func TestBuildReport(t *testing.T) {
+	now := time.Date(2024, 6, 30, 12, 0, 0, 0, time.UTC)
+	daysAgo := func(days int) time.Time { return now.AddDate(0, 0, -days) }
+
+	keys := []*types.GatewayAPIKey{
+		{ID: "key-active", Status: "active", CreatedAt: daysAgo(100), Usage: &types.KeyUsageStats{LastUsedAt: daysAgo(1)}},
+		{ID: "key-idle", Status: "active", CreatedAt: daysAgo(100), Usage: &types.KeyUsageStats{LastUsedAt: daysAgo(40)}},
+		{ID: "key-never-used", Status: "active", CreatedAt: daysAgo(50)},
+		{ID: "key-disabled", Status: "disabled", CreatedAt: daysAgo(100)},
+	}
+	accounts := []*types.UpstreamAccount{
+		{ID: "acc-idle", Status: "active", CreatedAt: daysAgo(90), Usage: &types.UpstreamUsageStats{LastUsedAt: daysAgo(60)}},
+		{ID: "acc-new", Status: "active", CreatedAt: daysAgo(2)},
+	}
+
+	report := BuildReport(keys, accounts, types.HygieneConfig{IdleDays: 30, GraceDays: 7}, now)
+	if len(report.Findings) != 3 {
+		t.Fatalf("期望3个闲置凭证, 实际 %d", len(report.Findings))
+	}
+
+	expected := []string{"acc-idle", "key-never-used", "key-idle"}
+	for i, id := range expected {
+		if report.Findings[i].ID != id {
+			t.Errorf("Findings[%d].ID = %s, 期望 %s", i, report.Findings[i].ID, id)
+		}
+		if report.Findings[i].DisableAt != nil {
+			t.Errorf("未开启自动禁用时 DisableAt 应为空")
+		}
+	}
+	if report.Findings[0].Kind != KindUpstreamAccount || report.Findings[0].IdleDays != 60 {
+		t.Errorf("acc-idle 报告异常: %+v", report.Findings[0])
+	}
+	if !report.Findings[2].FlaggedAt.Equal(daysAgo(10)) {
+		t.Errorf("key-idle FlaggedAt = %v, 期望 %v", report.Findings[2].FlaggedAt, daysAgo(10))
+	}
+}
+
+func TestMonitorAutoDisable(t *testing.T) {
+	now := time.Date(2024, 6, 30, 12, 0, 0, 0, time.UTC)
+	store := &mockStore{
+		keys: []*types.GatewayAPIKey{
+			// 闲置40天：已超过30天阈值+7天宽限期
+			{ID: "key-expired", Status: "active", CreatedAt: now.AddDate(0, 0, -40)},
+			// 闲置33天：仍在宽限期内
+			{ID: "key-grace", Status: "active", CreatedAt: now.AddDate(0, 0, -33)},
+		},
+		accounts: []*types.UpstreamAccount{
+			{ID: "acc-expired", Status: "error", CreatedAt: now.AddDate(0, 0, -60)},
+		},
+	}
+
+	cfg := &types.HygieneConfig{IdleDays: 30, GraceDays: 7}
+	monitor := NewMonitor(store, func() *types.HygieneConfig { return cfg }, time.Hour)
+
+	// 未开启自动禁用时只报告
+	if disabled := monitor.Evaluate(now); len(disabled) != 0 {
+		t.Fatalf("未开启自动禁用时不应禁用凭证: %+v", disabled)
+	}
+
+	cfg.AutoDisable = true
+	disabled := monitor.Evaluate(now)
+	if len(disabled) != 2 {
+		t.Fatalf("期望禁用2个凭证, 实际 %d", len(disabled))
+	}
+	if store.keys[0].Status != "disabled" || store.accounts[0].Status != "disabled" {
+		t.Errorf("超过宽限期的凭证应被禁用")
+	}
+	if store.keys[1].Status != "active" {
+		t.Errorf("宽限期内的凭证不应被禁用")
+	}
+
+	// 已禁用的凭证不再出现在报告中
+	if disabled := monitor.Evaluate(now); len(disabled) != 0 {
+		t.Errorf("重复检测不应再次禁用: %+v", disabled)
+	}
+}
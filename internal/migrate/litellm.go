@@ -0,0 +1,153 @@
+package migrate
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	yaml "gopkg.in/yaml.v2"
+)
+
+// liteLLMEnvPrefix LiteLLM 从环境变量读取值的写法，如 os.environ/OPENAI_API_KEY
+const liteLLMEnvPrefix = "os.environ/"
+
+// liteLLMConfig LiteLLM proxy 的 config.yaml（只解析需要的部分）
+type liteLLMConfig struct {
+	ModelList []struct {
+		ModelName string                 `yaml:"model_name"`
+		Params    map[string]interface{} `yaml:"litellm_params"`
+	} `yaml:"model_list"`
+	GeneralSettings struct {
+		MasterKey string `yaml:"master_key"`
+	} `yaml:"general_settings"`
+	RouterSettings  map[string]interface{} `yaml:"router_settings"`
+	LiteLLMSettings map[string]interface{} `yaml:"litellm_settings"`
+}
+
+// liteLLMProviders LiteLLM 模型前缀到网关提供商的映射
+var liteLLMProviders = map[string]types.Provider{
+	"openai":    types.ProviderOpenAI,
+	"anthropic": types.ProviderAnthropic,
+	"azure":     types.ProviderAzure,
+	"gemini":    types.ProviderGoogle,
+	"dashscope": types.ProviderQwen,
+}
+
+// ParseLiteLLM 解析 LiteLLM 的 config.yaml
+// 每个部署（api_key + api_base）生成一个上游账号，model_name 到实际模型的映射生成模型路由，
+// 同一模型的多个部署生成一条带账号池的路由规则；master_key 作为客户端密钥导入
+func ParseLiteLLM(data []byte, getenv func(string) string) (*Plan, error) {
+	var cfg liteLLMConfig
+	if err := yaml.Unmarshal(data, &cfg); err != nil {
+		return nil, fmt.Errorf("解析LiteLLM配置失败: %w", err)
+	}
+	if len(cfg.ModelList) == 0 {
+		return nil, fmt.Errorf("LiteLLM配置中没有 model_list")
+	}
+
+	plan := &Plan{Source: SourceLiteLLM}
+	resolve := func(value string) string {
+		if !strings.HasPrefix(value, liteLLMEnvPrefix) {
+			return value
+		}
+		name := strings.TrimPrefix(value, liteLLMEnvPrefix)
+		resolved := getenv(name)
+		if resolved == "" {
+			plan.warnf("环境变量 %s 未设置", name)
+		}
+		return resolved
+	}
+
+	accounts := make(map[string]string) // provider|api_key|api_base -> 账号ID
+	for _, entry := range cfg.ModelList {
+		model := paramString(entry.Params, "model")
+		if entry.ModelName == "" || model == "" {
+			plan.warnf("跳过缺少 model_name 或 litellm_params.model 的条目")
+			continue
+		}
+
+		provider, upstreamModel, ok := liteLLMProvider(model)
+		if !ok {
+			plan.warnf("模型 %s: 不支持的提供商 %s", entry.ModelName, model)
+			continue
+		}
+
+		apiKey := resolve(paramString(entry.Params, "api_key"))
+		if apiKey == "" {
+			plan.warnf("模型 %s: 缺少 api_key，已跳过", entry.ModelName)
+			continue
+		}
+		baseURL := normalizeBaseURL(provider, resolve(paramString(entry.Params, "api_base")))
+
+		var ignored []string
+		for param := range entry.Params {
+			if param != "model" && param != "api_key" && param != "api_base" {
+				ignored = append(ignored, param)
+			}
+		}
+		if len(ignored) > 0 {
+			sort.Strings(ignored)
+			plan.warnf("模型 %s: 忽略参数 %s", entry.ModelName, strings.Join(ignored, ", "))
+		}
+
+		accountKey := string(provider) + "|" + apiKey + "|" + baseURL
+		accountID, exists := accounts[accountKey]
+		if !exists {
+			accountID = fmt.Sprintf("upstream_litellm_%d", len(accounts)+1)
+			accounts[accountKey] = accountID
+			plan.Accounts = append(plan.Accounts, &types.UpstreamAccount{
+				ID:       accountID,
+				Name:     "litellm-" + entry.ModelName,
+				Type:     types.UpstreamTypeAPIKey,
+				Provider: provider,
+				BaseURL:  baseURL,
+				APIKey:   apiKey,
+			})
+		}
+
+		plan.addModelRoute(entry.ModelName, upstreamModel, provider)
+		plan.addToPool(upstreamModel, provider, accountID)
+	}
+
+	if masterKey := resolve(cfg.GeneralSettings.MasterKey); masterKey != "" {
+		plan.Keys = append(plan.Keys, &ImportedKey{Name: "litellm-master-key", RawKey: masterKey})
+	}
+	if len(cfg.RouterSettings) > 0 {
+		plan.warnf("router_settings 未导入")
+	}
+	if len(cfg.LiteLLMSettings) > 0 {
+		plan.warnf("litellm_settings 未导入")
+	}
+
+	return plan, nil
+}
+
+// liteLLMProvider 解析 LiteLLM 的 provider/model 写法，没有前缀时按模型名推断
+func liteLLMProvider(model string) (types.Provider, string, bool) {
+	if prefix, name, found := strings.Cut(model, "/"); found {
+		provider, ok := liteLLMProviders[prefix]
+		return provider, name, ok
+	}
+
+	lower := strings.ToLower(model)
+	switch {
+	case strings.HasPrefix(lower, "claude"):
+		return types.ProviderAnthropic, model, true
+	case strings.HasPrefix(lower, "gpt-"), strings.HasPrefix(lower, "o1"), strings.HasPrefix(lower, "o3"), strings.HasPrefix(lower, "o4"):
+		return types.ProviderOpenAI, model, true
+	case strings.HasPrefix(lower, "gemini"):
+		return types.ProviderGoogle, model, true
+	case strings.HasPrefix(lower, "qwen"):
+		return types.ProviderQwen, model, true
+	}
+	return "", "", false
+}
+
+// paramString 读取字符串参数
+func paramString(params map[string]interface{}, name string) string {
+	if value, ok := params[name].(string); ok {
+		return strings.TrimSpace(value)
+	}
+	return ""
+}
@@ -0,0 +1,186 @@
+package migrate
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/internal/client"
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 支持的导入来源
+const (
+	SourceLiteLLM = "litellm"
+	SourceOneAPI  = "one-api"
+)
+
+// ImportedKey 待导入的客户端密钥（保留原始值，客户端无需更换）
+type ImportedKey struct {
+	Name     string
+	RawKey   string
+	Disabled bool
+}
+
+// Plan 从其他网关配置转换得到的导入计划
+type Plan struct {
+	Source       string
+	Accounts     []*types.UpstreamAccount
+	ModelRoutes  []*types.ModelRoute
+	RoutingRules []*types.RoutingRule
+	Keys         []*ImportedKey
+
+	// Warnings 无法转换或被忽略的内容
+	Warnings []string
+}
+
+// warnf 记录无法转换的内容
+func (p *Plan) warnf(format string, args ...interface{}) {
+	p.Warnings = append(p.Warnings, fmt.Sprintf(format, args...))
+}
+
+// addToPool 将账号加入模型的账号池，同一模型和提供商只生成一条路由规则
+func (p *Plan) addToPool(model string, provider types.Provider, upstreamID string) {
+	for _, rule := range p.RoutingRules {
+		if rule.Pattern == model && rule.Provider == provider {
+			if !rule.InPool(upstreamID) {
+				rule.UpstreamIDs = append(rule.UpstreamIDs, upstreamID)
+			}
+			return
+		}
+	}
+
+	p.RoutingRules = append(p.RoutingRules, &types.RoutingRule{
+		ID:          fmt.Sprintf("rule_%s_%d", sourceIDPrefix(p.Source), len(p.RoutingRules)+1),
+		Pattern:     model,
+		Provider:    provider,
+		UpstreamIDs: []string{upstreamID},
+		Priority:    100,
+		Enabled:     true,
+		Description: "imported from " + p.Source,
+	})
+}
+
+// addModelRoute 添加模型名映射（源模型与目标模型相同时忽略）
+func (p *Plan) addModelRoute(source, target string, provider types.Provider) {
+	if source == target {
+		return
+	}
+	for _, route := range p.ModelRoutes {
+		if route.SourceModel == source {
+			if route.TargetModel != target || route.TargetProvider != provider {
+				p.warnf("模型 %s 存在多个映射目标，只保留 %s/%s", source, route.TargetProvider, route.TargetModel)
+			}
+			return
+		}
+	}
+
+	p.ModelRoutes = append(p.ModelRoutes, &types.ModelRoute{
+		ID:             fmt.Sprintf("route_%s_%d", sourceIDPrefix(p.Source), len(p.ModelRoutes)+1),
+		SourceModel:    source,
+		TargetModel:    target,
+		TargetProvider: provider,
+		Priority:       100,
+		Enabled:        true,
+		Description:    "imported from " + p.Source,
+	})
+}
+
+// sourceIDPrefix 生成ID时使用的来源前缀
+func sourceIDPrefix(source string) string {
+	return strings.ReplaceAll(source, "-", "")
+}
+
+// normalizeBaseURL 去掉末尾的 /v1，网关会按提供商自行拼接路径（Qwen的BaseURL本身包含 /v1）
+func normalizeBaseURL(provider types.Provider, baseURL string) string {
+	baseURL = strings.TrimRight(strings.TrimSpace(baseURL), "/")
+	if provider != types.ProviderQwen {
+		baseURL = strings.TrimSuffix(baseURL, "/v1")
+	}
+	return baseURL
+}
+
+// Result 导入结果
+type Result struct {
+	Accounts     int      `json:"accounts"`
+	ModelRoutes  int      `json:"model_routes"`
+	RoutingRules int      `json:"routing_rules"`
+	Keys         int      `json:"keys"`
+	Failures     []string `json:"failures,omitempty"`
+}
+
+// Apply 将导入计划写入网关配置，单个条目失败不影响其他条目
+func Apply(plan *Plan, configMgr *config.ConfigManager, upstreamMgr *upstream.UpstreamManager, keyMgr *client.GatewayKeyManager) *Result {
+	result := &Result{}
+	fail := func(format string, args ...interface{}) {
+		result.Failures = append(result.Failures, fmt.Sprintf(format, args...))
+	}
+
+	created := make(map[string]bool)
+	for _, account := range plan.Accounts {
+		if err := upstreamMgr.AddAccount(account); err != nil {
+			fail("上游账号 %s (%s): %v", account.ID, account.Name, err)
+			continue
+		}
+		created[account.ID] = true
+		result.Accounts++
+	}
+
+	for _, route := range plan.ModelRoutes {
+		if err := configMgr.CreateModelRoute(route); err != nil {
+			fail("模型路由 %s -> %s: %v", route.SourceModel, route.TargetModel, err)
+			continue
+		}
+		result.ModelRoutes++
+	}
+
+	for _, rule := range plan.RoutingRules {
+		// 只引用成功创建的账号
+		var pool []string
+		for _, id := range rule.UpstreamIDs {
+			if created[id] {
+				pool = append(pool, id)
+			}
+		}
+		if len(pool) == 0 {
+			fail("路由规则 %s: 账号池中没有成功导入的账号", rule.Pattern)
+			continue
+		}
+		rule.UpstreamIDs = pool
+
+		if err := configMgr.CreateRoutingRule(rule); err != nil {
+			fail("路由规则 %s: %v", rule.Pattern, err)
+			continue
+		}
+		result.RoutingRules++
+	}
+
+	permissions := []types.Permission{types.PermissionRead, types.PermissionWrite}
+	for _, imported := range plan.Keys {
+		key, err := keyMgr.ImportKey(imported.Name, imported.RawKey, permissions)
+		if err != nil {
+			fail("API Key %s: %v", imported.Name, err)
+			continue
+		}
+		if imported.Disabled {
+			if err := keyMgr.UpdateKeyStatus(key.ID, "disabled"); err != nil {
+				fail("API Key %s: %v", imported.Name, err)
+			}
+		}
+		result.Keys++
+	}
+
+	return result
+}
+
+// sortedKeys 返回map的有序键，保证导入结果稳定
+func sortedKeys(m map[string]string) []string {
+	keys := make([]string, 0, len(m))
+	for key := range m {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+	return keys
+}
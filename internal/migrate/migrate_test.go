@@ -0,0 +1,139 @@
+package migrate
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestParseLiteLLM(t *testing.T) {
+	config := `
+model_list:
+  - model_name: gpt-4o
+    litellm_params:
+      model: openai/gpt-4o
+      api_key: os.environ/OPENAI_API_KEY
+  - model_name: gpt-4o
+    litellm_params:
+      model: openai/gpt-4o
+      api_key: sk-second
+      api_base: https://proxy.example.com/v1/
+      rpm: 60
+  - model_name: smart
+    litellm_params:
+      model: anthropic/claude-3-5-sonnet-20241022
+      api_key: sk-ant-xxx
+  - model_name: titan
+    litellm_params:
+      model: bedrock/amazon.titan-text-express-v1
+      api_key: xxx
+general_settings:
+  master_key: sk-master
+router_settings:
+  routing_strategy: least-busy
+`
+	env := map[string]string{"OPENAI_API_KEY": "sk-first"}
+	plan, err := ParseLiteLLM([]byte(config), func(name string) string { return env[name] })
+	if err != nil {
+		t.Fatalf("ParseLiteLLM() error = %v", err)
+	}
+
+	if len(plan.Accounts) != 3 {
+		t.Fatalf("len(Accounts) = %d, want 3", len(plan.Accounts))
+	}
+	if plan.Accounts[0].APIKey != "sk-first" || plan.Accounts[0].BaseURL != "" {
+		t.Errorf("account from env = %+v", plan.Accounts[0])
+	}
+	if plan.Accounts[1].BaseURL != "https://proxy.example.com" {
+		t.Errorf("BaseURL = %s, want /v1 suffix stripped", plan.Accounts[1].BaseURL)
+	}
+
+	// 两个 gpt-4o 部署合并为一条账号池规则
+	if len(plan.RoutingRules) != 2 {
+		t.Fatalf("len(RoutingRules) = %d, want 2", len(plan.RoutingRules))
+	}
+	gpt := plan.RoutingRules[0]
+	if gpt.Pattern != "gpt-4o" || gpt.Provider != types.ProviderOpenAI || len(gpt.UpstreamIDs) != 2 {
+		t.Errorf("gpt-4o rule = %+v", gpt)
+	}
+
+	// 只有别名生成模型路由
+	if len(plan.ModelRoutes) != 1 || plan.ModelRoutes[0].SourceModel != "smart" || plan.ModelRoutes[0].TargetModel != "claude-3-5-sonnet-20241022" {
+		t.Errorf("ModelRoutes = %+v", plan.ModelRoutes)
+	}
+
+	if len(plan.Keys) != 1 || plan.Keys[0].RawKey != "sk-master" {
+		t.Errorf("Keys = %+v", plan.Keys)
+	}
+
+	warnings := strings.Join(plan.Warnings, "\n")
+	for _, expected := range []string{"bedrock", "rpm", "router_settings"} {
+		if !strings.Contains(warnings, expected) {
+			t.Errorf("warnings should mention %s: %v", expected, plan.Warnings)
+		}
+	}
+}
+
+func TestParseOneAPI(t *testing.T) {
+	export := `{
+  "channels": [
+    {"id": 1, "type": 1, "name": "openai", "key": "sk-a\nsk-b", "status": 1, "base_url": "",
+     "models": "gpt-4,gpt-3.5-turbo", "model_mapping": "{\"gpt-4\": \"gpt-4-0613\", \"gpt-5\": \"gpt-4o\"}"},
+    {"id": 2, "type": 14, "name": "claude", "key": "sk-ant", "status": 2, "models": "claude-3-opus-20240229"},
+    {"id": 3, "type": 15, "name": "baidu", "key": "x", "status": 1, "models": "ERNIE-Bot"}
+  ],
+  "tokens": [
+    {"name": "team", "key": "abc123", "status": 1, "unlimited_quota": true},
+    {"name": "trial", "key": "def456", "status": 2, "unlimited_quota": false}
+  ]
+}`
+	plan, err := ParseOneAPI([]byte(export))
+	if err != nil {
+		t.Fatalf("ParseOneAPI() error = %v", err)
+	}
+
+	if len(plan.Accounts) != 3 {
+		t.Fatalf("len(Accounts) = %d, want 3", len(plan.Accounts))
+	}
+	if plan.Accounts[0].ID != "upstream_oneapi_1_1" || plan.Accounts[1].APIKey != "sk-b" {
+		t.Errorf("multi-key channel accounts = %+v, %+v", plan.Accounts[0], plan.Accounts[1])
+	}
+	if plan.Accounts[2].Status != "disabled" {
+		t.Errorf("disabled channel should import as disabled account")
+	}
+
+	if len(plan.ModelRoutes) != 1 || plan.ModelRoutes[0].SourceModel != "gpt-4" || plan.ModelRoutes[0].TargetModel != "gpt-4-0613" {
+		t.Errorf("ModelRoutes = %+v", plan.ModelRoutes)
+	}
+	for _, rule := range plan.RoutingRules {
+		if rule.Pattern == "gpt-4-0613" && len(rule.UpstreamIDs) != 2 {
+			t.Errorf("gpt-4-0613 pool = %v, want both keys", rule.UpstreamIDs)
+		}
+	}
+
+	if len(plan.Keys) != 2 || plan.Keys[0].RawKey != "sk-abc123" || !plan.Keys[1].Disabled {
+		t.Errorf("Keys = %+v", plan.Keys)
+	}
+
+	warnings := strings.Join(plan.Warnings, "\n")
+	for _, expected := range []string{"baidu", "gpt-5", "trial"} {
+		if !strings.Contains(warnings, expected) {
+			t.Errorf("warnings should mention %s: %v", expected, plan.Warnings)
+		}
+	}
+}
+
+func TestParseOneAPI_ChannelArray(t *testing.T) {
+	plan, err := ParseOneAPI([]byte(`[{"id": 7, "type": 24, "name": "gemini", "key": "g-key", "status": 1, "models": "gemini-pro"}]`))
+	if err != nil {
+		t.Fatalf("ParseOneAPI() error = %v", err)
+	}
+	if len(plan.Accounts) != 1 || plan.Accounts[0].Provider != types.ProviderGoogle || plan.Accounts[0].ID != "upstream_oneapi_7" {
+		t.Errorf("Accounts = %+v", plan.Accounts)
+	}
+
+	if _, err := ParseOneAPI([]byte(`{}`)); err == nil {
+		t.Error("ParseOneAPI() should fail on empty export")
+	}
+}
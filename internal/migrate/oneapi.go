@@ -0,0 +1,200 @@
+package migrate
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// oneAPIChannel one-api 渠道（channels 表导出）
+type oneAPIChannel struct {
+	ID           int     `json:"id"`
+	Type         int     `json:"type"`
+	Name         string  `json:"name"`
+	Key          string  `json:"key"`
+	Status       int     `json:"status"`
+	BaseURL      *string `json:"base_url"`
+	Models       string  `json:"models"`
+	ModelMapping *string `json:"model_mapping"`
+	Other        string  `json:"other"`
+}
+
+// oneAPIToken one-api 令牌（tokens 表导出）
+type oneAPIToken struct {
+	Name           string  `json:"name"`
+	Key            string  `json:"key"`
+	Status         int     `json:"status"`
+	UnlimitedQuota bool    `json:"unlimited_quota"`
+	Models         *string `json:"models"`
+}
+
+// oneAPIExport 支持的导出格式：渠道数组、{"channels": [...], "tokens": [...]} 或管理API的 {"data": [...]}
+type oneAPIExport struct {
+	Channels []oneAPIChannel `json:"channels"`
+	Tokens   []oneAPIToken   `json:"tokens"`
+	Data     []oneAPIChannel `json:"data"`
+}
+
+// one-api 状态值
+const oneAPIStatusEnabled = 1
+
+// oneAPIChannelTypes one-api 渠道类型到网关提供商的映射
+var oneAPIChannelTypes = map[int]types.Provider{
+	1:  types.ProviderOpenAI,    // OpenAI
+	3:  types.ProviderAzure,     // Azure
+	8:  types.ProviderOpenAI,    // 自定义（OpenAI兼容）
+	14: types.ProviderAnthropic, // Anthropic
+	17: types.ProviderQwen,      // 阿里通义千问
+	24: types.ProviderGoogle,    // Gemini
+}
+
+// ParseOneAPI 解析 one-api 的渠道/令牌导出（JSON）
+// 渠道中的每个密钥生成一个上游账号，model_mapping 生成模型路由，渠道支持的模型生成带账号池的路由规则；
+// 令牌作为客户端密钥导入（自动补全 sk- 前缀）
+func ParseOneAPI(data []byte) (*Plan, error) {
+	var export oneAPIExport
+	if trimmed := bytes.TrimSpace(data); len(trimmed) > 0 && trimmed[0] == '[' {
+		if err := json.Unmarshal(trimmed, &export.Channels); err != nil {
+			return nil, fmt.Errorf("解析one-api导出失败: %w", err)
+		}
+	} else if err := json.Unmarshal(trimmed, &export); err != nil {
+		return nil, fmt.Errorf("解析one-api导出失败: %w", err)
+	}
+
+	channels := append(export.Channels, export.Data...)
+	if len(channels) == 0 && len(export.Tokens) == 0 {
+		return nil, fmt.Errorf("one-api导出中没有渠道或令牌")
+	}
+
+	plan := &Plan{Source: SourceOneAPI}
+	for _, channel := range channels {
+		parseOneAPIChannel(plan, &channel)
+	}
+
+	for _, token := range export.Tokens {
+		if token.Key == "" {
+			plan.warnf("令牌 %s: 缺少密钥，已跳过", token.Name)
+			continue
+		}
+
+		rawKey := token.Key
+		if !strings.HasPrefix(rawKey, "sk-") {
+			rawKey = "sk-" + rawKey
+		}
+		plan.Keys = append(plan.Keys, &ImportedKey{
+			Name:     "oneapi-" + token.Name,
+			RawKey:   rawKey,
+			Disabled: token.Status != oneAPIStatusEnabled,
+		})
+
+		if !token.UnlimitedQuota {
+			plan.warnf("令牌 %s: 额度限制未导入", token.Name)
+		}
+		if token.Models != nil && *token.Models != "" {
+			plan.warnf("令牌 %s: 模型限制未导入", token.Name)
+		}
+	}
+
+	return plan, nil
+}
+
+// parseOneAPIChannel 转换单个渠道
+func parseOneAPIChannel(plan *Plan, channel *oneAPIChannel) {
+	provider, ok := oneAPIChannelTypes[channel.Type]
+	if !ok {
+		plan.warnf("渠道 %s: 不支持的渠道类型 %d", channel.Name, channel.Type)
+		return
+	}
+
+	var keys []string
+	for _, key := range strings.Split(channel.Key, "\n") {
+		if key = strings.TrimSpace(key); key != "" {
+			keys = append(keys, key)
+		}
+	}
+	if len(keys) == 0 {
+		plan.warnf("渠道 %s: 缺少密钥，已跳过", channel.Name)
+		return
+	}
+
+	var baseURL string
+	if channel.BaseURL != nil {
+		baseURL = normalizeBaseURL(provider, *channel.BaseURL)
+	}
+	if provider == types.ProviderQwen && baseURL != "" {
+		// one-api 使用 DashScope 原生接口，网关使用 OpenAI 兼容模式
+		plan.warnf("渠道 %s: 通义千问的 base_url 未导入，使用默认地址", channel.Name)
+		baseURL = ""
+	}
+	if channel.Type == 8 && baseURL == "" {
+		plan.warnf("渠道 %s: 自定义渠道缺少 base_url，已跳过", channel.Name)
+		return
+	}
+	if channel.Other != "" {
+		plan.warnf("渠道 %s: 附加参数 %q 未导入", channel.Name, channel.Other)
+	}
+
+	mapping := make(map[string]string)
+	if channel.ModelMapping != nil && strings.TrimSpace(*channel.ModelMapping) != "" {
+		if err := json.Unmarshal([]byte(*channel.ModelMapping), &mapping); err != nil {
+			plan.warnf("渠道 %s: 无法解析 model_mapping: %v", channel.Name, err)
+		}
+	}
+
+	status := "active"
+	if channel.Status != oneAPIStatusEnabled {
+		status = "disabled"
+	}
+
+	for i, key := range keys {
+		accountID := fmt.Sprintf("upstream_oneapi_%d", channel.ID)
+		name := "oneapi-" + channel.Name
+		if len(keys) > 1 {
+			accountID = fmt.Sprintf("%s_%d", accountID, i+1)
+			name = fmt.Sprintf("%s-%d", name, i+1)
+		}
+
+		plan.Accounts = append(plan.Accounts, &types.UpstreamAccount{
+			ID:       accountID,
+			Name:     name,
+			Type:     types.UpstreamTypeAPIKey,
+			Status:   status,
+			Provider: provider,
+			BaseURL:  baseURL,
+			APIKey:   key,
+		})
+
+		for _, model := range strings.Split(channel.Models, ",") {
+			model = strings.TrimSpace(model)
+			if model == "" {
+				continue
+			}
+			target := model
+			if mapped, ok := mapping[model]; ok && mapped != "" {
+				target = mapped
+			}
+			plan.addModelRoute(model, target, provider)
+			plan.addToPool(target, provider, accountID)
+		}
+	}
+
+	// 不在模型列表中的映射不会被 one-api 使用，只做提示
+	for _, model := range sortedKeys(mapping) {
+		if !containsModel(channel.Models, model) {
+			plan.warnf("渠道 %s: 模型映射 %s 不在渠道模型列表中，未导入", channel.Name, model)
+		}
+	}
+}
+
+// containsModel 检查逗号分隔的模型列表是否包含模型
+func containsModel(models, model string) bool {
+	for _, candidate := range strings.Split(models, ",") {
+		if strings.TrimSpace(candidate) == model {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,211 @@
+package models
+
+import (
+	"regexp"
+	"sort"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 模型名校验模式
+const (
+	ValidationOff       = "off"       // 不处理（默认）
+	ValidationNormalize = "normalize" // 规范化已知模型的变体，未知模型原样透传
+	ValidationStrict    = "strict"    // 规范化并拒绝未知模型
+)
+
+// Model 已知模型及其别名
+type Model struct {
+	ID       string         `json:"id"`
+	Provider types.Provider `json:"provider"`
+	Aliases  []string       `json:"aliases,omitempty"`
+	NoTools  bool           `json:"no_tools,omitempty"` // 不支持工具调用（function calling）
+}
+
+// defaultModels 内置模型表，别名按大小写不敏感匹配
+var defaultModels = []Model{
+	// Anthropic
+	{ID: "claude-opus-4-1-20250805", Provider: types.ProviderAnthropic, Aliases: []string{"claude-opus-4-1"}},
+	{ID: "claude-opus-4-20250514", Provider: types.ProviderAnthropic, Aliases: []string{"claude-opus-4-0", "claude-opus-4"}},
+	{ID: "claude-sonnet-4-20250514", Provider: types.ProviderAnthropic, Aliases: []string{"claude-sonnet-4-0", "claude-sonnet-4"}},
+	{ID: "claude-3-7-sonnet-latest", Provider: types.ProviderAnthropic, Aliases: []string{"claude-3-7-sonnet"}},
+	{ID: "claude-3-7-sonnet-20250219", Provider: types.ProviderAnthropic},
+	{ID: "claude-3-5-sonnet-latest", Provider: types.ProviderAnthropic, Aliases: []string{"claude-3-5-sonnet"}},
+	{ID: "claude-3-5-sonnet-20241022", Provider: types.ProviderAnthropic},
+	{ID: "claude-3-5-sonnet-20240620", Provider: types.ProviderAnthropic},
+	{ID: "claude-3-5-haiku-latest", Provider: types.ProviderAnthropic, Aliases: []string{"claude-3-5-haiku"}},
+	{ID: "claude-3-5-haiku-20241022", Provider: types.ProviderAnthropic},
+	{ID: "claude-3-opus-latest", Provider: types.ProviderAnthropic, Aliases: []string{"claude-3-opus"}},
+	{ID: "claude-3-opus-20240229", Provider: types.ProviderAnthropic},
+	{ID: "claude-3-haiku-20240307", Provider: types.ProviderAnthropic, Aliases: []string{"claude-3-haiku"}},
+
+	// OpenAI
+	{ID: "gpt-4o", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4o-2024-08-06", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4o-2024-11-20", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4o-mini", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4o-mini-2024-07-18", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4.1", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4.1-mini", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4.1-nano", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4-turbo", Provider: types.ProviderOpenAI},
+	{ID: "gpt-4", Provider: types.ProviderOpenAI},
+	{ID: "gpt-3.5-turbo", Provider: types.ProviderOpenAI},
+	{ID: "o1", Provider: types.ProviderOpenAI},
+	{ID: "o1-mini", Provider: types.ProviderOpenAI, NoTools: true},
+	{ID: "o3-mini", Provider: types.ProviderOpenAI},
+
+	// Qwen
+	{ID: "qwen3-coder-plus", Provider: types.ProviderQwen},
+	{ID: "qwen-max", Provider: types.ProviderQwen},
+	{ID: "qwen-plus", Provider: types.ProviderQwen},
+	{ID: "qwen-turbo", Provider: types.ProviderQwen},
+}
+
+// dashedDateSuffix 形如 -2024-10-22 的日期后缀（OpenAI风格）
+var dashedDateSuffix = regexp.MustCompile(`-(\d{4})-(\d{2})-(\d{2})$`)
+
+// compactDateSuffix 形如 -20241022 的日期后缀（Anthropic风格）
+var compactDateSuffix = regexp.MustCompile(`-(\d{4})(\d{2})(\d{2})$`)
+
+// Registry 模型注册表，负责模型名规范化
+type Registry struct {
+	models []Model
+	index  map[string]string // 规范化后的ID/别名 -> 模型ID
+}
+
+// NewRegistry 创建模型注册表
+func NewRegistry(models []Model) *Registry {
+	r := &Registry{
+		models: models,
+		index:  make(map[string]string),
+	}
+	for _, model := range models {
+		r.index[normalizeKey(model.ID)] = model.ID
+		for _, alias := range model.Aliases {
+			r.index[normalizeKey(alias)] = model.ID
+		}
+	}
+	return r
+}
+
+// Default 返回内置模型表的注册表
+func Default() *Registry {
+	return NewRegistry(defaultModels)
+}
+
+// Models 返回注册的模型
+func (r *Registry) Models() []Model {
+	return r.models
+}
+
+// Normalize 将大小写、分隔符、别名和日期后缀的变体映射为注册表中的模型ID
+func (r *Registry) Normalize(name string) (string, bool) {
+	key := normalizeKey(name)
+	if key == "" {
+		return "", false
+	}
+
+	candidates := []string{key}
+	// claude-3.5-sonnet -> claude-3-5-sonnet
+	if strings.Contains(key, ".") {
+		candidates = append(candidates, strings.ReplaceAll(key, ".", "-"))
+	}
+	// 两种日期后缀写法互相转换
+	for _, candidate := range candidates {
+		if dashedDateSuffix.MatchString(candidate) {
+			candidates = append(candidates, dashedDateSuffix.ReplaceAllString(candidate, "-$1$2$3"))
+		} else if compactDateSuffix.MatchString(candidate) {
+			candidates = append(candidates, compactDateSuffix.ReplaceAllString(candidate, "-$1-$2-$3"))
+		}
+	}
+
+	for _, candidate := range candidates {
+		if id, ok := r.index[candidate]; ok {
+			return id, true
+		}
+	}
+	return "", false
+}
+
+// SupportsTools 模型是否支持工具调用，未知模型视为支持（由上游决定）
+func (r *Registry) SupportsTools(name string) bool {
+	id, ok := r.Normalize(name)
+	if !ok {
+		return true
+	}
+	for _, model := range r.models {
+		if model.ID == id {
+			return !model.NoTools
+		}
+	}
+	return true
+}
+
+// Suggest 从候选模型中找出与name最接近的几个，用于 "did you mean" 提示
+func Suggest(name string, candidates []string, limit int) []string {
+	key := normalizeKey(name)
+	maxDistance := len(key) / 3
+	if maxDistance < 3 {
+		maxDistance = 3
+	}
+
+	type match struct {
+		name     string
+		distance int
+	}
+	seen := make(map[string]bool)
+	var matches []match
+	for _, candidate := range candidates {
+		if seen[candidate] {
+			continue
+		}
+		seen[candidate] = true
+
+		if d := levenshtein(key, normalizeKey(candidate)); d <= maxDistance {
+			matches = append(matches, match{candidate, d})
+		}
+	}
+
+	sort.Slice(matches, func(i, j int) bool {
+		if matches[i].distance != matches[j].distance {
+			return matches[i].distance < matches[j].distance
+		}
+		return matches[i].name < matches[j].name
+	})
+
+	result := make([]string, 0, limit)
+	for i := 0; i < len(matches) && i < limit; i++ {
+		result = append(result, matches[i].name)
+	}
+	return result
+}
+
+// normalizeKey 统一大小写和分隔符
+func normalizeKey(name string) string {
+	key := strings.ToLower(strings.TrimSpace(name))
+	return strings.NewReplacer(" ", "-", "_", "-").Replace(key)
+}
+
+// levenshtein 计算编辑距离
+func levenshtein(a, b string) int {
+	prev := make([]int, len(b)+1)
+	curr := make([]int, len(b)+1)
+	for j := range prev {
+		prev[j] = j
+	}
+
+	for i := 1; i <= len(a); i++ {
+		curr[0] = i
+		for j := 1; j <= len(b); j++ {
+			cost := 1
+			if a[i-1] == b[j-1] {
+				cost = 0
+			}
+			curr[j] = min(prev[j]+1, curr[j-1]+1, prev[j-1]+cost)
+		}
+		prev, curr = curr, prev
+	}
+	return prev[len(b)]
+}
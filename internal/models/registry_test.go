@@ -0,0 +1,70 @@
+package models
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestRegistry_Normalize(t *testing.T) {
+	r := Default()
+
+	tests := []struct {
+		input string
+		want  string
+		ok    bool
+	}{
+		{"claude-3-5-sonnet-20241022", "claude-3-5-sonnet-20241022", true},
+		{"Claude-3-5-Sonnet", "claude-3-5-sonnet-latest", true},
+		{"claude 3.5 sonnet", "claude-3-5-sonnet-latest", true},
+		{"claude_3_5_haiku", "claude-3-5-haiku-latest", true},
+		{"claude-3-5-sonnet-2024-10-22", "claude-3-5-sonnet-20241022", true},
+		{"CLAUDE-SONNET-4", "claude-sonnet-4-20250514", true},
+		{"GPT-4o", "gpt-4o", true},
+		{"gpt-4o-20240806", "gpt-4o-2024-08-06", true},
+		{"gpt-4.1-mini", "gpt-4.1-mini", true},
+		{"claude-3-5-sonet", "", false},
+		{"", "", false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.input, func(t *testing.T) {
+			got, ok := r.Normalize(tt.input)
+			if got != tt.want || ok != tt.ok {
+				t.Errorf("Normalize(%q) = (%q, %v), want (%q, %v)", tt.input, got, ok, tt.want, tt.ok)
+			}
+		})
+	}
+}
+
+func TestSuggest(t *testing.T) {
+	candidates := []string{"claude-3-5-sonnet-latest", "claude-3-5-haiku-latest", "gpt-4o", "gpt-4o-mini", "qwen-max"}
+
+	got := Suggest("claude-3-5-sonet-latest", candidates, 3)
+	if len(got) == 0 || got[0] != "claude-3-5-sonnet-latest" {
+		t.Errorf("Suggest() = %v, 期望首个为 claude-3-5-sonnet-latest", got)
+	}
+
+	got = Suggest("gpt-4", candidates, 2)
+	if want := []string{"gpt-4o"}; !reflect.DeepEqual(got, want) {
+		t.Errorf("Suggest() = %v, want %v", got, want)
+	}
+
+	if got := Suggest("totally-unknown-model", candidates, 3); len(got) != 0 {
+		t.Errorf("不相近的模型不应有建议, got %v", got)
+	}
+}
+
+func TestRegistry_SupportsTools(t *testing.T) {
+	r := Default()
+	for name, want := range map[string]bool{
+		"gpt-4o":            true,
+		"Claude-3-5-Sonnet": true,
+		"o1-mini":           false,
+		"O1-Mini":           false,
+		"unknown-model":     true,
+	} {
+		if got := r.SupportsTools(name); got != want {
+			t.Errorf("SupportsTools(%q) = %v, want %v", name, got, want)
+		}
+	}
+}
@@ -0,0 +1,289 @@
+package moderation
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"regexp"
+	"sort"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	defaultEndpoint = "https://api.openai.com/v1/moderations"
+	defaultModel    = "omni-moderation-latest"
+	defaultTimeout  = 5 * time.Second
+	maxErrorBody    = 512 // 错误信息中保留的响应长度
+)
+
+// CodeModelFlagged 审核模型标记请求时返回的策略代码
+const CodeModelFlagged = "model_flagged"
+
+// Result 请求被拦截的原因
+type Result struct {
+	Policy     string   `json:"policy"`               // 命中的策略名称
+	Code       string   `json:"code"`                 // 规则配置的策略代码，审核模型拦截时为 model_flagged
+	Categories []string `json:"categories,omitempty"` // 审核模型标记的类别
+}
+
+// Gate 转发上游之前的内容审核，为nil时不审核
+type Gate struct {
+	policies []*policy
+	model    *modelClient
+}
+
+// policy 编译后的审核策略
+type policy struct {
+	name       string
+	keys       map[string]bool
+	rules      []*rule
+	useModel   bool
+	categories map[string]bool
+}
+
+// rule 编译后的审核规则
+type rule struct {
+	code      string
+	blocklist []string // 已转为小写
+	patterns  []*regexp.Regexp
+}
+
+// modelClient 调用 OpenAI moderations 格式的审核模型
+type modelClient struct {
+	endpoint   string
+	apiKey     string
+	model      string
+	failOpen   bool
+	httpClient *http.Client
+}
+
+// New 编译内容审核配置，没有策略时返回nil
+func New(config *types.ModerationConfig) (*Gate, error) {
+	if config == nil || len(config.Policies) == 0 {
+		return nil, nil
+	}
+
+	gate := &Gate{}
+	for _, pc := range config.Policies {
+		p := &policy{name: pc.Name, useModel: pc.UseModel}
+		if len(pc.Keys) > 0 {
+			p.keys = make(map[string]bool, len(pc.Keys))
+			for _, key := range pc.Keys {
+				p.keys[key] = true
+			}
+		}
+		if len(pc.Categories) > 0 {
+			p.categories = make(map[string]bool, len(pc.Categories))
+			for _, category := range pc.Categories {
+				p.categories[category] = true
+			}
+		}
+		for _, rc := range pc.Rules {
+			r := &rule{code: rc.Code}
+			for _, word := range rc.Blocklist {
+				r.blocklist = append(r.blocklist, strings.ToLower(word))
+			}
+			for _, pattern := range rc.Patterns {
+				re, err := regexp.Compile(pattern)
+				if err != nil {
+					return nil, fmt.Errorf("审核策略 %s: 无效的正则表达式 %q: %w", pc.Name, pattern, err)
+				}
+				r.patterns = append(r.patterns, re)
+			}
+			p.rules = append(p.rules, r)
+		}
+		if p.useModel && gate.model == nil {
+			gate.model = newModelClient(&config.Model)
+		}
+		gate.policies = append(gate.policies, p)
+	}
+	return gate, nil
+}
+
+// newModelClient 按配置创建审核模型客户端，未配置的项使用默认值
+func newModelClient(config *types.ModerationModelConfig) *modelClient {
+	client := &modelClient{
+		endpoint:   config.Endpoint,
+		apiKey:     config.APIKey,
+		model:      config.Model,
+		failOpen:   config.FailOpen,
+		httpClient: &http.Client{Timeout: defaultTimeout},
+	}
+	if client.endpoint == "" {
+		client.endpoint = defaultEndpoint
+	}
+	if client.model == "" {
+		client.model = defaultModel
+	}
+	if config.TimeoutSeconds > 0 {
+		client.httpClient.Timeout = time.Duration(config.TimeoutSeconds) * time.Second
+	}
+	return client
+}
+
+// policyFor 选择Key使用的策略：第一个包含该Key的策略，都不包含时使用第一个没有配置 keys 的策略
+func (g *Gate) policyFor(keyID string) *policy {
+	var fallback *policy
+	for _, p := range g.policies {
+		if p.keys == nil {
+			if fallback == nil {
+				fallback = p
+			}
+			continue
+		}
+		if p.keys[keyID] {
+			return p
+		}
+	}
+	return fallback
+}
+
+// Check 审核请求中的文本（所有消息和系统提示词），被拦截时返回拦截原因，允许时返回nil。
+// 审核模型调用失败且未配置 fail_open 时返回错误
+func (g *Gate) Check(ctx context.Context, request *types.UnifiedRequest, keyID string) (*Result, error) {
+	if g == nil {
+		return nil, nil
+	}
+	p := g.policyFor(keyID)
+	if p == nil {
+		return nil, nil
+	}
+
+	text := RequestText(request)
+	if text == "" {
+		return nil, nil
+	}
+	if code, ok := p.matchRules(text); ok {
+		return &Result{Policy: p.name, Code: code}, nil
+	}
+	if !p.useModel || g.model == nil {
+		return nil, nil
+	}
+
+	flagged, err := g.model.classify(ctx, text)
+	if err != nil {
+		if g.model.failOpen {
+			logger.Warn("审核模型调用失败，按 fail_open 放行请求: %v", err)
+			return nil, nil
+		}
+		return nil, err
+	}
+	var blocked []string
+	for _, category := range flagged {
+		if p.categories == nil || p.categories[category] {
+			blocked = append(blocked, category)
+		}
+	}
+	if len(blocked) == 0 {
+		return nil, nil
+	}
+	return &Result{Policy: p.name, Code: CodeModelFlagged, Categories: blocked}, nil
+}
+
+// matchRules 按顺序检查规则，返回第一个命中规则的策略代码
+func (p *policy) matchRules(text string) (string, bool) {
+	lower := strings.ToLower(text)
+	for _, r := range p.rules {
+		for _, word := range r.blocklist {
+			if strings.Contains(lower, word) {
+				return r.code, true
+			}
+		}
+		for _, re := range r.patterns {
+			if re.MatchString(text) {
+				return r.code, true
+			}
+		}
+	}
+	return "", false
+}
+
+// moderationResponse OpenAI moderations 接口的响应
+type moderationResponse struct {
+	Results []struct {
+		Flagged    bool            `json:"flagged"`
+		Categories map[string]bool `json:"categories"`
+	} `json:"results"`
+}
+
+// classify 调用审核模型，返回被标记的类别（按名称排序），没有标记时返回空
+func (c *modelClient) classify(ctx context.Context, text string) ([]string, error) {
+	body, err := json.Marshal(map[string]string{"model": c.model, "input": text})
+	if err != nil {
+		return nil, err
+	}
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, c.endpoint, bytes.NewReader(body))
+	if err != nil {
+		return nil, fmt.Errorf("创建审核请求失败: %w", err)
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if c.apiKey != "" {
+		req.Header.Set("Authorization", "Bearer "+c.apiKey)
+	}
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		return nil, fmt.Errorf("调用审核模型失败: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+	if resp.StatusCode != http.StatusOK {
+		detail, _ := io.ReadAll(io.LimitReader(resp.Body, maxErrorBody))
+		return nil, fmt.Errorf("审核模型返回 %d: %s", resp.StatusCode, strings.TrimSpace(string(detail)))
+	}
+
+	var result moderationResponse
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return nil, fmt.Errorf("解析审核模型响应失败: %w", err)
+	}
+	var flagged []string
+	for _, item := range result.Results {
+		if !item.Flagged {
+			continue
+		}
+		for category, hit := range item.Categories {
+			if hit {
+				flagged = append(flagged, category)
+			}
+		}
+	}
+	sort.Strings(flagged)
+	return flagged, nil
+}
+
+// RequestText 拼接请求中所有消息和系统提示词的文本内容（忽略图片、工具调用等非文本块）
+func RequestText(request *types.UnifiedRequest) string {
+	var builder strings.Builder
+	write := func(text string) {
+		if text != "" {
+			builder.WriteString(text)
+			builder.WriteByte('\n')
+		}
+	}
+	if request.OriginalSystem != nil {
+		for _, block := range request.OriginalSystem.ToArray() {
+			write(block.Text)
+		}
+	}
+	for _, message := range request.Messages {
+		switch content := message.Content.(type) {
+		case string:
+			write(content)
+		case []interface{}:
+			for _, item := range content {
+				if block, ok := item.(map[string]interface{}); ok {
+					if text, ok := block["text"].(string); ok {
+						write(text)
+					}
+				}
+			}
+		}
+	}
+	return builder.String()
+}
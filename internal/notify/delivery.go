@@ -0,0 +1,285 @@
+package notify
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"os"
+	"path/filepath"
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 投递状态
+const (
+	StatusPending   = "pending"   // 等待投递或重试
+	StatusDelivered = "delivered" // Webhook返回2xx
+	StatusFailed    = "failed"    // 达到最大尝试次数仍失败
+)
+
+const (
+	defaultMaxAttempts = 3
+	retryBaseDelay     = time.Minute
+	maxDeliveries      = 500 // 保留的投递记录数，超出时淘汰最旧的已结束记录
+	deliveriesFile     = "deliveries.json"
+	webhookTimeout     = 10 * time.Second
+)
+
+// Delivery 一次事件到一个Webhook的投递记录
+type Delivery struct {
+	ID            string    `json:"id"`
+	Event         Event     `json:"event"`
+	Webhook       string    `json:"webhook"`
+	Status        string    `json:"status"`
+	Attempts      int       `json:"attempts"`
+	LastStatus    int       `json:"last_status,omitempty"` // 最近一次尝试的HTTP状态码
+	LastError     string    `json:"last_error,omitempty"`
+	NextAttemptAt time.Time `json:"next_attempt_at"`
+	CreatedAt     time.Time `json:"created_at"`
+	UpdatedAt     time.Time `json:"updated_at"`
+}
+
+// DeliverPending 投递到期的待投递记录，失败时按指数退避安排重试
+func (s *Service) DeliverPending(now time.Time) {
+	s.sender.mutex.Lock()
+	defer s.sender.mutex.Unlock()
+
+	cfg := *s.settings()
+	maxAttempts := defaultMaxAttempts
+	if cfg.MaxAttempts > 0 {
+		maxAttempts = cfg.MaxAttempts
+	}
+	webhooks := make(map[string]types.WebhookConfig, len(cfg.Webhooks))
+	for _, webhook := range cfg.Webhooks {
+		webhooks[webhook.Name] = webhook
+	}
+
+	for _, delivery := range s.store.due(now) {
+		webhook, exists := webhooks[delivery.Webhook]
+		if !exists {
+			delivery.Status = StatusFailed
+			delivery.LastError = "webhook no longer configured"
+			delivery.UpdatedAt = now
+			s.store.update(delivery)
+			continue
+		}
+
+		delivery.Attempts++
+		delivery.UpdatedAt = now
+		status, err := s.sender.send(webhook, delivery.Event)
+		delivery.LastStatus = status
+		if err == nil {
+			delivery.Status = StatusDelivered
+			delivery.LastError = ""
+		} else {
+			delivery.LastError = err.Error()
+			if delivery.Attempts >= maxAttempts {
+				delivery.Status = StatusFailed
+				logger.Warn("通知投递失败，已放弃: webhook=%s, event=%s, error=%v", webhook.Name, delivery.Event.Type, err)
+			} else {
+				delivery.NextAttemptAt = now.Add(retryBaseDelay << (delivery.Attempts - 1))
+				logger.Warn("通知投递失败，将在 %s 重试: webhook=%s, event=%s, error=%v", delivery.NextAttemptAt.Format(time.RFC3339), webhook.Name, delivery.Event.Type, err)
+			}
+		}
+		s.store.update(delivery)
+	}
+
+	if err := s.store.save(); err != nil {
+		logger.Warn("保存通知投递记录失败: %v", err)
+	}
+}
+
+// sender 发送Webhook请求，mutex 保证同一时间只有一轮投递
+type sender struct {
+	client *http.Client
+	mutex  sync.Mutex
+}
+
+// newSender 创建Webhook发送器
+func newSender() *sender {
+	return &sender{client: &http.Client{Timeout: webhookTimeout}}
+}
+
+// send 发送一次Webhook请求，返回HTTP状态码（未收到响应时为0）
+func (s *sender) send(webhook types.WebhookConfig, event Event) (int, error) {
+	payload, err := webhookPayload(webhook, event)
+	if err != nil {
+		return 0, err
+	}
+
+	resp, err := s.client.Post(webhook.URL, "application/json", bytes.NewReader(payload))
+	if err != nil {
+		return 0, fmt.Errorf("请求Webhook失败: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+	_, _ = io.Copy(io.Discard, io.LimitReader(resp.Body, 64*1024))
+
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return resp.StatusCode, fmt.Errorf("webhook返回状态码 %d", resp.StatusCode)
+	}
+	return resp.StatusCode, nil
+}
+
+// webhookPayload 按Webhook格式生成请求体：slack 发送 {"text": ...}，其他发送事件JSON
+func webhookPayload(webhook types.WebhookConfig, event Event) ([]byte, error) {
+	if webhook.Format == "slack" {
+		return json.Marshal(map[string]string{"text": fmt.Sprintf("[LLM Gateway] %s", event.Message)})
+	}
+	return json.Marshal(event)
+}
+
+// deliveryStore 投递记录，保存在目录下的 deliveries.json，重启后继续重试未完成的投递
+type deliveryStore struct {
+	path       string
+	deliveries []*Delivery // 按创建时间从旧到新
+	mutex      sync.Mutex
+}
+
+// newDeliveryStore 创建投递记录存储，dir 为空时使用 ~/.llm-gateway/notifications
+func newDeliveryStore(dir string) *deliveryStore {
+	if dir == "" {
+		homeDir, err := os.UserHomeDir()
+		if err != nil {
+			dir = filepath.Join(".llm-gateway", "notifications")
+		} else {
+			dir = filepath.Join(homeDir, ".llm-gateway", "notifications")
+		}
+	}
+	return &deliveryStore{path: filepath.Join(dir, deliveriesFile)}
+}
+
+// load 从文件加载投递记录，文件不存在时为空
+func (s *deliveryStore) load() error {
+	data, err := os.ReadFile(s.path)
+	if os.IsNotExist(err) {
+		return nil
+	}
+	if err != nil {
+		return fmt.Errorf("读取投递记录失败: %w", err)
+	}
+
+	var deliveries []*Delivery
+	if err := json.Unmarshal(data, &deliveries); err != nil {
+		return fmt.Errorf("解析投递记录失败: %w", err)
+	}
+
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	s.deliveries = deliveries
+	return nil
+}
+
+// save 将投递记录写入文件
+func (s *deliveryStore) save() error {
+	s.mutex.Lock()
+	data, err := json.MarshalIndent(s.deliveries, "", "  ")
+	s.mutex.Unlock()
+	if err != nil {
+		return fmt.Errorf("序列化投递记录失败: %w", err)
+	}
+
+	if err := os.MkdirAll(filepath.Dir(s.path), 0700); err != nil {
+		return fmt.Errorf("创建通知目录失败: %w", err)
+	}
+	if err := os.WriteFile(s.path, data, 0600); err != nil {
+		return fmt.Errorf("写入投递记录失败: %w", err)
+	}
+	return nil
+}
+
+// add 追加投递记录并保存
+func (s *deliveryStore) add(deliveries []*Delivery) error {
+	s.mutex.Lock()
+	s.deliveries = append(s.deliveries, deliveries...)
+	s.trimLocked()
+	s.mutex.Unlock()
+	return s.save()
+}
+
+// trimLocked 超过容量时淘汰最旧的已结束记录（待投递的记录不淘汰）
+func (s *deliveryStore) trimLocked() {
+	excess := len(s.deliveries) - maxDeliveries
+	if excess <= 0 {
+		return
+	}
+
+	kept := make([]*Delivery, 0, maxDeliveries)
+	for _, delivery := range s.deliveries {
+		if excess > 0 && delivery.Status != StatusPending {
+			excess--
+			continue
+		}
+		kept = append(kept, delivery)
+	}
+	s.deliveries = kept
+}
+
+// due 返回到期的待投递记录副本
+func (s *deliveryStore) due(now time.Time) []Delivery {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	var result []Delivery
+	for _, delivery := range s.deliveries {
+		if delivery.Status == StatusPending && !delivery.NextAttemptAt.After(now) {
+			result = append(result, *delivery)
+		}
+	}
+	return result
+}
+
+// update 按ID替换投递记录
+func (s *deliveryStore) update(delivery Delivery) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	for i, existing := range s.deliveries {
+		if existing.ID == delivery.ID {
+			updated := delivery
+			s.deliveries[i] = &updated
+			return
+		}
+	}
+}
+
+// get 按ID返回投递记录副本
+func (s *deliveryStore) get(ids []string) []Delivery {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	wanted := make(map[string]bool, len(ids))
+	for _, id := range ids {
+		wanted[id] = true
+	}
+	result := make([]Delivery, 0, len(ids))
+	for _, delivery := range s.deliveries {
+		if wanted[delivery.ID] {
+			result = append(result, *delivery)
+		}
+	}
+	return result
+}
+
+// list 返回最近的投递记录副本，从新到旧，limit<=0 时返回全部
+func (s *deliveryStore) list(limit int) []Delivery {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	result := make([]Delivery, 0, len(s.deliveries))
+	for _, delivery := range s.deliveries {
+		result = append(result, *delivery)
+	}
+	sort.SliceStable(result, func(i, j int) bool {
+		return result[i].CreatedAt.After(result[j].CreatedAt)
+	})
+	if limit > 0 && len(result) > limit {
+		result = result[:limit]
+	}
+	return result
+}
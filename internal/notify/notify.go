@@ -0,0 +1,487 @@
+package notify
+
+import (
+	"crypto/rand"
+	"encoding/hex"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/quota"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 事件类型
+const (
+	EventCostThreshold      = "cost_threshold"       // 全局或单个Key当日费用超过阈值
+	EventErrorRate          = "error_rate"           // 错误率超过阈值
+	EventErrorClassRate     = "error_class_rate"     // 某个提供商的某一类上游错误（如 quota）占比超过规则阈值
+	EventHealthChange       = "health_change"        // 上游账号健康状态变化
+	EventCanaryFailure      = "canary_failure"       // 合成探针断言失败
+	EventQuotaWarning       = "quota_warning"        // Key配额或上游账号限流额度的用量达到软告警阈值
+	EventAccountDisabled    = "account_disabled"     // 上游账号因凭证连续被拒绝而被自动停用
+	EventKeyPendingApproval = "key_pending_approval" // 自助创建的Gateway Key等待 admin 批准
+	EventSLOBurnRate        = "slo_burn_rate"        // Gateway Key 或上游账号的SLO错误预算燃烧率超过阈值
+	EventTest               = "test"                 // 管理界面发送的测试通知
+)
+
+const (
+	defaultErrorRateWindow      = 15 * time.Minute
+	defaultErrorRateMinRequests = 20
+)
+
+// Event 一次告警事件
+type Event struct {
+	ID        string                 `json:"id"`
+	Type      string                 `json:"type"`
+	Message   string                 `json:"message"`
+	Details   map[string]interface{} `json:"details,omitempty"`
+	Timestamp time.Time              `json:"timestamp"`
+}
+
+// AccountSource 上游账号来源
+type AccountSource interface {
+	ListAccounts() []*types.UpstreamAccount
+}
+
+// RateLimitSource 上游报告的账号剩余额度
+type RateLimitSource interface {
+	Get(upstreamID string, now time.Time) *upstream.RateLimitState
+}
+
+// Service 定期检查费用、错误率、账号健康状态和账号限流额度，触发告警时向订阅的Webhook投递
+type Service struct {
+	settings   func() *types.NotificationConfig // 每次使用时读取，修改或重新加载配置后立即生效
+	recorder   *stats.Recorder
+	accounts   AccountSource
+	rateLimits RateLimitSource // 为nil时不检查账号限流额度
+	interval   time.Duration
+	store      *deliveryStore
+	sender     *sender
+
+	costDay        string          // costFired 对应的UTC日期
+	costFired      map[string]bool // 当日已告警的费用范围（global 或 key:<id>）
+	errorAlerting  bool            // 错误率告警中，恢复前不重复告警
+	classAlerting  map[string]bool // 告警中的错误分类规则（规则序号:提供商），恢复前不重复告警
+	healthStatuses map[string]string
+	quotaLevels    *quota.WarningLevels // 账号限流额度已告警的阈值
+
+	stopCh chan struct{}
+	mutex  sync.Mutex
+}
+
+// NewService 创建通知服务，settings 返回当前的通知配置；加载持久化的投递记录（目录在创建时确定）
+func NewService(settings func() *types.NotificationConfig, recorder *stats.Recorder, accounts AccountSource, interval time.Duration) *Service {
+	if interval <= 0 {
+		interval = time.Minute
+	}
+
+	store := newDeliveryStore(settings().Dir)
+	if err := store.load(); err != nil {
+		logger.Warn("加载通知投递记录失败: %v", err)
+	}
+
+	return &Service{
+		settings:       settings,
+		recorder:       recorder,
+		accounts:       accounts,
+		interval:       interval,
+		store:          store,
+		sender:         newSender(),
+		costFired:      make(map[string]bool),
+		classAlerting:  make(map[string]bool),
+		healthStatuses: make(map[string]string),
+		quotaLevels:    quota.NewWarningLevels(),
+	}
+}
+
+// SetRateLimitSource 设置上游账号限流额度来源，设置后检查额度用量是否达到软告警阈值
+func (s *Service) SetRateLimitSource(source RateLimitSource) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	s.rateLimits = source
+}
+
+// Start 启动后台检查与投递重试
+func (s *Service) Start() {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		return
+	}
+	s.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(s.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				now := time.Now()
+				s.Evaluate(now)
+				s.DeliverPending(now)
+			case <-stopCh:
+				return
+			}
+		}
+	}(s.stopCh)
+}
+
+// Stop 停止后台任务
+func (s *Service) Stop() {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		close(s.stopCh)
+		s.stopCh = nil
+	}
+}
+
+// Evaluate 检查告警条件，为新触发的事件创建投递记录并返回这些事件（通知未启用时不检查）
+func (s *Service) Evaluate(now time.Time) []Event {
+	cfg := *s.settings()
+	if !cfg.Enabled {
+		return nil
+	}
+
+	s.mutex.Lock()
+	var events []Event
+	events = append(events, s.checkCost(cfg, now)...)
+	events = append(events, s.checkErrorRate(cfg, now)...)
+	events = append(events, s.checkErrorClasses(cfg, now)...)
+	events = append(events, s.checkHealth(cfg, now)...)
+	events = append(events, s.checkRateLimits(cfg, now)...)
+	s.mutex.Unlock()
+
+	for _, event := range events {
+		logger.Warn("触发告警通知: %s", event.Message)
+		s.enqueue(cfg, event)
+	}
+	return events
+}
+
+// Alert 由其他组件（如合成探针）主动触发告警，下一次检查时投递；通知未启用时忽略
+func (s *Service) Alert(eventType, message string, details map[string]interface{}, now time.Time) {
+	cfg := *s.settings()
+	if !cfg.Enabled {
+		return
+	}
+
+	event := newEvent(eventType, message, details, now)
+	logger.Warn("触发告警通知: %s", event.Message)
+	s.enqueue(cfg, event)
+}
+
+// SendTest 向所有Webhook发送测试通知并立即投递
+func (s *Service) SendTest(now time.Time) []Delivery {
+	event := newEvent(EventTest, "LLM Gateway test notification", nil, now)
+	deliveries := s.enqueue(*s.settings(), event)
+	s.DeliverPending(now)
+
+	ids := make([]string, 0, len(deliveries))
+	for _, delivery := range deliveries {
+		ids = append(ids, delivery.ID)
+	}
+	return s.store.get(ids)
+}
+
+// Deliveries 返回最近的投递记录，从新到旧
+func (s *Service) Deliveries(limit int) []Delivery {
+	return s.store.list(limit)
+}
+
+// checkCost 检查全局与单个Key当日费用，每个范围每天只告警一次
+func (s *Service) checkCost(cfg types.NotificationConfig, now time.Time) []Event {
+	if cfg.DailyCostUSD <= 0 && cfg.KeyDailyCostUSD <= 0 {
+		return nil
+	}
+
+	day := now.UTC().Format("2006-01-02")
+	if day != s.costDay {
+		s.costDay = day
+		s.costFired = make(map[string]bool)
+	}
+
+	dayStart := time.Date(now.UTC().Year(), now.UTC().Month(), now.UTC().Day(), 0, 0, 0, 0, time.UTC)
+	total := 0.0
+	byKey := make(map[string]float64)
+	for _, record := range s.recorder.Query(stats.Filter{Since: dayStart}) {
+		total += record.CostUSD
+		if record.GatewayKeyID != "" {
+			byKey[record.GatewayKeyID] += record.CostUSD
+		}
+	}
+
+	var events []Event
+	if cfg.DailyCostUSD > 0 && total >= cfg.DailyCostUSD && !s.costFired["global"] {
+		s.costFired["global"] = true
+		events = append(events, newEvent(EventCostThreshold,
+			fmt.Sprintf("Daily spend $%.2f reached the global threshold $%.2f", total, cfg.DailyCostUSD),
+			map[string]interface{}{"scope": "global", "date": day, "cost_usd": total, "threshold_usd": cfg.DailyCostUSD}, now))
+	}
+	if cfg.KeyDailyCostUSD > 0 {
+		for keyID, cost := range byKey {
+			scope := "key:" + keyID
+			if cost < cfg.KeyDailyCostUSD || s.costFired[scope] {
+				continue
+			}
+			s.costFired[scope] = true
+			events = append(events, newEvent(EventCostThreshold,
+				fmt.Sprintf("Daily spend $%.2f of key %s reached the per-key threshold $%.2f", cost, keyID, cfg.KeyDailyCostUSD),
+				map[string]interface{}{"scope": "key", "key_id": keyID, "date": day, "cost_usd": cost, "threshold_usd": cfg.KeyDailyCostUSD}, now))
+		}
+	}
+	return events
+}
+
+// checkErrorRate 检查窗口内的错误率，超过阈值时告警一次，恢复后才会再次告警
+func (s *Service) checkErrorRate(cfg types.NotificationConfig, now time.Time) []Event {
+	if cfg.ErrorRateThreshold <= 0 {
+		return nil
+	}
+
+	window := defaultErrorRateWindow
+	if cfg.ErrorRateWindowMinutes > 0 {
+		window = time.Duration(cfg.ErrorRateWindowMinutes) * time.Minute
+	}
+	minRequests := defaultErrorRateMinRequests
+	if cfg.ErrorRateMinRequests > 0 {
+		minRequests = cfg.ErrorRateMinRequests
+	}
+
+	records := s.recorder.Query(stats.Filter{Since: now.Add(-window)})
+	errors := 0
+	for _, record := range records {
+		if !record.Success {
+			errors++
+		}
+	}
+	if len(records) < minRequests {
+		s.errorAlerting = false
+		return nil
+	}
+
+	rate := float64(errors) / float64(len(records))
+	if rate < cfg.ErrorRateThreshold {
+		if s.errorAlerting {
+			logger.Info("错误率已恢复: %.1f%%", rate*100)
+		}
+		s.errorAlerting = false
+		return nil
+	}
+	if s.errorAlerting {
+		return nil
+	}
+
+	s.errorAlerting = true
+	return []Event{newEvent(EventErrorRate,
+		fmt.Sprintf("Error rate %.1f%% over the last %s (%d of %d requests) exceeded %.1f%%", rate*100, window, errors, len(records), cfg.ErrorRateThreshold*100),
+		map[string]interface{}{"error_rate": rate, "errors": errors, "requests": len(records), "window_minutes": int(window.Minutes()), "threshold": cfg.ErrorRateThreshold}, now)}
+}
+
+// checkErrorClasses 按规则检查每个提供商窗口内某一类上游错误占请求数的比例，超过阈值时告警一次，恢复后才会再次告警
+func (s *Service) checkErrorClasses(cfg types.NotificationConfig, now time.Time) []Event {
+	if len(cfg.ErrorClassRules) == 0 {
+		s.classAlerting = make(map[string]bool)
+		return nil
+	}
+
+	defaultWindow := defaultErrorRateWindow
+	if cfg.ErrorRateWindowMinutes > 0 {
+		defaultWindow = time.Duration(cfg.ErrorRateWindowMinutes) * time.Minute
+	}
+	defaultMinRequests := defaultErrorRateMinRequests
+	if cfg.ErrorRateMinRequests > 0 {
+		defaultMinRequests = cfg.ErrorRateMinRequests
+	}
+
+	// 一次查询覆盖所有规则中最长的窗口，每条规则再按自己的窗口过滤
+	windows := make([]time.Duration, len(cfg.ErrorClassRules))
+	longest := time.Duration(0)
+	for i, rule := range cfg.ErrorClassRules {
+		windows[i] = defaultWindow
+		if rule.WindowMinutes > 0 {
+			windows[i] = time.Duration(rule.WindowMinutes) * time.Minute
+		}
+		if windows[i] > longest {
+			longest = windows[i]
+		}
+	}
+	records := s.recorder.Query(stats.Filter{Since: now.Add(-longest)})
+
+	var events []Event
+	alerting := make(map[string]bool)
+	for i, rule := range cfg.ErrorClassRules {
+		minRequests := defaultMinRequests
+		if rule.MinRequests > 0 {
+			minRequests = rule.MinRequests
+		}
+		since := now.Add(-windows[i])
+		requests := make(map[types.Provider]int)
+		errors := make(map[types.Provider]int)
+		for _, record := range records {
+			if record.Provider == "" || record.Timestamp.Before(since) || (rule.Provider != "" && record.Provider != rule.Provider) {
+				continue
+			}
+			requests[record.Provider]++
+			if !record.Success && record.ErrorClass == rule.Class {
+				errors[record.Provider]++
+			}
+		}
+
+		for provider, total := range requests {
+			rate := float64(errors[provider]) / float64(total)
+			if total < minRequests || rate < rule.Threshold {
+				continue
+			}
+			key := fmt.Sprintf("%d:%s", i, provider)
+			alerting[key] = true
+			if s.classAlerting[key] {
+				continue
+			}
+			events = append(events, newEvent(EventErrorClassRate,
+				fmt.Sprintf("%s errors on provider %s were %.1f%% of requests over the last %s (%d of %d), exceeding %.1f%%", rule.Class, provider, rate*100, windows[i], errors[provider], total, rule.Threshold*100),
+				map[string]interface{}{"provider": provider, "class": rule.Class, "error_rate": rate, "errors": errors[provider], "requests": total, "window_minutes": int(windows[i].Minutes()), "threshold": rule.Threshold}, now))
+		}
+	}
+	for key := range s.classAlerting {
+		if !alerting[key] {
+			logger.Info("上游错误分类告警已恢复: %s", key)
+		}
+	}
+	s.classAlerting = alerting
+	return events
+}
+
+// checkHealth 比较账号健康状态与上次检查时的状态，首次看到的账号和未探测过的状态不告警
+func (s *Service) checkHealth(cfg types.NotificationConfig, now time.Time) []Event {
+	if !cfg.HealthChanges || s.accounts == nil {
+		return nil
+	}
+
+	var events []Event
+	for _, account := range s.accounts.ListAccounts() {
+		status := account.HealthStatus
+		previous, seen := s.healthStatuses[account.ID]
+		s.healthStatuses[account.ID] = status
+		if !seen || !knownHealth(status) || !knownHealth(previous) || status == previous {
+			continue
+		}
+
+		details := map[string]interface{}{"upstream_id": account.ID, "name": account.Name, "provider": account.Provider, "from": previous, "to": status}
+		if account.HealthError != "" {
+			details["error"] = account.HealthError
+		}
+		events = append(events, newEvent(EventHealthChange,
+			fmt.Sprintf("Upstream account %s (%s) changed from %s to %s", account.Name, account.ID, previous, status),
+			details, now))
+	}
+	return events
+}
+
+// checkRateLimits 检查上游报告的账号限流额度用量，每个阈值在额度恢复前只告警一次
+func (s *Service) checkRateLimits(cfg types.NotificationConfig, now time.Time) []Event {
+	if s.rateLimits == nil || s.accounts == nil {
+		return nil
+	}
+
+	var events []Event
+	for _, account := range s.accounts.ListAccounts() {
+		// 没有记录或记录已过时说明额度已经恢复，用量按0计算以便下一个窗口重新告警
+		state := s.rateLimits.Get(account.ID, now)
+		if state == nil {
+			state = &upstream.RateLimitState{RequestsLimit: -1, RequestsRemaining: -1, TokensLimit: -1, TokensRemaining: -1}
+		}
+		for _, item := range []struct {
+			name             string
+			limit, remaining int64
+		}{
+			{"requests", state.RequestsLimit, state.RequestsRemaining},
+			{"tokens", state.TokensLimit, state.TokensRemaining},
+		} {
+			fraction := 0.0
+			if item.limit > 0 && item.remaining >= 0 {
+				fraction = 1 - float64(item.remaining)/float64(item.limit)
+			}
+			threshold, fired := s.quotaLevels.Observe("upstream:"+account.ID+"/"+item.name, fraction, cfg.QuotaWarningThresholds)
+			if !fired {
+				continue
+			}
+			details := map[string]interface{}{"scope": "upstream", "upstream_id": account.ID, "name": account.Name, "limit": item.name, "threshold": threshold, "used": item.limit - item.remaining, "max": item.limit}
+			if !state.ResetAt.IsZero() {
+				details["reset"] = state.ResetAt
+			}
+			events = append(events, newEvent(EventQuotaWarning,
+				fmt.Sprintf("Upstream account %s (%s) used %.0f%% of its %s rate limit (%d of %d)", account.Name, account.ID, fraction*100, item.name, item.limit-item.remaining, item.limit),
+				details, now))
+		}
+	}
+	return events
+}
+
+// knownHealth 是否为探测得出的健康状态（新账号为 unknown）
+func knownHealth(status string) bool {
+	return status == "healthy" || status == "unhealthy"
+}
+
+// enqueue 为订阅该事件的每个Webhook创建投递记录
+func (s *Service) enqueue(cfg types.NotificationConfig, event Event) []*Delivery {
+	var deliveries []*Delivery
+	for _, webhook := range cfg.Webhooks {
+		if !subscribed(webhook, event.Type) {
+			continue
+		}
+		deliveries = append(deliveries, &Delivery{
+			ID:            newID(),
+			Event:         event,
+			Webhook:       webhook.Name,
+			Status:        StatusPending,
+			NextAttemptAt: event.Timestamp,
+			CreatedAt:     event.Timestamp,
+			UpdatedAt:     event.Timestamp,
+		})
+	}
+	if len(deliveries) > 0 {
+		if err := s.store.add(deliveries); err != nil {
+			logger.Warn("保存通知投递记录失败: %v", err)
+		}
+	}
+	return deliveries
+}
+
+// subscribed 检查Webhook是否订阅了事件类型（测试通知发送给所有Webhook）
+func subscribed(webhook types.WebhookConfig, eventType string) bool {
+	if len(webhook.Events) == 0 || eventType == EventTest {
+		return true
+	}
+	for _, subscribedType := range webhook.Events {
+		if subscribedType == eventType {
+			return true
+		}
+	}
+	return false
+}
+
+// newEvent 创建事件
+func newEvent(eventType, message string, details map[string]interface{}, now time.Time) Event {
+	return Event{
+		ID:        newID(),
+		Type:      eventType,
+		Message:   message,
+		Details:   details,
+		Timestamp: now,
+	}
+}
+
+// newID 生成随机ID
+func newID() string {
+	bytes := make([]byte, 8)
+	_, _ = rand.Read(bytes)
+	return hex.EncodeToString(bytes)
+}
@@ -0,0 +1,414 @@
+package notify
+
+import (
+	"encoding/json"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+type stubAccounts struct {
+	accounts []*types.UpstreamAccount
+}
+
+func (s *stubAccounts) ListAccounts() []*types.UpstreamAccount {
+	return s.accounts
+}
+
+func TestService_Evaluate(t *testing.T) {
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	recorder := stats.NewRecorder(0)
+	for i := 0; i < 20; i++ {
+		recorder.Record(stats.UsageRecord{
+			Timestamp:    now.Add(-time.Minute),
+			GatewayKeyID: "key-a",
+			Success:      i >= 10,
+			CostUSD:      0.5,
+		})
+	}
+	accounts := &stubAccounts{accounts: []*types.UpstreamAccount{{ID: "up-1", Name: "primary", HealthStatus: "healthy"}}}
+
+	config := &types.NotificationConfig{
+		Enabled:            true,
+		Webhooks:           []types.WebhookConfig{{Name: "ops", URL: "http://127.0.0.1:1/hook"}},
+		DailyCostUSD:       5,
+		KeyDailyCostUSD:    20,
+		ErrorRateThreshold: 0.3,
+		HealthChanges:      true,
+		Dir:                t.TempDir(),
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, recorder, accounts, time.Minute)
+
+	countByType := func(events []Event) map[string]int {
+		counts := make(map[string]int)
+		for _, event := range events {
+			counts[event.Type]++
+		}
+		return counts
+	}
+
+	// 全局费用 $10 超过 $5，单Key费用未超过 $20，错误率 50% 超过 30%；首次看到的账号不告警
+	got := countByType(service.Evaluate(now))
+	if got[EventCostThreshold] != 1 || got[EventErrorRate] != 1 || got[EventHealthChange] != 0 {
+		t.Fatalf("first evaluation = %v", got)
+	}
+
+	// 同一天、未恢复时不重复告警，健康状态变化时告警
+	accounts.accounts[0].HealthStatus = "unhealthy"
+	got = countByType(service.Evaluate(now.Add(time.Minute)))
+	if got[EventCostThreshold] != 0 || got[EventErrorRate] != 0 || got[EventHealthChange] != 1 {
+		t.Fatalf("second evaluation = %v", got)
+	}
+
+	// 第二天重新计算费用
+	if got := countByType(service.Evaluate(now.Add(24 * time.Hour))); got[EventCostThreshold] != 0 {
+		t.Errorf("next day without spend should not alert, got %v", got)
+	}
+
+	if deliveries := service.Deliveries(0); len(deliveries) != 3 {
+		t.Errorf("Deliveries() = %d, want 3", len(deliveries))
+	}
+
+	// 未启用时不检查
+	config.Enabled = false
+	if events := service.Evaluate(now.Add(48 * time.Hour)); len(events) != 0 {
+		t.Errorf("disabled service returned %d events", len(events))
+	}
+}
+
+func TestService_DeliverPending(t *testing.T) {
+	var mutex sync.Mutex
+	var bodies []string
+	fail := true
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		body, _ := io.ReadAll(r.Body)
+		mutex.Lock()
+		defer mutex.Unlock()
+		bodies = append(bodies, string(body))
+		if fail {
+			w.WriteHeader(http.StatusInternalServerError)
+		}
+	}))
+	defer server.Close()
+
+	dir := t.TempDir()
+	config := &types.NotificationConfig{
+		Enabled:     true,
+		Webhooks:    []types.WebhookConfig{{Name: "slack", URL: server.URL, Format: "slack"}},
+		MaxAttempts: 2,
+		Dir:         dir,
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, stats.NewRecorder(0), nil, time.Minute)
+
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	deliveries := service.SendTest(now)
+	if len(deliveries) != 1 || deliveries[0].Status != StatusPending || deliveries[0].Attempts != 1 || deliveries[0].LastStatus != http.StatusInternalServerError {
+		t.Fatalf("SendTest() = %+v", deliveries)
+	}
+
+	var payload map[string]string
+	if err := json.Unmarshal([]byte(bodies[0]), &payload); err != nil || payload["text"] == "" {
+		t.Errorf("slack payload = %s", bodies[0])
+	}
+
+	// 未到重试时间不投递
+	service.DeliverPending(now.Add(30 * time.Second))
+	if len(bodies) != 1 {
+		t.Fatalf("retry before backoff: %d requests", len(bodies))
+	}
+
+	// 重启后从文件恢复待投递记录并继续重试
+	mutex.Lock()
+	fail = false
+	mutex.Unlock()
+	restarted := NewService(func() *types.NotificationConfig { return config }, stats.NewRecorder(0), nil, time.Minute)
+	restarted.DeliverPending(now.Add(time.Minute))
+	if got := restarted.Deliveries(1); len(got) != 1 || got[0].Status != StatusDelivered || got[0].Attempts != 2 {
+		t.Fatalf("after retry = %+v", got)
+	}
+}
+
+type stubRateLimits map[string]*upstream.RateLimitState
+
+func (s stubRateLimits) Get(upstreamID string, now time.Time) *upstream.RateLimitState {
+	return s[upstreamID]
+}
+
+func TestService_RateLimitWarnings(t *testing.T) {
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	accounts := &stubAccounts{accounts: []*types.UpstreamAccount{{ID: "up-1", Name: "primary"}}}
+	config := &types.NotificationConfig{
+		Enabled:                true,
+		QuotaWarningThresholds: []float64{0.5, 0.9},
+		Dir:                    t.TempDir(),
+	}
+	limits := stubRateLimits{}
+	service := NewService(func() *types.NotificationConfig { return config }, stats.NewRecorder(0), accounts, time.Minute)
+	service.SetRateLimitSource(limits)
+
+	report := func(remaining int64) {
+		limits["up-1"] = &upstream.RateLimitState{RequestsLimit: 100, RequestsRemaining: remaining, TokensLimit: -1, TokensRemaining: -1}
+	}
+
+	report(60)
+	if events := service.Evaluate(now); len(events) != 0 {
+		t.Fatalf("40%% used should not warn, got %v", events)
+	}
+	report(45)
+	events := service.Evaluate(now)
+	if len(events) != 1 || events[0].Type != EventQuotaWarning || events[0].Details["threshold"] != 0.5 {
+		t.Fatalf("55%% used = %v, want one 50%% warning", events)
+	}
+	if events := service.Evaluate(now); len(events) != 0 {
+		t.Errorf("the same threshold should warn once, got %v", events)
+	}
+	report(5)
+	if events := service.Evaluate(now); len(events) != 1 || events[0].Details["threshold"] != 0.9 {
+		t.Errorf("95%% used = %v, want one 90%% warning", events)
+	}
+
+	// 额度恢复后的下一个窗口重新告警
+	delete(limits, "up-1")
+	service.Evaluate(now)
+	report(40)
+	if events := service.Evaluate(now); len(events) != 1 {
+		t.Errorf("a new window should warn again, got %v", events)
+	}
+}
+
+func TestService_ErrorClassRules(t *testing.T) {
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	recorder := stats.NewRecorder(0)
+	record := func(provider types.Provider, class types.ErrorClass, count int) {
+		for i := 0; i < count; i++ {
+			recorder.Record(stats.UsageRecord{Timestamp: now.Add(-time.Minute), Provider: provider, Success: class == "", ErrorClass: class})
+		}
+	}
+	// openai 10个请求中4个 quota 错误；anthropic 的错误是 overloaded，不匹配规则
+	record(types.ProviderOpenAI, "", 6)
+	record(types.ProviderOpenAI, types.ErrorClassQuota, 4)
+	record(types.ProviderAnthropic, "", 5)
+	record(types.ProviderAnthropic, types.ErrorClassOverloaded, 5)
+
+	config := &types.NotificationConfig{
+		Enabled:         true,
+		ErrorClassRules: []types.ErrorClassRule{{Class: types.ErrorClassQuota, Threshold: 0.3, MinRequests: 10}},
+		Dir:             t.TempDir(),
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, recorder, nil, time.Minute)
+
+	events := service.Evaluate(now)
+	if len(events) != 1 || events[0].Type != EventErrorClassRate || events[0].Details["provider"] != types.ProviderOpenAI {
+		t.Fatalf("first evaluation = %v, want one quota alert for openai", events)
+	}
+	if events := service.Evaluate(now); len(events) != 0 {
+		t.Errorf("an alerting rule should not fire again, got %v", events)
+	}
+
+	// 窗口过去后恢复，之后再次超过阈值时重新告警
+	if events := service.Evaluate(now.Add(time.Hour)); len(events) != 0 {
+		t.Errorf("evaluation after the window = %v, want none", events)
+	}
+	if events := service.Evaluate(now); len(events) != 1 {
+		t.Errorf("evaluation after recovery = %v, want the alert again", events)
+	}
+
+	// 规则只看指定的提供商
+	config.ErrorClassRules = []types.ErrorClassRule{{Class: types.ErrorClassQuota, Provider: types.ProviderAnthropic, Threshold: 0.1, MinRequests: 1}}
+	if events := service.Evaluate(now.Add(time.Second)); len(events) != 0 {
+		t.Errorf("anthropic quota rule = %v, want none", events)
+	}
+}
+
+func TestService_CostThresholdDedup(t *testing.T) {
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	recorder := stats.NewRecorder(0)
+	spend := func(at time.Time, keyID string, cost float64) {
+		recorder.Record(stats.UsageRecord{Timestamp: at, GatewayKeyID: keyID, Success: true, CostUSD: cost})
+	}
+	config := &types.NotificationConfig{
+		Enabled:         true,
+		Webhooks:        []types.WebhookConfig{{Name: "ops", URL: "http://127.0.0.1:1/hook"}},
+		KeyDailyCostUSD: 1,
+		Dir:             t.TempDir(),
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, recorder, nil, time.Minute)
+
+	keysAlerted := func(events []Event) []interface{} {
+		var keys []interface{}
+		for _, event := range events {
+			if event.Type == EventCostThreshold {
+				keys = append(keys, event.Details["key_id"])
+			}
+		}
+		return keys
+	}
+
+	// key-a 达到阈值 $1，key-b 还没有
+	spend(now.Add(-time.Hour), "key-a", 0.6)
+	spend(now.Add(-time.Hour), "key-a", 0.4)
+	spend(now.Add(-time.Hour), "key-b", 0.5)
+	if got := keysAlerted(service.Evaluate(now)); len(got) != 1 || got[0] != "key-a" {
+		t.Fatalf("first evaluation alerted keys %v, want key-a", got)
+	}
+
+	// 已告警的Key当天不再告警，之后达到阈值的Key单独告警
+	spend(now.Add(-time.Minute), "key-a", 2)
+	spend(now.Add(-time.Minute), "key-b", 0.6)
+	if got := keysAlerted(service.Evaluate(now)); len(got) != 1 || got[0] != "key-b" {
+		t.Fatalf("second evaluation alerted keys %v, want only key-b", got)
+	}
+	if got := keysAlerted(service.Evaluate(now.Add(time.Minute))); len(got) != 0 {
+		t.Errorf("third evaluation alerted keys %v, want none", got)
+	}
+
+	// 第二天重新计算，再次达到阈值时重新告警
+	nextDay := now.Add(24 * time.Hour)
+	spend(nextDay.Add(-time.Minute), "key-a", 1.5)
+	if got := keysAlerted(service.Evaluate(nextDay)); len(got) != 1 || got[0] != "key-a" {
+		t.Errorf("next day alerted keys %v, want key-a again", got)
+	}
+}
+
+func TestService_ErrorRateThreshold(t *testing.T) {
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	recorder := stats.NewRecorder(0)
+	record := func(success bool, count int) {
+		for i := 0; i < count; i++ {
+			recorder.Record(stats.UsageRecord{Timestamp: now.Add(-time.Minute), Success: success})
+		}
+	}
+	config := &types.NotificationConfig{
+		Enabled:              true,
+		ErrorRateThreshold:   0.5,
+		ErrorRateMinRequests: 10,
+		Dir:                  t.TempDir(),
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, recorder, nil, time.Minute)
+
+	// 请求数不足 min_requests 时不告警
+	record(false, 5)
+	if events := service.Evaluate(now); len(events) != 0 {
+		t.Fatalf("5 requests = %v, want no alert below the minimum", events)
+	}
+
+	// 错误率达到阈值告警一次，恢复前不重复告警
+	record(false, 5)
+	events := service.Evaluate(now)
+	if len(events) != 1 || events[0].Type != EventErrorRate || events[0].Details["requests"] != 10 {
+		t.Fatalf("10 errors = %v, want one error_rate alert", events)
+	}
+	if events := service.Evaluate(now); len(events) != 0 {
+		t.Errorf("still above the threshold = %v, want no repeat", events)
+	}
+
+	// 10/30 低于阈值后恢复，再次超过阈值时重新告警
+	record(true, 20)
+	if events := service.Evaluate(now); len(events) != 0 {
+		t.Errorf("33%% errors = %v, want none", events)
+	}
+	record(false, 30)
+	if events := service.Evaluate(now); len(events) != 1 || events[0].Type != EventErrorRate {
+		t.Errorf("67%% errors after recovery = %v, want the alert again", events)
+	}
+}
+
+func TestService_WebhookPayload(t *testing.T) {
+	type received struct {
+		contentType string
+		body        []byte
+	}
+	var mutex sync.Mutex
+	requests := make(map[string][]received)
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		body, _ := io.ReadAll(r.Body)
+		mutex.Lock()
+		defer mutex.Unlock()
+		requests[r.URL.Path] = append(requests[r.URL.Path], received{contentType: r.Header.Get("Content-Type"), body: body})
+	}))
+	defer server.Close()
+
+	config := &types.NotificationConfig{
+		Enabled: true,
+		Webhooks: []types.WebhookConfig{
+			{Name: "billing", URL: server.URL + "/billing", Events: []string{EventCostThreshold}},
+			{Name: "health", URL: server.URL + "/health", Events: []string{EventHealthChange}},
+			{Name: "all", URL: server.URL + "/all"},
+		},
+		Dir: t.TempDir(),
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, stats.NewRecorder(0), nil, time.Minute)
+
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	service.Alert(EventCostThreshold, "Daily spend reached the threshold", map[string]interface{}{"cost_usd": 12.5}, now)
+	service.DeliverPending(now)
+
+	mutex.Lock()
+	defer mutex.Unlock()
+	// 只投递给订阅了该事件的Webhook，以及没有限制事件类型的Webhook
+	if len(requests["/billing"]) != 1 || len(requests["/all"]) != 1 || len(requests["/health"]) != 0 {
+		t.Fatalf("requests per webhook = billing %d, all %d, health %d, want 1, 1, 0",
+			len(requests["/billing"]), len(requests["/all"]), len(requests["/health"]))
+	}
+
+	got := requests["/billing"][0]
+	if got.contentType != "application/json" {
+		t.Errorf("Content-Type = %q, want application/json", got.contentType)
+	}
+	var event Event
+	if err := json.Unmarshal(got.body, &event); err != nil {
+		t.Fatalf("payload %s is not an event: %v", got.body, err)
+	}
+	if event.ID == "" || event.Type != EventCostThreshold || event.Message != "Daily spend reached the threshold" ||
+		event.Details["cost_usd"] != 12.5 || !event.Timestamp.Equal(now) {
+		t.Errorf("payload = %+v", event)
+	}
+
+	for _, delivery := range service.Deliveries(0) {
+		if delivery.Status != StatusDelivered || delivery.Attempts != 1 || delivery.LastStatus != http.StatusOK {
+			t.Errorf("delivery to %s = %+v, want delivered on the first attempt", delivery.Webhook, delivery)
+		}
+	}
+}
+
+func TestService_DeliveryGivesUp(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusBadGateway)
+	}))
+	defer server.Close()
+
+	config := &types.NotificationConfig{
+		Enabled: true,
+		Webhooks: []types.WebhookConfig{
+			{Name: "broken", URL: server.URL},
+			{Name: "removed", URL: server.URL},
+		},
+		MaxAttempts: 1,
+		Dir:         t.TempDir(),
+	}
+	service := NewService(func() *types.NotificationConfig { return config }, stats.NewRecorder(0), nil, time.Minute)
+
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	service.Alert(EventHealthChange, "Upstream account changed", nil, now)
+	// 投递前删除的Webhook不再投递
+	config.Webhooks = config.Webhooks[:1]
+	service.DeliverPending(now)
+
+	statuses := make(map[string]Delivery)
+	for _, delivery := range service.Deliveries(0) {
+		statuses[delivery.Webhook] = delivery
+	}
+	if got := statuses["broken"]; got.Status != StatusFailed || got.Attempts != 1 || got.LastStatus != http.StatusBadGateway {
+		t.Errorf("broken webhook delivery = %+v, want failed after one attempt", got)
+	}
+	if got := statuses["removed"]; got.Status != StatusFailed || got.Attempts != 0 || got.LastError != "webhook no longer configured" {
+		t.Errorf("removed webhook delivery = %+v, want failed without an attempt", got)
+	}
+}
@@ -0,0 +1,285 @@
+package objectstore
+
+import (
+	"bytes"
+	"crypto/md5"
+	"encoding/base64"
+	"encoding/xml"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/sigv4"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// maxErrorBodyBytes 错误信息中保留的响应体长度
+const maxErrorBodyBytes = 512
+
+// Client S3兼容对象存储客户端（AWS S3、MinIO、Cloudflare R2 等），只实现网关需要的操作
+type Client struct {
+	endpoint   *url.URL
+	bucket     string
+	pathStyle  bool
+	signer     *sigv4.Signer
+	httpClient *http.Client
+	now        func() time.Time
+}
+
+// NewClient 根据配置创建客户端
+func NewClient(config *types.ObjectStoreConfig) (*Client, error) {
+	if config.Endpoint == "" || config.Bucket == "" {
+		return nil, fmt.Errorf("对象存储缺少 endpoint 或 bucket")
+	}
+	if config.AccessKeyID == "" || config.SecretAccessKey == "" {
+		return nil, fmt.Errorf("对象存储缺少访问密钥")
+	}
+
+	endpoint, err := url.Parse(strings.TrimRight(config.Endpoint, "/"))
+	if err != nil || endpoint.Scheme == "" || endpoint.Host == "" {
+		return nil, fmt.Errorf("无效的对象存储 endpoint: %s", config.Endpoint)
+	}
+
+	region := config.Region
+	if region == "" {
+		region = "us-east-1"
+	}
+
+	return &Client{
+		endpoint:  endpoint,
+		bucket:    config.Bucket,
+		pathStyle: config.PathStyle,
+		signer: &sigv4.Signer{
+			AccessKeyID:     config.AccessKeyID,
+			SecretAccessKey: config.SecretAccessKey,
+			Region:          region,
+			Service:         "s3",
+		},
+		httpClient: &http.Client{
+			Timeout:   60 * time.Second,
+			Transport: &http.Transport{Proxy: http.ProxyFromEnvironment},
+		},
+		now: time.Now,
+	}, nil
+}
+
+// objectURL 生成对象地址：path-style 为 endpoint/bucket/key，否则为 bucket.endpoint/key
+func (c *Client) objectURL(key string, query url.Values) *url.URL {
+	u := *c.endpoint
+	path := strings.TrimRight(u.Path, "/")
+	if c.pathStyle {
+		path += "/" + c.bucket
+	} else {
+		u.Host = c.bucket + "." + u.Host
+	}
+	path += "/" + strings.TrimLeft(key, "/")
+
+	u.Path = path
+	u.RawPath = sigv4.CanonicalURI(path)
+	u.RawQuery = query.Encode()
+	return &u
+}
+
+// PutObject 上传对象
+func (c *Client) PutObject(key string, data []byte, contentType string) error {
+	headers := http.Header{}
+	if contentType != "" {
+		headers.Set("Content-Type", contentType)
+	}
+	_, err := c.do(http.MethodPut, c.objectURL(key, nil), data, headers)
+	return err
+}
+
+// GetObject 下载对象
+func (c *Client) GetObject(key string) ([]byte, error) {
+	return c.do(http.MethodGet, c.objectURL(key, nil), nil, nil)
+}
+
+// DeleteObject 删除对象
+func (c *Client) DeleteObject(key string) error {
+	_, err := c.do(http.MethodDelete, c.objectURL(key, nil), nil, nil)
+	return err
+}
+
+// Object 对象列表中的一项
+type Object struct {
+	Key          string    `xml:"Key"`
+	Size         int64     `xml:"Size"`
+	LastModified time.Time `xml:"LastModified"`
+}
+
+// listBucketResult ListObjectsV2 的响应（只解析需要的部分）
+type listBucketResult struct {
+	Contents              []Object `xml:"Contents"`
+	IsTruncated           bool     `xml:"IsTruncated"`
+	NextContinuationToken string   `xml:"NextContinuationToken"`
+}
+
+// ListObjects 列出 prefix 下的全部对象（自动翻页）
+func (c *Client) ListObjects(prefix string) ([]Object, error) {
+	var objects []Object
+	token := ""
+	for {
+		query := url.Values{"list-type": []string{"2"}, "prefix": []string{prefix}}
+		if token != "" {
+			query.Set("continuation-token", token)
+		}
+
+		data, err := c.do(http.MethodGet, c.bucketURL(query), nil, nil)
+		if err != nil {
+			return nil, err
+		}
+		var result listBucketResult
+		if err := xml.Unmarshal(data, &result); err != nil {
+			return nil, fmt.Errorf("解析对象列表失败: %w", err)
+		}
+		objects = append(objects, result.Contents...)
+
+		if !result.IsTruncated || result.NextContinuationToken == "" {
+			return objects, nil
+		}
+		token = result.NextContinuationToken
+	}
+}
+
+// PresignGet 生成有效期为 expires 的对象下载链接
+func (c *Client) PresignGet(key string, expires time.Duration) string {
+	return c.signer.Presign(http.MethodGet, c.objectURL(key, nil), expires, c.now())
+}
+
+// lifecycleConfiguration S3 生命周期配置。规则保留原始XML，写回时不会丢失网关不解析的字段（如 Transition、Tag 过滤）
+type lifecycleConfiguration struct {
+	XMLName xml.Name          `xml:"LifecycleConfiguration"`
+	Rules   []storedLifecycle `xml:"Rule"`
+}
+
+// storedLifecycle 存储桶中已有的一条生命周期规则
+type storedLifecycle struct {
+	ID    string `xml:"ID,omitempty"` // 只用于读取，写回时规则内容全部来自 Inner
+	Inner string `xml:",innerxml"`
+}
+
+// lifecycleRule 生命周期规则
+type lifecycleRule struct {
+	XMLName    xml.Name            `xml:"Rule"`
+	ID         string              `xml:"ID"`
+	Filter     lifecycleFilter     `xml:"Filter"`
+	Status     string              `xml:"Status"`
+	Expiration lifecycleExpiration `xml:"Expiration"`
+}
+
+// lifecycleFilter 规则作用的对象前缀
+type lifecycleFilter struct {
+	Prefix string `xml:"Prefix"`
+}
+
+// lifecycleExpiration 对象过期时间
+type lifecycleExpiration struct {
+	Days int `xml:"Days"`
+}
+
+// PutExpirationRule 设置存储桶生命周期规则：prefix 下的对象在 days 天后过期删除。
+// S3会用提交的配置替换存储桶的全部生命周期规则，因此先读取已有规则，替换或追加 ruleID 对应的规则后整体写回；
+// 读取和写回之间其他客户端对生命周期配置的修改会被覆盖
+func (c *Client) PutExpirationRule(ruleID, prefix string, days int) error {
+	lifecycleURL := c.bucketURL(url.Values{"lifecycle": []string{""}})
+
+	var config lifecycleConfiguration
+	data, err := c.do(http.MethodGet, lifecycleURL, nil, nil)
+	var statusErr *statusError
+	switch {
+	case errors.As(err, &statusErr) && statusErr.StatusCode == http.StatusNotFound:
+		// 存储桶还没有生命周期配置（NoSuchLifecycleConfiguration）
+	case err != nil:
+		return fmt.Errorf("读取生命周期配置失败: %w", err)
+	default:
+		if err := xml.Unmarshal(data, &config); err != nil {
+			return fmt.Errorf("解析生命周期配置失败: %w", err)
+		}
+	}
+
+	rule, err := xml.Marshal(lifecycleRule{
+		ID:         ruleID,
+		Filter:     lifecycleFilter{Prefix: prefix},
+		Status:     "Enabled",
+		Expiration: lifecycleExpiration{Days: days},
+	})
+	if err != nil {
+		return fmt.Errorf("序列化生命周期配置失败: %w", err)
+	}
+	merged := lifecycleConfiguration{Rules: make([]storedLifecycle, 0, len(config.Rules)+1)}
+	for _, existing := range config.Rules {
+		if strings.TrimSpace(existing.ID) != ruleID {
+			merged.Rules = append(merged.Rules, storedLifecycle{Inner: existing.Inner})
+		}
+	}
+	inner := strings.TrimSuffix(strings.TrimPrefix(string(rule), "<Rule>"), "</Rule>")
+	merged.Rules = append(merged.Rules, storedLifecycle{Inner: inner})
+
+	body, err := xml.Marshal(merged)
+	if err != nil {
+		return fmt.Errorf("序列化生命周期配置失败: %w", err)
+	}
+
+	// 生命周期接口要求 Content-MD5
+	sum := md5.Sum(body)
+	headers := http.Header{}
+	headers.Set("Content-MD5", base64.StdEncoding.EncodeToString(sum[:]))
+	headers.Set("Content-Type", "application/xml")
+
+	_, err = c.do(http.MethodPut, lifecycleURL, body, headers)
+	return err
+}
+
+// bucketURL 生成存储桶级别操作（列表、生命周期）的地址
+func (c *Client) bucketURL(query url.Values) *url.URL {
+	u := c.objectURL("", query)
+	u.Path = strings.TrimSuffix(u.Path, "/")
+	if u.Path == "" {
+		u.Path = "/"
+	}
+	u.RawPath = sigv4.CanonicalURI(u.Path)
+	return u
+}
+
+// do 发送签名请求，非2xx响应返回错误
+func (c *Client) do(method string, u *url.URL, body []byte, headers http.Header) ([]byte, error) {
+	req, err := http.NewRequest(method, u.String(), bytes.NewReader(body))
+	if err != nil {
+		return nil, fmt.Errorf("创建对象存储请求失败: %w", err)
+	}
+	for name, values := range headers {
+		req.Header[name] = values
+	}
+	c.signer.SignRequest(req, utils.SHA256HexBytes(body), c.now())
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		return nil, fmt.Errorf("对象存储请求失败: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	data, err := io.ReadAll(resp.Body)
+	if err != nil {
+		return nil, fmt.Errorf("读取对象存储响应失败: %w", err)
+	}
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return nil, &statusError{StatusCode: resp.StatusCode, Body: utils.TruncateUTF8(string(data), maxErrorBodyBytes)}
+	}
+	return data, nil
+}
+
+// statusError 对象存储返回的非2xx响应
+type statusError struct {
+	StatusCode int
+	Body       string
+}
+
+func (e *statusError) Error() string {
+	return fmt.Sprintf("对象存储返回 %d: %s", e.StatusCode, e.Body)
+}
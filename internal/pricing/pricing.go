@@ -0,0 +1,199 @@
+package pricing
+
+import (
+	"sort"
+	"strings"
+	"sync"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// ModelPrice 模型单价（美元/百万token），缓存价格为0时按输入价格计算
+type ModelPrice struct {
+	Input      float64 `json:"input"`
+	Output     float64 `json:"output"`
+	CacheRead  float64 `json:"cache_read,omitempty"`
+	CacheWrite float64 `json:"cache_write,omitempty"`
+}
+
+// Tokens 一次请求的token用量，Input 不包含缓存读取和写入的token
+type Tokens struct {
+	Input      int
+	Output     int
+	CacheRead  int
+	CacheWrite int
+}
+
+// 价格来源
+const (
+	SourceDefault = "default" // 内置价格表
+	SourceCustom  = "custom"  // pricing.models 配置
+)
+
+// Entry 价格表中的一项
+type Entry struct {
+	Model    string         `json:"model"` // 模型名前缀
+	Provider types.Provider `json:"provider,omitempty"`
+	Source   string         `json:"source"`
+	ModelPrice
+}
+
+// priceEntry 按模型名前缀匹配的价格项
+type priceEntry struct {
+	prefix   string
+	provider types.Provider // 为空时匹配所有提供商
+	price    ModelPrice
+}
+
+// defaultPrices 内置价格表（公开定价，按前缀匹配，最长前缀优先）
+var defaultPrices = []priceEntry{
+	// Anthropic（缓存读取为输入价格的0.1倍，写入为1.25倍）
+	{"claude-opus-4", "", ModelPrice{Input: 15, Output: 75, CacheRead: 1.5, CacheWrite: 18.75}},
+	{"claude-3-opus", "", ModelPrice{Input: 15, Output: 75, CacheRead: 1.5, CacheWrite: 18.75}},
+	{"claude-sonnet-4", "", ModelPrice{Input: 3, Output: 15, CacheRead: 0.3, CacheWrite: 3.75}},
+	{"claude-3-7-sonnet", "", ModelPrice{Input: 3, Output: 15, CacheRead: 0.3, CacheWrite: 3.75}},
+	{"claude-3-5-sonnet", "", ModelPrice{Input: 3, Output: 15, CacheRead: 0.3, CacheWrite: 3.75}},
+	{"claude-3-5-haiku", "", ModelPrice{Input: 0.8, Output: 4, CacheRead: 0.08, CacheWrite: 1}},
+	{"claude-3-haiku", "", ModelPrice{Input: 0.25, Output: 1.25, CacheRead: 0.03, CacheWrite: 0.3}},
+
+	// OpenAI（缓存命中的输入token，无写入费用）
+	{"gpt-4o-mini", "", ModelPrice{Input: 0.15, Output: 0.6, CacheRead: 0.075}},
+	{"gpt-4o", "", ModelPrice{Input: 2.5, Output: 10, CacheRead: 1.25}},
+	{"gpt-4.1-nano", "", ModelPrice{Input: 0.1, Output: 0.4, CacheRead: 0.025}},
+	{"gpt-4.1-mini", "", ModelPrice{Input: 0.4, Output: 1.6, CacheRead: 0.1}},
+	{"gpt-4.1", "", ModelPrice{Input: 2, Output: 8, CacheRead: 0.5}},
+	{"gpt-4-turbo", "", ModelPrice{Input: 10, Output: 30}},
+	{"gpt-4", "", ModelPrice{Input: 30, Output: 60}},
+	{"gpt-3.5-turbo", "", ModelPrice{Input: 0.5, Output: 1.5}},
+	{"o1-mini", "", ModelPrice{Input: 1.1, Output: 4.4, CacheRead: 0.55}},
+	{"o1", "", ModelPrice{Input: 15, Output: 60, CacheRead: 7.5}},
+	{"o3-mini", "", ModelPrice{Input: 1.1, Output: 4.4, CacheRead: 0.55}},
+
+	// 嵌入模型（只有输入价格）
+	{"text-embedding-3-small", "", ModelPrice{Input: 0.02}},
+	{"text-embedding-3-large", "", ModelPrice{Input: 0.13}},
+	{"text-embedding-ada-002", "", ModelPrice{Input: 0.1}},
+	{"gemini-embedding-001", "", ModelPrice{Input: 0.15}},
+
+	// Qwen
+	{"qwen3-coder-plus", "", ModelPrice{Input: 1, Output: 5}},
+	{"qwen-max", "", ModelPrice{Input: 1.6, Output: 6.4}},
+	{"qwen-plus", "", ModelPrice{Input: 0.4, Output: 1.2}},
+	{"qwen-turbo", "", ModelPrice{Input: 0.05, Output: 0.2}},
+}
+
+var (
+	customPrices []priceEntry // pricing.models 配置，优先于内置价格表
+	customMutex  sync.RWMutex
+)
+
+func init() {
+	sortEntries(defaultPrices)
+}
+
+// sortEntries 最长前缀优先，避免 gpt-4 抢先匹配 gpt-4o；同一前缀指定了提供商的优先
+func sortEntries(entries []priceEntry) {
+	sort.SliceStable(entries, func(i, j int) bool {
+		if len(entries[i].prefix) != len(entries[j].prefix) {
+			return len(entries[i].prefix) > len(entries[j].prefix)
+		}
+		return entries[i].provider != "" && entries[j].provider == ""
+	})
+}
+
+// SetCustomPrices 设置自定义价格（替换之前的设置），匹配时优先于内置价格表
+func SetCustomPrices(models []types.ModelPricing) {
+	entries := make([]priceEntry, 0, len(models))
+	for _, model := range models {
+		entries = append(entries, priceEntry{
+			prefix:   strings.ToLower(model.Model),
+			provider: model.Provider,
+			price: ModelPrice{
+				Input:      model.Input,
+				Output:     model.Output,
+				CacheRead:  model.CacheRead,
+				CacheWrite: model.CacheWrite,
+			},
+		})
+	}
+	sortEntries(entries)
+
+	customMutex.Lock()
+	defer customMutex.Unlock()
+	customPrices = entries
+}
+
+// Table 返回当前价格表：自定义价格在前，然后是内置价格
+func Table() []Entry {
+	customMutex.RLock()
+	defer customMutex.RUnlock()
+
+	result := make([]Entry, 0, len(customPrices)+len(defaultPrices))
+	for _, entry := range customPrices {
+		result = append(result, Entry{Model: entry.prefix, Provider: entry.provider, Source: SourceCustom, ModelPrice: entry.price})
+	}
+	for _, entry := range defaultPrices {
+		result = append(result, Entry{Model: entry.prefix, Provider: entry.provider, Source: SourceDefault, ModelPrice: entry.price})
+	}
+	return result
+}
+
+// Lookup 查找模型单价（不区分提供商）
+func Lookup(model string) (ModelPrice, bool) {
+	return LookupFor("", model)
+}
+
+// LookupFor 查找提供商下模型的单价：自定义价格与内置价格表中最长的前缀优先，长度相同时自定义价格优先
+func LookupFor(provider types.Provider, model string) (ModelPrice, bool) {
+	model = strings.ToLower(model)
+
+	customMutex.RLock()
+	custom := customPrices
+	customMutex.RUnlock()
+
+	var best *priceEntry
+	for _, entries := range [][]priceEntry{custom, defaultPrices} {
+		for i := range entries {
+			entry := &entries[i]
+			if entry.provider != "" && entry.provider != provider {
+				continue
+			}
+			if strings.HasPrefix(model, entry.prefix) {
+				// 每个列表已按前缀长度降序排列，第一个匹配即为该列表中最长的前缀
+				if best == nil || len(entry.prefix) > len(best.prefix) {
+					best = entry
+				}
+				break
+			}
+		}
+	}
+	if best == nil {
+		return ModelPrice{}, false
+	}
+	return best.price, true
+}
+
+// Cost 计算请求费用（美元），未知模型返回0
+func Cost(model string, inputTokens, outputTokens int) float64 {
+	return CostFor("", model, Tokens{Input: inputTokens, Output: outputTokens})
+}
+
+// CostFor 按提供商、模型和各类token用量计算请求费用（美元），未知模型返回0
+func CostFor(provider types.Provider, model string, tokens Tokens) float64 {
+	price, ok := LookupFor(provider, model)
+	if !ok {
+		return 0
+	}
+
+	cacheRead, cacheWrite := price.CacheRead, price.CacheWrite
+	if cacheRead == 0 {
+		cacheRead = price.Input
+	}
+	if cacheWrite == 0 {
+		cacheWrite = price.Input
+	}
+	return (float64(tokens.Input)*price.Input +
+		float64(tokens.Output)*price.Output +
+		float64(tokens.CacheRead)*cacheRead +
+		float64(tokens.CacheWrite)*cacheWrite) / 1_000_000
+}
@@ -0,0 +1,83 @@
+package pricing
+
+import (
+	"math"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestLookupPrefersLongestPrefix(t *testing.T) {
+	price, ok := Lookup("gpt-4o-mini-2024-07-18")
+	if !ok {
+		t.Fatal("expected gpt-4o-mini to be priced")
+	}
+	if price.Input != 0.15 || price.Output != 0.6 {
+		t.Errorf("unexpected price for gpt-4o-mini: %+v", price)
+	}
+
+	price, ok = Lookup("gpt-4-0613")
+	if !ok || price.Input != 30 {
+		t.Errorf("expected gpt-4 pricing, got %+v (ok=%v)", price, ok)
+	}
+
+	if _, ok := Lookup("unknown-model"); ok {
+		t.Error("unknown model should not be priced")
+	}
+}
+
+func TestCost(t *testing.T) {
+	cost := Cost("claude-3-5-sonnet-20241022", 1000, 500)
+	expected := (1000*3.0 + 500*15.0) / 1_000_000
+	if math.Abs(cost-expected) > 1e-12 {
+		t.Errorf("expected cost %f, got %f", expected, cost)
+	}
+
+	if cost := Cost("unknown-model", 1000, 1000); cost != 0 {
+		t.Errorf("expected zero cost for unknown model, got %f", cost)
+	}
+}
+
+func TestCostForCacheTokens(t *testing.T) {
+	cost := CostFor(types.ProviderAnthropic, "claude-3-5-sonnet-20241022", Tokens{Input: 1000, Output: 500, CacheRead: 10000, CacheWrite: 2000})
+	expected := (1000*3.0 + 500*15.0 + 10000*0.3 + 2000*3.75) / 1_000_000
+	if math.Abs(cost-expected) > 1e-12 {
+		t.Errorf("expected cost %f, got %f", expected, cost)
+	}
+
+	// 未配置缓存价格时按输入价格计算
+	cost = CostFor(types.ProviderOpenAI, "gpt-4-0613", Tokens{CacheRead: 1000})
+	if math.Abs(cost-1000*30.0/1_000_000) > 1e-12 {
+		t.Errorf("cache read without price should use input price, got %f", cost)
+	}
+}
+
+func TestSetCustomPrices(t *testing.T) {
+	defer SetCustomPrices(nil)
+
+	SetCustomPrices([]types.ModelPricing{
+		{Model: "gpt-4o", Input: 1, Output: 2},
+		{Model: "gpt-4o", Provider: types.ProviderAzure, Input: 5, Output: 6},
+		{Model: "My-Model", Input: 7, Output: 8},
+	})
+
+	// 自定义价格优先于内置价格，指定提供商的价格只对该提供商生效
+	if price, _ := LookupFor(types.ProviderOpenAI, "gpt-4o-2024-08-06"); price.Input != 1 {
+		t.Errorf("custom price should override default, got %+v", price)
+	}
+	if price, _ := LookupFor(types.ProviderAzure, "gpt-4o"); price.Input != 5 {
+		t.Errorf("provider-specific price should win for azure, got %+v", price)
+	}
+	if price, ok := Lookup("my-model-v2"); !ok || price.Output != 8 {
+		t.Errorf("custom model should be priced case-insensitively, got %+v (ok=%v)", price, ok)
+	}
+	// 内置价格表中更长的前缀不被自定义的短前缀覆盖
+	if price, _ := Lookup("gpt-4o-mini"); price.Input != 0.15 {
+		t.Errorf("longer default prefix should win, got %+v", price)
+	}
+
+	table := Table()
+	if len(table) != 3+len(defaultPrices) || table[0].Source != SourceCustom {
+		t.Errorf("Table() = %d entries, first %+v", len(table), table[0])
+	}
+}
@@ -0,0 +1,236 @@
+package quota
+
+import (
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 超出的配额类型
+const (
+	LimitDailyTokens    = "daily_tokens"
+	LimitMonthlyTokens  = "monthly_tokens"
+	LimitDailyCostUSD   = "daily_cost_usd"
+	LimitMonthlyCostUSD = "monthly_cost_usd"
+)
+
+// DefaultWarningThresholds 未配置时使用的软配额告警阈值（占上限的比例）
+var DefaultWarningThresholds = []float64{0.5, 0.8, 0.95}
+
+// Usage 当前周期内的用量
+type Usage struct {
+	DailyTokens    int64   `json:"daily_tokens"`
+	MonthlyTokens  int64   `json:"monthly_tokens"`
+	DailyCostUSD   float64 `json:"daily_cost_usd"`
+	MonthlyCostUSD float64 `json:"monthly_cost_usd"`
+}
+
+// Result 配额检查结果
+type Result struct {
+	Exceeded  bool
+	Limit     string    // 超出的配额类型
+	Max       float64   // 配额上限
+	Used      float64   // 当前用量
+	Requested float64   // 本次请求估算的token数（只在预检查时非0）
+	Reset     time.Time // 超出的配额周期结束时间
+
+	// RemainingUSD 日/月费用预算中较小的剩余额度，HasCostLimit为false时无意义
+	RemainingUSD float64
+	HasCostLimit bool
+}
+
+// Warning 一项配额的用量达到的软配额告警阈值
+type Warning struct {
+	Limit     string    `json:"limit"`
+	Threshold float64   `json:"threshold"` // 达到的最高阈值
+	Used      float64   `json:"used"`
+	Max       float64   `json:"max"`
+	Reset     time.Time `json:"reset"`
+	Fired     bool      `json:"-"` // 本次检查首次达到该阈值，应发送告警
+}
+
+// keyUsage 单个Key的周期计数
+type keyUsage struct {
+	day   time.Time
+	month time.Time
+	usage Usage
+}
+
+// Service 按Gateway Key统计日/月token与费用，并在请求前检查配额
+type Service struct {
+	mutex  sync.Mutex
+	usage  map[string]*keyUsage
+	levels *WarningLevels // Key/配额类型 -> 已告警的阈值
+}
+
+// NewService 创建配额服务，recorder不为nil时从使用记录中累计用量
+func NewService(recorder *stats.Recorder) *Service {
+	s := &Service{
+		usage:  make(map[string]*keyUsage),
+		levels: NewWarningLevels(),
+	}
+	if recorder != nil {
+		now := time.Now()
+		for _, record := range recorder.Query(stats.Filter{Since: monthStart(now)}) {
+			s.Add(record)
+		}
+		recorder.Subscribe(s.Add)
+	}
+	return s
+}
+
+// Add 累计一条使用记录
+func (s *Service) Add(record stats.UsageRecord) {
+	if record.GatewayKeyID == "" {
+		return
+	}
+	tokens := int64(record.InputTokens + record.OutputTokens)
+	if tokens == 0 && record.CostUSD == 0 {
+		return
+	}
+
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	ku := s.keyUsageLocked(record.GatewayKeyID, record.Timestamp)
+	if !dayStart(record.Timestamp).Before(ku.day) {
+		ku.usage.DailyTokens += tokens
+		ku.usage.DailyCostUSD += record.CostUSD
+	}
+	if !monthStart(record.Timestamp).Before(ku.month) {
+		ku.usage.MonthlyTokens += tokens
+		ku.usage.MonthlyCostUSD += record.CostUSD
+	}
+}
+
+// Usage 返回Key在当前周期内的用量
+func (s *Service) Usage(keyID string, now time.Time) Usage {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	return s.keyUsageLocked(keyID, now).usage
+}
+
+// Check 检查Key是否已超出配额；cfg为nil时不限制
+func (s *Service) Check(keyID string, cfg *types.QuotaConfig, now time.Time) Result {
+	return s.CheckRequest(keyID, cfg, now, 0)
+}
+
+// CheckRequest 检查Key的剩余token配额是否足够本次请求：已用量加上估算的输入token超过上限时视为超出，
+// estimatedTokens 为0时与 Check 相同
+func (s *Service) CheckRequest(keyID string, cfg *types.QuotaConfig, now time.Time, estimatedTokens int64) Result {
+	var result Result
+	if cfg == nil {
+		return result
+	}
+
+	usage := s.Usage(keyID, now)
+	nextDay := dayStart(now).AddDate(0, 0, 1)
+	nextMonth := monthStart(now).AddDate(0, 1, 0)
+
+	pending := float64(estimatedTokens)
+	checks := []struct {
+		limit   string
+		max     float64
+		used    float64
+		pending float64 // 本次请求预计消耗的量
+		reset   time.Time
+	}{
+		{LimitDailyTokens, float64(cfg.DailyTokens), float64(usage.DailyTokens), pending, nextDay},
+		{LimitMonthlyTokens, float64(cfg.MonthlyTokens), float64(usage.MonthlyTokens), pending, nextMonth},
+		{LimitDailyCostUSD, cfg.DailyCostUSD, usage.DailyCostUSD, 0, nextDay},
+		{LimitMonthlyCostUSD, cfg.MonthlyCostUSD, usage.MonthlyCostUSD, 0, nextMonth},
+	}
+	for _, c := range checks {
+		exceeded := c.used >= c.max || (c.pending > 0 && c.used+c.pending > c.max)
+		if c.max > 0 && exceeded && !result.Exceeded {
+			result = Result{Exceeded: true, Limit: c.limit, Max: c.max, Used: c.used, Requested: c.pending, Reset: c.reset}
+		}
+	}
+
+	// 剩余费用额度取日/月预算中较小者
+	for _, c := range checks[2:] {
+		if c.max <= 0 {
+			continue
+		}
+		remaining := c.max - c.used
+		if remaining < 0 {
+			remaining = 0
+		}
+		if !result.HasCostLimit || remaining < result.RemainingUSD {
+			result.RemainingUSD = remaining
+		}
+		result.HasCostLimit = true
+	}
+
+	return result
+}
+
+// Warnings 返回Key每项配额的用量达到的最高告警阈值，没有配额或都未达到阈值时返回空。
+// 每个阈值在一个周期内只有首次达到时标记 Fired，周期结束用量归零后重新计算
+func (s *Service) Warnings(keyID string, cfg *types.QuotaConfig, thresholds []float64, now time.Time) []Warning {
+	if cfg == nil {
+		return nil
+	}
+
+	usage := s.Usage(keyID, now)
+	nextDay := dayStart(now).AddDate(0, 0, 1)
+	nextMonth := monthStart(now).AddDate(0, 1, 0)
+
+	var warnings []Warning
+	for _, c := range []struct {
+		limit string
+		max   float64
+		used  float64
+		reset time.Time
+	}{
+		{LimitDailyTokens, float64(cfg.DailyTokens), float64(usage.DailyTokens), nextDay},
+		{LimitMonthlyTokens, float64(cfg.MonthlyTokens), float64(usage.MonthlyTokens), nextMonth},
+		{LimitDailyCostUSD, cfg.DailyCostUSD, usage.DailyCostUSD, nextDay},
+		{LimitMonthlyCostUSD, cfg.MonthlyCostUSD, usage.MonthlyCostUSD, nextMonth},
+	} {
+		if c.max <= 0 {
+			continue
+		}
+		threshold, fired := s.levels.Observe(keyID+"/"+c.limit, c.used/c.max, thresholds)
+		if threshold == 0 {
+			continue
+		}
+		warnings = append(warnings, Warning{Limit: c.limit, Threshold: threshold, Used: c.used, Max: c.max, Reset: c.reset, Fired: fired})
+	}
+	return warnings
+}
+
+// keyUsageLocked 获取Key的计数并按当前时间滚动周期（调用方持有锁）
+func (s *Service) keyUsageLocked(keyID string, now time.Time) *keyUsage {
+	ku, ok := s.usage[keyID]
+	if !ok {
+		ku = &keyUsage{day: dayStart(now), month: monthStart(now)}
+		s.usage[keyID] = ku
+	}
+
+	if day := dayStart(now); day.After(ku.day) {
+		ku.day = day
+		ku.usage.DailyTokens = 0
+		ku.usage.DailyCostUSD = 0
+	}
+	if month := monthStart(now); month.After(ku.month) {
+		ku.month = month
+		ku.usage.MonthlyTokens = 0
+		ku.usage.MonthlyCostUSD = 0
+	}
+	return ku
+}
+
+// dayStart UTC自然日起点
+func dayStart(t time.Time) time.Time {
+	t = t.UTC()
+	return time.Date(t.Year(), t.Month(), t.Day(), 0, 0, 0, 0, time.UTC)
+}
+
+// monthStart UTC自然月起点
+func monthStart(t time.Time) time.Time {
+	t = t.UTC()
+	return time.Date(t.Year(), t.Month(), 1, 0, 0, 0, 0, time.UTC)
+}
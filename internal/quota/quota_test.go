@@ -0,0 +1,139 @@
+package quota
+
+import (
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestService_Check(t *testing.T) {
+	now := time.Date(2024, 5, 20, 12, 0, 0, 0, time.UTC)
+	s := NewService(nil)
+
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now.Add(-time.Hour), InputTokens: 600, OutputTokens: 400, CostUSD: 1.5})
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now.AddDate(0, 0, -3), InputTokens: 1000, CostUSD: 2})
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-b", Timestamp: now, InputTokens: 5000, CostUSD: 10})
+
+	usage := s.Usage("key-a", now)
+	if usage.DailyTokens != 1000 || usage.MonthlyTokens != 2000 {
+		t.Errorf("token用量 = %+v, 期望 daily=1000 monthly=2000", usage)
+	}
+	if usage.DailyCostUSD != 1.5 || usage.MonthlyCostUSD != 3.5 {
+		t.Errorf("费用 = %+v, 期望 daily=1.5 monthly=3.5", usage)
+	}
+
+	tests := []struct {
+		name      string
+		cfg       *types.QuotaConfig
+		exceeded  bool
+		limit     string
+		remaining float64
+		hasCost   bool
+	}{
+		{name: "未配置", cfg: nil},
+		{name: "日token未超", cfg: &types.QuotaConfig{DailyTokens: 1001}},
+		{name: "日token已满", cfg: &types.QuotaConfig{DailyTokens: 1000}, exceeded: true, limit: LimitDailyTokens},
+		{name: "月token已超", cfg: &types.QuotaConfig{MonthlyTokens: 1500}, exceeded: true, limit: LimitMonthlyTokens},
+		{name: "月费用已超", cfg: &types.QuotaConfig{MonthlyCostUSD: 3}, exceeded: true, limit: LimitMonthlyCostUSD, remaining: 0, hasCost: true},
+		{name: "剩余取较小预算", cfg: &types.QuotaConfig{DailyCostUSD: 2, MonthlyCostUSD: 10}, remaining: 0.5, hasCost: true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			result := s.Check("key-a", tt.cfg, now)
+			if result.Exceeded != tt.exceeded || result.Limit != tt.limit {
+				t.Errorf("Check() = %+v, 期望 exceeded=%v limit=%q", result, tt.exceeded, tt.limit)
+			}
+			if result.HasCostLimit != tt.hasCost || result.RemainingUSD != tt.remaining {
+				t.Errorf("剩余费用 = %v (has=%v), 期望 %v (has=%v)", result.RemainingUSD, result.HasCostLimit, tt.remaining, tt.hasCost)
+			}
+		})
+	}
+}
+
+func TestService_CheckRequest(t *testing.T) {
+	now := time.Date(2024, 5, 20, 12, 0, 0, 0, time.UTC)
+	s := NewService(nil)
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now, InputTokens: 800, CostUSD: 1})
+
+	cfg := &types.QuotaConfig{DailyTokens: 1000, DailyCostUSD: 5}
+	if result := s.CheckRequest("key-a", cfg, now, 200); result.Exceeded {
+		t.Errorf("估算用量恰好用完配额时应放行, got %+v", result)
+	}
+
+	result := s.CheckRequest("key-a", cfg, now, 201)
+	if !result.Exceeded || result.Limit != LimitDailyTokens || result.Requested != 201 || result.Used != 800 {
+		t.Errorf("估算用量超出剩余配额时应拒绝, got %+v", result)
+	}
+	if !result.HasCostLimit || result.RemainingUSD != 4 {
+		t.Errorf("剩余费用 = %v, 期望 4", result.RemainingUSD)
+	}
+}
+
+func TestService_PeriodRollover(t *testing.T) {
+	day := time.Date(2024, 1, 31, 23, 0, 0, 0, time.UTC)
+	s := NewService(nil)
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: day, InputTokens: 100})
+
+	result := s.Check("key-a", &types.QuotaConfig{DailyTokens: 100}, day)
+	if !result.Exceeded || !result.Reset.Equal(time.Date(2024, 2, 1, 0, 0, 0, 0, time.UTC)) {
+		t.Errorf("当日应超出配额并在次日重置, got %+v", result)
+	}
+
+	// 跨日跨月后计数清零
+	next := day.Add(2 * time.Hour)
+	usage := s.Usage("key-a", next)
+	if usage.DailyTokens != 0 || usage.MonthlyTokens != 0 {
+		t.Errorf("新周期用量应为0, got %+v", usage)
+	}
+}
+
+func TestService_FollowsRecorder(t *testing.T) {
+	recorder := stats.NewRecorder(0)
+	now := time.Now()
+	recorder.Record(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now, InputTokens: 10})
+
+	s := NewService(recorder)
+	recorder.Record(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now, OutputTokens: 5})
+
+	if usage := s.Usage("key-a", now); usage.DailyTokens != 15 {
+		t.Errorf("应累计已有记录和新记录, got %+v", usage)
+	}
+}
+
+func TestService_Warnings(t *testing.T) {
+	now := time.Date(2024, 5, 20, 12, 0, 0, 0, time.UTC)
+	s := NewService(nil)
+	cfg := &types.QuotaConfig{DailyCostUSD: 10, MonthlyTokens: 100000}
+
+	if warnings := s.Warnings("key-a", cfg, nil, now); len(warnings) != 0 {
+		t.Fatalf("Warnings() without usage = %+v", warnings)
+	}
+
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now, InputTokens: 1000, CostUSD: 5.5})
+	warnings := s.Warnings("key-a", cfg, nil, now)
+	if len(warnings) != 1 || warnings[0].Limit != LimitDailyCostUSD || warnings[0].Threshold != 0.5 || !warnings[0].Fired {
+		t.Fatalf("Warnings() at 55%% = %+v, want daily cost at 0.5 fired", warnings)
+	}
+	if warnings := s.Warnings("key-a", cfg, nil, now); len(warnings) != 1 || warnings[0].Fired {
+		t.Errorf("Warnings() again = %+v, want the same threshold without firing", warnings)
+	}
+
+	// 一次越过多个阈值时只告警最高的一个
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: now, CostUSD: 4.2})
+	if warnings := s.Warnings("key-a", cfg, nil, now); len(warnings) != 1 || warnings[0].Threshold != 0.95 || !warnings[0].Fired {
+		t.Errorf("Warnings() at 97%% = %+v, want 0.95 fired", warnings)
+	}
+
+	// 第二天用量归零，阈值重新计算
+	tomorrow := now.AddDate(0, 0, 1)
+	if warnings := s.Warnings("key-a", cfg, nil, tomorrow); len(warnings) != 0 {
+		t.Errorf("Warnings() next day = %+v", warnings)
+	}
+	s.Add(stats.UsageRecord{GatewayKeyID: "key-a", Timestamp: tomorrow, CostUSD: 6})
+	if warnings := s.Warnings("key-a", cfg, []float64{0.6}, tomorrow); len(warnings) != 1 || warnings[0].Threshold != 0.6 || !warnings[0].Fired {
+		t.Errorf("Warnings() with custom thresholds = %+v", warnings)
+	}
+}
@@ -0,0 +1,52 @@
+package quota
+
+import (
+	"sort"
+	"sync"
+)
+
+// WarningLevels 记录每个范围（如Key的某项配额、上游账号的限流窗口）已经告警到的阈值，
+// 使每个阈值在用量上升过程中只告警一次；用量回落（周期重置或额度恢复）到阈值以下后可以再次告警
+type WarningLevels struct {
+	levels map[string]float64
+	mutex  sync.Mutex
+}
+
+// NewWarningLevels 创建空的告警记录
+func NewWarningLevels() *WarningLevels {
+	return &WarningLevels{levels: make(map[string]float64)}
+}
+
+// Observe 记录范围当前的用量比例，返回达到的最高阈值（未达到任何阈值时为0），
+// 以及该阈值是否为首次达到。thresholds 为空时使用 DefaultWarningThresholds
+func (l *WarningLevels) Observe(scope string, fraction float64, thresholds []float64) (float64, bool) {
+	threshold := CrossedThreshold(fraction, thresholds)
+
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	fired := threshold > l.levels[scope]
+	if threshold == 0 {
+		delete(l.levels, scope)
+	} else {
+		l.levels[scope] = threshold
+	}
+	return threshold, fired
+}
+
+// CrossedThreshold 返回用量比例达到的最高阈值，未达到任何阈值时返回0
+func CrossedThreshold(fraction float64, thresholds []float64) float64 {
+	if len(thresholds) == 0 {
+		thresholds = DefaultWarningThresholds
+	}
+	sorted := append([]float64(nil), thresholds...)
+	sort.Float64s(sorted)
+
+	crossed := 0.0
+	for _, threshold := range sorted {
+		if fraction >= threshold {
+			crossed = threshold
+		}
+	}
+	return crossed
+}
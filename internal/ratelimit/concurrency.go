@@ -0,0 +1,52 @@
+package ratelimit
+
+import "sync"
+
+// ConcurrencyLimiter 按ID（Gateway Key或上游账号）限制同时进行的请求数
+type ConcurrencyLimiter struct {
+	mutex    sync.Mutex
+	inFlight map[string]int
+}
+
+// NewConcurrencyLimiter 创建并发限制器
+func NewConcurrencyLimiter() *ConcurrencyLimiter {
+	return &ConcurrencyLimiter{
+		inFlight: make(map[string]int),
+	}
+}
+
+// Acquire 尝试占用一个并发名额，不等待；limit<=0 表示不限制（仍计入进行中的请求数）。
+// 成功时返回释放函数（可重复调用，只释放一次），名额已满时返回 ok=false
+func (l *ConcurrencyLimiter) Acquire(id string, limit int) (release func(), ok bool) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if limit > 0 && l.inFlight[id] >= limit {
+		return nil, false
+	}
+	l.inFlight[id]++
+
+	var once sync.Once
+	return func() {
+		once.Do(func() { l.release(id) })
+	}, true
+}
+
+// release 归还一个并发名额
+func (l *ConcurrencyLimiter) release(id string) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if l.inFlight[id] <= 1 {
+		delete(l.inFlight, id)
+		return
+	}
+	l.inFlight[id]--
+}
+
+// InFlight 返回ID当前进行中的请求数
+func (l *ConcurrencyLimiter) InFlight(id string) int {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+	return l.inFlight[id]
+}
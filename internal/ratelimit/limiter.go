@@ -0,0 +1,130 @@
+package ratelimit
+
+import (
+	"math"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// Result 限流检查结果（对应最紧张的时间窗口）
+type Result struct {
+	Allowed   bool
+	Limit     int
+	Remaining int
+	Reset     time.Time
+}
+
+// RetryAfter 距离窗口重置的剩余时间
+func (r *Result) RetryAfter(now time.Time) time.Duration {
+	if d := r.Reset.Sub(now); d > 0 {
+		return d
+	}
+	return 0
+}
+
+// bucket 令牌桶：tokens 为剩余令牌，updated 为上次补充令牌的时间
+type bucket struct {
+	tokens  float64
+	updated time.Time
+}
+
+// windowSpec 时间窗口定义
+type windowSpec struct {
+	name   string
+	length time.Duration
+	limit  func(*types.RateLimitConfig) int
+}
+
+var windowSpecs = []windowSpec{
+	{"minute", time.Minute, func(c *types.RateLimitConfig) int { return c.RequestsPerMinute }},
+	{"hour", time.Hour, func(c *types.RateLimitConfig) int { return c.RequestsPerHour }},
+	{"day", 24 * time.Hour, func(c *types.RateLimitConfig) int { return c.RequestsPerDay }},
+}
+
+// Limiter 按Gateway Key限流，每个配置的窗口（分钟/小时/天）对应一个令牌桶。
+// 算法与 RedisLimiter 的脚本相同：桶的容量为窗口上限，按窗口长度匀速补满，
+// 因此同一个Key无论使用哪种后端（包括 Redis 不可用时的退回）都得到相同的限制
+type Limiter struct {
+	mutex   sync.Mutex
+	buckets map[string]map[string]*bucket // keyID -> 窗口名 -> 令牌桶
+}
+
+// NewLimiter 创建限流器
+func NewLimiter() *Limiter {
+	return &Limiter{
+		buckets: make(map[string]map[string]*bucket),
+	}
+}
+
+// Allow 检查并消耗一个令牌；cfg为nil或所有窗口未配置时返回 ok=false 表示不限流。
+// 拒绝时 Reset 为下一个令牌可用的时间，允许时为剩余令牌最少的桶补满的时间
+func (l *Limiter) Allow(keyID string, cfg *types.RateLimitConfig, now time.Time) (Result, bool) {
+	if cfg == nil {
+		return Result{}, false
+	}
+
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	keyBuckets, exists := l.buckets[keyID]
+	if !exists {
+		keyBuckets = make(map[string]*bucket)
+		l.buckets[keyID] = keyBuckets
+	}
+
+	var active []*bucket
+	var limits []int
+	var periods []time.Duration
+	for _, spec := range windowSpecs {
+		limit := spec.limit(cfg)
+		if limit <= 0 {
+			continue
+		}
+
+		// 按经过的毫秒数补充令牌（与 Redis 脚本相同的精度），不超过容量
+		b, exists := keyBuckets[spec.name]
+		if !exists {
+			b = &bucket{tokens: float64(limit), updated: now}
+			keyBuckets[spec.name] = b
+		} else if now.After(b.updated) {
+			b.tokens += float64(now.Sub(b.updated).Milliseconds()) * float64(limit) / float64(spec.length.Milliseconds())
+			b.updated = now
+		}
+		b.tokens = math.Min(b.tokens, float64(limit))
+
+		active = append(active, b)
+		limits = append(limits, limit)
+		periods = append(periods, spec.length)
+	}
+
+	if len(active) == 0 {
+		return Result{}, false
+	}
+
+	// 任一桶不足一个令牌则拒绝，返回该桶的信息
+	for i, b := range active {
+		if b.tokens < 1 {
+			return Result{Allowed: false, Limit: limits[i], Remaining: 0, Reset: now.Add(refillTime(1-b.tokens, limits[i], periods[i]))}, true
+		}
+	}
+
+	// 每个桶消耗一个令牌，返回剩余令牌最少的桶
+	result := Result{Allowed: true, Remaining: -1}
+	for i, b := range active {
+		b.tokens--
+		remaining := int(math.Floor(b.tokens))
+		if result.Remaining < 0 || remaining < result.Remaining {
+			result.Limit = limits[i]
+			result.Remaining = remaining
+			result.Reset = now.Add(refillTime(float64(limits[i])-b.tokens, limits[i], periods[i]))
+		}
+	}
+	return result, true
+}
+
+// refillTime 补充指定数量的令牌所需的时间，与 Redis 脚本一样向上取整到毫秒
+func refillTime(tokens float64, limit int, period time.Duration) time.Duration {
+	return time.Duration(math.Ceil(tokens*float64(period.Milliseconds())/float64(limit))) * time.Millisecond
+}
@@ -0,0 +1,220 @@
+package ratelimit
+
+import (
+	"errors"
+	"math"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/redis"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestLimiter_Allow(t *testing.T) {
+	limiter := NewLimiter()
+	cfg := &types.RateLimitConfig{RequestsPerMinute: 2, RequestsPerHour: 10}
+	now := time.Date(2024, 1, 1, 10, 30, 15, 0, time.UTC)
+
+	// 分钟桶容量为2，每30秒补充一个令牌
+	result, limited := limiter.Allow("key-a", cfg, now)
+	if !limited || !result.Allowed || result.Limit != 2 || result.Remaining != 1 {
+		t.Fatalf("first request: got %+v (limited=%v)", result, limited)
+	}
+	if !result.Reset.Equal(now.Add(30 * time.Second)) {
+		t.Errorf("unexpected reset time: %v", result.Reset)
+	}
+
+	result, _ = limiter.Allow("key-a", cfg, now)
+	if !result.Allowed || result.Remaining != 0 {
+		t.Fatalf("second request: got %+v", result)
+	}
+
+	result, _ = limiter.Allow("key-a", cfg, now)
+	if result.Allowed {
+		t.Fatalf("third request should be rejected: %+v", result)
+	}
+	if retry := result.RetryAfter(now); retry != 30*time.Second {
+		t.Errorf("expected retry after 30s, got %v", retry)
+	}
+
+	// 其他Key互不影响
+	if result, _ := limiter.Allow("key-b", cfg, now); !result.Allowed {
+		t.Error("key-b should not be limited")
+	}
+
+	// 令牌匀速补充，20秒后还不到一个令牌
+	if result, _ := limiter.Allow("key-a", cfg, now.Add(20*time.Second)); result.Allowed {
+		t.Errorf("after 20s: %+v, want still rejected", result)
+	}
+	result, _ = limiter.Allow("key-a", cfg, now.Add(31*time.Second))
+	if !result.Allowed || result.Remaining != 0 {
+		t.Errorf("after 31s: got %+v, want the refilled token used", result)
+	}
+
+	// 一分钟后分钟桶补满，小时桶继续累计
+	result, _ = limiter.Allow("key-a", cfg, now.Add(91*time.Second))
+	if !result.Allowed || result.Remaining != 1 {
+		t.Errorf("a minute later: got %+v", result)
+	}
+}
+
+func TestLimiter_MatchesRedisLimiter(t *testing.T) {
+	// 同一组请求在本实例和 Redis 两种后端上得到相同的结果
+	scripts := &stubScripts{buckets: make(map[string]*stubBucket)}
+	redisLimiter := &RedisLimiter{client: scripts, fallback: NewLimiter()}
+	limiter := NewLimiter()
+	cfg := &types.RateLimitConfig{RequestsPerMinute: 3, RequestsPerHour: 5}
+	now := time.Date(2024, 1, 1, 12, 0, 0, 0, time.UTC)
+
+	for _, offset := range []time.Duration{0, 0, 0, 0, 10 * time.Second, 20 * time.Second, 50 * time.Second, 2 * time.Minute, 2 * time.Minute} {
+		at := now.Add(offset)
+		want, _ := redisLimiter.Allow("key-a", cfg, at)
+		got, _ := limiter.Allow("key-a", cfg, at)
+		if got != want {
+			t.Errorf("at +%v: Limiter = %+v, RedisLimiter = %+v", offset, got, want)
+		}
+	}
+}
+
+func TestLimiter_Unlimited(t *testing.T) {
+	limiter := NewLimiter()
+	if _, limited := limiter.Allow("key-a", nil, time.Now()); limited {
+		t.Error("nil config should not be limited")
+	}
+	if _, limited := limiter.Allow("key-a", &types.RateLimitConfig{}, time.Now()); limited {
+		t.Error("empty config should not be limited")
+	}
+}
+
+func TestConcurrencyLimiter_Acquire(t *testing.T) {
+	limiter := NewConcurrencyLimiter()
+
+	first, ok := limiter.Acquire("key-a", 2)
+	if !ok {
+		t.Fatal("first request should be allowed")
+	}
+	second, ok := limiter.Acquire("key-a", 2)
+	if !ok {
+		t.Fatal("second request should be allowed")
+	}
+	if _, ok := limiter.Acquire("key-a", 2); ok {
+		t.Fatal("third concurrent request should be rejected")
+	}
+
+	// 其他ID互不影响，未配置上限时不限制但仍计数（用于最少连接策略）
+	if _, ok := limiter.Acquire("key-b", 2); !ok {
+		t.Error("key-b should not be limited")
+	}
+	for i := 0; i < 3; i++ {
+		if _, ok := limiter.Acquire("key-c", 0); !ok {
+			t.Fatal("limit 0 should not be limited")
+		}
+	}
+	if got := limiter.InFlight("key-c"); got != 3 {
+		t.Errorf("in flight with limit 0 = %d, want 3", got)
+	}
+
+	// 重复释放只归还一个名额
+	first()
+	first()
+	if got := limiter.InFlight("key-a"); got != 1 {
+		t.Errorf("in flight after release = %d, want 1", got)
+	}
+	if _, ok := limiter.Acquire("key-a", 2); !ok {
+		t.Error("request after release should be allowed")
+	}
+	second()
+}
+
+// stubBucket 模拟脚本中一个令牌桶的状态
+type stubBucket struct {
+	tokens float64
+	ts     int64
+}
+
+// stubScripts 用与限流脚本相同的规则模拟令牌桶，并记录每次调用的键
+type stubScripts struct {
+	buckets map[string]*stubBucket
+	keys    [][]string
+	err     error
+}
+
+func (s *stubScripts) Key(parts ...string) string {
+	return strings.Join(parts, ":")
+}
+
+func (s *stubScripts) Eval(script *redis.Script, keys []string, args ...interface{}) (interface{}, error) {
+	if s.err != nil {
+		return nil, s.err
+	}
+	s.keys = append(s.keys, keys)
+
+	now := args[0].(int64)
+	tokens := make([]float64, len(keys))
+	for i, key := range keys {
+		capacity, period := float64(args[2*i+1].(int)), float64(args[2*i+2].(int64))
+		bucket, exists := s.buckets[key]
+		if !exists {
+			bucket = &stubBucket{tokens: capacity, ts: now}
+			s.buckets[key] = bucket
+		} else if now > bucket.ts {
+			bucket.tokens = math.Min(capacity, bucket.tokens+float64(now-bucket.ts)*capacity/period)
+			bucket.ts = now
+		}
+		if bucket.tokens < 1 {
+			return []interface{}{int64(0), int64(i + 1), int64(math.Ceil((1 - bucket.tokens) * period / capacity))}, nil
+		}
+		tokens[i] = bucket.tokens
+	}
+	reply := []interface{}{int64(1)}
+	for i, key := range keys {
+		capacity, period := float64(args[2*i+1].(int)), float64(args[2*i+2].(int64))
+		s.buckets[key].tokens = tokens[i] - 1
+		reply = append(reply, int64(math.Floor(tokens[i]-1)), int64(math.Ceil((capacity-tokens[i]+1)*period/capacity)))
+	}
+	return reply, nil
+}
+
+func TestRedisLimiter_Allow(t *testing.T) {
+	scripts := &stubScripts{buckets: make(map[string]*stubBucket)}
+	limiter := &RedisLimiter{client: scripts, fallback: NewLimiter()}
+	cfg := &types.RateLimitConfig{RequestsPerMinute: 2, RequestsPerHour: 10}
+	now := time.Date(2024, 1, 1, 12, 0, 59, 0, time.UTC)
+
+	first, limited := limiter.Allow("key-a", cfg, now)
+	if !limited || !first.Allowed || first.Limit != 2 || first.Remaining != 1 || !first.Reset.Equal(now.Add(30*time.Second)) {
+		t.Fatalf("first request = %+v, want 1 left in the minute bucket, full again in 30s", first)
+	}
+	limiter.Allow("key-a", cfg, now)
+	third, _ := limiter.Allow("key-a", cfg, now)
+	if third.Allowed || third.Limit != 2 || !third.Reset.Equal(now.Add(30*time.Second)) {
+		t.Errorf("third request = %+v, want rejected by the minute bucket until the next token in 30s", third)
+	}
+	if got := scripts.buckets["ratelimit:{key-a}:hour"].tokens; got != 8 {
+		t.Errorf("hour bucket tokens = %v, want rejected requests not counted", got)
+	}
+
+	// 令牌匀速补充，跨过整分钟不会一次补满
+	if result, _ := limiter.Allow("key-a", cfg, now.Add(time.Second)); result.Allowed {
+		t.Errorf("after the minute boundary: %+v, want still rejected", result)
+	}
+	if result, _ := limiter.Allow("key-a", cfg, now.Add(31*time.Second)); !result.Allowed || result.Remaining != 0 {
+		t.Errorf("after 31s: %+v, want the refilled token used", result)
+	}
+
+	// 同一个Key的所有桶带有相同的哈希标签，Redis Cluster 中落在同一个槽位
+	for _, keys := range scripts.keys {
+		for _, key := range keys {
+			if !strings.Contains(key, "{key-a}") {
+				t.Errorf("key %q has no {key-a} hash tag", key)
+			}
+		}
+	}
+
+	// Redis 不可用时退回本实例计数
+	scripts.err = errors.New("connection refused")
+	if result, _ := limiter.Allow("key-b", cfg, now); !result.Allowed || result.Remaining != 1 {
+		t.Errorf("fallback result = %+v", result)
+	}
+}
@@ -0,0 +1,164 @@
+package ratelimit
+
+import (
+	"context"
+	"errors"
+	"sync"
+)
+
+// ErrEvicted 排队的请求被更高优先级的请求挤出队列
+var ErrEvicted = errors.New("evicted from queue by a higher priority request")
+
+// Waiter 排队等待上游并发名额的请求
+type Waiter struct {
+	key      string // 等待的资源（如提供商），只有同一资源的名额归还时才唤醒
+	rank     int    // 数字越小越优先
+	seq      uint64 // 同一优先级内按进入顺序
+	ready    chan struct{}
+	signaled bool
+}
+
+// Queue 有界的优先级等待队列：名额归还时唤醒同一资源中优先级最高、最早进入的请求，
+// 被唤醒的请求重新尝试占用名额，失败时保留原来的位置继续等待
+type Queue struct {
+	mutex   sync.Mutex
+	maxSize int
+	seq     uint64
+	waiters []*Waiter
+}
+
+// NewQueue 创建最多容纳 maxSize 个请求的等待队列
+func NewQueue(maxSize int) *Queue {
+	return &Queue{maxSize: maxSize}
+}
+
+// Join 进入队列，队列已满时返回 ok=false。队列满时高优先级的请求会挤掉最低优先级中最晚进入的请求，
+// 被挤掉的请求立即被唤醒并在下一次 Wait 时返回 ErrEvicted
+func (q *Queue) Join(key string, rank int) (*Waiter, bool) {
+	q.mutex.Lock()
+	defer q.mutex.Unlock()
+
+	if len(q.waiters) >= q.maxSize {
+		victim := q.lowestLocked()
+		if victim == nil || victim.rank <= rank {
+			return nil, false
+		}
+		q.removeLocked(victim)
+		victim.key = ""
+		q.signalLocked(victim)
+	}
+
+	q.seq++
+	waiter := &Waiter{key: key, rank: rank, seq: q.seq, ready: make(chan struct{}, 1)}
+	q.waiters = append(q.waiters, waiter)
+	return waiter, true
+}
+
+// Wait 等待被唤醒，ctx 结束时返回 ctx 的错误，被挤出队列时返回 ErrEvicted
+func (q *Queue) Wait(ctx context.Context, waiter *Waiter) error {
+	select {
+	case <-waiter.ready:
+		q.mutex.Lock()
+		defer q.mutex.Unlock()
+		waiter.signaled = false
+		if waiter.key == "" {
+			return ErrEvicted
+		}
+		return nil
+	case <-ctx.Done():
+		return ctx.Err()
+	}
+}
+
+// Leave 离开队列（请求获得名额、超时或放弃时调用），重复调用无副作用。
+// 离开时若已被唤醒但没有用上，把唤醒转交给同一资源的下一个请求
+func (q *Queue) Leave(waiter *Waiter) {
+	q.mutex.Lock()
+	defer q.mutex.Unlock()
+
+	if !q.removeLocked(waiter) || !waiter.signaled {
+		return
+	}
+	if next := q.frontLocked(waiter.key); next != nil {
+		q.signalLocked(next)
+	}
+}
+
+// Signal 资源的一个名额已归还，唤醒排在最前面且尚未被唤醒的请求
+func (q *Queue) Signal(key string) {
+	q.mutex.Lock()
+	defer q.mutex.Unlock()
+
+	if next := q.frontLocked(key); next != nil {
+		q.signalLocked(next)
+	}
+}
+
+// Len 返回排队中的请求数
+func (q *Queue) Len() int {
+	q.mutex.Lock()
+	defer q.mutex.Unlock()
+	return len(q.waiters)
+}
+
+// Capacity 返回队列最多容纳的请求数
+func (q *Queue) Capacity() int {
+	return q.maxSize
+}
+
+// Depths 返回每个资源排队中的请求数
+func (q *Queue) Depths() map[string]int {
+	q.mutex.Lock()
+	defer q.mutex.Unlock()
+
+	depths := make(map[string]int)
+	for _, waiter := range q.waiters {
+		depths[waiter.key]++
+	}
+	return depths
+}
+
+// frontLocked 返回资源中优先级最高、最早进入且尚未被唤醒的请求（调用方持有锁）
+func (q *Queue) frontLocked(key string) *Waiter {
+	var front *Waiter
+	for _, waiter := range q.waiters {
+		if waiter.key != key || waiter.signaled {
+			continue
+		}
+		if front == nil || waiter.rank < front.rank || (waiter.rank == front.rank && waiter.seq < front.seq) {
+			front = waiter
+		}
+	}
+	return front
+}
+
+// lowestLocked 返回优先级最低、最晚进入的请求（调用方持有锁）
+func (q *Queue) lowestLocked() *Waiter {
+	var lowest *Waiter
+	for _, waiter := range q.waiters {
+		if lowest == nil || waiter.rank > lowest.rank || (waiter.rank == lowest.rank && waiter.seq > lowest.seq) {
+			lowest = waiter
+		}
+	}
+	return lowest
+}
+
+// signalLocked 唤醒请求（调用方持有锁）
+func (q *Queue) signalLocked(waiter *Waiter) {
+	waiter.signaled = true
+	select {
+	case waiter.ready <- struct{}{}:
+	default:
+	}
+}
+
+// removeLocked 从队列中移除请求，不在队列中时返回false（调用方持有锁）
+func (q *Queue) removeLocked(waiter *Waiter) bool {
+	for i, queued := range q.waiters {
+		if queued == waiter {
+			q.waiters = append(q.waiters[:i], q.waiters[i+1:]...)
+			return true
+		}
+	}
+	return false
+}
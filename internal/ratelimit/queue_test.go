@@ -0,0 +1,81 @@
+package ratelimit
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+)
+
+// signaled 检查请求是否已被唤醒（不阻塞）
+func signaled(waiter *Waiter) bool {
+	select {
+	case <-waiter.ready:
+		waiter.ready <- struct{}{}
+		return true
+	default:
+		return false
+	}
+}
+
+func TestQueue_PriorityOrder(t *testing.T) {
+	queue := NewQueue(10)
+	low, _ := queue.Join("anthropic", 3)
+	normal, _ := queue.Join("anthropic", 2)
+	other, _ := queue.Join("openai", 0)
+	critical, _ := queue.Join("anthropic", 0)
+
+	// 只唤醒同一提供商中优先级最高的请求，critical 后进入也排在最前
+	queue.Signal("anthropic")
+	if !signaled(critical) || signaled(normal) || signaled(low) || signaled(other) {
+		t.Fatal("the critical request should be woken first")
+	}
+	if err := queue.Wait(context.Background(), critical); err != nil {
+		t.Fatalf("Wait() error = %v", err)
+	}
+	queue.Leave(critical)
+
+	queue.Signal("anthropic")
+	if !signaled(normal) || signaled(low) {
+		t.Fatal("the normal request should be woken before the low one")
+	}
+
+	// 被唤醒但没有用上名额就离开时，唤醒转交给下一个请求
+	queue.Leave(normal)
+	if !signaled(low) {
+		t.Error("leaving with an unused wakeup should pass it on")
+	}
+	if queue.Len() != 2 {
+		t.Errorf("Len() = %d, want 2", queue.Len())
+	}
+}
+
+func TestQueue_FullAndEviction(t *testing.T) {
+	queue := NewQueue(2)
+	first, _ := queue.Join("anthropic", 2)
+	second, _ := queue.Join("anthropic", 2)
+
+	if _, ok := queue.Join("anthropic", 2); ok {
+		t.Fatal("a full queue should reject requests of the same priority")
+	}
+
+	// 高优先级的请求挤掉最低优先级中最晚进入的请求
+	if _, ok := queue.Join("anthropic", 0); !ok {
+		t.Fatal("a critical request should evict a lower priority one")
+	}
+	if err := queue.Wait(context.Background(), second); !errors.Is(err, ErrEvicted) {
+		t.Errorf("Wait() for the evicted request = %v, want ErrEvicted", err)
+	}
+	if signaled(first) {
+		t.Error("the earlier request should stay queued")
+	}
+	if depths := queue.Depths(); depths["anthropic"] != 2 || len(depths) != 1 {
+		t.Errorf("Depths() = %v, want 2 anthropic requests", depths)
+	}
+
+	ctx, cancel := context.WithTimeout(context.Background(), 10*time.Millisecond)
+	defer cancel()
+	if err := queue.Wait(ctx, first); !errors.Is(err, context.DeadlineExceeded) {
+		t.Errorf("Wait() = %v, want the context deadline", err)
+	}
+}
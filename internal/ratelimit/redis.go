@@ -0,0 +1,165 @@
+package ratelimit
+
+import (
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/redis"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// WindowLimiter 按Gateway Key的分钟/小时/天额度限流，由进程内的 Limiter 或多实例共享的 RedisLimiter 实现
+type WindowLimiter interface {
+	Allow(keyID string, cfg *types.RateLimitConfig, now time.Time) (Result, bool)
+}
+
+// redisBucketScript 原子地检查并消耗一个Key的所有令牌桶。每个桶是一个哈希（tokens 为剩余令牌，ts 为上次补充的毫秒时间），
+// 容量为窗口的上限，每个窗口长度补满一次。任一桶不足一个令牌时不消耗并返回 {0, 桶序号, 等到一个令牌的毫秒数}，
+// 否则每个桶减一并返回 {1, 剩余令牌, 补满的毫秒数, ...}。ARGV[1] 为当前毫秒时间，之后依次为每个桶的容量和窗口毫秒数。
+// 桶在补满后再保留一分钟才过期，容忍实例之间的时钟偏差
+var redisBucketScript = redis.NewScript(`
+local now = tonumber(ARGV[1])
+local tokens = {}
+local stamps = {}
+for i = 1, #KEYS do
+	local capacity = tonumber(ARGV[2 * i])
+	local period = tonumber(ARGV[2 * i + 1])
+	local state = redis.call('HMGET', KEYS[i], 'tokens', 'ts')
+	local t = tonumber(state[1])
+	local ts = tonumber(state[2])
+	if t == nil or ts == nil then
+		t, ts = capacity, now
+	elseif now > ts then
+		t = t + (now - ts) * capacity / period
+		ts = now
+	end
+	t = math.min(t, capacity)
+	if t < 1 then
+		return {0, i, math.ceil((1 - t) * period / capacity)}
+	end
+	tokens[i], stamps[i] = t, ts
+end
+local result = {1}
+for i = 1, #KEYS do
+	local capacity = tonumber(ARGV[2 * i])
+	local period = tonumber(ARGV[2 * i + 1])
+	local t = tokens[i] - 1
+	redis.call('HSET', KEYS[i], 'tokens', tostring(t), 'ts', tostring(stamps[i]))
+	redis.call('PEXPIRE', KEYS[i], period + 60000)
+	result[2 * i] = math.floor(t)
+	result[2 * i + 1] = math.ceil((capacity - t) * period / capacity)
+end
+return result
+`)
+
+// redisErrorLogInterval Redis 不可用时记录日志的最小间隔，避免每个请求都打日志
+const redisErrorLogInterval = time.Minute
+
+// scriptRunner 执行 Lua 脚本（由 redis.Client 实现）
+type scriptRunner interface {
+	Key(parts ...string) string
+	Eval(script *redis.Script, keys []string, args ...interface{}) (interface{}, error)
+}
+
+// RedisLimiter 令牌桶保存在 Redis 中，多个网关实例共享同一个Key的限流额度。
+// 每个配置的窗口对应一个令牌桶，容量为窗口上限，按窗口长度匀速补满，因此不会在窗口交界处放过两倍的请求。
+// 同一个Key的所有桶使用 {keyID} 哈希标签，落在同一个 Redis Cluster 槽位，可以由一个脚本原子地更新。
+// Redis 不可用时退回本实例的 Limiter（相同的令牌桶算法），不拒绝请求
+type RedisLimiter struct {
+	client   scriptRunner
+	fallback *Limiter
+
+	mutex     sync.Mutex
+	lastError time.Time // 上次记录 Redis 错误日志的时间
+}
+
+// NewRedisLimiter 创建使用 Redis 令牌桶的限流器
+func NewRedisLimiter(client *redis.Client) *RedisLimiter {
+	return &RedisLimiter{client: client, fallback: NewLimiter()}
+}
+
+// Allow 检查并消耗一个令牌，返回值的含义与 Limiter.Allow 相同；
+// 拒绝时 Reset 为下一个令牌可用的时间，允许时为剩余令牌最少的桶补满的时间
+func (l *RedisLimiter) Allow(keyID string, cfg *types.RateLimitConfig, now time.Time) (Result, bool) {
+	if cfg == nil {
+		return Result{}, false
+	}
+
+	var keys []string
+	args := []interface{}{now.UnixMilli()}
+	var limits []int
+	for _, spec := range windowSpecs {
+		limit := spec.limit(cfg)
+		if limit <= 0 {
+			continue
+		}
+
+		keys = append(keys, l.client.Key("ratelimit", "{"+keyID+"}", spec.name))
+		args = append(args, limit, spec.length.Milliseconds())
+		limits = append(limits, limit)
+	}
+	if len(keys) == 0 {
+		return Result{}, false
+	}
+
+	reply, err := l.eval(keys, args)
+	if err != nil {
+		l.logError(err, now)
+		return l.fallback.Allow(keyID, cfg, now)
+	}
+
+	// 任一桶没有令牌则拒绝，返回该桶的信息
+	if reply[0] == 0 {
+		i := int(reply[1]) - 1
+		return Result{Allowed: false, Limit: limits[i], Remaining: 0, Reset: now.Add(time.Duration(reply[2]) * time.Millisecond)}, true
+	}
+
+	// 返回剩余令牌最少的桶
+	result := Result{Allowed: true, Remaining: -1}
+	for i := range keys {
+		remaining := int(reply[2*i+1])
+		if remaining < 0 {
+			remaining = 0
+		}
+		if result.Remaining < 0 || remaining < result.Remaining {
+			result.Limit = limits[i]
+			result.Remaining = remaining
+			result.Reset = now.Add(time.Duration(reply[2*i+2]) * time.Millisecond)
+		}
+	}
+	return result, true
+}
+
+// eval 执行令牌桶脚本并检查回复格式
+func (l *RedisLimiter) eval(keys []string, args []interface{}) ([]int64, error) {
+	reply, err := l.client.Eval(redisBucketScript, keys, args...)
+	if err != nil {
+		return nil, err
+	}
+	values, err := redis.Int64s(reply)
+	if err != nil {
+		return nil, err
+	}
+
+	switch {
+	case len(values) == 3 && values[0] == 0 && values[1] >= 1 && int(values[1]) <= len(keys):
+	case len(values) == 2*len(keys)+1 && values[0] == 1:
+	default:
+		return nil, fmt.Errorf("限流脚本返回了无效的结果: %v", values)
+	}
+	return values, nil
+}
+
+// logError 记录 Redis 错误，每分钟最多一次
+func (l *RedisLimiter) logError(err error, now time.Time) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+
+	if now.Sub(l.lastError) < redisErrorLogInterval {
+		return
+	}
+	l.lastError = now
+	logger.Warn("Redis 限流不可用，暂时使用本实例的计数: %v", err)
+}
@@ -0,0 +1,339 @@
+package redis
+
+import (
+	"bufio"
+	"crypto/sha1"
+	"crypto/tls"
+	"encoding/hex"
+	"fmt"
+	"io"
+	"net"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	defaultTimeout   = 500 * time.Millisecond
+	defaultKeyPrefix = "llm-gateway:"
+	maxIdleConns     = 16
+)
+
+// Error Redis 返回的错误回复（如 NOSCRIPT、WRONGTYPE）
+type Error string
+
+func (e Error) Error() string {
+	return string(e)
+}
+
+// Client Redis 客户端，使用 RESP 协议直接通信，只实现网关需要的命令。
+// 连接按需建立并放回空闲池复用，网络错误的连接直接关闭
+type Client struct {
+	address  string
+	password string
+	db       int
+	tls      bool
+	prefix   string
+	timeout  time.Duration
+	idle     chan *conn
+	closed   bool
+	mutex    sync.Mutex
+}
+
+// conn 单个 Redis 连接
+type conn struct {
+	net.Conn
+	reader *bufio.Reader
+}
+
+// NewClient 根据配置创建客户端（不立即连接）
+func NewClient(config *types.RedisConfig) (*Client, error) {
+	if config.Address == "" {
+		return nil, fmt.Errorf("未配置 Redis 地址")
+	}
+
+	prefix := config.KeyPrefix
+	if prefix == "" {
+		prefix = defaultKeyPrefix
+	}
+	timeout := time.Duration(config.TimeoutMs) * time.Millisecond
+	if timeout <= 0 {
+		timeout = defaultTimeout
+	}
+
+	return &Client{
+		address:  config.Address,
+		password: config.Password,
+		db:       config.DB,
+		tls:      config.TLS,
+		prefix:   prefix,
+		timeout:  timeout,
+		idle:     make(chan *conn, maxIdleConns),
+	}, nil
+}
+
+// Key 加上配置的前缀，同一个 Redis 可以由多套网关共用
+func (c *Client) Key(parts ...string) string {
+	return c.prefix + strings.Join(parts, ":")
+}
+
+// Do 执行命令并返回回复：简单字符串和批量字符串为 string，整数为 int64，数组为 []interface{}，
+// 空回复为 nil；Redis 返回的错误为 Error
+func (c *Client) Do(args ...interface{}) (interface{}, error) {
+	cn, err := c.get()
+	if err != nil {
+		return nil, err
+	}
+
+	reply, err := cn.do(c.timeout, args)
+	if err != nil {
+		if _, isReply := err.(Error); !isReply {
+			cn.Close()
+			return nil, err
+		}
+	}
+	c.put(cn)
+	return reply, err
+}
+
+// Ping 检查连接是否可用
+func (c *Client) Ping() error {
+	_, err := c.Do("PING")
+	return err
+}
+
+// Close 关闭空闲连接，之后的命令返回错误
+func (c *Client) Close() {
+	if c == nil {
+		return
+	}
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+
+	if c.closed {
+		return
+	}
+	c.closed = true
+	close(c.idle)
+	for cn := range c.idle {
+		cn.Close()
+	}
+}
+
+// get 取一个空闲连接，没有时新建
+func (c *Client) get() (*conn, error) {
+	c.mutex.Lock()
+	closed := c.closed
+	c.mutex.Unlock()
+	if closed {
+		return nil, fmt.Errorf("客户端已关闭")
+	}
+
+	select {
+	case cn := <-c.idle:
+		if cn != nil {
+			return cn, nil
+		}
+	default:
+	}
+	return c.dial()
+}
+
+// put 归还连接，空闲池已满或客户端已关闭时关闭连接
+func (c *Client) put(cn *conn) {
+	c.mutex.Lock()
+	defer c.mutex.Unlock()
+
+	if c.closed {
+		cn.Close()
+		return
+	}
+	select {
+	case c.idle <- cn:
+	default:
+		cn.Close()
+	}
+}
+
+// dial 建立连接并完成认证和选择数据库
+func (c *Client) dial() (*conn, error) {
+	dialer := &net.Dialer{Timeout: c.timeout}
+	var netConn net.Conn
+	var err error
+	if c.tls {
+		host, _, _ := net.SplitHostPort(c.address)
+		netConn, err = tls.DialWithDialer(dialer, "tcp", c.address, &tls.Config{ServerName: host})
+	} else {
+		netConn, err = dialer.Dial("tcp", c.address)
+	}
+	if err != nil {
+		return nil, fmt.Errorf("连接 Redis 失败: %w", err)
+	}
+
+	cn := &conn{Conn: netConn, reader: bufio.NewReader(netConn)}
+	if c.password != "" {
+		if _, err := cn.do(c.timeout, []interface{}{"AUTH", c.password}); err != nil {
+			cn.Close()
+			return nil, fmt.Errorf("认证失败: %w", err)
+		}
+	}
+	if c.db != 0 {
+		if _, err := cn.do(c.timeout, []interface{}{"SELECT", c.db}); err != nil {
+			cn.Close()
+			return nil, fmt.Errorf("选择 Redis 数据库 %d 失败: %w", c.db, err)
+		}
+	}
+	return cn, nil
+}
+
+// do 在连接上发送一条命令并读取回复
+func (cn *conn) do(timeout time.Duration, args []interface{}) (interface{}, error) {
+	if err := cn.SetDeadline(time.Now().Add(timeout)); err != nil {
+		return nil, err
+	}
+	if _, err := cn.Write(encodeCommand(args)); err != nil {
+		return nil, fmt.Errorf("发送 Redis 命令失败: %w", err)
+	}
+	return readReply(cn.reader)
+}
+
+// encodeCommand 把命令编码为 RESP 批量字符串数组
+func encodeCommand(args []interface{}) []byte {
+	buf := make([]byte, 0, 64)
+	buf = append(buf, '*')
+	buf = strconv.AppendInt(buf, int64(len(args)), 10)
+	buf = append(buf, '\r', '\n')
+	for _, arg := range args {
+		var value string
+		switch v := arg.(type) {
+		case string:
+			value = v
+		case []byte:
+			value = string(v)
+		case int:
+			value = strconv.Itoa(v)
+		case int64:
+			value = strconv.FormatInt(v, 10)
+		case float64:
+			value = strconv.FormatFloat(v, 'f', -1, 64)
+		default:
+			value = fmt.Sprint(v)
+		}
+		buf = append(buf, '$')
+		buf = strconv.AppendInt(buf, int64(len(value)), 10)
+		buf = append(buf, '\r', '\n')
+		buf = append(buf, value...)
+		buf = append(buf, '\r', '\n')
+	}
+	return buf
+}
+
+// readReply 读取一个 RESP 回复
+func readReply(reader *bufio.Reader) (interface{}, error) {
+	line, err := reader.ReadString('\n')
+	if err != nil {
+		return nil, fmt.Errorf("读取 Redis 回复失败: %w", err)
+	}
+	if len(line) < 3 || !strings.HasSuffix(line, "\r\n") {
+		return nil, fmt.Errorf("无效的 Redis 回复: %q", line)
+	}
+	payload := line[1 : len(line)-2]
+
+	switch line[0] {
+	case '+':
+		return payload, nil
+	case '-':
+		return nil, Error(payload)
+	case ':':
+		n, err := strconv.ParseInt(payload, 10, 64)
+		if err != nil {
+			return nil, fmt.Errorf("无效的 Redis 整数回复: %q", payload)
+		}
+		return n, nil
+	case '$':
+		size, err := strconv.Atoi(payload)
+		if err != nil {
+			return nil, fmt.Errorf("无效的 Redis 批量回复长度: %q", payload)
+		}
+		if size < 0 {
+			return nil, nil
+		}
+		data := make([]byte, size+2)
+		if _, err := io.ReadFull(reader, data); err != nil {
+			return nil, fmt.Errorf("读取 Redis 回复失败: %w", err)
+		}
+		return string(data[:size]), nil
+	case '*':
+		count, err := strconv.Atoi(payload)
+		if err != nil {
+			return nil, fmt.Errorf("无效的 Redis 数组回复长度: %q", payload)
+		}
+		if count < 0 {
+			return nil, nil
+		}
+		items := make([]interface{}, count)
+		for i := range items {
+			item, err := readReply(reader)
+			if err != nil {
+				// 数组中的错误回复作为元素返回，读取失败则整个回复失败
+				if replyErr, isReply := err.(Error); isReply {
+					items[i] = replyErr
+					continue
+				}
+				return nil, err
+			}
+			items[i] = item
+		}
+		return items, nil
+	}
+	return nil, fmt.Errorf("无效的 Redis 回复类型: %q", line[0])
+}
+
+// Script Lua 脚本，优先用 EVALSHA 执行，服务器未缓存时回退到 EVAL
+type Script struct {
+	source string
+	hash   string
+}
+
+// NewScript 创建脚本
+func NewScript(source string) *Script {
+	sum := sha1.Sum([]byte(source))
+	return &Script{source: source, hash: hex.EncodeToString(sum[:])}
+}
+
+// Eval 执行脚本
+func (c *Client) Eval(script *Script, keys []string, args ...interface{}) (interface{}, error) {
+	command := make([]interface{}, 0, 3+len(keys)+len(args))
+	command = append(command, "EVALSHA", script.hash, len(keys))
+	for _, key := range keys {
+		command = append(command, key)
+	}
+	command = append(command, args...)
+
+	reply, err := c.Do(command...)
+	if replyErr, isReply := err.(Error); isReply && strings.HasPrefix(string(replyErr), "NOSCRIPT") {
+		command[0], command[1] = "EVAL", script.source
+		return c.Do(command...)
+	}
+	return reply, err
+}
+
+// Int64s 把数组回复转换为整数列表
+func Int64s(reply interface{}) ([]int64, error) {
+	items, ok := reply.([]interface{})
+	if !ok {
+		return nil, fmt.Errorf("回复不是数组: %T", reply)
+	}
+	values := make([]int64, len(items))
+	for i, item := range items {
+		n, ok := item.(int64)
+		if !ok {
+			return nil, fmt.Errorf("回复的第%d项不是整数: %T", i+1, item)
+		}
+		values[i] = n
+	}
+	return values, nil
+}
@@ -0,0 +1,169 @@
+package redis
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"net"
+	"strconv"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// fakeServer 按命令返回预设的 RESP 回复，记录收到的命令
+type fakeServer struct {
+	listener net.Listener
+	reply    func(args []string) string
+	commands []string
+	mutex    sync.Mutex
+}
+
+func newFakeServer(t *testing.T, reply func(args []string) string) *fakeServer {
+	listener, err := net.Listen("tcp", "127.0.0.1:0")
+	if err != nil {
+		t.Fatalf("Listen() error = %v", err)
+	}
+	s := &fakeServer{listener: listener, reply: reply}
+	t.Cleanup(func() { listener.Close() })
+
+	go func() {
+		for {
+			conn, err := listener.Accept()
+			if err != nil {
+				return
+			}
+			go s.serve(conn)
+		}
+	}()
+	return s
+}
+
+func (s *fakeServer) serve(conn net.Conn) {
+	defer conn.Close()
+	reader := bufio.NewReader(conn)
+	for {
+		args, err := readCommand(reader)
+		if err != nil {
+			return
+		}
+		s.mutex.Lock()
+		s.commands = append(s.commands, strings.Join(args, " "))
+		s.mutex.Unlock()
+		if _, err := io.WriteString(conn, s.reply(args)); err != nil {
+			return
+		}
+	}
+}
+
+func (s *fakeServer) received() []string {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	return append([]string(nil), s.commands...)
+}
+
+func readCommand(reader *bufio.Reader) ([]string, error) {
+	line, err := reader.ReadString('\n')
+	if err != nil {
+		return nil, err
+	}
+	count, _ := strconv.Atoi(strings.TrimSpace(line[1:]))
+	args := make([]string, count)
+	for i := range args {
+		if _, err := reader.ReadString('\n'); err != nil {
+			return nil, err
+		}
+		value, err := reader.ReadString('\n')
+		if err != nil {
+			return nil, err
+		}
+		args[i] = strings.TrimSuffix(value, "\r\n")
+	}
+	return args, nil
+}
+
+func TestClient_Do(t *testing.T) {
+	server := newFakeServer(t, func(args []string) string {
+		switch args[0] {
+		case "AUTH", "SELECT":
+			return "+OK\r\n"
+		case "INCR":
+			return ":7\r\n"
+		case "GET":
+			if args[1] == "missing" {
+				return "$-1\r\n"
+			}
+			return "$5\r\nhello\r\n"
+		case "HGETALL":
+			return "*2\r\n$4\r\nup-1\r\n$2\r\n{}\r\n"
+		case "EVALSHA":
+			return "-NOSCRIPT No matching script\r\n"
+		case "EVAL":
+			return "*3\r\n:1\r\n:2\r\n:3\r\n"
+		}
+		return "-ERR unknown command\r\n"
+	})
+
+	client, err := NewClient(&types.RedisConfig{Address: server.listener.Addr().String(), Password: "secret", DB: 2})
+	if err != nil {
+		t.Fatalf("NewClient() error = %v", err)
+	}
+	defer client.Close()
+
+	if reply, err := client.Do("INCR", client.Key("counter")); err != nil || reply != int64(7) {
+		t.Errorf("INCR = %v, %v", reply, err)
+	}
+	if reply, err := client.Do("GET", "key"); err != nil || reply != "hello" {
+		t.Errorf("GET = %v, %v", reply, err)
+	}
+	if reply, err := client.Do("GET", "missing"); err != nil || reply != nil {
+		t.Errorf("GET missing = %v, %v", reply, err)
+	}
+	if reply, err := client.Do("HGETALL", "h"); err != nil || fmt.Sprint(reply) != "[up-1 {}]" {
+		t.Errorf("HGETALL = %v, %v", reply, err)
+	}
+	if _, err := client.Do("FLUSHALL"); err == nil || err.Error() != "ERR unknown command" {
+		t.Errorf("FLUSHALL error = %v, want the server error", err)
+	}
+
+	// 服务器没有缓存脚本时回退到 EVAL
+	reply, err := client.Eval(NewScript("return {1, 2, 3}"), []string{"k"}, 10)
+	values, convErr := Int64s(reply)
+	if err != nil || convErr != nil || len(values) != 3 || values[2] != 3 {
+		t.Errorf("Eval() = %v, %v, %v", reply, err, convErr)
+	}
+
+	// 错误回复不关闭连接：所有命令复用认证后的同一个连接
+	commands := server.received()
+	if commands[0] != "AUTH secret" || commands[1] != "SELECT 2" || commands[2] != "INCR llm-gateway:counter" {
+		t.Errorf("commands = %v", commands)
+	}
+	auths := 0
+	for _, command := range commands {
+		if strings.HasPrefix(command, "AUTH") {
+			auths++
+		}
+	}
+	if auths != 1 {
+		t.Errorf("connected %d times, want the connection reused", auths)
+	}
+}
+
+func TestClient_Unreachable(t *testing.T) {
+	listener, err := net.Listen("tcp", "127.0.0.1:0")
+	if err != nil {
+		t.Fatalf("Listen() error = %v", err)
+	}
+	address := listener.Addr().String()
+	listener.Close()
+
+	client, _ := NewClient(&types.RedisConfig{Address: address, TimeoutMs: 100})
+	if err := client.Ping(); err == nil {
+		t.Error("Ping() should fail when Redis is unreachable")
+	}
+	if _, err := NewClient(&types.RedisConfig{}); err == nil {
+		t.Error("NewClient() should require an address")
+	}
+}
@@ -0,0 +1,305 @@
+package router
+
+import (
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 自动切换的默认参数
+const (
+	defaultAutopilotInterval    = 30 * time.Second
+	defaultAutopilotWindow      = 5 * time.Minute
+	defaultAutopilotLatencyMs   = 10000
+	defaultAutopilotSpikeFactor = 3.0
+	defaultAutopilotMinHold     = 5 * time.Minute
+)
+
+// autopilotBaselineWindow 流量突增与观察窗口之前多长时间的平均速率比较
+const autopilotBaselineWindow = time.Hour
+
+// autopilotRecoveryRatio 指标回落到阈值的该比例以下才视为恢复，避免在阈值附近来回切换
+const autopilotRecoveryRatio = 0.8
+
+// autopilotMinRequests 观察窗口内的请求少于该数量时样本太少，视为正常
+const autopilotMinRequests = 20
+
+// maxStrategySwitches 保留的切换记录条数
+const maxStrategySwitches = 50
+
+// 自动切换判断出的流量状况
+const (
+	ConditionNormal  = "normal"
+	ConditionLatency = "latency" // 延迟异常，切换到 fastest
+	ConditionSpike   = "spike"   // 流量突增，切换到 least_connections
+)
+
+// StrategySwitch 一次负载均衡策略切换
+type StrategySwitch struct {
+	Timestamp time.Time       `json:"timestamp"`
+	From      BalanceStrategy `json:"from"`
+	To        BalanceStrategy `json:"to"`
+	Reason    string          `json:"reason"`
+	Manual    bool            `json:"manual,omitempty"`
+}
+
+// AutopilotSignals 最近一次评估的观察值
+type AutopilotSignals struct {
+	EvaluatedAt       time.Time `json:"evaluated_at"`
+	Requests          int       `json:"requests"` // 观察窗口内的请求数
+	AvgLatencyMs      float64   `json:"avg_latency_ms"`
+	RequestsPerMinute float64   `json:"requests_per_minute"`
+	BaselinePerMinute float64   `json:"baseline_per_minute"` // 观察窗口之前一小时的平均速率
+}
+
+// AutopilotStatus 负载均衡策略的当前状态
+type AutopilotStatus struct {
+	Strategy  BalanceStrategy  `json:"strategy"`           // 当前生效的策略
+	Base      BalanceStrategy  `json:"base"`               // 配置的策略（routing.strategy）
+	Override  BalanceStrategy  `json:"override,omitempty"` // 手动指定的策略，优先于自动切换
+	Enabled   bool             `json:"autopilot_enabled"`
+	Condition string           `json:"condition"`
+	Signals   AutopilotSignals `json:"signals"`
+	Switches  []StrategySwitch `json:"switches"` // 最近的切换记录，最新的在前
+}
+
+// Autopilot 负载均衡策略的自动切换：定期按最近的请求判断是否出现延迟异常或流量突增，
+// 异常时切换到更合适的策略，恢复后切回配置的策略。进入和退出使用不同的阈值，且切换后至少保持
+// min_hold_seconds，避免来回切换。手动指定的策略优先于自动切换，直到清除
+type Autopilot struct {
+	router    *RequestRouter
+	recorder  *stats.Recorder
+	settings  func() *types.RoutingConfig
+	override  BalanceStrategy
+	condition string
+	changedAt time.Time // condition 上次变化的时间
+	signals   AutopilotSignals
+	switches  []StrategySwitch
+	stopCh    chan struct{}
+	mutex     sync.Mutex
+}
+
+// NewAutopilot 创建策略自动切换，settings 返回全局配置中当前的 routing（修改或重新加载后下次评估生效）
+func NewAutopilot(router *RequestRouter, recorder *stats.Recorder, settings func() *types.RoutingConfig) *Autopilot {
+	return &Autopilot{
+		router:    router,
+		recorder:  recorder,
+		settings:  settings,
+		condition: ConditionNormal,
+	}
+}
+
+// Start 启动后台评估
+func (a *Autopilot) Start() {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	if a.stopCh != nil {
+		return
+	}
+	a.stopCh = make(chan struct{})
+
+	interval := time.Duration(a.settings().Autopilot.IntervalSeconds) * time.Second
+	if interval <= 0 {
+		interval = defaultAutopilotInterval
+	}
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				a.Evaluate(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(a.stopCh)
+}
+
+// Stop 停止后台评估
+func (a *Autopilot) Stop() {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	if a.stopCh != nil {
+		close(a.stopCh)
+		a.stopCh = nil
+	}
+}
+
+// Evaluate 按最近的请求更新流量状况并应用对应的策略，返回生效的策略
+func (a *Autopilot) Evaluate(now time.Time) BalanceStrategy {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	settings := a.settings().Autopilot
+	if settings.Enabled {
+		a.signals = a.observe(now, settings)
+		a.updateCondition(now, settings)
+	} else if a.condition != ConditionNormal {
+		a.condition = ConditionNormal
+		a.changedAt = now
+	}
+	return a.applyLocked(now, false, "")
+}
+
+// SetOverride 手动指定策略（优先于配置和自动切换），strategy 为空时清除手动指定，立即生效
+func (a *Autopilot) SetOverride(strategy BalanceStrategy, reason string, now time.Time) BalanceStrategy {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	a.override = strategy
+	return a.applyLocked(now, true, reason)
+}
+
+// Status 返回当前策略、流量状况和最近的切换记录
+func (a *Autopilot) Status() AutopilotStatus {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	config := a.settings()
+	switches := make([]StrategySwitch, len(a.switches))
+	for i := range a.switches {
+		switches[i] = a.switches[len(a.switches)-1-i]
+	}
+	return AutopilotStatus{
+		Strategy:  a.router.Strategy(),
+		Base:      ConfiguredStrategy(config),
+		Override:  a.override,
+		Enabled:   config.Autopilot.Enabled,
+		Condition: a.condition,
+		Signals:   a.signals,
+		Switches:  switches,
+	}
+}
+
+// observe 统计观察窗口内的请求数、平均延迟和速率，以及之前一小时的平均速率
+func (a *Autopilot) observe(now time.Time, settings types.AutopilotConfig) AutopilotSignals {
+	window := time.Duration(settings.WindowMinutes) * time.Minute
+	if window <= 0 {
+		window = defaultAutopilotWindow
+	}
+	windowStart := now.Add(-window)
+
+	signals := AutopilotSignals{EvaluatedAt: now}
+	baseline := 0
+	var latencyMs int64
+	for _, record := range a.recorder.Query(stats.Filter{Since: windowStart.Add(-autopilotBaselineWindow), Until: now}) {
+		if record.Timestamp.Before(windowStart) {
+			baseline++
+			continue
+		}
+		signals.Requests++
+		latencyMs += record.LatencyMs
+	}
+	if signals.Requests > 0 {
+		signals.AvgLatencyMs = float64(latencyMs) / float64(signals.Requests)
+	}
+	signals.RequestsPerMinute = float64(signals.Requests) / window.Minutes()
+	signals.BaselinePerMinute = float64(baseline) / autopilotBaselineWindow.Minutes()
+	return signals
+}
+
+// updateCondition 按观察值更新流量状况（调用方持有锁）。超过阈值时进入异常状况，回落到阈值的
+// autopilotRecoveryRatio 以下才退出；延迟异常优先于流量突增；距上次变化不足 min_hold_seconds 时保持不变
+func (a *Autopilot) updateCondition(now time.Time, settings types.AutopilotConfig) {
+	latencyThreshold := float64(settings.LatencyThresholdMs)
+	if latencyThreshold <= 0 {
+		latencyThreshold = defaultAutopilotLatencyMs
+	}
+	spikeFactor := settings.SpikeFactor
+	if spikeFactor <= 0 {
+		spikeFactor = defaultAutopilotSpikeFactor
+	}
+	minHold := time.Duration(settings.MinHoldSeconds) * time.Second
+	if minHold <= 0 {
+		minHold = defaultAutopilotMinHold
+	}
+
+	// 已处于异常状况时使用较低的退出阈值
+	latencyLimit, spikeLimit := latencyThreshold, spikeFactor
+	switch a.condition {
+	case ConditionLatency:
+		latencyLimit *= autopilotRecoveryRatio
+	case ConditionSpike:
+		spikeLimit *= autopilotRecoveryRatio
+	}
+
+	signals := a.signals
+	next := ConditionNormal
+	if signals.Requests >= autopilotMinRequests {
+		switch {
+		case signals.AvgLatencyMs > latencyLimit:
+			next = ConditionLatency
+		case signals.BaselinePerMinute > 0 && signals.RequestsPerMinute > signals.BaselinePerMinute*spikeLimit:
+			next = ConditionSpike
+		}
+	}
+
+	if next == a.condition || (!a.changedAt.IsZero() && now.Sub(a.changedAt) < minHold) {
+		return
+	}
+	a.condition = next
+	a.changedAt = now
+}
+
+// ConfiguredStrategy 返回 routing.strategy 配置的策略，未配置时为健康优先
+func ConfiguredStrategy(config *types.RoutingConfig) BalanceStrategy {
+	if config.Strategy == "" {
+		return StrategyHealthFirst
+	}
+	return BalanceStrategy(config.Strategy)
+}
+
+// applyLocked 计算应生效的策略，与路由器当前策略不同时切换并记录（调用方持有锁）
+func (a *Autopilot) applyLocked(now time.Time, manual bool, reason string) BalanceStrategy {
+	target := ConfiguredStrategy(a.settings())
+	switch {
+	case a.override != "":
+		target = a.override
+	case a.condition == ConditionLatency:
+		target = StrategyFastest
+	case a.condition == ConditionSpike:
+		target = StrategyLeastConnections
+	}
+
+	current := a.router.Strategy()
+	if target == current {
+		return current
+	}
+	if reason == "" {
+		reason = a.describeLocked()
+	}
+
+	a.router.SetStrategy(target)
+	a.switches = append(a.switches, StrategySwitch{Timestamp: now, From: current, To: target, Reason: reason, Manual: manual})
+	if len(a.switches) > maxStrategySwitches {
+		a.switches = a.switches[len(a.switches)-maxStrategySwitches:]
+	}
+	logger.Warn("负载均衡策略从 %s 切换到 %s: %s", current, target, reason)
+	return target
+}
+
+// describeLocked 自动切换的原因
+func (a *Autopilot) describeLocked() string {
+	signals := a.signals
+	switch {
+	case a.override != "":
+		return "manual override"
+	case a.condition == ConditionLatency:
+		return fmt.Sprintf("latency incident: average latency %.0fms over %d requests", signals.AvgLatencyMs, signals.Requests)
+	case a.condition == ConditionSpike:
+		return fmt.Sprintf("traffic spike: %.1f requests/min against a baseline of %.1f", signals.RequestsPerMinute, signals.BaselinePerMinute)
+	case a.settings().Autopilot.Enabled:
+		return "conditions back to normal"
+	default:
+		return "configured strategy"
+	}
+}
@@ -0,0 +1,74 @@
+package router
+
+import (
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestAutopilot(t *testing.T) {
+	now := time.Date(2024, 6, 3, 12, 0, 0, 0, time.UTC)
+	recorder := stats.NewRecorder(1000)
+	record := func(at time.Time, count int, latencyMs int64) {
+		for i := 0; i < count; i++ {
+			recorder.Record(stats.UsageRecord{Timestamp: at, Success: true, LatencyMs: latencyMs})
+		}
+	}
+	// 平稳的背景流量：每分钟4个请求
+	for minute := -70; minute < 60; minute++ {
+		record(now.Add(time.Duration(minute)*time.Minute), 4, 100)
+	}
+
+	config := &types.RoutingConfig{Autopilot: types.AutopilotConfig{Enabled: true, LatencyThresholdMs: 1000, MinHoldSeconds: 60}}
+	r := &RequestRouter{strategy: StrategyHealthFirst}
+	autopilot := NewAutopilot(r, recorder, func() *types.RoutingConfig { return config })
+
+	// 延迟异常时切换到最快响应
+	record(now.Add(-time.Minute), 20, 5000)
+	if strategy := autopilot.Evaluate(now); strategy != StrategyFastest {
+		t.Fatalf("strategy = %s during latency incident, want fastest", strategy)
+	}
+
+	// 延迟回落但仍高于退出阈值（80%）时保持
+	record(now.Add(9*time.Minute), 20, 1600)
+	if strategy := autopilot.Evaluate(now.Add(10 * time.Minute)); strategy != StrategyFastest {
+		t.Errorf("strategy = %s within recovery band, want fastest", strategy)
+	}
+
+	if strategy := autopilot.Evaluate(now.Add(20 * time.Minute)); strategy != StrategyHealthFirst {
+		t.Errorf("strategy = %s after recovery, want health_first", strategy)
+	}
+
+	// 请求速率超过前一小时平均速率的3倍时切换到最少连接；切换后 min_hold_seconds 内不再切换
+	record(now.Add(39*time.Minute), 60, 100)
+	if strategy := autopilot.Evaluate(now.Add(40 * time.Minute)); strategy != StrategyLeastConnections {
+		t.Fatalf("strategy = %s during traffic spike, want least_connections", strategy)
+	}
+	if strategy := autopilot.Evaluate(now.Add(40*time.Minute + 30*time.Second)); strategy != StrategyLeastConnections {
+		t.Errorf("strategy = %s within min hold, want least_connections", strategy)
+	}
+
+	// 手动指定优先于自动切换，清除后恢复
+	autopilot.SetOverride(StrategyRoundRobin, "maintenance", now.Add(41*time.Minute))
+	if strategy := autopilot.Evaluate(now.Add(42 * time.Minute)); strategy != StrategyRoundRobin {
+		t.Errorf("strategy = %s with override, want round_robin", strategy)
+	}
+	autopilot.SetOverride("", "done", now.Add(42*time.Minute))
+
+	status := autopilot.Status()
+	if status.Strategy != StrategyLeastConnections || status.Condition != ConditionSpike || status.Override != "" {
+		t.Errorf("status = %+v", status)
+	}
+	if len(status.Switches) != 5 || !status.Switches[0].Manual || status.Switches[0].Reason != "done" || status.Switches[4].To != StrategyFastest {
+		t.Errorf("switches = %+v", status.Switches)
+	}
+
+	// 关闭自动切换后使用配置的策略
+	config.Autopilot.Enabled = false
+	config.Strategy = string(StrategyRandom)
+	if strategy := autopilot.Evaluate(now.Add(50 * time.Minute)); strategy != StrategyRandom {
+		t.Errorf("strategy = %s with autopilot disabled, want random", strategy)
+	}
+}
@@ -3,6 +3,7 @@ package router
 import (
 	"fmt"
 	"math/rand"
+	"sort"
 	"strings"
 	"sync"
 	"time"
@@ -18,13 +19,35 @@ const (
 	StrategyRoundRobin  BalanceStrategy = "round_robin"
 	StrategyRandom      BalanceStrategy = "random"
 	StrategyHealthFirst BalanceStrategy = "health_first"
+
+	// StrategyFastest 在健康账号中选择最近响应最快的账号
+	StrategyFastest BalanceStrategy = "fastest"
+	// StrategyLeastConnections 在健康账号中选择进行中请求最少的账号
+	StrategyLeastConnections BalanceStrategy = "least_connections"
 )
 
+// latencySmoothing 最近延迟的指数加权系数，越大越偏向最新的请求
+const latencySmoothing = 0.2
+
+// RoutingRuleSource 模型路由规则来源（由ConfigManager提供，修改后立即生效）
+type RoutingRuleSource interface {
+	ListRoutingRules() []*types.RoutingRule
+}
+
+// LoadSource 上游账号进行中的请求数来源（由代理的并发限制器提供）
+type LoadSource interface {
+	InFlight(id string) int
+}
+
 // RequestRouter 请求路由器
 type RequestRouter struct {
 	upstreamMgr *upstream.UpstreamManager
 	strategy    BalanceStrategy
-	rrIndex     map[types.Provider]int // Round Robin索引
+	rrCurrent   map[string]int     // 平滑加权轮询中每个账号的当前权重
+	latency     map[string]float64 // 每个账号最近成功请求的指数加权平均延迟（毫秒）
+	poolNext    map[string]int     // 每个账号池下一次轮换到的成员序号
+	ruleSource  RoutingRuleSource
+	load        LoadSource
 	mutex       sync.Mutex
 }
 
@@ -33,94 +56,439 @@ func NewRequestRouter(upstreamMgr *upstream.UpstreamManager, strategy BalanceStr
 	return &RequestRouter{
 		upstreamMgr: upstreamMgr,
 		strategy:    strategy,
-		rrIndex:     make(map[types.Provider]int),
+		rrCurrent:   make(map[string]int),
+		latency:     make(map[string]float64),
+		poolNext:    make(map[string]int),
 	}
 }
 
-// SelectUpstream 选择上游账号
-func (r *RequestRouter) SelectUpstream(provider types.Provider) (*types.UpstreamAccount, error) {
+// SelectUpstream 选择上游账号，excludeIDs 中的账号不参与选择（用于失败后切换账号重试）。
+// 只在未归属组织的共用账号中选择
+func (r *RequestRouter) SelectUpstream(provider types.Provider, excludeIDs ...string) (*types.UpstreamAccount, error) {
+	return r.selectAvailable(provider, "", excludeIDs)
+}
+
+// SelectUpstreamForModel 按模型选择上游账号，命中配置了账号池的路由规则时只在账号池中选择
+func (r *RequestRouter) SelectUpstreamForModel(provider types.Provider, model string, excludeIDs ...string) (*types.UpstreamAccount, error) {
+	return r.SelectUpstreamForRule(provider, model, "", r.MatchRule(model), excludeIDs...)
+}
+
+// SelectUpstreamForRule 按已评估出的路由规则为 orgID 组织的Key选择上游账号（只考虑该组织可用的账号），
+// 规则为nil、属于其他提供商或没有账号池时按模型选择
+func (r *RequestRouter) SelectUpstreamForRule(provider types.Provider, model, orgID string, rule *types.RoutingRule, excludeIDs ...string) (*types.UpstreamAccount, error) {
+	if rule == nil || rule.Provider != provider || len(rule.UpstreamIDs) == 0 {
+		if provider == types.ProviderOpenAICompatible {
+			return r.selectServingModel(provider, model, orgID, excludeIDs)
+		}
+		return r.selectAvailable(provider, orgID, excludeIDs)
+	}
+
 	r.mutex.Lock()
 	defer r.mutex.Unlock()
 
-	// 获取活跃的上游账号列表
-	accounts := r.upstreamMgr.ListActiveAccounts(provider)
+	var pool []*types.UpstreamAccount
+	for _, account := range r.upstreamMgr.ListAvailableAccounts(provider, orgID) {
+		if rule.InPool(account.ID) {
+			pool = append(pool, account)
+		}
+	}
+	accounts := excludeAccounts(pool, excludeIDs)
+	if len(accounts) == 0 {
+		return nil, fmt.Errorf("路由规则%s的账号池中没有可用的%s上游账号", rule.ID, provider)
+	}
+
+	return r.pick(accounts)
+}
+
+// selectAvailable 在组织可用的活跃账号中选择
+func (r *RequestRouter) selectAvailable(provider types.Provider, orgID string, excludeIDs []string) (*types.UpstreamAccount, error) {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	accounts := excludeAccounts(r.upstreamMgr.ListAvailableAccounts(provider, orgID), excludeIDs)
+	if len(accounts) == 0 {
+		return nil, fmt.Errorf("没有可用的%s上游账号", provider)
+	}
+
+	return r.pick(accounts)
+}
+
+// selectServingModel 只在后端模型列表包含该模型的账号中选择（各个自托管后端部署的模型不同）；
+// 没有账号发现该模型时（如尚未探测）在所有账号中选择
+func (r *RequestRouter) selectServingModel(provider types.Provider, model, orgID string, excludeIDs []string) (*types.UpstreamAccount, error) {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	active := r.upstreamMgr.ListAvailableAccounts(provider, orgID)
+	var serving []*types.UpstreamAccount
+	for _, account := range active {
+		if account.ServesModel(model) {
+			serving = append(serving, account)
+		}
+	}
+	if len(serving) == 0 {
+		serving = active
+	}
+	accounts := excludeAccounts(serving, excludeIDs)
 	if len(accounts) == 0 {
 		return nil, fmt.Errorf("没有可用的%s上游账号", provider)
 	}
 
+	return r.pick(accounts)
+}
+
+// SetRoutingRuleSource 设置模型路由规则来源
+func (r *RequestRouter) SetRoutingRuleSource(source RoutingRuleSource) {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	r.ruleSource = source
+}
+
+// SetLoadSource 设置进行中请求数来源，未设置时最少连接策略退化为加权轮询
+func (r *RequestRouter) SetLoadSource(source LoadSource) {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	r.load = source
+}
+
+// MatchRule 查找匹配模型的路由规则，没有配置或没有匹配时返回nil
+func (r *RequestRouter) MatchRule(model string) *types.RoutingRule {
+	r.mutex.Lock()
+	source := r.ruleSource
+	r.mutex.Unlock()
+
+	if source == nil {
+		return nil
+	}
+	return types.MatchRoutingRule(source.ListRoutingRules(), model)
+}
+
+// MatchRequest 按请求的模型、Key标签、估算token数和时间评估路由规则。
+// 转发规则的提供商处于过载退避中时，改用第一个不在退避中的备选规则
+func (r *RequestRouter) MatchRequest(ctx *types.RoutingContext) types.RoutingDecision {
+	r.mutex.Lock()
+	source := r.ruleSource
+	r.mutex.Unlock()
+
+	if source == nil {
+		return types.RoutingDecision{}
+	}
+	decision := types.EvaluateRoutingRules(source.ListRoutingRules(), ctx)
+	if decision.Route != nil {
+		overloads := r.upstreamMgr.Overloads()
+		now := time.Now()
+		if _, backingOff := overloads.Until(decision.Route.Provider, now); backingOff {
+			for _, rule := range decision.Fallbacks {
+				if _, fallbackOverloaded := overloads.Until(rule.Provider, now); !fallbackOverloaded {
+					decision.Route = rule
+					break
+				}
+			}
+		}
+	}
+	return decision
+}
+
+// selectByStrategy 按负载均衡策略从候选账号中选择（调用方持有锁）。
+// 只在优先级最高的账号中选择，账号按权重分配流量；选中账号池的成员时改为在池内轮换
+func (r *RequestRouter) selectByStrategy(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
+	selected, err := r.pickByStrategy(accounts)
+	if err != nil {
+		return nil, err
+	}
+	return r.rotatePool(selected, accounts), nil
+}
+
+// pickByStrategy 按负载均衡策略选择单个账号（调用方持有锁）
+func (r *RequestRouter) pickByStrategy(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
 	switch r.strategy {
 	case StrategyRoundRobin:
-		return r.selectRoundRobin(provider, accounts)
+		return r.selectRoundRobin(topPriority(accounts))
 	case StrategyRandom:
-		return r.selectRandom(accounts)
+		return r.selectRandom(topPriority(accounts))
 	case StrategyHealthFirst:
 		return r.selectHealthFirst(accounts)
+	case StrategyFastest:
+		return r.selectFastest(accounts)
+	case StrategyLeastConnections:
+		return r.selectLeastConnections(accounts)
 	default:
-		return r.selectRandom(accounts)
+		return r.selectRandom(topPriority(accounts))
+	}
+}
+
+// rotatePool 策略选中账号池的成员时，在同一账号池、同一优先级的候选账号中依次轮换（调用方持有锁）。
+// 账号池作为一份容量参与策略选择（流量份额为成员权重之和），池内按顺序使用每个Key，把提供商按Key计算的限流分散到所有成员
+func (r *RequestRouter) rotatePool(selected *types.UpstreamAccount, accounts []*types.UpstreamAccount) *types.UpstreamAccount {
+	key := selected.PoolKey()
+	if key == "" {
+		return selected
+	}
+
+	var members []*types.UpstreamAccount
+	for _, account := range accounts {
+		if account.PoolKey() == key && account.Priority == selected.Priority {
+			members = append(members, account)
+		}
+	}
+	if len(members) < 2 {
+		return selected
+	}
+	sort.Slice(members, func(i, j int) bool {
+		return members[i].ID < members[j].ID
+	})
+
+	next := r.poolNext[key] % len(members)
+	r.poolNext[key] = next + 1
+	return members[next]
+}
+
+// withWeight 过滤掉权重为0的账号：权重为0表示不分配流量（用于排空账号），流量交给其他账号或下一个优先级
+func withWeight(accounts []*types.UpstreamAccount) []*types.UpstreamAccount {
+	weighted := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		if account.EffectiveWeight() > 0 {
+			weighted = append(weighted, account)
+		}
+	}
+	return weighted
+}
+
+// topPriority 返回优先级数字最小的一组账号
+func topPriority(accounts []*types.UpstreamAccount) []*types.UpstreamAccount {
+	if len(accounts) == 0 {
+		return accounts
+	}
+	best := accounts[0].Priority
+	for _, account := range accounts[1:] {
+		if account.Priority < best {
+			best = account.Priority
+		}
+	}
+
+	top := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		if account.Priority == best {
+			top = append(top, account)
+		}
 	}
+	return top
 }
 
-// selectRoundRobin 轮询选择
-func (r *RequestRouter) selectRoundRobin(provider types.Provider, accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
-	index := r.rrIndex[provider]
-	if index >= len(accounts) {
-		index = 0
+// excludeAccounts 过滤掉指定ID的账号
+func excludeAccounts(accounts []*types.UpstreamAccount, excludeIDs []string) []*types.UpstreamAccount {
+	if len(excludeIDs) == 0 {
+		return accounts
 	}
 
-	selected := accounts[index]
-	r.rrIndex[provider] = (index + 1) % len(accounts)
+	filtered := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		excluded := false
+		for _, id := range excludeIDs {
+			if account.ID == id {
+				excluded = true
+				break
+			}
+		}
+		if !excluded {
+			filtered = append(filtered, account)
+		}
+	}
+	return filtered
+}
+
+// allowedByBreaker 过滤掉熔断器打开的账号。全部打开时返回 BreakersOpenError 而不是继续把请求发给出错的账号，
+// 由调用方告诉客户端最早什么时候有账号恢复试探
+func (r *RequestRouter) allowedByBreaker(accounts []*types.UpstreamAccount) ([]*types.UpstreamAccount, error) {
+	breakers := r.upstreamMgr.Breakers()
+	now := time.Now()
+
+	allowed := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		if breakers.Allow(account.ID, now) {
+			allowed = append(allowed, account)
+		}
+	}
+	if len(allowed) > 0 {
+		return allowed, nil
+	}
+
+	openErr := &BreakersOpenError{Accounts: len(accounts)}
+	for _, account := range accounts {
+		if at, ok := breakers.HalfOpenAt(account.ID); ok && (openErr.RetryAt.IsZero() || at.Before(openErr.RetryAt)) {
+			openErr.RetryAt = at
+		}
+	}
+	return nil, openErr
+}
+
+// BreakersOpenError 候选账号的熔断器全部打开
+type BreakersOpenError struct {
+	Accounts int       // 候选账号数
+	RetryAt  time.Time // 最早有账号转为半开的时间，全部为手动打开时为零值
+}
+
+func (e *BreakersOpenError) Error() string {
+	return fmt.Sprintf("%d个候选上游账号的熔断器全部打开", e.Accounts)
+}
+
+// pick 依次按权重、熔断器、剩余额度和路由策略从候选账号中选择
+func (r *RequestRouter) pick(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
+	accounts = withWeight(accounts)
+	if len(accounts) == 0 {
+		return nil, fmt.Errorf("候选上游账号的权重都为0")
+	}
+	allowed, err := r.allowedByBreaker(accounts)
+	if err != nil {
+		return nil, err
+	}
+	return r.selectByStrategy(r.withHeadroom(allowed))
+}
+
+// withHeadroom 过滤掉上游报告额度接近耗尽的账号，避免等到429再切换；全部接近耗尽时仍返回所有账号
+func (r *RequestRouter) withHeadroom(accounts []*types.UpstreamAccount) []*types.UpstreamAccount {
+	rateLimits := r.upstreamMgr.RateLimits()
+	now := time.Now()
+
+	available := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		if !rateLimits.Exhausted(account.ID, now) {
+			available = append(available, account)
+		}
+	}
+	if len(available) == 0 {
+		return accounts
+	}
+	return available
+}
+
+// selectRoundRobin 平滑加权轮询：每次给所有账号加上各自的权重，选当前权重最大的账号并减去总权重，
+// 权重相同时退化为普通轮询，权重不同时流量按比例分配且不会连续集中到同一个账号
+func (r *RequestRouter) selectRoundRobin(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
+	if len(accounts) == 0 {
+		return nil, fmt.Errorf("没有可用的上游账号")
+	}
+
+	total := 0
+	var selected *types.UpstreamAccount
+	for _, account := range accounts {
+		weight := account.EffectiveWeight()
+		r.rrCurrent[account.ID] += weight
+		total += weight
+		if selected == nil || r.rrCurrent[account.ID] > r.rrCurrent[selected.ID] {
+			selected = account
+		}
+	}
+	r.rrCurrent[selected.ID] -= total
 
 	return selected, nil
 }
 
-// selectRandom 随机选择
+// selectRandom 按权重随机选择
 func (r *RequestRouter) selectRandom(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
-	index := rand.Intn(len(accounts))
-	return accounts[index], nil
-}
+	if len(accounts) == 0 {
+		return nil, fmt.Errorf("没有可用的上游账号")
+	}
 
-// selectHealthFirst 优先选择健康的账号，在所有可用账号间轮询
-func (r *RequestRouter) selectHealthFirst(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
-	// 过滤出可用的账号（非unhealthy状态）
-	availableAccounts := make([]*types.UpstreamAccount, 0)
-	
+	total := 0
 	for _, account := range accounts {
-		// 接受 healthy, unknown, 空状态，拒绝 unhealthy
-		if account.HealthStatus != "unhealthy" {
-			availableAccounts = append(availableAccounts, account)
+		total += account.EffectiveWeight()
+	}
+	pick := rand.Intn(total)
+	for _, account := range accounts {
+		pick -= account.EffectiveWeight()
+		if pick < 0 {
+			return account, nil
 		}
 	}
+	return accounts[len(accounts)-1], nil
+}
+
+// selectHealthFirst 优先选择健康的账号，在可用账号中优先级最高的一组内加权轮询
+func (r *RequestRouter) selectHealthFirst(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
+	// 过滤出可用的账号（非unhealthy状态），没有可用账号时使用所有账号（包括unhealthy）
+	return r.selectRoundRobin(topPriority(healthyAccounts(accounts)))
+}
 
-	// 如果没有可用账号，使用所有账号（包括unhealthy）
-	if len(availableAccounts) == 0 {
-		availableAccounts = accounts
+// selectFastest 在健康账号中优先级最高的一组内选择最近平均延迟最低的账号；
+// 还没有延迟数据的账号优先（让它们获得样本），全部有数据时选最快的
+func (r *RequestRouter) selectFastest(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
+	candidates := topPriority(healthyAccounts(accounts))
+	if len(candidates) == 0 {
+		return nil, fmt.Errorf("没有可用的上游账号")
 	}
 
-	// 在可用账号中轮询选择
-	if len(availableAccounts) > 0 {
-		provider := availableAccounts[0].Provider
-		index := r.rrIndex[provider]
-		if index >= len(availableAccounts) {
-			index = 0
+	var unmeasured []*types.UpstreamAccount
+	var fastest *types.UpstreamAccount
+	for _, account := range candidates {
+		latency, measured := r.latency[account.ID]
+		if !measured {
+			unmeasured = append(unmeasured, account)
+			continue
+		}
+		if fastest == nil || latency < r.latency[fastest.ID] {
+			fastest = account
 		}
-		selected := availableAccounts[index]
-		r.rrIndex[provider] = (index + 1) % len(availableAccounts)
-		return selected, nil
+	}
+	if len(unmeasured) > 0 {
+		return r.selectRoundRobin(unmeasured)
+	}
+	return fastest, nil
+}
+
+// selectLeastConnections 在健康账号中优先级最高的一组内选择进行中请求最少的账号，并列时加权轮询
+func (r *RequestRouter) selectLeastConnections(accounts []*types.UpstreamAccount) (*types.UpstreamAccount, error) {
+	candidates := topPriority(healthyAccounts(accounts))
+	if r.load == nil || len(candidates) == 0 {
+		return r.selectRoundRobin(candidates)
 	}
 
-	return nil, fmt.Errorf("没有可用的上游账号")
+	least := -1
+	var idle []*types.UpstreamAccount
+	for _, account := range candidates {
+		inFlight := r.load.InFlight(account.ID)
+		switch {
+		case least < 0 || inFlight < least:
+			least = inFlight
+			idle = []*types.UpstreamAccount{account}
+		case inFlight == least:
+			idle = append(idle, account)
+		}
+	}
+	return r.selectRoundRobin(idle)
 }
 
 // MarkUpstreamError 标记上游账号错误
 func (r *RequestRouter) MarkUpstreamError(upstreamID string, err error) {
+	now := time.Now()
+	r.upstreamMgr.Breakers().RecordFailure(upstreamID, err, now)
+	r.upstreamMgr.RecordAuthFailure(upstreamID, err, now)
 	_ = r.upstreamMgr.UpdateAccountHealth(upstreamID, false)
 	_ = r.upstreamMgr.RecordError(upstreamID, err)
 }
 
 // MarkUpstreamSuccess 标记上游账号成功
 func (r *RequestRouter) MarkUpstreamSuccess(upstreamID string, latency time.Duration, tokensUsed int64) {
+	r.upstreamMgr.Breakers().RecordSuccess(upstreamID, time.Now())
+	r.upstreamMgr.RecordAuthSuccess(upstreamID)
 	_ = r.upstreamMgr.UpdateAccountHealth(upstreamID, true)
 	_ = r.upstreamMgr.RecordSuccess(upstreamID, latency, tokensUsed)
+
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	ms := float64(latency.Milliseconds())
+	if previous, exists := r.latency[upstreamID]; exists {
+		ms = previous + latencySmoothing*(ms-previous)
+	}
+	r.latency[upstreamID] = ms
+}
+
+// RecentLatency 返回账号最近成功请求的指数加权平均延迟（毫秒），还没有成功请求时返回false
+func (r *RequestRouter) RecentLatency(upstreamID string) (float64, bool) {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	latency, measured := r.latency[upstreamID]
+	return latency, measured
 }
 
 // GetUpstreamStats 获取上游账号统计信息
@@ -145,10 +513,39 @@ func (r *RequestRouter) SetStrategy(strategy BalanceStrategy) {
 	r.strategy = strategy
 }
 
+// Strategy 返回当前的负载均衡策略
+func (r *RequestRouter) Strategy() BalanceStrategy {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	return r.strategy
+}
+
 // DetermineProvider 根据模型名称确定提供商
 func (r *RequestRouter) DetermineProvider(model string) types.Provider {
+	// 优先使用管理员配置的路由规则
+	if rule := r.MatchRule(model); rule != nil {
+		return rule.Provider
+	}
+
+	// 自托管的 OpenAI 兼容后端发现了该模型时交给它处理（本地模型名可能与云端模型名的前缀规则冲突）
+	for _, account := range r.upstreamMgr.ListActiveAccounts(types.ProviderOpenAICompatible) {
+		if account.ServesModel(model) {
+			return types.ProviderOpenAICompatible
+		}
+	}
+
 	model = strings.ToLower(model)
 
+	// 嵌入模型：text-embedding-v* 为通义千问，text-embedding-00* 和 embedding-* 为 Gemini，其他 text-embedding-* 为 OpenAI
+	switch {
+	case strings.HasPrefix(model, "text-embedding-v"):
+		return types.ProviderQwen
+	case strings.HasPrefix(model, "text-embedding-00"), strings.HasPrefix(model, "embedding-"):
+		return types.ProviderGoogle
+	case strings.HasPrefix(model, "text-embedding"):
+		return types.ProviderOpenAI
+	}
+
 	// 根据模型名称前缀判断提供商
 	if strings.Contains(model, "claude") || strings.Contains(model, "anthropic") {
 		return types.ProviderAnthropic
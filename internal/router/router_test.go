@@ -0,0 +1,301 @@
+package router
+
+import (
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestSelectRoundRobin_Weighted(t *testing.T) {
+	r := &RequestRouter{rrCurrent: make(map[string]int)}
+	heavy := 300
+	accounts := []*types.UpstreamAccount{
+		{ID: "heavy", Weight: &heavy},
+		{ID: "light"}, // 默认权重100
+	}
+
+	counts := make(map[string]int)
+	var sequence []string
+	for i := 0; i < 8; i++ {
+		selected, err := r.selectRoundRobin(accounts)
+		if err != nil {
+			t.Fatalf("selectRoundRobin: %v", err)
+		}
+		counts[selected.ID]++
+		sequence = append(sequence, selected.ID)
+	}
+
+	if counts["heavy"] != 6 || counts["light"] != 2 {
+		t.Errorf("counts = %v, want heavy:6 light:2", counts)
+	}
+	// 平滑加权轮询把轻权重账号穿插在每个周期中，而不是集中在周期末尾
+	want := []string{"heavy", "heavy", "light", "heavy", "heavy", "heavy", "light", "heavy"}
+	for i := range want {
+		if sequence[i] != want[i] {
+			t.Fatalf("sequence = %v, want %v", sequence, want)
+		}
+	}
+}
+
+func TestSelectRandom_Weighted(t *testing.T) {
+	r := &RequestRouter{rrCurrent: make(map[string]int)}
+	heavy := 900
+	accounts := []*types.UpstreamAccount{
+		{ID: "heavy", Weight: &heavy},
+		{ID: "light"},
+	}
+
+	counts := make(map[string]int)
+	for i := 0; i < 2000; i++ {
+		selected, err := r.selectRandom(accounts)
+		if err != nil {
+			t.Fatalf("selectRandom: %v", err)
+		}
+		counts[selected.ID]++
+	}
+	if counts["heavy"] < 1600 || counts["light"] < 100 {
+		t.Errorf("counts = %v, want roughly 9:1", counts)
+	}
+}
+
+func TestPick_ZeroWeightGetsNoTraffic(t *testing.T) {
+	r := &RequestRouter{rrCurrent: make(map[string]int), poolNext: make(map[string]int), upstreamMgr: upstream.NewUpstreamManager(nil)}
+	drained := 0
+	primary := &types.UpstreamAccount{ID: "primary", Weight: &drained, HealthStatus: "healthy"}
+	backup := &types.UpstreamAccount{ID: "backup", Priority: 1, HealthStatus: "healthy"}
+
+	// 权重为0的账号不分配流量，由下一个优先级的账号接收
+	for i := 0; i < 3; i++ {
+		selected, err := r.pick([]*types.UpstreamAccount{primary, backup})
+		if err != nil || selected.ID != "backup" {
+			t.Fatalf("pick() = %v, %v, want backup while primary is drained", selected, err)
+		}
+	}
+	if _, err := r.pick([]*types.UpstreamAccount{primary}); err == nil {
+		t.Error("pick() should fail when every account has weight 0")
+	}
+}
+
+func TestSelectHealthFirst_Priority(t *testing.T) {
+	r := &RequestRouter{rrCurrent: make(map[string]int)}
+	primary := &types.UpstreamAccount{ID: "primary", HealthStatus: "healthy"}
+	backup := &types.UpstreamAccount{ID: "backup", Priority: 1, HealthStatus: "healthy"}
+	accounts := []*types.UpstreamAccount{backup, primary}
+
+	for i := 0; i < 3; i++ {
+		selected, _ := r.selectHealthFirst(accounts)
+		if selected.ID != "primary" {
+			t.Fatalf("selected %s, want primary while it is healthy", selected.ID)
+		}
+	}
+
+	// 高优先级账号不健康时使用低优先级账号
+	primary.HealthStatus = "unhealthy"
+	if selected, _ := r.selectHealthFirst(accounts); selected.ID != "backup" {
+		t.Errorf("selected %s, want backup when primary is unhealthy", selected.ID)
+	}
+
+	// 失败切换时排除高优先级账号后使用低优先级账号
+	primary.HealthStatus = "healthy"
+	if selected, _ := r.selectHealthFirst(excludeAccounts(accounts, []string{"primary"})); selected.ID != "backup" {
+		t.Errorf("selected %s, want backup after primary is excluded", selected.ID)
+	}
+}
+
+type staticLoad map[string]int
+
+func (l staticLoad) InFlight(id string) int {
+	return l[id]
+}
+
+func TestSelectFastestAndLeastConnections(t *testing.T) {
+	r := &RequestRouter{rrCurrent: make(map[string]int), latency: make(map[string]float64)}
+	a := &types.UpstreamAccount{ID: "a", HealthStatus: "healthy"}
+	b := &types.UpstreamAccount{ID: "b", HealthStatus: "healthy"}
+	accounts := []*types.UpstreamAccount{a, b}
+
+	// 没有延迟数据的账号先获得样本
+	r.latency["a"] = 300
+	if selected, _ := r.selectFastest(accounts); selected.ID != "b" {
+		t.Errorf("selected %s, want unmeasured account b", selected.ID)
+	}
+	r.latency["b"] = 800
+	if selected, _ := r.selectFastest(accounts); selected.ID != "a" {
+		t.Errorf("selected %s, want faster account a", selected.ID)
+	}
+	b.HealthStatus = "unhealthy"
+	r.latency["b"] = 100
+	if selected, _ := r.selectFastest(accounts); selected.ID != "a" {
+		t.Errorf("selected %s, want healthy account a", selected.ID)
+	}
+
+	b.HealthStatus = "healthy"
+	r.load = staticLoad{"a": 5, "b": 2}
+	for i := 0; i < 3; i++ {
+		if selected, _ := r.selectLeastConnections(accounts); selected.ID != "b" {
+			t.Fatalf("selected %s, want least loaded account b", selected.ID)
+		}
+	}
+}
+
+func TestSelectByStrategy_PoolRotation(t *testing.T) {
+	r := &RequestRouter{strategy: StrategyLeastConnections, rrCurrent: make(map[string]int), poolNext: make(map[string]int)}
+	accounts := []*types.UpstreamAccount{
+		{ID: "key-b", Provider: types.ProviderAnthropic, Pool: "acme", HealthStatus: "healthy"},
+		{ID: "key-a", Provider: types.ProviderAnthropic, Pool: "acme", HealthStatus: "healthy"},
+		{ID: "solo", Provider: types.ProviderAnthropic, HealthStatus: "healthy"},
+	}
+
+	// 策略在空闲的账号池成员中选择，实际使用的Key按ID顺序依次轮换
+	r.load = staticLoad{"solo": 3}
+	var sequence []string
+	for i := 0; i < 4; i++ {
+		selected, err := r.selectByStrategy(accounts)
+		if err != nil {
+			t.Fatalf("selectByStrategy: %v", err)
+		}
+		sequence = append(sequence, selected.ID)
+	}
+	want := []string{"key-a", "key-b", "key-a", "key-b"}
+	for i := range want {
+		if sequence[i] != want[i] {
+			t.Fatalf("sequence = %v, want %v", sequence, want)
+		}
+	}
+
+	// 不在账号池中的账号不轮换；同名但属于其他提供商的账号不是同一个账号池
+	r.load = staticLoad{"key-a": 3, "key-b": 3}
+	accounts = append(accounts, &types.UpstreamAccount{ID: "other", Provider: types.ProviderOpenAI, Pool: "acme", HealthStatus: "healthy"})
+	for i := 0; i < 2; i++ {
+		if selected, _ := r.selectByStrategy(accounts); selected.ID != "solo" && selected.ID != "other" {
+			t.Errorf("selected %s, want an idle account outside the anthropic pool", selected.ID)
+		}
+	}
+}
+
+type staticRuleSource []*types.RoutingRule
+
+func (s staticRuleSource) ListRoutingRules() []*types.RoutingRule {
+	return s
+}
+
+func TestMatchRequest(t *testing.T) {
+	hasTools := true
+	rules := staticRuleSource{
+		{ID: "fallback", Pattern: "*", Provider: types.ProviderOpenAI, Priority: 100, Enabled: true},
+		{ID: "batch-night", Pattern: "claude-*", KeyTags: []string{"batch"}, TimeOfDay: "22:00-06:00", Provider: types.ProviderBedrock, QueuePriority: types.PriorityLow, Priority: 10, Enabled: true},
+		{ID: "huge", Pattern: "*", MinInputTokens: 100000, Action: types.RoutingActionDeny, Priority: 1, Enabled: true},
+		{ID: "vip", Pattern: "*", KeyTags: []string{"vip"}, QueuePriority: types.PriorityCritical, Priority: 5, Enabled: true},
+		{ID: "disabled", Pattern: "*", Action: types.RoutingActionDeny, Enabled: false},
+		{ID: "tools", Pattern: "gpt-*", HasTools: &hasTools, Provider: types.ProviderAnthropic, Priority: 50, Enabled: true},
+	}
+	r := &RequestRouter{ruleSource: rules, upstreamMgr: upstream.NewUpstreamManager(nil)}
+	night := time.Date(2024, 5, 1, 23, 30, 0, 0, time.UTC)
+	day := time.Date(2024, 5, 1, 12, 0, 0, 0, time.UTC)
+
+	tests := []struct {
+		name         string
+		ctx          types.RoutingContext
+		wantDeny     string
+		wantRoute    string
+		wantPriority string
+	}{
+		{
+			name:      "model only",
+			ctx:       types.RoutingContext{Model: "claude-3-5-sonnet", Time: night},
+			wantRoute: "fallback",
+		},
+		{
+			name:         "tag and time window across midnight",
+			ctx:          types.RoutingContext{Model: "claude-3-5-sonnet", KeyTags: []string{"batch"}, Time: night},
+			wantRoute:    "batch-night",
+			wantPriority: "batch-night",
+		},
+		{
+			name:      "outside time window",
+			ctx:       types.RoutingContext{Model: "claude-3-5-sonnet", KeyTags: []string{"batch"}, Time: day},
+			wantRoute: "fallback",
+		},
+		{
+			name:         "priority and route from different rules",
+			ctx:          types.RoutingContext{Model: "gpt-4o", KeyTags: []string{"vip"}, Time: day},
+			wantRoute:    "fallback",
+			wantPriority: "vip",
+		},
+		{
+			name:     "deny stops evaluation",
+			ctx:      types.RoutingContext{Model: "gpt-4o", KeyTags: []string{"vip"}, InputTokens: 200000, Time: day},
+			wantDeny: "huge",
+		},
+		{
+			name:      "tool requests",
+			ctx:       types.RoutingContext{Model: "gpt-4o", HasTools: true, Time: day},
+			wantRoute: "tools",
+		},
+	}
+	ruleID := func(rule *types.RoutingRule) string {
+		if rule == nil {
+			return ""
+		}
+		return rule.ID
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			decision := r.MatchRequest(&tt.ctx)
+			if got := ruleID(decision.Deny); got != tt.wantDeny {
+				t.Errorf("Deny = %q, want %q", got, tt.wantDeny)
+			}
+			if got := ruleID(decision.Route); got != tt.wantRoute {
+				t.Errorf("Route = %q, want %q", got, tt.wantRoute)
+			}
+			if got := ruleID(decision.Priority); got != tt.wantPriority {
+				t.Errorf("Priority = %q, want %q", got, tt.wantPriority)
+			}
+		})
+	}
+
+	// 带请求条件的规则不参与只按模型名的匹配
+	if rule := r.MatchRule("claude-3-5-sonnet"); rule == nil || rule.ID != "fallback" {
+		t.Errorf("MatchRule() = %v, want fallback", rule)
+	}
+
+	// 转发规则的提供商过载退避期间改用其他提供商的备选规则
+	overloads := r.upstreamMgr.Overloads()
+	overloads.Configure(&types.OverloadBackoffConfig{Enabled: true})
+	overloads.Record(types.ProviderAnthropic, 0, time.Now())
+	toolsCtx := types.RoutingContext{Model: "gpt-4o", HasTools: true, Time: day}
+	if decision := r.MatchRequest(&toolsCtx); ruleID(decision.Route) != "fallback" {
+		t.Errorf("Route during overload = %q, want fallback", ruleID(decision.Route))
+	}
+	overloads.Record(types.ProviderOpenAI, 0, time.Now())
+	if decision := r.MatchRequest(&toolsCtx); ruleID(decision.Route) != "tools" {
+		t.Errorf("Route with every provider overloaded = %q, want tools", ruleID(decision.Route))
+	}
+}
+
+func TestRoutingRuleValidate(t *testing.T) {
+	tests := []struct {
+		name    string
+		rule    types.RoutingRule
+		wantErr bool
+	}{
+		{"route", types.RoutingRule{Pattern: "gpt-*", Provider: types.ProviderOpenAI}, false},
+		{"priority only", types.RoutingRule{Pattern: "*", QueuePriority: types.PriorityHigh}, false},
+		{"deny", types.RoutingRule{Pattern: "*", Action: types.RoutingActionDeny, TimeOfDay: "09:00-18:00", Timezone: "UTC"}, false},
+		{"no action", types.RoutingRule{Pattern: "*"}, true},
+		{"unknown action", types.RoutingRule{Pattern: "*", Action: "drop"}, true},
+		{"bad time", types.RoutingRule{Pattern: "*", Provider: types.ProviderOpenAI, TimeOfDay: "9-18"}, true},
+		{"bad timezone", types.RoutingRule{Pattern: "*", Provider: types.ProviderOpenAI, TimeOfDay: "09:00-18:00", Timezone: "Mars/Base"}, true},
+		{"token range", types.RoutingRule{Pattern: "*", Provider: types.ProviderOpenAI, MinInputTokens: 10, MaxInputTokens: 5}, true},
+		{"bad priority", types.RoutingRule{Pattern: "*", QueuePriority: "urgent"}, true},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if err := tt.rule.Validate(); (err != nil) != tt.wantErr {
+				t.Errorf("Validate() error = %v, wantErr %v", err, tt.wantErr)
+			}
+		})
+	}
+}
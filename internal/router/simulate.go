@@ -0,0 +1,289 @@
+package router
+
+import (
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// SimulationScenario 模拟评估的一组路由配置
+type SimulationScenario struct {
+	Name     string             `json:"name"`
+	Strategy BalanceStrategy    `json:"strategy,omitempty"` // 为空时使用当前策略
+	Weights  map[string]float64 `json:"weights,omitempty"`  // 账号ID -> 流量权重，设置后未列出的账号不分配流量；为空时使用账号当前的权重和优先级
+	Fallback []string           `json:"fallback,omitempty"` // 选中的账号失败后依次尝试的账号（只使用同一提供商的账号）
+}
+
+// SimulationOutcome 一组请求在某个路由配置下的费用、延迟和失败率
+type SimulationOutcome struct {
+	Name         string  `json:"name"`
+	Requests     int     `json:"requests"`
+	CostUSD      float64 `json:"cost_usd"`
+	AvgLatencyMs float64 `json:"avg_latency_ms"`
+	FailureRate  float64 `json:"failure_rate"`
+	Unroutable   int     `json:"unroutable,omitempty"` // 没有可分配账号的请求，按失败计
+
+	// 相对于历史实际结果的变化
+	CostDeltaUSD     float64 `json:"cost_delta_usd"`
+	LatencyDeltaMs   float64 `json:"latency_delta_ms"`
+	FailureRateDelta float64 `json:"failure_rate_delta"`
+}
+
+// SimulationReport 路由模拟结果：样本的历史实际结果和每个场景的预估结果
+type SimulationReport struct {
+	Sampled   int                  `json:"sampled"`
+	Baseline  *SimulationOutcome   `json:"baseline"`
+	Scenarios []*SimulationOutcome `json:"scenarios"`
+}
+
+// accountProfile 从历史记录得到的请求数、失败数和总延迟
+type accountProfile struct {
+	requests  int
+	failures  int
+	latencyMs int64
+}
+
+// add 累计一条记录
+func (p *accountProfile) add(record *stats.UsageRecord) {
+	p.requests++
+	if !record.Success {
+		p.failures++
+	}
+	p.latencyMs += record.LatencyMs
+}
+
+// failureRate 失败率，没有记录时为0
+func (p *accountProfile) failureRate() float64 {
+	if p.requests == 0 {
+		return 0
+	}
+	return float64(p.failures) / float64(p.requests)
+}
+
+// avgLatencyMs 平均延迟，没有记录时为0
+func (p *accountProfile) avgLatencyMs() float64 {
+	if p.requests == 0 {
+		return 0
+	}
+	return float64(p.latencyMs) / float64(p.requests)
+}
+
+// Simulation 离线路由模拟：用历史记录估计每个账号的失败率和平均延迟，再按候选路由配置重新分配样本请求，
+// 以期望值计算费用、延迟和失败率。结果只是估算：假设账号的表现与历史一致，且失败的请求不产生费用。
+// 轮询和随机策略的长期流量分布相同（都按账号权重分配），健康优先策略不向当前不健康的账号分配流量；
+// 最快响应和最少连接策略取决于运行时的延迟和并发，按健康优先估算
+type Simulation struct {
+	profiles   map[string]*accountProfile         // 账号ID -> 历史表现
+	byProvider map[types.Provider]*accountProfile // 没有历史记录的账号使用所属提供商的整体表现
+	overall    accountProfile
+	modelCost  map[string]float64 // 提供商/模型 -> 成功请求的平均费用，用于估算历史上失败的请求
+	accounts   map[string]*types.UpstreamAccount
+	candidates func(provider types.Provider, model string) []*types.UpstreamAccount
+	strategy   BalanceStrategy
+}
+
+// NewSimulation 创建路由模拟。history 用于估计账号表现，accounts 为可作为备用账号的活跃账号，
+// candidates 返回请求当前可路由到的账号，strategy 为场景未指定策略时使用的当前策略
+func NewSimulation(history []stats.UsageRecord, accounts []*types.UpstreamAccount, candidates func(provider types.Provider, model string) []*types.UpstreamAccount, strategy BalanceStrategy) *Simulation {
+	s := &Simulation{
+		profiles:   make(map[string]*accountProfile),
+		byProvider: make(map[types.Provider]*accountProfile),
+		modelCost:  make(map[string]float64),
+		accounts:   make(map[string]*types.UpstreamAccount, len(accounts)),
+		candidates: candidates,
+		strategy:   strategy,
+	}
+	for _, account := range accounts {
+		s.accounts[account.ID] = account
+	}
+
+	successes := make(map[string]int)
+	for i := range history {
+		record := &history[i]
+		if record.UpstreamID == "" {
+			continue
+		}
+		if s.profiles[record.UpstreamID] == nil {
+			s.profiles[record.UpstreamID] = &accountProfile{}
+		}
+		if s.byProvider[record.Provider] == nil {
+			s.byProvider[record.Provider] = &accountProfile{}
+		}
+		s.profiles[record.UpstreamID].add(record)
+		s.byProvider[record.Provider].add(record)
+		s.overall.add(record)
+
+		if record.Success {
+			key := costKey(record)
+			s.modelCost[key] += record.CostUSD
+			successes[key]++
+		}
+	}
+	for key, count := range successes {
+		s.modelCost[key] /= float64(count)
+	}
+	return s
+}
+
+// Run 计算样本的历史实际结果和每个场景的预估结果
+func (s *Simulation) Run(sample []stats.UsageRecord, scenarios []SimulationScenario) *SimulationReport {
+	baseline := &SimulationOutcome{Name: "baseline", Requests: len(sample)}
+	failures := 0
+	var latency int64
+	for i := range sample {
+		baseline.CostUSD += sample[i].CostUSD
+		latency += sample[i].LatencyMs
+		if !sample[i].Success {
+			failures++
+		}
+	}
+	if len(sample) > 0 {
+		baseline.AvgLatencyMs = float64(latency) / float64(len(sample))
+		baseline.FailureRate = float64(failures) / float64(len(sample))
+	}
+
+	report := &SimulationReport{
+		Sampled:   len(sample),
+		Baseline:  baseline,
+		Scenarios: make([]*SimulationOutcome, 0, len(scenarios)),
+	}
+	for _, scenario := range scenarios {
+		outcome := s.runScenario(sample, scenario)
+		outcome.CostDeltaUSD = outcome.CostUSD - baseline.CostUSD
+		outcome.LatencyDeltaMs = outcome.AvgLatencyMs - baseline.AvgLatencyMs
+		outcome.FailureRateDelta = outcome.FailureRate - baseline.FailureRate
+		report.Scenarios = append(report.Scenarios, outcome)
+	}
+	return report
+}
+
+// runScenario 按场景的流量分配计算每个请求的期望失败率、延迟和费用
+func (s *Simulation) runScenario(sample []stats.UsageRecord, scenario SimulationScenario) *SimulationOutcome {
+	strategy := scenario.Strategy
+	if strategy == "" {
+		strategy = s.strategy
+	}
+
+	outcome := &SimulationOutcome{Name: scenario.Name, Requests: len(sample)}
+	var failures, latency float64
+	routed := 0
+	for i := range sample {
+		record := &sample[i]
+		candidates := s.candidates(record.Provider, record.Model)
+		if strategy == StrategyHealthFirst || strategy == StrategyFastest || strategy == StrategyLeastConnections {
+			candidates = healthyAccounts(candidates)
+		}
+		if len(scenario.Weights) == 0 {
+			candidates = topPriority(withWeight(candidates))
+		}
+
+		weights := make([]float64, len(candidates))
+		total := 0.0
+		for j, account := range candidates {
+			weights[j] = float64(account.EffectiveWeight())
+			if len(scenario.Weights) > 0 {
+				weights[j] = scenario.Weights[account.ID]
+			}
+			if weights[j] > 0 {
+				total += weights[j]
+			}
+		}
+		if total == 0 {
+			outcome.Unroutable++
+			failures++
+			continue
+		}
+
+		// 每个候选账号按权重分得的流量，失败后沿备用账号链继续，直到成功或链结束
+		var failure, requestLatency float64
+		for j, account := range candidates {
+			if weights[j] <= 0 {
+				continue
+			}
+			share := weights[j] / total
+			reach := 1.0
+			var chainLatency float64
+			for _, id := range s.chain(account, scenario.Fallback) {
+				profile := s.profile(id, record.Provider)
+				chainLatency += reach * profile.avgLatencyMs()
+				reach *= profile.failureRate()
+			}
+			failure += share * reach
+			requestLatency += share * chainLatency
+		}
+
+		routed++
+		failures += failure
+		latency += requestLatency
+		outcome.CostUSD += (1 - failure) * s.requestCost(record)
+	}
+
+	if len(sample) > 0 {
+		outcome.FailureRate = failures / float64(len(sample))
+	}
+	if routed > 0 {
+		outcome.AvgLatencyMs = latency / float64(routed)
+	}
+	return outcome
+}
+
+// chain 返回选中账号及其备用账号的尝试顺序，跳过重复、不存在或属于其他提供商的备用账号
+func (s *Simulation) chain(primary *types.UpstreamAccount, fallback []string) []string {
+	chain := []string{primary.ID}
+	seen := map[string]bool{primary.ID: true}
+	for _, id := range fallback {
+		account, exists := s.accounts[id]
+		if !exists || seen[id] || account.Provider != primary.Provider {
+			continue
+		}
+		seen[id] = true
+		chain = append(chain, id)
+	}
+	return chain
+}
+
+// profile 返回账号的历史表现，没有记录时使用提供商或整体的表现
+func (s *Simulation) profile(upstreamID string, provider types.Provider) *accountProfile {
+	if profile := s.profiles[upstreamID]; profile != nil {
+		return profile
+	}
+	if profile := s.byProvider[provider]; profile != nil {
+		return profile
+	}
+	return &s.overall
+}
+
+// requestCost 请求成功时的费用：成功的记录使用实际费用，失败的记录使用同一模型成功请求的平均费用
+func (s *Simulation) requestCost(record *stats.UsageRecord) float64 {
+	if record.Success {
+		return record.CostUSD
+	}
+	return s.modelCost[costKey(record)]
+}
+
+// costKey 按提供商和模型区分请求费用
+func costKey(record *stats.UsageRecord) string {
+	return string(record.Provider) + "/" + record.Model
+}
+
+// healthyAccounts 过滤掉不健康的账号，全部不健康时返回所有账号（与 selectHealthFirst 一致）
+func healthyAccounts(accounts []*types.UpstreamAccount) []*types.UpstreamAccount {
+	healthy := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		if account.HealthStatus != "unhealthy" {
+			healthy = append(healthy, account)
+		}
+	}
+	if len(healthy) == 0 {
+		return accounts
+	}
+	return healthy
+}
+
+// ValidStrategy 检查负载均衡策略名称
+func ValidStrategy(strategy BalanceStrategy) bool {
+	switch strategy {
+	case StrategyRoundRobin, StrategyRandom, StrategyHealthFirst, StrategyFastest, StrategyLeastConnections:
+		return true
+	}
+	return false
+}
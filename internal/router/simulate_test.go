@@ -0,0 +1,69 @@
+package router
+
+import (
+	"math"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func almostEqual(a, b float64) bool {
+	return math.Abs(a-b) < 1e-9
+}
+
+func TestSimulation(t *testing.T) {
+	fast := &types.UpstreamAccount{ID: "fast", Provider: types.ProviderAnthropic, HealthStatus: "healthy"}
+	flaky := &types.UpstreamAccount{ID: "flaky", Provider: types.ProviderAnthropic, HealthStatus: "unhealthy"}
+	accounts := []*types.UpstreamAccount{fast, flaky}
+	candidates := func(provider types.Provider, model string) []*types.UpstreamAccount {
+		return accounts
+	}
+
+	// fast: 从不失败，延迟100ms；flaky: 一半失败，延迟300ms
+	var history []stats.UsageRecord
+	for i := 0; i < 4; i++ {
+		history = append(history, stats.UsageRecord{UpstreamID: "fast", Provider: types.ProviderAnthropic, Model: "m", Success: true, LatencyMs: 100, CostUSD: 1})
+		history = append(history, stats.UsageRecord{UpstreamID: "flaky", Provider: types.ProviderAnthropic, Model: "m", Success: i%2 == 0, LatencyMs: 300, CostUSD: 1})
+	}
+
+	simulation := NewSimulation(history, accounts, candidates, StrategyRoundRobin)
+	report := simulation.Run(history, []SimulationScenario{
+		{Name: "even"},
+		{Name: "fast-only", Weights: map[string]float64{"fast": 1}},
+		{Name: "flaky-with-fallback", Weights: map[string]float64{"flaky": 1}, Fallback: []string{"fast"}},
+		{Name: "health-first", Strategy: StrategyHealthFirst},
+		{Name: "nowhere", Weights: map[string]float64{"missing": 1}},
+	})
+
+	if report.Sampled != 8 || !almostEqual(report.Baseline.FailureRate, 0.25) || !almostEqual(report.Baseline.CostUSD, 6) {
+		t.Fatalf("baseline = %+v", report.Baseline)
+	}
+
+	even := report.Scenarios[0]
+	if !almostEqual(even.FailureRate, 0.25) || !almostEqual(even.AvgLatencyMs, 200) || !almostEqual(even.CostUSD, 6) {
+		t.Errorf("even = %+v, want the same as history", even)
+	}
+
+	fastOnly := report.Scenarios[1]
+	if fastOnly.FailureRate != 0 || !almostEqual(fastOnly.AvgLatencyMs, 100) || !almostEqual(fastOnly.CostUSD, 8) {
+		t.Errorf("fast-only = %+v", fastOnly)
+	}
+	if !almostEqual(fastOnly.FailureRateDelta, -0.25) || !almostEqual(fastOnly.CostDeltaUSD, 2) || !almostEqual(fastOnly.LatencyDeltaMs, -100) {
+		t.Errorf("fast-only deltas = %+v", fastOnly)
+	}
+
+	// flaky失败的一半请求切换到fast：延迟 300 + 0.5*100
+	fallback := report.Scenarios[2]
+	if fallback.FailureRate != 0 || !almostEqual(fallback.AvgLatencyMs, 350) {
+		t.Errorf("flaky-with-fallback = %+v", fallback)
+	}
+
+	if healthFirst := report.Scenarios[3]; healthFirst.FailureRate != 0 || !almostEqual(healthFirst.AvgLatencyMs, 100) {
+		t.Errorf("health-first = %+v, want unhealthy account skipped", healthFirst)
+	}
+
+	if nowhere := report.Scenarios[4]; nowhere.Unroutable != 8 || nowhere.FailureRate != 1 || nowhere.CostUSD != 0 {
+		t.Errorf("nowhere = %+v, want every request unroutable", nowhere)
+	}
+}
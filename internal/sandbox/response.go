@@ -0,0 +1,249 @@
+package sandbox
+
+import (
+	"crypto/sha256"
+	"encoding/binary"
+	"encoding/json"
+	"fmt"
+	"io"
+	"math"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/tokens"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// defaultDimensions 请求没有指定维度时模拟嵌入向量的维度
+const defaultDimensions = 64
+
+// Complete 等待模拟延迟后返回上游格式的非流式响应
+func (r *Responder) Complete(request *types.UnifiedRequest, provider types.Provider, format converter.Format) ([]byte, error) {
+	time.Sleep(r.latency())
+	return json.Marshal(responseBody(complete(request, provider), format))
+}
+
+// Stream 返回上游格式的SSE流：等待模拟延迟后输出首个事件，之后按间隔逐词输出。
+// 调用方关闭返回的流时停止输出
+func (r *Responder) Stream(request *types.UnifiedRequest, provider types.Provider, format converter.Format) io.ReadCloser {
+	events := streamEvents(complete(request, provider), format)
+	reader, writer := io.Pipe()
+	go func() {
+		time.Sleep(r.latency())
+		for i, event := range events {
+			if i > 0 {
+				time.Sleep(r.chunkInterval())
+			}
+			if _, err := writer.Write(event); err != nil {
+				return
+			}
+		}
+		_ = writer.Close()
+	}()
+	return reader
+}
+
+// Embeddings 等待模拟延迟后返回上游格式的嵌入响应，向量由输入文本的哈希生成（相同文本得到相同向量）
+func (r *Responder) Embeddings(model string, inputs []string, dimensions int, provider types.Provider) ([]byte, error) {
+	time.Sleep(r.latency())
+	if dimensions <= 0 {
+		dimensions = defaultDimensions
+	}
+
+	counter := tokens.ForProvider(provider)
+	vectors := make([][]float64, len(inputs))
+	promptTokens := 0
+	for i, input := range inputs {
+		vectors[i] = embedding(input, dimensions)
+		promptTokens += counter.Count(input)
+	}
+
+	// Gemini 单个输入使用 embedContent，多个输入使用 batchEmbedContents
+	if provider == types.ProviderGoogle {
+		if len(vectors) == 1 {
+			return json.Marshal(map[string]interface{}{"embedding": map[string]interface{}{"values": vectors[0]}})
+		}
+		embeddings := make([]map[string]interface{}, len(vectors))
+		for i, values := range vectors {
+			embeddings[i] = map[string]interface{}{"values": values}
+		}
+		return json.Marshal(map[string]interface{}{"embeddings": embeddings})
+	}
+
+	data := make([]map[string]interface{}, len(vectors))
+	for i, values := range vectors {
+		data[i] = map[string]interface{}{"object": "embedding", "index": i, "embedding": values}
+	}
+	return json.Marshal(map[string]interface{}{
+		"object": "list",
+		"data":   data,
+		"model":  model,
+		"usage":  map[string]int{"prompt_tokens": promptTokens, "total_tokens": promptTokens},
+	})
+}
+
+// embedding 由文本的SHA-256哈希扩展出指定维度的单位向量
+func embedding(text string, dimensions int) []float64 {
+	seed := sha256.Sum256([]byte(text))
+	values := make([]float64, dimensions)
+	norm := 0.0
+	var block [sha256.Size]byte
+	for i := range values {
+		// 每个哈希块提供8个维度
+		if i%8 == 0 {
+			var counter [4]byte
+			binary.BigEndian.PutUint32(counter[:], uint32(i/8))
+			block = sha256.Sum256(append(seed[:], counter[:]...))
+		}
+		n := binary.BigEndian.Uint32(block[(i%8)*4:])
+		values[i] = float64(n)/math.MaxUint32*2 - 1
+		norm += values[i] * values[i]
+	}
+	if norm = math.Sqrt(norm); norm > 0 {
+		for i := range values {
+			values[i] /= norm
+		}
+	}
+	return values
+}
+
+// responseBody 构建上游格式的非流式响应
+func responseBody(c *completion, format converter.Format) interface{} {
+	switch format {
+	case converter.FormatAnthropic:
+		return map[string]interface{}{
+			"id":            "msg_" + c.ID,
+			"type":          "message",
+			"role":          "assistant",
+			"model":         c.Model,
+			"content":       []map[string]interface{}{{"type": "text", "text": c.Text}},
+			"stop_reason":   c.finishReason(format),
+			"stop_sequence": nil,
+			"usage":         map[string]int{"input_tokens": c.InputTokens, "output_tokens": c.OutputTokens},
+		}
+	case converter.FormatGemini:
+		return geminiChunk(c, c.Text, true)
+	default:
+		return map[string]interface{}{
+			"id":      "chatcmpl-" + c.ID,
+			"object":  "chat.completion",
+			"created": time.Now().Unix(),
+			"model":   c.Model,
+			"choices": []map[string]interface{}{{
+				"index":         0,
+				"message":       map[string]interface{}{"role": "assistant", "content": c.Text},
+				"finish_reason": c.finishReason(format),
+			}},
+			"usage": openAIUsage(c),
+		}
+	}
+}
+
+// streamEvents 构建上游格式的SSE事件，文本按单词拆分为多个增量事件
+func streamEvents(c *completion, format converter.Format) [][]byte {
+	words := strings.SplitAfter(c.Text, " ")
+	var events [][]byte
+	switch format {
+	case converter.FormatAnthropic:
+		events = append(events,
+			sseEvent("message_start", map[string]interface{}{"type": "message_start", "message": map[string]interface{}{
+				"id":            "msg_" + c.ID,
+				"type":          "message",
+				"role":          "assistant",
+				"model":         c.Model,
+				"content":       []interface{}{},
+				"stop_reason":   nil,
+				"stop_sequence": nil,
+				"usage":         map[string]int{"input_tokens": c.InputTokens, "output_tokens": 0},
+			}}),
+			sseEvent("content_block_start", map[string]interface{}{"type": "content_block_start", "index": 0, "content_block": map[string]interface{}{"type": "text", "text": ""}}),
+		)
+		for _, word := range words {
+			events = append(events, sseEvent("content_block_delta", map[string]interface{}{"type": "content_block_delta", "index": 0, "delta": map[string]interface{}{"type": "text_delta", "text": word}}))
+		}
+		events = append(events,
+			sseEvent("content_block_stop", map[string]interface{}{"type": "content_block_stop", "index": 0}),
+			sseEvent("message_delta", map[string]interface{}{
+				"type":  "message_delta",
+				"delta": map[string]interface{}{"stop_reason": c.finishReason(format), "stop_sequence": nil},
+				"usage": map[string]int{"output_tokens": c.OutputTokens},
+			}),
+			sseEvent("message_stop", map[string]interface{}{"type": "message_stop"}),
+		)
+	case converter.FormatGemini:
+		// Gemini 的流式响应没有结束标记，最后一个分块携带结束原因和用量
+		for i, word := range words {
+			events = append(events, sseEvent("", geminiChunk(c, word, i == len(words)-1)))
+		}
+	default:
+		events = append(events, sseEvent("", openAIChunk(c, map[string]interface{}{"role": "assistant", "content": ""}, nil)))
+		for _, word := range words {
+			events = append(events, sseEvent("", openAIChunk(c, map[string]interface{}{"content": word}, nil)))
+		}
+		events = append(events, sseEvent("", openAIChunk(c, map[string]interface{}{}, c.finishReason(format))))
+		// 与 stream_options.include_usage 相同，最后一个分块只携带用量
+		events = append(events,
+			sseEvent("", map[string]interface{}{
+				"id":      "chatcmpl-" + c.ID,
+				"object":  "chat.completion.chunk",
+				"created": time.Now().Unix(),
+				"model":   c.Model,
+				"choices": []interface{}{},
+				"usage":   openAIUsage(c),
+			}),
+			[]byte("data: [DONE]\n\n"),
+		)
+	}
+	return events
+}
+
+// openAIChunk 构建 OpenAI 格式的流式分块，finishReason 为nil时输出 null
+func openAIChunk(c *completion, delta map[string]interface{}, finishReason interface{}) map[string]interface{} {
+	return map[string]interface{}{
+		"id":      "chatcmpl-" + c.ID,
+		"object":  "chat.completion.chunk",
+		"created": time.Now().Unix(),
+		"model":   c.Model,
+		"choices": []map[string]interface{}{{"index": 0, "delta": delta, "finish_reason": finishReason}},
+	}
+}
+
+func openAIUsage(c *completion) map[string]int {
+	return map[string]int{
+		"prompt_tokens":     c.InputTokens,
+		"completion_tokens": c.OutputTokens,
+		"total_tokens":      c.InputTokens + c.OutputTokens,
+	}
+}
+
+// geminiChunk 构建 Gemini 格式的响应（流式分块与非流式响应结构相同），last 为true时携带结束原因和用量
+func geminiChunk(c *completion, text string, last bool) map[string]interface{} {
+	candidate := map[string]interface{}{
+		"content": map[string]interface{}{"role": "model", "parts": []map[string]interface{}{{"text": text}}},
+		"index":   0,
+	}
+	chunk := map[string]interface{}{
+		"candidates":   []map[string]interface{}{candidate},
+		"modelVersion": c.Model,
+		"responseId":   c.ID,
+	}
+	if last {
+		candidate["finishReason"] = c.finishReason(converter.FormatGemini)
+		chunk["usageMetadata"] = map[string]int{
+			"promptTokenCount":     c.InputTokens,
+			"candidatesTokenCount": c.OutputTokens,
+			"totalTokenCount":      c.InputTokens + c.OutputTokens,
+		}
+	}
+	return chunk
+}
+
+// sseEvent 编码一个SSE事件，eventType 为空时只输出 data 行
+func sseEvent(eventType string, data interface{}) []byte {
+	payload, _ := json.Marshal(data)
+	if eventType == "" {
+		return []byte(fmt.Sprintf("data: %s\n\n", payload))
+	}
+	return []byte(fmt.Sprintf("event: %s\ndata: %s\n\n", eventType, payload))
+}
@@ -0,0 +1,170 @@
+package sandbox
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/tokens"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// AccountID 沙箱请求使用的虚拟上游账号ID，记录在使用统计中
+const AccountID = "sandbox"
+
+const (
+	defaultLatency       = 300 * time.Millisecond
+	defaultChunkInterval = 30 * time.Millisecond
+
+	// maxEcho 回复中引用的用户消息最大字符数
+	maxEcho = 200
+)
+
+// Responder 沙箱Key的模拟响应器：按上游格式生成确定的回复（相同请求得到相同内容），
+// 并模拟响应延迟和流式输出，网关的转换、统计和计费流程与真实上游相同
+type Responder struct {
+	config *types.SandboxConfig
+}
+
+// New 创建模拟响应器，config 为nil时使用默认延迟
+func New(config *types.SandboxConfig) *Responder {
+	if config == nil {
+		config = &types.SandboxConfig{}
+	}
+	return &Responder{config: config}
+}
+
+// Account 返回代表沙箱的虚拟上游账号，提供商与请求的目标提供商相同，使响应按其格式转换
+func Account(provider types.Provider) *types.UpstreamAccount {
+	return &types.UpstreamAccount{ID: AccountID, Name: "Sandbox", Provider: provider, Status: "active"}
+}
+
+// IsAccount 账号是否为沙箱虚拟账号
+func IsAccount(account *types.UpstreamAccount) bool {
+	return account != nil && account.ID == AccountID
+}
+
+// latency 返回响应前的模拟延迟
+func (r *Responder) latency() time.Duration {
+	return duration(r.config.LatencyMs, defaultLatency)
+}
+
+// chunkInterval 返回流式事件之间的间隔
+func (r *Responder) chunkInterval() time.Duration {
+	return duration(r.config.ChunkIntervalMs, defaultChunkInterval)
+}
+
+func duration(ms int, fallback time.Duration) time.Duration {
+	switch {
+	case ms < 0:
+		return 0
+	case ms == 0:
+		return fallback
+	default:
+		return time.Duration(ms) * time.Millisecond
+	}
+}
+
+// completion 模拟回复的内容和用量
+type completion struct {
+	ID           string
+	Model        string
+	Text         string
+	InputTokens  int
+	OutputTokens int
+	Truncated    bool // 达到 max_tokens 被截断
+}
+
+// complete 根据请求生成确定的回复：引用最后一条用户消息，超过 max_tokens 时按单词截断
+func complete(request *types.UnifiedRequest, provider types.Provider) *completion {
+	counter := tokens.ForProvider(provider)
+	prompt := lastUserText(request.Messages)
+	text := "This is a sandbox response from LLM Gateway; no upstream provider was called."
+	if prompt != "" {
+		if runes := []rune(prompt); len(runes) > maxEcho {
+			prompt = string(runes[:maxEcho]) + "..."
+		}
+		text += fmt.Sprintf(" You said: %q", prompt)
+	}
+
+	result := &completion{
+		ID:          requestHash(request),
+		Model:       request.Model,
+		Text:        text,
+		InputTokens: counter.CountRequest(request),
+	}
+	result.OutputTokens = counter.Count(text)
+	if request.MaxTokens > 0 && result.OutputTokens > request.MaxTokens {
+		words := strings.SplitAfter(text, " ")
+		kept := ""
+		for _, word := range words {
+			if counter.Count(kept+word) > request.MaxTokens {
+				break
+			}
+			kept += word
+		}
+		result.Text = strings.TrimRight(kept, " ")
+		result.OutputTokens = counter.Count(result.Text)
+		result.Truncated = true
+	}
+	return result
+}
+
+// lastUserText 返回最后一条用户消息的文本内容（多个文本块以空格连接）
+func lastUserText(messages []types.Message) string {
+	for i := len(messages) - 1; i >= 0; i-- {
+		if messages[i].Role != "user" {
+			continue
+		}
+		switch content := messages[i].Content.(type) {
+		case string:
+			return content
+		case []interface{}:
+			var parts []string
+			for _, item := range content {
+				if block, ok := item.(map[string]interface{}); ok && block["type"] == "text" {
+					if text, ok := block["text"].(string); ok {
+						parts = append(parts, text)
+					}
+				}
+			}
+			return strings.Join(parts, " ")
+		}
+		return ""
+	}
+	return ""
+}
+
+// requestHash 由模型和消息计算回复ID，相同请求得到相同ID
+func requestHash(request *types.UnifiedRequest) string {
+	hash := sha256.New()
+	hash.Write([]byte(request.Model))
+	for _, msg := range request.Messages {
+		_, _ = fmt.Fprintf(hash, "\x00%s\x00%v", msg.Role, msg.Content)
+	}
+	return hex.EncodeToString(hash.Sum(nil))[:24]
+}
+
+// finishReason 返回上游格式的结束原因
+func (c *completion) finishReason(format converter.Format) string {
+	switch format {
+	case converter.FormatAnthropic:
+		if c.Truncated {
+			return "max_tokens"
+		}
+		return "end_turn"
+	case converter.FormatGemini:
+		if c.Truncated {
+			return "MAX_TOKENS"
+		}
+		return "STOP"
+	default:
+		if c.Truncated {
+			return converter.FinishReasonLength
+		}
+		return converter.FinishReasonStop
+	}
+}
@@ -0,0 +1,131 @@
+package sandbox
+
+import (
+	"io"
+	"math"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// noDelay 测试中关闭模拟延迟
+var noDelay = &types.SandboxConfig{LatencyMs: -1, ChunkIntervalMs: -1}
+
+func chatRequest(maxTokens int) *types.UnifiedRequest {
+	return &types.UnifiedRequest{
+		Model:     "test-model",
+		MaxTokens: maxTokens,
+		Messages: []types.Message{
+			{Role: "system", Content: "Be brief."},
+			{Role: "user", Content: []interface{}{map[string]interface{}{"type": "text", "text": "Hello sandbox"}}},
+		},
+	}
+}
+
+func TestResponder_Complete(t *testing.T) {
+	responder := New(noDelay)
+	manager := converter.NewManager()
+	for _, provider := range []types.Provider{types.ProviderOpenAI, types.ProviderAnthropic, types.ProviderGoogle, types.ProviderBedrock} {
+		t.Run(string(provider), func(t *testing.T) {
+			body, err := responder.Complete(chatRequest(0), provider, manager.ProviderFormat(provider))
+			if err != nil {
+				t.Fatalf("Complete() error = %v", err)
+			}
+			response, err := manager.ParseUpstreamResponse(body, provider)
+			if err != nil {
+				t.Fatalf("ParseUpstreamResponse() error = %v, body = %s", err, body)
+			}
+			if len(response.Choices) == 0 || !strings.Contains(response.Choices[0].Message.Content.(string), `"Hello sandbox"`) {
+				t.Errorf("response = %+v, want the user message echoed", response.Choices)
+			}
+			if response.Usage.PromptTokens == 0 || response.Usage.CompletionTokens == 0 {
+				t.Errorf("usage = %+v, want estimated tokens", response.Usage)
+			}
+
+			// 相同请求得到相同内容
+			again, _ := responder.Complete(chatRequest(0), provider, manager.ProviderFormat(provider))
+			if second, _ := manager.ParseUpstreamResponse(again, provider); second.ID != response.ID || second.Choices[0].Message.Content != response.Choices[0].Message.Content {
+				t.Errorf("responses differ: %s / %s", body, again)
+			}
+		})
+	}
+}
+
+func TestComplete_MaxTokens(t *testing.T) {
+	full := complete(chatRequest(0), types.ProviderOpenAI)
+	truncated := complete(chatRequest(5), types.ProviderOpenAI)
+	if !truncated.Truncated || truncated.OutputTokens > 5 || !strings.HasPrefix(full.Text, truncated.Text) {
+		t.Errorf("truncated = %+v", truncated)
+	}
+	if truncated.finishReason(converter.FormatAnthropic) != "max_tokens" || full.finishReason(converter.FormatOpenAI) != "stop" {
+		t.Errorf("finish reasons = %s, %s", truncated.finishReason(converter.FormatAnthropic), full.finishReason(converter.FormatOpenAI))
+	}
+}
+
+func TestResponder_Stream(t *testing.T) {
+	responder := New(noDelay)
+	manager := converter.NewManager()
+	for _, provider := range []types.Provider{types.ProviderOpenAI, types.ProviderAnthropic, types.ProviderGoogle} {
+		t.Run(string(provider), func(t *testing.T) {
+			stream := responder.Stream(chatRequest(0), provider, manager.ProviderFormat(provider))
+			reader := converter.NewUsageCaptureReader(stream)
+			body, err := io.ReadAll(reader)
+			_ = stream.Close()
+			if err != nil {
+				t.Fatalf("read stream: %v", err)
+			}
+			want := complete(chatRequest(0), provider)
+			usage := reader.Usage()
+			if usage.InputTokens != want.InputTokens || usage.OutputTokens != want.OutputTokens || usage.FinishReason != converter.FinishReasonStop {
+				t.Errorf("usage = %+v, want %d/%d", usage, want.InputTokens, want.OutputTokens)
+			}
+			if strings.Count(string(body), "data: ") < 3 {
+				t.Errorf("stream has too few events: %s", body)
+			}
+		})
+	}
+}
+
+func TestResponder_Embeddings(t *testing.T) {
+	responder := New(noDelay)
+	body, err := responder.Embeddings("text-embedding-3-small", []string{"a", "b"}, 16, types.ProviderOpenAI)
+	if err != nil {
+		t.Fatalf("Embeddings() error = %v", err)
+	}
+	result, err := converter.ParseEmbeddingResponse(body, types.ProviderOpenAI, 2)
+	if err != nil {
+		t.Fatalf("ParseEmbeddingResponse() error = %v", err)
+	}
+	if len(result.Embeddings[0]) != 16 || !result.UsagePresent {
+		t.Errorf("result = %+v", result)
+	}
+
+	// 相同文本得到相同的单位向量，不同文本的向量不同
+	again := embedding("a", 16)
+	norm := 0.0
+	for i, value := range again {
+		if value != result.Embeddings[0][i] {
+			t.Fatalf("embedding(a) differs at %d", i)
+		}
+		norm += value * value
+	}
+	if math.Abs(norm-1) > 1e-9 {
+		t.Errorf("norm = %v, want 1", norm)
+	}
+	if result.Embeddings[0][0] == result.Embeddings[1][0] {
+		t.Errorf("different inputs produced the same vector")
+	}
+
+	body, _ = responder.Embeddings("text-embedding-004", []string{"a"}, 0, types.ProviderGoogle)
+	if result, err := converter.ParseEmbeddingResponse(body, types.ProviderGoogle, 1); err != nil || len(result.Embeddings[0]) != defaultDimensions {
+		t.Errorf("Gemini result = %+v, %v", result, err)
+	}
+}
+
+func TestIsAccount(t *testing.T) {
+	if !IsAccount(Account(types.ProviderOpenAI)) || IsAccount(&types.UpstreamAccount{ID: "acc-1"}) || IsAccount(nil) {
+		t.Error("IsAccount() mismatch")
+	}
+}
@@ -0,0 +1,263 @@
+package server
+
+import (
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"path/filepath"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/client"
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+var allRoles = []string{types.RoleViewer, types.RoleOperator, types.RoleAdmin}
+
+// newAccessTestHandler 创建为每个角色各登录一个会话的Web处理器，会话令牌与角色同名
+func newAccessTestHandler() *WebHandler {
+	h := &WebHandler{sessions: make(map[string]*Session)}
+	for _, role := range allRoles {
+		h.sessions[role] = &Session{Token: role, Username: role + "-user", Role: role, ExpiresAt: time.Now().Add(time.Hour)}
+	}
+	return h
+}
+
+// accessStatus 以 token 对应的会话通过 rule 访问，返回状态码
+func accessStatus(h *WebHandler, rule accessRule, method, path, token string) int {
+	req := httptest.NewRequest(method, path, nil)
+	if token != "" {
+		req.Header.Set("Authorization", "Bearer "+token)
+	}
+	rec := httptest.NewRecorder()
+	h.requireRole(rule, func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusNoContent)
+	})(rec, req)
+	return rec.Code
+}
+
+func TestRequireRole_ReadWrite(t *testing.T) {
+	rules := map[string]accessRule{
+		"adminWrite":    readWrite(types.RoleViewer, types.RoleAdmin),
+		"operator":      readWrite(types.RoleOperator, types.RoleOperator),
+		"operatorWrite": readWrite(types.RoleViewer, types.RoleOperator),
+		"admin":         readWrite(types.RoleAdmin, types.RoleAdmin),
+	}
+	// 每条规则下每个角色读（GET）和写（POST）的预期结果
+	tests := []struct {
+		rule  string
+		role  string
+		read  int
+		write int
+	}{
+		{rule: "adminWrite", role: types.RoleViewer, read: http.StatusNoContent, write: http.StatusForbidden},
+		{rule: "adminWrite", role: types.RoleOperator, read: http.StatusNoContent, write: http.StatusForbidden},
+		{rule: "adminWrite", role: types.RoleAdmin, read: http.StatusNoContent, write: http.StatusNoContent},
+		{rule: "operator", role: types.RoleViewer, read: http.StatusForbidden, write: http.StatusForbidden},
+		{rule: "operator", role: types.RoleOperator, read: http.StatusNoContent, write: http.StatusNoContent},
+		{rule: "operator", role: types.RoleAdmin, read: http.StatusNoContent, write: http.StatusNoContent},
+		{rule: "operatorWrite", role: types.RoleViewer, read: http.StatusNoContent, write: http.StatusForbidden},
+		{rule: "operatorWrite", role: types.RoleOperator, read: http.StatusNoContent, write: http.StatusNoContent},
+		{rule: "operatorWrite", role: types.RoleAdmin, read: http.StatusNoContent, write: http.StatusNoContent},
+		{rule: "admin", role: types.RoleViewer, read: http.StatusForbidden, write: http.StatusForbidden},
+		{rule: "admin", role: types.RoleOperator, read: http.StatusForbidden, write: http.StatusForbidden},
+		{rule: "admin", role: types.RoleAdmin, read: http.StatusNoContent, write: http.StatusNoContent},
+	}
+
+	h := newAccessTestHandler()
+	for _, tt := range tests {
+		t.Run(tt.rule+"/"+tt.role, func(t *testing.T) {
+			rule := rules[tt.rule]
+			if got := accessStatus(h, rule, http.MethodGet, "/api/v1/test", tt.role); got != tt.read {
+				t.Errorf("GET status = %d, want %d", got, tt.read)
+			}
+			if got := accessStatus(h, rule, http.MethodHead, "/api/v1/test", tt.role); got != tt.read {
+				t.Errorf("HEAD status = %d, want %d", got, tt.read)
+			}
+			for _, method := range []string{http.MethodPost, http.MethodPut, http.MethodDelete} {
+				if got := accessStatus(h, rule, method, "/api/v1/test", tt.role); got != tt.write {
+					t.Errorf("%s status = %d, want %d", method, got, tt.write)
+				}
+			}
+		})
+	}
+}
+
+func TestRequireRole_Unauthenticated(t *testing.T) {
+	h := newAccessTestHandler()
+	h.sessions["expired"] = &Session{Token: "expired", Username: "old", Role: types.RoleAdmin, ExpiresAt: time.Now().Add(-time.Minute)}
+	rule := readWrite(types.RoleViewer, types.RoleViewer)
+
+	for _, token := range []string{"", "unknown", "expired"} {
+		if got := accessStatus(h, rule, http.MethodGet, "/api/v1/test", token); got != http.StatusUnauthorized {
+			t.Errorf("token %q: status = %d, want %d", token, got, http.StatusUnauthorized)
+		}
+	}
+	if _, ok := h.sessions["expired"]; ok {
+		t.Error("expired session was not removed")
+	}
+}
+
+func TestAccessRules(t *testing.T) {
+	tests := []struct {
+		name   string
+		rule   accessRule
+		method string
+		path   string
+		want   string
+	}{
+		// /api/v1/upstream/{id}/...：探测和手动操作熔断器只需 operator，其他修改需要 admin
+		{name: "upstream read", rule: upstreamAccess, method: http.MethodGet, path: "/api/v1/upstream/up-1", want: types.RoleViewer},
+		{name: "upstream health check", rule: upstreamAccess, method: http.MethodPost, path: "/api/v1/upstream/up-1/health", want: types.RoleOperator},
+		{name: "upstream breaker reset", rule: upstreamAccess, method: http.MethodPost, path: "/api/v1/upstream/up-1/circuit-breaker/", want: types.RoleOperator},
+		{name: "upstream breaker settings", rule: upstreamAccess, method: http.MethodPut, path: "/api/v1/upstream/up-1/circuit-breaker", want: types.RoleAdmin},
+		{name: "upstream delete", rule: upstreamAccess, method: http.MethodDelete, path: "/api/v1/upstream/up-1", want: types.RoleAdmin},
+		{name: "upstream update", rule: upstreamAccess, method: http.MethodPut, path: "/api/v1/upstream/up-1", want: types.RoleAdmin},
+
+		// /api/v1/announcements/{id}/...：任何角色都可以关闭公告，管理公告需要 operator
+		{name: "announcement read", rule: announcementAccess, method: http.MethodGet, path: "/api/v1/announcements/a-1", want: types.RoleViewer},
+		{name: "announcement dismiss", rule: announcementAccess, method: http.MethodPost, path: "/api/v1/announcements/a-1/dismiss", want: types.RoleViewer},
+		{name: "announcement update", rule: announcementAccess, method: http.MethodPut, path: "/api/v1/announcements/a-1", want: types.RoleOperator},
+		{name: "announcement delete", rule: announcementAccess, method: http.MethodDelete, path: "/api/v1/announcements/a-1", want: types.RoleOperator},
+
+		// /api/v1/apikeys：operator 可以创建（是否允许由自助创建设置决定），其他修改需要 admin
+		{name: "apikey list", rule: apiKeyAccess, method: http.MethodGet, path: "/api/v1/apikeys", want: types.RoleViewer},
+		{name: "apikey create", rule: apiKeyAccess, method: http.MethodPost, path: "/api/v1/apikeys", want: types.RoleOperator},
+		{name: "apikey update", rule: apiKeyAccess, method: http.MethodPut, path: "/api/v1/apikeys", want: types.RoleAdmin},
+		{name: "apikey delete", rule: apiKeyAccess, method: http.MethodDelete, path: "/api/v1/apikeys", want: types.RoleAdmin},
+	}
+
+	h := newAccessTestHandler()
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			req := httptest.NewRequest(tt.method, tt.path, nil)
+			if got := tt.rule(req); got != tt.want {
+				t.Fatalf("required role = %s, want %s", got, tt.want)
+			}
+			// 每个角色按等级判定：不低于所需角色时放行，否则403
+			for _, role := range allRoles {
+				want := http.StatusForbidden
+				if types.RoleRank(role) >= types.RoleRank(tt.want) {
+					want = http.StatusNoContent
+				}
+				if got := accessStatus(h, tt.rule, tt.method, tt.path, role); got != want {
+					t.Errorf("%s: status = %d, want %d", role, got, want)
+				}
+			}
+		})
+	}
+}
+
+func TestWebHandler_SessionsConcurrentAccess(t *testing.T) {
+	h := newAccessTestHandler()
+	req := httptest.NewRequest(http.MethodGet, "/api/v1/test", nil)
+	req.Header.Set("Authorization", "Bearer "+types.RoleAdmin)
+
+	var wg sync.WaitGroup
+	for i := 0; i < 8; i++ {
+		wg.Add(2)
+		go func(i int) {
+			defer wg.Done()
+			token := fmt.Sprintf("token-%d", i)
+			h.sessionsMutex.Lock()
+			h.sessions[token] = &Session{Token: token, Username: "alice", Role: types.RoleViewer, ExpiresAt: time.Now().Add(time.Hour)}
+			h.sessionsMutex.Unlock()
+			h.endUserSessions("alice")
+		}(i)
+		go func() {
+			defer wg.Done()
+			if h.currentSession(req) == nil {
+				t.Error("currentSession() = nil for a valid session")
+			}
+		}()
+	}
+	wg.Wait()
+
+	for _, session := range h.sessions {
+		if session.Username == "alice" {
+			t.Errorf("endUserSessions() left session %s", session.Token)
+		}
+	}
+}
+
+func TestHandleAPIConfig_HidesSecrets(t *testing.T) {
+	configMgr := config.NewConfigManager(filepath.Join(t.TempDir(), "config.yaml"))
+	if err := configMgr.Save(&types.Config{
+		Server: types.ServerConfig{Host: "localhost", Port: 8080, Web: types.WebConfig{
+			Enabled:            true,
+			Password:           "admin-password-secret",
+			ServiceTokenSecret: "service-token-secret-0123456789abcdef",
+		}},
+		Proxy: types.ProxyConfig{Moderation: types.ModerationConfig{Model: types.ModerationModelConfig{APIKey: "moderation-api-key"}}},
+	}); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	if _, err := configMgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	// viewer 也能读取配置，响应中不能出现任何凭证
+	rec := httptest.NewRecorder()
+	(&WebHandler{configMgr: configMgr}).HandleAPIConfig(rec, httptest.NewRequest(http.MethodGet, "/api/v1/config", nil))
+	if rec.Code != http.StatusOK {
+		t.Fatalf("status = %d, want 200", rec.Code)
+	}
+	for _, secret := range []string{"admin-password-secret", "service-token-secret-0123456789abcdef", "moderation-api-key"} {
+		if strings.Contains(rec.Body.String(), secret) {
+			t.Errorf("response contains %q: %s", secret, rec.Body.String())
+		}
+	}
+}
+
+func TestHandleCreateAPIKey_SelfServe(t *testing.T) {
+	t.Setenv(config.MaxKeysPerUserEnvVar, "")
+	configMgr := config.NewConfigManager(filepath.Join(t.TempDir(), "config.yaml"))
+	if err := configMgr.Save(&types.Config{
+		Server:        types.ServerConfig{Host: "localhost", Port: 8080, Web: types.WebConfig{KeyCreation: types.KeyCreationConfig{AllowOperators: true, MaxKeysPerUser: 1, RequireApproval: true}}},
+		Organizations: []types.Organization{{ID: "org-a", Name: "A"}},
+	}); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	if _, err := configMgr.Load(); err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+	h := newAccessTestHandler()
+	h.configMgr = configMgr
+	h.keyMgr = client.NewGatewayKeyManager(configMgr)
+
+	create := func(body string) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodPost, "/api/v1/apikeys", strings.NewReader(body))
+		req.Header.Set("Authorization", "Bearer "+types.RoleOperator)
+		rec := httptest.NewRecorder()
+		h.handleCreateAPIKey(rec, req)
+		return rec
+	}
+
+	// operator 不能把Key绑定到组织或设置作用域和权限
+	for _, body := range []string{
+		`{"name":"k","org_id":"org-a"}`,
+		`{"name":"k","scopes":["provider:openai"]}`,
+		`{"name":"k","permissions":["admin"]}`,
+	} {
+		if rec := create(body); rec.Code != http.StatusForbidden {
+			t.Errorf("%s: status = %d, want 403", body, rec.Code)
+		}
+	}
+
+	// 待审批的Key保存时就是停用状态
+	rec := create(`{"name":"mine"}`)
+	if rec.Code != http.StatusCreated {
+		t.Fatalf("status = %d, want 201, body = %s", rec.Code, rec.Body.String())
+	}
+	keys := configMgr.ListGatewayKeys()
+	if len(keys) != 1 || keys[0].Status != "disabled" || !keys[0].PendingApproval || keys[0].CreatedBy != "web:operator-user" {
+		t.Errorf("saved keys = %+v, want one disabled key pending approval", keys)
+	}
+
+	if rec := create(`{"name":"second"}`); rec.Code != http.StatusForbidden {
+		t.Errorf("over the limit: status = %d, want 403", rec.Code)
+	}
+}
@@ -0,0 +1,235 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// announcementRequest 创建/更新公告的请求体
+type announcementRequest struct {
+	Title    string     `json:"title"`
+	Message  string     `json:"message"`
+	Level    string     `json:"level"`
+	Enabled  *bool      `json:"enabled"`
+	StartsAt *time.Time `json:"starts_at"`
+	EndsAt   *time.Time `json:"ends_at"`
+}
+
+// validate 验证公告请求
+func (req *announcementRequest) validate() string {
+	if req.Title == "" || req.Message == "" {
+		return "title and message are required"
+	}
+	switch req.Level {
+	case "", "info", "warning", "critical":
+	default:
+		return "level must be one of info, warning, critical"
+	}
+	if req.StartsAt != nil && req.EndsAt != nil && req.EndsAt.Before(*req.StartsAt) {
+		return "ends_at must be after starts_at"
+	}
+	return ""
+}
+
+// HandleAnnouncements 公告列表与创建
+func (h *WebHandler) HandleAnnouncements(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.handleListAnnouncements(w, r)
+	case http.MethodPost:
+		h.handleCreateAnnouncement(w, r)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) handleListAnnouncements(w http.ResponseWriter, r *http.Request) {
+	announcements := h.configMgr.ListAnnouncements()
+
+	// active=true 时只返回当前用户未关闭且处于展示期的公告（用于Dashboard横幅）
+	if r.URL.Query().Get("active") == "true" {
+		announcements = filterVisibleAnnouncements(announcements, h.sessionUser(r))
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"data": announcements,
+	})
+}
+
+func (h *WebHandler) handleCreateAnnouncement(w http.ResponseWriter, r *http.Request) {
+	var req announcementRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	if msg := req.validate(); msg != "" {
+		h.writeError(w, http.StatusBadRequest, msg)
+		return
+	}
+
+	now := time.Now()
+	announcement := &types.Announcement{
+		ID:        h.generateID("announcement"),
+		Title:     req.Title,
+		Message:   req.Message,
+		Level:     req.Level,
+		Enabled:   true,
+		StartsAt:  req.StartsAt,
+		EndsAt:    req.EndsAt,
+		CreatedAt: now,
+		UpdatedAt: now,
+	}
+	if announcement.Level == "" {
+		announcement.Level = "info"
+	}
+	if req.Enabled != nil {
+		announcement.Enabled = *req.Enabled
+	}
+
+	if err := h.configMgr.CreateAnnouncement(announcement); err != nil {
+		logger.Error("Failed to create announcement: %v", err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to create announcement")
+		return
+	}
+
+	logger.Info("Created announcement: %s (%s)", announcement.Title, announcement.ID)
+	h.writeJSON(w, http.StatusCreated, announcement)
+}
+
+// HandleAnnouncementActions 处理单个公告的更新、删除和关闭
+func (h *WebHandler) HandleAnnouncementActions(w http.ResponseWriter, r *http.Request) {
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) < 4 {
+		h.writeError(w, http.StatusBadRequest, "Invalid announcement ID")
+		return
+	}
+
+	announcementID := pathParts[3] // /api/v1/announcements/{id}
+
+	if len(pathParts) == 5 && pathParts[4] == "dismiss" {
+		// /api/v1/announcements/{id}/dismiss
+		if r.Method != http.MethodPost {
+			h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+			return
+		}
+		if err := h.configMgr.DismissAnnouncement(announcementID, h.sessionUser(r)); err != nil {
+			h.writeError(w, http.StatusNotFound, "Announcement not found")
+			return
+		}
+		w.WriteHeader(http.StatusNoContent)
+		return
+	}
+
+	if len(pathParts) != 4 {
+		h.writeError(w, http.StatusNotFound, "API endpoint not found")
+		return
+	}
+
+	switch r.Method {
+	case http.MethodPut:
+		h.handleUpdateAnnouncement(w, r, announcementID)
+	case http.MethodDelete:
+		if err := h.configMgr.DeleteAnnouncement(announcementID); err != nil {
+			h.writeError(w, http.StatusNotFound, "Announcement not found")
+			return
+		}
+		logger.Info("Deleted announcement: %s", announcementID)
+		w.WriteHeader(http.StatusNoContent)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) handleUpdateAnnouncement(w http.ResponseWriter, r *http.Request, announcementID string) {
+	var req announcementRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	if msg := req.validate(); msg != "" {
+		h.writeError(w, http.StatusBadRequest, msg)
+		return
+	}
+
+	err := h.configMgr.UpdateAnnouncement(announcementID, func(announcement *types.Announcement) error {
+		announcement.Title = req.Title
+		announcement.Message = req.Message
+		if req.Level != "" {
+			announcement.Level = req.Level
+		}
+		if req.Enabled != nil {
+			announcement.Enabled = *req.Enabled
+		}
+		announcement.StartsAt = req.StartsAt
+		announcement.EndsAt = req.EndsAt
+		announcement.UpdatedAt = time.Now()
+		return nil
+	})
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "Announcement not found")
+		return
+	}
+
+	logger.Info("Updated announcement: %s", announcementID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Announcement updated successfully",
+	})
+}
+
+// handleGatewayAnnouncements 面向Gateway API Key用户的公告端点
+// GET /v1/announcements 返回当前Key未关闭的有效公告
+// POST /v1/announcements/{id}/dismiss 为当前Key关闭公告
+func (s *HTTPServer) handleGatewayAnnouncements(w http.ResponseWriter, r *http.Request) {
+	keyID := r.Header.Get("X-Gateway-Key-ID")
+
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) == 4 && pathParts[3] == "dismiss" && r.Method == http.MethodPost {
+		if err := s.configMgr.DismissAnnouncement(pathParts[2], keyID); err != nil {
+			s.writeJSONResponse(w, http.StatusNotFound, map[string]interface{}{
+				"error": map[string]string{
+					"type":    "not_found",
+					"message": "Announcement not found",
+				},
+			})
+			return
+		}
+		w.WriteHeader(http.StatusNoContent)
+		return
+	}
+
+	if len(pathParts) != 2 || r.Method != http.MethodGet {
+		s.writeJSONResponse(w, http.StatusNotFound, map[string]interface{}{
+			"error": map[string]string{
+				"type":    "not_found",
+				"message": "Endpoint not found",
+			},
+		})
+		return
+	}
+
+	s.writeJSONResponse(w, http.StatusOK, map[string]interface{}{
+		"data": filterVisibleAnnouncements(s.configMgr.ListAnnouncements(), keyID),
+	})
+}
+
+// filterVisibleAnnouncements 过滤出指定用户可见的公告
+func filterVisibleAnnouncements(announcements []*types.Announcement, userID string) []*types.Announcement {
+	now := time.Now()
+	visible := make([]*types.Announcement, 0, len(announcements))
+	for _, announcement := range announcements {
+		if announcement.IsActive(now) && !announcement.IsDismissedBy(userID) {
+			// 不向普通用户暴露其他用户的关闭记录
+			announcement.DismissedBy = nil
+			visible = append(visible, announcement)
+		}
+	}
+	return visible
+}
@@ -0,0 +1,87 @@
+package server
+
+import (
+	"net/http"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/audit"
+)
+
+// auditResponseWriter 记录写给客户端的状态码和响应体，用于审计日志
+type auditResponseWriter struct {
+	http.ResponseWriter
+	capture *audit.Capture
+	status  int
+}
+
+// WriteHeader 记录状态码
+func (w *auditResponseWriter) WriteHeader(status int) {
+	if w.status == 0 {
+		w.status = status
+	}
+	w.ResponseWriter.WriteHeader(status)
+}
+
+// Write 记录响应体
+func (w *auditResponseWriter) Write(data []byte) (int, error) {
+	if w.status == 0 {
+		w.status = http.StatusOK
+	}
+	_, _ = w.capture.Write(data)
+	return w.ResponseWriter.Write(data)
+}
+
+// Flush 支持流式响应
+func (w *auditResponseWriter) Flush() {
+	if flusher, ok := w.ResponseWriter.(http.Flusher); ok {
+		flusher.Flush()
+	}
+}
+
+// HandleAuditQuery 查询审计日志，按时间从新到旧返回
+func (h *WebHandler) HandleAuditQuery(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	query := r.URL.Query()
+	filter := audit.Filter{
+		GatewayKeyID: query.Get("key_id"),
+		RequestID:    query.Get("request_id"),
+		Limit:        100,
+	}
+
+	for name, target := range map[string]*time.Time{"since": &filter.Since, "until": &filter.Until} {
+		if value := query.Get(name); value != "" {
+			parsed, err := time.Parse(time.RFC3339, value)
+			if err != nil {
+				h.writeError(w, http.StatusBadRequest, name+" must be an RFC3339 timestamp")
+				return
+			}
+			*target = parsed
+		}
+	}
+
+	if value := query.Get("limit"); value != "" {
+		limit, err := strconv.Atoi(value)
+		if err != nil || limit < 1 || limit > 1000 {
+			h.writeError(w, http.StatusBadRequest, "limit must be between 1 and 1000")
+			return
+		}
+		filter.Limit = limit
+	}
+
+	entries, err := h.audit.Query(filter)
+	if err != nil {
+		h.writeError(w, http.StatusInternalServerError, "Failed to query audit log")
+		return
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"enabled": h.configMgr.Get().Audit.Enabled,
+		"data":    entries,
+		"total":   len(entries),
+	})
+}
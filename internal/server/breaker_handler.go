@@ -0,0 +1,95 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleCircuitBreakerConfig 查看（GET）或更新（PUT）全局熔断器参数，更新立即生效
+func (h *WebHandler) HandleCircuitBreakerConfig(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, h.configMgr.Get().HealthCheck.CircuitBreaker)
+	case http.MethodPut:
+		var req types.CircuitBreakerConfig
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if err := h.configMgr.SetCircuitBreakerConfig(req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid circuit breaker configuration: "+err.Error())
+			return
+		}
+		logger.Info("Updated circuit breaker settings by %s", h.sessionUser(r))
+		h.writeJSON(w, http.StatusOK, h.configMgr.Get().HealthCheck.CircuitBreaker)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+// handleUpstreamCircuitBreaker 账号熔断器：GET 查看状态和生效参数，POST 手动重置（reset）或打开（trip），
+// PUT 设置账号的参数覆盖（请求体为 null 时恢复使用全局参数）
+func (h *WebHandler) handleUpstreamCircuitBreaker(w http.ResponseWriter, r *http.Request, upstreamID string) {
+	account, err := h.upstreamMgr.GetAccount(upstreamID)
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "Upstream account not found")
+		return
+	}
+
+	breakers := h.upstreamMgr.Breakers()
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"upstream_id": upstreamID,
+			"status":      breakers.Status(upstreamID),
+			"override":    account.CircuitBreaker,
+			"history":     breakers.History(upstreamID, 10),
+		})
+	case http.MethodPost:
+		var req struct {
+			Action string `json:"action"`
+			Reason string `json:"reason"`
+		}
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		reason := req.Reason
+		if reason == "" {
+			reason = "manual " + req.Action + " by " + h.sessionUser(r)
+		}
+		switch req.Action {
+		case "reset":
+			breakers.Reset(upstreamID, reason, time.Now())
+		case "trip":
+			breakers.Trip(upstreamID, reason, time.Now())
+		default:
+			h.writeError(w, http.StatusBadRequest, "action must be reset or trip")
+			return
+		}
+		logger.Info("Circuit breaker for upstream account %s: %s by %s", upstreamID, req.Action, h.sessionUser(r))
+		h.writeJSON(w, http.StatusOK, breakers.Status(upstreamID))
+	case http.MethodPut:
+		var req *types.CircuitBreakerConfig
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if err := h.configMgr.SetUpstreamCircuitBreaker(upstreamID, req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid circuit breaker configuration: "+err.Error())
+			return
+		}
+		logger.Info("Updated circuit breaker override for upstream account %s by %s", upstreamID, h.sessionUser(r))
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"upstream_id": upstreamID,
+			"status":      breakers.Status(upstreamID),
+			"override":    req,
+		})
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
@@ -0,0 +1,40 @@
+package server
+
+import (
+	"bytes"
+	"net/http"
+	"strings"
+)
+
+// cacheHeader 客户端选择使用响应缓存的请求头，响应中的同名头表示是否命中（hit/miss）
+const cacheHeader = "X-LLM-Cache"
+
+// wantsResponseCache 判断请求是否选择使用响应缓存
+func wantsResponseCache(r *http.Request) bool {
+	value := r.Header.Get(cacheHeader)
+	return value == "1" || strings.EqualFold(value, "true")
+}
+
+// cacheResponseWriter 记录写给客户端的非流式响应，请求成功后写入缓存
+type cacheResponseWriter struct {
+	http.ResponseWriter
+	status int
+	body   bytes.Buffer
+}
+
+// WriteHeader 记录状态码
+func (w *cacheResponseWriter) WriteHeader(status int) {
+	if w.status == 0 {
+		w.status = status
+	}
+	w.ResponseWriter.WriteHeader(status)
+}
+
+// Write 记录响应体
+func (w *cacheResponseWriter) Write(data []byte) (int, error) {
+	if w.status == 0 {
+		w.status = http.StatusOK
+	}
+	w.body.Write(data)
+	return w.ResponseWriter.Write(data)
+}
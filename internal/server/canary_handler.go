@@ -0,0 +1,31 @@
+package server
+
+import "net/http"
+
+// HandleCanaries 查看（GET）每个合成探针在各账号上最近一次的结果，或立即运行所有探针（POST）
+func (h *WebHandler) HandleCanaries(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		config := h.configMgr.Get().Canaries
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"enabled": config.Enabled,
+			"checks":  config.Checks,
+			"data":    h.canaries.Results(),
+		})
+	case http.MethodPost:
+		results := h.canaries.RunOnce()
+		failed := 0
+		for _, result := range results {
+			if !result.Passed {
+				failed++
+			}
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data":   results,
+			"total":  len(results),
+			"failed": failed,
+		})
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
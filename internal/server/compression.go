@@ -0,0 +1,79 @@
+package server
+
+import (
+	"bytes"
+	"compress/gzip"
+	"fmt"
+	"io"
+	"net/http"
+	"strconv"
+	"strings"
+)
+
+// acceptsGzip 判断客户端的 Accept-Encoding 是否接受 gzip（q=0 表示明确拒绝）。
+// 明确列出的 gzip 优先于通配符 *，如 "gzip;q=0, *" 不接受 gzip
+func acceptsGzip(r *http.Request) bool {
+	var gzipListed, gzipAccepted, wildcardAccepted bool
+	for _, value := range r.Header.Values("Accept-Encoding") {
+		for _, item := range strings.Split(value, ",") {
+			coding, params, _ := strings.Cut(strings.TrimSpace(item), ";")
+			switch strings.ToLower(strings.TrimSpace(coding)) {
+			case "gzip", "x-gzip":
+				gzipListed = true
+				gzipAccepted = gzipAccepted || !zeroQuality(params)
+			case "*":
+				wildcardAccepted = wildcardAccepted || !zeroQuality(params)
+			}
+		}
+	}
+	if gzipListed {
+		return gzipAccepted
+	}
+	return wildcardAccepted
+}
+
+// zeroQuality 判断编码的参数中是否有 q=0
+func zeroQuality(params string) bool {
+	for _, param := range strings.Split(params, ";") {
+		key, value, _ := strings.Cut(param, "=")
+		if strings.TrimSpace(key) != "q" {
+			continue
+		}
+		q, err := strconv.ParseFloat(strings.TrimSpace(value), 64)
+		return err == nil && q == 0
+	}
+	return false
+}
+
+// gzipPassthrough 判断非流式响应能否把上游的 gzip 响应体原样写给客户端：客户端接受 gzip，
+// 且响应体不会被审计日志、响应缓存记录或经 WebSocket 转发（它们需要未压缩的响应体）
+func gzipPassthrough(w http.ResponseWriter, r *http.Request) bool {
+	switch w.(type) {
+	case *auditResponseWriter, *cacheResponseWriter, *wsStreamWriter:
+		return false
+	}
+	return acceptsGzip(r)
+}
+
+// readUpstreamBody 读取上游响应体。Content-Encoding 为 gzip 时解压，同时返回压缩的原始字节供原样转发；
+// 未压缩时 compressed 为nil
+func readUpstreamBody(resp *http.Response) (body, compressed []byte, err error) {
+	data, err := io.ReadAll(resp.Body)
+	if err != nil {
+		return nil, nil, err
+	}
+	if !strings.EqualFold(strings.TrimSpace(resp.Header.Get("Content-Encoding")), "gzip") {
+		return data, nil, nil
+	}
+
+	reader, err := gzip.NewReader(bytes.NewReader(data))
+	if err != nil {
+		return nil, nil, fmt.Errorf("解压上游响应失败: %w", err)
+	}
+	defer func() { _ = reader.Close() }()
+	body, err = io.ReadAll(reader)
+	if err != nil {
+		return nil, nil, fmt.Errorf("解压上游响应失败: %w", err)
+	}
+	return body, data, nil
+}
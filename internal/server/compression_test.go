@@ -0,0 +1,182 @@
+package server
+
+import (
+	"bytes"
+	"compress/gzip"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// gzipBytes 返回 data 的 gzip 压缩字节
+func gzipBytes(t *testing.T, data []byte) []byte {
+	t.Helper()
+	var buf bytes.Buffer
+	writer := gzip.NewWriter(&buf)
+	if _, err := writer.Write(data); err != nil {
+		t.Fatalf("gzip Write() error = %v", err)
+	}
+	if err := writer.Close(); err != nil {
+		t.Fatalf("gzip Close() error = %v", err)
+	}
+	return buf.Bytes()
+}
+
+func TestAcceptsGzip(t *testing.T) {
+	tests := []struct {
+		name   string
+		values []string
+		want   bool
+	}{
+		{name: "no header", want: false},
+		{name: "identity", values: []string{"identity"}, want: false},
+		{name: "br only", values: []string{"br"}, want: false},
+		{name: "gzip", values: []string{"gzip"}, want: true},
+		{name: "case insensitive", values: []string{"GZIP"}, want: true},
+		{name: "x-gzip", values: []string{"x-gzip"}, want: true},
+		{name: "in a list", values: []string{"deflate, gzip;q=0.8, br"}, want: true},
+		{name: "several header values", values: []string{"br", "gzip"}, want: true},
+		{name: "q=0", values: []string{"gzip;q=0"}, want: false},
+		{name: "q=0.000 with spaces", values: []string{"gzip; q=0.000"}, want: false},
+		{name: "non-zero q", values: []string{"gzip;q=0.5"}, want: true},
+		{name: "wildcard", values: []string{"*"}, want: true},
+		{name: "wildcard q=0", values: []string{"identity, *;q=0"}, want: false},
+		{name: "gzip rejected despite wildcard", values: []string{"gzip;q=0, *"}, want: false},
+		{name: "gzip accepted despite rejected wildcard", values: []string{"gzip, *;q=0"}, want: true},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", nil)
+			for _, value := range tt.values {
+				req.Header.Add("Accept-Encoding", value)
+			}
+			if got := acceptsGzip(req); got != tt.want {
+				t.Errorf("acceptsGzip(%q) = %v, want %v", tt.values, got, tt.want)
+			}
+		})
+	}
+}
+
+func TestZeroQuality(t *testing.T) {
+	tests := []struct {
+		params string
+		want   bool
+	}{
+		{params: "", want: false},
+		{params: "q=0", want: true},
+		{params: " q = 0.0 ", want: true},
+		{params: "q=1", want: false},
+		{params: "q=0.001", want: false},
+		{params: "level=1;q=0", want: true},
+		{params: "q=abc", want: false},
+	}
+	for _, tt := range tests {
+		if got := zeroQuality(tt.params); got != tt.want {
+			t.Errorf("zeroQuality(%q) = %v, want %v", tt.params, got, tt.want)
+		}
+	}
+}
+
+func TestGzipPassthrough(t *testing.T) {
+	gzipReq := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", nil)
+	gzipReq.Header.Set("Accept-Encoding", "gzip")
+	plainReq := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", nil)
+
+	rec := httptest.NewRecorder()
+	if !gzipPassthrough(rec, gzipReq) {
+		t.Error("gzipPassthrough() = false for a client that accepts gzip")
+	}
+	if gzipPassthrough(rec, plainReq) {
+		t.Error("gzipPassthrough() = true for a client without Accept-Encoding")
+	}
+
+	// 审计、响应缓存和 WebSocket 需要未压缩的响应体
+	writers := map[string]http.ResponseWriter{
+		"audit":     &auditResponseWriter{ResponseWriter: rec},
+		"cache":     &cacheResponseWriter{ResponseWriter: rec},
+		"websocket": &wsStreamWriter{header: make(http.Header)},
+	}
+	for name, w := range writers {
+		if gzipPassthrough(w, gzipReq) {
+			t.Errorf("%s: gzipPassthrough() = true, want the body decompressed", name)
+		}
+	}
+}
+
+func TestReadUpstreamBody(t *testing.T) {
+	body := []byte(`{"ok":true}`)
+
+	plain := &http.Response{Header: make(http.Header), Body: io.NopCloser(bytes.NewReader(body))}
+	got, compressed, err := readUpstreamBody(plain)
+	if err != nil || !bytes.Equal(got, body) || compressed != nil {
+		t.Errorf("plain body: readUpstreamBody() = %q, %v, %v, want the body and nil compressed bytes", got, compressed, err)
+	}
+
+	zipped := gzipBytes(t, body)
+	resp := &http.Response{Header: http.Header{"Content-Encoding": []string{"GZIP"}}, Body: io.NopCloser(bytes.NewReader(zipped))}
+	got, compressed, err = readUpstreamBody(resp)
+	if err != nil || !bytes.Equal(got, body) || !bytes.Equal(compressed, zipped) {
+		t.Errorf("gzip body: readUpstreamBody() = %q, %v, want the decoded body and the original compressed bytes", got, err)
+	}
+
+	broken := &http.Response{Header: http.Header{"Content-Encoding": []string{"gzip"}}, Body: io.NopCloser(strings.NewReader("not gzip"))}
+	if _, _, err := readUpstreamBody(broken); err == nil {
+		t.Error("readUpstreamBody() error = nil for a body that is not gzip")
+	}
+}
+
+func TestNonStreamGzipResponse(t *testing.T) {
+	zipped := gzipBytes(t, []byte(chatCompletionBody))
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		// 网关对非流式请求显式要求 gzip
+		if r.Header.Get("Accept-Encoding") != "gzip" {
+			w.WriteHeader(http.StatusBadRequest)
+			return
+		}
+		w.Header().Set("Content-Type", "application/json")
+		w.Header().Set("Content-Encoding", "gzip")
+		_, _ = w.Write(zipped)
+	}))
+	defer server.Close()
+
+	h, _ := newUpstreamTestHandler(t, types.ProxyConfig{UsageHeaders: true}, testOpenAIAccount("primary", server.URL, 0))
+
+	tests := []struct {
+		name           string
+		acceptEncoding string
+		passthrough    bool
+	}{
+		{name: "gzip client", acceptEncoding: "gzip", passthrough: true},
+		{name: "identity client", acceptEncoding: "identity", passthrough: false},
+		{name: "gzip refused", acceptEncoding: "gzip;q=0", passthrough: false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			rec := postChat(h, map[string]string{"Accept-Encoding": tt.acceptEncoding})
+			if rec.Code != http.StatusOK {
+				t.Fatalf("status = %d, want 200, body = %s", rec.Code, rec.Body.String())
+			}
+			// 用量从解压后的响应体中读取，与是否原样转发无关
+			if got := rec.Header().Get("X-Gateway-Input-Tokens"); got != "1" {
+				t.Errorf("X-Gateway-Input-Tokens = %q, want 1 from the decoded upstream body", got)
+			}
+			if got := rec.Header().Get("Vary"); got != "Accept-Encoding" {
+				t.Errorf("Vary = %q, want Accept-Encoding", got)
+			}
+
+			if tt.passthrough {
+				if rec.Header().Get("Content-Encoding") != "gzip" || !bytes.Equal(rec.Body.Bytes(), zipped) {
+					t.Errorf("Content-Encoding = %q, want the upstream gzip bytes forwarded as-is", rec.Header().Get("Content-Encoding"))
+				}
+				return
+			}
+			if rec.Header().Get("Content-Encoding") != "" || !strings.Contains(rec.Body.String(), "from backup") {
+				t.Errorf("Content-Encoding = %q, body = %s, want the decompressed body", rec.Header().Get("Content-Encoding"), rec.Body.String())
+			}
+		})
+	}
+}
@@ -0,0 +1,106 @@
+package server
+
+import (
+	"fmt"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// dashboardAlert 看板上当前生效的告警
+type dashboardAlert struct {
+	Type       string         `json:"type"` // account_unhealthy / breaker_open / canary_failure
+	Message    string         `json:"message"`
+	UpstreamID string         `json:"upstream_id,omitempty"`
+	Provider   types.Provider `json:"provider,omitempty"`
+	Since      *time.Time     `json:"since,omitempty"`
+}
+
+// HandleDashboardSummary 一次返回看板首页需要的数据：当日用量、账号健康、最近24小时错误率趋势和当前告警。
+// 用量来自随请求增量维护的聚合，账号状态和探针结果都在内存中，不扫描使用记录
+func (h *WebHandler) HandleDashboardSummary(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	now := time.Now()
+	breakers := h.upstreamMgr.Breakers()
+	var alerts []dashboardAlert
+
+	accountCounts := map[string]int{
+		"total":         0,
+		"active":        0,
+		"healthy":       0,
+		"unhealthy":     0,
+		"breakers_open": 0,
+	}
+	accounts := make([]map[string]interface{}, 0)
+	for _, account := range h.configMgr.ListUpstreamAccounts() {
+		accountCounts["total"]++
+		if account.Status != "active" {
+			continue
+		}
+		accountCounts["active"]++
+
+		breakerState, _ := breakers.State(account.ID)
+		accounts = append(accounts, map[string]interface{}{
+			"id":            account.ID,
+			"name":          account.Name,
+			"provider":      account.Provider,
+			"health_status": account.HealthStatus,
+			"breaker_state": breakerState,
+		})
+
+		switch account.HealthStatus {
+		case "healthy":
+			accountCounts["healthy"]++
+		case "unhealthy":
+			accountCounts["unhealthy"]++
+			alerts = append(alerts, dashboardAlert{
+				Type:       "account_unhealthy",
+				Message:    fmt.Sprintf("Upstream account %s is unhealthy: %s", account.Name, account.HealthError),
+				UpstreamID: account.ID,
+				Provider:   account.Provider,
+				Since:      account.LastHealthCheck,
+			})
+		}
+		if breakerState != upstream.BreakerClosed {
+			accountCounts["breakers_open"]++
+			alerts = append(alerts, dashboardAlert{
+				Type:       "breaker_open",
+				Message:    fmt.Sprintf("Circuit breaker for upstream account %s is %s", account.Name, breakerState),
+				UpstreamID: account.ID,
+				Provider:   account.Provider,
+			})
+		}
+	}
+
+	for _, result := range h.canaries.Results() {
+		if result.Passed {
+			continue
+		}
+		checkedAt := result.CheckedAt
+		alerts = append(alerts, dashboardAlert{
+			Type:       "canary_failure",
+			Message:    fmt.Sprintf("Canary %s failed on upstream account %s: %s", result.Canary, result.Name, result.Error),
+			UpstreamID: result.UpstreamID,
+			Provider:   result.Provider,
+			Since:      &checkedAt,
+		})
+	}
+	if alerts == nil {
+		alerts = []dashboardAlert{}
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"generated_at":     now,
+		"today":            h.dashboard.Today(now),
+		"accounts":         accountCounts,
+		"account_health":   accounts,
+		"error_rate_trend": h.dashboard.ErrorTrend(now),
+		"alerts":           alerts,
+	})
+}
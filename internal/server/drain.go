@@ -0,0 +1,120 @@
+package server
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"sync"
+	"time"
+)
+
+// drainGate 跟踪进行中的代理请求（流式请求持续到流结束）和请求结束后的异步写入（用量统计、审计），
+// 进入排空状态后拒绝新的代理请求
+type drainGate struct {
+	draining bool
+	inFlight int
+	pending  int
+	mutex    sync.Mutex
+}
+
+// wrap 包装代理处理器：排空期间返回503，否则计入进行中的请求
+func (g *drainGate) wrap(next http.HandlerFunc) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		g.mutex.Lock()
+		if g.draining {
+			g.mutex.Unlock()
+			w.Header().Set("Connection", "close")
+			w.Header().Set("Retry-After", "5")
+			writeDrainingResponse(w)
+			return
+		}
+		g.inFlight++
+		g.mutex.Unlock()
+
+		defer func() {
+			g.mutex.Lock()
+			g.inFlight--
+			g.mutex.Unlock()
+		}()
+		next(w, r)
+	}
+}
+
+// start 进入排空状态，返回此时进行中的请求数
+func (g *drainGate) start() int {
+	g.mutex.Lock()
+	defer g.mutex.Unlock()
+	g.draining = true
+	return g.inFlight
+}
+
+// active 返回进行中的请求数
+func (g *drainGate) active() int {
+	g.mutex.Lock()
+	defer g.mutex.Unlock()
+	return g.inFlight
+}
+
+// isDraining 返回是否处于排空状态，g为nil时返回false
+func (g *drainGate) isDraining() bool {
+	if g == nil {
+		return false
+	}
+	g.mutex.Lock()
+	defer g.mutex.Unlock()
+	return g.draining
+}
+
+// async 在后台执行请求结束后的写入，关闭时等待其完成；g为nil时直接在新goroutine中执行
+func (g *drainGate) async(fn func()) {
+	if g == nil {
+		go fn()
+		return
+	}
+	g.mutex.Lock()
+	g.pending++
+	g.mutex.Unlock()
+
+	go func() {
+		defer func() {
+			g.mutex.Lock()
+			g.pending--
+			g.mutex.Unlock()
+		}()
+		fn()
+	}()
+}
+
+// flush 等待异步写入完成，ctx到期时返回false
+func (g *drainGate) flush(ctx context.Context) bool {
+	ticker := time.NewTicker(20 * time.Millisecond)
+	defer ticker.Stop()
+
+	for {
+		g.mutex.Lock()
+		pending := g.pending
+		g.mutex.Unlock()
+		if pending == 0 {
+			return true
+		}
+
+		select {
+		case <-ticker.C:
+		case <-ctx.Done():
+			return false
+		}
+	}
+}
+
+// writeDrainingResponse 服务器关闭期间拒绝新请求
+func writeDrainingResponse(w http.ResponseWriter) {
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(http.StatusServiceUnavailable)
+	_ = json.NewEncoder(w).Encode(map[string]interface{}{
+		"error": map[string]string{
+			"type":    "server_shutting_down",
+			"message": "The gateway is shutting down, retry on another instance",
+		},
+		"timestamp": time.Now().Unix(),
+	})
+}
@@ -0,0 +1,248 @@
+package server
+
+import (
+	"errors"
+	"fmt"
+	"net/http"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/sandbox"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/tokens"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleEmbeddings 处理 OpenAI 格式的嵌入端点 POST /v1/embeddings：
+// 按模型确定提供商（OpenAI 兼容提供商转发 /v1/embeddings，Gemini 转换为 embedContent），
+// 与聊天请求共用Key的作用域、配额、账号选择和用量统计
+func (h *ProxyHandler) HandleEmbeddings(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeErrorResponse(w, http.StatusMethodNotAllowed, "method_not_allowed", "Method not allowed")
+		return
+	}
+
+	startTime := time.Now()
+	r = withRequestID(w, r)
+	record := &stats.UsageRecord{
+		RequestID: requestIDFrom(r.Context()),
+		Timestamp: startTime,
+		Endpoint:  converter.EmbeddingsEndpoint,
+	}
+
+	// 1. 读取并解析请求
+	if !isJSONContentType(r.Header.Get("Content-Type")) {
+		h.writeErrorResponse(w, http.StatusUnsupportedMediaType, "unsupported_media_type", fmt.Sprintf("Unsupported Content-Type %q, expected application/json", r.Header.Get("Content-Type")))
+		return
+	}
+	settings := h.settings()
+	body, err := readRequestBody(w, r, settings.maxRequestBytes)
+	if errors.Is(err, errRequestTooLarge) {
+		h.writeRequestTooLarge(w, settings.maxRequestBytes)
+		return
+	}
+	if err != nil {
+		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_request_body", "Failed to read request body")
+		return
+	}
+	request, err := converter.ParseEmbeddingRequest(body)
+	if err != nil {
+		h.writeErrorResponse(w, http.StatusBadRequest, "request_parse_error", fmt.Sprintf("Failed to parse request: %v", err))
+		return
+	}
+	requestedModel := request.Model
+
+	// 2. 模型路由（优先使用Key级别配置）和提供商
+	gatewayKey, _ := r.Context().Value("gatewayKey").(*types.GatewayAPIKey)
+	var targetProvider types.Provider
+	if modelRoutes := h.modelRoutes(); modelRoutes != nil {
+		if routeContext := modelRoutes.CreateContextWithKey(request.Model, gatewayKey); routeContext != nil && routeContext.Enabled {
+			request.Model = routeContext.TargetModel
+			targetProvider = routeContext.TargetProvider
+		}
+	}
+	if targetProvider == "" {
+		targetProvider = h.router.DetermineProvider(request.Model)
+	}
+
+	keyID := r.Header.Get("X-Gateway-Key-ID")
+	record.GatewayKeyID = keyID
+	record.Model = request.Model
+	record.RequestedModel = requestedModel
+	record.Provider = targetProvider
+	record.App, record.AppVersion, _ = types.ParseClientApp(r.Header.Get(types.ClientAppHeader))
+	if gatewayKey != nil {
+		record.OrgID = gatewayKey.OrgID
+		record.Sandbox = gatewayKey.Sandbox
+	}
+	h.live.add(record)
+	defer h.live.remove(record)
+
+	// 3. 提供商、路径规则和Key作用域检查
+	if !converter.EmbeddingsSupported(targetProvider) {
+		h.writeErrorResponse(w, http.StatusBadRequest, "embeddings_not_supported", fmt.Sprintf("Provider %s does not support embeddings", targetProvider))
+		return
+	}
+	if !h.upstreamMgr.Providers().IsEnabled(targetProvider) {
+		h.finishUsage(record, startTime, "provider_disabled")
+		h.writeErrorResponse(w, http.StatusServiceUnavailable, "provider_disabled", fmt.Sprintf("Provider %s is disabled", targetProvider))
+		return
+	}
+	if status, message := h.checkPathAccess(converter.EmbeddingsEndpoint, targetProvider); status != 0 {
+		h.rejectPath(w, record, startTime, status, message)
+		return
+	}
+	if message := h.checkKeyScopes(gatewayKey, targetProvider, request.Model); message != "" {
+		h.finishUsage(record, startTime, "scope_forbidden")
+		h.writeErrorResponse(w, http.StatusForbidden, "scope_forbidden", message)
+		return
+	}
+
+	// 4. 估算输入token，Key配置了token配额时检查剩余额度
+	counter := tokens.ForProvider(targetProvider)
+	for _, text := range request.Input {
+		record.EstimatedInputTokens += counter.Count(text)
+	}
+	if h.quota != nil && gatewayKey != nil && gatewayKey.Quota != nil {
+		now := time.Now()
+		if result := h.quota.CheckRequest(keyID, gatewayKey.Quota, now, int64(record.EstimatedInputTokens)); result.Exceeded {
+			h.finishUsage(record, startTime, "quota_exceeded")
+			w.Header().Set("Retry-After", strconv.Itoa(int(result.Reset.Sub(now).Seconds()+0.999)))
+			h.writeErrorResponse(w, http.StatusTooManyRequests, "quota_exceeded", fmt.Sprintf("Quota %s exceeded: about %g input tokens requested with %g of %g used, resets at %s", result.Limit, result.Requested, result.Used, result.Max, result.Reset.Format(time.RFC3339)))
+			return
+		}
+	}
+
+	upstreamPath, upstreamBody, err := converter.BuildEmbeddingRequest(request, targetProvider)
+	if err != nil {
+		h.writeErrorResponse(w, http.StatusInternalServerError, "request_transform_error", fmt.Sprintf("Failed to build upstream request: %v", err))
+		return
+	}
+
+	// 5. 选择上游账号并发送，429/5xx或超时时切换账号重试
+	slot := &upstreamSlot{limiter: h.concurrency, queue: h.queue, orgID: record.OrgID}
+	defer slot.Release()
+	if record.Sandbox {
+		h.handleSandboxEmbeddings(w, request, requestedModel, record, startTime)
+		return
+	}
+	account, saturated, err := h.selectUpstream(slot, targetProvider, request.Model, nil)
+	if err != nil {
+		if saturated {
+			h.finishUsage(record, startTime, "upstream_concurrency_exceeded")
+			w.Header().Set("Retry-After", "1")
+			h.writeErrorResponse(w, http.StatusTooManyRequests, "upstream_concurrency_exceeded", fmt.Sprintf("All upstream accounts for provider %s are at their concurrency limit", targetProvider))
+			return
+		}
+		h.writeNoUpstream(w, record, startTime, targetProvider, err)
+		return
+	}
+
+	// 账号选择和URL构建（如Azure的部署名）只需要模型和请求ID
+	unified := &types.UnifiedRequest{Model: request.Model, GatewayKeyID: keyID, RequestID: record.RequestID, UpstreamID: account.ID}
+	record.UpstreamID = account.ID
+	record.Pool = account.PoolKey()
+	h.live.setUpstream(record, account)
+	tried := []string{account.ID}
+	responseBody, upstreamReqID, err := h.callEmbeddingsAPI(account, unified, upstreamPath, upstreamBody)
+	for err != nil {
+		next := h.failoverUpstream(slot, account, request.Model, tried, err)
+		if next == nil {
+			break
+		}
+		account = next
+		tried = append(tried, account.ID)
+		h.switchUpstream(unified, record, account)
+		responseBody, upstreamReqID, err = h.callEmbeddingsAPI(account, unified, upstreamPath, upstreamBody)
+	}
+	record.UpstreamRequestID = upstreamReqID
+	if err != nil {
+		err = h.wrapTimeout(err, 0)
+		record.ErrorClass = upstreamErrorClass(err)
+		h.finishUsage(record, startTime, upstreamErrorType(err))
+		h.handleUpstreamError(w, account, err)
+		return
+	}
+
+	// 6. 转换为 OpenAI 格式，上游没有返回用量时（Gemini）使用估算的token数
+	result, err := converter.ParseEmbeddingResponse(responseBody, targetProvider, len(request.Input))
+	if err != nil {
+		h.finishUsage(record, startTime, "response_transform_error")
+		h.writeErrorResponse(w, http.StatusBadGateway, "response_transform_error", fmt.Sprintf("Failed to parse upstream response: %v", err))
+		return
+	}
+	if !result.UsagePresent {
+		result.InputTokens = record.EstimatedInputTokens
+	}
+	responseModel := requestedModel
+	if result.Model != "" && requestedModel == request.Model {
+		responseModel = result.Model
+	}
+	clientBody, err := converter.BuildEmbeddingResponse(result, responseModel, request.EncodingFormat)
+	if err != nil {
+		h.finishUsage(record, startTime, "response_transform_error")
+		h.writeErrorResponse(w, http.StatusInternalServerError, "response_transform_error", fmt.Sprintf("Failed to transform response: %v", err))
+		return
+	}
+
+	applyUsage(record, converter.StreamUsage{InputTokens: result.InputTokens})
+	h.finishUsage(record, startTime, "")
+	duration := time.Since(startTime)
+	upstreamID := account.ID
+	h.drain.async(func() { h.recordSuccess(keyID, upstreamID, duration, result.InputTokens) })
+
+	if settings.usageHeaders {
+		w.Header().Set("X-Gateway-Cost-USD", strconv.FormatFloat(record.CostUSD, 'f', 6, 64))
+		w.Header().Set("X-Gateway-Input-Tokens", strconv.Itoa(record.InputTokens))
+		w.Header().Set("X-Gateway-Output-Tokens", "0")
+	}
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(http.StatusOK)
+	_, _ = w.Write(clientBody)
+}
+
+// callEmbeddingsAPI 向账号发送嵌入请求，返回原始响应字节和上游的请求ID
+func (h *ProxyHandler) callEmbeddingsAPI(account *types.UpstreamAccount, request *types.UnifiedRequest, path string, body []byte) ([]byte, string, error) {
+	upstreamReq, err := h.newUpstreamRequest(account, request, path, body)
+	if err != nil {
+		return nil, "", fmt.Errorf("failed to build upstream request: %w", err)
+	}
+	body, _, requestID, err := h.sendUpstreamRequest(account, upstreamReq, 0, nil)
+	return body, requestID, err
+}
+
+// handleSandboxEmbeddings 沙箱Key的嵌入请求由模拟响应器生成确定的向量，用量照常记录
+func (h *ProxyHandler) handleSandboxEmbeddings(w http.ResponseWriter, request *converter.EmbeddingRequest, requestedModel string, record *stats.UsageRecord, startTime time.Time) {
+	record.UpstreamID = sandbox.AccountID
+	settings := h.settings()
+	responseBody, err := settings.sandbox.Embeddings(request.Model, request.Input, request.Dimensions, record.Provider)
+	if err == nil {
+		var result *converter.EmbeddingResult
+		if result, err = converter.ParseEmbeddingResponse(responseBody, record.Provider, len(request.Input)); err == nil {
+			if !result.UsagePresent {
+				result.InputTokens = record.EstimatedInputTokens
+			}
+			responseBody, err = converter.BuildEmbeddingResponse(result, requestedModel, request.EncodingFormat)
+			applyUsage(record, converter.StreamUsage{InputTokens: result.InputTokens})
+		}
+	}
+	if err != nil {
+		h.finishUsage(record, startTime, "response_transform_error")
+		h.writeErrorResponse(w, http.StatusInternalServerError, "response_transform_error", fmt.Sprintf("Failed to transform response: %v", err))
+		return
+	}
+
+	h.finishUsage(record, startTime, "")
+	keyID, inputTokens, duration := record.GatewayKeyID, record.InputTokens, time.Since(startTime)
+	h.drain.async(func() { h.recordSuccess(keyID, sandbox.AccountID, duration, inputTokens) })
+
+	if settings.usageHeaders {
+		w.Header().Set("X-Gateway-Cost-USD", strconv.FormatFloat(record.CostUSD, 'f', 6, 64))
+		w.Header().Set("X-Gateway-Input-Tokens", strconv.Itoa(record.InputTokens))
+		w.Header().Set("X-Gateway-Output-Tokens", "0")
+	}
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(http.StatusOK)
+	_, _ = w.Write(responseBody)
+}
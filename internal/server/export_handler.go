@@ -0,0 +1,163 @@
+package server
+
+import (
+	"encoding/csv"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// exportPageSize 导出时每次从记录存储读取的记录数
+const exportPageSize = 1000
+
+// exportColumns CSV导出的列
+var exportColumns = []string{
+	"request_id", "timestamp", "gateway_key_id", "gateway_key_name", "app", "app_version",
+	"upstream_id", "provider", "model", "requested_model", "endpoint", "stream", "success", "error_type", "error_class",
+	"termination_reason", "finish_reason", "queue_time_ms", "latency_ms", "input_tokens", "output_tokens", "cache_read_tokens", "cache_write_tokens", "cost_usd",
+}
+
+// HandleUsageExport 以CSV（默认）或JSONL流式导出使用记录，可按 since/until（RFC3339 或 YYYY-MM-DD，
+// until 为日期时包含当天）、key_id 和 provider 过滤。记录按游标分页读取并逐页写出，不在内存中拼装整个结果
+func (h *WebHandler) HandleUsageExport(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	query := r.URL.Query()
+	filter := stats.Filter{
+		GatewayKeyID: query.Get("key_id"),
+		Provider:     types.Provider(query.Get("provider")),
+	}
+	for name, target := range map[string]*time.Time{"since": &filter.Since, "until": &filter.Until} {
+		if value := query.Get(name); value != "" {
+			parsed, err := parseExportTime(value, name == "until")
+			if err != nil {
+				h.writeError(w, http.StatusBadRequest, name+" must be an RFC3339 timestamp or a YYYY-MM-DD date")
+				return
+			}
+			*target = parsed
+		}
+	}
+
+	format := query.Get("format")
+	switch format {
+	case "", "csv":
+		format = "csv"
+		w.Header().Set("Content-Type", "text/csv; charset=utf-8")
+	case "jsonl":
+		w.Header().Set("Content-Type", "application/x-ndjson")
+	default:
+		h.writeError(w, http.StatusBadRequest, "format must be csv or jsonl")
+		return
+	}
+
+	keyNames := make(map[string]string)
+	for _, key := range h.configMgr.ListGatewayKeys() {
+		keyNames[key.ID] = key.Name
+	}
+
+	w.Header().Set("Content-Disposition", fmt.Sprintf("attachment; filename=usage-%s.%s", time.Now().UTC().Format("20060102-150405"), format))
+	w.Header().Set("Cache-Control", "no-cache")
+	w.WriteHeader(http.StatusOK)
+	flusher, _ := w.(http.Flusher)
+
+	var writeRecord func(record *stats.UsageRecord) error
+	var flush func() error
+	if format == "csv" {
+		writer := csv.NewWriter(w)
+		_ = writer.Write(exportColumns)
+		writeRecord = func(record *stats.UsageRecord) error {
+			return writer.Write(exportRow(record, keyNames[record.GatewayKeyID]))
+		}
+		flush = func() error {
+			writer.Flush()
+			return writer.Error()
+		}
+	} else {
+		encoder := json.NewEncoder(w)
+		writeRecord = func(record *stats.UsageRecord) error {
+			return encoder.Encode(record)
+		}
+		flush = func() error { return nil }
+	}
+
+	exported := 0
+	for cursor := int64(0); cursor >= 0; {
+		if r.Context().Err() != nil {
+			logger.Warn("Usage export cancelled by client after %d records", exported)
+			return
+		}
+
+		var page []stats.UsageRecord
+		page, cursor = h.recorder.QueryPage(filter, cursor, exportPageSize)
+		for i := range page {
+			if err := writeRecord(&page[i]); err != nil {
+				logger.Warn("Usage export aborted after %d records: %v", exported, err)
+				return
+			}
+			exported++
+		}
+		if err := flush(); err != nil {
+			logger.Warn("Usage export aborted after %d records: %v", exported, err)
+			return
+		}
+		if flusher != nil {
+			flusher.Flush()
+		}
+	}
+
+	logger.Info("Exported %d usage records as %s by %s", exported, format, h.sessionUser(r))
+}
+
+// parseExportTime 解析RFC3339时间或UTC日期；endOfDay为true时日期解析为次日零点（用作不包含的结束时间）
+func parseExportTime(value string, endOfDay bool) (time.Time, error) {
+	if parsed, err := time.Parse(time.RFC3339, value); err == nil {
+		return parsed, nil
+	}
+	day, err := time.Parse("2006-01-02", value)
+	if err != nil {
+		return time.Time{}, err
+	}
+	if endOfDay {
+		day = day.AddDate(0, 0, 1)
+	}
+	return day, nil
+}
+
+// exportRow 将使用记录转换为CSV行，列顺序与 exportColumns 一致
+func exportRow(record *stats.UsageRecord, keyName string) []string {
+	return []string{
+		record.RequestID,
+		record.Timestamp.UTC().Format(time.RFC3339Nano),
+		record.GatewayKeyID,
+		keyName,
+		record.App,
+		record.AppVersion,
+		record.UpstreamID,
+		string(record.Provider),
+		record.Model,
+		record.RequestedModel,
+		record.Endpoint,
+		strconv.FormatBool(record.Stream),
+		strconv.FormatBool(record.Success),
+		record.ErrorType,
+		string(record.ErrorClass),
+		record.TerminationReason,
+		record.FinishReason,
+		strconv.FormatInt(record.QueueTimeMs, 10),
+		strconv.FormatInt(record.LatencyMs, 10),
+		strconv.Itoa(record.InputTokens),
+		strconv.Itoa(record.OutputTokens),
+		strconv.Itoa(record.CacheReadTokens),
+		strconv.Itoa(record.CacheWriteTokens),
+		strconv.FormatFloat(record.CostUSD, 'f', -1, 64),
+	}
+}
@@ -0,0 +1,201 @@
+package server
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"math"
+	"net"
+	"net/http"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/ratelimit"
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/trim"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// maxErrorBodyBytes 错误信息中保留的上游响应体长度
+const maxErrorBodyBytes = 2048
+
+// contextTrimmedHeader 上下文超限后删减消息重试时返回删除的消息数
+const contextTrimmedHeader = "X-Gateway-Context-Trimmed"
+
+// upstreamStatusError 上游返回非200状态码
+type upstreamStatusError struct {
+	StatusCode int
+	Body       string
+	RequestID  string // 上游返回的请求ID
+	RetryAfter string // 上游返回的 Retry-After 头部
+}
+
+func (e *upstreamStatusError) Error() string {
+	if e.Body == "" {
+		return fmt.Sprintf("upstream API error: status=%d", e.StatusCode)
+	}
+	return fmt.Sprintf("upstream API error: status=%d, body=%s", e.StatusCode, utils.Excerpt([]byte(e.Body), maxErrorBodyBytes))
+}
+
+// ClientError 上游以4xx拒绝了请求内容本身（如400、404、413），说明是客户端请求的问题而不是账号故障，不计入熔断器。
+// 401/403（凭证）、408（超时）和429（限流）仍属于账号问题
+func (e *upstreamStatusError) ClientError() bool {
+	switch e.StatusCode {
+	case http.StatusUnauthorized, http.StatusForbidden, http.StatusRequestTimeout, http.StatusTooManyRequests:
+		return false
+	}
+	return e.StatusCode >= 400 && e.StatusCode < 500
+}
+
+// CredentialRejected 上游以401/403拒绝了账号凭证（密钥被吊销、权限被收回），连续出现时自动停用账号
+func (e *upstreamStatusError) CredentialRejected() bool {
+	return e.StatusCode == http.StatusUnauthorized || e.StatusCode == http.StatusForbidden
+}
+
+// isRetryableUpstreamError 判断错误是否可以切换到其他账号重试：429/500/502/503/504 以及请求超时
+func isRetryableUpstreamError(err error) bool {
+	var statusErr *upstreamStatusError
+	if errors.As(err, &statusErr) {
+		switch statusErr.StatusCode {
+		case http.StatusTooManyRequests,
+			http.StatusInternalServerError,
+			http.StatusBadGateway,
+			http.StatusServiceUnavailable,
+			http.StatusGatewayTimeout:
+			return true
+		}
+		return false
+	}
+	return isTimeoutError(err)
+}
+
+// isTimeoutError 判断错误是否为等待或读取上游响应超时
+func isTimeoutError(err error) bool {
+	if errors.Is(err, context.DeadlineExceeded) {
+		return true
+	}
+	var netErr net.Error
+	return errors.As(err, &netErr) && netErr.Timeout()
+}
+
+// failoverUpstream 当前账号请求失败时选择下一个账号重试
+// tried 为已经尝试过的账号（包含当前账号），返回nil表示不再重试
+func (h *ProxyHandler) failoverUpstream(slot *upstreamSlot, account *types.UpstreamAccount, model string, tried []string, err error) *types.UpstreamAccount {
+	// 过载是提供商整体的问题，不切换同一提供商的账号，只让提供商进入退避窗口
+	h.recordOverload(account, err)
+	if len(tried) > h.settings().maxRetryAttempts || !isRetryableUpstreamError(err) {
+		return nil
+	}
+
+	next, _, selectErr := h.selectUpstream(slot, account.Provider, model, tried)
+	if selectErr != nil {
+		logger.Debug("没有其他可切换的上游账号: %v", selectErr)
+		return nil
+	}
+
+	// 失败的账号计入错误统计，健康优先策略会据此降低其优先级
+	go h.router.MarkUpstreamError(account.ID, err)
+	logger.Warn("上游账号 %s 请求失败，切换到账号 %s 重试（第%d次）: %v", account.ID, next.ID, len(tried), err)
+	return next
+}
+
+// upstreamSlot 请求当前占用的上游账号并发名额，切换账号时归还旧账号的名额
+type upstreamSlot struct {
+	limiter  *ratelimit.ConcurrencyLimiter
+	queue    *ratelimit.Queue // 归还名额时唤醒同一提供商排队的请求，为nil时不排队
+	release  func()
+	provider types.Provider
+	rule     *types.RoutingRule // 请求命中的路由规则，切换账号时仍只在其账号池中选择
+	orgID    string             // Key所属的组织，只在该组织可用的账号中选择
+}
+
+// acquire 占用账号的并发名额，名额已满时返回false并保留当前名额
+func (s *upstreamSlot) acquire(account *types.UpstreamAccount) bool {
+	release, ok := s.limiter.Acquire(account.ID, account.MaxConcurrent)
+	if !ok {
+		return false
+	}
+	s.Release()
+	s.release = release
+	s.provider = account.Provider
+	return true
+}
+
+// Release 归还当前占用的名额
+func (s *upstreamSlot) Release() {
+	if s.release != nil {
+		s.release()
+		s.release = nil
+		if s.queue != nil {
+			s.queue.Signal(string(s.provider))
+		}
+	}
+}
+
+// selectUpstream 选择上游账号并占用其并发名额，跳过名额已满的账号
+// 没有可用账号时 saturated 表示是否有账号因为并发名额已满被跳过
+func (h *ProxyHandler) selectUpstream(slot *upstreamSlot, provider types.Provider, model string, excludeIDs []string) (account *types.UpstreamAccount, saturated bool, err error) {
+	exclude := append([]string(nil), excludeIDs...)
+	for {
+		account, err = h.router.SelectUpstreamForRule(provider, model, slot.orgID, slot.rule, exclude...)
+		if err != nil {
+			return nil, saturated, err
+		}
+		if slot.acquire(account) {
+			return account, false, nil
+		}
+		saturated = true
+		exclude = append(exclude, account.ID)
+	}
+}
+
+// writeNoUpstream 没有可选的账号时返回503。候选账号的熔断器全部打开时返回 circuit_open，
+// Retry-After 为最早有账号恢复试探的剩余秒数（全部为手动打开时不返回）
+func (h *ProxyHandler) writeNoUpstream(w http.ResponseWriter, record *stats.UsageRecord, startTime time.Time, provider types.Provider, err error) {
+	var openErr *router.BreakersOpenError
+	if !errors.As(err, &openErr) {
+		h.finishUsage(record, startTime, "no_upstream_available")
+		h.writeErrorResponse(w, http.StatusServiceUnavailable, "no_upstream_available", fmt.Sprintf("No available upstream for provider %s: %v", provider, err))
+		return
+	}
+
+	h.finishUsage(record, startTime, "circuit_open")
+	if !openErr.RetryAt.IsZero() {
+		retryAfter := int(math.Ceil(time.Until(openErr.RetryAt).Seconds()))
+		if retryAfter < 1 {
+			retryAfter = 1
+		}
+		w.Header().Set("Retry-After", strconv.Itoa(retryAfter))
+	}
+	h.writeErrorResponse(w, http.StatusServiceUnavailable, "circuit_open", fmt.Sprintf("Circuit breakers are open for all %d upstream accounts of provider %s", openErr.Accounts, provider))
+}
+
+// switchUpstream 将请求、统计记录和进行中请求登记的账号切换到新的上游账号
+func (h *ProxyHandler) switchUpstream(request *types.UnifiedRequest, record *stats.UsageRecord, account *types.UpstreamAccount) {
+	request.UpstreamID = account.ID
+	record.UpstreamID = account.ID
+	record.Pool = account.PoolKey()
+	h.live.setUpstream(record, account)
+}
+
+// trimForRetry 上游因超出上下文窗口拒绝请求时，按 proxy.context_trim 删减最早的消息，返回是否应在同一账号上重试一次。
+// 删减后在响应头 X-Gateway-Context-Trimmed 中返回删除的消息数，并在使用记录中标记
+func (h *ProxyHandler) trimForRetry(w http.ResponseWriter, request *types.UnifiedRequest, record *stats.UsageRecord, err error) bool {
+	var statusErr *upstreamStatusError
+	contextTrim := h.settings().contextTrim
+	if !contextTrim.Enabled() || !errors.As(err, &statusErr) || !trim.IsContextWindowError(statusErr.StatusCode, statusErr.Body) {
+		return false
+	}
+
+	removed := contextTrim.Apply(request, record.Provider, statusErr.Body)
+	if removed == 0 {
+		return false
+	}
+	record.ContextTrimmed = true
+	w.Header().Set(contextTrimmedHeader, strconv.Itoa(removed))
+	logger.Warn("请求 %s 超出模型 %s 的上下文窗口，删除最早的%d条消息后重试", request.RequestID, request.Model, removed)
+	return true
+}
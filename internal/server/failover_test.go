@@ -0,0 +1,265 @@
+package server
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"sync"
+	"sync/atomic"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestIsRetryableUpstreamError(t *testing.T) {
+	tests := []struct {
+		name string
+		err  error
+		want bool
+	}{
+		{name: "429", err: &upstreamStatusError{StatusCode: http.StatusTooManyRequests}, want: true},
+		{name: "500", err: &upstreamStatusError{StatusCode: http.StatusInternalServerError}, want: true},
+		{name: "502", err: &upstreamStatusError{StatusCode: http.StatusBadGateway}, want: true},
+		{name: "503", err: &upstreamStatusError{StatusCode: http.StatusServiceUnavailable}, want: true},
+		{name: "504", err: &upstreamStatusError{StatusCode: http.StatusGatewayTimeout}, want: true},
+		{name: "wrapped 503", err: fmt.Errorf("upstream: %w", &upstreamStatusError{StatusCode: http.StatusServiceUnavailable}), want: true},
+		{name: "400", err: &upstreamStatusError{StatusCode: http.StatusBadRequest}, want: false},
+		{name: "401", err: &upstreamStatusError{StatusCode: http.StatusUnauthorized}, want: false},
+		{name: "404", err: &upstreamStatusError{StatusCode: http.StatusNotFound}, want: false},
+		{name: "529 overloaded", err: &upstreamStatusError{StatusCode: upstream.StatusOverloaded}, want: false},
+		{name: "deadline exceeded", err: fmt.Errorf("upstream request failed: %w", context.DeadlineExceeded), want: true},
+		{name: "net timeout", err: fmt.Errorf("read body: %w", timeoutNetError{}), want: true},
+		{name: "upstream timeout", err: &upstreamTimeoutError{Timeout: time.Second, Err: context.DeadlineExceeded}, want: true},
+		{name: "connection refused", err: errors.New("dial tcp: connection refused"), want: false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := isRetryableUpstreamError(tt.err); got != tt.want {
+				t.Errorf("isRetryableUpstreamError() = %v, want %v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestOverloadedError(t *testing.T) {
+	tests := []struct {
+		name string
+		err  error
+		want bool
+	}{
+		{name: "529", err: &upstreamStatusError{StatusCode: upstream.StatusOverloaded}, want: true},
+		{name: "503 overloaded_error", err: &upstreamStatusError{StatusCode: http.StatusServiceUnavailable, Body: `{"error":{"type":"overloaded_error"}}`}, want: true},
+		{name: "503 Overloaded_Error", err: &upstreamStatusError{StatusCode: http.StatusServiceUnavailable, Body: `{"error":{"type":"Overloaded_Error"}}`}, want: true},
+		{name: "400 overloaded_error", err: &upstreamStatusError{StatusCode: http.StatusBadRequest, Body: `{"error":{"type":"overloaded_error"}}`}, want: false},
+		{name: "503", err: &upstreamStatusError{StatusCode: http.StatusServiceUnavailable}, want: false},
+		{name: "not a status error", err: errors.New("overloaded_error"), want: false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if _, got := overloadedError(tt.err); got != tt.want {
+				t.Errorf("overloadedError() = %v, want %v", got, tt.want)
+			}
+		})
+	}
+}
+
+// chatCompletionBody 上游返回的 OpenAI 聊天响应
+const chatCompletionBody = `{"id":"chatcmpl-1","object":"chat.completion","created":1,"model":"gpt-4o",` +
+	`"choices":[{"index":0,"message":{"role":"assistant","content":"from backup"},"finish_reason":"stop"}],` +
+	`"usage":{"prompt_tokens":1,"completion_tokens":2,"total_tokens":3}}`
+
+func TestFailover_SwitchesToNextAccount(t *testing.T) {
+	tests := []struct {
+		name          string
+		primaryStatus int
+		wantStatus    int
+		wantBackup    int32
+	}{
+		{name: "503 fails over", primaryStatus: http.StatusServiceUnavailable, wantStatus: http.StatusOK, wantBackup: 1},
+		{name: "504 fails over", primaryStatus: http.StatusGatewayTimeout, wantStatus: http.StatusOK, wantBackup: 1},
+		{name: "400 is not retried", primaryStatus: http.StatusBadRequest, wantStatus: http.StatusBadGateway, wantBackup: 0},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var primaryHits, backupHits int32
+			primary := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+				atomic.AddInt32(&primaryHits, 1)
+				w.WriteHeader(tt.primaryStatus)
+			}))
+			defer primary.Close()
+			backup := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+				atomic.AddInt32(&backupHits, 1)
+				w.Header().Set("Content-Type", "application/json")
+				_, _ = w.Write([]byte(chatCompletionBody))
+			}))
+			defer backup.Close()
+
+			// 优先级数字小的账号先被选中，失败后才切换到备用账号
+			h, _ := newUpstreamTestHandler(t, types.ProxyConfig{},
+				testOpenAIAccount("primary", primary.URL, 0),
+				testOpenAIAccount("backup", backup.URL, 1))
+
+			rec := postChat(h, nil)
+			if rec.Code != tt.wantStatus {
+				t.Fatalf("status = %d, want %d, body = %s", rec.Code, tt.wantStatus, rec.Body.String())
+			}
+			if got := atomic.LoadInt32(&primaryHits); got != 1 {
+				t.Errorf("primary requests = %d, want 1", got)
+			}
+			if got := atomic.LoadInt32(&backupHits); got != tt.wantBackup {
+				t.Errorf("backup requests = %d, want %d", got, tt.wantBackup)
+			}
+			if tt.wantStatus == http.StatusOK && !strings.Contains(rec.Body.String(), "from backup") {
+				t.Errorf("body = %s, want the backup account's response", rec.Body.String())
+			}
+		})
+	}
+}
+
+// brokenStreamServer 返回一个发出第一个流式事件后就断开连接的上游
+func brokenStreamServer() *httptest.Server {
+	return httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "text/event-stream")
+		_, _ = w.Write([]byte("data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hi\"}}]}\n\n"))
+		w.(http.Flusher).Flush()
+		conn, _, err := w.(http.Hijacker).Hijack()
+		if err == nil {
+			_ = conn.Close()
+		}
+	}))
+}
+
+// postStreamChat 发送流式聊天请求
+func postStreamChat(h *ProxyHandler) *httptest.ResponseRecorder {
+	req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", strings.NewReader(`{"model":"gpt-4o","stream":true,"messages":[{"role":"user","content":"hi"}]}`))
+	req.Header.Set("Content-Type", "application/json")
+	rec := httptest.NewRecorder()
+	h.HandleChatCompletions(rec, req)
+	return rec
+}
+
+func TestStreamError_CountsAsFailure(t *testing.T) {
+	server := brokenStreamServer()
+	defer server.Close()
+
+	h, _ := newUpstreamTestHandler(t, types.ProxyConfig{}, testOpenAIAccount("primary", server.URL, 0))
+	breakers := h.upstreamMgr.Breakers()
+	breakers.Configure(func() *types.CircuitBreakerConfig { return &types.CircuitBreakerConfig{FailureThreshold: 1} })
+
+	postStreamChat(h)
+
+	// 中途失败的流不算成功，按失败计入熔断器
+	deadline := time.Now().Add(2 * time.Second)
+	for {
+		state, _ := breakers.State("primary")
+		if state == upstream.BreakerOpen {
+			break
+		}
+		if time.Now().After(deadline) {
+			t.Fatalf("breaker = %s, want open after the stream failed", state)
+		}
+		time.Sleep(10 * time.Millisecond)
+	}
+}
+
+// recordingOverloadStore 记录过载退避状态的每次变化
+type recordingOverloadStore struct {
+	mutex  sync.Mutex
+	states []upstream.OverloadState
+}
+
+func (s *recordingOverloadStore) Publish(provider types.Provider, state upstream.OverloadState) error {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	s.states = append(s.states, state)
+	return nil
+}
+
+func (s *recordingOverloadStore) Load() (map[types.Provider]upstream.OverloadState, error) {
+	return nil, nil
+}
+
+// cleared 返回连续过载次数是否被清零过
+func (s *recordingOverloadStore) cleared() bool {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	for _, state := range s.states {
+		if state.Strikes == 0 {
+			return true
+		}
+	}
+	return false
+}
+
+func TestStreamError_KeepsOverloadStrikes(t *testing.T) {
+	broken := brokenStreamServer()
+	defer broken.Close()
+
+	h, _ := newUpstreamTestHandler(t, types.ProxyConfig{}, testOpenAIAccount("primary", broken.URL, 0))
+	overloads := h.upstreamMgr.Overloads()
+	overloads.Configure(&types.OverloadBackoffConfig{Enabled: true})
+	store := &recordingOverloadStore{}
+	overloads.SetStore(store)
+	// 之前的过载窗口已经结束，但还没有成功的请求
+	overloads.Record(types.ProviderOpenAI, 0, time.Now().Add(-time.Hour))
+
+	// 中途失败的流不说明提供商已经恢复
+	h.drain = &drainGate{}
+	postStreamChat(h)
+	ctx, cancel := context.WithTimeout(context.Background(), 2*time.Second)
+	defer cancel()
+	if !h.drain.flush(ctx) {
+		t.Fatal("stats writes did not finish")
+	}
+	if store.cleared() {
+		t.Fatal("a failed stream cleared the provider's overload strikes")
+	}
+
+	h.recordSuccess("", "primary", time.Millisecond, 0)
+	if !store.cleared() {
+		t.Error("a successful request did not clear the provider's overload strikes")
+	}
+}
+
+func TestAllBreakersOpen_Returns503(t *testing.T) {
+	var calls int32
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		atomic.AddInt32(&calls, 1)
+		w.Header().Set("Content-Type", "application/json")
+		_, _ = w.Write([]byte(chatCompletionBody))
+	}))
+	defer server.Close()
+
+	h, _ := newUpstreamTestHandler(t, types.ProxyConfig{},
+		testOpenAIAccount("primary", server.URL, 0), testOpenAIAccount("backup", server.URL, 1))
+	breakers := h.upstreamMgr.Breakers()
+	breakers.Configure(func() *types.CircuitBreakerConfig {
+		return &types.CircuitBreakerConfig{FailureThreshold: 1, OpenSeconds: 30}
+	})
+	breakers.RecordFailure("primary", errors.New("upstream down"), time.Now().Add(-10*time.Second))
+	breakers.RecordFailure("backup", errors.New("upstream down"), time.Now())
+
+	// 所有账号都熔断时不再把请求发给出错的账号，而是告诉客户端最早什么时候重试
+	rec := postChat(h, nil)
+	if rec.Code != http.StatusServiceUnavailable || !strings.Contains(rec.Body.String(), "circuit_open") {
+		t.Fatalf("status = %d, body = %s, want 503 circuit_open", rec.Code, rec.Body.String())
+	}
+	if got := rec.Header().Get("Retry-After"); got != "20" {
+		t.Errorf("Retry-After = %q, want 20 (primary half-opens first)", got)
+	}
+	if got := atomic.LoadInt32(&calls); got != 0 {
+		t.Errorf("upstream received %d requests while every breaker was open", got)
+	}
+
+	// 熔断器关闭后恢复路由
+	breakers.Reset("primary", "test", time.Now())
+	if rec := postChat(h, nil); rec.Code != http.StatusOK {
+		t.Errorf("after reset: status = %d, want 200", rec.Code)
+	}
+}
@@ -0,0 +1,71 @@
+package server
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/internal/converter"
+)
+
+// geminiModelsPrefix Gemini原生端点的路径前缀
+const geminiModelsPrefix = "/v1beta/models/"
+
+// HandleGemini 处理Gemini原生端点：
+// POST /v1beta/models/{model}:generateContent
+// POST /v1beta/models/{model}:streamGenerateContent?alt=sse
+// 模型名和是否流式从路径中取出写入请求体，之后与其他端点共用同一代理流程
+func (h *ProxyHandler) HandleGemini(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeErrorResponse(w, http.StatusMethodNotAllowed, "method_not_allowed", "Method not allowed")
+		return
+	}
+
+	model, method, ok := strings.Cut(strings.TrimPrefix(r.URL.Path, geminiModelsPrefix), ":")
+	if !ok || model == "" {
+		h.writeErrorResponse(w, http.StatusNotFound, "path_not_found", fmt.Sprintf("Path %s is not available through the gateway", r.URL.Path))
+		return
+	}
+
+	stream := false
+	switch method {
+	case "generateContent":
+	case "streamGenerateContent":
+		// 只支持SSE流（Gemini SDK的默认方式），不支持JSON数组流
+		if r.URL.Query().Get("alt") != "sse" {
+			h.writeErrorResponse(w, http.StatusBadRequest, "unsupported_stream_format", "streamGenerateContent requires alt=sse")
+			return
+		}
+		stream = true
+	default:
+		h.writeErrorResponse(w, http.StatusNotFound, "unsupported_method", fmt.Sprintf("Gemini method %q is not supported", method))
+		return
+	}
+
+	maxRequestBytes := h.settings().maxRequestBytes
+	body, err := readRequestBody(w, r, maxRequestBytes)
+	if errors.Is(err, errRequestTooLarge) {
+		h.writeRequestTooLarge(w, maxRequestBytes)
+		return
+	}
+	if err != nil {
+		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_request_body", "Failed to read request body")
+		return
+	}
+	var request map[string]interface{}
+	if err := json.Unmarshal(body, &request); err != nil {
+		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_request_body", "Request body must be a JSON object")
+		return
+	}
+	request["model"] = model
+	request["stream"] = stream
+	body, _ = json.Marshal(request)
+
+	r.Body = io.NopCloser(bytes.NewReader(body))
+	r.ContentLength = int64(len(body))
+	h.handleProxyRequest(w, r, converter.GeminiEndpoint)
+}
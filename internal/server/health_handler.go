@@ -0,0 +1,408 @@
+package server
+
+import (
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleUpstreamHealth 批量探测上游账号健康状态，未指定ids时探测所有未禁用的账号。
+// 请求 ?stream=1 或 Accept: application/x-ndjson 时每完成一个账号输出一行结果，最后一行为汇总
+func (h *WebHandler) HandleUpstreamHealth(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	var req struct {
+		IDs []string `json:"ids"`
+	}
+	// 请求体可以为空
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil && err != io.EOF {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	ids := req.IDs
+	if len(ids) == 0 {
+		ids = h.healthSvc.EnabledIDs()
+	}
+
+	if wantsHealthStream(r) {
+		h.streamUpstreamHealth(w, r, ids)
+		return
+	}
+
+	results := h.healthSvc.CheckMany(ids)
+	summary := healthSummary(results)
+	logger.Info("Upstream health check: %d/%d healthy", summary["healthy"], summary["total"])
+
+	summary["data"] = results
+	h.writeJSON(w, http.StatusOK, summary)
+}
+
+// wantsHealthStream 判断批量探测是否以NDJSON流式返回
+func wantsHealthStream(r *http.Request) bool {
+	stream := r.URL.Query().Get("stream")
+	return stream == "1" || stream == "true" || strings.Contains(r.Header.Get("Accept"), "application/x-ndjson")
+}
+
+// streamUpstreamHealth 以NDJSON逐行返回探测结果：{"type":"result","data":{...}}，最后一行为 {"type":"summary",...}。
+// 客户端断开后不再开始新的探测，已完成的结果仍会保存
+func (h *WebHandler) streamUpstreamHealth(w http.ResponseWriter, r *http.Request, ids []string) {
+	flusher, _ := w.(http.Flusher)
+	w.Header().Set("Content-Type", "application/x-ndjson")
+	w.Header().Set("Cache-Control", "no-cache")
+	w.WriteHeader(http.StatusOK)
+
+	encoder := json.NewEncoder(w)
+	var results []*upstream.HealthResult
+	h.healthSvc.CheckEach(r.Context(), ids, func(_ int, result *upstream.HealthResult) {
+		results = append(results, result)
+		_ = encoder.Encode(map[string]interface{}{"type": "result", "data": result})
+		if flusher != nil {
+			flusher.Flush()
+		}
+	})
+
+	summary := healthSummary(results)
+	logger.Info("Upstream health check (streamed): %d/%d healthy", summary["healthy"], summary["total"])
+
+	summary["type"] = "summary"
+	_ = encoder.Encode(summary)
+	if flusher != nil {
+		flusher.Flush()
+	}
+}
+
+// healthSummary 统计探测结果中健康与不健康的账号数
+func healthSummary(results []*upstream.HealthResult) map[string]interface{} {
+	healthy := 0
+	for _, result := range results {
+		if result.Healthy {
+			healthy++
+		}
+	}
+	return map[string]interface{}{
+		"total":     len(results),
+		"healthy":   healthy,
+		"unhealthy": len(results) - healthy,
+	}
+}
+
+// handleUpstreamHealth 探测单个上游账号（POST），或查询其探测历史（GET，?limit=N，从新到旧）
+func (h *WebHandler) handleUpstreamHealth(w http.ResponseWriter, r *http.Request, upstreamID string) {
+	if r.Method != http.MethodPost && r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	if _, err := h.upstreamMgr.GetAccount(upstreamID); err != nil {
+		h.writeError(w, http.StatusNotFound, "Upstream account not found")
+		return
+	}
+
+	if r.Method == http.MethodGet {
+		limit, ok := h.parseLimit(w, r)
+		if !ok {
+			return
+		}
+		history := h.healthSvc.History(upstreamID, limit)
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data":  history,
+			"total": len(history),
+		})
+		return
+	}
+
+	result, err := h.healthSvc.Check(upstreamID)
+	if err != nil {
+		logger.Error("Failed to check upstream health %s: %v", upstreamID, err)
+	}
+	if result == nil {
+		h.writeError(w, http.StatusInternalServerError, "Failed to check upstream health")
+		return
+	}
+
+	h.writeJSON(w, http.StatusOK, result)
+}
+
+// handleBreakerHistory 查询上游账号熔断器的当前状态和状态转换记录（?limit=N，从新到旧）
+func (h *WebHandler) handleBreakerHistory(w http.ResponseWriter, r *http.Request, upstreamID string) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	if _, err := h.upstreamMgr.GetAccount(upstreamID); err != nil {
+		h.writeError(w, http.StatusNotFound, "Upstream account not found")
+		return
+	}
+
+	limit, ok := h.parseLimit(w, r)
+	if !ok {
+		return
+	}
+
+	breakers := h.upstreamMgr.Breakers()
+	state, failures := breakers.State(upstreamID)
+	history := breakers.History(upstreamID, limit)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"state":                state,
+		"consecutive_failures": failures,
+		"data":                 history,
+		"total":                len(history),
+	})
+}
+
+// parseLimit 解析 ?limit=N，未指定时为0（全部），无效时返回400
+func (h *WebHandler) parseLimit(w http.ResponseWriter, r *http.Request) (int, bool) {
+	value := r.URL.Query().Get("limit")
+	if value == "" {
+		return 0, true
+	}
+	limit, err := strconv.Atoi(value)
+	if err != nil || limit < 0 {
+		h.writeError(w, http.StatusBadRequest, "Invalid limit")
+		return 0, false
+	}
+	return limit, true
+}
+
+// verifyUpstreamCredentials 保存前验证API Key账号的凭证：上游拒绝凭证（401/403）时返回422且不保存；
+// 其他失败（网络错误、限流等）无法判断凭证是否有效，账号照常保存但标记为不健康
+func (h *WebHandler) verifyUpstreamCredentials(w http.ResponseWriter, account *types.UpstreamAccount, skip bool) (*upstream.HealthResult, bool) {
+	if skip {
+		return nil, true
+	}
+
+	result := h.healthSvc.Verify(account)
+	if result == nil {
+		return nil, true
+	}
+	if result.CredentialRejected() {
+		logger.Warn("Upstream rejected credentials for account %s: %s", account.Name, result.Error)
+		h.writeJSON(w, http.StatusUnprocessableEntity, map[string]interface{}{
+			"error":        "Upstream rejected the credentials",
+			"verification": result,
+		})
+		return result, false
+	}
+
+	upstream.ApplyHealthResult(account, result)
+	return result, true
+}
+
+// addVerification 在创建/更新响应中附加凭证验证结果，验证未通过时附加警告
+func addVerification(response map[string]interface{}, result *upstream.HealthResult) {
+	if result == nil {
+		return
+	}
+	response["verification"] = result
+	if !result.Healthy {
+		response["warning"] = "Credentials could not be verified; the account was saved as unhealthy"
+	}
+}
+
+// handleUpdateUpstream 更新上游账号的名称、API Key、端点、路由权重和优先级或账号池（PUT /api/v1/upstream/{id}），凭证或端点变化时先验证
+func (h *WebHandler) handleUpdateUpstream(w http.ResponseWriter, r *http.Request, upstreamID string) {
+	existing, err := h.upstreamMgr.GetAccount(upstreamID)
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "Upstream account not found")
+		return
+	}
+
+	var req struct {
+		Name       *string `json:"name,omitempty"`
+		APIKey     *string `json:"api_key,omitempty"`
+		BaseURL    *string `json:"base_url,omitempty"`
+		APIVersion *string `json:"api_version,omitempty"` // 空字符串取消固定，恢复默认版本
+		Weight     *int    `json:"weight,omitempty"`      // 0表示不分配流量
+		Priority   *int    `json:"priority,omitempty"`
+		Status     *string `json:"status,omitempty"` // active 重新启用（清除自动停用原因）或 disabled 手动停用
+		OrgID      *string `json:"org_id,omitempty"` // 所属组织，空字符串表示所有Key共用
+		Pool       *string `json:"pool,omitempty"`   // 账号池名称，空字符串表示退出账号池
+		SkipVerify bool    `json:"skip_verify,omitempty"`
+
+		Deployments map[string]string     `json:"deployments,omitempty"` // 替换整个部署映射，空对象清除映射
+		AWS         *types.AWSCredentials `json:"aws,omitempty"`         // 替换 Bedrock 账号的AWS凭证
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+	if req.APIKey != nil && (existing.Type != types.UpstreamTypeAPIKey || *req.APIKey == "") {
+		h.writeError(w, http.StatusBadRequest, "api_key can only be set to a non-empty value on api-key accounts")
+		return
+	}
+	if req.Status != nil && *req.Status != "active" && *req.Status != "disabled" {
+		h.writeError(w, http.StatusBadRequest, "status must be active or disabled")
+		return
+	}
+	if req.OrgID != nil {
+		if message := h.validateOrgID(*req.OrgID); message != "" {
+			h.writeError(w, http.StatusBadRequest, message)
+			return
+		}
+	}
+	if req.APIVersion != nil {
+		if message := h.validateAPIVersion(existing.Provider, *req.APIVersion); message != "" {
+			h.writeError(w, http.StatusBadRequest, message)
+			return
+		}
+	}
+
+	// 在副本上应用修改并验证，验证通过后再写入配置
+	updated := *existing
+	if req.Name != nil && *req.Name != "" {
+		updated.Name = *req.Name
+	}
+	if req.APIKey != nil {
+		updated.APIKey = *req.APIKey
+	}
+	if req.BaseURL != nil {
+		updated.BaseURL = *req.BaseURL
+	}
+	if req.APIVersion != nil {
+		updated.APIVersion = *req.APIVersion
+	}
+	if req.Weight != nil {
+		updated.Weight = req.Weight
+	}
+	if req.Priority != nil {
+		updated.Priority = *req.Priority
+	}
+	if req.OrgID != nil {
+		updated.OrgID = *req.OrgID
+	}
+	if req.Pool != nil {
+		updated.Pool = strings.TrimSpace(*req.Pool)
+	}
+	if req.Deployments != nil {
+		updated.Deployments = req.Deployments
+	}
+	if req.AWS != nil {
+		if message := validateAWSCredentials(existing.Provider, req.AWS); message != "" {
+			h.writeError(w, http.StatusBadRequest, message)
+			return
+		}
+		updated.AWS = req.AWS
+	}
+	if message := validateRouting(updated.Weight, updated.Priority); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+	if message := validateDeployments(updated.Provider, updated.BaseURL, updated.Deployments); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+
+	var verification *upstream.HealthResult
+	if updated.APIKey != existing.APIKey || updated.BaseURL != existing.BaseURL || req.AWS != nil {
+		var ok bool
+		if verification, ok = h.verifyUpstreamCredentials(w, &updated, req.SkipVerify); !ok {
+			return
+		}
+	}
+
+	err = h.configMgr.UpdateUpstreamAccount(upstreamID, func(account *types.UpstreamAccount) error {
+		account.Name = updated.Name
+		account.APIKey = updated.APIKey
+		account.BaseURL = updated.BaseURL
+		account.APIVersion = updated.APIVersion
+		account.Weight = updated.Weight
+		account.Priority = updated.Priority
+		account.Deployments = updated.Deployments
+		account.AWS = updated.AWS
+		account.OrgID = updated.OrgID
+		account.Pool = updated.Pool
+		if req.Status != nil && *req.Status != account.Status {
+			account.Status = *req.Status
+			account.DisabledReason = ""
+		}
+		if verification != nil {
+			upstream.ApplyHealthResult(account, verification)
+		}
+		account.UpdatedAt = time.Now()
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update upstream account %s: %v", upstreamID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update upstream account")
+		return
+	}
+	if req.Status != nil && *req.Status == "active" {
+		h.upstreamMgr.RecordAuthSuccess(upstreamID) // 重新启用后重新开始统计连续凭证拒绝
+	}
+
+	logger.Info("Updated upstream account: %s (%s)", updated.Name, upstreamID)
+	response := map[string]interface{}{"id": upstreamID}
+	addVerification(response, verification)
+	h.writeJSON(w, http.StatusOK, response)
+}
+
+// validateAPIVersion 检查要固定的上游API版本，返回错误信息，合法或为空（不固定）时返回空字符串
+func (h *WebHandler) validateAPIVersion(provider types.Provider, version string) string {
+	if version == "" {
+		return ""
+	}
+	if !h.upstreamMgr.SupportsAPIVersion(provider) {
+		return fmt.Sprintf("api_version is not supported for provider %s", provider)
+	}
+	if !types.ValidAPIVersion(version) {
+		return "api_version must start with a letter or digit and contain only letters, digits, '.', '_' or '-' (max 64)"
+	}
+	return ""
+}
+
+// validateRouting 检查账号的路由权重和优先级，返回错误信息，合法时返回空字符串
+func validateRouting(weight *int, priority int) string {
+	if weight != nil && (*weight < 0 || *weight > types.MaxUpstreamWeight) {
+		return fmt.Sprintf("weight must be between 0 and %d", types.MaxUpstreamWeight)
+	}
+	if priority < 0 {
+		return "priority must not be negative"
+	}
+	return ""
+}
+
+// validateDeployments 检查 Azure OpenAI 和 OpenAI 兼容账号的端点，以及部署映射（Bedrock 为模型ID映射），返回错误信息，合法时返回空字符串
+func validateDeployments(provider types.Provider, baseURL string, deployments map[string]string) string {
+	if provider == types.ProviderAzure && baseURL == "" {
+		return "base_url is required for azure accounts (e.g. https://your-resource.openai.azure.com)"
+	}
+	if provider == types.ProviderOpenAICompatible && baseURL == "" {
+		return "base_url is required for openai-compatible accounts (e.g. http://localhost:11434)"
+	}
+	if len(deployments) > 0 && provider != types.ProviderAzure && provider != types.ProviderBedrock {
+		return "deployments are only supported for azure and bedrock accounts"
+	}
+	for model, deployment := range deployments {
+		if model == "" || deployment == "" {
+			return "deployments must map non-empty model names to non-empty deployment names"
+		}
+	}
+	return ""
+}
+
+// validateAWSCredentials 检查 Bedrock 账号的AWS凭证，返回错误信息，合法时返回空字符串
+func validateAWSCredentials(provider types.Provider, credentials *types.AWSCredentials) string {
+	if provider != types.ProviderBedrock {
+		if credentials != nil {
+			return "aws credentials are only supported for bedrock accounts"
+		}
+		return ""
+	}
+	if credentials == nil || credentials.AccessKeyID == "" || credentials.SecretAccessKey == "" || credentials.Region == "" {
+		return "aws.access_key_id, aws.secret_access_key and aws.region are required for bedrock accounts"
+	}
+	return ""
+}
@@ -0,0 +1,119 @@
+package server
+
+import (
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/notify"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// apiKeyAccess /api/v1/apikeys 的权限：开启自助创建后 operator 也可以创建Key（由 handleCreateAPIKey 检查设置），其他修改需要 admin
+func apiKeyAccess(r *http.Request) string {
+	switch r.Method {
+	case http.MethodGet, http.MethodHead:
+		return types.RoleViewer
+	case http.MethodPost:
+		return types.RoleOperator
+	}
+	return types.RoleAdmin
+}
+
+// HandleKeyCreationSettings 查看（GET）或更新（PUT）自助创建Key的设置
+func (h *WebHandler) HandleKeyCreationSettings(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeKeyCreationSettings(w)
+	case http.MethodPut:
+		var req types.KeyCreationConfig
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if err := h.configMgr.SetKeyCreationConfig(req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid key creation settings: "+err.Error())
+			return
+		}
+		logger.Info("Updated key creation settings by %s", h.sessionUser(r))
+		h.writeKeyCreationSettings(w)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+// writeKeyCreationSettings 返回配置的设置和生效的数量上限（可能来自环境变量）
+func (h *WebHandler) writeKeyCreationSettings(w http.ResponseWriter) {
+	settings := h.configMgr.Get().Server.Web.KeyCreation
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"settings":                    settings,
+		"effective_max_keys_per_user": config.MaxKeysPerUser(&settings),
+	})
+}
+
+// selfServeKey 非 admin 自助创建Key时适用的限制
+type selfServeKey struct {
+	selfServe bool // 非 admin 创建：不能设置组织、作用域和权限
+	pending   bool // 创建为待审批状态
+	limit     int  // 每个用户最多创建的Key数量，0表示不限制
+}
+
+// checkSelfServeKey 检查当前用户能否自助创建Key并返回适用的限制；admin 不受限制。
+// 不允许时返回非零的HTTP状态码和错误信息
+func (h *WebHandler) checkSelfServeKey(r *http.Request) (policy selfServeKey, status int, message string) {
+	session := h.currentSession(r)
+	if session == nil || session.Role == types.RoleAdmin {
+		return selfServeKey{}, 0, ""
+	}
+
+	settings := h.configMgr.Get().Server.Web.KeyCreation
+	if !settings.AllowOperators {
+		return selfServeKey{}, http.StatusForbidden, "Self-service key creation is disabled; ask an admin to create the key"
+	}
+	return selfServeKey{selfServe: true, pending: settings.RequireApproval, limit: config.MaxKeysPerUser(&settings)}, 0, ""
+}
+
+// notifyPendingKey 通知 admin 有新的Key等待批准
+func (h *WebHandler) notifyPendingKey(key *types.GatewayAPIKey, creator string) {
+	if h.notifier == nil {
+		return
+	}
+	h.notifier.Alert(notify.EventKeyPendingApproval,
+		fmt.Sprintf("API key %s (%s) created by %s is waiting for approval", key.Name, key.ID, creator),
+		map[string]interface{}{"key_id": key.ID, "key_name": key.Name, "created_by": creator},
+		time.Now())
+}
+
+// handleAPIKeyApprove 批准待审批的Key（POST），批准后立即可以使用
+func (h *WebHandler) handleAPIKeyApprove(w http.ResponseWriter, r *http.Request, keyID string) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "API key not found")
+		return
+	}
+	if !gatewayKey.PendingApproval {
+		h.writeError(w, http.StatusConflict, "API key is not waiting for approval")
+		return
+	}
+
+	approver := h.sessionUser(r)
+	if err := h.configMgr.ApproveGatewayKey(keyID, approver, time.Now()); err != nil {
+		logger.Error("Failed to approve API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to approve API key")
+		return
+	}
+
+	logger.Info("Approved API key %s (created by %s) by %s", keyID, gatewayKey.CreatedBy, approver)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "API key approved",
+	})
+}
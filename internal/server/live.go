@@ -0,0 +1,101 @@
+package server
+
+import (
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 进行中请求所处的阶段
+const (
+	livePhaseRouting   = "routing"   // 校验请求、匹配路由规则、选择账号
+	livePhaseQueued    = "queued"    // 所有账号都达到并发上限，排队等待名额
+	livePhaseUpstream  = "upstream"  // 已选定账号，等待上游响应
+	livePhaseStreaming = "streaming" // 上游开始返回流式响应，正在转发给客户端
+)
+
+// LiveRequest 一个进行中的代理请求
+type LiveRequest struct {
+	RequestID    string         `json:"request_id"`
+	GatewayKeyID string         `json:"gateway_key_id"`
+	Provider     types.Provider `json:"provider,omitempty"`
+	UpstreamID   string         `json:"upstream_id,omitempty"` // 故障转移后为当前使用的账号
+	Model        string         `json:"model"`
+	Endpoint     string         `json:"endpoint"`
+	Stream       bool           `json:"stream"`
+	Phase        string         `json:"phase"`
+	StartedAt    time.Time      `json:"started_at"`
+	ElapsedMs    int64          `json:"elapsed_ms"`
+}
+
+// liveRequests 进行中请求的登记表，由代理在请求开始时登记、结束时移除。
+// 登记项以请求的使用记录（网关为每个请求创建）为键，而不是请求ID：请求ID可以由客户端通过 X-Request-Id 指定，
+// 同一ID的并发请求会互相覆盖，先结束的请求还会移除仍在进行中的请求
+type liveRequests struct {
+	requests map[*stats.UsageRecord]*LiveRequest
+	mutex    sync.Mutex
+}
+
+func newLiveRequests() *liveRequests {
+	return &liveRequests{requests: make(map[*stats.UsageRecord]*LiveRequest)}
+}
+
+// add 按使用记录登记请求
+func (l *liveRequests) add(record *stats.UsageRecord) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+	l.requests[record] = &LiveRequest{
+		RequestID:    record.RequestID,
+		GatewayKeyID: record.GatewayKeyID,
+		Model:        record.Model,
+		Endpoint:     record.Endpoint,
+		Stream:       record.Stream,
+		Phase:        livePhaseRouting,
+		StartedAt:    record.Timestamp,
+	}
+}
+
+// remove 请求结束时移除
+func (l *liveRequests) remove(record *stats.UsageRecord) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+	delete(l.requests, record)
+}
+
+// setPhase 更新请求所处的阶段，请求未登记时忽略
+func (l *liveRequests) setPhase(record *stats.UsageRecord, phase string) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+	if request, ok := l.requests[record]; ok {
+		request.Phase = phase
+	}
+}
+
+// setUpstream 记录请求当前使用的账号，请求未登记时忽略
+func (l *liveRequests) setUpstream(record *stats.UsageRecord, account *types.UpstreamAccount) {
+	l.mutex.Lock()
+	defer l.mutex.Unlock()
+	if request, ok := l.requests[record]; ok {
+		request.Provider = account.Provider
+		request.UpstreamID = account.ID
+		request.Phase = livePhaseUpstream
+	}
+}
+
+// snapshot 返回所有进行中请求的副本，按开始时间排序（最早的在前）
+func (l *liveRequests) snapshot(now time.Time) []LiveRequest {
+	l.mutex.Lock()
+	result := make([]LiveRequest, 0, len(l.requests))
+	for _, request := range l.requests {
+		item := *request
+		item.ElapsedMs = now.Sub(item.StartedAt).Milliseconds()
+		result = append(result, item)
+	}
+	l.mutex.Unlock()
+
+	sort.Slice(result, func(i, j int) bool { return result[i].StartedAt.Before(result[j].StartedAt) })
+	return result
+}
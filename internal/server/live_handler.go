@@ -0,0 +1,98 @@
+package server
+
+import (
+	"net/http"
+	"sort"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// liveErrorWindow 实时流量接口返回的最近错误的时间范围
+const liveErrorWindow = 15 * time.Minute
+
+// defaultLiveErrors 默认返回的最近错误条数
+const defaultLiveErrors = 50
+
+// LiveAccount 有进行中请求的上游账号
+type LiveAccount struct {
+	ID            string         `json:"id"`
+	Name          string         `json:"name"`
+	Provider      types.Provider `json:"provider"`
+	InFlight      int            `json:"in_flight"`
+	MaxConcurrent int            `json:"max_concurrent"` // 0表示不限制
+}
+
+// HandleLiveTraffic 返回当前的实时流量（GET）：进行中的请求、最近15分钟的失败请求和各账号的进行中请求数
+func (h *WebHandler) HandleLiveTraffic(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+	if h.proxy == nil {
+		h.writeError(w, http.StatusServiceUnavailable, "Proxy is not available")
+		return
+	}
+
+	limit := defaultLiveErrors
+	if value := r.URL.Query().Get("errors"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 0 || parsed > 500 {
+			h.writeError(w, http.StatusBadRequest, "errors must be between 0 and 500")
+			return
+		}
+		limit = parsed
+	}
+
+	now := time.Now()
+	requests := h.proxy.live.snapshot(now)
+
+	// 最近的失败请求，最新的在前
+	recentErrors := []stats.UsageRecord{}
+	records := h.recorder.Query(stats.Filter{Since: now.Add(-liveErrorWindow)})
+	for i := len(records) - 1; i >= 0 && len(recentErrors) < limit; i-- {
+		if !records[i].Success {
+			recentErrors = append(recentErrors, records[i])
+		}
+	}
+
+	accounts := []LiveAccount{}
+	for _, account := range h.configMgr.ListUpstreamAccounts() {
+		inFlight := h.proxy.concurrency.InFlight(account.ID)
+		if inFlight == 0 {
+			continue
+		}
+		accounts = append(accounts, LiveAccount{
+			ID:            account.ID,
+			Name:          account.Name,
+			Provider:      account.Provider,
+			InFlight:      inFlight,
+			MaxConcurrent: account.MaxConcurrent,
+		})
+	}
+	sort.Slice(accounts, func(i, j int) bool {
+		if accounts[i].InFlight != accounts[j].InFlight {
+			return accounts[i].InFlight > accounts[j].InFlight
+		}
+		return accounts[i].ID < accounts[j].ID
+	})
+
+	streaming := 0
+	for _, request := range requests {
+		if request.Phase == livePhaseStreaming {
+			streaming++
+		}
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"captured_at":          now,
+		"in_flight":            len(requests),
+		"streaming":            streaming,
+		"requests":             requests,
+		"accounts":             accounts,
+		"recent_errors":        recentErrors,
+		"error_window_minutes": int(liveErrorWindow.Minutes()),
+	})
+}
@@ -0,0 +1,216 @@
+package server
+
+import (
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestLiveRequests_Lifecycle(t *testing.T) {
+	live := newLiveRequests()
+	start := time.Now()
+	account := &types.UpstreamAccount{ID: "up-1", Provider: types.ProviderOpenAI}
+
+	record := &stats.UsageRecord{RequestID: "req-1", GatewayKeyID: "key-1", Model: "gpt-4o", Endpoint: "/v1/chat/completions", Stream: true, Timestamp: start}
+
+	// 未登记的请求不受阶段和账号更新影响
+	live.setPhase(record, livePhaseStreaming)
+	live.setUpstream(record, account)
+	if got := live.snapshot(start); len(got) != 0 {
+		t.Fatalf("snapshot() = %+v, want no requests before add", got)
+	}
+
+	live.add(record)
+	got := live.snapshot(start.Add(250 * time.Millisecond))
+	if len(got) != 1 || got[0].Phase != livePhaseRouting || got[0].ElapsedMs != 250 || got[0].GatewayKeyID != "key-1" || !got[0].Stream {
+		t.Fatalf("after add: snapshot() = %+v, want req-1 routing for 250ms", got)
+	}
+
+	live.setUpstream(record, account)
+	if got := live.snapshot(start)[0]; got.Phase != livePhaseUpstream || got.UpstreamID != "up-1" || got.Provider != types.ProviderOpenAI {
+		t.Errorf("after setUpstream: %+v, want phase upstream on up-1", got)
+	}
+	live.setPhase(record, livePhaseStreaming)
+	if got := live.snapshot(start)[0]; got.Phase != livePhaseStreaming {
+		t.Errorf("after setPhase: phase = %s, want %s", got.Phase, livePhaseStreaming)
+	}
+
+	// 移除后不再出现，之后的更新也不会重新登记
+	live.remove(record)
+	live.setPhase(record, livePhaseUpstream)
+	if got := live.snapshot(start); len(got) != 0 {
+		t.Errorf("after remove: snapshot() = %+v, want no requests", got)
+	}
+}
+
+func TestLiveRequests_SnapshotIsCopy(t *testing.T) {
+	live := newLiveRequests()
+	start := time.Now()
+	newer := &stats.UsageRecord{RequestID: "newer", Timestamp: start.Add(time.Second)}
+	older := &stats.UsageRecord{RequestID: "older", Timestamp: start}
+	live.add(newer)
+	live.add(older)
+
+	snapshot := live.snapshot(start.Add(2 * time.Second))
+	if len(snapshot) != 2 || snapshot[0].RequestID != "older" || snapshot[1].RequestID != "newer" {
+		t.Fatalf("snapshot() = %+v, want oldest first", snapshot)
+	}
+
+	// 读取方持有的快照与登记表互不影响
+	live.setPhase(older, livePhaseStreaming)
+	live.remove(newer)
+	if snapshot[0].Phase != livePhaseRouting || len(snapshot) != 2 {
+		t.Errorf("snapshot changed after later updates: %+v", snapshot)
+	}
+	snapshot[0].Phase = "edited"
+	if got := live.snapshot(start)[0]; got.Phase != livePhaseStreaming {
+		t.Errorf("editing a snapshot changed the registry: phase = %s", got.Phase)
+	}
+}
+
+func TestLiveRequests_SameRequestID(t *testing.T) {
+	live := newLiveRequests()
+	start := time.Now()
+	first := &stats.UsageRecord{RequestID: "client-chosen", GatewayKeyID: "key-1", Timestamp: start}
+	second := &stats.UsageRecord{RequestID: "client-chosen", GatewayKeyID: "key-2", Timestamp: start.Add(time.Second)}
+
+	// 客户端为两个并发请求指定了相同的 X-Request-Id，两个请求各自登记
+	live.add(first)
+	live.add(second)
+	if got := live.snapshot(start); len(got) != 2 {
+		t.Fatalf("snapshot() = %+v, want both requests", got)
+	}
+
+	// 先结束的请求只移除自己
+	live.remove(first)
+	got := live.snapshot(start)
+	if len(got) != 1 || got[0].GatewayKeyID != "key-2" {
+		t.Errorf("after removing the first request: snapshot() = %+v, want only key-2", got)
+	}
+}
+
+func TestLiveRequests_ConcurrentAccess(t *testing.T) {
+	live := newLiveRequests()
+	account := &types.UpstreamAccount{ID: "up-1", Provider: types.ProviderOpenAI}
+
+	var wg sync.WaitGroup
+	for i := 0; i < 8; i++ {
+		wg.Add(2)
+		go func(i int) {
+			defer wg.Done()
+			record := &stats.UsageRecord{RequestID: fmt.Sprintf("req-%d", i), Timestamp: time.Now()}
+			live.add(record)
+			live.setUpstream(record, account)
+			live.setPhase(record, livePhaseStreaming)
+			live.remove(record)
+		}(i)
+		go func() {
+			defer wg.Done()
+			for _, request := range live.snapshot(time.Now()) {
+				if request.RequestID == "" {
+					t.Error("snapshot() returned a request without an ID")
+				}
+			}
+		}()
+	}
+	wg.Wait()
+
+	if got := live.snapshot(time.Now()); len(got) != 0 {
+		t.Errorf("snapshot() = %+v, want every request removed", got)
+	}
+}
+
+func TestLiveRequests_TracksProxyRequest(t *testing.T) {
+	tests := []struct {
+		name  string
+		body  string // 上游的响应
+		model string
+		send  func(h *ProxyHandler) *httptest.ResponseRecorder
+	}{
+		{name: "chat", body: chatCompletionBody, model: "gpt-4o", send: func(h *ProxyHandler) *httptest.ResponseRecorder { return postChat(h, nil) }},
+		{name: "embeddings", body: embeddingBody, model: "text-embedding-3-small", send: func(h *ProxyHandler) *httptest.ResponseRecorder {
+			req := httptest.NewRequest(http.MethodPost, "/v1/embeddings", strings.NewReader(`{"model":"text-embedding-3-small","input":"hi"}`))
+			req.Header.Set("Content-Type", "application/json")
+			rec := httptest.NewRecorder()
+			h.HandleEmbeddings(rec, req)
+			return rec
+		}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			arrived := make(chan struct{})
+			release := make(chan struct{})
+			server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+				close(arrived)
+				<-release
+				w.Header().Set("Content-Type", "application/json")
+				_, _ = w.Write([]byte(tt.body))
+			}))
+			defer server.Close()
+			unblock := sync.OnceFunc(func() { close(release) })
+			defer unblock()
+
+			h, _ := newUpstreamTestHandler(t, types.ProxyConfig{}, testOpenAIAccount("primary", server.URL, 0))
+			done := make(chan *httptest.ResponseRecorder)
+			go func() { done <- tt.send(h) }()
+
+			// 等待上游响应期间请求登记为 upstream 阶段，并记录所用的账号
+			select {
+			case <-arrived:
+			case <-time.After(5 * time.Second):
+				t.Fatal("the request did not reach the upstream")
+			}
+			requests := h.live.snapshot(time.Now())
+			if len(requests) != 1 || requests[0].UpstreamID != "primary" || requests[0].Phase != livePhaseUpstream || requests[0].Model != tt.model {
+				t.Errorf("in flight: snapshot() = %+v, want one request on primary waiting for the upstream", requests)
+			}
+
+			unblock()
+			if rec := <-done; rec.Code != http.StatusOK {
+				t.Fatalf("status = %d, want 200, body = %s", rec.Code, rec.Body.String())
+			}
+			if got := h.live.snapshot(time.Now()); len(got) != 0 {
+				t.Errorf("after the response: snapshot() = %+v, want the request removed", got)
+			}
+		})
+	}
+}
+
+// embeddingBody 上游返回的 OpenAI 嵌入响应
+const embeddingBody = `{"object":"list","model":"text-embedding-3-small",` +
+	`"data":[{"object":"embedding","index":0,"embedding":[0.1,0.2]}],"usage":{"prompt_tokens":1,"total_tokens":1}}`
+
+func TestHandleLiveTraffic_Validation(t *testing.T) {
+	tests := []struct {
+		name  string
+		h     *WebHandler
+		query string
+		want  int
+	}{
+		{name: "no proxy", h: &WebHandler{}, want: http.StatusServiceUnavailable},
+		{name: "errors not a number", h: &WebHandler{proxy: &ProxyHandler{live: newLiveRequests()}}, query: "?errors=all", want: http.StatusBadRequest},
+		{name: "errors above 500", h: &WebHandler{proxy: &ProxyHandler{live: newLiveRequests()}}, query: "?errors=501", want: http.StatusBadRequest},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			rec := httptest.NewRecorder()
+			tt.h.HandleLiveTraffic(rec, httptest.NewRequest(http.MethodGet, "/api/v1/live"+tt.query, nil))
+			if rec.Code != tt.want {
+				t.Errorf("status = %d, want %d", rec.Code, tt.want)
+			}
+		})
+	}
+
+	rec := httptest.NewRecorder()
+	(&WebHandler{}).HandleLiveTraffic(rec, httptest.NewRequest(http.MethodPost, "/api/v1/live", nil))
+	if rec.Code != http.StatusMethodNotAllowed {
+		t.Errorf("POST status = %d, want %d", rec.Code, http.StatusMethodNotAllowed)
+	}
+}
@@ -1,19 +1,30 @@
 package server
 
 import (
+	"bufio"
 	"context"
 	"encoding/json"
+	"fmt"
+	"net"
 	"net/http"
+	"strconv"
 	"strings"
+	"sync"
 	"time"
 
 	"github.com/iBreaker/llm-gateway/internal/client"
+	"github.com/iBreaker/llm-gateway/internal/notify"
+	"github.com/iBreaker/llm-gateway/internal/quota"
+	"github.com/iBreaker/llm-gateway/internal/ratelimit"
+	"github.com/iBreaker/llm-gateway/pkg/debug"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
 	"github.com/iBreaker/llm-gateway/pkg/types"
 )
 
 // AuthMiddleware 认证中间件
 type AuthMiddleware struct {
 	gatewayKeyMgr *client.GatewayKeyManager
+	drain         *drainGate // 跟踪异步的用量写入，为nil时不跟踪
 }
 
 // NewAuthMiddleware 创建认证中间件
@@ -26,15 +37,18 @@ func NewAuthMiddleware(gatewayKeyMgr *client.GatewayKeyManager) *AuthMiddleware
 // Authenticate 认证中间件处理函数
 func (m *AuthMiddleware) Authenticate(next http.HandlerFunc) http.HandlerFunc {
 	return func(w http.ResponseWriter, r *http.Request) {
-		// 支持两种认证方式：
+		// 支持以下认证方式：
 		// 1. Authorization: Bearer <token>  (Gateway标准格式)
 		// 2. x-api-key: <token>            (Anthropic原生格式)
+		// 3. x-goog-api-key: <token> 或 ?key=<token>  (Gemini原生格式，仅 /v1beta/ 路径)
 
 		var token string
 
 		// 首先检查 x-api-key 头部（Anthropic原生格式）
 		if apiKey := r.Header.Get("x-api-key"); apiKey != "" {
 			token = apiKey
+		} else if apiKey := geminiAPIKey(r); apiKey != "" {
+			token = apiKey
 		} else {
 			// 检查 Authorization 头部（Gateway标准格式）
 			authHeader := r.Header.Get("Authorization")
@@ -71,10 +85,44 @@ func (m *AuthMiddleware) Authenticate(next http.HandlerFunc) http.HandlerFunc {
 			return
 		}
 
+		// 检查端点作用域（提供商和模型作用域在确定路由后由代理处理器检查）
+		if !gatewayKey.ScopeAllows(types.ScopeEndpoint, r.URL.Path) {
+			m.writeErrorResponse(w, http.StatusForbidden, "scope_forbidden", fmt.Sprintf("API key is not allowed to use endpoint %s", r.URL.Path))
+			return
+		}
+
+		// 检查客户端应用标识：头部可选，但登记了应用的Key必须携带且名称在列表中
+		if app := r.Header.Get(types.ClientAppHeader); app != "" || len(gatewayKey.Apps) > 0 {
+			name, _, ok := types.ParseClientApp(app)
+			if !ok {
+				m.writeErrorResponse(w, http.StatusBadRequest, "invalid_app_header", fmt.Sprintf("%s must be <name>@<version>, got %q", types.ClientAppHeader, app))
+				return
+			}
+			if !gatewayKey.AppAllowed(name) {
+				m.writeErrorResponse(w, http.StatusForbidden, "app_not_registered", fmt.Sprintf("App %s is not registered for this API key", name))
+				return
+			}
+		}
+
+		// 检查客户端网络和请求来源：按TCP连接的对端地址判断，不信任可伪造的 X-Forwarded-For
+		if !gatewayKey.IPAllowed(remoteIP(r)) {
+			m.writeErrorResponse(w, http.StatusForbidden, "ip_not_allowed", "API key is not allowed from this IP address")
+			return
+		}
+		if !gatewayKey.OriginAllowed(requestOrigin(r)) {
+			m.writeErrorResponse(w, http.StatusForbidden, "origin_not_allowed", "API key is not allowed from this origin")
+			return
+		}
+
+		// 强制采样请求头只对有 trace 或 admin 权限的Key生效，其他客户端不能强制记录完整跟踪
+		if canForceTrace(gatewayKey) {
+			debug.ForceSample(r)
+		}
+
 		// 在请求上下文中保存Gateway Key信息，供后续处理使用
 		r.Header.Set("X-Gateway-Key-ID", gatewayKey.ID)
 		r.Header.Set("X-Gateway-Key-Name", gatewayKey.Name)
-		
+
 		// 通过 context 传递完整的 GatewayKey 对象
 		ctx := context.WithValue(r.Context(), "gatewayKey", gatewayKey)
 		r = r.WithContext(ctx)
@@ -84,15 +132,53 @@ func (m *AuthMiddleware) Authenticate(next http.HandlerFunc) http.HandlerFunc {
 
 		// 记录使用统计（在请求完成后）
 		// 这里暂时记录为成功，实际应该根据响应状态码判断
-		go func() {
+		m.drain.async(func() {
 			err := m.gatewayKeyMgr.UpdateKeyUsage(gatewayKey.ID, true, 0)
 			if err != nil {
 				// 记录日志，但不影响请求处理
 				// TODO: 添加日志记录
 				_ = err // 显式使用错误变量
 			}
-		}()
+		})
+	}
+}
+
+// geminiAPIKey 从Gemini原生端点的请求中取出密钥（x-goog-api-key 头部或 key 查询参数）
+func geminiAPIKey(r *http.Request) string {
+	if !strings.HasPrefix(r.URL.Path, "/v1beta/") {
+		return ""
+	}
+	if apiKey := r.Header.Get("x-goog-api-key"); apiKey != "" {
+		return apiKey
+	}
+	return r.URL.Query().Get("key")
+}
+
+// remoteIP 返回TCP连接对端的IP地址，无法解析时返回nil
+func remoteIP(r *http.Request) net.IP {
+	host, _, err := net.SplitHostPort(r.RemoteAddr)
+	if err != nil {
+		host = r.RemoteAddr
+	}
+	return net.ParseIP(host)
+}
+
+// requestOrigin 返回请求来源：优先使用 Origin 头部，没有时使用 Referer
+func requestOrigin(r *http.Request) string {
+	if origin := r.Header.Get("Origin"); origin != "" && origin != "null" {
+		return origin
 	}
+	return r.Header.Get("Referer")
+}
+
+// canForceTrace 判断Key是否可以用强制采样请求头记录完整跟踪
+func canForceTrace(key *types.GatewayAPIKey) bool {
+	for _, perm := range key.Permissions {
+		if perm == types.PermissionTrace || perm == types.PermissionAdmin {
+			return true
+		}
+	}
+	return false
 }
 
 // hasRequiredPermission 检查权限
@@ -141,15 +227,26 @@ func (m *AuthMiddleware) writeErrorResponse(w http.ResponseWriter, statusCode in
 	_ = json.NewEncoder(w).Encode(errorResp)
 }
 
-// RateLimitMiddleware 限流中间件（简化版本）
+// RateLimitMiddleware 限流中间件
 type RateLimitMiddleware struct {
 	gatewayKeyMgr *client.GatewayKeyManager
+	limiter       ratelimit.WindowLimiter
+	concurrency   *ratelimit.ConcurrencyLimiter
+	quota         *quota.Service
+	notifications func() *types.NotificationConfig // 返回软配额告警阈值所在的配置，为nil时使用默认阈值
+	notifier      *notify.Service                  // 首次达到软配额阈值时告警，为nil时只返回响应头
 }
 
-// NewRateLimitMiddleware 创建限流中间件
-func NewRateLimitMiddleware(gatewayKeyMgr *client.GatewayKeyManager) *RateLimitMiddleware {
+// NewRateLimitMiddleware 创建限流中间件，limiter 为nil时使用进程内计数
+func NewRateLimitMiddleware(gatewayKeyMgr *client.GatewayKeyManager, quotaSvc *quota.Service, limiter ratelimit.WindowLimiter) *RateLimitMiddleware {
+	if limiter == nil {
+		limiter = ratelimit.NewLimiter()
+	}
 	return &RateLimitMiddleware{
 		gatewayKeyMgr: gatewayKeyMgr,
+		limiter:       limiter,
+		concurrency:   ratelimit.NewConcurrencyLimiter(),
+		quota:         quotaSvc,
 	}
 }
 
@@ -170,23 +267,165 @@ func (m *RateLimitMiddleware) RateLimit(next http.HandlerFunc) http.HandlerFunc
 			return
 		}
 
-		// 简单的限流检查（这里是简化版本，实际应该使用更复杂的限流算法）
-		if gatewayKey.RateLimit != nil {
-			// 这里可以实现令牌桶或滑动窗口算法
-			// 暂时跳过具体实现，后续可以优化
-			_ = gatewayKey.RateLimit // 显式使用限流配置
+		// 按分钟/小时/天窗口限流，并在响应中返回限流状态供客户端自行降速
+		now := time.Now()
+		if result, limited := m.limiter.Allow(keyID, gatewayKey.RateLimit, now); limited {
+			w.Header().Set("X-RateLimit-Limit", strconv.Itoa(result.Limit))
+			w.Header().Set("X-RateLimit-Remaining", strconv.Itoa(result.Remaining))
+			w.Header().Set("X-RateLimit-Reset", strconv.FormatInt(result.Reset.Unix(), 10))
+
+			if !result.Allowed {
+				retryAfter := int(result.RetryAfter(now).Seconds() + 0.999)
+				w.Header().Set("Retry-After", strconv.Itoa(retryAfter))
+				m.writeErrorResponse(w, "rate_limit_exceeded", fmt.Sprintf("Rate limit exceeded, retry after %d seconds", retryAfter))
+				return
+			}
+		}
+
+		// token/费用配额检查
+		if m.quota != nil && gatewayKey.Quota != nil {
+			result := m.quota.Check(keyID, gatewayKey.Quota, now)
+			if result.HasCostLimit {
+				w.Header().Set("X-Gateway-Quota-Remaining-USD", strconv.FormatFloat(result.RemainingUSD, 'f', 6, 64))
+			}
+			if result.Exceeded {
+				w.Header().Set("Retry-After", strconv.Itoa(int(result.Reset.Sub(now).Seconds()+0.999)))
+				m.writeQuotaExceeded(w, result)
+				return
+			}
+			m.warnQuota(w, gatewayKey, now)
+		}
+
+		// 并发限制：名额占用到请求处理结束（流式请求持续到流结束）
+		if gatewayKey.RateLimit != nil && gatewayKey.RateLimit.MaxConcurrent > 0 {
+			release, ok := m.concurrency.Acquire(keyID, gatewayKey.RateLimit.MaxConcurrent)
+			if !ok {
+				w.Header().Set("Retry-After", "1")
+				m.writeErrorResponse(w, "concurrency_limit_exceeded", fmt.Sprintf("Too many concurrent requests (limit %d)", gatewayKey.RateLimit.MaxConcurrent))
+				return
+			}
+			defer release()
 		}
 
 		next(w, r)
 	}
 }
 
+// warnQuota 配额用量达到软告警阈值时在响应头 X-Gateway-Quota-Warning 中返回（如 daily_cost_usd=0.8），
+// 每个阈值在周期内首次达到时发送 quota_warning 通知
+func (m *RateLimitMiddleware) warnQuota(w http.ResponseWriter, gatewayKey *types.GatewayAPIKey, now time.Time) {
+	var thresholds []float64
+	if m.notifications != nil {
+		thresholds = m.notifications().QuotaWarningThresholds
+	}
+
+	var values []string
+	for _, warning := range m.quota.Warnings(gatewayKey.ID, gatewayKey.Quota, thresholds, now) {
+		values = append(values, warning.Limit+"="+strconv.FormatFloat(warning.Threshold, 'f', -1, 64))
+		if !warning.Fired || m.notifier == nil {
+			continue
+		}
+		m.notifier.Alert(notify.EventQuotaWarning,
+			fmt.Sprintf("Key %s (%s) used %.0f%% of its %s quota (%g of %g)", gatewayKey.Name, gatewayKey.ID, warning.Used/warning.Max*100, warning.Limit, warning.Used, warning.Max),
+			map[string]interface{}{"scope": "key", "key_id": gatewayKey.ID, "name": gatewayKey.Name, "limit": warning.Limit, "threshold": warning.Threshold, "used": warning.Used, "max": warning.Max, "reset": warning.Reset},
+			now)
+	}
+	if len(values) > 0 {
+		w.Header().Set("X-Gateway-Quota-Warning", strings.Join(values, ", "))
+	}
+}
+
+// writeErrorResponse 写入429响应
+func (m *RateLimitMiddleware) writeErrorResponse(w http.ResponseWriter, errorType, message string) {
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(http.StatusTooManyRequests)
+
+	errorResp := map[string]interface{}{
+		"error": map[string]string{
+			"type":    errorType,
+			"message": message,
+		},
+		"timestamp": time.Now().Unix(),
+	}
+
+	_ = json.NewEncoder(w).Encode(errorResp)
+}
+
+// writeQuotaExceeded 写入配额超限的429响应
+func (m *RateLimitMiddleware) writeQuotaExceeded(w http.ResponseWriter, result quota.Result) {
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(http.StatusTooManyRequests)
+
+	errorResp := map[string]interface{}{
+		"error": map[string]interface{}{
+			"type":    "quota_exceeded",
+			"message": fmt.Sprintf("Quota %s exceeded (used %g of %g), resets at %s", result.Limit, result.Used, result.Max, result.Reset.Format(time.RFC3339)),
+			"quota": map[string]interface{}{
+				"limit": result.Limit,
+				"max":   result.Max,
+				"used":  result.Used,
+				"reset": result.Reset.Unix(),
+			},
+		},
+		"timestamp": time.Now().Unix(),
+	}
+
+	_ = json.NewEncoder(w).Encode(errorResp)
+}
+
+var (
+	// corsAllowedOrigins 允许跨域的来源，由运行环境配置档决定，重新加载配置时替换
+	corsAllowedOrigins = []string{"*"}
+	corsMutex          sync.RWMutex
+)
+
+// ConfigureCORS 设置允许跨域的来源，空列表表示不允许跨域
+func ConfigureCORS(origins []string) {
+	origins = append([]string{}, origins...)
+
+	corsMutex.Lock()
+	defer corsMutex.Unlock()
+	corsAllowedOrigins = origins
+}
+
+// allowedOrigin 返回应写入 Access-Control-Allow-Origin 的值，空字符串表示不允许
+func allowedOrigin(origin string) string {
+	corsMutex.RLock()
+	origins := corsAllowedOrigins
+	corsMutex.RUnlock()
+
+	for _, allowed := range origins {
+		if allowed == "*" {
+			return "*"
+		}
+		if origin != "" && strings.EqualFold(allowed, origin) {
+			return origin
+		}
+	}
+	return ""
+}
+
 // CORSMiddleware CORS中间件
 func CORSMiddleware(next http.HandlerFunc) http.HandlerFunc {
 	return func(w http.ResponseWriter, r *http.Request) {
-		w.Header().Set("Access-Control-Allow-Origin", "*")
+		allowOrigin := allowedOrigin(r.Header.Get("Origin"))
+		if allowOrigin == "" {
+			// 不允许跨域时只处理同源请求，不返回CORS头
+			if r.Method == "OPTIONS" {
+				w.WriteHeader(http.StatusForbidden)
+				return
+			}
+			next(w, r)
+			return
+		}
+
+		w.Header().Set("Access-Control-Allow-Origin", allowOrigin)
+		if allowOrigin != "*" {
+			w.Header().Add("Vary", "Origin")
+		}
 		w.Header().Set("Access-Control-Allow-Methods", "GET, POST, PUT, DELETE, OPTIONS")
-		w.Header().Set("Access-Control-Allow-Headers", "Content-Type, Authorization")
+		w.Header().Set("Access-Control-Allow-Headers", "Content-Type, Authorization, X-Gateway-Usage-Event, X-Gateway-App, X-Gateway-Priority, X-LLM-Timeout-Ms, X-Request-Id")
+		w.Header().Set("Access-Control-Expose-Headers", "X-Gateway-Cost-USD, X-Gateway-Input-Tokens, X-Gateway-Output-Tokens, X-RateLimit-Limit, X-RateLimit-Remaining, X-RateLimit-Reset, X-Gateway-Quota-Remaining-USD, X-Gateway-Quota-Warning, X-Gateway-Queue-Time-Ms, Retry-After, X-Request-Id")
 
 		if r.Method == "OPTIONS" {
 			w.WriteHeader(http.StatusOK)
@@ -202,24 +441,36 @@ func LoggingMiddleware(next http.HandlerFunc) http.HandlerFunc {
 	return func(w http.ResponseWriter, r *http.Request) {
 		start := time.Now()
 
+		// 没有经过 RequestContextMiddleware 的路由（管理API）在此确定请求ID
+		r = withRequestID(w, r)
+
+		// 按路由采样，结果保存在请求上下文中，代理请求据此决定是否记录完整跟踪
+		r, sampled := debug.MarkSampled(r)
+
 		// 创建ResponseWriter包装器来捕获状态码
 		wrapped := &responseWriter{ResponseWriter: w, statusCode: http.StatusOK}
 
 		next(wrapped, r)
 
+		// 认证中间件可能已按强制采样请求头把请求改为采样
+		if !*sampled {
+			return
+		}
 		duration := time.Since(start)
 
-		// 记录请求日志
+		// 记录采样到的请求
 		keyID := r.Header.Get("X-Gateway-Key-ID")
 		if keyID == "" {
 			keyID = "anonymous"
 		}
-		_ = keyID // 使用keyID变量
-
-		// TODO: 使用结构化日志记录
-		_ = duration // 暂时避免未使用变量错误
-		// log.Printf("[%s] %s %s - %d - %v - key:%s",
-		//     r.Method, r.URL.Path, r.RemoteAddr, wrapped.statusCode, duration, keyID)
+		logger.InfoWith(logger.Fields{
+			"request_id":  requestIDFrom(r.Context()),
+			"method":      r.Method,
+			"path":        r.URL.Path,
+			"status":      wrapped.statusCode,
+			"duration_ms": duration.Milliseconds(),
+			"key_id":      keyID,
+		}, "[trace] request completed")
 	}
 }
 
@@ -240,3 +491,12 @@ func (rw *responseWriter) Flush() {
 		flusher.Flush()
 	}
 }
+
+// Hijack 实现http.Hijacker接口，支持WebSocket升级
+func (rw *responseWriter) Hijack() (net.Conn, *bufio.ReadWriter, error) {
+	hijacker, ok := rw.ResponseWriter.(http.Hijacker)
+	if !ok {
+		return nil, nil, fmt.Errorf("连接不支持接管")
+	}
+	return hijacker.Hijack()
+}
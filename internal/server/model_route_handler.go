@@ -0,0 +1,37 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleModelRoutes 查看（GET）或替换（PUT）全局模型路由（模型别名），对所有Key生效，Key级别路由优先
+func (h *WebHandler) HandleModelRoutes(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, h.configMgr.Get().ModelRoutes)
+	case http.MethodPut:
+		var req types.ModelRouteConfig
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+			return
+		}
+		if err := req.Validate(); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid route configuration: "+err.Error())
+			return
+		}
+		if err := h.configMgr.SetModelRoutes(req); err != nil {
+			logger.Error("Failed to update global model routes: %v", err)
+			h.writeError(w, http.StatusInternalServerError, "Failed to update model routes")
+			return
+		}
+
+		logger.Info("Updated global model routes: %d routes", len(req.Routes))
+		h.writeJSON(w, http.StatusOK, h.configMgr.Get().ModelRoutes)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
@@ -0,0 +1,101 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestHandleModelRoutes(t *testing.T) {
+	_, configMgr := newUpstreamTestHandler(t, types.ProxyConfig{})
+	h := &WebHandler{configMgr: configMgr}
+
+	request := func(method, body string) *httptest.ResponseRecorder {
+		rec := httptest.NewRecorder()
+		h.HandleModelRoutes(rec, httptest.NewRequest(method, "/api/v1/model-routes", strings.NewReader(body)))
+		return rec
+	}
+
+	if rec := request(http.MethodGet, ""); rec.Code != http.StatusOK {
+		t.Fatalf("GET status = %d, want 200", rec.Code)
+	}
+
+	valid := `{"routes":[{"id":"fast","source_model":"fast","target_model":"gpt-4o-mini","target_provider":"openai","enabled":true}],"default_behavior":"passthrough"}`
+	rec := request(http.MethodPut, valid)
+	if rec.Code != http.StatusOK {
+		t.Fatalf("PUT status = %d, want 200, body = %s", rec.Code, rec.Body.String())
+	}
+	var saved types.ModelRouteConfig
+	if err := json.Unmarshal(rec.Body.Bytes(), &saved); err != nil || len(saved.Routes) != 1 || saved.Routes[0].TargetModel != "gpt-4o-mini" {
+		t.Errorf("PUT response = %s, want the saved routes", rec.Body.String())
+	}
+	if routes := configMgr.Get().ModelRoutes.Routes; len(routes) != 1 || routes[0].ID != "fast" {
+		t.Errorf("configured routes = %+v, want the fast alias", routes)
+	}
+
+	// 无效的请求不修改已保存的路由
+	tests := []struct {
+		name string
+		body string
+	}{
+		{name: "invalid JSON", body: `{"routes":`},
+		{name: "missing target model", body: `{"routes":[{"id":"bad","source_model":"bad","target_provider":"openai","enabled":true}]}`},
+		{name: "duplicate IDs", body: `{"routes":[{"id":"a","source_model":"a","target_model":"gpt-4o","target_provider":"openai"},{"id":"a","source_model":"b","target_model":"gpt-4o","target_provider":"openai"}]}`},
+		{name: "unknown default behavior", body: `{"default_behavior":"drop"}`},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if rec := request(http.MethodPut, tt.body); rec.Code != http.StatusBadRequest {
+				t.Errorf("PUT status = %d, want 400", rec.Code)
+			}
+			if routes := configMgr.Get().ModelRoutes.Routes; len(routes) != 1 || routes[0].ID != "fast" {
+				t.Errorf("configured routes = %+v, want the fast alias kept", routes)
+			}
+		})
+	}
+
+	if rec := request(http.MethodDelete, ""); rec.Code != http.StatusMethodNotAllowed {
+		t.Errorf("DELETE status = %d, want 405", rec.Code)
+	}
+}
+
+func TestModelRoutes_RecordRequestedModel(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		var body struct {
+			Model string `json:"model"`
+		}
+		if err := json.NewDecoder(r.Body).Decode(&body); err != nil || body.Model != "gpt-4o-mini" {
+			w.WriteHeader(http.StatusBadRequest)
+			return
+		}
+		w.Header().Set("Content-Type", "application/json")
+		_, _ = w.Write([]byte(chatCompletionBody))
+	}))
+	defer server.Close()
+
+	h, configMgr := newUpstreamTestHandler(t, types.ProxyConfig{}, testOpenAIAccount("primary", server.URL, 0))
+	if err := configMgr.SetModelRoutes(types.ModelRouteConfig{Routes: []types.ModelRoute{
+		{ID: "fast", SourceModel: "fast", TargetModel: "gpt-4o-mini", TargetProvider: types.ProviderOpenAI, Enabled: true},
+	}}); err != nil {
+		t.Fatalf("SetModelRoutes() error = %v", err)
+	}
+
+	// 全局别名对没有Key级别路由的请求生效，上游收到目标模型
+	req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", strings.NewReader(`{"model":"fast","messages":[{"role":"user","content":"hi"}]}`))
+	req.Header.Set("Content-Type", "application/json")
+	rec := httptest.NewRecorder()
+	h.HandleChatCompletions(rec, req)
+	if rec.Code != http.StatusOK {
+		t.Fatalf("status = %d, want 200, body = %s", rec.Code, rec.Body.String())
+	}
+
+	records := h.recorder.Query(stats.Filter{})
+	if len(records) != 1 || records[0].Model != "gpt-4o-mini" || records[0].RequestedModel != "fast" {
+		t.Errorf("usage records = %+v, want model gpt-4o-mini requested as fast", records)
+	}
+}
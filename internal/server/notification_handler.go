@@ -0,0 +1,71 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleNotifications 查看（GET）或更新（PUT）告警通知配置，更新立即生效
+func (h *WebHandler) HandleNotifications(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, h.configMgr.Get().Notifications)
+	case http.MethodPut:
+		var req types.NotificationConfig
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if err := h.configMgr.SetNotificationConfig(req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid notification configuration: "+err.Error())
+			return
+		}
+		h.writeJSON(w, http.StatusOK, h.configMgr.Get().Notifications)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+// HandleNotificationDeliveries 返回最近的Webhook投递记录（GET /api/v1/notifications/deliveries?limit=）
+func (h *WebHandler) HandleNotificationDeliveries(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	limit := 100
+	if value := r.URL.Query().Get("limit"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > 500 {
+			h.writeError(w, http.StatusBadRequest, "limit must be between 1 and 500")
+			return
+		}
+		limit = parsed
+	}
+
+	deliveries := h.notifier.Deliveries(limit)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"data":  deliveries,
+		"total": len(deliveries),
+	})
+}
+
+// HandleNotificationTest 向所有已配置的Webhook发送测试通知，返回首次投递结果
+func (h *WebHandler) HandleNotificationTest(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+	if len(h.configMgr.Get().Notifications.Webhooks) == 0 {
+		h.writeError(w, http.StatusBadRequest, "No webhooks configured")
+		return
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"data": h.notifier.SendTest(time.Now()),
+	})
+}
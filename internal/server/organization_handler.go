@@ -0,0 +1,204 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// organizationRequest 创建/更新组织的请求体
+type organizationRequest struct {
+	Name    string   `json:"name"`
+	Members []string `json:"members"`
+}
+
+// HandleOrganizations 组织列表与创建，列表可按 member 只看某个用户所在的组织
+func (h *WebHandler) HandleOrganizations(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		member := r.URL.Query().Get("member")
+		orgs := make([]map[string]interface{}, 0)
+		for _, org := range h.configMgr.ListOrganizations() {
+			if member != "" && !org.HasMember(member) {
+				continue
+			}
+			orgs = append(orgs, h.organizationSummary(org))
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{"data": orgs})
+	case http.MethodPost:
+		h.handleCreateOrganization(w, r)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) handleCreateOrganization(w http.ResponseWriter, r *http.Request) {
+	var req organizationRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+	if req.Name == "" {
+		h.writeError(w, http.StatusBadRequest, "Name is required")
+		return
+	}
+
+	now := time.Now()
+	org := &types.Organization{
+		ID:        h.generateID("org"),
+		Name:      req.Name,
+		Members:   req.Members,
+		CreatedAt: now,
+		UpdatedAt: now,
+	}
+	if err := h.configMgr.CreateOrganization(org); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid organization: "+err.Error())
+		return
+	}
+
+	logger.Info("Created organization %s (%s) by %s", org.Name, org.ID, h.sessionUser(r))
+	h.writeJSON(w, http.StatusCreated, org)
+}
+
+// HandleOrganizationActions 查看、更新或删除单个组织
+func (h *WebHandler) HandleOrganizationActions(w http.ResponseWriter, r *http.Request) {
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) != 4 {
+		h.writeError(w, http.StatusNotFound, "API endpoint not found")
+		return
+	}
+
+	orgID := pathParts[3] // /api/v1/organizations/{id}
+	org, err := h.configMgr.GetOrganization(orgID)
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "Organization not found")
+		return
+	}
+
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, h.organizationSummary(org))
+	case http.MethodPut:
+		var req organizationRequest
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		err := h.configMgr.UpdateOrganization(orgID, func(org *types.Organization) error {
+			if req.Name != "" {
+				org.Name = req.Name
+			}
+			if req.Members != nil {
+				org.Members = req.Members
+			}
+			org.UpdatedAt = time.Now()
+			return nil
+		})
+		if err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid organization: "+err.Error())
+			return
+		}
+		logger.Info("Updated organization %s by %s", orgID, h.sessionUser(r))
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"success": true,
+			"message": "Organization updated successfully",
+		})
+	case http.MethodDelete:
+		if err := h.configMgr.DeleteOrganization(orgID); err != nil {
+			h.writeError(w, http.StatusConflict, "Organization still owns API keys or upstream accounts; move or delete them first")
+			return
+		}
+		logger.Info("Deleted organization %s by %s", orgID, h.sessionUser(r))
+		w.WriteHeader(http.StatusNoContent)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+// organizationSummary 组织及其拥有的Key和上游账号ID
+func (h *WebHandler) organizationSummary(org *types.Organization) map[string]interface{} {
+	keyIDs := make([]string, 0)
+	for _, key := range h.configMgr.ListGatewayKeys() {
+		if key.OrgID == org.ID {
+			keyIDs = append(keyIDs, key.ID)
+		}
+	}
+	upstreamIDs := make([]string, 0)
+	for _, account := range h.configMgr.ListUpstreamAccounts() {
+		if account.OrgID == org.ID {
+			upstreamIDs = append(upstreamIDs, account.ID)
+		}
+	}
+	return map[string]interface{}{
+		"id":           org.ID,
+		"name":         org.Name,
+		"members":      org.Members,
+		"key_ids":      keyIDs,
+		"upstream_ids": upstreamIDs,
+		"created_at":   org.CreatedAt,
+		"updated_at":   org.UpdatedAt,
+	}
+}
+
+// validateOrgID 检查要设置的所属组织，返回错误信息，存在或为空（不属于组织）时返回空字符串
+func (h *WebHandler) validateOrgID(orgID string) string {
+	if orgID == "" {
+		return ""
+	}
+	if _, err := h.configMgr.GetOrganization(orgID); err != nil {
+		return "unknown organization: " + orgID
+	}
+	return ""
+}
+
+// handleAPIKeyOrg 查看或修改Key所属的组织，org_id 为空字符串时移出组织
+func (h *WebHandler) handleAPIKeyOrg(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":   keyID,
+			"key_name": gatewayKey.Name,
+			"org_id":   gatewayKey.OrgID,
+		})
+	case http.MethodPut:
+		var req struct {
+			OrgID string `json:"org_id"`
+		}
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+			return
+		}
+		if message := h.validateOrgID(req.OrgID); message != "" {
+			h.writeError(w, http.StatusBadRequest, message)
+			return
+		}
+
+		err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+			key.OrgID = req.OrgID
+			key.UpdatedAt = time.Now()
+			return nil
+		})
+		if err != nil {
+			logger.Error("Failed to update organization for API key %s: %v", keyID, err)
+			h.writeError(w, http.StatusInternalServerError, "Failed to update organization")
+			return
+		}
+
+		logger.Info("Moved API key %s to organization %q", keyID, req.OrgID)
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"success": true,
+			"message": "Organization updated successfully",
+		})
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
@@ -0,0 +1,53 @@
+package server
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// overloadedError 判断上游是否报告了过载，判断规则与错误分类相同（upstream.ClassifyStatus）
+func overloadedError(err error) (*upstreamStatusError, bool) {
+	var statusErr *upstreamStatusError
+	if !errors.As(err, &statusErr) {
+		return nil, false
+	}
+	return statusErr, upstream.ClassifyStatus(statusErr.StatusCode, statusErr.Body) == types.ErrorClassOverloaded
+}
+
+// recordOverload 上游报告过载时让账号所属的提供商进入退避窗口，上游的 Retry-After 更长时以它为准
+func (h *ProxyHandler) recordOverload(account *types.UpstreamAccount, err error) {
+	statusErr, ok := overloadedError(err)
+	if !ok {
+		return
+	}
+	var retryAfter time.Duration
+	if seconds, parseErr := strconv.Atoi(strings.TrimSpace(statusErr.RetryAfter)); parseErr == nil && seconds > 0 {
+		retryAfter = time.Duration(seconds) * time.Second
+	}
+	h.upstreamMgr.Overloads().Record(account.Provider, retryAfter, time.Now())
+}
+
+// providerOverloaded 返回提供商退避窗口的结束时间，未启用过载退避或不在退避中时返回 ok=false
+func (h *ProxyHandler) providerOverloaded(provider types.Provider) (time.Time, bool) {
+	return h.upstreamMgr.Overloads().Until(provider, time.Now())
+}
+
+// writeOverloaded 返回 529 和 Retry-After（退避窗口剩余的秒数，至少1秒）
+func (h *ProxyHandler) writeOverloaded(w http.ResponseWriter, provider types.Provider, until time.Time) {
+	retryAfter := int(math.Ceil(time.Until(until).Seconds()))
+	if retryAfter < 1 {
+		retryAfter = 1
+	}
+	w.Header().Set("Retry-After", strconv.Itoa(retryAfter))
+	h.writeErrorDetails(w, upstream.StatusOverloaded, "overloaded",
+		fmt.Sprintf("Provider %s is overloaded, retry after %d seconds", provider, retryAfter),
+		map[string]interface{}{"provider": provider, "retry_after_seconds": retryAfter})
+}
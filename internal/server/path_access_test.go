@@ -0,0 +1,116 @@
+package server
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// pathAccessRequest 以 key 的身份经过 PathAccess 请求 path，返回响应和是否到达了后续处理器
+func pathAccessRequest(h *ProxyHandler, key *types.GatewayAPIKey, path string) (*httptest.ResponseRecorder, bool) {
+	req := httptest.NewRequest(http.MethodPost, path, nil)
+	if key != nil {
+		req.Header.Set("X-Gateway-Key-ID", key.ID)
+		req = req.WithContext(context.WithValue(req.Context(), "gatewayKey", key))
+	}
+	reached := false
+	rec := httptest.NewRecorder()
+	h.PathAccess(func(w http.ResponseWriter, r *http.Request) {
+		reached = true
+		w.WriteHeader(http.StatusNoContent)
+	})(rec, req)
+	return rec, reached
+}
+
+func TestPathAccess_KeyRules(t *testing.T) {
+	s, _ := newReloadTestServer(t, types.ProxyConfig{})
+	h := s.proxyHandler
+	key := &types.GatewayAPIKey{ID: "key-1", OrgID: "org-1", PathRules: &types.PathRules{
+		Allow: []string{"/v1/chat/completions", "/v1/files*"},
+		Deny:  []string{"/v1/files*"},
+	}}
+
+	tests := []struct {
+		name      string
+		key       *types.GatewayAPIKey
+		path      string
+		status    int
+		errorType string
+	}{
+		{name: "allowed", key: key, path: "/v1/chat/completions", status: http.StatusNoContent},
+		{name: "no key", key: nil, path: "/v1/files", status: http.StatusNoContent},
+		{name: "no rules", key: &types.GatewayAPIKey{ID: "key-2"}, path: "/v1/files", status: http.StatusNoContent},
+		// 网关未开放的路径同样按Key的规则检查
+		{name: "denied unknown endpoint", key: key, path: "/v1/files/file-1", status: http.StatusForbidden, errorType: "path_forbidden"},
+		{name: "not in allow list", key: key, path: "/v1/messages", status: http.StatusNotFound, errorType: "path_not_found"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			before := len(h.recorder.Query(stats.Filter{}))
+			rec, reached := pathAccessRequest(h, tt.key, tt.path)
+			if rec.Code != tt.status {
+				t.Fatalf("status = %d, want %d", rec.Code, tt.status)
+			}
+			if reached != (tt.errorType == "") {
+				t.Fatalf("next handler reached = %v, want %v", reached, tt.errorType == "")
+			}
+
+			records := h.recorder.Query(stats.Filter{})
+			if tt.errorType == "" {
+				if len(records) != before {
+					t.Errorf("allowed request recorded %d usage records", len(records)-before)
+				}
+				return
+			}
+
+			var resp struct {
+				Error struct {
+					Type string `json:"type"`
+				} `json:"error"`
+			}
+			_ = json.Unmarshal(rec.Body.Bytes(), &resp)
+			if resp.Error.Type != tt.errorType {
+				t.Errorf("error type = %q, want %q", resp.Error.Type, tt.errorType)
+			}
+			if len(records) != before+1 {
+				t.Fatalf("rejected request recorded %d usage records, want 1", len(records)-before)
+			}
+			record := records[len(records)-1]
+			if record.Success || record.ErrorType != tt.errorType || record.GatewayKeyID != "key-1" || record.OrgID != "org-1" || record.Endpoint != tt.path || record.RequestID == "" {
+				t.Errorf("usage record = %+v, want a failed %s record for key-1 on %s", record, tt.errorType, tt.path)
+			}
+		})
+	}
+}
+
+func TestCheckPathAccess_ProviderRulesRecordUsage(t *testing.T) {
+	s, _ := newReloadTestServer(t, types.ProxyConfig{
+		PathRules: map[types.Provider]types.PathRules{types.ProviderOpenAI: {Deny: []string{"/v1/chat/completions"}}},
+	})
+	h := s.proxyHandler
+
+	if status, _ := h.checkPathAccess("/v1/chat/completions", types.ProviderAnthropic); status != 0 {
+		t.Errorf("checkPathAccess() status = %d for a provider without rules, want 0", status)
+	}
+
+	body := `{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}]}`
+	req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", strings.NewReader(body))
+	req.Header.Set("Content-Type", "application/json")
+	rec := httptest.NewRecorder()
+	h.HandleChatCompletions(rec, req)
+
+	if rec.Code != http.StatusForbidden {
+		t.Fatalf("status = %d, want %d from the provider path rules; body: %s", rec.Code, http.StatusForbidden, rec.Body.String())
+	}
+	records := h.recorder.Query(stats.Filter{})
+	if len(records) != 1 || records[0].ErrorType != "path_forbidden" || records[0].Provider != types.ProviderOpenAI {
+		t.Errorf("usage records = %+v, want one path_forbidden record for openai", records)
+	}
+}
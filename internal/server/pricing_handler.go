@@ -0,0 +1,40 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+
+	"github.com/iBreaker/llm-gateway/internal/pricing"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandlePricing 查看当前价格表（GET）或替换自定义价格（PUT），新价格对之后的请求立即生效
+func (h *WebHandler) HandlePricing(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data":   pricing.Table(),
+			"custom": h.configMgr.Get().Pricing.Models,
+		})
+	case http.MethodPut:
+		var req types.PricingConfig
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if err := h.configMgr.SetPricing(req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid pricing configuration: "+err.Error())
+			return
+		}
+		pricing.SetCustomPrices(req.Models)
+
+		logger.Info("Updated custom model pricing: %d models", len(req.Models))
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data":   pricing.Table(),
+			"custom": req.Models,
+		})
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
@@ -0,0 +1,108 @@
+package server
+
+import (
+	"bytes"
+	"fmt"
+	"net/http"
+	"runtime"
+	"runtime/pprof"
+	"strconv"
+	"strings"
+	"time"
+)
+
+const (
+	// profilePrefix 性能剖析端点的路径前缀
+	profilePrefix = "/api/v1/debug/pprof/"
+
+	defaultCPUProfileSeconds = 30
+	defaultMaxCPUSeconds     = 60
+)
+
+// HandleProfile 采集运行时性能剖析数据（pprof 格式，可直接用 go tool pprof 打开）：
+// GET /api/v1/debug/pprof/profile?seconds=30 采集CPU剖析
+// GET /api/v1/debug/pprof/{heap|allocs|goroutine|block|mutex|threadcreate} 导出对应的运行时剖析
+func (h *WebHandler) HandleProfile(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	name := strings.TrimPrefix(r.URL.Path, profilePrefix)
+	if name == "profile" {
+		h.handleCPUProfile(w, r)
+		return
+	}
+
+	profile := pprof.Lookup(name)
+	if profile == nil {
+		h.writeError(w, http.StatusNotFound, fmt.Sprintf("Unknown profile: %s", name))
+		return
+	}
+
+	if name == "heap" && r.URL.Query().Get("gc") == "1" {
+		runtime.GC()
+	}
+	// debug=1/2 输出可读文本（goroutine 的 debug=2 包含完整堆栈），默认输出 pprof 二进制格式
+	debugLevel, _ := strconv.Atoi(r.URL.Query().Get("debug"))
+
+	var buf bytes.Buffer
+	if err := profile.WriteTo(&buf, debugLevel); err != nil {
+		h.writeError(w, http.StatusInternalServerError, fmt.Sprintf("Failed to write profile: %v", err))
+		return
+	}
+	writeProfile(w, name, debugLevel > 0, buf.Bytes())
+}
+
+// handleCPUProfile 在指定时长内采集CPU剖析，同一时间只能进行一次
+func (h *WebHandler) handleCPUProfile(w http.ResponseWriter, r *http.Request) {
+	maxSeconds := h.configMgr.Get().Server.Profiling.MaxCPUSeconds
+	if maxSeconds <= 0 {
+		maxSeconds = defaultMaxCPUSeconds
+	}
+
+	seconds := defaultCPUProfileSeconds
+	if value := r.URL.Query().Get("seconds"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > maxSeconds {
+			h.writeError(w, http.StatusBadRequest, fmt.Sprintf("seconds must be between 1 and %d", maxSeconds))
+			return
+		}
+		seconds = parsed
+	}
+	if seconds > maxSeconds {
+		seconds = maxSeconds
+	}
+
+	var buf bytes.Buffer
+	if err := pprof.StartCPUProfile(&buf); err != nil {
+		h.writeError(w, http.StatusConflict, "A CPU profile is already being captured")
+		return
+	}
+
+	timer := time.NewTimer(time.Duration(seconds) * time.Second)
+	defer timer.Stop()
+	select {
+	case <-timer.C:
+	case <-r.Context().Done():
+		// 客户端断开时立即停止，避免剖析继续占用
+		pprof.StopCPUProfile()
+		return
+	}
+	pprof.StopCPUProfile()
+
+	writeProfile(w, "cpu", false, buf.Bytes())
+}
+
+// writeProfile 写出剖析数据，二进制格式作为附件下载
+func writeProfile(w http.ResponseWriter, name string, text bool, data []byte) {
+	if text {
+		w.Header().Set("Content-Type", "text/plain; charset=utf-8")
+	} else {
+		w.Header().Set("Content-Type", "application/octet-stream")
+		w.Header().Set("Content-Disposition", fmt.Sprintf(`attachment; filename="%s-%s.pprof"`, name, time.Now().Format("20060102-150405")))
+	}
+	w.Header().Set("Cache-Control", "no-store")
+	w.WriteHeader(http.StatusOK)
+	_, _ = w.Write(data)
+}
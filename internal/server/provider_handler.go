@@ -0,0 +1,62 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleProviders 列出已注册的提供商及其启用状态
+func (h *WebHandler) HandleProviders(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"data": h.upstreamMgr.Providers().List(),
+	})
+}
+
+// HandleProviderActions 启用或禁用提供商（PUT /api/v1/providers/{provider}），立即生效无需重启
+func (h *WebHandler) HandleProviderActions(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPut {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) != 4 {
+		h.writeError(w, http.StatusBadRequest, "Invalid provider")
+		return
+	}
+	provider := types.Provider(pathParts[3]) // /api/v1/providers/{provider}
+
+	var req struct {
+		Enabled *bool `json:"enabled"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil || req.Enabled == nil {
+		h.writeError(w, http.StatusBadRequest, "enabled is required")
+		return
+	}
+
+	if err := h.upstreamMgr.Providers().SetEnabled(provider, *req.Enabled); err != nil {
+		h.writeError(w, http.StatusNotFound, "Provider not found")
+		return
+	}
+
+	if err := h.configMgr.SetProviderEnabled(provider, *req.Enabled); err != nil {
+		logger.Error("Failed to save provider settings: %v", err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to save provider settings")
+		return
+	}
+
+	logger.Info("Provider %s enabled=%v", provider, *req.Enabled)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Provider updated successfully",
+	})
+}
@@ -2,19 +2,32 @@ package server
 
 import (
 	"bytes"
-	"crypto/rand"
-	"encoding/hex"
 	"encoding/json"
+	"errors"
 	"fmt"
 	"io"
-	"log"
+	"mime"
 	"net/http"
+	"strconv"
 	"strings"
+	"sync"
 	"time"
 
+	"github.com/iBreaker/llm-gateway/internal/audit"
+	"github.com/iBreaker/llm-gateway/internal/cache"
 	"github.com/iBreaker/llm-gateway/internal/client"
 	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/models"
+	"github.com/iBreaker/llm-gateway/internal/moderation"
+	"github.com/iBreaker/llm-gateway/internal/pricing"
+	"github.com/iBreaker/llm-gateway/internal/quota"
+	"github.com/iBreaker/llm-gateway/internal/ratelimit"
 	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/sandbox"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/tokens"
+	"github.com/iBreaker/llm-gateway/internal/transform"
+	"github.com/iBreaker/llm-gateway/internal/trim"
 	"github.com/iBreaker/llm-gateway/internal/upstream"
 	"github.com/iBreaker/llm-gateway/pkg/debug"
 	"github.com/iBreaker/llm-gateway/pkg/logger"
@@ -27,16 +40,50 @@ type ProxyHandler struct {
 	upstreamMgr      *upstream.UpstreamManager
 	router           *router.RequestRouter
 	converter        *converter.Manager
+	recorder         *stats.Recorder
+	modelRouteSource func() *types.ModelRouteConfig // 返回当前的全局模型路由配置，通过 modelRoutes() 读取
+	checkedRoutes    *types.ModelRouteConfig        // 最近一次校验的模型路由配置
+	routesValid      bool                           // checkedRoutes 是否通过校验
+	routesMutex      sync.Mutex
+	modelRegistry    *models.Registry
+	audit            *audit.Log
+	quota            *quota.Service                // 为nil时不做转发前的token配额预检查
+	concurrency      *ratelimit.ConcurrencyLimiter // 上游账号的并发限制
+	queue            *ratelimit.Queue              // 账号都达到并发上限时的等待队列，未启用排队时为nil（修改需要重启）
+	current          *proxySettings                // 重新加载配置时整体替换，通过 settings() 读取
+	settingsMutex    sync.RWMutex
+	drain            *drainGate    // 跟踪异步的统计和审计写入，为nil时不跟踪
+	live             *liveRequests // 进行中的请求，供实时流量接口查看
+}
+
+// proxySettings 按代理配置生成的设置，创建后不再修改；重新加载配置时整体替换，
+// 每个请求开始时取一次，进行中的请求继续使用原来的设置
+type proxySettings struct {
 	httpClient       *http.Client
-	modelRouteConfig *types.ModelRouteConfig
+	pathRules        map[types.Provider]types.PathRules
+	usageHeaders     bool
+	normalizeOpts    converter.NormalizeOptions
+	paramFilter      converter.ParamFilter
+	maxRetryAttempts int
+	maxRequestBytes  int64 // 请求体大小上限，超过时返回413
+	modelValidation  string
+	queueWait        time.Duration        // 最长排队时间
+	responseCache    *cache.ResponseCache // 未启用响应缓存时为nil
+	transforms       *transform.Pipeline  // 请求/响应转换器，未配置时为nil
+	moderation       *moderation.Gate     // 内容审核，未配置审核策略时为nil
+	contextTrim      *trim.Trimmer        // 上下文超限时删减消息重试，未配置时为nil
+	sandbox          *sandbox.Responder   // 沙箱Key的模拟响应器
 }
 
 // httpStreamWriter HTTP流式写入器
 type httpStreamWriter struct {
-	writer      http.ResponseWriter
+	writer      *clientWriter
 	flusher     http.Flusher
 	totalTokens *int
 	trace       *debug.RequestTrace
+	beforeDone  func()                    // 在首个[DONE]之前执行一次
+	omitDone    bool                      // 客户端格式没有[DONE]结束标记（Gemini）
+	filter      *transform.ResponseFilter // 响应转换器，为nil时不处理
 }
 
 // WriteChunk 写入数据块
@@ -45,7 +92,13 @@ func (w *httpStreamWriter) WriteChunk(chunk *converter.StreamChunk) error {
 	var rawData []byte
 	var convertedData []byte
 
+	if chunk.IsDone && w.omitDone {
+		w.runBeforeDone()
+		return nil
+	}
+
 	if chunk.IsDone {
+		w.runBeforeDone()
 		rawData = []byte("[DONE]")
 		_, _ = fmt.Fprintf(w.writer, "data: [DONE]\n\n")
 		convertedData = []byte("data: [DONE]\n\n")
@@ -60,6 +113,9 @@ func (w *httpStreamWriter) WriteChunk(chunk *converter.StreamChunk) error {
 			return err
 		}
 		rawData = data
+		if w.filter != nil {
+			data = w.filter.Body(data)
+		}
 
 		if chunk.EventType != "" {
 			convertedData = []byte(fmt.Sprintf("event: %s\ndata: %s\n\n", chunk.EventType, string(data)))
@@ -81,35 +137,163 @@ func (w *httpStreamWriter) WriteChunk(chunk *converter.StreamChunk) error {
 
 	w.flusher.Flush()
 	*w.totalTokens += chunk.Tokens
-	return nil
+	return w.writer.err
 }
 
 // WriteDone 写入完成信号
 func (w *httpStreamWriter) WriteDone() error {
+	w.runBeforeDone()
+	if w.omitDone {
+		w.flusher.Flush()
+		return nil
+	}
 	_, _ = fmt.Fprintf(w.writer, "data: [DONE]\n\n")
 	w.flusher.Flush()
-	return nil
+	return w.writer.err
+}
+
+// runBeforeDone 执行结束前回调（只执行一次）
+func (w *httpStreamWriter) runBeforeDone() {
+	if w.beforeDone != nil {
+		beforeDone := w.beforeDone
+		w.beforeDone = nil
+		beforeDone()
+	}
+}
+
+// clientWriter 记录向客户端写入时的第一个错误，用于区分客户端断开和上游故障
+type clientWriter struct {
+	writer io.Writer
+	err    error
 }
 
-// NewProxyHandler 创建代理处理器
+// Write 写入客户端连接
+func (w *clientWriter) Write(p []byte) (int, error) {
+	n, err := w.writer.Write(p)
+	if err != nil && w.err == nil {
+		w.err = err
+	}
+	return n, err
+}
+
+// NewProxyHandler 创建代理处理器，modelRoutes 返回当前的全局模型路由配置（修改后下个请求生效）
 func NewProxyHandler(
 	gatewayKeyMgr *client.GatewayKeyManager,
 	upstreamMgr *upstream.UpstreamManager,
 	router *router.RequestRouter,
 	converter *converter.Manager,
+	recorder *stats.Recorder,
 	proxyConfig *types.ProxyConfig,
-	modelRouteConfig *types.ModelRouteConfig,
+	modelRoutes func() *types.ModelRouteConfig,
 ) *ProxyHandler {
-	// 验证模型路由配置
-	if modelRouteConfig != nil {
-		if err := modelRouteConfig.Validate(); err != nil {
-			logger.Warn("模型路由配置验证失败，将禁用模型路由功能: %v", err)
-			modelRouteConfig = nil
-		} else {
-			logger.Info("模型路由配置验证成功，共 %d 条路由规则", len(modelRouteConfig.Routes))
+	var queue *ratelimit.Queue
+	if proxyConfig != nil && proxyConfig.Queue.Enabled {
+		size := defaultQueueSize
+		if proxyConfig.Queue.MaxSize > 0 {
+			size = proxyConfig.Queue.MaxSize
+		}
+		queue = ratelimit.NewQueue(size)
+	}
+
+	// 上游账号的并发名额同时为最少连接策略提供进行中的请求数
+	concurrency := ratelimit.NewConcurrencyLimiter()
+	router.SetLoadSource(concurrency)
+
+	h := &ProxyHandler{
+		gatewayKeyMgr:    gatewayKeyMgr,
+		upstreamMgr:      upstreamMgr,
+		router:           router,
+		converter:        converter,
+		recorder:         recorder,
+		modelRouteSource: modelRoutes,
+		modelRegistry:    models.Default(),
+		concurrency:      concurrency,
+		queue:            queue,
+		current:          newProxySettings(proxyConfig, nil),
+		live:             newLiveRequests(),
+	}
+	if routes := h.modelRoutes(); routes != nil {
+		logger.Info("模型路由配置验证成功，共 %d 条路由规则", len(routes.Routes))
+	}
+	return h
+}
+
+// newProxySettings 按代理配置生成设置，previous 为当前设置（首次创建时为nil）：
+// 响应缓存保持启用时沿用原来的缓存及其中的条目
+func newProxySettings(proxyConfig *types.ProxyConfig, previous *proxySettings) *proxySettings {
+	settings := &proxySettings{
+		httpClient:       newUpstreamHTTPClient(proxyConfig),
+		maxRetryAttempts: 2, // 默认最多切换2次账号
+		maxRequestBytes:  int64(types.MaxRequestSizeBytes),
+		modelValidation:  models.ValidationOff,
+		queueWait:        defaultQueueWaitSec * time.Second,
+	}
+	if proxyConfig == nil {
+		settings.sandbox = sandbox.New(nil)
+		return settings
+	}
+
+	if proxyConfig.MaxRetryAttempts != 0 {
+		settings.maxRetryAttempts = proxyConfig.MaxRetryAttempts
+	}
+	if settings.maxRetryAttempts < 0 {
+		settings.maxRetryAttempts = 0
+	}
+	if proxyConfig.MaxRequestBytes > 0 {
+		settings.maxRequestBytes = proxyConfig.MaxRequestBytes
+	}
+	if proxyConfig.ModelValidation != "" {
+		switch proxyConfig.ModelValidation {
+		case models.ValidationOff, models.ValidationNormalize, models.ValidationStrict:
+			settings.modelValidation = proxyConfig.ModelValidation
+		default:
+			logger.Warn("未知的模型校验模式 %q，将不进行模型名校验", proxyConfig.ModelValidation)
 		}
 	}
-	// 设置超时配置，使用传入的配置或默认值
+	if proxyConfig.Queue.MaxWaitSeconds > 0 {
+		settings.queueWait = time.Duration(proxyConfig.Queue.MaxWaitSeconds) * time.Second
+	}
+
+	switch {
+	case !proxyConfig.ResponseCache.Enabled:
+	case previous != nil && previous.responseCache != nil:
+		settings.responseCache = previous.responseCache
+		settings.responseCache.Configure(&proxyConfig.ResponseCache)
+	default:
+		settings.responseCache = cache.NewResponseCache(&proxyConfig.ResponseCache)
+	}
+	if pipeline, err := transform.New(proxyConfig.Transforms); err != nil {
+		logger.Warn("转换器配置无效，将禁用请求/响应转换: %v", err)
+	} else {
+		settings.transforms = pipeline
+	}
+	if gate, err := moderation.New(&proxyConfig.Moderation); err != nil {
+		logger.Warn("内容审核配置无效，将禁用内容审核: %v", err)
+	} else {
+		settings.moderation = gate
+	}
+	settings.contextTrim = trim.New(&proxyConfig.ContextTrim)
+	settings.sandbox = sandbox.New(&proxyConfig.Sandbox)
+	settings.pathRules = proxyConfig.PathRules
+	settings.usageHeaders = proxyConfig.UsageHeaders
+	settings.normalizeOpts = converter.NormalizeOptions{
+		MergeConsecutive: proxyConfig.MergeConsecutiveMessages,
+		MaxMessages:      proxyConfig.MaxMessages,
+	}
+
+	settings.paramFilter.Allow = proxyConfig.Params.Allow
+	switch proxyConfig.Params.Mode {
+	case "", converter.ParamModeAllowlist:
+	case converter.ParamModePassthrough:
+		settings.paramFilter.Passthrough = true
+	default:
+		logger.Warn("未知的参数过滤模式 %q，将只转发允许列表中的参数", proxyConfig.Params.Mode)
+	}
+	return settings
+}
+
+// newUpstreamHTTPClient 按代理配置创建请求上游的HTTP客户端，未配置的超时使用默认值
+func newUpstreamHTTPClient(proxyConfig *types.ProxyConfig) *http.Client {
 	streamTimeout := 5 * time.Minute // 默认5分钟
 	if proxyConfig != nil && proxyConfig.StreamTimeout > 0 {
 		streamTimeout = time.Duration(proxyConfig.StreamTimeout) * time.Second
@@ -130,24 +314,62 @@ func NewProxyHandler(
 		responseTimeout = time.Duration(proxyConfig.ResponseTimeout) * time.Second
 	}
 
-	return &ProxyHandler{
-		gatewayKeyMgr:    gatewayKeyMgr,
-		upstreamMgr:      upstreamMgr,
-		router:           router,
-		converter:        converter,
-		modelRouteConfig: modelRouteConfig,
-		httpClient: &http.Client{
-			Timeout: streamTimeout,
-			Transport: &http.Transport{
-				Proxy:                 http.ProxyFromEnvironment,
-				IdleConnTimeout:       idleTimeout,
-				TLSHandshakeTimeout:   tlsTimeout,
-				ResponseHeaderTimeout: responseTimeout,
-			},
+	return &http.Client{
+		Timeout: streamTimeout,
+		Transport: &http.Transport{
+			Proxy:                 http.ProxyFromEnvironment,
+			IdleConnTimeout:       idleTimeout,
+			TLSHandshakeTimeout:   tlsTimeout,
+			ResponseHeaderTimeout: responseTimeout,
 		},
 	}
 }
 
+// Reconfigure 按重新加载的代理配置替换全部代理设置（超时、重试次数、请求体上限、路径规则、用量响应头、
+// 消息规范化、参数过滤、排队时间、响应缓存、转换器、内容审核等），之后的请求立即使用新设置，
+// 进行中的请求继续使用原来的设置；响应缓存保持启用时保留已缓存的条目。排队的启用和容量需要重启
+func (h *ProxyHandler) Reconfigure(proxyConfig *types.ProxyConfig) {
+	h.settingsMutex.Lock()
+	previous := h.current
+	h.current = newProxySettings(proxyConfig, previous)
+	h.settingsMutex.Unlock()
+
+	// 原客户端的空闲连接不再复用，进行中的请求不受影响
+	previous.httpClient.CloseIdleConnections()
+}
+
+// settings 返回当前的代理设置
+func (h *ProxyHandler) settings() *proxySettings {
+	h.settingsMutex.RLock()
+	defer h.settingsMutex.RUnlock()
+	return h.current
+}
+
+// modelRoutes 返回当前的全局模型路由配置，未配置或规则无效时为nil（配置变化后重新校验一次）
+func (h *ProxyHandler) modelRoutes() *types.ModelRouteConfig {
+	if h.modelRouteSource == nil {
+		return nil
+	}
+	routes := h.modelRouteSource()
+
+	h.routesMutex.Lock()
+	defer h.routesMutex.Unlock()
+	if routes != h.checkedRoutes {
+		h.checkedRoutes = routes
+		h.routesValid = true
+		if routes == nil {
+			h.routesValid = false
+		} else if err := routes.Validate(); err != nil {
+			logger.Warn("模型路由配置验证失败，将禁用模型路由功能: %v", err)
+			h.routesValid = false
+		}
+	}
+	if !h.routesValid {
+		return nil
+	}
+	return routes
+}
+
 // HandleChatCompletions 处理聊天完成请求
 func (h *ProxyHandler) HandleChatCompletions(w http.ResponseWriter, r *http.Request) {
 	h.handleProxyRequest(w, r, "/v1/chat/completions")
@@ -163,35 +385,245 @@ func (h *ProxyHandler) HandleMessages(w http.ResponseWriter, r *http.Request) {
 	h.handleProxyRequest(w, r, "/v1/messages")
 }
 
-// generateRequestID 生成请求ID
-func (h *ProxyHandler) generateRequestID() string {
-	bytes := make([]byte, 8)
-	_, _ = rand.Read(bytes) // crypto/rand.Read never fails
-	return hex.EncodeToString(bytes)
+// PathAccess 在分发到具体端点之前按Key级别的路径规则检查请求路径（包括网关未开放的 /v1 路径），
+// 需要放在认证中间件之后；提供商级别的规则在确定目标提供商后由 checkPathAccess 检查
+func (h *ProxyHandler) PathAccess(next http.HandlerFunc) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		gatewayKey, _ := r.Context().Value("gatewayKey").(*types.GatewayAPIKey)
+		if gatewayKey == nil {
+			next(w, r)
+			return
+		}
+
+		var status int
+		var message string
+		switch gatewayKey.PathRules.Evaluate(r.URL.Path) {
+		case types.PathDenied:
+			status, message = http.StatusForbidden, fmt.Sprintf("Path %s is not allowed for this API key", r.URL.Path)
+		case types.PathNotAllowed:
+			status, message = http.StatusNotFound, fmt.Sprintf("Path %s is not available for this API key", r.URL.Path)
+		default:
+			next(w, r)
+			return
+		}
+
+		startTime := time.Now()
+		r = withRequestID(w, r)
+		record := &stats.UsageRecord{
+			RequestID:    requestIDFrom(r.Context()),
+			Timestamp:    startTime,
+			GatewayKeyID: r.Header.Get("X-Gateway-Key-ID"),
+			OrgID:        gatewayKey.OrgID,
+			Sandbox:      gatewayKey.Sandbox,
+			Endpoint:     r.URL.Path,
+		}
+		h.rejectPath(w, record, startTime, status, message)
+	}
+}
+
+// checkPathAccess 按提供商级别规则检查路径，返回0表示允许访问（Key级别的规则已由 PathAccess 检查）
+func (h *ProxyHandler) checkPathAccess(path string, provider types.Provider) (int, string) {
+	rules, ok := h.settings().pathRules[provider]
+	if !ok {
+		return 0, ""
+	}
+
+	switch rules.Evaluate(path) {
+	case types.PathDenied:
+		return http.StatusForbidden, fmt.Sprintf("Path %s is not allowed for provider %s", path, provider)
+	case types.PathNotAllowed:
+		return http.StatusNotFound, fmt.Sprintf("Path %s is not available for provider %s", path, provider)
+	default:
+		return 0, ""
+	}
+}
+
+// rejectPath 记录并返回路径规则拒绝的请求：403 为 path_forbidden，404 为 path_not_found
+func (h *ProxyHandler) rejectPath(w http.ResponseWriter, record *stats.UsageRecord, startTime time.Time, status int, message string) {
+	errorType := "path_not_found"
+	if status == http.StatusForbidden {
+		errorType = "path_forbidden"
+	}
+	h.finishUsage(record, startTime, errorType)
+	h.writeErrorResponse(w, status, errorType, message)
+}
+
+// checkKeyScopes 检查Key的作用域是否允许提供商和模型，返回拒绝原因，允许时返回空字符串
+func (h *ProxyHandler) checkKeyScopes(gatewayKey *types.GatewayAPIKey, provider types.Provider, model string) string {
+	if gatewayKey == nil {
+		return ""
+	}
+	if !gatewayKey.ScopeAllows(types.ScopeProvider, string(provider)) {
+		return fmt.Sprintf("API key is not allowed to use provider %s", provider)
+	}
+	if !h.gatewayKeyMgr.ModelScopes(gatewayKey).Allows(provider, model) {
+		return fmt.Sprintf("API key is not allowed to use model %s", model)
+	}
+	return ""
+}
+
+// isJSONContentType 判断请求的Content-Type是否为JSON，未设置时按JSON处理
+func isJSONContentType(contentType string) bool {
+	if contentType == "" {
+		return true
+	}
+	mediaType, _, err := mime.ParseMediaType(contentType)
+	if err != nil {
+		return false
+	}
+	return mediaType == "application/json" || strings.HasSuffix(mediaType, "+json")
+}
+
+// errRequestTooLarge 请求体超过 proxy.max_request_bytes
+var errRequestTooLarge = errors.New("请求体超过大小上限")
+
+// readRequestBody 按字节读取请求体，超过 limit 字节时返回 errRequestTooLarge。
+// Content-Length 已超过上限时不读取直接拒绝；已知长度时预分配缓冲区，chunked上传（长度未知）时流式读取，
+// 读到上限就停止，不会缓冲整个超大的请求体
+func readRequestBody(w http.ResponseWriter, r *http.Request, limit int64) ([]byte, error) {
+	if r.ContentLength > limit {
+		return nil, errRequestTooLarge
+	}
+	var buf bytes.Buffer
+	if r.ContentLength > 0 {
+		buf.Grow(int(r.ContentLength))
+	}
+	if _, err := buf.ReadFrom(http.MaxBytesReader(w, r.Body, limit)); err != nil {
+		var tooLarge *http.MaxBytesError
+		if errors.As(err, &tooLarge) {
+			return nil, errRequestTooLarge
+		}
+		return nil, err
+	}
+	if r.ContentLength > 0 && int64(buf.Len()) != r.ContentLength {
+		return nil, fmt.Errorf("请求体长度 %d 与Content-Length %d 不一致", buf.Len(), r.ContentLength)
+	}
+	return buf.Bytes(), nil
+}
+
+// allowedModels 返回Key可以使用的模型名：注册表和 OpenAI 兼容后端中已启用提供商且在Key作用域内的模型，以及模型路由中的精确源模型
+func (h *ProxyHandler) allowedModels(gatewayKey *types.GatewayAPIKey) []string {
+	var result []string
+	for _, model := range h.modelRegistry.Models() {
+		if h.upstreamMgr.Providers().IsEnabled(model.Provider) && h.checkKeyScopes(gatewayKey, model.Provider, model.ID) == "" {
+			result = append(result, model.ID)
+		}
+	}
+	if h.upstreamMgr.Providers().IsEnabled(types.ProviderOpenAICompatible) {
+		for _, model := range h.upstreamMgr.DiscoveredModels() {
+			if h.checkKeyScopes(gatewayKey, types.ProviderOpenAICompatible, model) == "" {
+				result = append(result, model)
+			}
+		}
+	}
+
+	addRoutes := func(config *types.ModelRouteConfig) {
+		if config == nil {
+			return
+		}
+		for _, route := range config.Routes {
+			if route.Enabled && !strings.Contains(route.SourceModel, "*") {
+				result = append(result, route.SourceModel)
+			}
+		}
+	}
+	if gatewayKey != nil {
+		addRoutes(gatewayKey.ModelRoutes)
+	}
+	addRoutes(h.modelRoutes())
+
+	return result
+}
+
+// servedByCompatibleBackend 检查模型是否由某个 OpenAI 兼容后端提供
+func (h *ProxyHandler) servedByCompatibleBackend(model string) bool {
+	for _, served := range h.upstreamMgr.DiscoveredModels() {
+		if served == model {
+			return true
+		}
+	}
+	return false
+}
+
+// upstreamRequestIDHeaders 上游返回自身请求ID的响应头（Anthropic 为 request-id，OpenAI 等为 x-request-id）
+var upstreamRequestIDHeaders = []string{"Request-Id", "X-Request-Id"}
+
+// upstreamRequestID 从上游响应头中取出上游的请求ID
+func upstreamRequestID(header http.Header) string {
+	for _, name := range upstreamRequestIDHeaders {
+		if id := header.Get(name); id != "" {
+			return id
+		}
+	}
+	return ""
 }
 
 // handleProxyRequest 处理代理请求的核心逻辑
 func (h *ProxyHandler) handleProxyRequest(w http.ResponseWriter, r *http.Request, clientEndpoint string) {
 	startTime := time.Now()
 
-	// 生成请求ID
-	requestID := h.generateRequestID()
+	// 请求ID由 RequestContextMiddleware 确定并已写入响应头（未经过中间件时在此生成）
+	r = withRequestID(w, r)
+	requestID := requestIDFrom(r.Context())
 
-	// 初始化调试跟踪
-	trace := debug.NewRequestTrace(requestID)
+	// 初始化调试跟踪（按路由采样，未采样的请求不记录）
+	var trace *debug.RequestTrace
+	if debug.Sampled(r) {
+		trace = debug.NewRequestTrace(requestID)
+	}
+
+	// 初始化使用记录
+	record := &stats.UsageRecord{
+		RequestID: requestID,
+		Timestamp: startTime,
+		Endpoint:  clientEndpoint,
+	}
 
-	// 1. 读取请求体
-	requestBody, err := io.ReadAll(r.Body)
+	// 1. 读取请求体（代理端点只接受JSON，支持Content-Length和chunked两种上传方式）
+	if !isJSONContentType(r.Header.Get("Content-Type")) {
+		h.writeErrorResponse(w, http.StatusUnsupportedMediaType, "unsupported_media_type", fmt.Sprintf("Unsupported Content-Type %q, expected application/json", r.Header.Get("Content-Type")))
+		return
+	}
+	settings := h.settings()
+	requestBody, err := readRequestBody(w, r, settings.maxRequestBytes)
 	if err != nil {
 		if trace != nil {
 			trace.SetError(err, "read_request_body")
 			trace.SaveAsync()
 		}
+		if errors.Is(err, errRequestTooLarge) {
+			h.writeRequestTooLarge(w, settings.maxRequestBytes)
+			return
+		}
 		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_request_body", "Failed to read request body")
 		return
 	}
 	defer func() { _ = r.Body.Close() }()
 
+	// 审计日志：记录请求体和写给客户端的响应，请求结束后异步写入
+	if keyID := r.Header.Get("X-Gateway-Key-ID"); h.audit.ShouldAudit(keyID) {
+		requestCapture := h.audit.NewCapture()
+		_, _ = requestCapture.Write(requestBody)
+		auditWriter := &auditResponseWriter{ResponseWriter: w, capture: h.audit.NewCapture()}
+		w = auditWriter
+
+		defer func() {
+			entry := &audit.Entry{
+				RequestID:         requestID,
+				Timestamp:         startTime,
+				GatewayKeyID:      keyID,
+				UpstreamID:        record.UpstreamID,
+				UpstreamRequestID: record.UpstreamRequestID,
+				Provider:          record.Provider,
+				Model:             record.Model,
+				Endpoint:          clientEndpoint,
+				StatusCode:        auditWriter.status,
+				LatencyMs:         time.Since(startTime).Milliseconds(),
+			}
+			h.drain.async(func() { h.audit.Record(entry, requestCapture, auditWriter.capture) })
+		}()
+	}
+
 	// 记录原始客户端请求
 	if trace != nil {
 		trace.SetClientRequest(requestBody)
@@ -209,10 +641,10 @@ func (h *ProxyHandler) handleProxyRequest(w http.ResponseWriter, r *http.Request
 	}
 
 	// 3. 模型路由处理（优先使用Key级别配置）
+	gatewayKey, _ := r.Context().Value("gatewayKey").(*types.GatewayAPIKey)
 	var modelRouteContext *types.ModelRouteContext
-	if h.modelRouteConfig != nil {
-		gatewayKey, _ := r.Context().Value("gatewayKey").(*types.GatewayAPIKey)
-		modelRouteContext = h.modelRouteConfig.CreateContextWithKey(tempReq.Model, gatewayKey)
+	if modelRoutes := h.modelRoutes(); modelRoutes != nil {
+		modelRouteContext = modelRoutes.CreateContextWithKey(tempReq.Model, gatewayKey)
 	}
 
 	// 4. 重新解析请求并应用模型路由
@@ -226,24 +658,218 @@ func (h *ProxyHandler) handleProxyRequest(w http.ResponseWriter, r *http.Request
 		return
 	}
 
+	// 4.1. 未命中模型路由时规范化模型名，strict模式下拒绝未知模型（OpenAI 兼容后端发现的模型视为已知）
+	if settings.modelValidation != models.ValidationOff && (modelRouteContext == nil || !modelRouteContext.Enabled) {
+		if canonical, ok := h.modelRegistry.Normalize(proxyReq.Model); ok {
+			if canonical != proxyReq.Model {
+				logger.Debug("模型名规范化: %s -> %s", proxyReq.Model, canonical)
+				proxyReq.Model = canonical
+			}
+		} else if settings.modelValidation == models.ValidationStrict && !h.servedByCompatibleBackend(proxyReq.Model) {
+			message := fmt.Sprintf("Unknown model %q", proxyReq.Model)
+			if suggestions := models.Suggest(proxyReq.Model, h.allowedModels(gatewayKey), 3); len(suggestions) > 0 {
+				message += fmt.Sprintf(", did you mean: %s?", strings.Join(suggestions, ", "))
+			}
+			if trace != nil {
+				trace.SetError(fmt.Errorf("未知模型: %s", proxyReq.Model), "model_validation")
+				trace.SaveAsync()
+			}
+			h.writeErrorResponse(w, http.StatusBadRequest, "model_not_found", message)
+			return
+		}
+	}
+
 	// 5. 设置请求上下文信息
 	keyID := r.Header.Get("X-Gateway-Key-ID")
 	proxyReq.GatewayKeyID = keyID
+	proxyReq.RequestID = requestID
+	record.GatewayKeyID = keyID
+	record.Model = proxyReq.Model
+	record.RequestedModel = tempReq.Model
+	record.Stream = proxyReq.Stream != nil && *proxyReq.Stream
+	record.Language = stats.DetectLanguage(stats.PromptText(proxyReq))
+	record.App, record.AppVersion, _ = types.ParseClientApp(r.Header.Get(types.ClientAppHeader))
+	if gatewayKey != nil {
+		record.OrgID = gatewayKey.OrgID // 用量计入Key所属的组织
+		record.Sandbox = gatewayKey.Sandbox
+	}
+	h.live.add(record)
+	defer h.live.remove(record)
+
+	// 排队优先级由客户端指定，不能超过Key允许的最高优先级
+	priority, ok := requestPriority(r, gatewayKey)
+	if !ok {
+		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_priority", fmt.Sprintf("Invalid %s %q, expected critical, high, normal or low", priorityHeader, r.Header.Get(priorityHeader)))
+		return
+	}
+
+	// 客户端可以通过 X-LLM-Timeout-Ms 指定本次请求的上游超时，不能超过Key允许的最大值
+	timeout, ok := requestTimeout(r, gatewayKey, settings.httpClient.Timeout)
+	if !ok {
+		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_timeout", fmt.Sprintf("Invalid %s %q, expected a positive number of milliseconds", timeoutHeader, r.Header.Get(timeoutHeader)))
+		return
+	}
+	proxyReq.Timeout = timeout
+
+	// 5.1. 评估运营配置的路由规则（按模型、Key标签、时间段、估算token数和是否使用工具匹配），
+	// 可以拒绝请求、调整排队优先级或指定提供商和账号池；此时还未确定提供商，按通用分词方式估算token
+	routingCtx := &types.RoutingContext{
+		Model:       proxyReq.Model,
+		InputTokens: tokens.ForProvider("").CountRequest(proxyReq),
+		Time:        time.Now(),
+		HasTools:    proxyReq.HasTools(),
+	}
+	if gatewayKey != nil {
+		routingCtx.KeyTags = gatewayKey.Tags
+	}
+	decision := h.router.MatchRequest(routingCtx)
+	if rule := decision.Deny; rule != nil {
+		if trace != nil {
+			trace.SetError(fmt.Errorf("命中拒绝规则: %s", rule.ID), "routing_rules")
+			trace.SaveAsync()
+		}
+		h.finishUsage(record, startTime, "routing_denied")
+		h.writeErrorResponse(w, http.StatusForbidden, "routing_denied", fmt.Sprintf("Request denied by routing rule %s", rule.ID))
+		return
+	}
+	if rule := decision.Priority; rule != nil {
+		logger.Debug("路由规则 %s 将排队优先级设置为 %s", rule.ID, rule.QueuePriority)
+		priority = rule.QueuePriority
+	}
 
 	// 记录模型路由后的请求
 	if trace != nil {
 		trace.SetUnifiedRequest(proxyReq)
 	}
 
-	// 6. 确定目标提供商（根据模型路由上下文或模型名称）
+	// 6. 确定目标提供商（模型路由优先，其次是命中的路由规则，最后按模型名称推断）
 	var targetProvider types.Provider
 	if modelRouteContext != nil && modelRouteContext.Enabled {
 		targetProvider = modelRouteContext.TargetProvider
+	} else if decision.Route != nil {
+		targetProvider = decision.Route.Provider
 	} else {
 		targetProvider = h.router.DetermineProvider(proxyReq.Model)
 	}
+	record.Provider = targetProvider
+
+	// 6.1. 检查提供商是否已启用（可在运行时通过Web管理界面切换）
+	if !h.upstreamMgr.Providers().IsEnabled(targetProvider) {
+		if trace != nil {
+			trace.SetError(fmt.Errorf("provider %s is disabled", targetProvider), "provider_disabled")
+			trace.SaveAsync()
+		}
+		h.finishUsage(record, startTime, "provider_disabled")
+		h.writeErrorResponse(w, http.StatusServiceUnavailable, "provider_disabled", fmt.Sprintf("Provider %s is disabled", targetProvider))
+		return
+	}
+
+	// 6.2. 提供商在过载退避窗口内时直接返回529（路由规则有其他提供商可选时已经改用了备选规则）
+	if until, overloaded := h.providerOverloaded(targetProvider); overloaded && !record.Sandbox {
+		h.finishUsage(record, startTime, "provider_overloaded")
+		h.writeOverloaded(w, targetProvider, until)
+		return
+	}
+
+	// 6.3. 检查提供商级别的路径访问规则（在路由选择之前）
+	if status, message := h.checkPathAccess(clientEndpoint, targetProvider); status != 0 {
+		if trace != nil {
+			trace.SetError(fmt.Errorf("%s", message), "path_rules")
+			trace.SaveAsync()
+		}
+		h.rejectPath(w, record, startTime, status, message)
+		return
+	}
+
+	// 6.4. 检查Key的提供商和模型作用域（按实际发往上游的提供商和模型）
+	if message := h.checkKeyScopes(gatewayKey, targetProvider, proxyReq.Model); message != "" {
+		if trace != nil {
+			trace.SetError(fmt.Errorf("%s", message), "key_scopes")
+			trace.SaveAsync()
+		}
+		h.finishUsage(record, startTime, "scope_forbidden")
+		h.writeErrorResponse(w, http.StatusForbidden, "scope_forbidden", message)
+		return
+	}
 
-	// 5.1. 通过 converter 获取上游路径
+	// 6.5. 声明了工具的请求只能发往支持工具调用的模型，避免上游忽略工具或返回难以理解的错误
+	if routingCtx.HasTools && !h.modelRegistry.SupportsTools(proxyReq.Model) {
+		if trace != nil {
+			trace.SetError(fmt.Errorf("模型不支持工具调用: %s", proxyReq.Model), "tools")
+			trace.SaveAsync()
+		}
+		h.finishUsage(record, startTime, "tools_not_supported")
+		h.writeErrorResponse(w, http.StatusBadRequest, "tools_not_supported", fmt.Sprintf("Model %s does not support tool calling", proxyReq.Model))
+		return
+	}
+
+	// 6.6. 规范化消息并校验目标提供商的角色顺序约束
+	if err := h.converter.NormalizeRequest(proxyReq, targetProvider, settings.normalizeOpts); err != nil {
+		if trace != nil {
+			trace.SetError(err, "normalize_request")
+			trace.SaveAsync()
+		}
+		var imageErr *converter.ImageError
+		if errors.As(err, &imageErr) {
+			h.writeErrorResponse(w, http.StatusBadRequest, "invalid_image", fmt.Sprintf("Invalid image in message %d: %s", imageErr.Index, imageErr.Reason))
+			return
+		}
+		h.writeErrorResponse(w, http.StatusBadRequest, "invalid_messages", fmt.Sprintf("Invalid messages: %v", err))
+		return
+	}
+
+	// 删除目标提供商不支持的额外参数，避免其他提供商的专有参数在转换后导致上游报错
+	if stripped := h.converter.FilterParams(proxyReq, targetProvider, settings.paramFilter); len(stripped) > 0 {
+		logger.Debug("删除 %s 不支持的请求参数: %s", targetProvider, strings.Join(stripped, ", "))
+		w.Header().Set("X-Gateway-Stripped-Params", strings.Join(stripped, ","))
+	}
+
+	// 内容审核：按Key的审核策略检查客户端发送的文本（在请求转换器注入系统提示词和脱敏之前），命中时拒绝请求
+	if result, err := settings.moderation.Check(r.Context(), proxyReq, keyID); err != nil {
+		if trace != nil {
+			trace.SetError(err, "moderation")
+			trace.SaveAsync()
+		}
+		h.finishUsage(record, startTime, "moderation_unavailable")
+		h.writeErrorResponse(w, http.StatusServiceUnavailable, "moderation_unavailable", "Content moderation is unavailable, please retry later")
+		return
+	} else if result != nil {
+		if trace != nil {
+			trace.SetError(fmt.Errorf("内容审核拦截: %s/%s", result.Policy, result.Code), "moderation")
+			trace.SaveAsync()
+		}
+		details := map[string]interface{}{"policy": result.Policy, "code": result.Code}
+		if len(result.Categories) > 0 {
+			details["categories"] = result.Categories
+		}
+		h.finishUsage(record, startTime, "moderation_blocked")
+		h.writeErrorDetails(w, http.StatusBadRequest, "moderation_blocked", fmt.Sprintf("Request blocked by moderation policy %s (%s)", result.Policy, result.Code), details)
+		return
+	}
+
+	// 按配置顺序执行请求转换器（注入系统提示词、删除参数、脱敏），在估算token和计算缓存键之前完成
+	if applied := settings.transforms.ApplyRequest(proxyReq, keyID); len(applied) > 0 {
+		logger.Debug("执行请求转换器: %s", strings.Join(applied, ", "))
+		w.Header().Set("X-Gateway-Transforms", strings.Join(applied, ","))
+	}
+
+	// 6.7. 按目标提供商的分词方式估算输入token，Key配置了token配额时检查剩余额度是否足够本次请求
+	record.EstimatedInputTokens = tokens.ForProvider(targetProvider).CountRequest(proxyReq)
+	if h.quota != nil && gatewayKey != nil && gatewayKey.Quota != nil {
+		now := time.Now()
+		if result := h.quota.CheckRequest(keyID, gatewayKey.Quota, now, int64(record.EstimatedInputTokens)); result.Exceeded {
+			if trace != nil {
+				trace.SetError(fmt.Errorf("配额不足: %s", result.Limit), "quota_precheck")
+				trace.SaveAsync()
+			}
+			h.finishUsage(record, startTime, "quota_exceeded")
+			w.Header().Set("Retry-After", strconv.Itoa(int(result.Reset.Sub(now).Seconds()+0.999)))
+			h.writeErrorResponse(w, http.StatusTooManyRequests, "quota_exceeded", fmt.Sprintf("Quota %s exceeded: about %g input tokens requested with %g of %g used, resets at %s", result.Limit, result.Requested, result.Used, result.Max, result.Reset.Format(time.RFC3339)))
+			return
+		}
+	}
+
+	// 7. 通过 converter 获取上游路径
 	upstreamPath, err := h.converter.GetUpstreamPath(targetProvider, clientEndpoint)
 	if err != nil {
 		if trace != nil {
@@ -254,44 +880,119 @@ func (h *ProxyHandler) handleProxyRequest(w http.ResponseWriter, r *http.Request
 		return
 	}
 
-	// 6. 选择上游账号
-	upstreamAccount, err := h.router.SelectUpstream(targetProvider)
+	// 7.1. 响应缓存：选择使用缓存的非流式请求先查缓存，命中时不请求上游
+	if responseCache := settings.responseCache; responseCache != nil && !record.Stream && wantsResponseCache(r) {
+		if cacheKey, err := cache.Key(keyID, targetProvider, clientEndpoint, string(requestFormat), proxyReq); err == nil {
+			if entry, hit := responseCache.Get(cacheKey); hit {
+				record.CacheInfo = &stats.CacheInfo{Hit: true}
+				h.finishUsage(record, startTime, "")
+				if trace != nil {
+					trace.SetClientResponse(entry.Body)
+					trace.SaveAsync()
+				}
+				w.Header().Set(cacheHeader, "hit")
+				w.Header().Set("Content-Type", entry.ContentType)
+				w.WriteHeader(http.StatusOK)
+				_, _ = w.Write(entry.Body)
+				return
+			}
+
+			record.CacheInfo = &stats.CacheInfo{Hit: false}
+			w.Header().Set(cacheHeader, "miss")
+			cacheWriter := &cacheResponseWriter{ResponseWriter: w}
+			w = cacheWriter
+			defer func() {
+				if cacheWriter.status == http.StatusOK {
+					responseCache.Set(cacheKey, cacheWriter.body.Bytes(), cacheWriter.Header().Get("Content-Type"))
+				}
+			}()
+		}
+	}
+
+	// 8. 选择上游账号（并占用账号的并发名额，请求结束时归还），启用排队时所有账号都已占满则排队等待
+	slot := &upstreamSlot{limiter: h.concurrency, queue: h.queue, rule: decision.Route, orgID: record.OrgID}
+	defer slot.Release()
+	var upstreamAccount *types.UpstreamAccount
+	var saturated bool
+	if record.Sandbox {
+		// 沙箱Key不选择真实账号，由模拟响应器应答
+		upstreamAccount = sandbox.Account(targetProvider)
+	} else {
+		upstreamAccount, saturated, err = h.selectUpstream(slot, targetProvider, proxyReq.Model, nil)
+	}
+	if err != nil && saturated && h.queue != nil {
+		upstreamAccount, saturated, err = h.waitForUpstream(r.Context(), slot, targetProvider, proxyReq.Model, priority, record)
+		w.Header().Set(queueTimeHeader, strconv.FormatInt(record.QueueTimeMs, 10))
+	}
 	if err != nil {
 		if trace != nil {
 			trace.SetError(err, "select_upstream")
 			trace.SaveAsync()
 		}
-		h.writeErrorResponse(w, http.StatusServiceUnavailable, "no_upstream_available", fmt.Sprintf("No available upstream for provider %s: %v", targetProvider, err))
+		if saturated {
+			h.finishUsage(record, startTime, "upstream_concurrency_exceeded")
+			w.Header().Set("Retry-After", "1")
+			h.writeErrorResponse(w, http.StatusTooManyRequests, "upstream_concurrency_exceeded", fmt.Sprintf("All upstream accounts for provider %s are at their concurrency limit", targetProvider))
+			return
+		}
+		h.writeNoUpstream(w, record, startTime, targetProvider, err)
 		return
 	}
 	proxyReq.UpstreamID = upstreamAccount.ID
+	record.UpstreamID = upstreamAccount.ID
+	record.Pool = upstreamAccount.PoolKey()
+	h.live.setUpstream(record, upstreamAccount)
 
 	// 记录上下文信息
 	if trace != nil {
 		trace.SetContextInfo(targetProvider, clientEndpoint, upstreamPath, string(requestFormat), string(requestFormat))
 	}
 
-	// 7. 根据上游账号类型注入特殊处理
+	// 9. 根据上游账号类型注入特殊处理
 	h.converter.InjectSystemPrompt(proxyReq, upstreamAccount.Provider, upstreamAccount.Type)
 
-	// 8. 根据stream参数选择处理方式
+	// 客户端指定的超时是整个上游调用的截止时间，切换账号和删减消息后的重试不会重新计时
+	if proxyReq.Timeout > 0 {
+		proxyReq.Deadline = time.Now().Add(proxyReq.Timeout)
+	}
+
+	// 10. 根据stream参数选择处理方式
 	if proxyReq.Stream != nil && *proxyReq.Stream {
-		// 流式响应处理
-		h.handleStreamResponse(w, upstreamAccount, proxyReq, upstreamPath, requestFormat, keyID, startTime, trace, modelRouteContext)
+		// 流式响应处理，客户端可通过 X-Gateway-Usage-Event 请求追加 gateway_usage 事件
+		usageEvent := settings.usageHeaders || strings.EqualFold(r.Header.Get("X-Gateway-Usage-Event"), "true")
+		h.handleStreamResponse(w, upstreamAccount, slot, proxyReq, upstreamPath, requestFormat, keyID, startTime, trace, modelRouteContext, record, usageEvent)
 	} else {
-		// 非流式响应处理
-		h.handleNonStreamResponse(w, upstreamAccount, proxyReq, upstreamPath, requestFormat, keyID, startTime, trace)
+		// 非流式响应处理，客户端接受 gzip 时上游的压缩响应体可以原样转发
+		proxyReq.AcceptGzip = gzipPassthrough(w, r)
+		h.handleNonStreamResponse(w, upstreamAccount, slot, proxyReq, upstreamPath, requestFormat, keyID, startTime, trace, record)
 	}
 }
 
 // handleNonStreamResponse 处理非流式响应
-func (h *ProxyHandler) handleNonStreamResponse(w http.ResponseWriter, account *types.UpstreamAccount, request *types.UnifiedRequest, upstreamPath string, requestFormat converter.Format, keyID string, startTime time.Time, trace *debug.RequestTrace) {
+func (h *ProxyHandler) handleNonStreamResponse(w http.ResponseWriter, account *types.UpstreamAccount, slot *upstreamSlot, request *types.UnifiedRequest, upstreamPath string, requestFormat converter.Format, keyID string, startTime time.Time, trace *debug.RequestTrace, record *stats.UsageRecord) {
 	conversionStart := time.Now()
 
 	// 调用上游API获取原始响应
 	upstreamStart := time.Now()
-	responseBytes, err := h.callUpstreamAPIRaw(account, request, upstreamPath, trace)
+	tried := []string{account.ID}
+	responseBytes, compressedBytes, upstreamReqID, err := h.callUpstreamAPIRaw(account, request, upstreamPath, trace)
+	for err != nil && !deadlinePassed(request, time.Now()) {
+		// 429/5xx或超时时切换到其他账号重试
+		next := h.failoverUpstream(slot, account, request.Model, tried, err)
+		if next == nil {
+			break
+		}
+		account = next
+		tried = append(tried, account.ID)
+		h.switchUpstream(request, record, account)
+		responseBytes, compressedBytes, upstreamReqID, err = h.callUpstreamAPIRaw(account, request, upstreamPath, trace)
+	}
+	// 超出上下文窗口时删减最早的消息后重试一次
+	if err != nil && !deadlinePassed(request, time.Now()) && h.trimForRetry(w, request, record, err) {
+		responseBytes, compressedBytes, upstreamReqID, err = h.callUpstreamAPIRaw(account, request, upstreamPath, trace)
+	}
 	upstreamDuration := time.Since(upstreamStart)
+	record.UpstreamRequestID = upstreamReqID
 
 	if err != nil {
 		if trace != nil {
@@ -299,20 +1000,15 @@ func (h *ProxyHandler) handleNonStreamResponse(w http.ResponseWriter, account *t
 			trace.SetDurations(time.Since(startTime), upstreamDuration, 0)
 			trace.SaveAsync()
 		}
+		err = h.wrapTimeout(err, request.Timeout)
+		record.ErrorClass = upstreamErrorClass(err)
+		h.finishUsage(record, startTime, upstreamErrorType(err))
 		h.handleUpstreamError(w, account, err)
 		return
 	}
 
 	// 使用Manager统一处理响应转换
-	var upstreamFormat converter.Format
-	switch account.Provider {
-	case types.ProviderAnthropic:
-		upstreamFormat = converter.FormatAnthropic
-	case types.ProviderOpenAI, types.ProviderQwen:
-		upstreamFormat = converter.FormatOpenAI
-	default:
-		upstreamFormat = converter.FormatOpenAI
-	}
+	upstreamFormat := h.converter.ProviderFormat(account.Provider)
 
 	transformedBytes, err := h.converter.ConvertResponse(upstreamFormat, requestFormat, responseBytes)
 	conversionDuration := time.Since(conversionStart)
@@ -323,10 +1019,18 @@ func (h *ProxyHandler) handleNonStreamResponse(w http.ResponseWriter, account *t
 			trace.SetDurations(time.Since(startTime), upstreamDuration, conversionDuration)
 			trace.SaveAsync()
 		}
+		h.finishUsage(record, startTime, "response_transform_error")
 		h.writeErrorResponse(w, http.StatusInternalServerError, "response_transform_error", fmt.Sprintf("Failed to transform response: %v", err))
 		return
 	}
 
+	// 执行响应转换器
+	settings := h.settings()
+	filter := settings.transforms.ResponseFilter(request.Model, keyID)
+	if filter != nil {
+		transformedBytes = filter.Body(transformedBytes)
+	}
+
 	// 记录转换后的客户端响应
 	if trace != nil {
 		trace.SetClientResponse(transformedBytes)
@@ -334,24 +1038,45 @@ func (h *ProxyHandler) handleNonStreamResponse(w http.ResponseWriter, account *t
 		trace.SaveAsync()
 	}
 
-	// 记录成功统计
+	// 记录成功统计，token使用信息从上游响应中提取
 	duration := time.Since(startTime)
-	// TODO: 从transformedBytes中提取token使用信息
-	go h.recordSuccess(keyID, account.ID, duration, 0)
+	if upstreamResp, err := h.converter.ParseUpstreamResponse(responseBytes, account.Provider); err == nil {
+		usage := converter.ParseUsage(responseBytes)
+		usage.InputTokens = upstreamResp.Usage.PromptTokens
+		usage.OutputTokens = upstreamResp.Usage.CompletionTokens
+		applyUsage(record, usage)
+	}
+	h.finishUsage(record, startTime, "")
+	tokensUsed := record.InputTokens + record.OutputTokens
+	h.drain.async(func() { h.recordSuccess(keyID, account.ID, duration, tokensUsed) })
 
 	// 返回响应
+	if settings.usageHeaders {
+		w.Header().Set("X-Gateway-Cost-USD", strconv.FormatFloat(record.CostUSD, 'f', 6, 64))
+		w.Header().Set("X-Gateway-Input-Tokens", strconv.Itoa(record.InputTokens))
+		w.Header().Set("X-Gateway-Output-Tokens", strconv.Itoa(record.OutputTokens))
+	}
 	w.Header().Set("Content-Type", "application/json")
+	if compressedBytes != nil {
+		w.Header().Add("Vary", "Accept-Encoding")
+	}
+	// 响应体没有被转换或改写时直接转发上游的 gzip 字节，省去解压后再压缩
+	if compressedBytes != nil && request.AcceptGzip && upstreamFormat == requestFormat && filter == nil {
+		w.Header().Set("Content-Encoding", "gzip")
+		w.WriteHeader(http.StatusOK)
+		_, _ = w.Write(compressedBytes)
+		return
+	}
 	w.WriteHeader(http.StatusOK)
 	_, _ = w.Write(transformedBytes)
 }
 
 // handleStreamResponse 处理流式响应
-func (h *ProxyHandler) handleStreamResponse(w http.ResponseWriter, account *types.UpstreamAccount, request *types.UnifiedRequest, upstreamPath string, requestFormat converter.Format, keyID string, startTime time.Time, trace *debug.RequestTrace, modelRouteContext *types.ModelRouteContext) {
+func (h *ProxyHandler) handleStreamResponse(w http.ResponseWriter, account *types.UpstreamAccount, slot *upstreamSlot, request *types.UnifiedRequest, upstreamPath string, requestFormat converter.Format, keyID string, startTime time.Time, trace *debug.RequestTrace, modelRouteContext *types.ModelRouteContext, record *stats.UsageRecord, usageEvent bool) {
 	// 设置SSE响应头
 	w.Header().Set("Content-Type", "text/event-stream; charset=utf-8")
 	w.Header().Set("Cache-Control", "no-cache")
 	w.Header().Set("Connection", "keep-alive")
-	w.Header().Set("Access-Control-Allow-Origin", "*")
 
 	// 获取Flusher确保实时推送
 	flusher, ok := w.(http.Flusher)
@@ -365,12 +1090,24 @@ func (h *ProxyHandler) handleStreamResponse(w http.ResponseWriter, account *type
 	}
 
 	// 调用上游流式API
-	err := h.callUpstreamStreamAPI(w, flusher, account, request, upstreamPath, requestFormat, keyID, startTime, trace, modelRouteContext)
+	err := h.callUpstreamStreamAPI(w, flusher, account, slot, request, upstreamPath, requestFormat, keyID, startTime, trace, modelRouteContext, record, usageEvent)
 	if err != nil {
 		if trace != nil {
 			trace.SetError(err, "stream_processing")
 			trace.SaveAsync()
 		}
+		// 向客户端写入任何数据之前超时时返回 504，之后的错误只能写入流中
+		var timeoutErr *upstreamTimeoutError
+		if errors.As(err, &timeoutErr) {
+			h.writeUpstreamTimeout(w, timeoutErr.Timeout)
+			return
+		}
+		if _, overloaded := overloadedError(err); overloaded {
+			if until, backingOff := h.providerOverloaded(account.Provider); backingOff {
+				h.writeOverloaded(w, account.Provider, until)
+				return
+			}
+		}
 		// 流式响应中的错误处理
 		h.writeStreamError(w, flusher, err)
 		return
@@ -378,32 +1115,96 @@ func (h *ProxyHandler) handleStreamResponse(w http.ResponseWriter, account *type
 }
 
 // callUpstreamStreamAPI 调用上游流式API
-func (h *ProxyHandler) callUpstreamStreamAPI(w http.ResponseWriter, flusher http.Flusher, account *types.UpstreamAccount, request *types.UnifiedRequest, path string, requestFormat converter.Format, keyID string, startTime time.Time, trace *debug.RequestTrace, modelRouteContext *types.ModelRouteContext) error {
+func (h *ProxyHandler) callUpstreamStreamAPI(w http.ResponseWriter, flusher http.Flusher, account *types.UpstreamAccount, slot *upstreamSlot, request *types.UnifiedRequest, path string, requestFormat converter.Format, keyID string, startTime time.Time, trace *debug.RequestTrace, modelRouteContext *types.ModelRouteContext, record *stats.UsageRecord, usageEvent bool) error {
 	logger.Debug("开始流式请求，上游ID: %s, Provider: %s", account.ID, account.Provider)
 
+	// 在向客户端写入任何数据之前，429/5xx或超时可以切换到其他账号重试
+	tried := []string{account.ID}
+	trimmed := false
+	resp, err := h.openUpstreamStream(account, request, path, trace)
+	for err != nil {
+		var next *types.UpstreamAccount
+		expired := deadlinePassed(request, time.Now())
+		if !expired {
+			next = h.failoverUpstream(slot, account, request.Model, tried, err)
+		}
+		if next == nil && !trimmed && !expired && h.trimForRetry(w, request, record, err) {
+			// 超出上下文窗口时删减最早的消息后在同一账号上重试一次
+			trimmed = true
+			resp, err = h.openUpstreamStream(account, request, path, trace)
+			continue
+		}
+		if next == nil {
+			var statusErr *upstreamStatusError
+			if errors.As(err, &statusErr) {
+				record.UpstreamRequestID = statusErr.RequestID
+			}
+			record.TerminationReason = h.streamTermination(err, false)
+			err = h.wrapTimeout(err, request.Timeout)
+			record.ErrorClass = upstreamErrorClass(err)
+			h.finishUsage(record, startTime, upstreamErrorType(err))
+			return err
+		}
+		account = next
+		tried = append(tried, account.ID)
+		h.switchUpstream(request, record, account)
+		resp, err = h.openUpstreamStream(account, request, path, trace)
+	}
+	defer func() { _ = resp.Body.Close() }()
+	record.UpstreamRequestID = upstreamRequestID(resp.Header)
+
+	// 不需要显式调用WriteHeader，让Go在第一次写入时自动发送200状态码
+	// 这样可以避免与中间件包装器的WriteHeader冲突
+	flusher.Flush()
+	h.live.setPhase(record, livePhaseStreaming)
+
+	logger.Debug("开始处理流式响应")
+	// 开始处理流式响应
+	return h.processStreamResponse(w, flusher, resp.Body, account.Provider, requestFormat, keyID, account.ID, request.Timeout, startTime, trace, modelRouteContext, record, usageEvent)
+}
+
+// openUpstreamStream 发送流式请求并检查响应状态，成功时由调用方关闭响应体
+func (h *ProxyHandler) openUpstreamStream(account *types.UpstreamAccount, request *types.UnifiedRequest, path string, trace *debug.RequestTrace) (*http.Response, error) {
+	if sandbox.IsAccount(account) {
+		body := h.settings().sandbox.Stream(request, account.Provider, h.converter.ProviderFormat(account.Provider))
+		return &http.Response{StatusCode: http.StatusOK, Header: http.Header{"Content-Type": {"text/event-stream"}}, Body: body}, nil
+	}
+
 	// 构建上游请求
 	upstreamReq, err := h.buildUpstreamRequest(account, request, path, trace)
 	if err != nil {
 		logger.Debug("构建上游请求失败: %v", err)
-		return fmt.Errorf("failed to build upstream request: %w", err)
+		return nil, fmt.Errorf("failed to build upstream request: %w", err)
 	}
 
 	logger.Debug("发送流式请求到: %s", upstreamReq.URL.String())
 
-	// 发送流式请求
-	resp, err := h.httpClient.Do(upstreamReq)
+	// 发送流式请求，客户端指定了超时时只使用截止时间前剩余的时间
+	timeout, err := attemptTimeout(request, time.Now())
+	if err != nil {
+		return nil, fmt.Errorf("upstream request failed: %w", err)
+	}
+	resp, err := h.upstreamClient(timeout).Do(upstreamReq)
 	if err != nil {
 		logger.Debug("上游请求失败: %v", err)
-		return fmt.Errorf("upstream request failed: %w", err)
+		return nil, fmt.Errorf("upstream request failed: %w", err)
 	}
-	defer func() { _ = resp.Body.Close() }()
 
 	logger.Debug("收到上游响应，状态码: %d", resp.StatusCode)
+	h.upstreamMgr.RateLimits().Record(account.ID, resp.StatusCode, resp.Header, time.Now())
 
 	// 检查响应状态
 	if resp.StatusCode != http.StatusOK {
 		logger.Debug("上游API返回错误状态码: %d", resp.StatusCode)
-		return fmt.Errorf("upstream API error: status=%d", resp.StatusCode)
+		body, _ := io.ReadAll(io.LimitReader(resp.Body, maxErrorBodyBytes))
+		_ = resp.Body.Close()
+		return nil, &upstreamStatusError{StatusCode: resp.StatusCode, Body: string(body), RequestID: upstreamRequestID(resp.Header), RetryAfter: resp.Header.Get("Retry-After")}
+	}
+
+	// 提供商自己编码的流式响应（如Bedrock的event stream）转换为SSE后不再检查Content-Type
+	if body, converted := h.upstreamMgr.StreamBody(account, resp.Body); converted {
+		resp.Body = body
+		return resp, nil
 	}
 
 	// 验证Content-Type是否为流式响应
@@ -411,34 +1212,81 @@ func (h *ProxyHandler) callUpstreamStreamAPI(w http.ResponseWriter, flusher http
 	logger.Debug("响应Content-Type: %s", contentType)
 	if !strings.HasPrefix(contentType, "text/event-stream") {
 		logger.Debug("非流式响应Content-Type: %s", contentType)
-		return fmt.Errorf("unexpected content type: %s", contentType)
+		_ = resp.Body.Close()
+		return nil, fmt.Errorf("unexpected content type: %s", contentType)
 	}
 
-	// 不需要显式调用WriteHeader，让Go在第一次写入时自动发送200状态码
-	// 这样可以避免与中间件包装器的WriteHeader冲突
-	flusher.Flush()
+	return resp, nil
+}
 
-	logger.Debug("开始处理流式响应")
-	// 开始处理流式响应
-	return h.processStreamResponse(w, flusher, resp.Body, account.Provider, requestFormat, keyID, account.ID, startTime, trace, modelRouteContext)
+// passthroughRewriter 组合透传模式下的模型名恢复和响应转换器，两者都不需要时返回nil
+func passthroughRewriter(modelRewrite func(line []byte) []byte, filter *transform.ResponseFilter) func(line []byte) []byte {
+	if filter == nil {
+		return modelRewrite
+	}
+	return func(line []byte) []byte {
+		changed := false
+		if modelRewrite != nil {
+			if rewritten := modelRewrite(line); rewritten != nil {
+				line = rewritten
+				changed = true
+			}
+		}
+		if rewritten := filter.SSELine(line); rewritten != nil {
+			return rewritten
+		}
+		if changed {
+			return line
+		}
+		return nil
+	}
 }
 
 // processStreamResponse 处理流式响应
-func (h *ProxyHandler) processStreamResponse(w http.ResponseWriter, flusher http.Flusher, responseBody io.Reader, provider types.Provider, requestFormat converter.Format, keyID, upstreamID string, startTime time.Time, trace *debug.RequestTrace, modelRouteContext *types.ModelRouteContext) error {
+func (h *ProxyHandler) processStreamResponse(w http.ResponseWriter, flusher http.Flusher, responseBody io.Reader, provider types.Provider, requestFormat converter.Format, keyID, upstreamID string, timeout time.Duration, startTime time.Time, trace *debug.RequestTrace, modelRouteContext *types.ModelRouteContext, record *stats.UsageRecord, usageEvent bool) error {
 	var totalTokens int
 	logger.Debug("开始处理流式响应，Provider: %s, RequestFormat: %v", provider, requestFormat)
 
+	// 转发的同时从上游事件中提取token用量
+	usageReader := converter.NewUsageCaptureReader(responseBody)
+
 	// 使用新的Manager处理流式响应
 
 	// 创建流写入器
+	client := &clientWriter{writer: w}
 	writer := &httpStreamWriter{
-		writer:      w,
+		writer:      client,
 		flusher:     flusher,
 		totalTokens: &totalTokens,
 		trace:       trace,
+		omitDone:    requestFormat == converter.FormatGemini,
+		filter:      h.settings().transforms.ResponseFilter(record.Model, keyID),
+	}
+
+	// 上游的最后一个事件之后、[DONE]之前追加 gateway_usage 事件
+	if usageEvent {
+		writer.beforeDone = func() {
+			applyUsage(record, usageReader.Usage())
+			h.writeUsageEvent(w, flusher, record, startTime)
+		}
+	}
+
+	var err error
+	if h.converter.IsPassthrough(provider, requestFormat) {
+		// 客户端格式与上游一致：跳过解析重组，原样透传
+		logger.Debug("使用透传模式转发流式响应")
+		err = converter.ForwardSSEStream(usageReader, client, flusher.Flush, converter.PassthroughOptions{
+			Rewrite: passthroughRewriter(h.converter.PassthroughModelRewriter(modelRouteContext), writer.filter),
+			OnDone:  writer.beforeDone,
+		})
+	} else {
+		err = h.converter.ProcessStreamWithModelRoute(usageReader, provider, requestFormat, writer, modelRouteContext)
 	}
 
-	err := h.converter.ProcessStreamWithModelRoute(responseBody, provider, requestFormat, writer, modelRouteContext)
+	applyUsage(record, usageReader.Usage())
+	applyStreamTiming(record, startTime, usageReader.FirstDataAt(), time.Now())
+	record.TerminationReason = h.streamTermination(err, client.err != nil)
+	totalTokens += record.InputTokens + record.OutputTokens
 
 	if err != nil {
 		logger.Debug("流式处理出现错误: %v", err)
@@ -449,17 +1297,110 @@ func (h *ProxyHandler) processStreamResponse(w http.ResponseWriter, flusher http
 		logger.Debug("流式处理完成，总tokens: %d", totalTokens)
 	}
 
-	// 记录成功统计和调试信息
+	// 记录统计和调试信息
 	duration := time.Since(startTime)
 	if trace != nil {
 		trace.SetDurations(duration, 0, 0)
 		trace.SaveAsync()
 	}
-	go h.recordSuccess(keyID, upstreamID, duration, totalTokens)
+	if record.TerminationReason == stats.TerminationTimeout {
+		record.ErrorClass = types.ErrorClassTimeout
+		h.finishUsage(record, startTime, "upstream_timeout")
+	} else if err != nil {
+		// 客户端断开不是上游的错误，不计入错误分类
+		if record.TerminationReason == stats.TerminationUpstreamError {
+			record.ErrorClass = upstreamErrorClass(err)
+		}
+		h.finishUsage(record, startTime, "stream_error")
+	} else {
+		h.finishUsage(record, startTime, "")
+	}
+
+	// 只有完整结束的流算作成功；上游中途出错或超时按失败计入熔断器，客户端断开不影响上游账号
+	switch record.TerminationReason {
+	case stats.TerminationCompleted:
+		h.drain.async(func() { h.recordSuccess(keyID, upstreamID, duration, totalTokens) })
+	case stats.TerminationTimeout, stats.TerminationUpstreamError:
+		failure := h.wrapTimeout(err, timeout)
+		h.drain.async(func() { h.recordFailure(keyID, upstreamID, duration, failure) })
+	default:
+		h.drain.async(func() { h.recordKeyUsage(keyID, false, duration) })
+	}
 
 	return err
 }
 
+// applyUsage 将上游响应中的用量写入使用记录，并按提供商和模型的价格（含提示词缓存价格）计算费用
+func applyUsage(record *stats.UsageRecord, usage converter.StreamUsage) {
+	record.InputTokens = usage.InputTokens
+	record.OutputTokens = usage.OutputTokens
+	record.CacheReadTokens = usage.CacheReadTokens
+	record.CacheWriteTokens = usage.CacheWriteTokens
+	if usage.FinishReason != "" {
+		record.FinishReason = usage.FinishReason
+	}
+	if record.Sandbox {
+		// 沙箱请求没有消耗提供商额度，只记录token数
+		record.CostUSD = 0
+		return
+	}
+	record.CostUSD = pricing.CostFor(record.Provider, record.Model, pricing.Tokens{
+		Input:      usage.UncachedInputTokens(),
+		Output:     usage.OutputTokens,
+		CacheRead:  usage.CacheReadTokens,
+		CacheWrite: usage.CacheWriteTokens,
+	})
+}
+
+// applyStreamTiming 根据首个事件和流结束时间计算首token延迟与输出速度
+func applyStreamTiming(record *stats.UsageRecord, startTime, firstDataAt, endTime time.Time) {
+	if firstDataAt.IsZero() {
+		return
+	}
+	record.FirstTokenLatencyMs = firstDataAt.Sub(startTime).Milliseconds()
+	if seconds := endTime.Sub(firstDataAt).Seconds(); seconds > 0 && record.OutputTokens > 0 {
+		record.TokensPerSecond = float64(record.OutputTokens) / seconds
+	}
+}
+
+// streamTermination 判断流式请求的结束原因，clientAborted 表示向客户端写入失败。
+// 网关关闭时强制断开的连接在客户端看来也是写入失败，排空期间的写入失败记为关闭中断
+func (h *ProxyHandler) streamTermination(err error, clientAborted bool) string {
+	switch {
+	case err == nil:
+		return stats.TerminationCompleted
+	case clientAborted && h.drain.isDraining():
+		return stats.TerminationShutdown
+	case clientAborted:
+		return stats.TerminationClientAbort
+	case isTimeoutError(err):
+		return stats.TerminationTimeout
+	default:
+		return stats.TerminationUpstreamError
+	}
+}
+
+// writeUsageEvent 写入 gateway_usage 事件（流式响应无法再追加响应头，用量通过事件返回）
+func (h *ProxyHandler) writeUsageEvent(w http.ResponseWriter, flusher http.Flusher, record *stats.UsageRecord, startTime time.Time) {
+	usageEvent := map[string]interface{}{
+		"type":            "gateway_usage",
+		"request_id":      record.RequestID,
+		"input_tokens":    record.InputTokens,
+		"output_tokens":   record.OutputTokens,
+		"total_tokens":    record.InputTokens + record.OutputTokens,
+		"cost_usd":        record.CostUSD,
+		"upstream_id":     record.UpstreamID,
+		"provider":        record.Provider,
+		"model":           record.Model,
+		"requested_model": record.RequestedModel,
+		"latency_ms":      time.Since(startTime).Milliseconds(),
+	}
+
+	eventBytes, _ := json.Marshal(usageEvent)
+	_, _ = fmt.Fprintf(w, "event: gateway_usage\ndata: %s\n\n", string(eventBytes))
+	flusher.Flush()
+}
+
 // writeStreamError 写入流式错误
 func (h *ProxyHandler) writeStreamError(w http.ResponseWriter, flusher http.Flusher, err error) {
 	errorEvent := map[string]interface{}{
@@ -474,25 +1415,44 @@ func (h *ProxyHandler) writeStreamError(w http.ResponseWriter, flusher http.Flus
 	flusher.Flush()
 }
 
-// callUpstreamAPIRaw 调用上游API并返回原始响应字节
-func (h *ProxyHandler) callUpstreamAPIRaw(account *types.UpstreamAccount, request *types.UnifiedRequest, path string, trace *debug.RequestTrace) ([]byte, error) {
+// callUpstreamAPIRaw 调用上游API并返回原始响应字节、上游返回的 gzip 压缩字节（未压缩时为nil）和上游的请求ID
+func (h *ProxyHandler) callUpstreamAPIRaw(account *types.UpstreamAccount, request *types.UnifiedRequest, path string, trace *debug.RequestTrace) ([]byte, []byte, string, error) {
+	if sandbox.IsAccount(account) {
+		body, err := h.settings().sandbox.Complete(request, account.Provider, h.converter.ProviderFormat(account.Provider))
+		if trace != nil && err == nil {
+			trace.SetUpstreamResponse(body)
+		}
+		return body, nil, "", err
+	}
+
 	// 1. 构建上游请求
 	upstreamReq, err := h.buildUpstreamRequest(account, request, path, trace)
 	if err != nil {
-		return nil, fmt.Errorf("failed to build upstream request: %w", err)
+		return nil, nil, "", fmt.Errorf("failed to build upstream request: %w", err)
+	}
+	timeout, err := attemptTimeout(request, time.Now())
+	if err != nil {
+		return nil, nil, "", fmt.Errorf("upstream request failed: %w", err)
 	}
+	return h.sendUpstreamRequest(account, upstreamReq, timeout, trace)
+}
 
+// sendUpstreamRequest 发送已构建的上游请求，返回解压后的响应字节、上游返回的 gzip 压缩字节（未压缩时为nil）
+// 和上游的请求ID，非200状态码返回 upstreamStatusError。timeout 为0时使用全局超时
+func (h *ProxyHandler) sendUpstreamRequest(account *types.UpstreamAccount, upstreamReq *http.Request, timeout time.Duration, trace *debug.RequestTrace) ([]byte, []byte, string, error) {
 	// 2. 发送请求
-	resp, err := h.httpClient.Do(upstreamReq)
+	resp, err := h.upstreamClient(timeout).Do(upstreamReq)
 	if err != nil {
-		return nil, fmt.Errorf("upstream request failed: %w", err)
+		return nil, nil, "", fmt.Errorf("upstream request failed: %w", err)
 	}
 	defer func() { _ = resp.Body.Close() }()
+	requestID := upstreamRequestID(resp.Header)
+	h.upstreamMgr.RateLimits().Record(account.ID, resp.StatusCode, resp.Header, time.Now())
 
-	// 3. 读取响应
-	responseBody, err := io.ReadAll(resp.Body)
+	// 3. 读取响应，gzip 压缩的响应体解压后用于转换和统计
+	responseBody, compressed, err := readUpstreamBody(resp)
 	if err != nil {
-		return nil, fmt.Errorf("failed to read upstream response: %w", err)
+		return nil, nil, requestID, fmt.Errorf("failed to read upstream response: %w", err)
 	}
 
 	// 记录原始上游响应
@@ -502,10 +1462,10 @@ func (h *ProxyHandler) callUpstreamAPIRaw(account *types.UpstreamAccount, reques
 
 	// 4. 检查HTTP状态码
 	if resp.StatusCode != http.StatusOK {
-		return nil, fmt.Errorf("upstream API error: status=%d, body=%s", resp.StatusCode, string(responseBody))
+		return nil, nil, requestID, &upstreamStatusError{StatusCode: resp.StatusCode, Body: string(responseBody), RequestID: requestID, RetryAfter: resp.Header.Get("Retry-After")}
 	}
 
-	return responseBody, nil
+	return responseBody, compressed, requestID, nil
 }
 
 // buildUpstreamRequest 构建上游请求
@@ -513,6 +1473,11 @@ func (h *ProxyHandler) buildUpstreamRequest(account *types.UpstreamAccount, requ
 	// 1. 根据上游提供商转换请求格式
 	requestBody, err := h.converter.BuildUpstreamRequest(request, account.Provider)
 
+	if err != nil {
+		return nil, fmt.Errorf("failed to transform request for upstream: %w", err)
+	}
+	// 提供商对请求体的额外要求（如Bedrock的模型在路径中）
+	requestBody, err = h.upstreamMgr.UpstreamBody(account, requestBody)
 	if err != nil {
 		return nil, fmt.Errorf("failed to transform request for upstream: %w", err)
 	}
@@ -521,10 +1486,13 @@ func (h *ProxyHandler) buildUpstreamRequest(account *types.UpstreamAccount, requ
 	if trace != nil {
 		trace.SetUpstreamRequest(requestBody)
 	}
+	return h.newUpstreamRequest(account, request, path, requestBody)
+}
 
-	// 2. 构建URL
-	baseURL := h.upstreamMgr.GetBaseURL(account)
-	url := baseURL + path
+// newUpstreamRequest 用已转换的请求体创建上游请求：确定URL，设置通用头部、认证头部和签名
+func (h *ProxyHandler) newUpstreamRequest(account *types.UpstreamAccount, request *types.UnifiedRequest, path string, requestBody []byte) (*http.Request, error) {
+	// 2. 构建URL：提供商的实际路径（如Azure的部署路径）和API版本查询参数
+	url := h.upstreamMgr.UpstreamURL(account, converter.ExpandUpstreamPath(path, request), request)
 
 	// 3. 创建HTTP请求
 	req, err := http.NewRequest("POST", url, bytes.NewBuffer(requestBody))
@@ -532,8 +1500,16 @@ func (h *ProxyHandler) buildUpstreamRequest(account *types.UpstreamAccount, requ
 		return nil, fmt.Errorf("failed to create request: %w", err)
 	}
 
-	// 4. 设置通用头部
+	// 4. 设置通用头部，请求ID转发给上游便于跨系统排查
 	req.Header.Set("Content-Type", "application/json")
+	if request.RequestID != "" {
+		req.Header.Set(requestIDHeader, request.RequestID)
+	}
+	// 非流式请求显式要求 gzip，由网关自己解压，客户端接受 gzip 时可以原样转发压缩字节；
+	// 流式请求仍由 Transport 协商并透明解压
+	if request.Stream == nil || !*request.Stream {
+		req.Header.Set("Accept-Encoding", "gzip")
+	}
 
 	// 对Anthropic使用Claude Code User-Agent，其他提供商使用通用User-Agent
 	if account.Provider == types.ProviderAnthropic {
@@ -552,44 +1528,137 @@ func (h *ProxyHandler) buildUpstreamRequest(account *types.UpstreamAccount, requ
 		req.Header.Set(key, value)
 	}
 
+	// 6. 需要签名的提供商（如Bedrock的SigV4）最后签名，覆盖上面设置的所有头部
+	if err := h.upstreamMgr.SignRequest(account, req, requestBody); err != nil {
+		return nil, fmt.Errorf("failed to sign upstream request: %w", err)
+	}
+
 	return req, nil
 }
 
 // handleUpstreamError 处理上游错误
 func (h *ProxyHandler) handleUpstreamError(w http.ResponseWriter, account *types.UpstreamAccount, err error) {
-	// 记录错误到上游账号统计
-	go h.router.MarkUpstreamError(account.ID, err)
+	// 记录错误到上游账号统计，客户端选择的更短超时不算账号的错误
+	if !clientTimeout(err) {
+		go h.router.MarkUpstreamError(account.ID, err)
+	}
 
-	// 返回错误响应
+	// 返回错误响应，超时返回 504，提供商过载且启用了过载退避时返回 529
+	var timeoutErr *upstreamTimeoutError
+	if errors.As(err, &timeoutErr) {
+		h.writeUpstreamTimeout(w, timeoutErr.Timeout)
+		return
+	}
+	if _, overloaded := overloadedError(err); overloaded {
+		if until, backingOff := h.providerOverloaded(account.Provider); backingOff {
+			h.writeOverloaded(w, account.Provider, until)
+			return
+		}
+	}
 	h.writeErrorResponse(w, http.StatusBadGateway, "upstream_error", fmt.Sprintf("Upstream API error: %v", err))
 }
 
 // recordSuccess 记录成功请求统计
 func (h *ProxyHandler) recordSuccess(keyID, upstreamID string, latency time.Duration, tokensUsed int) {
 	// 更新Gateway Key统计
+	h.recordKeyUsage(keyID, true, latency)
+
+	// 更新上游账号统计（沙箱请求没有真实账号），提供商恢复后清零连续过载次数
+	if upstreamID != sandbox.AccountID {
+		h.router.MarkUpstreamSuccess(upstreamID, latency, int64(tokensUsed))
+		if account, err := h.upstreamMgr.GetAccount(upstreamID); err == nil {
+			h.upstreamMgr.Overloads().Clear(account.Provider, time.Now())
+		}
+	}
+}
+
+// recordFailure 记录流式响应开始后上游中途出错或超时：计入Key和上游账号的错误统计，客户端选择的更短超时不计入账号
+func (h *ProxyHandler) recordFailure(keyID, upstreamID string, latency time.Duration, err error) {
+	h.recordKeyUsage(keyID, false, latency)
+	if upstreamID != sandbox.AccountID && !clientTimeout(err) {
+		h.router.MarkUpstreamError(upstreamID, err)
+	}
+}
+
+// recordKeyUsage 更新Gateway Key的请求统计
+func (h *ProxyHandler) recordKeyUsage(keyID string, success bool, latency time.Duration) {
 	if keyID != "" {
-		_ = h.gatewayKeyMgr.UpdateKeyUsage(keyID, true, latency)
+		_ = h.gatewayKeyMgr.UpdateKeyUsage(keyID, success, latency)
 	}
+}
 
-	// 更新上游账号统计
-	h.router.MarkUpstreamSuccess(upstreamID, latency, int64(tokensUsed))
+// finishUsage 完成使用记录并写入统计模块，errorType为空表示成功
+func (h *ProxyHandler) finishUsage(record *stats.UsageRecord, startTime time.Time, errorType string) {
+	if h.recorder == nil || record == nil {
+		return
+	}
+
+	record.LatencyMs = time.Since(startTime).Milliseconds()
+	record.Success = errorType == ""
+	record.ErrorType = errorType
+	h.recorder.Record(*record)
+
+	// 请求日志：失败的请求记为警告，带上网关和上游的请求ID便于跨系统排查
+	fields := logger.Fields{
+		"request_id":  record.RequestID,
+		"key_id":      record.GatewayKeyID,
+		"upstream_id": record.UpstreamID,
+		"provider":    record.Provider,
+		"model":       record.Model,
+		"endpoint":    record.Endpoint,
+		"latency_ms":  record.LatencyMs,
+	}
+	if record.UpstreamRequestID != "" {
+		fields["upstream_request_id"] = record.UpstreamRequestID
+	}
+	if errorType != "" {
+		fields["error_type"] = errorType
+		logger.WarnWith(fields, "proxy request failed")
+	} else {
+		fields["input_tokens"] = record.InputTokens
+		fields["output_tokens"] = record.OutputTokens
+		logger.DebugWith(fields, "proxy request completed")
+	}
 }
 
 // writeErrorResponse 写入错误响应
 func (h *ProxyHandler) writeErrorResponse(w http.ResponseWriter, statusCode int, errorType, message string) {
-	// 记录错误日志到控制台
-	log.Printf("[ERROR] HTTP %d - %s: %s", statusCode, errorType, message)
+	h.writeErrorDetails(w, statusCode, errorType, message, nil)
+}
+
+// writeRequestTooLarge 返回413，说明请求体的大小上限
+func (h *ProxyHandler) writeRequestTooLarge(w http.ResponseWriter, limit int64) {
+	h.writeErrorDetails(w, http.StatusRequestEntityTooLarge, "request_too_large",
+		fmt.Sprintf("Request body exceeds the limit of %d bytes", limit),
+		map[string]interface{}{"max_request_bytes": limit})
+}
+
+// writeErrorDetails 写入错误响应，details 中的字段（如审核策略代码）附加到 error 对象中
+func (h *ProxyHandler) writeErrorDetails(w http.ResponseWriter, statusCode int, errorType, message string, details map[string]interface{}) {
+	// 记录错误日志并在错误响应中返回请求ID，便于与客户端和上游日志关联
+	logger.WarnWith(logger.Fields{
+		"request_id": w.Header().Get(requestIDHeader),
+		"status":     statusCode,
+		"error_type": errorType,
+	}, "%s", message)
 
 	w.Header().Set("Content-Type", "application/json")
 	w.WriteHeader(statusCode)
 
+	errorBody := map[string]interface{}{
+		"type":    errorType,
+		"message": message,
+	}
+	for field, value := range details {
+		errorBody[field] = value
+	}
 	errorResp := map[string]interface{}{
-		"error": map[string]string{
-			"type":    errorType,
-			"message": message,
-		},
+		"error":     errorBody,
 		"timestamp": time.Now().Unix(),
 	}
+	if requestID := w.Header().Get(requestIDHeader); requestID != "" {
+		errorResp["request_id"] = requestID
+	}
 
 	_ = json.NewEncoder(w).Encode(errorResp)
 }
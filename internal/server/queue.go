@@ -0,0 +1,67 @@
+package server
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	// priorityHeader 请求排队时的优先级：critical、high、normal（默认）、low
+	priorityHeader = "X-Gateway-Priority"
+
+	// queueTimeHeader 请求排队等待的毫秒数，只在排过队时返回
+	queueTimeHeader = "X-Gateway-Queue-Time-Ms"
+
+	defaultQueueSize    = 100
+	defaultQueueWaitSec = 30
+)
+
+// requestPriority 读取请求的排队优先级，不能高于Key的 max_priority（未设置时不能高于 normal）。
+// 头部值无效时返回 ok=false
+func requestPriority(r *http.Request, key *types.GatewayAPIKey) (types.RequestPriority, bool) {
+	priority, ok := types.ParseRequestPriority(r.Header.Get(priorityHeader))
+	if !ok {
+		return priority, false
+	}
+
+	limit := types.PriorityNormal
+	if key != nil && key.MaxPriority != "" {
+		limit = key.MaxPriority
+	}
+	if priority.Rank() < limit.Rank() {
+		priority = limit
+	}
+	return priority, true
+}
+
+// waitForUpstream 提供商的所有账号都达到并发上限时排队等待名额：账号归还名额时按优先级和进入顺序唤醒，
+// 被唤醒的请求重新选择账号，直到获得名额、超过最长等待时间或被更高优先级的请求挤出队列。
+// 排队时间记录在 record.QueueTimeMs 中
+func (h *ProxyHandler) waitForUpstream(ctx context.Context, slot *upstreamSlot, provider types.Provider, model string, priority types.RequestPriority, record *stats.UsageRecord) (account *types.UpstreamAccount, saturated bool, err error) {
+	start := time.Now()
+	defer func() { record.QueueTimeMs = time.Since(start).Milliseconds() }()
+
+	waiter, ok := h.queue.Join(string(provider), priority.Rank())
+	if !ok {
+		return nil, true, fmt.Errorf("请求队列已满")
+	}
+	defer h.queue.Leave(waiter)
+	h.live.setPhase(record, livePhaseQueued)
+
+	ctx, cancel := context.WithTimeout(ctx, h.settings().queueWait)
+	defer cancel()
+	for {
+		account, saturated, err = h.selectUpstream(slot, provider, model, nil)
+		if err == nil || !saturated {
+			return account, saturated, err
+		}
+		if waitErr := h.queue.Wait(ctx, waiter); waitErr != nil {
+			return nil, true, fmt.Errorf("排队等待并发名额失败: %w", waitErr)
+		}
+	}
+}
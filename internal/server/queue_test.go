@@ -0,0 +1,45 @@
+package server
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestRequestPriority(t *testing.T) {
+	tests := []struct {
+		name   string
+		header string
+		key    *types.GatewayAPIKey
+		want   types.RequestPriority
+		wantOK bool
+	}{
+		{name: "default", header: "", key: &types.GatewayAPIKey{}, want: types.PriorityNormal, wantOK: true},
+		{name: "low allowed", header: "low", key: &types.GatewayAPIKey{}, want: types.PriorityLow, wantOK: true},
+		{name: "no grant clamps critical", header: "critical", key: &types.GatewayAPIKey{}, want: types.PriorityNormal, wantOK: true},
+		{name: "no key clamps high", header: "HIGH", key: nil, want: types.PriorityNormal, wantOK: true},
+		{name: "grant high clamps critical", header: "critical", key: &types.GatewayAPIKey{MaxPriority: types.PriorityHigh}, want: types.PriorityHigh, wantOK: true},
+		{name: "grant high allows high", header: "high", key: &types.GatewayAPIKey{MaxPriority: types.PriorityHigh}, want: types.PriorityHigh, wantOK: true},
+		{name: "grant critical", header: "critical", key: &types.GatewayAPIKey{MaxPriority: types.PriorityCritical}, want: types.PriorityCritical, wantOK: true},
+		{name: "grant low caps normal", header: "normal", key: &types.GatewayAPIKey{MaxPriority: types.PriorityLow}, want: types.PriorityLow, wantOK: true},
+		{name: "invalid", header: "urgent", key: &types.GatewayAPIKey{MaxPriority: types.PriorityCritical}, wantOK: false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			req := httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+			if tt.header != "" {
+				req.Header.Set(priorityHeader, tt.header)
+			}
+			got, ok := requestPriority(req, tt.key)
+			if ok != tt.wantOK {
+				t.Fatalf("requestPriority() ok = %v, want %v", ok, tt.wantOK)
+			}
+			if ok && got != tt.want {
+				t.Errorf("requestPriority() = %s, want %s", got, tt.want)
+			}
+		})
+	}
+}
@@ -0,0 +1,75 @@
+package server
+
+import (
+	"fmt"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/pricing"
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// ReloadConfig 重新读取配置文件并应用到运行中的组件：CORS、自定义价格、提供商设置、过载退避参数、
+// 负载均衡策略和代理设置（超时、重试、请求体上限、路径规则、响应缓存等）。配置整体替换，Gateway Key 的限流和配额、
+// 路由规则、熔断器、告警和探测等每次使用时读取配置的设置随之生效；监听地址、共享状态后端和各类存储目录需要重启
+func (s *HTTPServer) ReloadConfig() (*types.Config, error) {
+	configMgr, ok := s.configMgr.(*config.ConfigManager)
+	if !ok {
+		return nil, fmt.Errorf("配置管理器不支持重新加载")
+	}
+
+	previous := *s.config
+	cfg, err := configMgr.Reload()
+	if err != nil {
+		return nil, err
+	}
+
+	if profile := configMgr.Profile(); profile != nil {
+		ConfigureCORS(profile.ResolveCORSOrigins(cfg))
+	}
+	pricing.SetCustomPrices(cfg.Pricing.Models)
+	s.upstreamMgr.Providers().ApplySettings(cfg.Providers)
+	s.upstreamMgr.Overloads().Configure(&cfg.Proxy.OverloadBackoff)
+	if s.autopilot != nil {
+		// 自动切换按新配置重新计算生效的策略（手动指定的策略仍然优先）
+		s.autopilot.Evaluate(time.Now())
+	} else {
+		s.router.SetStrategy(router.ConfiguredStrategy(&cfg.Routing))
+	}
+	s.proxyHandler.Reconfigure(&cfg.Proxy)
+
+	if cfg.Server.Host != previous.Host || cfg.Server.Port != previous.Port {
+		logger.Warn("server.host 和 server.port 的修改需要重启后生效")
+	}
+	logger.Info("配置已重新加载: %s", configMgr.GetConfigPath())
+	return cfg, nil
+}
+
+// HandleConfigReload 重新读取配置文件并立即应用（POST），新配置无效时返回400并保留当前配置
+func (h *WebHandler) HandleConfigReload(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+	if h.reload == nil {
+		h.writeError(w, http.StatusNotImplemented, "Configuration reload is not available")
+		return
+	}
+
+	cfg, err := h.reload()
+	if err != nil {
+		logger.Warn("Configuration reload by %s failed: %v", h.sessionUser(r), err)
+		h.writeError(w, http.StatusBadRequest, "Configuration reload failed: "+err.Error())
+		return
+	}
+	logger.Info("Configuration reloaded by %s", h.sessionUser(r))
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"reloaded":         true,
+		"config_path":      h.configMgr.GetConfigPath(),
+		"routing_strategy": router.ConfiguredStrategy(&cfg.Routing),
+		"env_overrides":    h.configMgr.EnvOverrides(),
+	})
+}
@@ -0,0 +1,119 @@
+package server
+
+import (
+	"bytes"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"path/filepath"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// newReloadTestServer 创建只包含重新加载所需组件的服务器，配置文件中的代理设置为 proxy
+func newReloadTestServer(t *testing.T, proxy types.ProxyConfig) (*HTTPServer, string) {
+	t.Helper()
+	configPath := filepath.Join(t.TempDir(), "config.yaml")
+	configMgr := config.NewConfigManager(configPath)
+	if err := configMgr.Save(&types.Config{Server: types.ServerConfig{Host: "localhost", Port: 8080}, Proxy: proxy}); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	cfg, err := configMgr.Load()
+	if err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	upstreamMgr := upstream.NewUpstreamManager(configMgr)
+	requestRouter := router.NewRequestRouter(upstreamMgr, router.ConfiguredStrategy(&cfg.Routing))
+	proxyHandler := NewProxyHandler(nil, upstreamMgr, requestRouter, converter.NewManager(), stats.NewRecorder(0), &cfg.Proxy,
+		func() *types.ModelRouteConfig { return &configMgr.Get().ModelRoutes })
+	return &HTTPServer{
+		config:       &cfg.Server,
+		configMgr:    configMgr,
+		upstreamMgr:  upstreamMgr,
+		router:       requestRouter,
+		proxyHandler: proxyHandler,
+	}, configPath
+}
+
+// postOversized 发送 size 字节的聊天请求，返回状态码和错误中报告的请求体上限
+func postOversized(t *testing.T, h *ProxyHandler, size int) (int, float64) {
+	t.Helper()
+	body := bytes.Repeat([]byte(" "), size)
+	req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", bytes.NewReader(body))
+	req.Header.Set("Content-Type", "application/json")
+	rec := httptest.NewRecorder()
+	h.HandleChatCompletions(rec, req)
+
+	var resp struct {
+		Error struct {
+			MaxRequestBytes float64 `json:"max_request_bytes"`
+		} `json:"error"`
+	}
+	_ = json.Unmarshal(rec.Body.Bytes(), &resp)
+	return rec.Code, resp.Error.MaxRequestBytes
+}
+
+func TestReloadConfig_ReappliesProxySettings(t *testing.T) {
+	s, configPath := newReloadTestServer(t, types.ProxyConfig{MaxRequestBytes: 1024, MaxRetryAttempts: 1})
+
+	if code, limit := postOversized(t, s.proxyHandler, 2048); code != http.StatusRequestEntityTooLarge || limit != 1024 {
+		t.Fatalf("before reload: status = %d, max_request_bytes = %v, want 413 with 1024", code, limit)
+	}
+
+	// 外部修改配置文件后重新加载，之后的请求使用新的上限和重试次数
+	err := config.NewConfigManager(configPath).Save(&types.Config{
+		Server: types.ServerConfig{Host: "localhost", Port: 8080},
+		Proxy: types.ProxyConfig{
+			MaxRequestBytes:  4096,
+			MaxRetryAttempts: 3,
+			UsageHeaders:     true,
+			PathRules:        map[types.Provider]types.PathRules{types.ProviderOpenAI: {Deny: []string{"/v1/files"}}},
+		},
+	})
+	if err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	if _, err := s.ReloadConfig(); err != nil {
+		t.Fatalf("ReloadConfig() error = %v", err)
+	}
+
+	if code, limit := postOversized(t, s.proxyHandler, 8192); code != http.StatusRequestEntityTooLarge || limit != 4096 {
+		t.Errorf("after reload: status = %d, max_request_bytes = %v, want 413 with 4096", code, limit)
+	}
+	settings := s.proxyHandler.settings()
+	if settings.maxRetryAttempts != 3 || !settings.usageHeaders {
+		t.Errorf("after reload: maxRetryAttempts = %d, usageHeaders = %v, want 3 and true", settings.maxRetryAttempts, settings.usageHeaders)
+	}
+	if status, _ := s.proxyHandler.checkPathAccess("/v1/files", types.ProviderOpenAI); status != http.StatusForbidden {
+		t.Errorf("after reload: checkPathAccess() status = %d, want %d from the reloaded path rules", status, http.StatusForbidden)
+	}
+}
+
+func TestReloadConfig_InvalidConfigKeepsSettings(t *testing.T) {
+	s, configPath := newReloadTestServer(t, types.ProxyConfig{MaxRequestBytes: 1024})
+	previous := s.proxyHandler.settings()
+
+	// 端口无效，重新加载失败，代理设置保持不变
+	if err := config.NewConfigManager(configPath).Save(&types.Config{
+		Server: types.ServerConfig{Host: "localhost", Port: 0},
+		Proxy:  types.ProxyConfig{MaxRequestBytes: 4096},
+	}); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	if _, err := s.ReloadConfig(); err == nil {
+		t.Fatal("ReloadConfig() error = nil, want the invalid port rejected")
+	}
+	if s.proxyHandler.settings() != previous {
+		t.Error("ReloadConfig() replaced the proxy settings after a failed reload")
+	}
+	if code, limit := postOversized(t, s.proxyHandler, 2048); code != http.StatusRequestEntityTooLarge || limit != 1024 {
+		t.Errorf("status = %d, max_request_bytes = %v, want 413 with the previous limit 1024", code, limit)
+	}
+}
@@ -0,0 +1,66 @@
+package server
+
+import (
+	"context"
+	"crypto/rand"
+	"encoding/hex"
+	"net/http"
+	"strings"
+)
+
+// requestIDHeader 请求ID头部：接受客户端提供的ID，转发给上游并在响应中返回
+const requestIDHeader = "X-Request-Id"
+
+// maxRequestIDLength 客户端提供的请求ID的最大长度
+const maxRequestIDLength = 128
+
+// requestIDKey 请求上下文中保存请求ID的键
+type requestIDKey struct{}
+
+// RequestContextMiddleware 为请求确定请求ID：沿用客户端提供的有效 X-Request-Id，否则生成新的ID。
+// ID 保存在请求上下文中并写入响应头，之后的中间件和处理器用它关联日志、调试跟踪、使用记录和上游请求
+func RequestContextMiddleware(next http.HandlerFunc) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		next(w, withRequestID(w, r))
+	}
+}
+
+// withRequestID 确保请求上下文中有请求ID，已经有时原样返回
+func withRequestID(w http.ResponseWriter, r *http.Request) *http.Request {
+	if requestIDFrom(r.Context()) != "" {
+		return r
+	}
+	id := clientRequestID(r)
+	if id == "" {
+		id = generateRequestID()
+	}
+	w.Header().Set(requestIDHeader, id)
+	return r.WithContext(context.WithValue(r.Context(), requestIDKey{}, id))
+}
+
+// requestIDFrom 返回请求上下文中的请求ID，没有时返回空
+func requestIDFrom(ctx context.Context) string {
+	id, _ := ctx.Value(requestIDKey{}).(string)
+	return id
+}
+
+// clientRequestID 返回客户端提供的请求ID，只接受字母、数字和 -_.: 组成的ID，无效时返回空
+func clientRequestID(r *http.Request) string {
+	id := r.Header.Get(requestIDHeader)
+	if id == "" || len(id) > maxRequestIDLength {
+		return ""
+	}
+	for _, c := range id {
+		if !(c >= 'a' && c <= 'z' || c >= 'A' && c <= 'Z' || c >= '0' && c <= '9' || strings.ContainsRune("-_.:", c)) {
+			return ""
+		}
+	}
+	return id
+}
+
+// generateRequestID 生成请求ID
+func generateRequestID() string {
+	bytes := make([]byte, 8)
+	_, _ = rand.Read(bytes) // crypto/rand.Read never fails
+	return hex.EncodeToString(bytes)
+}
@@ -0,0 +1,116 @@
+package server
+
+import (
+	"bytes"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"os"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// syncBuffer 可并发写入的日志缓冲区（请求结束后仍可能有后台goroutine写日志）
+type syncBuffer struct {
+	mutex  sync.Mutex
+	buffer bytes.Buffer
+}
+
+func (b *syncBuffer) Write(p []byte) (int, error) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	return b.buffer.Write(p)
+}
+
+func (b *syncBuffer) String() string {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	return b.buffer.String()
+}
+
+func TestClientRequestID(t *testing.T) {
+	tests := []struct {
+		value string
+		want  string
+	}{
+		{value: "", want: ""},
+		{value: "req-1_a.b:c", want: "req-1_a.b:c"},
+		{value: "bad id", want: ""},
+		{value: "bad\nid", want: ""},
+		{value: strings.Repeat("a", maxRequestIDLength), want: strings.Repeat("a", maxRequestIDLength)},
+		{value: strings.Repeat("a", maxRequestIDLength+1), want: ""},
+	}
+	for _, tt := range tests {
+		req := httptest.NewRequest(http.MethodGet, "/", nil)
+		req.Header.Set(requestIDHeader, tt.value)
+		if got := clientRequestID(req); got != tt.want {
+			t.Errorf("clientRequestID(%q) = %q, want %q", tt.value, got, tt.want)
+		}
+	}
+}
+
+func TestRequestID_Propagation(t *testing.T) {
+	tests := []struct {
+		name     string
+		clientID string
+		wantID   string // 为空时应使用网关生成的ID
+	}{
+		{name: "client supplied", clientID: "client-req-1", wantID: "client-req-1"},
+		{name: "invalid client id is replaced", clientID: "bad id"},
+		{name: "generated"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			received := make(chan string, 1)
+			server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+				received <- r.Header.Get(requestIDHeader)
+				w.Header().Set("Request-Id", "req_upstream_1")
+				w.WriteHeader(http.StatusBadRequest)
+				_, _ = w.Write([]byte(`{"error":{"message":"bad request"}}`))
+			}))
+			defer server.Close()
+
+			var logs syncBuffer
+			logger.SetOutput(&logs)
+			defer logger.SetOutput(os.Stdout)
+
+			h, _ := newUpstreamTestHandler(t, types.ProxyConfig{}, testOpenAIAccount("primary", server.URL, 0))
+			headers := map[string]string{}
+			if tt.clientID != "" {
+				headers[requestIDHeader] = tt.clientID
+			}
+			rec := postChat(h, headers)
+
+			// 响应头、上游请求头、错误响应和日志使用同一个请求ID
+			id := rec.Header().Get(requestIDHeader)
+			if id == "" || (tt.wantID != "" && id != tt.wantID) || (tt.wantID == "" && id == tt.clientID) {
+				t.Fatalf("response %s = %q, want %q", requestIDHeader, id, tt.wantID)
+			}
+			if upstreamID := <-received; upstreamID != id {
+				t.Errorf("upstream %s = %q, want %q", requestIDHeader, upstreamID, id)
+			}
+
+			var body struct {
+				RequestID string `json:"request_id"`
+			}
+			if err := json.Unmarshal(rec.Body.Bytes(), &body); err != nil || body.RequestID != id {
+				t.Errorf("error body = %s, want request_id %q", rec.Body.String(), id)
+			}
+
+			var failed string
+			for _, line := range strings.Split(logs.String(), "\n") {
+				if strings.Contains(line, "proxy request failed") {
+					failed = line
+				}
+			}
+			if !strings.Contains(failed, "request_id="+id) || !strings.Contains(failed, "upstream_request_id=req_upstream_1") {
+				t.Errorf("request log = %q, want request_id=%s and upstream_request_id=req_upstream_1", failed, id)
+			}
+		})
+	}
+}
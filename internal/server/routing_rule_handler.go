@@ -0,0 +1,191 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// routingRuleRequest 创建/更新路由规则的请求体
+type routingRuleRequest struct {
+	Pattern        string                `json:"pattern"`
+	Provider       types.Provider        `json:"provider"`
+	UpstreamIDs    []string              `json:"upstream_ids"`
+	Priority       int                   `json:"priority"`
+	Enabled        *bool                 `json:"enabled"`
+	Description    string                `json:"description"`
+	KeyTags        []string              `json:"key_tags"`
+	TimeOfDay      string                `json:"time_of_day"`
+	Timezone       string                `json:"timezone"`
+	MinInputTokens int                   `json:"min_input_tokens"`
+	MaxInputTokens int                   `json:"max_input_tokens"`
+	HasTools       *bool                 `json:"has_tools"`
+	Action         string                `json:"action"`
+	QueuePriority  types.RequestPriority `json:"queue_priority"`
+}
+
+// apply 将请求中的规则定义写入路由规则（不修改ID、启用状态和时间戳）
+func (req *routingRuleRequest) apply(rule *types.RoutingRule) {
+	rule.Pattern = req.Pattern
+	rule.Provider = req.Provider
+	rule.UpstreamIDs = req.UpstreamIDs
+	rule.Priority = req.Priority
+	rule.Description = req.Description
+	rule.KeyTags = req.KeyTags
+	rule.TimeOfDay = req.TimeOfDay
+	rule.Timezone = req.Timezone
+	rule.MinInputTokens = req.MinInputTokens
+	rule.MaxInputTokens = req.MaxInputTokens
+	rule.HasTools = req.HasTools
+	rule.Action = req.Action
+	rule.QueuePriority = req.QueuePriority
+}
+
+// validateRoutingRule 验证路由规则请求
+func (h *WebHandler) validateRoutingRule(req *routingRuleRequest) string {
+	var rule types.RoutingRule
+	req.apply(&rule)
+	if err := rule.Validate(); err != nil {
+		return "Invalid routing rule: " + err.Error()
+	}
+	if req.Provider == "" {
+		if len(req.UpstreamIDs) > 0 {
+			return "upstream_ids requires a provider"
+		}
+		return ""
+	}
+	if _, ok := h.upstreamMgr.Providers().Get(req.Provider); !ok {
+		return "unknown provider: " + string(req.Provider)
+	}
+	for _, id := range req.UpstreamIDs {
+		account, err := h.upstreamMgr.GetAccount(id)
+		if err != nil {
+			return "unknown upstream account: " + id
+		}
+		if account.Provider != req.Provider {
+			return "upstream account " + id + " does not belong to provider " + string(req.Provider)
+		}
+	}
+	return ""
+}
+
+// HandleRoutingRules 路由规则列表与创建
+func (h *WebHandler) HandleRoutingRules(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data": h.configMgr.ListRoutingRules(),
+		})
+	case http.MethodPost:
+		h.handleCreateRoutingRule(w, r)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) handleCreateRoutingRule(w http.ResponseWriter, r *http.Request) {
+	var req routingRuleRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	if msg := h.validateRoutingRule(&req); msg != "" {
+		h.writeError(w, http.StatusBadRequest, msg)
+		return
+	}
+
+	now := time.Now()
+	rule := &types.RoutingRule{
+		ID:        h.generateID("rule"),
+		Enabled:   true,
+		CreatedAt: now,
+		UpdatedAt: now,
+	}
+	req.apply(rule)
+	if req.Enabled != nil {
+		rule.Enabled = *req.Enabled
+	}
+
+	if err := h.configMgr.CreateRoutingRule(rule); err != nil {
+		logger.Error("Failed to create routing rule: %v", err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to create routing rule")
+		return
+	}
+
+	logger.Info("Created routing rule: %s -> %s (%s)", rule.Pattern, routingRuleTarget(rule), rule.ID)
+	h.writeJSON(w, http.StatusCreated, rule)
+}
+
+// HandleRoutingRuleActions 处理单个路由规则的更新和删除
+func (h *WebHandler) HandleRoutingRuleActions(w http.ResponseWriter, r *http.Request) {
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) != 4 {
+		h.writeError(w, http.StatusNotFound, "API endpoint not found")
+		return
+	}
+
+	ruleID := pathParts[3] // /api/v1/routing-rules/{id}
+
+	switch r.Method {
+	case http.MethodPut:
+		h.handleUpdateRoutingRule(w, r, ruleID)
+	case http.MethodDelete:
+		if err := h.configMgr.DeleteRoutingRule(ruleID); err != nil {
+			h.writeError(w, http.StatusNotFound, "Routing rule not found")
+			return
+		}
+		logger.Info("Deleted routing rule: %s", ruleID)
+		w.WriteHeader(http.StatusNoContent)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) handleUpdateRoutingRule(w http.ResponseWriter, r *http.Request, ruleID string) {
+	var req routingRuleRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	if msg := h.validateRoutingRule(&req); msg != "" {
+		h.writeError(w, http.StatusBadRequest, msg)
+		return
+	}
+
+	err := h.configMgr.UpdateRoutingRule(ruleID, func(rule *types.RoutingRule) error {
+		req.apply(rule)
+		if req.Enabled != nil {
+			rule.Enabled = *req.Enabled
+		}
+		rule.UpdatedAt = time.Now()
+		return nil
+	})
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "Routing rule not found")
+		return
+	}
+
+	logger.Info("Updated routing rule: %s", ruleID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Routing rule updated successfully",
+	})
+}
+
+// routingRuleTarget 规则动作的简短描述，用于日志
+func routingRuleTarget(rule *types.RoutingRule) string {
+	switch {
+	case rule.Denies():
+		return types.RoutingActionDeny
+	case rule.Provider != "":
+		return string(rule.Provider)
+	default:
+		return "priority " + string(rule.QueuePriority)
+	}
+}
@@ -0,0 +1,49 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+)
+
+// HandleRoutingStrategy 查看（GET）当前负载均衡策略、自动切换状态和切换记录，
+// 或手动指定策略（PUT，strategy 为空时恢复配置的策略和自动切换）
+func (h *WebHandler) HandleRoutingStrategy(w http.ResponseWriter, r *http.Request) {
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, h.autopilot.Status())
+	case http.MethodPut:
+		var req struct {
+			Strategy router.BalanceStrategy `json:"strategy"`
+			Reason   string                 `json:"reason"`
+		}
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if req.Strategy != "" && !router.ValidStrategy(req.Strategy) {
+			h.writeError(w, http.StatusBadRequest, "Unknown strategy: "+string(req.Strategy))
+			return
+		}
+
+		reason := req.Reason
+		if reason == "" {
+			reason = "manual override by " + h.sessionUser(r)
+			if req.Strategy == "" {
+				reason = "manual override cleared by " + h.sessionUser(r)
+			}
+		}
+		h.autopilot.SetOverride(req.Strategy, reason, time.Now())
+		if req.Strategy == "" {
+			logger.Info("Cleared routing strategy override by %s", h.sessionUser(r))
+		} else {
+			logger.Info("Pinned routing strategy to %s by %s", req.Strategy, h.sessionUser(r))
+		}
+		h.writeJSON(w, http.StatusOK, h.autopilot.Status())
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
@@ -0,0 +1,79 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+
+	"github.com/iBreaker/llm-gateway/internal/models"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// scopeMatch 单个模型作用域匹配到的注册表模型
+type scopeMatch struct {
+	Scope  string   `json:"scope"`
+	Models []string `json:"models"` // 为空说明作用域没有匹配任何已知模型（可能拼写错误）
+}
+
+// scopeModel 作用域集合允许的注册表模型
+type scopeModel struct {
+	ID       string         `json:"id"`
+	Provider types.Provider `json:"provider"`
+	Enabled  bool           `json:"enabled"` // 提供商是否已启用
+}
+
+// HandleScopePreview 预览一组作用域允许使用的注册表模型（包括 OpenAI 兼容后端发现的模型），不修改任何Key。
+// 同时应用提供商和模型作用域，并列出每个模型作用域各自匹配的模型
+func (h *WebHandler) HandleScopePreview(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	var req struct {
+		Scopes []string `json:"scopes"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+	if message := validateScopes(req.Scopes); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+
+	key := &types.GatewayAPIKey{Scopes: req.Scopes}
+	modelScopes := types.CompileModelScopes(req.Scopes)
+	registry := models.Default().Models()
+	candidates := append([]models.Model{}, registry...)
+	for _, id := range h.upstreamMgr.DiscoveredModels() {
+		candidates = append(candidates, models.Model{ID: id, Provider: types.ProviderOpenAICompatible})
+	}
+
+	allowed := []scopeModel{}
+	for _, model := range candidates {
+		if key.ScopeAllows(types.ScopeProvider, string(model.Provider)) && modelScopes.Allows(model.Provider, model.ID) {
+			allowed = append(allowed, scopeModel{ID: model.ID, Provider: model.Provider, Enabled: h.upstreamMgr.Providers().IsEnabled(model.Provider)})
+		}
+	}
+
+	matches := []scopeMatch{}
+	for _, scope := range req.Scopes {
+		if kind, _, _ := types.ParseScope(scope); kind != types.ScopeModel {
+			continue
+		}
+		single := types.CompileModelScopes([]string{scope})
+		match := scopeMatch{Scope: scope, Models: []string{}}
+		for _, model := range candidates {
+			if single.Allows(model.Provider, model.ID) {
+				match.Models = append(match.Models, model.ID)
+			}
+		}
+		matches = append(matches, match)
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"models":          allowed,
+		"model_scopes":    matches,
+		"registry_models": len(registry),
+	})
+}
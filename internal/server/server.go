@@ -6,12 +6,21 @@ import (
 	"fmt"
 	"log"
 	"net/http"
+	"time"
 
+	"github.com/iBreaker/llm-gateway/internal/audit"
+	"github.com/iBreaker/llm-gateway/internal/canary"
 	"github.com/iBreaker/llm-gateway/internal/client"
 	"github.com/iBreaker/llm-gateway/internal/config"
 	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/notify"
+	"github.com/iBreaker/llm-gateway/internal/quota"
+	"github.com/iBreaker/llm-gateway/internal/ratelimit"
 	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/serviceaccount"
+	"github.com/iBreaker/llm-gateway/internal/stats"
 	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
 	"github.com/iBreaker/llm-gateway/pkg/types"
 )
 
@@ -23,6 +32,8 @@ type ConfigManager interface {
 	DeleteUpstreamAccount(id string) error
 	ListGatewayKeys() []*types.GatewayAPIKey
 	DeleteGatewayKey(id string) error
+	ListAnnouncements() []*types.Announcement
+	DismissAnnouncement(id, userID string) error
 }
 
 // HTTPServer HTTP服务器
@@ -39,6 +50,17 @@ type HTTPServer struct {
 	proxyHandler *ProxyHandler
 	configMgr    ConfigManager
 	oauthMgr     *upstream.OAuthManager
+	healthSvc    *upstream.HealthService
+	recorder     *stats.Recorder
+	quota        *quota.Service
+	audit        *audit.Log
+	notifier     *notify.Service
+	canaries     *canary.Runner
+	usageWAL     *stats.WAL
+	rollups      *stats.Rollups
+	autopilot    *router.Autopilot
+	drain        *drainGate
+	version      string
 }
 
 // NewServer 创建新的HTTP服务器
@@ -50,15 +72,32 @@ func NewServer(
 	converter *converter.Manager,
 	configMgr ConfigManager,
 	oauthMgr *upstream.OAuthManager,
+	healthSvc *upstream.HealthService,
+	recorder *stats.Recorder,
+	auditLog *audit.Log,
+	notifier *notify.Service,
+	canaries *canary.Runner,
+	usageWAL *stats.WAL,
+	rollups *stats.Rollups,
+	autopilot *router.Autopilot,
+	rateLimiter ratelimit.WindowLimiter,
 ) *HTTPServer {
 	mux := http.NewServeMux()
 
 	// 创建中间件
+	drain := &drainGate{}
 	authMW := NewAuthMiddleware(clientMgr)
-	rateLimitMW := NewRateLimitMiddleware(clientMgr)
+	authMW.drain = drain
+	quotaSvc := quota.NewService(recorder)
+	rateLimitMW := NewRateLimitMiddleware(clientMgr, quotaSvc, rateLimiter)
+	rateLimitMW.notifications = func() *types.NotificationConfig { return &configMgr.Get().Notifications }
+	rateLimitMW.notifier = notifier
 
 	// 创建代理处理器
-	proxyHandler := NewProxyHandler(clientMgr, upstreamMgr, router, converter, &config.Proxy, &config.ModelRoutes)
+	proxyHandler := NewProxyHandler(clientMgr, upstreamMgr, router, converter, recorder, &config.Proxy, func() *types.ModelRouteConfig { return &configMgr.Get().ModelRoutes })
+	proxyHandler.audit = auditLog
+	proxyHandler.quota = quotaSvc
+	proxyHandler.drain = drain
 
 	s := &HTTPServer{
 		mux:          mux,
@@ -72,6 +111,17 @@ func NewServer(
 		proxyHandler: proxyHandler,
 		configMgr:    configMgr,
 		oauthMgr:     oauthMgr,
+		healthSvc:    healthSvc,
+		recorder:     recorder,
+		quota:        quotaSvc,
+		audit:        auditLog,
+		notifier:     notifier,
+		canaries:     canaries,
+		usageWAL:     usageWAL,
+		rollups:      rollups,
+		autopilot:    autopilot,
+		drain:        drain,
+		version:      "dev",
 	}
 
 	s.setupRoutes()
@@ -83,11 +133,23 @@ func NewServer(
 func (s *HTTPServer) setupRoutes() {
 	// 健康检查路由（无需认证）
 	s.mux.HandleFunc("/health", CORSMiddleware(LoggingMiddleware(s.handleHealth)))
+	s.mux.HandleFunc("/api/version", CORSMiddleware(LoggingMiddleware(s.handleVersion)))
 
 	// API代理路由（需要完整的中间件链）
 	s.mux.HandleFunc("/v1/chat/completions", s.withMiddleware(s.proxyHandler.HandleChatCompletions))
 	s.mux.HandleFunc("/v1/completions", s.withMiddleware(s.proxyHandler.HandleCompletions))
+	s.mux.HandleFunc("/v1/embeddings", s.withMiddleware(s.proxyHandler.HandleEmbeddings))
 	s.mux.HandleFunc("/v1/messages", s.withMiddleware(s.proxyHandler.HandleMessages)) // Anthropic原生端点
+	s.mux.HandleFunc("/v1/messages/ws", s.withMiddleware(s.proxyHandler.HandleMessagesWebSocket))
+	s.mux.HandleFunc(geminiModelsPrefix, s.withMiddleware(s.proxyHandler.HandleGemini)) // Gemini原生端点
+
+	// 公告端点（面向Gateway API Key用户）
+	s.mux.HandleFunc("/v1/announcements", s.withMiddleware(s.handleGatewayAnnouncements))
+	s.mux.HandleFunc("/v1/announcements/", s.withMiddleware(s.handleGatewayAnnouncements))
+
+	// 未注册的 /v1 路径统一返回404，避免落入Web静态资源处理
+	s.mux.HandleFunc("/v1/", s.withMiddleware(s.handleUnknownEndpoint))
+	s.mux.HandleFunc("/v1beta/", s.withMiddleware(s.handleUnknownEndpoint))
 }
 
 // setupWebRoutes 设置Web管理界面路由
@@ -95,41 +157,108 @@ func (s *HTTPServer) setupWebRoutes() {
 	// 由于接口限制，这里需要具体的ConfigManager实现类型
 	// 这个方法需要在调用方传入具体的类型
 	if configMgr, ok := s.configMgr.(*config.ConfigManager); ok {
-		webHandler := NewWebHandler(configMgr, s.upstreamMgr, s.clientMgr, s.oauthMgr)
-		
+		webHandler := NewWebHandler(configMgr, s.upstreamMgr, s.clientMgr, s.oauthMgr, s.healthSvc, s.recorder, s.quota, s.audit, s.notifier, s.canaries)
+		webHandler.usageWAL = s.usageWAL
+		webHandler.rollups = s.rollups
+		webHandler.autopilot = s.autopilot
+		webHandler.proxy = s.proxyHandler
+		webHandler.reload = s.ReloadConfig
+		webCfg := configMgr.Get().Server.Web
+		if issuer, err := serviceaccount.NewIssuer(webCfg.ServiceTokenSecret, time.Duration(webCfg.ServiceTokenTTLSeconds)*time.Second); err != nil {
+			logger.Error("Service account tokens disabled: %v", err)
+		} else {
+			webHandler.serviceTokens = issuer
+		}
+
 		// 根路径提供web管理界面
 		s.mux.HandleFunc("/", webHandler.ServeStatic)
-		
+
 		// 静态资源路径
 		s.mux.HandleFunc("/static/", webHandler.ServeStatic)
-		
+
 		// 公开的认证端点（不需要认证）
 		s.mux.HandleFunc("/api/v1/login", CORSMiddleware(LoggingMiddleware(webHandler.HandleLogin)))
 		s.mux.HandleFunc("/api/v1/logout", CORSMiddleware(LoggingMiddleware(webHandler.HandleLogout)))
 		s.mux.HandleFunc("/api/v1/change-password", CORSMiddleware(LoggingMiddleware(webHandler.HandleChangePassword)))
-		
-		// 受保护的Web API 端点（需要认证）
+		s.mux.HandleFunc("/api/v1/service-accounts/token", CORSMiddleware(LoggingMiddleware(webHandler.HandleServiceAccountToken)))
+
+		// 受保护的Web API 端点（需要认证）：查询对所有角色开放，修改配置需要 admin，
+		// 运维操作（健康探测、合成探针、公告、审计、测试通知）需要 operator
+		adminWrite := readWrite(types.RoleViewer, types.RoleAdmin)
+		operator := readWrite(types.RoleOperator, types.RoleOperator)
+		operatorWrite := readWrite(types.RoleViewer, types.RoleOperator)
+		admin := readWrite(types.RoleAdmin, types.RoleAdmin)
 		s.mux.HandleFunc("/api/v1/health", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAPIHealth))))
-		s.mux.HandleFunc("/api/v1/config", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAPIConfig))))
-		s.mux.HandleFunc("/api/v1/upstream", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAPIUpstream))))
-		s.mux.HandleFunc("/api/v1/upstream/", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAPIUpstreamDelete))))
-		s.mux.HandleFunc("/api/v1/apikeys", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAPIKeys))))
-		s.mux.HandleFunc("/api/v1/apikeys/", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAPIKeyActions))))
-		
-		// 受保护的OAuth API 端点（需要认证）
-		s.mux.HandleFunc("/api/v1/oauth/start", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleOAuthStart))))
-		s.mux.HandleFunc("/api/v1/oauth/callback", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleOAuthCallback))))
-		s.mux.HandleFunc("/api/v1/oauth/status/", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleOAuthStatus))))
+		s.mux.HandleFunc("/api/v1/config", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleAPIConfig))))
+		s.mux.HandleFunc("/api/v1/config/reload", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleConfigReload))))
+		s.mux.HandleFunc("/api/v1/upstream", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleAPIUpstream))))
+		s.mux.HandleFunc("/api/v1/upstream/health", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operatorWrite, webHandler.HandleUpstreamHealth))))
+		s.mux.HandleFunc("/api/v1/upstream/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(upstreamAccess, webHandler.HandleAPIUpstreamDelete))))
+		s.mux.HandleFunc("/api/v1/apikeys", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(apiKeyAccess, webHandler.HandleAPIKeys))))
+		s.mux.HandleFunc("/api/v1/apikeys/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleAPIKeyActions))))
+		s.mux.HandleFunc("/api/v1/apikeys/creation-settings", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleKeyCreationSettings))))
+		s.mux.HandleFunc("/api/v1/apikeys/scope-preview", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operator, webHandler.HandleScopePreview))))
+		s.mux.HandleFunc("/api/v1/announcements", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operatorWrite, webHandler.HandleAnnouncements))))
+		s.mux.HandleFunc("/api/v1/announcements/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(announcementAccess, webHandler.HandleAnnouncementActions))))
+		s.mux.HandleFunc("/api/v1/dashboard/summary", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleDashboardSummary))))
+		s.mux.HandleFunc("/api/v1/stats/export", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operator, webHandler.HandleUsageExport))))
+		s.mux.HandleFunc("/api/v1/stats/slo", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleSLOStats))))
+		s.mux.HandleFunc("/api/v1/stats/forecast", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleForecastStats))))
+		s.mux.HandleFunc("/api/v1/stats/hygiene", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleHygieneStats))))
+		s.mux.HandleFunc("/api/v1/stats/languages", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleLanguageStats))))
+		s.mux.HandleFunc("/api/v1/stats/apps", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleAppStats))))
+		s.mux.HandleFunc("/api/v1/stats/terminations", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleTerminationStats))))
+		s.mux.HandleFunc("/api/v1/stats/detailed", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleDetailedStats))))
+		s.mux.HandleFunc("/api/v1/stats/organizations", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleOrgStats))))
+		s.mux.HandleFunc("/api/v1/stats/usage-wal", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleUsageWAL))))
+		s.mux.HandleFunc("/api/v1/audit", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operator, webHandler.HandleAuditQuery))))
+		s.mux.HandleFunc("/api/v1/notifications", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleNotifications))))
+		s.mux.HandleFunc("/api/v1/notifications/deliveries", CORSMiddleware(LoggingMiddleware(webHandler.requireAuth(webHandler.HandleNotificationDeliveries))))
+		s.mux.HandleFunc("/api/v1/notifications/test", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operator, webHandler.HandleNotificationTest))))
+		s.mux.HandleFunc("/api/v1/canaries", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operatorWrite, webHandler.HandleCanaries))))
+		s.mux.HandleFunc("/api/v1/pricing", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandlePricing))))
+		s.mux.HandleFunc("/api/v1/model-routes", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleModelRoutes))))
+		s.mux.HandleFunc("/api/v1/routing-rules", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleRoutingRules))))
+		s.mux.HandleFunc("/api/v1/routing-rules/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleRoutingRuleActions))))
+		s.mux.HandleFunc("/api/v1/routing/strategy", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operatorWrite, webHandler.HandleRoutingStrategy))))
+		s.mux.HandleFunc("/api/v1/routing/snapshot", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleRoutingSnapshot))))
+		s.mux.HandleFunc("/api/v1/routing/simulate", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(operator, webHandler.HandleRoutingSimulation))))
+		s.mux.HandleFunc("/api/v1/live", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleLiveTraffic))))
+		s.mux.HandleFunc("/api/v1/circuit-breaker", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleCircuitBreakerConfig))))
+		s.mux.HandleFunc("/api/v1/providers", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleProviders))))
+		s.mux.HandleFunc("/api/v1/providers/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleProviderActions))))
+		s.mux.HandleFunc("/api/v1/organizations", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleOrganizations))))
+		s.mux.HandleFunc("/api/v1/organizations/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(adminWrite, webHandler.HandleOrganizationActions))))
+		s.mux.HandleFunc("/api/v1/users", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleUsers))))
+		s.mux.HandleFunc("/api/v1/users/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleUserActions))))
+		s.mux.HandleFunc("/api/v1/service-accounts", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleServiceAccounts))))
+		s.mux.HandleFunc("/api/v1/service-accounts/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleServiceAccountActions))))
+
+		// 受保护的OAuth API 端点（需要 admin）
+		s.mux.HandleFunc("/api/v1/oauth/start", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleOAuthStart))))
+		s.mux.HandleFunc("/api/v1/oauth/callback", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleOAuthCallback))))
+		s.mux.HandleFunc("/api/v1/oauth/status/", CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleOAuthStatus))))
+
+		// 性能剖析端点（需要 admin，且仅在 server.profiling.enabled 时注册）
+		if configMgr.Get().Server.Profiling.Enabled {
+			s.mux.HandleFunc(profilePrefix, CORSMiddleware(LoggingMiddleware(webHandler.requireRole(admin, webHandler.HandleProfile))))
+		}
 	}
 }
 
 // withMiddleware 应用中间件链
 func (s *HTTPServer) withMiddleware(handler http.HandlerFunc) http.HandlerFunc {
-	// 中间件链：CORS -> 日志 -> 认证 -> 限流 -> 处理器
+	// 中间件链：CORS -> 请求ID -> 排空 -> 日志 -> 认证 -> Key路径规则 -> 限流 -> 处理器
 	return CORSMiddleware(
-		LoggingMiddleware(
-			s.authMW.Authenticate(
-				s.rateLimitMW.RateLimit(handler),
+		RequestContextMiddleware(
+			s.drain.wrap(
+				LoggingMiddleware(
+					s.authMW.Authenticate(
+						s.proxyHandler.PathAccess(
+							s.rateLimitMW.RateLimit(handler),
+						),
+					),
+				),
 			),
 		),
 	)
@@ -155,6 +284,31 @@ func (s *HTTPServer) Stop(ctx context.Context) error {
 	return nil
 }
 
+// Drain 优雅关闭：拒绝新的代理请求（503 server_shutting_down），停止监听并等待进行中的请求
+// （包括SSE流）结束，再等待用量统计和审计的异步写入完成；ctx到期时强制关闭剩余连接，返回被中断的请求数
+func (s *HTTPServer) Drain(ctx context.Context) int {
+	if inFlight := s.drain.start(); inFlight > 0 {
+		logger.Info("等待 %d 个进行中的代理请求结束...", inFlight)
+	}
+
+	start := time.Now()
+	interrupted := 0
+	if s.server != nil {
+		if err := s.server.Shutdown(ctx); err != nil {
+			interrupted = s.drain.active()
+			logger.Warn("排空超时（%s），强制关闭 %d 个未结束的代理请求", time.Since(start).Round(time.Second), interrupted)
+			_ = s.server.Close()
+		}
+	}
+
+	if !s.drain.flush(ctx) {
+		logger.Warn("等待用量统计和审计写入超时，部分记录可能丢失")
+	} else if interrupted == 0 {
+		logger.Info("所有请求已结束，耗时 %s", time.Since(start).Round(time.Millisecond))
+	}
+	return interrupted
+}
+
 // loggingMiddleware 日志中间件
 func (s *HTTPServer) loggingMiddleware(next http.Handler) http.Handler {
 	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
@@ -170,6 +324,16 @@ func (s *HTTPServer) writeJSONResponse(w http.ResponseWriter, status int, data i
 	_ = json.NewEncoder(w).Encode(data)
 }
 
+// handleUnknownEndpoint 处理网关未开放的 /v1 路径
+func (s *HTTPServer) handleUnknownEndpoint(w http.ResponseWriter, r *http.Request) {
+	s.writeJSONResponse(w, http.StatusNotFound, map[string]interface{}{
+		"error": map[string]string{
+			"type":    "path_not_found",
+			"message": fmt.Sprintf("Path %s is not available through the gateway", r.URL.Path),
+		},
+	})
+}
+
 // handleHealth 健康检查处理器
 func (s *HTTPServer) handleHealth(w http.ResponseWriter, r *http.Request) {
 	if r.Method != http.MethodGet {
@@ -182,3 +346,22 @@ func (s *HTTPServer) handleHealth(w http.ResponseWriter, r *http.Request) {
 		"service": "llm-gateway",
 	})
 }
+
+// SetVersion 设置 /api/version 返回的构建版本
+func (s *HTTPServer) SetVersion(version string) {
+	s.version = version
+}
+
+// handleVersion 返回构建版本和当前生效的Go运行时配置
+func (s *HTTPServer) handleVersion(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	s.writeJSONResponse(w, http.StatusOK, map[string]interface{}{
+		"service": "llm-gateway",
+		"version": s.version,
+		"runtime": config.CurrentRuntime(),
+	})
+}
@@ -0,0 +1,184 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// serviceAccountRequest 创建服务账号的请求体
+type serviceAccountRequest struct {
+	Name string `json:"name"`
+	Role string `json:"role"`
+}
+
+// serviceSession 校验服务账号令牌，角色以配置中的当前值为准，账号删除后令牌立即失效
+func (h *WebHandler) serviceSession(token string) *Session {
+	if h.serviceTokens == nil {
+		return nil
+	}
+	claims, err := h.serviceTokens.Verify(token, time.Now())
+	if err != nil {
+		return nil
+	}
+	role, ok := h.configMgr.ServiceAccountRole(claims.Subject)
+	if !ok {
+		return nil
+	}
+	return &Session{
+		Username:  claims.Subject,
+		Role:      role,
+		ExpiresAt: time.Unix(claims.ExpiresAt, 0),
+		CreatedAt: time.Unix(claims.IssuedAt, 0),
+		Service:   true,
+	}
+}
+
+// rejectServiceSession 服务账号不能管理Web用户和服务账号，避免自动化凭证自行扩大权限
+func (h *WebHandler) rejectServiceSession(w http.ResponseWriter, r *http.Request) bool {
+	if session := h.currentSession(r); session != nil && session.Service {
+		h.writeError(w, http.StatusForbidden, "Service accounts cannot manage users or service accounts")
+		return true
+	}
+	return false
+}
+
+// HandleServiceAccountToken 用 client_id 和 client_secret 换取短期访问令牌（公开端点）。
+// 请求体支持 JSON 或 OAuth2 client_credentials 风格的表单
+func (h *WebHandler) HandleServiceAccountToken(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+	if h.serviceTokens == nil {
+		h.writeError(w, http.StatusServiceUnavailable, "Service account tokens are unavailable")
+		return
+	}
+
+	var req struct {
+		ClientID     string `json:"client_id"`
+		ClientSecret string `json:"client_secret"`
+	}
+	if strings.HasPrefix(r.Header.Get("Content-Type"), "application/x-www-form-urlencoded") {
+		if err := r.ParseForm(); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if grantType := r.PostForm.Get("grant_type"); grantType != "" && grantType != "client_credentials" {
+			h.writeError(w, http.StatusBadRequest, "grant_type must be client_credentials")
+			return
+		}
+		req.ClientID = r.PostForm.Get("client_id")
+		req.ClientSecret = r.PostForm.Get("client_secret")
+	} else if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	role, ok := h.configMgr.AuthenticateServiceAccount(req.ClientID, req.ClientSecret)
+	if !ok {
+		logger.Warn("Service account token request rejected for client %s", req.ClientID)
+		h.writeError(w, http.StatusUnauthorized, "Invalid client credentials")
+		return
+	}
+
+	token, expiresAt, err := h.serviceTokens.Issue(req.ClientID, role, time.Now())
+	if err != nil {
+		logger.Error("Failed to issue service account token: %v", err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to issue token")
+		return
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"access_token": token,
+		"token_type":   "Bearer",
+		"expires_in":   int(time.Until(expiresAt).Seconds()),
+		"role":         role,
+	})
+}
+
+// HandleServiceAccounts 服务账号列表与创建（仅 admin）
+func (h *WebHandler) HandleServiceAccounts(w http.ResponseWriter, r *http.Request) {
+	if h.rejectServiceSession(w, r) {
+		return
+	}
+
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data":              h.configMgr.ListServiceAccounts(),
+			"token_ttl_seconds": int(h.serviceTokenTTL().Seconds()),
+		})
+	case http.MethodPost:
+		var req serviceAccountRequest
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if req.Name == "" {
+			h.writeError(w, http.StatusBadRequest, "name is required")
+			return
+		}
+		if types.RoleRank(req.Role) == 0 {
+			h.writeError(w, http.StatusBadRequest, "role must be one of admin, operator, viewer")
+			return
+		}
+
+		account, secret, err := h.configMgr.CreateServiceAccount(req.Name, req.Role)
+		if err != nil {
+			logger.Error("Failed to create service account: %v", err)
+			h.writeError(w, http.StatusInternalServerError, "Failed to create service account")
+			return
+		}
+
+		logger.Info("Created service account %s (%s, %s) by %s", account.ClientID, account.Name, account.Role, h.sessionUser(r))
+		// client_secret 只在创建时返回一次
+		h.writeJSON(w, http.StatusCreated, map[string]interface{}{
+			"client_id":     account.ClientID,
+			"client_secret": secret,
+			"name":          account.Name,
+			"role":          account.Role,
+			"created_at":    account.CreatedAt,
+		})
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+// HandleServiceAccountActions 删除单个服务账号（仅 admin），已签发的令牌随之失效
+func (h *WebHandler) HandleServiceAccountActions(w http.ResponseWriter, r *http.Request) {
+	if h.rejectServiceSession(w, r) {
+		return
+	}
+
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) != 4 {
+		h.writeError(w, http.StatusNotFound, "API endpoint not found")
+		return
+	}
+	clientID := pathParts[3] // /api/v1/service-accounts/{client_id}
+
+	switch r.Method {
+	case http.MethodDelete:
+		if err := h.configMgr.DeleteServiceAccount(clientID); err != nil {
+			h.writeError(w, http.StatusNotFound, "Service account not found")
+			return
+		}
+		logger.Info("Deleted service account %s by %s", clientID, h.sessionUser(r))
+		w.WriteHeader(http.StatusNoContent)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+// serviceTokenTTL 服务账号令牌的有效期
+func (h *WebHandler) serviceTokenTTL() time.Duration {
+	if h.serviceTokens == nil {
+		return 0
+	}
+	return h.serviceTokens.TTL()
+}
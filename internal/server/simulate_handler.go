@@ -0,0 +1,140 @@
+package server
+
+import (
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	defaultSimulationSample = 1000
+	maxSimulationSample     = 10000
+	maxSimulationScenarios  = 10
+)
+
+// simulationRequest 路由模拟的请求体
+type simulationRequest struct {
+	Hours      int                         `json:"hours"`       // 使用最近多少小时的记录，默认24
+	SampleSize int                         `json:"sample_size"` // 重放的记录数，默认1000
+	Scenarios  []router.SimulationScenario `json:"scenarios"`
+}
+
+// HandleRoutingSimulation 用最近的使用记录离线评估候选路由配置（策略、权重、备用账号链），
+// 返回样本的历史结果和每个场景预估的费用、延迟和失败率变化，不影响实际路由
+func (h *WebHandler) HandleRoutingSimulation(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	var req simulationRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+	if req.Hours == 0 {
+		req.Hours = 24
+	}
+	if req.SampleSize == 0 {
+		req.SampleSize = defaultSimulationSample
+	}
+	if req.Hours < 1 || req.Hours > 24*90 {
+		h.writeError(w, http.StatusBadRequest, "hours must be between 1 and 2160")
+		return
+	}
+	if req.SampleSize < 1 || req.SampleSize > maxSimulationSample {
+		h.writeError(w, http.StatusBadRequest, fmt.Sprintf("sample_size must be between 1 and %d", maxSimulationSample))
+		return
+	}
+	if message := h.validateScenarios(req.Scenarios); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+
+	// 只重放实际路由到上游的请求（跳过认证失败等未选择账号的请求和响应缓存命中）
+	var history []stats.UsageRecord
+	for _, record := range h.recorder.Query(stats.Filter{Since: time.Now().Add(-time.Duration(req.Hours) * time.Hour)}) {
+		if record.UpstreamID == "" || (record.CacheInfo != nil && record.CacheInfo.Hit) {
+			continue
+		}
+		history = append(history, record)
+	}
+
+	var active []*types.UpstreamAccount
+	for _, account := range h.upstreamMgr.ListAccounts() {
+		if account.Status == "active" {
+			active = append(active, account)
+		}
+	}
+
+	simulation := router.NewSimulation(history, active, h.simulationCandidates, h.autopilot.Status().Strategy)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"window_hours": req.Hours,
+		"history":      len(history),
+		"report":       simulation.Run(sampleRecords(history, req.SampleSize), req.Scenarios),
+	})
+}
+
+// validateScenarios 检查模拟场景，返回错误信息，合法时返回空字符串
+func (h *WebHandler) validateScenarios(scenarios []router.SimulationScenario) string {
+	if len(scenarios) == 0 || len(scenarios) > maxSimulationScenarios {
+		return fmt.Sprintf("between 1 and %d scenarios are required", maxSimulationScenarios)
+	}
+	for i := range scenarios {
+		scenario := &scenarios[i]
+		if scenario.Name == "" {
+			scenario.Name = fmt.Sprintf("scenario-%d", i+1)
+		}
+		if scenario.Strategy != "" && !router.ValidStrategy(scenario.Strategy) {
+			return fmt.Sprintf("scenario %s: unknown strategy %s", scenario.Name, scenario.Strategy)
+		}
+		for id, weight := range scenario.Weights {
+			if weight < 0 {
+				return fmt.Sprintf("scenario %s: weight for %s must not be negative", scenario.Name, id)
+			}
+			if _, err := h.upstreamMgr.GetAccount(id); err != nil {
+				return fmt.Sprintf("scenario %s: unknown upstream account %s", scenario.Name, id)
+			}
+		}
+		for _, id := range scenario.Fallback {
+			if _, err := h.upstreamMgr.GetAccount(id); err != nil {
+				return fmt.Sprintf("scenario %s: unknown upstream account %s", scenario.Name, id)
+			}
+		}
+	}
+	return ""
+}
+
+// simulationCandidates 返回请求当前可以路由到的账号：提供商的活跃账号，命中带账号池的路由规则时只取账号池
+func (h *WebHandler) simulationCandidates(provider types.Provider, model string) []*types.UpstreamAccount {
+	accounts := h.upstreamMgr.ListActiveAccounts(provider)
+	rule := types.MatchRoutingRule(h.configMgr.ListRoutingRules(), model)
+	if rule == nil || rule.Provider != provider || len(rule.UpstreamIDs) == 0 {
+		return accounts
+	}
+
+	pool := make([]*types.UpstreamAccount, 0, len(accounts))
+	for _, account := range accounts {
+		if rule.InPool(account.ID) {
+			pool = append(pool, account)
+		}
+	}
+	return pool
+}
+
+// sampleRecords 按固定间隔抽取最多 size 条记录，保持样本在时间窗口内均匀分布
+func sampleRecords(records []stats.UsageRecord, size int) []stats.UsageRecord {
+	if len(records) <= size {
+		return records
+	}
+	sample := make([]stats.UsageRecord, 0, size)
+	for i := 0; i < size; i++ {
+		sample = append(sample, records[i*len(records)/size])
+	}
+	return sample
+}
@@ -0,0 +1,193 @@
+package server
+
+import (
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"os"
+	"sort"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/cache"
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// routingSnapshotSchema 路由状态快照的格式版本，字段含义变化时递增
+const routingSnapshotSchema = 1
+
+// snapshotBreakerHistory 快照中每个账号保留的熔断器状态转换记录数
+const snapshotBreakerHistory = 10
+
+// RoutingSnapshot 某一时刻完整的路由状态，用于事后复盘：内存中的状态会很快变化，
+// 快照保存为自包含的JSON文档，离线查看时不需要访问网关
+type RoutingSnapshot struct {
+	Schema     int       `json:"schema_version"`
+	CapturedAt time.Time `json:"captured_at"`
+	CapturedBy string    `json:"captured_by"`
+	Instance   string    `json:"instance"` // 网关实例的主机名，多副本时区分快照来源
+
+	Summary   SnapshotSummary            `json:"summary"`
+	Routing   *router.AutopilotStatus    `json:"routing,omitempty"` // 负载均衡策略和自动切换状态
+	Providers map[types.Provider]bool    `json:"providers"`         // 提供商是否启用
+	Accounts  []AccountSnapshot          `json:"accounts"`
+	Rules     []*types.RoutingRule       `json:"routing_rules"`
+	Breaker   types.CircuitBreakerConfig `json:"circuit_breaker"`          // 全局熔断器参数
+	Queue     *QueueSnapshot             `json:"queue,omitempty"`          // 未启用排队时为空
+	Cache     *cache.Stats               `json:"response_cache,omitempty"` // 未启用响应缓存时为空
+}
+
+// SnapshotSummary 快照的概要，便于快速判断当时的状况
+type SnapshotSummary struct {
+	Accounts     int `json:"accounts"`
+	Active       int `json:"active"`
+	Healthy      int `json:"healthy"`
+	BreakersOpen int `json:"breakers_open"` // 熔断器打开或半开的账号数
+	CoolingDown  int `json:"cooling_down"`  // 上游报告额度接近耗尽、路由暂时避开的账号数
+	InFlight     int `json:"in_flight"`     // 所有账号进行中的请求数
+	Queued       int `json:"queued"`
+}
+
+// AccountSnapshot 单个上游账号在快照时刻的路由相关状态
+type AccountSnapshot struct {
+	ID              string                       `json:"id"`
+	Name            string                       `json:"name"`
+	Provider        types.Provider               `json:"provider"`
+	Status          string                       `json:"status"`
+	OrgID           string                       `json:"org_id,omitempty"`
+	Priority        int                          `json:"priority"`
+	Weight          int                          `json:"weight"`
+	HealthStatus    string                       `json:"health_status"`
+	LastHealthCheck *time.Time                   `json:"last_health_check,omitempty"`
+	HealthLatencyMs int64                        `json:"health_latency_ms"`
+	InFlight        int                          `json:"in_flight"`
+	MaxConcurrent   int                          `json:"max_concurrent"`              // 0表示不限制
+	RecentLatencyMs *float64                     `json:"recent_latency_ms,omitempty"` // fastest 策略使用的加权平均延迟，没有成功请求时为空
+	Usage           *types.UpstreamUsageStats    `json:"usage,omitempty"`
+	Breaker         upstream.BreakerStatus       `json:"breaker"`
+	BreakerUntil    *time.Time                   `json:"breaker_open_until,omitempty"` // 自动打开的熔断器进入半开状态的时间
+	BreakerHistory  []upstream.BreakerTransition `json:"breaker_history,omitempty"`
+	RateLimit       *upstream.RateLimitState     `json:"rate_limit,omitempty"`
+	CoolingDown     bool                         `json:"cooling_down"` // 额度接近耗尽，路由暂时避开
+}
+
+// QueueSnapshot 等待上游并发名额的请求队列
+type QueueSnapshot struct {
+	Depth      int                    `json:"depth"`
+	Capacity   int                    `json:"capacity"`
+	ByProvider map[types.Provider]int `json:"by_provider"`
+}
+
+// HandleRoutingSnapshot 导出当前完整的路由状态快照（GET），作为JSON附件下载
+func (h *WebHandler) HandleRoutingSnapshot(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	snapshot := h.captureRoutingSnapshot(h.sessionUser(r), time.Now())
+	body, err := json.MarshalIndent(snapshot, "", "  ")
+	if err != nil {
+		h.writeError(w, http.StatusInternalServerError, "Failed to encode snapshot: "+err.Error())
+		return
+	}
+
+	logger.Info("Captured routing snapshot of %d upstream accounts by %s", len(snapshot.Accounts), snapshot.CapturedBy)
+	w.Header().Set("Content-Type", "application/json")
+	w.Header().Set("Content-Disposition", fmt.Sprintf("attachment; filename=routing-snapshot-%s.json", snapshot.CapturedAt.UTC().Format("20060102-150405")))
+	w.WriteHeader(http.StatusOK)
+	_, _ = w.Write(body)
+}
+
+// captureRoutingSnapshot 收集账号、熔断器、额度、并发、队列和缓存的当前状态。
+// 各部分分别读取，不是同一把锁下的原子快照，但足以还原当时的路由决策依据
+func (h *WebHandler) captureRoutingSnapshot(capturedBy string, now time.Time) *RoutingSnapshot {
+	cfg := h.configMgr.Get()
+	instance, _ := os.Hostname()
+	snapshot := &RoutingSnapshot{
+		Schema:     routingSnapshotSchema,
+		CapturedAt: now,
+		CapturedBy: capturedBy,
+		Instance:   instance,
+		Providers:  make(map[types.Provider]bool),
+		Accounts:   []AccountSnapshot{},
+		Rules:      h.configMgr.ListRoutingRules(),
+		Breaker:    cfg.HealthCheck.CircuitBreaker,
+	}
+	if h.autopilot != nil {
+		status := h.autopilot.Status()
+		snapshot.Routing = &status
+	}
+
+	breakers := h.upstreamMgr.Breakers()
+	rateLimits := h.upstreamMgr.RateLimits()
+	accounts := h.configMgr.ListUpstreamAccounts()
+	sort.Slice(accounts, func(i, j int) bool { return accounts[i].ID < accounts[j].ID })
+	for _, account := range accounts {
+		item := AccountSnapshot{
+			ID:              account.ID,
+			Name:            account.Name,
+			Provider:        account.Provider,
+			Status:          account.Status,
+			OrgID:           account.OrgID,
+			Priority:        account.Priority,
+			Weight:          account.EffectiveWeight(),
+			HealthStatus:    account.HealthStatus,
+			LastHealthCheck: account.LastHealthCheck,
+			HealthLatencyMs: account.HealthLatencyMs,
+			MaxConcurrent:   account.MaxConcurrent,
+			Usage:           account.Usage,
+			Breaker:         breakers.Status(account.ID),
+			BreakerHistory:  breakers.History(account.ID, snapshotBreakerHistory),
+			RateLimit:       rateLimits.Get(account.ID, now),
+			CoolingDown:     rateLimits.Exhausted(account.ID, now),
+		}
+		if item.Breaker.State == upstream.BreakerOpen && !item.Breaker.Forced && item.Breaker.OpenedAt != nil {
+			until := item.Breaker.OpenedAt.Add(time.Duration(item.Breaker.OpenSeconds) * time.Second)
+			item.BreakerUntil = &until
+		}
+		if h.proxy != nil {
+			item.InFlight = h.proxy.concurrency.InFlight(account.ID)
+			if latency, measured := h.proxy.router.RecentLatency(account.ID); measured {
+				item.RecentLatencyMs = &latency
+			}
+		}
+		snapshot.Accounts = append(snapshot.Accounts, item)
+		snapshot.Providers[account.Provider] = h.upstreamMgr.Providers().IsEnabled(account.Provider)
+
+		summary := &snapshot.Summary
+		summary.Accounts++
+		summary.InFlight += item.InFlight
+		if account.Status == "active" {
+			summary.Active++
+		}
+		if account.HealthStatus == "healthy" {
+			summary.Healthy++
+		}
+		if item.Breaker.State != upstream.BreakerClosed {
+			summary.BreakersOpen++
+		}
+		if item.CoolingDown {
+			summary.CoolingDown++
+		}
+	}
+
+	if h.proxy != nil && h.proxy.queue != nil {
+		queue := &QueueSnapshot{Capacity: h.proxy.queue.Capacity(), ByProvider: make(map[types.Provider]int)}
+		for key, depth := range h.proxy.queue.Depths() {
+			queue.ByProvider[types.Provider(key)] = depth
+			queue.Depth += depth
+		}
+		snapshot.Queue = queue
+		snapshot.Summary.Queued = queue.Depth
+	}
+	if h.proxy != nil {
+		if responseCache := h.proxy.settings().responseCache; responseCache != nil {
+			stats := responseCache.Stats()
+			snapshot.Cache = &stats
+		}
+	}
+	return snapshot
+}
@@ -0,0 +1,463 @@
+package server
+
+import (
+	"net/http"
+	"sort"
+	"strconv"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/hygiene"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// HandleSLOStats 返回每个Gateway Key和上游账号的Apdex/SLO达成情况
+func (h *WebHandler) HandleSLOStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	cfg := h.configMgr.Get().SLO
+	now := time.Now()
+	records := h.recorder.Query(stats.Filter{
+		Since: now.Add(-time.Duration(cfg.WindowHours) * time.Hour),
+	})
+
+	keyNames := make(map[string]string)
+	for _, key := range h.configMgr.ListGatewayKeys() {
+		keyNames[key.ID] = key.Name
+	}
+	accountNames := make(map[string]string)
+	for _, account := range h.configMgr.ListUpstreamAccounts() {
+		accountNames[account.ID] = account.Name
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"targets": map[string]interface{}{
+			"latency_threshold_ms":      cfg.LatencyThresholdMs,
+			"availability_target":       cfg.AvailabilityTarget,
+			"window_hours":              cfg.WindowHours,
+			"burn_rate_window_minutes":  cfg.BurnRateWindowMinutes,
+			"burn_rate_alert_threshold": cfg.BurnRateAlertThreshold,
+		},
+		"keys":     withNames(stats.ComputeSLOReports(records, cfg, now, stats.GroupByKey), keyNames),
+		"accounts": withNames(stats.ComputeSLOReports(records, cfg, now, stats.GroupByAccount), accountNames),
+	})
+}
+
+// HandleForecastStats 按Gateway Key和上游账号预测未来7天/30天的token与费用
+func (h *WebHandler) HandleForecastStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	historyDays := 28
+	if value := r.URL.Query().Get("history_days"); value != "" {
+		days, err := strconv.Atoi(value)
+		if err != nil || days < 1 || days > 90 {
+			h.writeError(w, http.StatusBadRequest, "history_days must be between 1 and 90")
+			return
+		}
+		historyDays = days
+	}
+
+	now := time.Now()
+	records := h.recorder.Query(stats.Filter{
+		Since: now.AddDate(0, 0, -historyDays-1),
+	})
+
+	keyNames := make(map[string]string)
+	for _, key := range h.configMgr.ListGatewayKeys() {
+		keyNames[key.ID] = key.Name
+	}
+	accountNames := make(map[string]string)
+	for _, account := range h.configMgr.ListUpstreamAccounts() {
+		accountNames[account.ID] = account.Name
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"history_days": historyDays,
+		"keys":         forecastsWithNames(stats.ComputeForecasts(records, now, historyDays, stats.GroupByKey), keyNames),
+		"accounts":     forecastsWithNames(stats.ComputeForecasts(records, now, historyDays, stats.GroupByAccount), accountNames),
+	})
+}
+
+// HandleHygieneStats 返回超过闲置阈值未使用的Gateway Key和上游账号
+func (h *WebHandler) HandleHygieneStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	cfg := h.configMgr.Get().Hygiene
+	h.writeJSON(w, http.StatusOK, hygiene.BuildReport(h.configMgr.ListGatewayKeys(), h.configMgr.ListUpstreamAccounts(), cfg, time.Now()))
+}
+
+// HandleLanguageStats 按提示词语言汇总最近的流量，可按 key_id 只看单个Gateway Key，按 org_id 只看单个组织
+func (h *WebHandler) HandleLanguageStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	hours := 24
+	if value := r.URL.Query().Get("hours"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > 24*90 {
+			h.writeError(w, http.StatusBadRequest, "hours must be between 1 and 2160")
+			return
+		}
+		hours = parsed
+	}
+
+	keyID := r.URL.Query().Get("key_id")
+	orgID := r.URL.Query().Get("org_id")
+	records := h.recorder.Query(stats.Filter{
+		Since:        time.Now().Add(-time.Duration(hours) * time.Hour),
+		GatewayKeyID: keyID,
+		OrgID:        orgID,
+	})
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"window_hours": hours,
+		"key_id":       keyID,
+		"org_id":       orgID,
+		"total":        len(records),
+		"languages":    stats.ComputeLanguageStats(records),
+	})
+}
+
+// HandleAppStats 按客户端应用（X-Gateway-App）汇总最近的流量，group_by=version 时按应用版本分组，
+// 可按 key_id 只看单个Gateway Key，按 org_id 只看单个组织
+func (h *WebHandler) HandleAppStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	hours := 24
+	if value := r.URL.Query().Get("hours"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > 24*90 {
+			h.writeError(w, http.StatusBadRequest, "hours must be between 1 and 2160")
+			return
+		}
+		hours = parsed
+	}
+
+	groupBy := stats.GroupByApp
+	switch value := r.URL.Query().Get("group_by"); value {
+	case "", "app":
+	case "version":
+		groupBy = stats.GroupByAppVersion
+	default:
+		h.writeError(w, http.StatusBadRequest, "group_by must be app or version")
+		return
+	}
+
+	keyID := r.URL.Query().Get("key_id")
+	orgID := r.URL.Query().Get("org_id")
+	records := h.recorder.Query(stats.Filter{
+		Since:        time.Now().Add(-time.Duration(hours) * time.Hour),
+		GatewayKeyID: keyID,
+		OrgID:        orgID,
+		App:          r.URL.Query().Get("app"),
+	})
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"window_hours": hours,
+		"key_id":       keyID,
+		"org_id":       orgID,
+		"total":        len(records),
+		"apps":         stats.ComputeAppStats(records, groupBy),
+	})
+}
+
+// HandleTerminationStats 按结束原因（正常完成、客户端断开、上游错误、超时、网关关闭）汇总最近的流式请求，
+// 可按 key_id、org_id 和 provider 过滤
+func (h *WebHandler) HandleTerminationStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	hours := 24
+	if value := r.URL.Query().Get("hours"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > 24*90 {
+			h.writeError(w, http.StatusBadRequest, "hours must be between 1 and 2160")
+			return
+		}
+		hours = parsed
+	}
+
+	keyID := r.URL.Query().Get("key_id")
+	orgID := r.URL.Query().Get("org_id")
+	provider := types.Provider(r.URL.Query().Get("provider"))
+	records := h.recorder.Query(stats.Filter{
+		Since:        time.Now().Add(-time.Duration(hours) * time.Hour),
+		GatewayKeyID: keyID,
+		OrgID:        orgID,
+		Provider:     provider,
+	})
+
+	terminations := stats.ComputeTerminationStats(records)
+	streams := 0
+	for _, entry := range terminations {
+		streams += entry.Requests
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"window_hours": hours,
+		"key_id":       keyID,
+		"org_id":       orgID,
+		"provider":     provider,
+		"streams":      streams,
+		"terminations": terminations,
+	})
+}
+
+// HandleOrgStats 按Key所属的组织汇总最近的用量（请求数、错误数、token、费用和产生用量的Key数）
+func (h *WebHandler) HandleOrgStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	hours := 24
+	if value := r.URL.Query().Get("hours"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > 24*90 {
+			h.writeError(w, http.StatusBadRequest, "hours must be between 1 and 2160")
+			return
+		}
+		hours = parsed
+	}
+
+	records := h.recorder.Query(stats.Filter{
+		Since: time.Now().Add(-time.Duration(hours) * time.Hour),
+	})
+
+	names := make(map[string]string)
+	for _, org := range h.configMgr.ListOrganizations() {
+		names[org.ID] = org.Name
+	}
+	orgs := make([]map[string]interface{}, 0)
+	for _, entry := range stats.ComputeOrgStats(records) {
+		orgs = append(orgs, map[string]interface{}{
+			"org_id":        entry.OrgID,
+			"name":          names[entry.OrgID],
+			"keys":          entry.Keys,
+			"requests":      entry.Requests,
+			"errors":        entry.Errors,
+			"input_tokens":  entry.InputTokens,
+			"output_tokens": entry.OutputTokens,
+			"cost_usd":      entry.CostUSD,
+		})
+	}
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"window_hours":  hours,
+		"total":         len(records),
+		"organizations": orgs,
+	})
+}
+
+// handleAPIKeyHeatmap 按星期×小时返回Key最近 days 天（默认28）的请求数和费用分布，tz 指定划分小时所用的时区（默认UTC）
+func (h *WebHandler) handleAPIKeyHeatmap(w http.ResponseWriter, r *http.Request, keyID string) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+	gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "API key not found")
+		return
+	}
+
+	days := 28
+	if value := r.URL.Query().Get("days"); value != "" {
+		parsed, err := strconv.Atoi(value)
+		if err != nil || parsed < 1 || parsed > 90 {
+			h.writeError(w, http.StatusBadRequest, "days must be between 1 and 90")
+			return
+		}
+		days = parsed
+	}
+	loc := time.UTC
+	if value := r.URL.Query().Get("tz"); value != "" {
+		parsed, err := time.LoadLocation(value)
+		if err != nil {
+			h.writeError(w, http.StatusBadRequest, "Unknown time zone: "+value)
+			return
+		}
+		loc = parsed
+	}
+
+	now := time.Now()
+	since := now.AddDate(0, 0, -days)
+	buckets := h.rollups.Query(stats.RollupQuery{
+		Granularity: stats.RollupHourly,
+		Dimension:   stats.RollupByKey,
+		ID:          keyID,
+		Since:       since,
+	}, now)
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"key_id":   keyID,
+		"key_name": gatewayKey.Name,
+		"days":     days,
+		"timezone": loc.String(),
+		"since":    since,
+		"until":    now,
+		"heatmap":  stats.ComputeHeatmapFromRollups(buckets, loc),
+	})
+}
+
+// HandleDetailedStats 从小时/天汇总返回每个Gateway Key、上游账号、模型、提供商或账号池的用量时间序列，
+// 以及成功率、延迟和首token延迟的分位数、平均输出速度，不扫描原始使用记录。
+// granularity=hour 时按 hours（默认24）取窗口，granularity=day 时按 days（默认30）取窗口，id 只看单个取值
+func (h *WebHandler) HandleDetailedStats(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+		return
+	}
+
+	query := r.URL.Query()
+	dimension := query.Get("group_by")
+	switch dimension {
+	case "":
+		dimension = stats.RollupByKey
+	case stats.RollupByKey, stats.RollupByAccount, stats.RollupByModel, stats.RollupByProvider, stats.RollupByPool:
+	default:
+		h.writeError(w, http.StatusBadRequest, "group_by must be key, account, model, provider or pool")
+		return
+	}
+
+	now := time.Now()
+	var since time.Time
+	granularity := query.Get("granularity")
+	switch granularity {
+	case "", stats.RollupHourly:
+		granularity = stats.RollupHourly
+		hours := 24
+		if value := query.Get("hours"); value != "" {
+			parsed, err := strconv.Atoi(value)
+			if err != nil || parsed < 1 || parsed > 24*90 {
+				h.writeError(w, http.StatusBadRequest, "hours must be between 1 and 2160")
+				return
+			}
+			hours = parsed
+		}
+		since = now.Add(-time.Duration(hours) * time.Hour)
+	case stats.RollupDaily:
+		days := 30
+		if value := query.Get("days"); value != "" {
+			parsed, err := strconv.Atoi(value)
+			if err != nil || parsed < 1 || parsed > 400 {
+				h.writeError(w, http.StatusBadRequest, "days must be between 1 and 400")
+				return
+			}
+			days = parsed
+		}
+		since = now.AddDate(0, 0, -days)
+	default:
+		h.writeError(w, http.StatusBadRequest, "granularity must be hour or day")
+		return
+	}
+
+	buckets := h.rollups.Query(stats.RollupQuery{
+		Granularity: granularity,
+		Dimension:   dimension,
+		ID:          query.Get("id"),
+		Since:       since,
+	}, now)
+
+	names := make(map[string]string)
+	switch dimension {
+	case stats.RollupByKey:
+		for _, key := range h.configMgr.ListGatewayKeys() {
+			names[key.ID] = key.Name
+		}
+	case stats.RollupByAccount:
+		for _, account := range h.configMgr.ListUpstreamAccounts() {
+			names[account.ID] = account.Name
+		}
+	case stats.RollupByPool:
+		for _, account := range h.configMgr.ListUpstreamAccounts() {
+			if key := account.PoolKey(); key != "" {
+				names[key] = account.Pool
+			}
+		}
+	}
+
+	// 每个取值在窗口内的合计
+	totals := make(map[string]*stats.RollupBucket)
+	series := make([]map[string]interface{}, 0, len(buckets))
+	for i := range buckets {
+		bucket := &buckets[i]
+		total, exists := totals[bucket.ID]
+		if !exists {
+			total = &stats.RollupBucket{Dimension: dimension, ID: bucket.ID}
+			totals[bucket.ID] = total
+		}
+		total.Merge(bucket)
+		series = append(series, map[string]interface{}{
+			"name":    names[bucket.ID],
+			"bucket":  bucket,
+			"metrics": bucket.Metrics(),
+		})
+	}
+	summary := make([]map[string]interface{}, 0, len(totals))
+	for id, total := range totals {
+		summary = append(summary, map[string]interface{}{
+			"id":            id,
+			"name":          names[id],
+			"requests":      total.Requests,
+			"errors":        total.Errors,
+			"error_classes": total.ErrorClasses,
+			"input_tokens":  total.InputTokens,
+			"output_tokens": total.OutputTokens,
+			"cost_usd":      total.CostUSD,
+			"metrics":       total.Metrics(),
+		})
+	}
+	sort.Slice(summary, func(i, j int) bool {
+		return summary[i]["id"].(string) < summary[j]["id"].(string)
+	})
+
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"granularity": granularity,
+		"group_by":    dimension,
+		"since":       since,
+		"until":       now,
+		"totals":      summary,
+		"buckets":     series,
+	})
+}
+
+// withNames 为SLO报告附加可读名称
+func withNames(reports []*stats.SLOReport, names map[string]string) []map[string]interface{} {
+	result := make([]map[string]interface{}, 0, len(reports))
+	for _, report := range reports {
+		result = append(result, map[string]interface{}{
+			"name":   names[report.ID],
+			"report": report,
+		})
+	}
+	return result
+}
+
+// forecastsWithNames 为用量预测附加可读名称
+func forecastsWithNames(reports []*stats.ForecastReport, names map[string]string) []map[string]interface{} {
+	result := make([]map[string]interface{}, 0, len(reports))
+	for _, report := range reports {
+		result = append(result, map[string]interface{}{
+			"name":     names[report.ID],
+			"forecast": report,
+		})
+	}
+	return result
+}
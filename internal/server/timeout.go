@@ -0,0 +1,148 @@
+package server
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// timeoutHeader 客户端指定本次请求的上游超时（毫秒）
+const timeoutHeader = "X-LLM-Timeout-Ms"
+
+// upstreamTimeoutError 上游在超时时间内没有完成响应
+type upstreamTimeoutError struct {
+	Timeout   time.Duration
+	ClientSet bool // 超时由客户端通过 X-LLM-Timeout-Ms 指定，且短于配置的超时
+	Err       error
+}
+
+func (e *upstreamTimeoutError) Error() string {
+	return fmt.Sprintf("上游响应超时（%v）: %v", e.Timeout, e.Err)
+}
+
+func (e *upstreamTimeoutError) Unwrap() error {
+	return e.Err
+}
+
+// ClientError 客户端选择的更短超时不说明账号有问题，不计入熔断器；
+// 否则任何Key都可以用很短的超时让共享账号的熔断器打开
+func (e *upstreamTimeoutError) ClientError() bool {
+	return e.ClientSet
+}
+
+// clientTimeout 错误是否为客户端选择的更短超时，这类错误也不计入账号健康状态和错误统计
+func clientTimeout(err error) bool {
+	var timeoutErr *upstreamTimeoutError
+	return errors.As(err, &timeoutErr) && timeoutErr.ClientSet
+}
+
+// requestTimeout 读取客户端指定的上游超时，不能超过Key的 max_timeout_ms（未设置时不能超过全局超时）。
+// 没有指定时返回0，头部值无效时返回 ok=false
+func requestTimeout(r *http.Request, key *types.GatewayAPIKey, global time.Duration) (time.Duration, bool) {
+	value := strings.TrimSpace(r.Header.Get(timeoutHeader))
+	if value == "" {
+		return 0, true
+	}
+	ms, err := strconv.Atoi(value)
+	if err != nil || ms <= 0 {
+		return 0, false
+	}
+
+	timeout := time.Duration(ms) * time.Millisecond
+	limit := global
+	if key != nil && key.MaxTimeoutMs > 0 {
+		limit = time.Duration(key.MaxTimeoutMs) * time.Millisecond
+	}
+	if limit > 0 && timeout > limit {
+		timeout = limit
+	}
+	return timeout, true
+}
+
+// attemptTimeout 返回一次上游尝试可用的超时：请求设置了截止时间时为剩余时间（截止时间已过时返回
+// context.DeadlineExceeded），否则为0（使用全局超时）
+func attemptTimeout(request *types.UnifiedRequest, now time.Time) (time.Duration, error) {
+	if request.Deadline.IsZero() {
+		return 0, nil
+	}
+	remaining := request.Deadline.Sub(now)
+	if remaining <= 0 {
+		return 0, context.DeadlineExceeded
+	}
+	return remaining, nil
+}
+
+// deadlinePassed 请求的截止时间是否已过，已过时不再切换账号或重试
+func deadlinePassed(request *types.UnifiedRequest, now time.Time) bool {
+	return !request.Deadline.IsZero() && !now.Before(request.Deadline)
+}
+
+// upstreamClient 返回发送上游请求的客户端，指定了超时时使用共享连接池、超时不同的客户端
+func (h *ProxyHandler) upstreamClient(timeout time.Duration) *http.Client {
+	httpClient := h.settings().httpClient
+	if timeout <= 0 || timeout == httpClient.Timeout {
+		return httpClient
+	}
+	return &http.Client{Transport: httpClient.Transport, Timeout: timeout}
+}
+
+// effectiveTimeout 返回本次请求实际使用的上游超时
+func (h *ProxyHandler) effectiveTimeout(timeout time.Duration) time.Duration {
+	if timeout > 0 {
+		return timeout
+	}
+	return h.settings().httpClient.Timeout
+}
+
+// writeUpstreamTimeout 返回 504，错误中带上实际使用的超时时间
+func (h *ProxyHandler) writeUpstreamTimeout(w http.ResponseWriter, timeout time.Duration) {
+	h.writeErrorDetails(w, http.StatusGatewayTimeout, "upstream_timeout",
+		fmt.Sprintf("Upstream did not respond within %d ms", timeout.Milliseconds()),
+		map[string]interface{}{"timeout_ms": timeout.Milliseconds()})
+}
+
+// wrapTimeout 把等待上游超时的错误包装为 upstreamTimeoutError，其他错误原样返回；
+// timeout 为客户端指定的超时，短于配置的超时时标记为客户端选择的超时
+func (h *ProxyHandler) wrapTimeout(err error, timeout time.Duration) error {
+	if err == nil || !isTimeoutError(err) {
+		return err
+	}
+	configured := h.settings().httpClient.Timeout
+	clientSet := timeout > 0 && (configured <= 0 || timeout < configured)
+	return &upstreamTimeoutError{Timeout: h.effectiveTimeout(timeout), ClientSet: clientSet, Err: err}
+}
+
+// upstreamErrorType 返回上游请求失败时使用记录的错误类型
+func upstreamErrorType(err error) string {
+	var timeoutErr *upstreamTimeoutError
+	if errors.As(err, &timeoutErr) {
+		return "upstream_timeout"
+	}
+	if _, overloaded := overloadedError(err); overloaded {
+		return "upstream_overloaded"
+	}
+	return "upstream_error"
+}
+
+// upstreamErrorClass 返回上游请求失败时使用记录的错误分类：带状态码的错误按状态码和错误体分类，
+// 超时归为 timeout，其他错误（连接失败、响应中途断开）归为 network
+func upstreamErrorClass(err error) types.ErrorClass {
+	var statusErr *upstreamStatusError
+	switch {
+	case err == nil:
+		return ""
+	case errors.As(err, &statusErr):
+		return upstream.ClassifyStatus(statusErr.StatusCode, statusErr.Body)
+	case isTimeoutError(err):
+		return types.ErrorClassTimeout
+	default:
+		return types.ErrorClassNetwork
+	}
+}
@@ -0,0 +1,280 @@
+package server
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"path/filepath"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/converter"
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/stats"
+	"github.com/iBreaker/llm-gateway/internal/upstream"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// newUpstreamTestHandler 创建代理处理器，配置文件中只有 accounts 这些上游账号（通常指向 httptest 服务器），
+// 同时返回配置管理器以便测试修改配置
+func newUpstreamTestHandler(t *testing.T, proxy types.ProxyConfig, accounts ...types.UpstreamAccount) (*ProxyHandler, *config.ConfigManager) {
+	t.Helper()
+	configMgr := config.NewConfigManager(filepath.Join(t.TempDir(), "config.yaml"))
+	if err := configMgr.Save(&types.Config{
+		Server:           types.ServerConfig{Host: "localhost", Port: 8080},
+		Proxy:            proxy,
+		UpstreamAccounts: accounts,
+	}); err != nil {
+		t.Fatalf("Save() error = %v", err)
+	}
+	cfg, err := configMgr.Load()
+	if err != nil {
+		t.Fatalf("Load() error = %v", err)
+	}
+
+	upstreamMgr := upstream.NewUpstreamManager(configMgr)
+	requestRouter := router.NewRequestRouter(upstreamMgr, router.ConfiguredStrategy(&cfg.Routing))
+	h := NewProxyHandler(nil, upstreamMgr, requestRouter, converter.NewManager(), stats.NewRecorder(0), &cfg.Proxy,
+		func() *types.ModelRouteConfig { return &configMgr.Get().ModelRoutes })
+	return h, configMgr
+}
+
+// testOpenAIAccount 返回指向 baseURL 的 OpenAI API Key 账号
+func testOpenAIAccount(id, baseURL string, priority int) types.UpstreamAccount {
+	return types.UpstreamAccount{
+		ID:        id,
+		Name:      id,
+		Type:      types.UpstreamTypeAPIKey,
+		Provider:  types.ProviderOpenAI,
+		BaseURL:   baseURL,
+		APIKey:    "sk-test",
+		Status:    "active",
+		Priority:  priority,
+		CreatedAt: time.Now(),
+		UpdatedAt: time.Now(),
+	}
+}
+
+// postChat 发送非流式聊天请求，headers 为额外的请求头
+func postChat(h *ProxyHandler, headers map[string]string) *httptest.ResponseRecorder {
+	body := `{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}]}`
+	req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", strings.NewReader(body))
+	req.Header.Set("Content-Type", "application/json")
+	for name, value := range headers {
+		req.Header.Set(name, value)
+	}
+	rec := httptest.NewRecorder()
+	h.HandleChatCompletions(rec, req)
+	return rec
+}
+
+func TestRequestTimeout(t *testing.T) {
+	const global = 60 * time.Second
+	capped := &types.GatewayAPIKey{MaxTimeoutMs: 2000}
+	tests := []struct {
+		name   string
+		header string
+		key    *types.GatewayAPIKey
+		global time.Duration
+		want   time.Duration
+		ok     bool
+	}{
+		{name: "not set", header: "", global: global, want: 0, ok: true},
+		{name: "not a number", header: "fast", global: global, ok: false},
+		{name: "zero", header: "0", global: global, ok: false},
+		{name: "negative", header: "-100", global: global, ok: false},
+		{name: "below the limit", header: "1500", global: global, want: 1500 * time.Millisecond, ok: true},
+		{name: "surrounding spaces", header: " 1500 ", global: global, want: 1500 * time.Millisecond, ok: true},
+		{name: "clamped to the key limit", header: "5000", key: capped, global: global, want: 2000 * time.Millisecond, ok: true},
+		{name: "key limit above global", header: "90000", key: &types.GatewayAPIKey{MaxTimeoutMs: 120000}, global: global, want: 90 * time.Second, ok: true},
+		{name: "clamped to global without a key limit", header: "90000", key: &types.GatewayAPIKey{}, global: global, want: global, ok: true},
+		{name: "no global limit", header: "90000", global: 0, want: 90 * time.Second, ok: true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			req := httptest.NewRequest(http.MethodPost, "/v1/chat/completions", nil)
+			if tt.header != "" {
+				req.Header.Set(timeoutHeader, tt.header)
+			}
+			got, ok := requestTimeout(req, tt.key, tt.global)
+			if ok != tt.ok || (ok && got != tt.want) {
+				t.Errorf("requestTimeout() = %v, %v, want %v, %v", got, ok, tt.want, tt.ok)
+			}
+		})
+	}
+}
+
+// timeoutNetError 模拟读取上游响应超时的网络错误
+type timeoutNetError struct{}
+
+func (timeoutNetError) Error() string   { return "i/o timeout" }
+func (timeoutNetError) Timeout() bool   { return true }
+func (timeoutNetError) Temporary() bool { return true }
+
+func TestWrapTimeout(t *testing.T) {
+	h := &ProxyHandler{current: newProxySettings(&types.ProxyConfig{StreamTimeout: 30}, nil)}
+
+	if err := h.wrapTimeout(nil, time.Second); err != nil {
+		t.Errorf("wrapTimeout(nil) = %v, want nil", err)
+	}
+	statusErr := &upstreamStatusError{StatusCode: http.StatusBadGateway}
+	if err := h.wrapTimeout(statusErr, time.Second); err != statusErr {
+		t.Errorf("wrapTimeout() = %v, want the non-timeout error unchanged", err)
+	}
+
+	tests := []struct {
+		name      string
+		err       error
+		timeout   time.Duration
+		want      time.Duration
+		clientSet bool
+	}{
+		{name: "deadline exceeded", err: fmt.Errorf("upstream request failed: %w", context.DeadlineExceeded), timeout: 500 * time.Millisecond, want: 500 * time.Millisecond, clientSet: true},
+		{name: "net timeout", err: fmt.Errorf("read body: %w", timeoutNetError{}), timeout: 2 * time.Second, want: 2 * time.Second, clientSet: true},
+		{name: "global timeout", err: context.DeadlineExceeded, timeout: 0, want: 30 * time.Second},
+		{name: "client timeout equal to the configured one", err: context.DeadlineExceeded, timeout: 30 * time.Second, want: 30 * time.Second},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var timeoutErr *upstreamTimeoutError
+			err := h.wrapTimeout(tt.err, tt.timeout)
+			if !errors.As(err, &timeoutErr) {
+				t.Fatalf("wrapTimeout() = %v, want an upstreamTimeoutError", err)
+			}
+			if timeoutErr.Timeout != tt.want || !errors.Is(err, tt.err) {
+				t.Errorf("wrapTimeout() timeout = %v, want %v wrapping the original error", timeoutErr.Timeout, tt.want)
+			}
+			if timeoutErr.ClientError() != tt.clientSet || clientTimeout(err) != tt.clientSet {
+				t.Errorf("ClientError() = %v, want %v", timeoutErr.ClientError(), tt.clientSet)
+			}
+		})
+	}
+}
+
+func TestUpstreamErrorType(t *testing.T) {
+	tests := []struct {
+		name string
+		err  error
+		want string
+	}{
+		{name: "timeout", err: &upstreamTimeoutError{Timeout: time.Second, Err: context.DeadlineExceeded}, want: "upstream_timeout"},
+		{name: "529", err: &upstreamStatusError{StatusCode: upstream.StatusOverloaded}, want: "upstream_overloaded"},
+		{name: "overloaded_error body", err: &upstreamStatusError{StatusCode: http.StatusServiceUnavailable, Body: `{"type":"error","error":{"type":"overloaded_error"}}`}, want: "upstream_overloaded"},
+		{name: "status error", err: &upstreamStatusError{StatusCode: http.StatusBadGateway}, want: "upstream_error"},
+		{name: "network error", err: errors.New("connection refused"), want: "upstream_error"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := upstreamErrorType(tt.err); got != tt.want {
+				t.Errorf("upstreamErrorType() = %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestAttemptTimeout(t *testing.T) {
+	now := time.Now()
+
+	if timeout, err := attemptTimeout(&types.UnifiedRequest{}, now); timeout != 0 || err != nil {
+		t.Errorf("no deadline: attemptTimeout() = %v, %v, want 0 and nil", timeout, err)
+	}
+	if deadlinePassed(&types.UnifiedRequest{}, now) {
+		t.Error("no deadline: deadlinePassed() = true")
+	}
+
+	request := &types.UnifiedRequest{Deadline: now.Add(300 * time.Millisecond)}
+	if timeout, err := attemptTimeout(request, now.Add(100*time.Millisecond)); timeout != 200*time.Millisecond || err != nil {
+		t.Errorf("before the deadline: attemptTimeout() = %v, %v, want the remaining 200ms", timeout, err)
+	}
+	if deadlinePassed(request, now.Add(100*time.Millisecond)) {
+		t.Error("before the deadline: deadlinePassed() = true")
+	}
+
+	for _, at := range []time.Time{request.Deadline, now.Add(time.Second)} {
+		if _, err := attemptTimeout(request, at); !errors.Is(err, context.DeadlineExceeded) {
+			t.Errorf("attemptTimeout() at %v error = %v, want context.DeadlineExceeded", at.Sub(now), err)
+		}
+		if !deadlinePassed(request, at) {
+			t.Errorf("deadlinePassed() at %v = false", at.Sub(now))
+		}
+	}
+}
+
+func TestTimeoutCoversFailover(t *testing.T) {
+	// 第一个账号在超时时间内返回503，切换后的账号没有在剩余时间内响应
+	failing := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		time.Sleep(400 * time.Millisecond)
+		w.WriteHeader(http.StatusServiceUnavailable)
+	}))
+	defer failing.Close()
+	slow := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		select {
+		case <-r.Context().Done():
+		case <-time.After(5 * time.Second):
+		}
+	}))
+	defer slow.Close()
+
+	h, _ := newUpstreamTestHandler(t, types.ProxyConfig{},
+		testOpenAIAccount("primary", failing.URL, 0),
+		testOpenAIAccount("backup", slow.URL, 1))
+
+	start := time.Now()
+	rec := postChat(h, map[string]string{timeoutHeader: "500"})
+	elapsed := time.Since(start)
+
+	if rec.Code != http.StatusGatewayTimeout {
+		t.Fatalf("status = %d, want %d, body = %s", rec.Code, http.StatusGatewayTimeout, rec.Body.String())
+	}
+	var resp struct {
+		Error struct {
+			Type      string  `json:"type"`
+			TimeoutMs float64 `json:"timeout_ms"`
+		} `json:"error"`
+	}
+	_ = json.Unmarshal(rec.Body.Bytes(), &resp)
+	if resp.Error.Type != "upstream_timeout" || resp.Error.TimeoutMs != 500 {
+		t.Errorf("error = %+v, want upstream_timeout with timeout_ms 500", resp.Error)
+	}
+	// 切换账号不重新计时：总耗时接近500ms，而不是第一个账号的400ms再加上完整的500ms
+	if elapsed >= 800*time.Millisecond {
+		t.Errorf("elapsed = %v, want the timeout applied across both attempts", elapsed)
+	}
+}
+
+func TestClientTimeoutKeepsBreakerClosed(t *testing.T) {
+	slow := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		select {
+		case <-r.Context().Done():
+		case <-time.After(5 * time.Second):
+		}
+	}))
+	defer slow.Close()
+
+	h, _ := newUpstreamTestHandler(t, types.ProxyConfig{}, testOpenAIAccount("primary", slow.URL, 0))
+	breakers := h.upstreamMgr.Breakers()
+	breakers.Configure(func() *types.CircuitBreakerConfig { return &types.CircuitBreakerConfig{FailureThreshold: 1} })
+
+	// 客户端选择的短超时返回504，但不算作账号的失败
+	for i := 0; i < 3; i++ {
+		if rec := postChat(h, map[string]string{timeoutHeader: "20"}); rec.Code != http.StatusGatewayTimeout {
+			t.Fatalf("status = %d, want %d", rec.Code, http.StatusGatewayTimeout)
+		}
+	}
+	time.Sleep(50 * time.Millisecond) // 错误统计在后台goroutine中记录
+	if state, failures := breakers.State("primary"); state != upstream.BreakerClosed || failures != 0 {
+		t.Errorf("breaker = %s with %d failures, want closed with none", state, failures)
+	}
+
+	// 配置的超时仍然计入熔断器
+	breakers.RecordFailure("primary", h.wrapTimeout(context.DeadlineExceeded, 0), time.Now())
+	if state, _ := breakers.State("primary"); state != upstream.BreakerOpen {
+		t.Errorf("breaker = %s after a configured timeout, want open", state)
+	}
+}
@@ -0,0 +1,29 @@
+package server
+
+import "net/http"
+
+// HandleUsageWAL 查看使用记录日志的积压（GET），或立即把缓冲的记录写入磁盘（POST）
+func (h *WebHandler) HandleUsageWAL(w http.ResponseWriter, r *http.Request) {
+	if h.usageWAL == nil {
+		h.writeError(w, http.StatusNotFound, "Usage WAL is not enabled")
+		return
+	}
+
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data": h.usageWAL.Status(),
+		})
+	case http.MethodPost:
+		// 写入失败的原因记录在状态的 last_error 中
+		if err := h.usageWAL.Flush(); err != nil {
+			h.writeError(w, http.StatusInternalServerError, "Failed to flush usage WAL")
+			return
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"data": h.usageWAL.Status(),
+		})
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
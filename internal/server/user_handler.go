@@ -0,0 +1,130 @@
+package server
+
+import (
+	"encoding/json"
+	"net/http"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// webUserRequest 创建/更新Web用户的请求体
+type webUserRequest struct {
+	Username string `json:"username"`
+	Role     string `json:"role"`
+	Password string `json:"password"`
+}
+
+// HandleUsers Web用户列表与创建（仅 admin）
+func (h *WebHandler) HandleUsers(w http.ResponseWriter, r *http.Request) {
+	if h.rejectServiceSession(w, r) {
+		return
+	}
+
+	switch r.Method {
+	case http.MethodGet:
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"builtin": map[string]string{"username": config.BuiltinAdminUser, "role": types.RoleAdmin},
+			"data":    h.configMgr.ListWebUsers(),
+		})
+	case http.MethodPost:
+		h.handleCreateUser(w, r)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) handleCreateUser(w http.ResponseWriter, r *http.Request) {
+	var req webUserRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+
+	if req.Username == "" || req.Password == "" {
+		h.writeError(w, http.StatusBadRequest, "username and password are required")
+		return
+	}
+	if req.Username == config.BuiltinAdminUser {
+		h.writeError(w, http.StatusBadRequest, "username "+config.BuiltinAdminUser+" is reserved for the built-in administrator")
+		return
+	}
+	if types.RoleRank(req.Role) == 0 {
+		h.writeError(w, http.StatusBadRequest, "role must be one of admin, operator, viewer")
+		return
+	}
+	for _, user := range h.configMgr.ListWebUsers() {
+		if user.Username == req.Username {
+			h.writeError(w, http.StatusConflict, "User already exists")
+			return
+		}
+	}
+
+	if err := h.configMgr.CreateWebUser(req.Username, req.Role, req.Password); err != nil {
+		logger.Error("Failed to create web user: %v", err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to create user")
+		return
+	}
+
+	logger.Info("Created web user %s (%s) by %s", req.Username, req.Role, h.sessionUser(r))
+	h.writeJSON(w, http.StatusCreated, map[string]interface{}{
+		"username": req.Username,
+		"role":     req.Role,
+	})
+}
+
+// HandleUserActions 修改或删除单个Web用户（仅 admin）
+func (h *WebHandler) HandleUserActions(w http.ResponseWriter, r *http.Request) {
+	if h.rejectServiceSession(w, r) {
+		return
+	}
+
+	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
+	if len(pathParts) != 4 {
+		h.writeError(w, http.StatusNotFound, "API endpoint not found")
+		return
+	}
+
+	username := pathParts[3] // /api/v1/users/{username}
+	if username == config.BuiltinAdminUser {
+		h.writeError(w, http.StatusBadRequest, "The built-in administrator is configured by server.web.password")
+		return
+	}
+	actor := h.sessionUser(r) // 会话可能随后被删除，先记录操作者
+
+	switch r.Method {
+	case http.MethodPut:
+		var req webUserRequest
+		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+			h.writeError(w, http.StatusBadRequest, "Invalid request body")
+			return
+		}
+		if req.Role != "" && types.RoleRank(req.Role) == 0 {
+			h.writeError(w, http.StatusBadRequest, "role must be one of admin, operator, viewer")
+			return
+		}
+		if err := h.configMgr.UpdateWebUser(username, req.Role, req.Password); err != nil {
+			h.writeError(w, http.StatusNotFound, "User not found")
+			return
+		}
+		// 角色或密码变化后，已登录的会话需要重新登录
+		h.endUserSessions(username)
+		logger.Info("Updated web user %s by %s", username, actor)
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"success": true,
+			"message": "User updated successfully",
+		})
+	case http.MethodDelete:
+		if err := h.configMgr.DeleteWebUser(username); err != nil {
+			h.writeError(w, http.StatusNotFound, "User not found")
+			return
+		}
+		h.endUserSessions(username)
+		logger.Info("Deleted web user %s by %s", username, actor)
+		w.WriteHeader(http.StatusNoContent)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
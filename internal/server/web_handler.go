@@ -4,42 +4,76 @@ import (
 	"crypto/rand"
 	"encoding/base64"
 	"encoding/json"
+	"errors"
 	"fmt"
 	"net/http"
 	"path/filepath"
 	"strings"
+	"sync"
 	"time"
 
+	"github.com/iBreaker/llm-gateway/internal/audit"
+	"github.com/iBreaker/llm-gateway/internal/canary"
 	"github.com/iBreaker/llm-gateway/internal/client"
 	"github.com/iBreaker/llm-gateway/internal/config"
+	"github.com/iBreaker/llm-gateway/internal/notify"
+	"github.com/iBreaker/llm-gateway/internal/quota"
+	"github.com/iBreaker/llm-gateway/internal/router"
+	"github.com/iBreaker/llm-gateway/internal/serviceaccount"
+	"github.com/iBreaker/llm-gateway/internal/stats"
 	"github.com/iBreaker/llm-gateway/internal/upstream"
 	"github.com/iBreaker/llm-gateway/pkg/logger"
 	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
 )
 
 // WebHandler 处理 Web 管理界面的请求
 type WebHandler struct {
-	configMgr   *config.ConfigManager
-	upstreamMgr *upstream.UpstreamManager
-	keyMgr      *client.GatewayKeyManager
-	oauthMgr    *upstream.OAuthManager
-	sessions    map[string]*Session // 简单的内存session存储
+	configMgr     *config.ConfigManager
+	upstreamMgr   *upstream.UpstreamManager
+	keyMgr        *client.GatewayKeyManager
+	oauthMgr      *upstream.OAuthManager
+	healthSvc     *upstream.HealthService
+	recorder      *stats.Recorder
+	dashboard     *stats.Aggregates
+	rollups       *stats.Rollups    // 按小时/天预先汇总的用量
+	autopilot     *router.Autopilot // 负载均衡策略及其自动切换
+	proxy         *ProxyHandler     // 读取并发、排队和响应缓存的状态，用于路由状态快照
+	quota         *quota.Service
+	audit         *audit.Log
+	notifier      *notify.Service
+	canaries      *canary.Runner
+	usageWAL      *stats.WAL                    // 未启用使用记录持久化时为nil
+	serviceTokens *serviceaccount.Issuer        // 服务账号令牌签发器，创建失败时为nil
+	reload        func() (*types.Config, error) // 重新加载配置文件并应用到运行中的组件
+	sessions      map[string]*Session           // 简单的内存session存储
+	sessionsMutex sync.RWMutex                  // 保护 sessions，登录、登出和鉴权并发访问
 }
 
 // Session 会话信息
 type Session struct {
 	Token     string
+	Username  string
+	Role      string // admin / operator / viewer
 	ExpiresAt time.Time
 	CreatedAt time.Time
+	Service   bool // 服务账号令牌（Username 为 client_id），不在 sessions 中保存
 }
 
 // NewWebHandler 创建 Web 处理器
-func NewWebHandler(configMgr *config.ConfigManager, upstreamMgr *upstream.UpstreamManager, keyMgr *client.GatewayKeyManager, oauthMgr *upstream.OAuthManager) *WebHandler {
+func NewWebHandler(configMgr *config.ConfigManager, upstreamMgr *upstream.UpstreamManager, keyMgr *client.GatewayKeyManager, oauthMgr *upstream.OAuthManager, healthSvc *upstream.HealthService, recorder *stats.Recorder, quotaSvc *quota.Service, auditLog *audit.Log, notifier *notify.Service, canaries *canary.Runner) *WebHandler {
 	return &WebHandler{
 		configMgr:   configMgr,
 		upstreamMgr: upstreamMgr,
 		keyMgr:      keyMgr,
 		oauthMgr:    oauthMgr,
+		healthSvc:   healthSvc,
+		recorder:    recorder,
+		dashboard:   stats.NewAggregates(recorder),
+		quota:       quotaSvc,
+		audit:       auditLog,
+		notifier:    notifier,
+		canaries:    canaries,
 		sessions:    make(map[string]*Session),
 	}
 }
@@ -47,29 +81,29 @@ func NewWebHandler(configMgr *config.ConfigManager, upstreamMgr *upstream.Upstre
 // ServeStatic 处理静态文件请求
 func (h *WebHandler) ServeStatic(w http.ResponseWriter, r *http.Request) {
 	path := r.URL.Path
-	
+
 	// 根路径根据认证状态返回不同页面
 	if path == "" || path == "/" {
-		if h.isAuthenticated(r) {
+		if h.currentSession(r) != nil {
 			path = "/static/html/index.html"  // 已认证用户看到管理页面
 		} else {
 			path = "/static/html/login.html"  // 未认证用户看到登录页面
 		}
 	}
-	
+
 	// 安全检查：只允许访问 /static/ 路径下的文件
 	if !strings.HasPrefix(path, "/static/") {
 		http.Error(w, "Forbidden", http.StatusForbidden)
 		return
 	}
-	
+
 	// 构建文件路径并进行安全清理
 	cleanPath := filepath.Clean(path)
 	if !strings.HasPrefix(cleanPath, "/static/") {
 		http.Error(w, "Forbidden", http.StatusForbidden)
 		return
 	}
-	
+
 	// 只允许特定文件扩展名
 	ext := filepath.Ext(cleanPath)
 	switch ext {
@@ -79,10 +113,10 @@ func (h *WebHandler) ServeStatic(w http.ResponseWriter, r *http.Request) {
 		http.Error(w, "Forbidden", http.StatusForbidden)
 		return
 	}
-	
+
 	// 构建完整文件路径
 	filePath := filepath.Join("web", cleanPath)
-	
+
 	// 确保最终路径仍在web目录内（防止路径穿越）
 	absWebPath, err := filepath.Abs("web")
 	if err != nil {
@@ -98,7 +132,7 @@ func (h *WebHandler) ServeStatic(w http.ResponseWriter, r *http.Request) {
 		http.Error(w, "Forbidden", http.StatusForbidden)
 		return
 	}
-	
+
 	// 设置Content-Type
 	switch ext {
 	case ".css":
@@ -118,12 +152,12 @@ func (h *WebHandler) ServeStatic(w http.ResponseWriter, r *http.Request) {
 	case ".ico":
 		w.Header().Set("Content-Type", "image/x-icon")
 	}
-	
+
 	// 设置安全头
 	w.Header().Set("X-Content-Type-Options", "nosniff")
 	w.Header().Set("X-Frame-Options", "DENY")
 	w.Header().Set("X-XSS-Protection", "1; mode=block")
-	
+
 	http.ServeFile(w, r, filePath)
 }
 
@@ -144,7 +178,7 @@ func (h *WebHandler) HandleAPIConfig(w http.ResponseWriter, r *http.Request) {
 		h.writeError(w, http.StatusInternalServerError, "Configuration not loaded")
 		return
 	}
-	
+
 	// 返回配置（隐藏敏感信息）
 	safeConfig := map[string]interface{}{
 		"server": config.Server,
@@ -156,7 +190,7 @@ func (h *WebHandler) HandleAPIConfig(w http.ResponseWriter, r *http.Request) {
 			"no_proxy":    config.Environment.NoProxy,
 		},
 	}
-	
+
 	h.writeJSON(w, http.StatusOK, safeConfig)
 }
 
@@ -174,7 +208,16 @@ func (h *WebHandler) HandleAPIUpstream(w http.ResponseWriter, r *http.Request) {
 
 func (h *WebHandler) handleListUpstream(w http.ResponseWriter, r *http.Request) {
 	accounts := h.configMgr.ListUpstreamAccounts()
-	
+	if orgID := r.URL.Query().Get("org_id"); orgID != "" {
+		owned := make([]*types.UpstreamAccount, 0, len(accounts))
+		for _, account := range accounts {
+			if account.OrgID == orgID {
+				owned = append(owned, account)
+			}
+		}
+		accounts = owned
+	}
+
 	// 计算统计信息
 	stats := map[string]interface{}{
 		"total":   len(accounts),
@@ -182,8 +225,9 @@ func (h *WebHandler) handleListUpstream(w http.ResponseWriter, r *http.Request)
 		"healthy": 0,
 		"by_provider": map[string]int{},
 		"by_type": map[string]int{},
+		"by_pool": map[string]int{}, // 每个账号池（提供商/账号池名称）的成员数
 	}
-	
+
 	// 转换为安全的响应格式（隐藏敏感信息）
 	safeAccounts := make([]map[string]interface{}, len(accounts))
 	for i, account := range accounts {
@@ -194,73 +238,131 @@ func (h *WebHandler) handleListUpstream(w http.ResponseWriter, r *http.Request)
 		if account.HealthStatus == "healthy" {
 			stats["healthy"] = stats["healthy"].(int) + 1
 		}
-		
+
 		// 按提供商统计
 		providerCounts := stats["by_provider"].(map[string]int)
 		providerCounts[string(account.Provider)]++
-		
+
 		// 按类型统计
 		typeCounts := stats["by_type"].(map[string]int)
 		typeCounts[string(account.Type)]++
-		
+		if pool := account.PoolKey(); pool != "" {
+			stats["by_pool"].(map[string]int)[pool]++
+		}
+
 		safeAccounts[i] = map[string]interface{}{
-			"id":            account.ID,
-			"name":          account.Name,
-			"provider":      account.Provider,
-			"type":          account.Type,
-			"status":        account.Status,
-			"health_status": account.HealthStatus,
-			"created_at":    account.CreatedAt,
-			"usage":         account.Usage, // 包含使用统计
+			"id":                account.ID,
+			"name":              account.Name,
+			"provider":          account.Provider,
+			"type":              account.Type,
+			"status":            account.Status,
+			"disabled_reason":   account.DisabledReason, // 自动停用的原因
+			"org_id":            account.OrgID,
+			"pool":              account.Pool,
+			"health_status":     account.HealthStatus,
+			"last_health_check": account.LastHealthCheck,
+			"health_latency_ms": account.HealthLatencyMs,
+			"health_error":      account.HealthError,
+			"api_version":       account.APIVersion,
+			"weight":            account.EffectiveWeight(),
+			"priority":          account.Priority,
+			"deployments":       account.Deployments,
+			"models":            account.Models, // OpenAI 兼容后端发现的模型
+			"rate_limit":        h.upstreamMgr.RateLimits().Get(account.ID, time.Now()), // 上游最近报告的剩余额度
+			"created_at":        account.CreatedAt,
+			"usage":             account.Usage, // 包含使用统计
+		}
+		if account.AWS != nil {
+			safeAccounts[i]["aws_region"] = account.AWS.Region // 只返回区域，不返回AWS密钥
 		}
 	}
-	
+
 	response := map[string]interface{}{
 		"data":  safeAccounts,
 		"stats": stats,
 	}
-	
+
 	h.writeJSON(w, http.StatusOK, response)
 }
 
 func (h *WebHandler) handleCreateUpstream(w http.ResponseWriter, r *http.Request) {
 	var req struct {
-		Name     string `json:"name"`
-		Provider string `json:"provider"`
-		Type     string `json:"type"`
-		APIKey   string `json:"api_key,omitempty"`
-		BaseURL  string `json:"base_url,omitempty"`
+		Name       string `json:"name"`
+		Provider   string `json:"provider"`
+		Type       string `json:"type"`
+		APIKey     string `json:"api_key,omitempty"`
+		BaseURL    string `json:"base_url,omitempty"`
+		APIVersion string `json:"api_version,omitempty"` // 固定的上游API版本
+		Weight     *int   `json:"weight,omitempty"`      // 路由权重，不设置时为默认值，0表示不分配流量
+		Priority   int    `json:"priority,omitempty"`    // 路由优先级，数字越小越优先
+		SkipVerify bool   `json:"skip_verify,omitempty"` // 跳过保存前的凭证验证
+		OrgID      string `json:"org_id,omitempty"`      // 所属组织，为空时所有Key共用
+		Pool       string `json:"pool,omitempty"`        // 账号池名称，同一提供商同名账号池的账号轮换使用
+
+		Deployments map[string]string     `json:"deployments,omitempty"` // Azure OpenAI 模型名到部署名的映射，Bedrock 为模型ID
+		AWS         *types.AWSCredentials `json:"aws,omitempty"`         // Bedrock 的AWS凭证和区域
 	}
-	
+
 	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
 		h.writeError(w, http.StatusBadRequest, "Invalid request body")
 		return
 	}
-	
+
 	// 验证必需字段
 	if req.Name == "" || req.Provider == "" || req.Type == "" {
 		h.writeError(w, http.StatusBadRequest, "Missing required fields")
 		return
 	}
-	
+	if message := h.validateAPIVersion(types.Provider(req.Provider), req.APIVersion); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+	if message := validateRouting(req.Weight, req.Priority); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+	if message := validateDeployments(types.Provider(req.Provider), req.BaseURL, req.Deployments); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+	if message := validateAWSCredentials(types.Provider(req.Provider), req.AWS); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+	if message := h.validateOrgID(req.OrgID); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+
 	// 创建上游账号
 	account := &types.UpstreamAccount{
 		ID:            h.generateID("upstream"),
 		Name:          req.Name,
 		Provider:      types.Provider(req.Provider),
 		Type:          types.UpstreamType(req.Type),
+		APIVersion:    req.APIVersion,
+		Weight:        req.Weight,
+		Priority:      req.Priority,
+		Deployments:   req.Deployments,
+		AWS:           req.AWS,
+		OrgID:         req.OrgID,
+		Pool:          strings.TrimSpace(req.Pool),
 		Status:        "active",
 		HealthStatus:  "unknown",
 		CreatedAt:     time.Now(),
 	}
-	
-	// Set base URL if provided
+
+	// Set base URL if provided (Qwen OAuth accounts use it as resource URL)
 	if req.BaseURL != "" {
-		account.ResourceURL = req.BaseURL
+		if account.Type == types.UpstreamTypeOAuth {
+			account.ResourceURL = req.BaseURL
+		} else {
+			account.BaseURL = req.BaseURL
+		}
 	}
-	
+
 	if req.Type == "api-key" {
-		if req.APIKey == "" {
+		if req.APIKey == "" && account.Provider != types.ProviderBedrock && account.Provider != types.ProviderOpenAICompatible {
 			h.writeError(w, http.StatusBadRequest, "API key is required for api-key type")
 			return
 		}
@@ -274,40 +376,68 @@ func (h *WebHandler) handleCreateUpstream(w http.ResponseWriter, r *http.Request
 		// OAuth uses predefined client credentials, no additional setup needed
 		logger.Info("Creating OAuth account for provider: %s", req.Provider)
 	}
-	
+
+	verification, ok := h.verifyUpstreamCredentials(w, account, req.SkipVerify)
+	if !ok {
+		return
+	}
+
 	// 通过UpstreamManager添加账号（包含业务逻辑初始化）
 	if err := h.upstreamMgr.AddAccount(account); err != nil {
 		logger.Error("Failed to create upstream account: %v", err)
 		h.writeError(w, http.StatusInternalServerError, "Failed to create upstream account")
 		return
 	}
-	
+
 	logger.Info("Created upstream account: %s (%s)", account.Name, account.ID)
-	h.writeJSON(w, http.StatusCreated, map[string]string{"id": account.ID})
+	response := map[string]interface{}{"id": account.ID}
+	addVerification(response, verification)
+	h.writeJSON(w, http.StatusCreated, response)
 }
 
 // API Delete Upstream Account
 func (h *WebHandler) HandleAPIUpstreamDelete(w http.ResponseWriter, r *http.Request) {
-	if r.Method != http.MethodDelete {
+	// /api/v1/upstream/{id}/health
+	if pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/"); len(pathParts) == 5 && pathParts[4] == "health" {
+		h.handleUpstreamHealth(w, r, pathParts[3])
+		return
+	}
+	// /api/v1/upstream/{id}/breaker-history
+	if pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/"); len(pathParts) == 5 && pathParts[4] == "breaker-history" {
+		h.handleBreakerHistory(w, r, pathParts[3])
+		return
+	}
+	// /api/v1/upstream/{id}/circuit-breaker
+	if pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/"); len(pathParts) == 5 && pathParts[4] == "circuit-breaker" {
+		h.handleUpstreamCircuitBreaker(w, r, pathParts[3])
+		return
+	}
+
+	if r.Method != http.MethodDelete && r.Method != http.MethodPut {
 		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
 		return
 	}
-	
+
 	// 从URL路径中提取ID
 	pathParts := strings.Split(strings.Trim(r.URL.Path, "/"), "/")
 	if len(pathParts) < 4 {
 		h.writeError(w, http.StatusBadRequest, "Invalid upstream ID")
 		return
 	}
-	
+
 	upstreamID := pathParts[3] // /api/v1/upstream/{id}
-	
+
+	if r.Method == http.MethodPut {
+		h.handleUpdateUpstream(w, r, upstreamID)
+		return
+	}
+
 	if err := h.configMgr.DeleteUpstreamAccount(upstreamID); err != nil {
 		logger.Error("Failed to delete upstream account %s: %v", upstreamID, err)
 		h.writeError(w, http.StatusInternalServerError, "Failed to delete upstream account")
 		return
 	}
-	
+
 	logger.Info("Deleted upstream account: %s", upstreamID)
 	w.WriteHeader(http.StatusNoContent)
 }
@@ -326,16 +456,36 @@ func (h *WebHandler) HandleAPIKeys(w http.ResponseWriter, r *http.Request) {
 
 func (h *WebHandler) handleListAPIKeys(w http.ResponseWriter, r *http.Request) {
 	keys := h.configMgr.ListGatewayKeys()
-	
+	if orgID := r.URL.Query().Get("org_id"); orgID != "" {
+		owned := make([]*types.GatewayAPIKey, 0, len(keys))
+		for _, key := range keys {
+			if key.OrgID == orgID {
+				owned = append(owned, key)
+			}
+		}
+		keys = owned
+	}
+	// status=pending 只列出等待批准的Key
+	if status := r.URL.Query().Get("status"); status != "" {
+		matched := make([]*types.GatewayAPIKey, 0, len(keys))
+		for _, key := range keys {
+			if (status == "pending" && key.PendingApproval) || (status != "pending" && key.Status == status) {
+				matched = append(matched, key)
+			}
+		}
+		keys = matched
+	}
+
 	// 计算统计信息
 	stats := map[string]interface{}{
 		"total":          len(keys),
 		"active":         0,
+		"pending":        0,
 		"total_requests": 0,
 		"by_permissions": map[string]int{},
 		"recent_usage":   0,
 	}
-	
+
 	// 转换为安全的响应格式（隐藏密钥值）
 	safeKeys := make([]map[string]interface{}, len(keys))
 	for i, key := range keys {
@@ -343,37 +493,52 @@ func (h *WebHandler) handleListAPIKeys(w http.ResponseWriter, r *http.Request) {
 		if key.Status == "active" {
 			stats["active"] = stats["active"].(int) + 1
 		}
-		
+		if key.PendingApproval {
+			stats["pending"] = stats["pending"].(int) + 1
+		}
+
 		if key.Usage != nil {
 			stats["total_requests"] = stats["total_requests"].(int) + int(key.Usage.TotalRequests)
-			
+
 			// 计算最近使用（24小时内）
 			if key.Usage.LastUsedAt.After(time.Now().Add(-24 * time.Hour)) {
 				stats["recent_usage"] = stats["recent_usage"].(int) + 1
 			}
 		}
-		
+
 		// 按权限统计
 		permCounts := stats["by_permissions"].(map[string]int)
 		for _, perm := range key.Permissions {
 			permCounts[string(perm)]++
 		}
-		
+
 		safeKeys[i] = map[string]interface{}{
-			"id":            key.ID,
-			"name":          key.Name,
-			"permissions":   key.Permissions,
-			"status":        key.Status,
-			"created_at":    key.CreatedAt,
-			"usage":         key.Usage,
+			"id":               key.ID,
+			"name":             key.Name,
+			"permissions":      key.Permissions,
+			"scopes":           key.Scopes,
+			"apps":             key.Apps,
+			"tags":             key.Tags,
+			"org_id":           key.OrgID,
+			"status":           key.Status,
+			"pending_approval": key.PendingApproval,
+			"sandbox":          key.Sandbox,
+			"max_timeout_ms":   key.MaxTimeoutMs,
+			"max_priority":     key.MaxPriority,
+			"allowed_ips":      key.AllowedIPs,
+			"allowed_origins":  key.AllowedOrigins,
+			"created_by":       key.CreatedBy,
+			"approved_by":      key.ApprovedBy,
+			"created_at":       key.CreatedAt,
+			"usage":            key.Usage,
 		}
 	}
-	
+
 	response := map[string]interface{}{
 		"data":  safeKeys,
 		"stats": stats,
 	}
-	
+
 	h.writeJSON(w, http.StatusOK, response)
 }
 
@@ -381,41 +546,82 @@ func (h *WebHandler) handleCreateAPIKey(w http.ResponseWriter, r *http.Request)
 	var req struct {
 		Name        string   `json:"name"`
 		Permissions []string `json:"permissions"`
+		Scopes      []string `json:"scopes"`
+		OrgID       string   `json:"org_id"`  // 所属组织
+		Sandbox     bool     `json:"sandbox"` // 沙箱Key，请求由模拟响应器应答
 	}
-	
+
 	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
 		h.writeError(w, http.StatusBadRequest, "Invalid request body")
 		return
 	}
-	
+
 	// 验证必需字段
 	if req.Name == "" {
 		h.writeError(w, http.StatusBadRequest, "Name is required")
 		return
 	}
-	
+
+	// 非 admin 自助创建时检查设置；组织、作用域和权限决定Key能使用哪些上游账号，只有 admin 可以设置
+	creator := h.sessionUser(r)
+	policy, status, message := h.checkSelfServeKey(r)
+	if status != 0 {
+		h.writeError(w, status, message)
+		return
+	}
+	if policy.selfServe && (req.OrgID != "" || len(req.Scopes) > 0 || len(req.Permissions) > 0) {
+		h.writeError(w, http.StatusForbidden, "Only admins can set org_id, scopes or permissions")
+		return
+	}
+
 	if len(req.Permissions) == 0 {
 		req.Permissions = []string{"read", "write"}
 	}
-	
+
+	if message := validateScopes(req.Scopes); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+	if message := h.validateOrgID(req.OrgID); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+
 	// 将字符串权限转换为types.Permission类型
 	perms := make([]types.Permission, len(req.Permissions))
 	for i, p := range req.Permissions {
 		perms[i] = types.Permission(p)
 	}
-	
-	// 生成新的 API 密钥
-	key, plainKey, err := h.keyMgr.CreateKey(req.Name, perms)
+
+	// 生成新的 API 密钥；待审批的Key保存时就是停用状态，数量上限与保存在同一次配置修改中检查
+	key, plainKey, err := h.keyMgr.CreateKeyWithOptions(req.Name, perms, client.KeyOptions{
+		Scopes:          req.Scopes,
+		OrgID:           req.OrgID,
+		CreatedBy:       creator,
+		Sandbox:         req.Sandbox,
+		PendingApproval: policy.pending,
+		MaxPerCreator:   policy.limit,
+	})
+	if errors.Is(err, config.ErrKeyLimitReached) {
+		h.writeError(w, http.StatusForbidden, fmt.Sprintf("Key limit reached: each user may create at most %d keys", policy.limit))
+		return
+	}
 	if err != nil {
 		logger.Error("Failed to generate API key: %v", err)
 		h.writeError(w, http.StatusInternalServerError, "Failed to generate API key")
 		return
 	}
-	
-	logger.Info("Generated new API key: %s (%s)", key.Name, key.ID)
-	h.writeJSON(w, http.StatusCreated, map[string]string{
-		"id":  key.ID,
-		"key": plainKey, // 只在创建时返回原始密钥
+
+	if policy.pending {
+		logger.Info("Generated new API key pending approval: %s (%s) by %s", key.Name, key.ID, creator)
+		h.notifyPendingKey(key, creator)
+	} else {
+		logger.Info("Generated new API key: %s (%s, sandbox=%t)", key.Name, key.ID, req.Sandbox)
+	}
+	h.writeJSON(w, http.StatusCreated, map[string]interface{}{
+		"id":               key.ID,
+		"key":              plainKey, // 只在创建时返回原始密钥
+		"pending_approval": policy.pending,
 	})
 }
 
@@ -427,9 +633,9 @@ func (h *WebHandler) HandleAPIKeyActions(w http.ResponseWriter, r *http.Request)
 		h.writeError(w, http.StatusBadRequest, "Invalid API key action path")
 		return
 	}
-	
+
 	keyID := pathParts[3] // /api/v1/apikeys/{id}
-	
+
 	// 检查是否有子路径
 	if len(pathParts) == 4 {
 		// /api/v1/apikeys/{id} - Delete API Key
@@ -437,6 +643,39 @@ func (h *WebHandler) HandleAPIKeyActions(w http.ResponseWriter, r *http.Request)
 	} else if len(pathParts) == 5 && pathParts[4] == "model-routes" {
 		// /api/v1/apikeys/{id}/model-routes - Model Routes operations
 		h.handleAPIKeyModelRoutes(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "quota" {
+		// /api/v1/apikeys/{id}/quota - Quota operations
+		h.handleAPIKeyQuota(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "scopes" {
+		// /api/v1/apikeys/{id}/scopes - Scope operations
+		h.handleAPIKeyScopes(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "apps" {
+		// /api/v1/apikeys/{id}/apps - Registered client app operations
+		h.handleAPIKeyApps(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "network" {
+		// /api/v1/apikeys/{id}/network - Client IP and origin restrictions
+		h.handleAPIKeyNetwork(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "tags" {
+		// /api/v1/apikeys/{id}/tags - Tag operations
+		h.handleAPIKeyTags(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "sandbox" {
+		// /api/v1/apikeys/{id}/sandbox - Sandbox mode
+		h.handleAPIKeySandbox(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "timeout" {
+		// /api/v1/apikeys/{id}/timeout - Maximum client-requested upstream timeout
+		h.handleAPIKeyTimeout(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "priority" {
+		// /api/v1/apikeys/{id}/priority - Highest client-requested queue priority
+		h.handleAPIKeyPriority(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "org" {
+		// /api/v1/apikeys/{id}/org - Organization ownership
+		h.handleAPIKeyOrg(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "heatmap" {
+		// /api/v1/apikeys/{id}/heatmap - Usage heatmap
+		h.handleAPIKeyHeatmap(w, r, keyID)
+	} else if len(pathParts) == 5 && pathParts[4] == "approve" {
+		// /api/v1/apikeys/{id}/approve - Approve a self-service key
+		h.handleAPIKeyApprove(w, r, keyID)
 	} else {
 		h.writeError(w, http.StatusNotFound, "API endpoint not found")
 	}
@@ -447,13 +686,13 @@ func (h *WebHandler) handleAPIKeyDelete(w http.ResponseWriter, r *http.Request,
 		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
 		return
 	}
-	
+
 	if err := h.configMgr.DeleteGatewayKey(keyID); err != nil {
 		logger.Error("Failed to delete API key %s: %v", keyID, err)
 		h.writeError(w, http.StatusInternalServerError, "Failed to delete API key")
 		return
 	}
-	
+
 	logger.Info("Deleted API key: %s", keyID)
 	w.WriteHeader(http.StatusNoContent)
 }
@@ -476,7 +715,7 @@ func (h *WebHandler) getAPIKeyModelRoutes(w http.ResponseWriter, r *http.Request
 		h.writeError(w, http.StatusNotFound, "API key not found")
 		return
 	}
-	
+
 	// 返回模型路由配置
 	response := map[string]interface{}{
 		"key_id":           keyID,
@@ -485,13 +724,13 @@ func (h *WebHandler) getAPIKeyModelRoutes(w http.ResponseWriter, r *http.Request
 		"default_behavior": "passthrough",
 		"enable_logging":   true,
 	}
-	
+
 	if gatewayKey.ModelRoutes != nil {
 		response["routes"] = gatewayKey.ModelRoutes.Routes
 		response["default_behavior"] = gatewayKey.ModelRoutes.DefaultBehavior
 		response["enable_logging"] = gatewayKey.ModelRoutes.EnableLogging
 	}
-	
+
 	h.writeJSON(w, http.StatusOK, response)
 }
 
@@ -499,40 +738,40 @@ func (h *WebHandler) updateAPIKeyModelRoutes(w http.ResponseWriter, r *http.Requ
 	// 解析请求体
 	var req struct {
 		Routes          []types.ModelRoute `json:"routes"`
-		DefaultBehavior string            `json:"default_behavior"`
-		EnableLogging   bool              `json:"enable_logging"`
+		DefaultBehavior string             `json:"default_behavior"`
+		EnableLogging   bool               `json:"enable_logging"`
 	}
-	
+
 	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
 		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
 		return
 	}
-	
+
 	// 创建模型路由配置
 	modelRoutes := &types.ModelRouteConfig{
 		Routes:          req.Routes,
 		DefaultBehavior: req.DefaultBehavior,
 		EnableLogging:   req.EnableLogging,
 	}
-	
+
 	// 验证配置
 	if err := modelRoutes.Validate(); err != nil {
 		h.writeError(w, http.StatusBadRequest, "Invalid route configuration: "+err.Error())
 		return
 	}
-	
+
 	// 更新Gateway Key的模型路由配置
 	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
 		key.ModelRoutes = modelRoutes
 		return nil
 	})
-	
+
 	if err != nil {
 		logger.Error("Failed to update model routes for API key %s: %v", keyID, err)
 		h.writeError(w, http.StatusInternalServerError, "Failed to update model routes")
 		return
 	}
-	
+
 	logger.Info("Updated model routes for API key: %s", keyID)
 	h.writeJSON(w, http.StatusOK, map[string]interface{}{
 		"success": true,
@@ -540,6 +779,507 @@ func (h *WebHandler) updateAPIKeyModelRoutes(w http.ResponseWriter, r *http.Requ
 	})
 }
 
+func (h *WebHandler) handleAPIKeyQuota(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		h.getAPIKeyQuota(w, r, keyID)
+	case http.MethodPut:
+		h.updateAPIKeyQuota(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) getAPIKeyQuota(w http.ResponseWriter, r *http.Request, keyID string) {
+	gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+	if err != nil {
+		h.writeError(w, http.StatusNotFound, "API key not found")
+		return
+	}
+
+	response := map[string]interface{}{
+		"key_id":   keyID,
+		"key_name": gatewayKey.Name,
+		"quota":    gatewayKey.Quota,
+	}
+	if h.quota != nil {
+		response["usage"] = h.quota.Usage(keyID, time.Now())
+	}
+
+	h.writeJSON(w, http.StatusOK, response)
+}
+
+func (h *WebHandler) updateAPIKeyQuota(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req types.QuotaConfig
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+
+	if req.DailyTokens < 0 || req.MonthlyTokens < 0 || req.DailyCostUSD < 0 || req.MonthlyCostUSD < 0 {
+		h.writeError(w, http.StatusBadRequest, "Quota limits must not be negative")
+		return
+	}
+
+	// 全部为0表示取消配额
+	var quotaCfg *types.QuotaConfig
+	if req != (types.QuotaConfig{}) {
+		quotaCfg = &req
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.Quota = quotaCfg
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update quota for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update quota")
+		return
+	}
+
+	logger.Info("Updated quota for API key: %s", keyID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Quota updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeyScopes(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		scopes := gatewayKey.Scopes
+		if scopes == nil {
+			scopes = []string{}
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":   keyID,
+			"key_name": gatewayKey.Name,
+			"scopes":   scopes,
+		})
+	case http.MethodPut:
+		h.updateAPIKeyScopes(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeyScopes(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		Scopes []string `json:"scopes"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+
+	if message := validateScopes(req.Scopes); message != "" {
+		h.writeError(w, http.StatusBadRequest, message)
+		return
+	}
+
+	// 空列表表示取消所有作用域限制
+	var scopes []string
+	if len(req.Scopes) > 0 {
+		scopes = req.Scopes
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.Scopes = scopes
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update scopes for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update scopes")
+		return
+	}
+
+	logger.Info("Updated scopes for API key: %s", keyID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Scopes updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeyApps(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		apps := gatewayKey.Apps
+		if apps == nil {
+			apps = []string{}
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":   keyID,
+			"key_name": gatewayKey.Name,
+			"apps":     apps,
+		})
+	case http.MethodPut:
+		h.updateAPIKeyApps(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeyApps(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		Apps []string `json:"apps"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+
+	for _, app := range req.Apps {
+		if !types.ValidClientAppName(app) {
+			h.writeError(w, http.StatusBadRequest, fmt.Sprintf("Invalid app name %q", app))
+			return
+		}
+	}
+
+	// 空列表表示不再校验应用
+	var apps []string
+	if len(req.Apps) > 0 {
+		apps = req.Apps
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.Apps = apps
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update apps for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update apps")
+		return
+	}
+
+	logger.Info("Updated registered apps for API key: %s", keyID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Apps updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeyNetwork(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		allowedIPs := gatewayKey.AllowedIPs
+		if allowedIPs == nil {
+			allowedIPs = []string{}
+		}
+		allowedOrigins := gatewayKey.AllowedOrigins
+		if allowedOrigins == nil {
+			allowedOrigins = []string{}
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":          keyID,
+			"key_name":        gatewayKey.Name,
+			"allowed_ips":     allowedIPs,
+			"allowed_origins": allowedOrigins,
+		})
+	case http.MethodPut:
+		h.updateAPIKeyNetwork(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeyNetwork(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		AllowedIPs     []string `json:"allowed_ips"`
+		AllowedOrigins []string `json:"allowed_origins"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+
+	// 空列表表示不限制
+	var allowedIPs, allowedOrigins []string
+	for _, rule := range req.AllowedIPs {
+		network, ok := types.ParseIPRule(strings.TrimSpace(rule))
+		if !ok {
+			h.writeError(w, http.StatusBadRequest, fmt.Sprintf("Invalid IP address or CIDR %q", rule))
+			return
+		}
+		allowedIPs = append(allowedIPs, network.String())
+	}
+	for _, origin := range req.AllowedOrigins {
+		normalized, ok := types.NormalizeOrigin(origin)
+		if !ok {
+			h.writeError(w, http.StatusBadRequest, fmt.Sprintf("Invalid origin %q, expected https://host[:port]", origin))
+			return
+		}
+		allowedOrigins = append(allowedOrigins, normalized)
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.AllowedIPs = allowedIPs
+		key.AllowedOrigins = allowedOrigins
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update network restrictions for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update network restrictions")
+		return
+	}
+
+	logger.Info("Updated network restrictions for API key: %s", keyID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Network restrictions updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeyTags(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		tags := gatewayKey.Tags
+		if tags == nil {
+			tags = []string{}
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":   keyID,
+			"key_name": gatewayKey.Name,
+			"tags":     tags,
+		})
+	case http.MethodPut:
+		h.updateAPIKeyTags(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeyTags(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		Tags []string `json:"tags"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+
+	for _, tag := range req.Tags {
+		if tag == "" {
+			h.writeError(w, http.StatusBadRequest, "Tags must not be empty")
+			return
+		}
+	}
+
+	var tags []string
+	if len(req.Tags) > 0 {
+		tags = req.Tags
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.Tags = tags
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update tags for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update tags")
+		return
+	}
+
+	logger.Info("Updated tags for API key: %s", keyID)
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Tags updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeySandbox(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":   keyID,
+			"key_name": gatewayKey.Name,
+			"sandbox":  gatewayKey.Sandbox,
+		})
+	case http.MethodPut:
+		h.updateAPIKeySandbox(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeySandbox(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		Sandbox *bool `json:"sandbox"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+	if req.Sandbox == nil {
+		h.writeError(w, http.StatusBadRequest, "sandbox is required")
+		return
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.Sandbox = *req.Sandbox
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update sandbox mode for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update sandbox mode")
+		return
+	}
+
+	logger.Info("Set sandbox mode for API key %s to %t by %s", keyID, *req.Sandbox, h.sessionUser(r))
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Sandbox mode updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeyTimeout(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":         keyID,
+			"key_name":       gatewayKey.Name,
+			"max_timeout_ms": gatewayKey.MaxTimeoutMs,
+		})
+	case http.MethodPut:
+		h.updateAPIKeyTimeout(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeyTimeout(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		MaxTimeoutMs *int `json:"max_timeout_ms"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+	if req.MaxTimeoutMs == nil {
+		h.writeError(w, http.StatusBadRequest, "max_timeout_ms is required")
+		return
+	}
+	if *req.MaxTimeoutMs < 0 {
+		h.writeError(w, http.StatusBadRequest, "max_timeout_ms must not be negative")
+		return
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.MaxTimeoutMs = *req.MaxTimeoutMs
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update max timeout for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update max timeout")
+		return
+	}
+
+	logger.Info("Set max timeout for API key %s to %d ms by %s", keyID, *req.MaxTimeoutMs, h.sessionUser(r))
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Max timeout updated successfully",
+	})
+}
+
+func (h *WebHandler) handleAPIKeyPriority(w http.ResponseWriter, r *http.Request, keyID string) {
+	switch r.Method {
+	case http.MethodGet:
+		gatewayKey, err := h.configMgr.GetGatewayKey(keyID)
+		if err != nil {
+			h.writeError(w, http.StatusNotFound, "API key not found")
+			return
+		}
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"key_id":       keyID,
+			"key_name":     gatewayKey.Name,
+			"max_priority": gatewayKey.MaxPriority,
+		})
+	case http.MethodPut:
+		h.updateAPIKeyPriority(w, r, keyID)
+	default:
+		h.writeError(w, http.StatusMethodNotAllowed, "Method not allowed")
+	}
+}
+
+func (h *WebHandler) updateAPIKeyPriority(w http.ResponseWriter, r *http.Request, keyID string) {
+	var req struct {
+		MaxPriority *string `json:"max_priority"`
+	}
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		h.writeError(w, http.StatusBadRequest, "Invalid JSON format")
+		return
+	}
+	if req.MaxPriority == nil {
+		h.writeError(w, http.StatusBadRequest, "max_priority is required")
+		return
+	}
+	// 空字符串恢复默认（最高 normal）
+	var maxPriority types.RequestPriority
+	if strings.TrimSpace(*req.MaxPriority) != "" {
+		priority, ok := types.ParseRequestPriority(*req.MaxPriority)
+		if !ok {
+			h.writeError(w, http.StatusBadRequest, "max_priority must be critical, high, normal or low")
+			return
+		}
+		maxPriority = priority
+	}
+
+	err := h.configMgr.UpdateGatewayKey(keyID, func(key *types.GatewayAPIKey) error {
+		key.MaxPriority = maxPriority
+		return nil
+	})
+	if err != nil {
+		logger.Error("Failed to update max priority for API key %s: %v", keyID, err)
+		h.writeError(w, http.StatusInternalServerError, "Failed to update max priority")
+		return
+	}
+
+	logger.Info("Set max priority for API key %s to %q by %s", keyID, maxPriority, h.sessionUser(r))
+	h.writeJSON(w, http.StatusOK, map[string]interface{}{
+		"success": true,
+		"message": "Max priority updated successfully",
+	})
+}
+
+// validateScopes 校验作用域格式，返回错误信息，全部有效时返回空字符串
+func validateScopes(scopes []string) string {
+	for _, scope := range scopes {
+		if _, _, ok := types.ParseScope(scope); !ok {
+			return fmt.Sprintf("Invalid scope %q (expected provider:, model: or endpoint: followed by a value, with '*' only at the end)", scope)
+		}
+	}
+	return ""
+}
+
 // 辅助方法
 func (h *WebHandler) writeJSON(w http.ResponseWriter, status int, data interface{}) {
 	w.Header().Set("Content-Type", "application/json")
@@ -609,7 +1349,7 @@ func (h *WebHandler) HandleOAuthStart(w http.ResponseWriter, r *http.Request) {
 		}
 		// 构造完整的授权链接，包含user_code参数
 		fullVerificationURI := fmt.Sprintf("https://chat.qwen.ai/authorize?user_code=%s&client=llm-gateway", result.UserCode)
-		
+
 		h.writeJSON(w, http.StatusOK, map[string]interface{}{
 			"flow_type":         "device_code",
 			"device_code":       result.DeviceCode,
@@ -717,6 +1457,7 @@ func (h *WebHandler) HandleLogin(w http.ResponseWriter, r *http.Request) {
 	}
 
 	var req struct {
+		Username string `json:"username"` // 为空时登录内置的 admin 用户
 		Password string `json:"password"`
 	}
 
@@ -725,18 +1466,16 @@ func (h *WebHandler) HandleLogin(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	// 获取配置
-	config, err := h.configMgr.Load()
-	if err != nil {
-		h.writeError(w, http.StatusInternalServerError, "Failed to load configuration")
-		return
-	}
-
-	// 验证密码
-	if req.Password != config.Server.Web.Password {
+	// 验证用户名和密码
+	role, ok := h.configMgr.AuthenticateWebUser(req.Username, req.Password)
+	if !ok {
 		h.writeError(w, http.StatusUnauthorized, "Invalid password")
 		return
 	}
+	username := req.Username
+	if username == "" {
+		username = config.BuiltinAdminUser
+	}
 
 	// 生成会话token
 	token, err := h.generateSessionToken()
@@ -748,11 +1487,15 @@ func (h *WebHandler) HandleLogin(w http.ResponseWriter, r *http.Request) {
 	// 创建会话
 	session := &Session{
 		Token:     token,
+		Username:  username,
+		Role:      role,
 		ExpiresAt: time.Now().Add(24 * time.Hour), // 24小时过期
 		CreatedAt: time.Now(),
 	}
 
+	h.sessionsMutex.Lock()
 	h.sessions[token] = session
+	h.sessionsMutex.Unlock()
 
 	// 设置cookie
 	http.SetCookie(w, &http.Cookie{
@@ -765,9 +1508,11 @@ func (h *WebHandler) HandleLogin(w http.ResponseWriter, r *http.Request) {
 	})
 
 	h.writeJSON(w, http.StatusOK, map[string]interface{}{
-		"success": true,
-		"message": "Login successful",
-		"token":   token,
+		"success":  true,
+		"message":  "Login successful",
+		"token":    token,
+		"username": username,
+		"role":     role,
 	})
 }
 
@@ -782,7 +1527,9 @@ func (h *WebHandler) HandleLogout(w http.ResponseWriter, r *http.Request) {
 	token := h.getTokenFromRequest(r)
 	if token != "" {
 		// 删除会话
+		h.sessionsMutex.Lock()
 		delete(h.sessions, token)
+		h.sessionsMutex.Unlock()
 	}
 
 	// 清除cookie
@@ -808,6 +1555,7 @@ func (h *WebHandler) HandleChangePassword(w http.ResponseWriter, r *http.Request
 	}
 
 	var req struct {
+		Username    string `json:"username"` // 为空时修改内置 admin 用户的密码
 		OldPassword string `json:"old_password"`
 		NewPassword string `json:"new_password"`
 	}
@@ -822,6 +1570,24 @@ func (h *WebHandler) HandleChangePassword(w http.ResponseWriter, r *http.Request
 		return
 	}
 
+	// 其他Web用户的密码以加盐哈希保存在 server.web.users 中
+	if req.Username != "" && req.Username != config.BuiltinAdminUser {
+		if _, ok := h.configMgr.AuthenticateWebUser(req.Username, req.OldPassword); !ok {
+			h.writeError(w, http.StatusUnauthorized, "Invalid old password")
+			return
+		}
+		if err := h.configMgr.UpdateWebUser(req.Username, "", req.NewPassword); err != nil {
+			h.writeError(w, http.StatusInternalServerError, "Failed to save configuration")
+			return
+		}
+		logger.Info("Web password changed for user %s", req.Username)
+		h.writeJSON(w, http.StatusOK, map[string]interface{}{
+			"success": true,
+			"message": "Password changed successfully",
+		})
+		return
+	}
+
 	// 获取配置
 	config, err := h.configMgr.Load()
 	if err != nil {
@@ -830,14 +1596,15 @@ func (h *WebHandler) HandleChangePassword(w http.ResponseWriter, r *http.Request
 	}
 
 	// 验证旧密码
-	if req.OldPassword != config.Server.Web.Password {
+	if !utils.SecureEqual(req.OldPassword, config.Server.Web.Password) {
 		h.writeError(w, http.StatusUnauthorized, "Invalid old password")
 		return
 	}
 
-	// 更新密码
-	config.Server.Web.Password = req.NewPassword
-	if err := h.configMgr.Save(config); err != nil {
+	// 更新密码（在副本上修改，已取得的配置不变）
+	next := *config
+	next.Server.Web.Password = req.NewPassword
+	if err := h.configMgr.Save(&next); err != nil {
 		h.writeError(w, http.StatusInternalServerError, "Failed to save configuration")
 		return
 	}
@@ -875,35 +1642,108 @@ func (h *WebHandler) getTokenFromRequest(r *http.Request) string {
 	return ""
 }
 
-// isAuthenticated 检查是否已认证
-func (h *WebHandler) isAuthenticated(r *http.Request) bool {
+// currentSession 返回请求对应的有效会话，未登录或已过期时返回nil
+func (h *WebHandler) currentSession(r *http.Request) *Session {
 	token := h.getTokenFromRequest(r)
 	if token == "" {
-		return false
+		return nil
+	}
+	if serviceaccount.IsToken(token) {
+		return h.serviceSession(token)
 	}
 
+	h.sessionsMutex.RLock()
 	session, exists := h.sessions[token]
+	h.sessionsMutex.RUnlock()
 	if !exists {
-		return false
+		return nil
 	}
 
 	// 检查是否过期
 	if time.Now().After(session.ExpiresAt) {
+		h.sessionsMutex.Lock()
 		delete(h.sessions, token)
-		return false
+		h.sessionsMutex.Unlock()
+		return nil
 	}
 
-	return true
+	return session
+}
+
+// sessionUser 获取当前Web会话对应的用户标识
+func (h *WebHandler) sessionUser(r *http.Request) string {
+	if session := h.currentSession(r); session != nil {
+		if session.Service {
+			return "service:" + session.Username
+		}
+		return "web:" + session.Username
+	}
+	return "web:" + config.BuiltinAdminUser
 }
 
-// requireAuth 认证中间件
+// endUserSessions 删除用户的所有会话（用户被删除或角色变化后需要重新登录）
+func (h *WebHandler) endUserSessions(username string) {
+	h.sessionsMutex.Lock()
+	defer h.sessionsMutex.Unlock()
+
+	for token, session := range h.sessions {
+		if session.Username == username {
+			delete(h.sessions, token)
+		}
+	}
+}
+
+// requireAuth 认证中间件，任何角色登录后都可以访问
 func (h *WebHandler) requireAuth(handler http.HandlerFunc) http.HandlerFunc {
+	return h.requireRole(readWrite(types.RoleViewer, types.RoleViewer), handler)
+}
+
+// accessRule 按请求决定需要的最低角色
+type accessRule func(r *http.Request) string
+
+// readWrite 查询（GET/HEAD）需要 read 角色，其他方法需要 write 角色
+func readWrite(read, write string) accessRule {
+	return func(r *http.Request) string {
+		if r.Method == http.MethodGet || r.Method == http.MethodHead {
+			return read
+		}
+		return write
+	}
+}
+
+// requireRole 认证并检查角色：未登录返回401，角色权限不足返回403
+func (h *WebHandler) requireRole(rule accessRule, handler http.HandlerFunc) http.HandlerFunc {
 	return func(w http.ResponseWriter, r *http.Request) {
-		if !h.isAuthenticated(r) {
+		session := h.currentSession(r)
+		if session == nil {
 			h.writeError(w, http.StatusUnauthorized, "Authentication required")
 			return
 		}
+		if required := rule(r); types.RoleRank(session.Role) < types.RoleRank(required) {
+			h.writeError(w, http.StatusForbidden, fmt.Sprintf("This action requires the %s role", required))
+			return
+		}
 		handler(w, r)
 	}
 }
 
+// upstreamAccess /api/v1/upstream/{id}/... 的权限：探测（health）和手动重置/打开熔断器只需 operator，其他修改需要 admin
+func upstreamAccess(r *http.Request) string {
+	if r.Method == http.MethodGet || r.Method == http.MethodHead {
+		return types.RoleViewer
+	}
+	path := strings.TrimSuffix(r.URL.Path, "/")
+	if strings.HasSuffix(path, "/health") || (r.Method == http.MethodPost && strings.HasSuffix(path, "/circuit-breaker")) {
+		return types.RoleOperator
+	}
+	return types.RoleAdmin
+}
+
+// announcementAccess /api/v1/announcements/{id}/... 的权限：关闭公告只影响自己，任何角色都可以；管理公告需要 operator
+func announcementAccess(r *http.Request) string {
+	if r.Method == http.MethodGet || strings.HasSuffix(strings.TrimSuffix(r.URL.Path, "/"), "/dismiss") {
+		return types.RoleViewer
+	}
+	return types.RoleOperator
+}
+
@@ -0,0 +1,260 @@
+package server
+
+import (
+	"bufio"
+	"crypto/sha1"
+	"encoding/base64"
+	"encoding/binary"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"strings"
+	"sync"
+	"time"
+)
+
+// WebSocket（RFC 6455）协议常量，只实现网关需要的服务端部分
+const (
+	wsAcceptGUID = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+
+	wsOpContinuation = 0x0
+	wsOpText         = 0x1
+	wsOpBinary       = 0x2
+	wsOpClose        = 0x8
+	wsOpPing         = 0x9
+	wsOpPong         = 0xA
+
+	wsCloseNormal        = 1000
+	wsCloseProtocolError = 1002
+	wsCloseTooBig        = 1009
+
+	wsWriteTimeout = 30 * time.Second
+)
+
+// 读取客户端消息时的协议错误
+var (
+	errWebSocketProtocol = errors.New("websocket协议错误")
+	errWebSocketTooBig   = errors.New("websocket消息过大")
+)
+
+// wsConn 已升级的WebSocket连接
+type wsConn struct {
+	conn            net.Conn
+	reader          *bufio.Reader
+	maxMessageBytes int64      // 单条消息（合并分片后）的大小上限，与 proxy.max_request_bytes 相同
+	writeMu         sync.Mutex // 帧写入（数据帧与pong/close帧可能来自不同goroutine）
+}
+
+// validateWebSocketUpgrade 检查请求是否为合法的WebSocket升级请求
+func validateWebSocketUpgrade(r *http.Request) error {
+	if r.Method != http.MethodGet {
+		return fmt.Errorf("upgrade to WebSocket requires GET")
+	}
+	if !headerContainsToken(r.Header, "Connection", "upgrade") || !strings.EqualFold(r.Header.Get("Upgrade"), "websocket") {
+		return fmt.Errorf("missing WebSocket upgrade headers")
+	}
+	if r.Header.Get("Sec-WebSocket-Version") != "13" {
+		return fmt.Errorf("unsupported Sec-WebSocket-Version, expected 13")
+	}
+	if r.Header.Get("Sec-WebSocket-Key") == "" {
+		return fmt.Errorf("missing Sec-WebSocket-Key")
+	}
+	return nil
+}
+
+// headerContainsToken 检查逗号分隔的请求头中是否包含某个值（不区分大小写）
+func headerContainsToken(header http.Header, name, token string) bool {
+	for _, value := range header.Values(name) {
+		for _, part := range strings.Split(value, ",") {
+			if strings.EqualFold(strings.TrimSpace(part), token) {
+				return true
+			}
+		}
+	}
+	return false
+}
+
+// wsAcceptKey 计算 Sec-WebSocket-Accept
+func wsAcceptKey(key string) string {
+	sum := sha1.Sum([]byte(key + wsAcceptGUID))
+	return base64.StdEncoding.EncodeToString(sum[:])
+}
+
+// upgradeWebSocket 接管连接并完成握手，maxMessageBytes 为客户端单条消息的大小上限；
+// 调用前应先通过 validateWebSocketUpgrade 检查
+func upgradeWebSocket(w http.ResponseWriter, r *http.Request, maxMessageBytes int64) (*wsConn, error) {
+	hijacker, ok := w.(http.Hijacker)
+	if !ok {
+		return nil, fmt.Errorf("连接不支持接管")
+	}
+	conn, rw, err := hijacker.Hijack()
+	if err != nil {
+		return nil, fmt.Errorf("接管连接失败: %w", err)
+	}
+
+	response := "HTTP/1.1 101 Switching Protocols\r\n" +
+		"Upgrade: websocket\r\n" +
+		"Connection: Upgrade\r\n" +
+		"Sec-WebSocket-Accept: " + wsAcceptKey(r.Header.Get("Sec-WebSocket-Key")) + "\r\n\r\n"
+	_, err = rw.WriteString(response)
+	if err == nil {
+		err = rw.Flush()
+	}
+	if err != nil {
+		_ = conn.Close()
+		return nil, fmt.Errorf("写入握手响应失败: %w", err)
+	}
+
+	// 清除 http.Server 设置的超时，连接的生命周期由处理器管理
+	_ = conn.SetDeadline(time.Time{})
+	return &wsConn{conn: conn, reader: rw.Reader, maxMessageBytes: maxMessageBytes}, nil
+}
+
+// readFrame 读取一个客户端帧（客户端帧必须带掩码）
+func (c *wsConn) readFrame() (fin bool, opcode byte, payload []byte, err error) {
+	var header [2]byte
+	if _, err = io.ReadFull(c.reader, header[:]); err != nil {
+		return false, 0, nil, err
+	}
+	fin = header[0]&0x80 != 0
+	opcode = header[0] & 0x0F
+	if header[0]&0x70 != 0 || header[1]&0x80 == 0 {
+		// 未协商扩展，RSV位必须为0；客户端帧必须掩码
+		return false, 0, nil, errWebSocketProtocol
+	}
+
+	length := uint64(header[1] & 0x7F)
+	switch length {
+	case 126:
+		var ext [2]byte
+		if _, err = io.ReadFull(c.reader, ext[:]); err != nil {
+			return false, 0, nil, err
+		}
+		length = uint64(binary.BigEndian.Uint16(ext[:]))
+	case 127:
+		var ext [8]byte
+		if _, err = io.ReadFull(c.reader, ext[:]); err != nil {
+			return false, 0, nil, err
+		}
+		length = binary.BigEndian.Uint64(ext[:])
+	}
+	if length > uint64(c.maxMessageBytes) {
+		return false, 0, nil, errWebSocketTooBig
+	}
+
+	var mask [4]byte
+	if _, err = io.ReadFull(c.reader, mask[:]); err != nil {
+		return false, 0, nil, err
+	}
+	payload = make([]byte, length)
+	if _, err = io.ReadFull(c.reader, payload); err != nil {
+		return false, 0, nil, err
+	}
+	for i := range payload {
+		payload[i] ^= mask[i%4]
+	}
+	return fin, opcode, payload, nil
+}
+
+// ReadMessage 读取一条完整的数据消息（合并分片），自动回复ping；收到close帧时回复并返回 io.EOF
+func (c *wsConn) ReadMessage() ([]byte, error) {
+	var message []byte
+	started := false
+	for {
+		fin, opcode, payload, err := c.readFrame()
+		if err != nil {
+			return nil, err
+		}
+
+		switch opcode {
+		case wsOpPing:
+			if err := c.writeFrame(wsOpPong, payload); err != nil {
+				return nil, err
+			}
+			continue
+		case wsOpPong:
+			continue
+		case wsOpClose:
+			if len(payload) > 2 {
+				payload = payload[:2]
+			}
+			_ = c.writeFrame(wsOpClose, payload)
+			return nil, io.EOF
+		case wsOpText, wsOpBinary:
+			if started {
+				return nil, errWebSocketProtocol
+			}
+			started = true
+			message = payload
+		case wsOpContinuation:
+			if !started {
+				return nil, errWebSocketProtocol
+			}
+			message = append(message, payload...)
+		default:
+			return nil, errWebSocketProtocol
+		}
+
+		if int64(len(message)) > c.maxMessageBytes {
+			return nil, errWebSocketTooBig
+		}
+		if fin {
+			return message, nil
+		}
+	}
+}
+
+// writeFrame 写入一个不分片的服务端帧（服务端帧不掩码）
+func (c *wsConn) writeFrame(opcode byte, payload []byte) error {
+	c.writeMu.Lock()
+	defer c.writeMu.Unlock()
+
+	frame := []byte{0x80 | opcode}
+	switch n := len(payload); {
+	case n < 126:
+		frame = append(frame, byte(n))
+	case n <= 0xFFFF:
+		frame = append(frame, 126, byte(n>>8), byte(n))
+	default:
+		var ext [8]byte
+		binary.BigEndian.PutUint64(ext[:], uint64(n))
+		frame = append(append(frame, 127), ext[:]...)
+	}
+	frame = append(frame, payload...)
+
+	_ = c.conn.SetWriteDeadline(time.Now().Add(wsWriteTimeout))
+	_, err := c.conn.Write(frame)
+	return err
+}
+
+// WriteJSON 以文本帧发送JSON
+func (c *wsConn) WriteJSON(value interface{}) error {
+	data, err := json.Marshal(value)
+	if err != nil {
+		return err
+	}
+	return c.writeFrame(wsOpText, data)
+}
+
+// Close 发送close帧并关闭连接
+func (c *wsConn) Close(code int, reason string) {
+	payload := make([]byte, 2, 2+len(reason))
+	binary.BigEndian.PutUint16(payload, uint16(code))
+	_ = c.writeFrame(wsOpClose, append(payload, reason...))
+	_ = c.conn.Close()
+}
+
+// closeCode 根据读取错误选择关闭码
+func closeCode(err error) int {
+	switch {
+	case errors.Is(err, errWebSocketTooBig):
+		return wsCloseTooBig
+	case errors.Is(err, errWebSocketProtocol):
+		return wsCloseProtocolError
+	default:
+		return wsCloseNormal
+	}
+}
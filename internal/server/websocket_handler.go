@@ -0,0 +1,227 @@
+package server
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"io"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// wsRequestTimeout 连接建立后等待客户端发送请求的时间
+const wsRequestTimeout = 30 * time.Second
+
+// wsFrame 发送给客户端的JSON帧
+type wsFrame struct {
+	Event  string          `json:"event"`            // SSE事件名（无事件名时为 message），[DONE] 为 done，非流式响应为 response 或 error
+	Status int             `json:"status,omitempty"` // 非流式响应的HTTP状态码
+	Data   json.RawMessage `json:"data,omitempty"`
+}
+
+// wsStreamWriter 把代理流程写出的响应转换为WebSocket帧：SSE响应每个事件一帧，其他响应整体一帧
+type wsStreamWriter struct {
+	conn    *wsConn
+	header  http.Header
+	status  int
+	pending bytes.Buffer
+	err     error // 首个发送错误（客户端已断开）
+}
+
+// Header 实现 http.ResponseWriter
+func (w *wsStreamWriter) Header() http.Header {
+	return w.header
+}
+
+// WriteHeader 实现 http.ResponseWriter
+func (w *wsStreamWriter) WriteHeader(statusCode int) {
+	if w.status == 0 {
+		w.status = statusCode
+	}
+}
+
+// Write 缓存响应内容，SSE响应中每个完整事件立即发送
+func (w *wsStreamWriter) Write(p []byte) (int, error) {
+	if w.status == 0 {
+		w.status = http.StatusOK
+	}
+	if w.err != nil {
+		return 0, w.err
+	}
+	w.pending.Write(bytes.ReplaceAll(p, []byte("\r\n"), []byte("\n")))
+	if w.streaming() {
+		w.sendEvents()
+	}
+	return len(p), nil
+}
+
+// Flush 实现 http.Flusher（事件在写入时已发送）
+func (w *wsStreamWriter) Flush() {}
+
+// streaming 响应是否为SSE流
+func (w *wsStreamWriter) streaming() bool {
+	return w.status < http.StatusMultipleChoices && strings.HasPrefix(w.header.Get("Content-Type"), "text/event-stream")
+}
+
+// sendEvents 发送缓冲区中所有完整的SSE事件
+func (w *wsStreamWriter) sendEvents() {
+	for w.err == nil {
+		data := w.pending.Bytes()
+		end := bytes.Index(data, []byte("\n\n"))
+		if end < 0 {
+			return
+		}
+		event := string(data[:end])
+		w.pending.Next(end + 2)
+		w.sendEvent(event)
+	}
+}
+
+// sendEvent 解析一个SSE事件并发送
+func (w *wsStreamWriter) sendEvent(event string) {
+	name := ""
+	var data []string
+	for _, line := range strings.Split(event, "\n") {
+		switch {
+		case strings.HasPrefix(line, "event:"):
+			name = strings.TrimSpace(strings.TrimPrefix(line, "event:"))
+		case strings.HasPrefix(line, "data:"):
+			data = append(data, strings.TrimPrefix(strings.TrimPrefix(line, "data:"), " "))
+		}
+	}
+	if len(data) == 0 {
+		return // 注释或空事件
+	}
+
+	payload := strings.Join(data, "\n")
+	if payload == "[DONE]" {
+		w.send(wsFrame{Event: "done"})
+		return
+	}
+	if name == "" {
+		name = "message"
+	}
+	w.send(wsFrame{Event: name, Data: rawJSON([]byte(payload))})
+}
+
+// finish 发送剩余内容：SSE流中未以空行结束的最后一个事件，或完整的非流式响应
+func (w *wsStreamWriter) finish() {
+	if w.streaming() {
+		if rest := strings.TrimSpace(w.pending.String()); rest != "" {
+			w.sendEvent(rest)
+		}
+		return
+	}
+
+	event := "response"
+	if w.status >= http.StatusBadRequest {
+		event = "error"
+	}
+	w.send(wsFrame{Event: event, Status: w.status, Data: rawJSON(bytes.TrimSpace(w.pending.Bytes()))})
+}
+
+// send 发送一帧，失败后不再发送
+func (w *wsStreamWriter) send(frame wsFrame) {
+	if w.err == nil {
+		w.err = w.conn.WriteJSON(frame)
+	}
+}
+
+// rawJSON 合法JSON原样返回，否则编码为JSON字符串
+func rawJSON(data []byte) json.RawMessage {
+	if len(data) == 0 {
+		return nil
+	}
+	if json.Valid(data) {
+		return data
+	}
+	encoded, _ := json.Marshal(string(data))
+	return encoded
+}
+
+// forceStream 把请求改为流式（stream: true）
+func forceStream(message []byte) ([]byte, error) {
+	var request map[string]interface{}
+	if err := json.Unmarshal(message, &request); err != nil {
+		return nil, err
+	}
+	request["stream"] = true
+	return json.Marshal(request)
+}
+
+// hasWritePermission Key是否可以发送消息（write或admin权限）
+func hasWritePermission(key *types.GatewayAPIKey) bool {
+	for _, perm := range key.Permissions {
+		if perm == types.PermissionWrite || perm == types.PermissionAdmin {
+			return true
+		}
+	}
+	return false
+}
+
+// HandleMessagesWebSocket 通过WebSocket提供 /v1/messages：连接建立后客户端发送一条请求JSON，
+// 网关按 /v1/messages 的同一流程处理（强制流式，计费与统计不变），每个SSE事件作为一个JSON文本帧返回，结束后关闭连接
+func (h *ProxyHandler) HandleMessagesWebSocket(w http.ResponseWriter, r *http.Request) {
+	if err := validateWebSocketUpgrade(r); err != nil {
+		h.writeErrorResponse(w, http.StatusBadRequest, "websocket_upgrade_required", err.Error())
+		return
+	}
+
+	// 升级请求是GET，认证中间件只检查了read权限
+	gatewayKey, _ := r.Context().Value("gatewayKey").(*types.GatewayAPIKey)
+	if gatewayKey == nil || !hasWritePermission(gatewayKey) {
+		h.writeErrorResponse(w, http.StatusForbidden, "insufficient_permissions", "API key does not have required permissions")
+		return
+	}
+
+	// 请求消息与HTTP请求体使用同一个大小上限
+	conn, err := upgradeWebSocket(w, r, h.settings().maxRequestBytes)
+	if err != nil {
+		logger.Warn("WebSocket升级失败: %v", err)
+		return
+	}
+
+	// 第一条消息是请求体
+	_ = conn.conn.SetReadDeadline(time.Now().Add(wsRequestTimeout))
+	message, err := conn.ReadMessage()
+	if err != nil {
+		conn.Close(closeCode(err), "")
+		return
+	}
+	_ = conn.conn.SetReadDeadline(time.Time{})
+
+	writer := &wsStreamWriter{conn: conn, header: http.Header{}}
+	body, err := forceStream(message)
+	if err != nil {
+		h.writeErrorResponse(writer, http.StatusBadRequest, "invalid_request_body", "Request message must be a JSON object")
+		writer.finish()
+		conn.Close(wsCloseNormal, "")
+		return
+	}
+
+	// 之后客户端只会发送ping或close，连接关闭时取消请求上下文
+	ctx, cancel := context.WithCancel(r.Context())
+	defer cancel()
+	go func() {
+		for {
+			if _, err := conn.ReadMessage(); err != nil {
+				cancel()
+				return
+			}
+		}
+	}()
+
+	proxyReq := r.Clone(ctx)
+	proxyReq.Method = http.MethodPost
+	proxyReq.Body = io.NopCloser(bytes.NewReader(body))
+	proxyReq.ContentLength = int64(len(body))
+	proxyReq.Header.Set("Content-Type", "application/json")
+
+	h.handleProxyRequest(writer, proxyReq, "/v1/messages")
+	writer.finish()
+	conn.Close(wsCloseNormal, "")
+}
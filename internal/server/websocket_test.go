@@ -0,0 +1,444 @@
+package server
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/binary"
+	"encoding/json"
+	"errors"
+	"io"
+	"net"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+)
+
+// recordingConn 记录服务端写出的帧，其余 net.Conn 方法不会被调用
+type recordingConn struct {
+	net.Conn
+	written bytes.Buffer
+}
+
+func (c *recordingConn) Write(p []byte) (int, error) { return c.written.Write(p) }
+
+func (c *recordingConn) SetWriteDeadline(time.Time) error { return nil }
+
+func (c *recordingConn) Close() error { return nil }
+
+// newTestWSConn 创建从 input 读取客户端帧的连接
+func newTestWSConn(input []byte, maxMessageBytes int64) (*wsConn, *recordingConn) {
+	conn := &recordingConn{}
+	return &wsConn{conn: conn, reader: bufio.NewReader(bytes.NewReader(input)), maxMessageBytes: maxMessageBytes}, conn
+}
+
+// clientFrame 按客户端格式编码一帧（带掩码）
+func clientFrame(fin bool, opcode byte, payload []byte) []byte {
+	first := opcode
+	if fin {
+		first |= 0x80
+	}
+	frame := []byte{first}
+	switch n := len(payload); {
+	case n < 126:
+		frame = append(frame, 0x80|byte(n))
+	case n <= 0xFFFF:
+		frame = append(frame, 0x80|126, byte(n>>8), byte(n))
+	default:
+		var ext [8]byte
+		binary.BigEndian.PutUint64(ext[:], uint64(n))
+		frame = append(append(frame, 0x80|127), ext[:]...)
+	}
+	mask := []byte{0x12, 0x34, 0x56, 0x78}
+	frame = append(frame, mask...)
+	for i, b := range payload {
+		frame = append(frame, b^mask[i%4])
+	}
+	return frame
+}
+
+// serverFrame 解码后的服务端帧
+type serverFrame struct {
+	opcode  byte
+	payload []byte
+}
+
+// readServerFrames 解码服务端写出的所有帧，服务端帧不能带掩码
+func readServerFrames(t *testing.T, data []byte) []serverFrame {
+	t.Helper()
+	var frames []serverFrame
+	r := bytes.NewReader(data)
+	for r.Len() > 0 {
+		var header [2]byte
+		if _, err := io.ReadFull(r, header[:]); err != nil {
+			t.Fatalf("read frame header: %v", err)
+		}
+		if header[0]&0x80 == 0 || header[1]&0x80 != 0 {
+			t.Fatalf("frame header %x: want FIN set and no mask", header)
+		}
+		length := uint64(header[1] & 0x7F)
+		switch length {
+		case 126:
+			var ext [2]byte
+			_, _ = io.ReadFull(r, ext[:])
+			length = uint64(binary.BigEndian.Uint16(ext[:]))
+		case 127:
+			var ext [8]byte
+			_, _ = io.ReadFull(r, ext[:])
+			length = binary.BigEndian.Uint64(ext[:])
+		}
+		payload := make([]byte, length)
+		if _, err := io.ReadFull(r, payload); err != nil {
+			t.Fatalf("read frame payload: %v", err)
+		}
+		frames = append(frames, serverFrame{opcode: header[0] & 0x0F, payload: payload})
+	}
+	return frames
+}
+
+func TestWsAcceptKey(t *testing.T) {
+	// RFC 6455 第1.3节的示例
+	if got := wsAcceptKey("dGhlIHNhbXBsZSBub25jZQ=="); got != "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=" {
+		t.Errorf("wsAcceptKey() = %s, want s3pPLMBiTxaQ9kYGzzhZRbK+xOo=", got)
+	}
+}
+
+func TestUpgradeWebSocket_Handshake(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if err := validateWebSocketUpgrade(r); err != nil {
+			http.Error(w, err.Error(), http.StatusBadRequest)
+			return
+		}
+		conn, err := upgradeWebSocket(w, r, 1024)
+		if err != nil {
+			t.Errorf("upgradeWebSocket() error = %v", err)
+			return
+		}
+		message, err := conn.ReadMessage()
+		if err != nil {
+			conn.Close(closeCode(err), "")
+			return
+		}
+		_ = conn.writeFrame(wsOpText, message)
+		conn.Close(wsCloseNormal, "")
+	}))
+	defer server.Close()
+
+	conn, err := net.Dial("tcp", server.Listener.Addr().String())
+	if err != nil {
+		t.Fatalf("Dial() error = %v", err)
+	}
+	defer conn.Close()
+	_ = conn.SetDeadline(time.Now().Add(5 * time.Second))
+
+	request := "GET /v1/messages/ws HTTP/1.1\r\n" +
+		"Host: " + server.Listener.Addr().String() + "\r\n" +
+		"Upgrade: websocket\r\n" +
+		"Connection: keep-alive, Upgrade\r\n" +
+		"Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n" +
+		"Sec-WebSocket-Version: 13\r\n\r\n"
+	if _, err := conn.Write([]byte(request)); err != nil {
+		t.Fatalf("write handshake: %v", err)
+	}
+	reader := bufio.NewReader(conn)
+	resp, err := http.ReadResponse(reader, nil)
+	if err != nil {
+		t.Fatalf("ReadResponse() error = %v", err)
+	}
+	if resp.StatusCode != http.StatusSwitchingProtocols {
+		t.Fatalf("handshake status = %d, want 101", resp.StatusCode)
+	}
+	if got := resp.Header.Get("Sec-WebSocket-Accept"); got != "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=" {
+		t.Errorf("Sec-WebSocket-Accept = %s", got)
+	}
+
+	// 握手后按帧通信：服务端回显一条消息后正常关闭
+	if _, err := conn.Write(clientFrame(true, wsOpText, []byte("hello"))); err != nil {
+		t.Fatalf("write frame: %v", err)
+	}
+	rest, _ := io.ReadAll(reader)
+	frames := readServerFrames(t, rest)
+	if len(frames) != 2 || string(frames[0].payload) != "hello" || frames[1].opcode != wsOpClose {
+		t.Fatalf("frames = %+v, want the echoed message and a close frame", frames)
+	}
+	if code := binary.BigEndian.Uint16(frames[1].payload); code != wsCloseNormal {
+		t.Errorf("close code = %d, want %d", code, wsCloseNormal)
+	}
+}
+
+func TestValidateWebSocketUpgrade(t *testing.T) {
+	valid := func() *http.Request {
+		req := httptest.NewRequest(http.MethodGet, "/v1/messages/ws", nil)
+		req.Header.Set("Connection", "Upgrade")
+		req.Header.Set("Upgrade", "websocket")
+		req.Header.Set("Sec-WebSocket-Version", "13")
+		req.Header.Set("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+		return req
+	}
+	if err := validateWebSocketUpgrade(valid()); err != nil {
+		t.Fatalf("validateWebSocketUpgrade() error = %v for a valid request", err)
+	}
+
+	tests := map[string]func(r *http.Request){
+		"post":          func(r *http.Request) { r.Method = http.MethodPost },
+		"no upgrade":    func(r *http.Request) { r.Header.Del("Upgrade") },
+		"no connection": func(r *http.Request) { r.Header.Set("Connection", "keep-alive") },
+		"old version":   func(r *http.Request) { r.Header.Set("Sec-WebSocket-Version", "8") },
+		"missing key":   func(r *http.Request) { r.Header.Del("Sec-WebSocket-Key") },
+	}
+	for name, modify := range tests {
+		req := valid()
+		modify(req)
+		if err := validateWebSocketUpgrade(req); err == nil {
+			t.Errorf("%s: validateWebSocketUpgrade() error = nil", name)
+		}
+	}
+}
+
+func TestWsConn_ReadMessageLengths(t *testing.T) {
+	// 7位、16位和64位长度编码
+	for _, size := range []int{5, 125, 126, 300, 0xFFFF, 70000} {
+		payload := bytes.Repeat([]byte("x"), size)
+		conn, _ := newTestWSConn(clientFrame(true, wsOpText, payload), 1<<20)
+		message, err := conn.ReadMessage()
+		if err != nil {
+			t.Fatalf("size %d: ReadMessage() error = %v", size, err)
+		}
+		if !bytes.Equal(message, payload) {
+			t.Errorf("size %d: ReadMessage() returned %d bytes, want the unmasked payload", size, len(message))
+		}
+	}
+}
+
+func TestWsConn_ReadMessageRejectsUnmaskedFrame(t *testing.T) {
+	frame := []byte{0x80 | wsOpText, 5, 'h', 'e', 'l', 'l', 'o'}
+	conn, _ := newTestWSConn(frame, 1024)
+	if _, err := conn.ReadMessage(); !errors.Is(err, errWebSocketProtocol) {
+		t.Fatalf("ReadMessage() error = %v, want %v", err, errWebSocketProtocol)
+	}
+	if closeCode(errWebSocketProtocol) != wsCloseProtocolError {
+		t.Errorf("closeCode() = %d, want %d", closeCode(errWebSocketProtocol), wsCloseProtocolError)
+	}
+}
+
+func TestWsConn_ReadMessageFragments(t *testing.T) {
+	var input []byte
+	input = append(input, clientFrame(false, wsOpText, []byte(`{"model":`))...)
+	input = append(input, clientFrame(true, wsOpPing, []byte("keepalive"))...) // 控制帧可以插在分片之间
+	input = append(input, clientFrame(true, wsOpContinuation, []byte(`"claude"}`))...)
+	conn, out := newTestWSConn(input, 1024)
+
+	message, err := conn.ReadMessage()
+	if err != nil {
+		t.Fatalf("ReadMessage() error = %v", err)
+	}
+	if string(message) != `{"model":"claude"}` {
+		t.Errorf("ReadMessage() = %s, want the fragments joined", message)
+	}
+	frames := readServerFrames(t, out.written.Bytes())
+	if len(frames) != 1 || frames[0].opcode != wsOpPong || string(frames[0].payload) != "keepalive" {
+		t.Errorf("frames = %+v, want a pong echoing the ping payload", frames)
+	}
+
+	// 没有起始帧的续帧是协议错误
+	conn, _ = newTestWSConn(clientFrame(true, wsOpContinuation, []byte("x")), 1024)
+	if _, err := conn.ReadMessage(); !errors.Is(err, errWebSocketProtocol) {
+		t.Errorf("ReadMessage() error = %v for a stray continuation, want %v", err, errWebSocketProtocol)
+	}
+}
+
+func TestWsConn_ReadMessageTooBig(t *testing.T) {
+	// 单帧超过上限时只读取帧头即拒绝
+	header := clientFrame(true, wsOpText, make([]byte, 2048))[:4]
+	conn, _ := newTestWSConn(header, 1024)
+	if _, err := conn.ReadMessage(); !errors.Is(err, errWebSocketTooBig) {
+		t.Errorf("ReadMessage() error = %v for an oversized frame, want %v", err, errWebSocketTooBig)
+	}
+
+	// 每个分片都在上限内，但合并后超过上限
+	var input []byte
+	input = append(input, clientFrame(false, wsOpText, make([]byte, 600))...)
+	input = append(input, clientFrame(true, wsOpContinuation, make([]byte, 600))...)
+	conn, _ = newTestWSConn(input, 1024)
+	if _, err := conn.ReadMessage(); !errors.Is(err, errWebSocketTooBig) {
+		t.Errorf("ReadMessage() error = %v for oversized fragments, want %v", err, errWebSocketTooBig)
+	}
+	if closeCode(errWebSocketTooBig) != wsCloseTooBig {
+		t.Errorf("closeCode() = %d, want %d", closeCode(errWebSocketTooBig), wsCloseTooBig)
+	}
+}
+
+func TestWsConn_ReadMessagePingPongClose(t *testing.T) {
+	var input []byte
+	input = append(input, clientFrame(true, wsOpPong, nil)...)
+	input = append(input, clientFrame(true, wsOpPing, []byte("p1"))...)
+	input = append(input, clientFrame(true, wsOpText, []byte("request"))...)
+	input = append(input, clientFrame(true, wsOpClose, []byte{0x03, 0xE8, 'b', 'y', 'e'})...)
+	conn, out := newTestWSConn(input, 1024)
+
+	message, err := conn.ReadMessage()
+	if err != nil || string(message) != "request" {
+		t.Fatalf("ReadMessage() = %q, %v, want the text message after skipping pong and answering ping", message, err)
+	}
+	if _, err := conn.ReadMessage(); err != io.EOF {
+		t.Fatalf("ReadMessage() error = %v after close, want io.EOF", err)
+	}
+
+	frames := readServerFrames(t, out.written.Bytes())
+	if len(frames) != 2 {
+		t.Fatalf("frames = %+v, want a pong and a close", frames)
+	}
+	if frames[0].opcode != wsOpPong || string(frames[0].payload) != "p1" {
+		t.Errorf("first frame = %+v, want a pong echoing p1", frames[0])
+	}
+	// close帧只回显状态码
+	if frames[1].opcode != wsOpClose || !bytes.Equal(frames[1].payload, []byte{0x03, 0xE8}) {
+		t.Errorf("second frame = %+v, want a close echoing code 1000", frames[1])
+	}
+}
+
+func TestWsConn_WriteFrameLengths(t *testing.T) {
+	tests := []struct {
+		size   int
+		header []byte
+	}{
+		{size: 5, header: []byte{0x81, 5}},
+		{size: 125, header: []byte{0x81, 125}},
+		{size: 300, header: []byte{0x81, 126, 0x01, 0x2C}},
+		{size: 70000, header: []byte{0x81, 127, 0, 0, 0, 0, 0, 0x01, 0x11, 0x70}},
+	}
+	for _, tt := range tests {
+		conn, out := newTestWSConn(nil, 1024)
+		payload := bytes.Repeat([]byte("y"), tt.size)
+		if err := conn.writeFrame(wsOpText, payload); err != nil {
+			t.Fatalf("size %d: writeFrame() error = %v", tt.size, err)
+		}
+		written := out.written.Bytes()
+		if !bytes.HasPrefix(written, tt.header) {
+			t.Errorf("size %d: header = %x, want %x", tt.size, written[:len(tt.header)], tt.header)
+		}
+		if !bytes.Equal(written[len(tt.header):], payload) {
+			t.Errorf("size %d: payload was not written unmasked after the header", tt.size)
+		}
+	}
+}
+
+// streamFrames 解码 wsStreamWriter 发送的JSON帧
+func streamFrames(t *testing.T, out *recordingConn) []wsFrame {
+	t.Helper()
+	var frames []wsFrame
+	for _, frame := range readServerFrames(t, out.written.Bytes()) {
+		if frame.opcode != wsOpText {
+			t.Fatalf("opcode = %d, want text frames", frame.opcode)
+		}
+		var decoded wsFrame
+		if err := json.Unmarshal(frame.payload, &decoded); err != nil {
+			t.Fatalf("frame %s is not JSON: %v", frame.payload, err)
+		}
+		frames = append(frames, decoded)
+	}
+	return frames
+}
+
+func TestWsStreamWriter_SSEEvents(t *testing.T) {
+	conn, out := newTestWSConn(nil, 1024)
+	writer := &wsStreamWriter{conn: conn, header: http.Header{}}
+	writer.Header().Set("Content-Type", "text/event-stream")
+	writer.WriteHeader(http.StatusOK)
+
+	// 事件跨多次写入、使用CRLF换行、包含注释行，最后一个事件没有以空行结束
+	chunks := []string{
+		"event: message_start\r\ndata: {\"type\":\"message_start\"}\r\n\r\n: ping\n\n",
+		"event: content_block_delta\ndata: {\"type\":\"content_",
+		"block_delta\",\"text\":\"hi\"}\n\ndata: {\"id\":1}\n\n",
+		"data: [DONE]",
+	}
+	for _, chunk := range chunks {
+		if _, err := writer.Write([]byte(chunk)); err != nil {
+			t.Fatalf("Write() error = %v", err)
+		}
+	}
+	if got := len(streamFrames(t, out)); got != 3 {
+		t.Errorf("frames sent before finish = %d, want 3 complete events", got)
+	}
+	writer.finish()
+
+	frames := streamFrames(t, out)
+	want := []struct {
+		event string
+		data  string
+	}{
+		{event: "message_start", data: `{"type":"message_start"}`},
+		{event: "content_block_delta", data: `{"type":"content_block_delta","text":"hi"}`},
+		{event: "message", data: `{"id":1}`},
+		{event: "done"},
+	}
+	if len(frames) != len(want) {
+		t.Fatalf("frames = %+v, want %d frames", frames, len(want))
+	}
+	for i, w := range want {
+		if frames[i].Event != w.event || string(frames[i].Data) != w.data || frames[i].Status != 0 {
+			t.Errorf("frame %d = {%s %d %s}, want {%s 0 %s}", i, frames[i].Event, frames[i].Status, frames[i].Data, w.event, w.data)
+		}
+	}
+}
+
+func TestWsStreamWriter_NonStreamResponse(t *testing.T) {
+	tests := []struct {
+		name   string
+		status int
+		body   string
+		event  string
+		data   string
+	}{
+		{name: "error", status: http.StatusTooManyRequests, body: `{"error":{"type":"rate_limited"}}` + "\n", event: "error", data: `{"error":{"type":"rate_limited"}}`},
+		{name: "json response", status: http.StatusOK, body: `{"id":"msg_1"}`, event: "response", data: `{"id":"msg_1"}`},
+		{name: "plain text error", status: http.StatusBadGateway, body: "upstream failed", event: "error", data: `"upstream failed"`},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			conn, out := newTestWSConn(nil, 1024)
+			writer := &wsStreamWriter{conn: conn, header: http.Header{}}
+			writer.Header().Set("Content-Type", "application/json")
+			writer.WriteHeader(tt.status)
+			_, _ = writer.Write([]byte(tt.body))
+			if len(out.written.Bytes()) != 0 {
+				t.Fatal("non-stream response was sent before finish")
+			}
+			writer.finish()
+
+			frames := streamFrames(t, out)
+			if len(frames) != 1 {
+				t.Fatalf("frames = %+v, want one frame", frames)
+			}
+			if frames[0].Event != tt.event || frames[0].Status != tt.status || string(frames[0].Data) != tt.data {
+				t.Errorf("frame = {%s %d %s}, want {%s %d %s}", frames[0].Event, frames[0].Status, frames[0].Data, tt.event, tt.status, tt.data)
+			}
+		})
+	}
+
+	// 状态码为错误的SSE响应不按流处理
+	conn, out := newTestWSConn(nil, 1024)
+	writer := &wsStreamWriter{conn: conn, header: http.Header{}}
+	writer.Header().Set("Content-Type", "text/event-stream")
+	writer.WriteHeader(http.StatusServiceUnavailable)
+	_, _ = writer.Write([]byte("data: {}\n\n"))
+	writer.finish()
+	if frames := streamFrames(t, out); len(frames) != 1 || frames[0].Event != "error" {
+		t.Errorf("frames = %+v, want one error frame", frames)
+	}
+}
+
+func TestForceStream(t *testing.T) {
+	body, err := forceStream([]byte(`{"model":"claude","stream":false}`))
+	if err != nil {
+		t.Fatalf("forceStream() error = %v", err)
+	}
+	if !strings.Contains(string(body), `"stream":true`) {
+		t.Errorf("forceStream() = %s, want stream set to true", body)
+	}
+	if _, err := forceStream([]byte(`["not","an","object"]`)); err == nil {
+		t.Error("forceStream() error = nil for a non-object message")
+	}
+}
@@ -0,0 +1,119 @@
+package serviceaccount
+
+import (
+	"crypto/hmac"
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strings"
+	"time"
+)
+
+// DefaultTTL 访问令牌的默认有效期
+const DefaultTTL = 15 * time.Minute
+
+// tokenType 令牌用途，区分服务账号令牌和其他用同一密钥签名的数据
+const tokenType = "service"
+
+// jwtHeader 固定的 HS256 JWT 头部
+var jwtHeader = base64.RawURLEncoding.EncodeToString([]byte(`{"alg":"HS256","typ":"JWT"}`))
+
+var (
+	// ErrInvalidToken 令牌格式、签名或用途不正确
+	ErrInvalidToken = errors.New("无效的服务账号令牌")
+	// ErrExpiredToken 令牌已过期
+	ErrExpiredToken = errors.New("服务账号令牌已过期")
+)
+
+// Claims 服务账号访问令牌中的声明
+type Claims struct {
+	Subject   string `json:"sub"`  // 服务账号的 client_id
+	Role      string `json:"role"` // 签发时的角色，校验时以配置中的当前角色为准
+	Type      string `json:"typ"`
+	IssuedAt  int64  `json:"iat"`
+	ExpiresAt int64  `json:"exp"`
+}
+
+// Issuer 签发和校验服务账号的短期访问令牌（HS256 JWT）
+type Issuer struct {
+	key []byte
+	ttl time.Duration
+}
+
+// NewIssuer 创建令牌签发器。secret 为空时使用随机密钥，令牌在重启后失效，且只在签发它的实例上有效；
+// 多个实例共享令牌时需配置相同的 secret。ttl<=0 时使用 DefaultTTL
+func NewIssuer(secret string, ttl time.Duration) (*Issuer, error) {
+	key := []byte(secret)
+	if secret == "" {
+		key = make([]byte, 32)
+		if _, err := rand.Read(key); err != nil {
+			return nil, fmt.Errorf("生成令牌签名密钥失败: %w", err)
+		}
+	}
+	if ttl <= 0 {
+		ttl = DefaultTTL
+	}
+	return &Issuer{key: key, ttl: ttl}, nil
+}
+
+// TTL 令牌有效期
+func (i *Issuer) TTL() time.Duration {
+	return i.ttl
+}
+
+// Issue 为服务账号签发访问令牌，返回令牌和过期时间
+func (i *Issuer) Issue(clientID, role string, now time.Time) (string, time.Time, error) {
+	expiresAt := now.Add(i.ttl)
+	payload, err := json.Marshal(&Claims{
+		Subject:   clientID,
+		Role:      role,
+		Type:      tokenType,
+		IssuedAt:  now.Unix(),
+		ExpiresAt: expiresAt.Unix(),
+	})
+	if err != nil {
+		return "", time.Time{}, fmt.Errorf("序列化令牌声明失败: %w", err)
+	}
+
+	signingInput := jwtHeader + "." + base64.RawURLEncoding.EncodeToString(payload)
+	return signingInput + "." + i.sign(signingInput), expiresAt, nil
+}
+
+// Verify 校验令牌的签名、用途和有效期，返回其中的声明
+func (i *Issuer) Verify(token string, now time.Time) (*Claims, error) {
+	parts := strings.Split(token, ".")
+	if len(parts) != 3 || parts[0] != jwtHeader {
+		return nil, ErrInvalidToken
+	}
+	if !hmac.Equal([]byte(parts[2]), []byte(i.sign(parts[0]+"."+parts[1]))) {
+		return nil, ErrInvalidToken
+	}
+
+	payload, err := base64.RawURLEncoding.DecodeString(parts[1])
+	if err != nil {
+		return nil, ErrInvalidToken
+	}
+	var claims Claims
+	if err := json.Unmarshal(payload, &claims); err != nil || claims.Type != tokenType || claims.Subject == "" {
+		return nil, ErrInvalidToken
+	}
+	if now.Unix() >= claims.ExpiresAt {
+		return nil, ErrExpiredToken
+	}
+	return &claims, nil
+}
+
+// IsToken 判断字符串是否为JWT形式（用于和Web会话token区分），不校验签名
+func IsToken(token string) bool {
+	return strings.HasPrefix(token, jwtHeader+".") && strings.Count(token, ".") == 2
+}
+
+// sign 计算签名输入的 HMAC-SHA256 签名
+func (i *Issuer) sign(signingInput string) string {
+	mac := hmac.New(sha256.New, i.key)
+	mac.Write([]byte(signingInput))
+	return base64.RawURLEncoding.EncodeToString(mac.Sum(nil))
+}
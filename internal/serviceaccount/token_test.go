@@ -0,0 +1,56 @@
+package serviceaccount
+
+import (
+	"errors"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestIssuer_IssueAndVerify(t *testing.T) {
+	issuer, err := NewIssuer("shared-secret", time.Minute)
+	if err != nil {
+		t.Fatal(err)
+	}
+	now := time.Date(2024, 6, 1, 12, 0, 0, 0, time.UTC)
+
+	token, expiresAt, err := issuer.Issue("sa_ci", "operator", now)
+	if err != nil {
+		t.Fatalf("Issue() error = %v", err)
+	}
+	if !IsToken(token) || !expiresAt.Equal(now.Add(time.Minute)) {
+		t.Fatalf("Issue() = %s, expires %v", token, expiresAt)
+	}
+
+	claims, err := issuer.Verify(token, now.Add(30*time.Second))
+	if err != nil {
+		t.Fatalf("Verify() error = %v", err)
+	}
+	if claims.Subject != "sa_ci" || claims.Role != "operator" {
+		t.Errorf("claims = %+v", claims)
+	}
+
+	if _, err := issuer.Verify(token, now.Add(time.Minute)); !errors.Is(err, ErrExpiredToken) {
+		t.Errorf("Verify() after expiry = %v, want ErrExpiredToken", err)
+	}
+
+	// 同一 secret 的其他实例可以校验，其他密钥签发的令牌被拒绝
+	peer, _ := NewIssuer("shared-secret", 0)
+	if _, err := peer.Verify(token, now); err != nil {
+		t.Errorf("Verify() on a peer with the same secret = %v", err)
+	}
+	other, _ := NewIssuer("", 0)
+	if _, err := other.Verify(token, now); !errors.Is(err, ErrInvalidToken) {
+		t.Errorf("Verify() with another key = %v, want ErrInvalidToken", err)
+	}
+
+	// 篡改声明（如提升角色）会使签名失效
+	parts := strings.Split(token, ".")
+	forged, _, _ := issuer.Issue("sa_ci", "admin", now)
+	if _, err := issuer.Verify(parts[0]+"."+strings.Split(forged, ".")[1]+"."+parts[2], now); !errors.Is(err, ErrInvalidToken) {
+		t.Errorf("Verify() of a tampered token = %v, want ErrInvalidToken", err)
+	}
+	if IsToken("c2Vzc2lvbi10b2tlbg==") {
+		t.Error("a web session token should not look like a service token")
+	}
+}
@@ -0,0 +1,184 @@
+package sigv4
+
+import (
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"net/http"
+	"net/url"
+	"sort"
+	"strings"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// AWS Signature Version 4 常量
+const (
+	signAlgorithm   = "AWS4-HMAC-SHA256"
+	amzDateLayout   = "20060102T150405Z"
+	shortDateLayout = "20060102"
+
+	// UnsignedPayload 请求体不参与签名（S3预签名URL）
+	UnsignedPayload = "UNSIGNED-PAYLOAD"
+)
+
+// Signer 按 SigV4 规范对AWS请求签名
+type Signer struct {
+	AccessKeyID     string
+	SecretAccessKey string
+	SessionToken    string // 临时凭证的会话令牌，为空时不发送
+	Region          string
+	Service         string // s3、bedrock 等
+}
+
+// SignRequest 为请求添加 x-amz-date、x-amz-content-sha256 和 Authorization 头（签名所有已设置的请求头）
+func (s *Signer) SignRequest(req *http.Request, payloadHash string, now time.Time) {
+	now = now.UTC()
+	amzDate := now.Format(amzDateLayout)
+	req.Header.Set("X-Amz-Date", amzDate)
+	req.Header.Set("X-Amz-Content-Sha256", payloadHash)
+	if s.SessionToken != "" {
+		req.Header.Set("X-Amz-Security-Token", s.SessionToken)
+	}
+
+	headerNames := []string{"host"}
+	for name := range req.Header {
+		headerNames = append(headerNames, strings.ToLower(name))
+	}
+	sort.Strings(headerNames)
+
+	var canonicalHeaders strings.Builder
+	for _, name := range headerNames {
+		value := strings.Join(req.Header.Values(name), ",")
+		if name == "host" {
+			value = req.URL.Host
+		}
+		canonicalHeaders.WriteString(name + ":" + strings.TrimSpace(value) + "\n")
+	}
+	signedHeaders := strings.Join(headerNames, ";")
+
+	canonicalRequest := strings.Join([]string{
+		req.Method,
+		s.canonicalPath(req.URL),
+		canonicalQuery(req.URL.Query()),
+		canonicalHeaders.String(),
+		signedHeaders,
+		payloadHash,
+	}, "\n")
+
+	scope := s.scope(now)
+	signature := s.signature(now, scope, canonicalRequest)
+	req.Header.Set("Authorization", fmt.Sprintf("%s Credential=%s/%s, SignedHeaders=%s, Signature=%s",
+		signAlgorithm, s.AccessKeyID, scope, signedHeaders, signature))
+}
+
+// Presign 生成带签名查询参数的URL（只签名host头，请求体不参与签名）
+func (s *Signer) Presign(method string, u *url.URL, expires time.Duration, now time.Time) string {
+	now = now.UTC()
+	scope := s.scope(now)
+
+	query := u.Query()
+	query.Set("X-Amz-Algorithm", signAlgorithm)
+	query.Set("X-Amz-Credential", s.AccessKeyID+"/"+scope)
+	query.Set("X-Amz-Date", now.Format(amzDateLayout))
+	query.Set("X-Amz-Expires", fmt.Sprintf("%d", int64(expires/time.Second)))
+	query.Set("X-Amz-SignedHeaders", "host")
+	if s.SessionToken != "" {
+		query.Set("X-Amz-Security-Token", s.SessionToken)
+	}
+
+	canonicalRequest := strings.Join([]string{
+		method,
+		s.canonicalPath(u),
+		canonicalQuery(query),
+		"host:" + u.Host + "\n",
+		"host",
+		UnsignedPayload,
+	}, "\n")
+
+	signature := s.signature(now, scope, canonicalRequest)
+	return u.Scheme + "://" + u.Host + CanonicalURI(u.Path) + "?" + canonicalQuery(query) + "&X-Amz-Signature=" + signature
+}
+
+// canonicalPath 签名使用的路径：S3 对原始路径编码一次，其他服务对请求中已编码的路径再编码一次
+func (s *Signer) canonicalPath(u *url.URL) string {
+	if s.Service == "s3" {
+		return CanonicalURI(u.Path)
+	}
+	return CanonicalURI(u.EscapedPath())
+}
+
+// scope 凭证范围：日期/区域/服务/aws4_request
+func (s *Signer) scope(now time.Time) string {
+	return now.Format(shortDateLayout) + "/" + s.Region + "/" + s.Service + "/aws4_request"
+}
+
+// signature 计算待签字符串的签名
+func (s *Signer) signature(now time.Time, scope, canonicalRequest string) string {
+	stringToSign := strings.Join([]string{
+		signAlgorithm,
+		now.Format(amzDateLayout),
+		scope,
+		utils.SHA256Hex(canonicalRequest),
+	}, "\n")
+
+	key := hmacSHA256([]byte("AWS4"+s.SecretAccessKey), now.Format(shortDateLayout))
+	key = hmacSHA256(key, s.Region)
+	key = hmacSHA256(key, s.Service)
+	key = hmacSHA256(key, "aws4_request")
+	return hex.EncodeToString(hmacSHA256(key, stringToSign))
+}
+
+// CanonicalURI 按SigV4规则编码路径（保留斜杠，不做二次规范化）
+func CanonicalURI(path string) string {
+	if path == "" {
+		return "/"
+	}
+	return uriEncode(path, false)
+}
+
+// canonicalQuery 按键排序并编码查询参数
+func canonicalQuery(query url.Values) string {
+	keys := make([]string, 0, len(query))
+	for key := range query {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+
+	var parts []string
+	for _, key := range keys {
+		values := append([]string{}, query[key]...)
+		sort.Strings(values)
+		for _, value := range values {
+			parts = append(parts, uriEncode(key, true)+"="+uriEncode(value, true))
+		}
+	}
+	return strings.Join(parts, "&")
+}
+
+// uriEncode SigV4 要求的URI编码：只保留非保留字符，其余按 %XX（大写）编码
+func uriEncode(value string, encodeSlash bool) string {
+	var builder strings.Builder
+	for i := 0; i < len(value); i++ {
+		c := value[i]
+		switch {
+		case c >= 'A' && c <= 'Z', c >= 'a' && c <= 'z', c >= '0' && c <= '9',
+			c == '-', c == '_', c == '.', c == '~':
+			builder.WriteByte(c)
+		case c == '/' && !encodeSlash:
+			builder.WriteByte(c)
+		default:
+			fmt.Fprintf(&builder, "%%%02X", c)
+		}
+	}
+	return builder.String()
+}
+
+// hmacSHA256 计算HMAC-SHA256
+func hmacSHA256(key []byte, data string) []byte {
+	mac := hmac.New(sha256.New, key)
+	mac.Write([]byte(data))
+	return mac.Sum(nil)
+}
@@ -0,0 +1,65 @@
+package stats
+
+import "sort"
+
+// AppUnknown 未携带 X-Gateway-App 头部的请求归入的分组
+const AppUnknown = "unknown"
+
+// GroupByApp 按客户端应用名称分组（未声明的记录归为 AppUnknown）
+func GroupByApp(record *UsageRecord) string {
+	if record.App == "" {
+		return AppUnknown
+	}
+	return record.App
+}
+
+// GroupByAppVersion 按客户端应用名称和版本分组，格式为 "<名称>@<版本>"
+func GroupByAppVersion(record *UsageRecord) string {
+	if record.App == "" {
+		return AppUnknown
+	}
+	return record.App + "@" + record.AppVersion
+}
+
+// AppStats 单个客户端应用（或应用版本）的流量统计
+type AppStats struct {
+	App          string  `json:"app"`
+	Requests     int     `json:"requests"`
+	Errors       int     `json:"errors"`
+	InputTokens  int64   `json:"input_tokens"`
+	OutputTokens int64   `json:"output_tokens"`
+	CostUSD      float64 `json:"cost_usd"`
+}
+
+// ComputeAppStats 按 groupBy 汇总请求数、错误数、token和费用，按请求数从多到少排序
+func ComputeAppStats(records []UsageRecord, groupBy func(*UsageRecord) string) []*AppStats {
+	byApp := make(map[string]*AppStats)
+	for i := range records {
+		record := &records[i]
+		app := groupBy(record)
+		entry, exists := byApp[app]
+		if !exists {
+			entry = &AppStats{App: app}
+			byApp[app] = entry
+		}
+		entry.Requests++
+		if !record.Success {
+			entry.Errors++
+		}
+		entry.InputTokens += int64(record.InputTokens)
+		entry.OutputTokens += int64(record.OutputTokens)
+		entry.CostUSD += record.CostUSD
+	}
+
+	result := make([]*AppStats, 0, len(byApp))
+	for _, entry := range byApp {
+		result = append(result, entry)
+	}
+	sort.Slice(result, func(i, j int) bool {
+		if result[i].Requests != result[j].Requests {
+			return result[i].Requests > result[j].Requests
+		}
+		return result[i].App < result[j].App
+	})
+	return result
+}
@@ -0,0 +1,43 @@
+package stats
+
+import "testing"
+
+func TestComputeAppStats(t *testing.T) {
+	records := []UsageRecord{
+		{App: "billing-bot", AppVersion: "1.4.2", Success: true, InputTokens: 100, OutputTokens: 50, CostUSD: 0.1},
+		{App: "billing-bot", AppVersion: "1.5.0", Success: false},
+		{App: "ci", AppVersion: "2.0", Success: true, InputTokens: 10, OutputTokens: 5},
+		{App: "billing-bot", AppVersion: "1.5.0", Success: true, InputTokens: 200, OutputTokens: 100, CostUSD: 0.2},
+		{Success: true},
+	}
+
+	byApp := ComputeAppStats(records, GroupByApp)
+	if len(byApp) != 3 {
+		t.Fatalf("got %d apps, want 3", len(byApp))
+	}
+	bot := byApp[0]
+	if bot.App != "billing-bot" || bot.Requests != 3 || bot.Errors != 1 || bot.InputTokens != 300 || bot.OutputTokens != 150 {
+		t.Errorf("billing-bot stats = %+v", bot)
+	}
+	if byApp[1].App != "ci" || byApp[2].App != AppUnknown {
+		t.Errorf("order = %s, %s, want ci, %s (ties sorted by name)", byApp[1].App, byApp[2].App, AppUnknown)
+	}
+
+	byVersion := ComputeAppStats(records, GroupByAppVersion)
+	if len(byVersion) != 4 {
+		t.Fatalf("got %d app versions, want 4", len(byVersion))
+	}
+	if byVersion[0].App != "billing-bot@1.5.0" || byVersion[0].Requests != 2 {
+		t.Errorf("top version = %+v, want billing-bot@1.5.0 with 2 requests", byVersion[0])
+	}
+}
+
+func TestFilterMatchApp(t *testing.T) {
+	filter := Filter{App: "ci"}
+	if !filter.Match(&UsageRecord{App: "ci"}) {
+		t.Error("record from ci should match")
+	}
+	if filter.Match(&UsageRecord{App: "billing-bot"}) || filter.Match(&UsageRecord{}) {
+		t.Error("records from other or unknown apps should not match")
+	}
+}
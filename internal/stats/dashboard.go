@@ -0,0 +1,137 @@
+package stats
+
+import (
+	"sync"
+	"time"
+)
+
+// trendHours 错误率趋势覆盖的小时数
+const trendHours = 24
+
+// DailyTotals 当日（UTC）的用量合计
+type DailyTotals struct {
+	Date         string  `json:"date"` // YYYY-MM-DD
+	Requests     int64   `json:"requests"`
+	Errors       int64   `json:"errors"`
+	InputTokens  int64   `json:"input_tokens"`
+	OutputTokens int64   `json:"output_tokens"`
+	CostUSD      float64 `json:"cost_usd"`
+}
+
+// HourlyPoint 一个小时内的请求数与错误率
+type HourlyPoint struct {
+	Hour      time.Time `json:"hour"`
+	Requests  int64     `json:"requests"`
+	Errors    int64     `json:"errors"`
+	ErrorRate float64   `json:"error_rate"`
+}
+
+// hourBucket 环形缓冲中的一个小时
+type hourBucket struct {
+	hour     time.Time
+	requests int64
+	errors   int64
+}
+
+// Aggregates 随使用记录写入增量维护当日合计和最近24小时的每小时错误数，
+// 看板读取时无需扫描使用记录
+type Aggregates struct {
+	day   time.Time
+	today DailyTotals
+	hours [trendHours]hourBucket // 按小时序号取模的环形缓冲
+	mutex sync.Mutex
+}
+
+// NewAggregates 创建看板聚合，recorder不为nil时先累计最近24小时的记录再订阅后续写入
+func NewAggregates(recorder *Recorder) *Aggregates {
+	a := &Aggregates{}
+	if recorder != nil {
+		now := time.Now()
+		since := utcDay(now)
+		if trendStart := now.Add(-trendHours * time.Hour); trendStart.Before(since) {
+			since = trendStart
+		}
+		for _, record := range recorder.Query(Filter{Since: since}) {
+			a.Add(record)
+		}
+		recorder.Subscribe(a.Add)
+	}
+	return a
+}
+
+// Add 累计一条使用记录，早于当前周期的记录被忽略
+func (a *Aggregates) Add(record UsageRecord) {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	day := utcDay(record.Timestamp)
+	if day.After(a.day) {
+		a.day = day
+		a.today = DailyTotals{}
+	}
+	if day.Equal(a.day) {
+		a.today.Requests++
+		if !record.Success {
+			a.today.Errors++
+		}
+		a.today.InputTokens += int64(record.InputTokens)
+		a.today.OutputTokens += int64(record.OutputTokens)
+		a.today.CostUSD += record.CostUSD
+	}
+
+	hour := record.Timestamp.UTC().Truncate(time.Hour)
+	bucket := &a.hours[hourIndex(hour)]
+	if hour.After(bucket.hour) {
+		*bucket = hourBucket{hour: hour}
+	}
+	if hour.Equal(bucket.hour) {
+		bucket.requests++
+		if !record.Success {
+			bucket.errors++
+		}
+	}
+}
+
+// Today 返回当日（UTC）的用量合计
+func (a *Aggregates) Today(now time.Time) DailyTotals {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	day := utcDay(now)
+	totals := DailyTotals{Date: day.Format("2006-01-02")}
+	if day.Equal(a.day) {
+		totals.Requests = a.today.Requests
+		totals.Errors = a.today.Errors
+		totals.InputTokens = a.today.InputTokens
+		totals.OutputTokens = a.today.OutputTokens
+		totals.CostUSD = a.today.CostUSD
+	}
+	return totals
+}
+
+// ErrorTrend 返回最近24小时（含当前小时）每小时的请求数与错误率，按时间从旧到新
+func (a *Aggregates) ErrorTrend(now time.Time) []HourlyPoint {
+	a.mutex.Lock()
+	defer a.mutex.Unlock()
+
+	current := now.UTC().Truncate(time.Hour)
+	points := make([]HourlyPoint, 0, trendHours)
+	for i := trendHours - 1; i >= 0; i-- {
+		hour := current.Add(-time.Duration(i) * time.Hour)
+		point := HourlyPoint{Hour: hour}
+		if bucket := a.hours[hourIndex(hour)]; bucket.hour.Equal(hour) {
+			point.Requests = bucket.requests
+			point.Errors = bucket.errors
+			if bucket.requests > 0 {
+				point.ErrorRate = float64(bucket.errors) / float64(bucket.requests)
+			}
+		}
+		points = append(points, point)
+	}
+	return points
+}
+
+// hourIndex 小时在环形缓冲中的位置
+func hourIndex(hour time.Time) int {
+	return int(hour.Unix() / 3600 % trendHours)
+}
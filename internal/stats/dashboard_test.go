@@ -0,0 +1,54 @@
+package stats
+
+import (
+	"testing"
+	"time"
+)
+
+func TestAggregates(t *testing.T) {
+	now := time.Date(2024, 6, 2, 10, 30, 0, 0, time.UTC)
+	recorder := NewRecorder(0)
+	aggregates := NewAggregates(recorder)
+
+	recorder.Record(UsageRecord{Timestamp: now.Add(-26 * time.Hour), Success: true, InputTokens: 999}) // 超出趋势窗口
+	recorder.Record(UsageRecord{Timestamp: now.Add(-11 * time.Hour), Success: false})                  // 前一天 23:30
+	recorder.Record(UsageRecord{Timestamp: now.Add(-time.Hour), Success: true, InputTokens: 100, OutputTokens: 20, CostUSD: 0.5})
+	recorder.Record(UsageRecord{Timestamp: now, Success: true, InputTokens: 10, OutputTokens: 2, CostUSD: 0.05})
+	recorder.Record(UsageRecord{Timestamp: now, Success: false})
+
+	today := aggregates.Today(now)
+	if today.Date != "2024-06-02" || today.Requests != 3 || today.Errors != 1 || today.InputTokens != 110 || today.OutputTokens != 22 {
+		t.Errorf("today = %+v", today)
+	}
+
+	trend := aggregates.ErrorTrend(now)
+	if len(trend) != 24 {
+		t.Fatalf("len(trend) = %d, want 24", len(trend))
+	}
+	current := trend[23]
+	if !current.Hour.Equal(now.Truncate(time.Hour)) || current.Requests != 2 || current.ErrorRate != 0.5 {
+		t.Errorf("current hour = %+v", current)
+	}
+	if previous := trend[22]; previous.Requests != 1 || previous.Errors != 0 {
+		t.Errorf("previous hour = %+v", previous)
+	}
+	if yesterday := trend[12]; yesterday.Requests != 1 || yesterday.ErrorRate != 1 {
+		t.Errorf("hour before midnight = %+v", yesterday)
+	}
+
+	// 跨天后当日合计从零开始
+	if next := aggregates.Today(now.Add(24 * time.Hour)); next.Requests != 0 || next.Date != "2024-06-03" {
+		t.Errorf("next day = %+v", next)
+	}
+}
+
+func TestNewAggregatesSeedsFromRecorder(t *testing.T) {
+	now := time.Now()
+	recorder := NewRecorder(0)
+	recorder.Record(UsageRecord{Timestamp: now, Success: true, InputTokens: 5})
+
+	aggregates := NewAggregates(recorder)
+	if today := aggregates.Today(now); today.Requests != 1 || today.InputTokens != 5 {
+		t.Errorf("today = %+v, want the existing record counted", today)
+	}
+}
@@ -0,0 +1,162 @@
+package stats
+
+import (
+	"math"
+	"sort"
+	"time"
+)
+
+// ForecastTotals 预测期内的用量合计
+type ForecastTotals struct {
+	Tokens  int64   `json:"tokens"`
+	CostUSD float64 `json:"cost_usd"`
+}
+
+// ForecastReport 单个维度（Gateway Key 或上游账号）的用量预测
+type ForecastReport struct {
+	ID string `json:"id"`
+
+	// HistoryDays 参与计算的完整历史天数（从该分组首条记录所在日起算）
+	HistoryDays int `json:"history_days"`
+
+	// AvgDailyTokens/AvgDailyCostUSD 最近7天的日均用量（移动平均）
+	AvgDailyTokens  float64 `json:"avg_daily_tokens"`
+	AvgDailyCostUSD float64 `json:"avg_daily_cost_usd"`
+
+	// Seasonal 是否应用了按星期的季节性系数（至少需要两周历史）
+	Seasonal bool `json:"seasonal"`
+
+	Next7Days  ForecastTotals `json:"next_7_days"`
+	Next30Days ForecastTotals `json:"next_30_days"`
+}
+
+// 预测参数
+const (
+	forecastMovingAverageDays = 7
+	forecastMinSeasonalDays   = 14
+)
+
+// dailyUsage 单日用量
+type dailyUsage struct {
+	tokens float64
+	cost   float64
+}
+
+// ComputeForecasts 根据最近 historyDays 个完整自然日（UTC）的用量预测未来7天和30天的用量
+// 基线为最近7天的移动平均，历史足够时按星期几的用量占比进行季节性修正
+func ComputeForecasts(records []UsageRecord, now time.Time, historyDays int, groupBy func(*UsageRecord) string) []*ForecastReport {
+	today := utcDay(now)
+	since := today.AddDate(0, 0, -historyDays)
+
+	days := make(map[string][]dailyUsage)
+	firstDay := make(map[string]int)
+	for i := range records {
+		record := &records[i]
+		id := groupBy(record)
+		if id == "" {
+			continue
+		}
+
+		day := utcDay(record.Timestamp)
+		if day.Before(since) || !day.Before(today) {
+			continue
+		}
+
+		series, exists := days[id]
+		if !exists {
+			series = make([]dailyUsage, historyDays)
+			days[id] = series
+			firstDay[id] = historyDays
+		}
+
+		index := int(day.Sub(since).Hours() / 24)
+		series[index].tokens += float64(record.InputTokens + record.OutputTokens)
+		series[index].cost += record.CostUSD
+		if index < firstDay[id] {
+			firstDay[id] = index
+		}
+	}
+
+	result := make([]*ForecastReport, 0, len(days))
+	for id, series := range days {
+		// 只使用首条记录之后的历史，避免新Key被之前的空白天数拉低
+		history := series[firstDay[id]:]
+		start := since.AddDate(0, 0, firstDay[id])
+		result = append(result, forecastSeries(id, history, start, today))
+	}
+
+	sort.Slice(result, func(i, j int) bool {
+		return result[i].ID < result[j].ID
+	})
+	return result
+}
+
+// forecastSeries 预测单个分组，history[0] 对应 start 当天
+func forecastSeries(id string, history []dailyUsage, start, today time.Time) *ForecastReport {
+	report := &ForecastReport{ID: id, HistoryDays: len(history)}
+
+	window := history
+	if len(window) > forecastMovingAverageDays {
+		window = window[len(window)-forecastMovingAverageDays:]
+	}
+	for _, day := range window {
+		report.AvgDailyTokens += day.tokens
+		report.AvgDailyCostUSD += day.cost
+	}
+	report.AvgDailyTokens /= float64(len(window))
+	report.AvgDailyCostUSD /= float64(len(window))
+
+	factors := [7]float64{1, 1, 1, 1, 1, 1, 1}
+	if len(history) >= forecastMinSeasonalDays {
+		factors = weekdayFactors(history, start)
+		report.Seasonal = true
+	}
+
+	var tokens7, cost7, tokens30, cost30 float64
+	for i := 0; i < 30; i++ {
+		factor := factors[today.AddDate(0, 0, i).Weekday()]
+		tokens := report.AvgDailyTokens * factor
+		cost := report.AvgDailyCostUSD * factor
+		if i < 7 {
+			tokens7 += tokens
+			cost7 += cost
+		}
+		tokens30 += tokens
+		cost30 += cost
+	}
+
+	report.Next7Days = ForecastTotals{Tokens: int64(math.Round(tokens7)), CostUSD: cost7}
+	report.Next30Days = ForecastTotals{Tokens: int64(math.Round(tokens30)), CostUSD: cost30}
+	return report
+}
+
+// weekdayFactors 计算每个星期几的用量相对于整体日均用量的系数
+func weekdayFactors(history []dailyUsage, start time.Time) [7]float64 {
+	var sums [7]float64
+	var counts [7]int
+	var total float64
+	for i, day := range history {
+		weekday := start.AddDate(0, 0, i).Weekday()
+		sums[weekday] += day.tokens
+		counts[weekday]++
+		total += day.tokens
+	}
+
+	factors := [7]float64{1, 1, 1, 1, 1, 1, 1}
+	overall := total / float64(len(history))
+	if overall <= 0 {
+		return factors
+	}
+	for weekday := range factors {
+		if counts[weekday] > 0 {
+			factors[weekday] = sums[weekday] / float64(counts[weekday]) / overall
+		}
+	}
+	return factors
+}
+
+// utcDay UTC自然日起点
+func utcDay(t time.Time) time.Time {
+	t = t.UTC()
+	return time.Date(t.Year(), t.Month(), t.Day(), 0, 0, 0, 0, time.UTC)
+}
@@ -0,0 +1,61 @@
+package stats
+
+import (
+	"math"
+	"testing"
+	"time"
+)
+
+func TestComputeForecasts(t *testing.T) {
+	// 2024-06-17 是星期一
+	now := time.Date(2024, 6, 17, 10, 0, 0, 0, time.UTC)
+	var records []UsageRecord
+
+	// key-a：两周历史，只有工作日有用量
+	for i := 1; i <= 14; i++ {
+		day := now.AddDate(0, 0, -i)
+		if day.Weekday() == time.Saturday || day.Weekday() == time.Sunday {
+			continue
+		}
+		records = append(records, UsageRecord{GatewayKeyID: "key-a", Timestamp: day, InputTokens: 600, OutputTokens: 400, CostUSD: 1})
+	}
+
+	// key-b：最近3天每天300 token
+	for i := 1; i <= 3; i++ {
+		records = append(records, UsageRecord{GatewayKeyID: "key-b", Timestamp: now.AddDate(0, 0, -i), InputTokens: 300, CostUSD: 0.3})
+	}
+
+	// 当天和窗口之外的记录不参与计算
+	records = append(records,
+		UsageRecord{GatewayKeyID: "key-b", Timestamp: now, InputTokens: 100000},
+		UsageRecord{GatewayKeyID: "key-b", Timestamp: now.AddDate(0, 0, -60), InputTokens: 100000},
+	)
+
+	reports := ComputeForecasts(records, now, 28, GroupByKey)
+	if len(reports) != 2 {
+		t.Fatalf("期望2个报告, 实际 %d", len(reports))
+	}
+
+	a := reports[0]
+	if a.ID != "key-a" || !a.Seasonal || a.HistoryDays != 14 {
+		t.Fatalf("key-a 报告异常: %+v", a)
+	}
+	// 工作日系数1.4、周末0：未来7天5个工作日，未来30天22个工作日
+	if a.Next7Days.Tokens != 5000 || a.Next30Days.Tokens != 22000 {
+		t.Errorf("key-a 预测 = %+v / %+v, 期望 5000 / 22000", a.Next7Days, a.Next30Days)
+	}
+	if math.Abs(a.Next7Days.CostUSD-5) > 1e-9 {
+		t.Errorf("key-a 7天费用 = %v, 期望 5", a.Next7Days.CostUSD)
+	}
+
+	b := reports[1]
+	if b.ID != "key-b" || b.Seasonal || b.HistoryDays != 3 {
+		t.Fatalf("key-b 报告异常: %+v", b)
+	}
+	if b.AvgDailyTokens != 300 || b.Next7Days.Tokens != 2100 || b.Next30Days.Tokens != 9000 {
+		t.Errorf("key-b 预测 = %+v", b)
+	}
+	if math.Abs(b.Next30Days.CostUSD-9) > 1e-9 {
+		t.Errorf("key-b 30天费用 = %v, 期望 9", b.Next30Days.CostUSD)
+	}
+}
@@ -0,0 +1,71 @@
+package stats
+
+import "time"
+
+// HeatmapCell 一周中某天某小时的用量
+type HeatmapCell struct {
+	Weekday  int     `json:"weekday"` // 0=周日 ... 6=周六
+	Hour     int     `json:"hour"`    // 0-23
+	Requests int64   `json:"requests"`
+	Errors   int64   `json:"errors"`
+	Tokens   int64   `json:"tokens"` // 输入+输出token
+	CostUSD  float64 `json:"cost_usd"`
+}
+
+// Heatmap 按星期×小时汇总的用量，MaxRequests/MaxCostUSD 用于前端换算颜色强度
+type Heatmap struct {
+	Cells       []HeatmapCell `json:"cells"` // 168个格子，按 weekday*24+hour 排列
+	Requests    int64         `json:"requests"`
+	CostUSD     float64       `json:"cost_usd"`
+	MaxRequests int64         `json:"max_requests"`
+	MaxCostUSD  float64       `json:"max_cost_usd"`
+}
+
+// ComputeHeatmapFromRollups 由小时汇总计算热力图，时间桶按起始时间换算到 loc 时区（非整点时区按桶起始时间所在的小时计入）
+func ComputeHeatmapFromRollups(buckets []RollupBucket, loc *time.Location) *Heatmap {
+	heatmap := newHeatmap()
+	for i := range buckets {
+		bucket := &buckets[i]
+		heatmap.add(bucket.Start, loc, bucket.Requests, bucket.Errors, bucket.InputTokens+bucket.OutputTokens, bucket.CostUSD)
+	}
+	heatmap.finish()
+	return heatmap
+}
+
+// newHeatmap 创建168个空格子
+func newHeatmap() *Heatmap {
+	heatmap := &Heatmap{Cells: make([]HeatmapCell, 7*24)}
+	for i := range heatmap.Cells {
+		heatmap.Cells[i].Weekday = i / 24
+		heatmap.Cells[i].Hour = i % 24
+	}
+	return heatmap
+}
+
+// add 把用量计入时间点在 loc 时区下所在的格子
+func (h *Heatmap) add(t time.Time, loc *time.Location, requests, errors, tokens int64, cost float64) {
+	if loc == nil {
+		loc = time.UTC
+	}
+	local := t.In(loc)
+	cell := &h.Cells[int(local.Weekday())*24+local.Hour()]
+	cell.Requests += requests
+	cell.Errors += errors
+	cell.Tokens += tokens
+	cell.CostUSD += cost
+}
+
+// finish 计算合计和最大值
+func (h *Heatmap) finish() {
+	for i := range h.Cells {
+		cell := &h.Cells[i]
+		h.Requests += cell.Requests
+		h.CostUSD += cell.CostUSD
+		if cell.Requests > h.MaxRequests {
+			h.MaxRequests = cell.Requests
+		}
+		if cell.CostUSD > h.MaxCostUSD {
+			h.MaxCostUSD = cell.CostUSD
+		}
+	}
+}
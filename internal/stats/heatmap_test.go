@@ -0,0 +1,36 @@
+package stats
+
+import (
+	"testing"
+	"time"
+)
+
+func TestComputeHeatmapFromRollups(t *testing.T) {
+	// 2024-01-01 是周一
+	monday9 := time.Date(2024, 1, 1, 9, 0, 0, 0, time.UTC)
+	buckets := []RollupBucket{
+		{Start: monday9, Requests: 2, Errors: 1, InputTokens: 100, OutputTokens: 50, CostUSD: 0.1},
+		{Start: monday9.AddDate(0, 0, 6), Requests: 1, CostUSD: 0.5}, // 周日
+	}
+
+	heatmap := ComputeHeatmapFromRollups(buckets, nil)
+	if len(heatmap.Cells) != 168 {
+		t.Fatalf("got %d cells, want 168", len(heatmap.Cells))
+	}
+	cell := heatmap.Cells[1*24+9]
+	if cell.Weekday != 1 || cell.Hour != 9 || cell.Requests != 2 || cell.Errors != 1 || cell.Tokens != 150 {
+		t.Errorf("Monday 09:00 cell = %+v", cell)
+	}
+	if sunday := heatmap.Cells[9]; sunday.Requests != 1 || sunday.CostUSD != 0.5 {
+		t.Errorf("Sunday 09:00 cell = %+v", sunday)
+	}
+	if heatmap.Requests != 3 || heatmap.MaxRequests != 2 || heatmap.MaxCostUSD != 0.5 {
+		t.Errorf("totals = %d requests, max %d requests, max $%.2f", heatmap.Requests, heatmap.MaxRequests, heatmap.MaxCostUSD)
+	}
+
+	// 按指定时区划分星期和小时：UTC 周一 09:00 是东京周一 18:00
+	tokyo := time.FixedZone("JST", 9*3600)
+	if cell := ComputeHeatmapFromRollups(buckets[:1], tokyo).Cells[1*24+18]; cell.Requests != 2 {
+		t.Errorf("Tokyo Monday 18:00 cell = %+v, want 2 requests", cell)
+	}
+}
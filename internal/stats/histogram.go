@@ -0,0 +1,111 @@
+package stats
+
+import (
+	"math"
+	"sort"
+)
+
+// latencyBounds 延迟直方图各区间的上界（毫秒），每个区间比上一个宽约20%，覆盖1毫秒到1小时，
+// 超过最后一个上界的延迟计入溢出区间
+var latencyBounds = func() []int64 {
+	var bounds []int64
+	for bound := 1.0; bound < 3600000; bound *= 1.2 {
+		value := int64(math.Ceil(bound))
+		if len(bounds) == 0 || value > bounds[len(bounds)-1] {
+			bounds = append(bounds, value)
+		}
+	}
+	return append(bounds, 3600000)
+}()
+
+// LatencyHistogram 按固定区间计数的延迟直方图。区间边界固定，任意时间桶的直方图可以直接相加，
+// 合并后的分位数与逐条记录计算的误差不超过所在区间的宽度（约20%）
+type LatencyHistogram struct {
+	Counts []int64 // 长度为 len(latencyBounds)+1，第一次计入时分配
+	Count  int64
+	Sum    int64
+}
+
+// LatencySummary 延迟的平均值和分位数（毫秒）
+type LatencySummary struct {
+	Avg float64 `json:"avg"`
+	P50 float64 `json:"p50"`
+	P90 float64 `json:"p90"`
+	P95 float64 `json:"p95"`
+	P99 float64 `json:"p99"`
+}
+
+// Add 计入一个延迟
+func (h *LatencyHistogram) Add(ms int64) {
+	if ms < 0 {
+		ms = 0
+	}
+	if h.Counts == nil {
+		h.Counts = make([]int64, len(latencyBounds)+1)
+	}
+	h.Counts[sort.Search(len(latencyBounds), func(i int) bool { return latencyBounds[i] >= ms })]++
+	h.Count++
+	h.Sum += ms
+}
+
+// Merge 把另一个直方图的计数加到当前直方图
+func (h *LatencyHistogram) Merge(other *LatencyHistogram) {
+	if other.Count == 0 {
+		return
+	}
+	if h.Counts == nil {
+		h.Counts = make([]int64, len(latencyBounds)+1)
+	}
+	for i, count := range other.Counts {
+		h.Counts[i] += count
+	}
+	h.Count += other.Count
+	h.Sum += other.Sum
+}
+
+// clone 返回不共享计数数组的副本
+func (h LatencyHistogram) clone() LatencyHistogram {
+	if h.Counts != nil {
+		h.Counts = append([]int64(nil), h.Counts...)
+	}
+	return h
+}
+
+// Percentile 返回第p百分位（0-100）的延迟，在所在区间内按线性插值估算，没有数据时返回0
+func (h *LatencyHistogram) Percentile(p float64) float64 {
+	if h.Count == 0 {
+		return 0
+	}
+	rank := p / 100 * float64(h.Count)
+	var cumulative int64
+	for i, count := range h.Counts {
+		if count == 0 || float64(cumulative+count) < rank {
+			cumulative += count
+			continue
+		}
+		if i == len(latencyBounds) {
+			return float64(latencyBounds[i-1]) // 溢出区间没有上界
+		}
+		lower := 0.0
+		if i > 0 {
+			lower = float64(latencyBounds[i-1])
+		}
+		fraction := (rank - float64(cumulative)) / float64(count)
+		return lower + fraction*(float64(latencyBounds[i])-lower)
+	}
+	return float64(latencyBounds[len(latencyBounds)-1])
+}
+
+// Summary 返回平均值和 P50/P90/P95/P99，没有数据时返回nil
+func (h *LatencyHistogram) Summary() *LatencySummary {
+	if h.Count == 0 {
+		return nil
+	}
+	return &LatencySummary{
+		Avg: float64(h.Sum) / float64(h.Count),
+		P50: h.Percentile(50),
+		P90: h.Percentile(90),
+		P95: h.Percentile(95),
+		P99: h.Percentile(99),
+	}
+}
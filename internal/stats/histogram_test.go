@@ -0,0 +1,53 @@
+package stats
+
+import (
+	"math"
+	"reflect"
+	"testing"
+)
+
+func TestLatencyHistogram_Percentiles(t *testing.T) {
+	var all, low, high LatencyHistogram
+	for ms := int64(1); ms <= 1000; ms++ {
+		all.Add(ms)
+		if ms <= 500 {
+			low.Add(ms)
+		} else {
+			high.Add(ms)
+		}
+	}
+
+	summary := all.Summary()
+	for _, tt := range []struct {
+		name string
+		got  float64
+		want float64
+	}{
+		{"avg", summary.Avg, 500.5},
+		{"p50", summary.P50, 500},
+		{"p90", summary.P90, 900},
+		{"p95", summary.P95, 950},
+		{"p99", summary.P99, 990},
+	} {
+		// 误差不超过所在区间的宽度
+		if math.Abs(tt.got-tt.want) > tt.want*0.2 {
+			t.Errorf("%s = %v, want about %v", tt.name, tt.got, tt.want)
+		}
+	}
+	if summary.P50 > summary.P90 || summary.P90 > summary.P95 || summary.P95 > summary.P99 {
+		t.Errorf("percentiles not monotonic: %+v", summary)
+	}
+
+	// 分别计入的直方图合并后与整体计入的结果相同
+	var merged LatencyHistogram
+	merged.Merge(&low)
+	merged.Merge(&high)
+	if !reflect.DeepEqual(merged, all) {
+		t.Errorf("merged histogram differs from the combined one")
+	}
+
+	var empty LatencyHistogram
+	if empty.Summary() != nil || empty.Percentile(50) != 0 {
+		t.Errorf("empty histogram should have no summary")
+	}
+}
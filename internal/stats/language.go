@@ -0,0 +1,213 @@
+package stats
+
+import (
+	"sort"
+	"strings"
+	"unicode"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// LanguageUnknown 无法判断语言时使用的代码（ISO 639-2 "undetermined"）
+const LanguageUnknown = "und"
+
+// 语言检测参数：只检查提示词开头的一部分字符，保证每个请求的开销固定
+const (
+	languageSampleRunes = 2000
+	languageMinLetters  = 3
+)
+
+// languageScripts 单一文字对应的语言（汉字和假名单独处理）
+var languageScripts = []struct {
+	table    *unicode.RangeTable
+	language string
+}{
+	{unicode.Hangul, "ko"},
+	{unicode.Cyrillic, "ru"},
+	{unicode.Arabic, "ar"},
+	{unicode.Devanagari, "hi"},
+	{unicode.Thai, "th"},
+	{unicode.Greek, "el"},
+	{unicode.Hebrew, "he"},
+}
+
+// latinStopwords 拉丁字母语言的高频词，按顺序优先（得分相同时取靠前的语言）
+var latinStopwords = []struct {
+	language string
+	words    map[string]bool
+}{
+	{"en", stopwordSet("the and is are of to in that it for with you this what how please can be not")},
+	{"es", stopwordSet("el los las que y es por para una con cómo qué del se lo más pero está")},
+	{"fr", stopwordSet("le les des et est une pour dans avec vous je du ce pas sur qui au")},
+	{"de", stopwordSet("der die das und ist nicht ein eine ich sie zu mit den auf für wie von")},
+	{"pt", stopwordSet("o os que e é não um uma para com do da em você isso mais")},
+	{"it", stopwordSet("il di che è non un una per con del della sono come gli questo")},
+}
+
+// stopwordSet 将空格分隔的词表转换为集合
+func stopwordSet(words string) map[string]bool {
+	set := make(map[string]bool)
+	for _, word := range strings.Fields(words) {
+		set[word] = true
+	}
+	return set
+}
+
+// DetectLanguage 判断文本的主要语言，返回 ISO 639-1 代码，无法判断时返回 LanguageUnknown
+// 先按 Unicode 文字统计，拉丁字母文本再按常见虚词打分，只用于流量构成统计，不追求精确
+func DetectLanguage(text string) string {
+	var han, kana, latin, letters int
+	scripts := make([]int, len(languageScripts))
+
+	sampled := 0
+	for _, r := range text {
+		if sampled >= languageSampleRunes {
+			break
+		}
+		sampled++
+		if !unicode.IsLetter(r) {
+			continue
+		}
+		letters++
+
+		switch {
+		case unicode.Is(unicode.Han, r):
+			han++
+		case unicode.In(r, unicode.Hiragana, unicode.Katakana):
+			kana++
+		case unicode.Is(unicode.Latin, r):
+			latin++
+		default:
+			for i, script := range languageScripts {
+				if unicode.Is(script.table, r) {
+					scripts[i]++
+					break
+				}
+			}
+		}
+	}
+	if letters < languageMinLetters {
+		return LanguageUnknown
+	}
+
+	// 日文混用汉字和假名，假名占比达到10%即视为日文
+	best, bestCount := LanguageUnknown, 0
+	if han+kana > 0 {
+		best, bestCount = "zh", han+kana
+		if kana*10 >= han+kana {
+			best = "ja"
+		}
+	}
+	for i, count := range scripts {
+		if count > bestCount {
+			best, bestCount = languageScripts[i].language, count
+		}
+	}
+	if latin > bestCount {
+		return detectLatinLanguage(text)
+	}
+	return best
+}
+
+// detectLatinLanguage 按高频词命中数判断拉丁字母语言
+func detectLatinLanguage(text string) string {
+	if len(text) > languageSampleRunes*4 {
+		text = text[:languageSampleRunes*4]
+	}
+	words := strings.FieldsFunc(strings.ToLower(text), func(r rune) bool {
+		return !unicode.IsLetter(r)
+	})
+
+	best, bestScore := LanguageUnknown, 0
+	for _, candidate := range latinStopwords {
+		score := 0
+		for _, word := range words {
+			if candidate.words[word] {
+				score++
+			}
+		}
+		if score > bestScore {
+			best, bestScore = candidate.language, score
+		}
+	}
+	return best
+}
+
+// PromptText 提取请求中用户消息的文本内容（忽略图片、工具调用等非文本块）
+func PromptText(req *types.UnifiedRequest) string {
+	var builder strings.Builder
+	for _, message := range req.Messages {
+		if builder.Len() >= languageSampleRunes*4 {
+			break // 超出检测采样长度的内容不再拼接
+		}
+		if message.Role != "user" {
+			continue
+		}
+		switch content := message.Content.(type) {
+		case string:
+			builder.WriteString(content)
+			builder.WriteByte('\n')
+		case []interface{}:
+			for _, item := range content {
+				block, ok := item.(map[string]interface{})
+				if !ok || block["type"] != "text" {
+					continue
+				}
+				if text, ok := block["text"].(string); ok {
+					builder.WriteString(text)
+					builder.WriteByte('\n')
+				}
+			}
+		}
+	}
+	return builder.String()
+}
+
+// GroupByLanguage 按提示词语言分组（未检测的记录归为 LanguageUnknown）
+func GroupByLanguage(record *UsageRecord) string {
+	if record.Language == "" {
+		return LanguageUnknown
+	}
+	return record.Language
+}
+
+// LanguageStats 单个语言的流量统计
+type LanguageStats struct {
+	Language     string  `json:"language"`
+	Requests     int     `json:"requests"`
+	Share        float64 `json:"share"` // 请求数占比
+	InputTokens  int64   `json:"input_tokens"`
+	OutputTokens int64   `json:"output_tokens"`
+	CostUSD      float64 `json:"cost_usd"`
+}
+
+// ComputeLanguageStats 按语言汇总请求数、token和费用，按请求数从多到少排序
+func ComputeLanguageStats(records []UsageRecord) []*LanguageStats {
+	byLanguage := make(map[string]*LanguageStats)
+	for i := range records {
+		record := &records[i]
+		language := GroupByLanguage(record)
+		entry, exists := byLanguage[language]
+		if !exists {
+			entry = &LanguageStats{Language: language}
+			byLanguage[language] = entry
+		}
+		entry.Requests++
+		entry.InputTokens += int64(record.InputTokens)
+		entry.OutputTokens += int64(record.OutputTokens)
+		entry.CostUSD += record.CostUSD
+	}
+
+	result := make([]*LanguageStats, 0, len(byLanguage))
+	for _, entry := range byLanguage {
+		entry.Share = float64(entry.Requests) / float64(len(records))
+		result = append(result, entry)
+	}
+	sort.Slice(result, func(i, j int) bool {
+		if result[i].Requests != result[j].Requests {
+			return result[i].Requests > result[j].Requests
+		}
+		return result[i].Language < result[j].Language
+	})
+	return result
+}
@@ -0,0 +1,74 @@
+package stats
+
+import (
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestDetectLanguage(t *testing.T) {
+	tests := []struct {
+		text string
+		want string
+	}{
+		{"How do I configure the cache for this application?", "en"},
+		{"¿Cómo puedo configurar el servidor para que use la caché?", "es"},
+		{"Pouvez-vous m'expliquer comment fonctionne le cache dans cette application ?", "fr"},
+		{"Wie funktioniert das? Ich verstehe die Frage nicht.", "de"},
+		{"请解释一下这个函数的作用", "zh"},
+		{"この関数の役割を説明してください", "ja"},
+		{"이 함수가 무엇을 하는지 설명해 주세요", "ko"},
+		{"Объясни, что делает эта функция", "ru"},
+		{"ok", LanguageUnknown},
+		{"12345 + 67890", LanguageUnknown},
+	}
+
+	for _, tt := range tests {
+		if got := DetectLanguage(tt.text); got != tt.want {
+			t.Errorf("DetectLanguage(%q) = %s, want %s", tt.text, got, tt.want)
+		}
+	}
+}
+
+func TestPromptText(t *testing.T) {
+	req := &types.UnifiedRequest{
+		Messages: []types.Message{
+			{Role: "system", Content: "You are a helpful assistant."},
+			{Role: "user", Content: []interface{}{
+				map[string]interface{}{"type": "image", "source": map[string]interface{}{}},
+				map[string]interface{}{"type": "text", "text": "这张图片里有什么？"},
+			}},
+			{Role: "assistant", Content: "There is a cat in the picture."},
+		},
+	}
+
+	if got := PromptText(req); got != "这张图片里有什么？\n" {
+		t.Errorf("PromptText() = %q", got)
+	}
+	if got := DetectLanguage(PromptText(req)); got != "zh" {
+		t.Errorf("language = %s, want zh (system and assistant messages ignored)", got)
+	}
+}
+
+func TestComputeLanguageStats(t *testing.T) {
+	records := []UsageRecord{
+		{Language: "zh", InputTokens: 100, OutputTokens: 50, CostUSD: 0.1},
+		{Language: "en", InputTokens: 10, OutputTokens: 5},
+		{Language: "zh", InputTokens: 200, OutputTokens: 100, CostUSD: 0.2},
+		{},
+	}
+
+	result := ComputeLanguageStats(records)
+	if len(result) != 3 {
+		t.Fatalf("len(result) = %d, want 3", len(result))
+	}
+
+	zh := result[0]
+	if zh.Language != "zh" || zh.Requests != 2 || zh.InputTokens != 300 || zh.OutputTokens != 150 || zh.Share != 0.5 {
+		t.Errorf("zh stats = %+v", zh)
+	}
+	// 请求数相同时按语言代码排序，未检测的记录归为 und
+	if result[1].Language != "en" || result[2].Language != LanguageUnknown {
+		t.Errorf("order = %s, %s", result[1].Language, result[2].Language)
+	}
+}
@@ -0,0 +1,63 @@
+package stats
+
+import "sort"
+
+// OrgNone 不属于任何组织的Key产生的用量归入的分组
+const OrgNone = "none"
+
+// OrgStats 单个组织的用量统计
+type OrgStats struct {
+	OrgID        string  `json:"org_id"`
+	Keys         int     `json:"keys"` // 产生用量的Key数量
+	Requests     int     `json:"requests"`
+	Errors       int     `json:"errors"`
+	InputTokens  int64   `json:"input_tokens"`
+	OutputTokens int64   `json:"output_tokens"`
+	CostUSD      float64 `json:"cost_usd"`
+}
+
+// ComputeOrgStats 按Key所属的组织汇总请求数、错误数、token和费用（不属于组织的归为 OrgNone），
+// 按费用从高到低排序
+func ComputeOrgStats(records []UsageRecord) []*OrgStats {
+	byOrg := make(map[string]*OrgStats)
+	keys := make(map[string]map[string]bool)
+	for i := range records {
+		record := &records[i]
+		orgID := record.OrgID
+		if orgID == "" {
+			orgID = OrgNone
+		}
+		entry, exists := byOrg[orgID]
+		if !exists {
+			entry = &OrgStats{OrgID: orgID}
+			byOrg[orgID] = entry
+			keys[orgID] = make(map[string]bool)
+		}
+		if record.GatewayKeyID != "" && !keys[orgID][record.GatewayKeyID] {
+			keys[orgID][record.GatewayKeyID] = true
+			entry.Keys++
+		}
+		entry.Requests++
+		if !record.Success {
+			entry.Errors++
+		}
+		entry.InputTokens += int64(record.InputTokens)
+		entry.OutputTokens += int64(record.OutputTokens)
+		entry.CostUSD += record.CostUSD
+	}
+
+	result := make([]*OrgStats, 0, len(byOrg))
+	for _, entry := range byOrg {
+		result = append(result, entry)
+	}
+	sort.Slice(result, func(i, j int) bool {
+		if result[i].CostUSD != result[j].CostUSD {
+			return result[i].CostUSD > result[j].CostUSD
+		}
+		if result[i].Requests != result[j].Requests {
+			return result[i].Requests > result[j].Requests
+		}
+		return result[i].OrgID < result[j].OrgID
+	})
+	return result
+}
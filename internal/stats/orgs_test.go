@@ -0,0 +1,30 @@
+package stats
+
+import "testing"
+
+func TestComputeOrgStats(t *testing.T) {
+	records := []UsageRecord{
+		{OrgID: "org-ml", GatewayKeyID: "key-1", Success: true, InputTokens: 100, OutputTokens: 50, CostUSD: 0.5},
+		{OrgID: "org-ml", GatewayKeyID: "key-2", Success: false},
+		{OrgID: "org-ml", GatewayKeyID: "key-1", Success: true, InputTokens: 10, OutputTokens: 5, CostUSD: 0.1},
+		{OrgID: "org-web", GatewayKeyID: "key-3", Success: true, CostUSD: 0.2},
+		{GatewayKeyID: "key-4", Success: true, CostUSD: 0.05},
+	}
+
+	orgs := ComputeOrgStats(records)
+	if len(orgs) != 3 {
+		t.Fatalf("got %d orgs, want 3", len(orgs))
+	}
+	ml := orgs[0]
+	if ml.OrgID != "org-ml" || ml.Keys != 2 || ml.Requests != 3 || ml.Errors != 1 || ml.InputTokens != 110 || ml.OutputTokens != 55 {
+		t.Errorf("org-ml stats = %+v", ml)
+	}
+	if orgs[1].OrgID != "org-web" || orgs[2].OrgID != OrgNone {
+		t.Errorf("order = %s, %s, want org-web, %s (sorted by cost)", orgs[1].OrgID, orgs[2].OrgID, OrgNone)
+	}
+
+	filter := Filter{OrgID: "org-web"}
+	if !filter.Match(&records[3]) || filter.Match(&records[0]) || filter.Match(&records[4]) {
+		t.Error("org filter should only match records of org-web")
+	}
+}
@@ -0,0 +1,226 @@
+package stats
+
+import (
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// UsageRecord 单次代理请求的使用记录
+type UsageRecord struct {
+	RequestID        string           `json:"request_id"`
+	Timestamp        time.Time        `json:"timestamp"`
+	GatewayKeyID     string           `json:"gateway_key_id"`
+	OrgID            string           `json:"org_id,omitempty"` // Key所属的组织
+	UpstreamID       string           `json:"upstream_id,omitempty"`
+	Pool             string           `json:"pool,omitempty"` // 上游账号所属的账号池（提供商/账号池名称）
+	Provider         types.Provider   `json:"provider,omitempty"`
+	Model            string           `json:"model"`
+	RequestedModel   string           `json:"requested_model,omitempty"` // 客户端请求的模型名，模型路由或规范化后与 Model 不同
+	Endpoint         string           `json:"endpoint"`
+	Language         string           `json:"language,omitempty"` // 提示词的主要语言（ISO 639-1）
+	Stream           bool             `json:"stream"`
+	Success          bool             `json:"success"`
+	ErrorType        string           `json:"error_type,omitempty"`
+	ErrorClass       types.ErrorClass `json:"error_class,omitempty"` // 上游错误的分类（auth、quota、overloaded 等），网关自身拒绝的请求为空
+	LatencyMs        int64            `json:"latency_ms"`
+	QueueTimeMs      int64            `json:"queue_time_ms,omitempty"` // 所有账号都达到并发上限时排队等待的时间，包含在 LatencyMs 中
+	InputTokens      int              `json:"input_tokens"`
+	OutputTokens     int              `json:"output_tokens"`
+	CacheReadTokens  int              `json:"cache_read_tokens,omitempty"`  // 命中提示词缓存的输入token
+	CacheWriteTokens int              `json:"cache_write_tokens,omitempty"` // 写入提示词缓存的输入token
+	CostUSD          float64          `json:"cost_usd"`
+	FinishReason     string           `json:"finish_reason,omitempty"` // 规范化的结束原因：stop、length、tool_calls 或 content_filter
+
+	// 流式请求在流结束后填充
+	FirstTokenLatencyMs int64   `json:"first_token_latency_ms,omitempty"` // 请求开始到首个data事件的时间
+	TokensPerSecond     float64 `json:"tokens_per_second,omitempty"`      // 首个事件到流结束期间的输出速度
+	TerminationReason   string  `json:"termination_reason,omitempty"`     // 流的结束原因，见 Termination* 常量
+
+	// EstimatedInputTokens 转发前按目标提供商的分词方式估算的输入token，用于配额预检查；可与上游返回的 input_tokens 对比
+	EstimatedInputTokens int `json:"estimated_input_tokens,omitempty"`
+
+	// App/AppVersion 客户端通过 X-Gateway-App 头部声明的应用名称和版本，用于区分共用同一Key的应用
+	App        string `json:"app,omitempty"`
+	AppVersion string `json:"app_version,omitempty"`
+
+	// UpstreamRequestID 上游返回的请求ID（Anthropic 的 request-id、OpenAI 的 x-request-id），用于向提供商提交工单
+	UpstreamRequestID string `json:"upstream_request_id,omitempty"`
+
+	// ContextTrimmed 上游因超出上下文窗口拒绝后，网关删减了最早的消息并重试
+	ContextTrimmed bool `json:"context_trimmed,omitempty"`
+
+	// Sandbox 沙箱Key的请求，由模拟响应器应答，token数照常记录，费用为0
+	Sandbox bool `json:"sandbox,omitempty"`
+
+	// CacheInfo 请求使用响应缓存（X-LLM-Cache: true）时填充
+	CacheInfo *CacheInfo `json:"cache_info,omitempty"`
+}
+
+// CacheInfo 响应缓存的使用情况
+type CacheInfo struct {
+	Hit bool `json:"hit"` // 命中时响应直接来自缓存，不请求上游，token和费用为0
+}
+
+// Filter 使用记录查询条件，零值字段表示不过滤
+type Filter struct {
+	Since        time.Time
+	Until        time.Time
+	GatewayKeyID string
+	OrgID        string
+	UpstreamID   string
+	Provider     types.Provider
+	App          string
+}
+
+// Match 检查记录是否满足过滤条件
+func (f *Filter) Match(record *UsageRecord) bool {
+	if !f.Since.IsZero() && record.Timestamp.Before(f.Since) {
+		return false
+	}
+	if !f.Until.IsZero() && !record.Timestamp.Before(f.Until) {
+		return false
+	}
+	if f.GatewayKeyID != "" && record.GatewayKeyID != f.GatewayKeyID {
+		return false
+	}
+	if f.OrgID != "" && record.OrgID != f.OrgID {
+		return false
+	}
+	if f.UpstreamID != "" && record.UpstreamID != f.UpstreamID {
+		return false
+	}
+	if f.Provider != "" && record.Provider != f.Provider {
+		return false
+	}
+	if f.App != "" && record.App != f.App {
+		return false
+	}
+	return true
+}
+
+// defaultMaxRecords 默认保留的最大记录数
+const defaultMaxRecords = 100000
+
+// Recorder 内存中的使用记录存储，按时间顺序保存最近的请求
+type Recorder struct {
+	records    []UsageRecord
+	dropped    int64 // 已淘汰的记录数，records[i] 的游标为 dropped+i
+	maxRecords int
+	observers  []func(UsageRecord)
+	mutex      sync.RWMutex
+}
+
+// NewRecorder 创建使用记录存储，maxRecords<=0 时使用默认容量
+func NewRecorder(maxRecords int) *Recorder {
+	if maxRecords <= 0 {
+		maxRecords = defaultMaxRecords
+	}
+	return &Recorder{
+		records:    make([]UsageRecord, 0, 1024),
+		maxRecords: maxRecords,
+	}
+}
+
+// Subscribe 注册记录写入后的回调（在写入方的goroutine中同步调用）
+func (r *Recorder) Subscribe(observer func(UsageRecord)) {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	r.observers = append(r.observers, observer)
+}
+
+// Record 写入一条使用记录
+func (r *Recorder) Record(record UsageRecord) {
+	r.mutex.Lock()
+	observers := r.observers
+	r.appendLocked(record)
+	r.mutex.Unlock()
+
+	for _, observer := range observers {
+		observer(record)
+	}
+}
+
+// appendLocked 追加记录（调用方持有写锁）
+func (r *Recorder) appendLocked(record UsageRecord) {
+	r.records = append(r.records, record)
+
+	// 超出容量时一次性淘汰最旧的10%，避免每次写入都移动数据
+	if len(r.records) > r.maxRecords {
+		drop := len(r.records) - r.maxRecords + r.maxRecords/10
+		if drop > len(r.records) {
+			drop = len(r.records)
+		}
+		r.records = append(r.records[:0:0], r.records[drop:]...)
+		r.dropped += int64(drop)
+	}
+}
+
+// Query 查询满足条件的使用记录（返回副本）
+func (r *Recorder) Query(filter Filter) []UsageRecord {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+
+	result := make([]UsageRecord, 0)
+	for i := range r.records {
+		if filter.Match(&r.records[i]) {
+			result = append(result, r.records[i])
+		}
+	}
+	return result
+}
+
+// QueryPage 从游标处按时间顺序查询最多limit条满足条件的记录，返回记录和下一页的游标，没有更多记录时游标为-1。
+// 游标在记录淘汰后仍然有效（已淘汰的部分被跳过），用于大批量导出时分页读取而不一次性复制所有记录
+func (r *Recorder) QueryPage(filter Filter, cursor int64, limit int) ([]UsageRecord, int64) {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+
+	start := cursor - r.dropped
+	if start < 0 {
+		start = 0
+	}
+
+	result := make([]UsageRecord, 0, limit)
+	for i := start; i < int64(len(r.records)); i++ {
+		if !filter.Match(&r.records[i]) {
+			continue
+		}
+		result = append(result, r.records[i])
+		if len(result) == limit {
+			if i+1 < int64(len(r.records)) {
+				return result, r.dropped + i + 1
+			}
+			break
+		}
+	}
+	return result, -1
+}
+
+// Len 返回当前保存的记录数
+func (r *Recorder) Len() int {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+	return len(r.records)
+}
+
+// ReadFrom 返回从游标处开始最多limit条记录（不过滤）和读取结束位置的游标，没有新记录时游标不变。
+// 与 QueryPage 不同，返回的游标总是可以用于下一次读取，供增量处理新写入的记录
+func (r *Recorder) ReadFrom(cursor int64, limit int) ([]UsageRecord, int64) {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+
+	start := cursor - r.dropped
+	if start < 0 {
+		start = 0
+	}
+	end := int64(len(r.records))
+	if start > end {
+		start = end
+	}
+	if limit > 0 && end-start > int64(limit) {
+		end = start + int64(limit)
+	}
+	return append([]UsageRecord{}, r.records[start:end]...), r.dropped + end
+}
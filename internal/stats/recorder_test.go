@@ -0,0 +1,36 @@
+package stats
+
+import (
+	"testing"
+	"time"
+)
+
+func TestRecorderQueryPage(t *testing.T) {
+	start := time.Date(2024, 6, 1, 0, 0, 0, 0, time.UTC)
+	recorder := NewRecorder(10)
+	for i := 0; i < 10; i++ {
+		recorder.Record(UsageRecord{RequestID: string(rune('a' + i)), Timestamp: start.Add(time.Duration(i) * time.Minute), GatewayKeyID: []string{"k1", "k2"}[i%2]})
+	}
+
+	filter := Filter{GatewayKeyID: "k1"}
+	page, cursor := recorder.QueryPage(filter, 0, 3)
+	if len(page) != 3 || page[0].RequestID != "a" || page[2].RequestID != "e" {
+		t.Fatalf("first page = %v", page)
+	}
+
+	// 翻页期间淘汰旧记录，游标仍指向原来的位置
+	recorder.Record(UsageRecord{RequestID: "k", Timestamp: start.Add(10 * time.Minute), GatewayKeyID: "k1"})
+	page, cursor = recorder.QueryPage(filter, cursor, 3)
+	if len(page) != 3 || page[0].RequestID != "g" || page[2].RequestID != "k" {
+		t.Fatalf("second page = %v", page)
+	}
+	if cursor != -1 {
+		t.Errorf("cursor = %d, want -1 after the last record", cursor)
+	}
+
+	// 游标已被淘汰时从最旧的记录开始
+	page, _ = recorder.QueryPage(Filter{}, 0, 100)
+	if len(page) != recorder.Len() {
+		t.Errorf("len(page) = %d, want %d", len(page), recorder.Len())
+	}
+}
@@ -0,0 +1,324 @@
+package stats
+
+import (
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 汇总粒度
+const (
+	RollupHourly = "hour"
+	RollupDaily  = "day"
+)
+
+// 汇总维度
+const (
+	RollupByKey      = "key"
+	RollupByAccount  = "account"
+	RollupByModel    = "model"
+	RollupByProvider = "provider"
+	RollupByPool     = "pool"
+)
+
+// 汇总保留时长：小时汇总覆盖热力图和近期明细，按天汇总覆盖长期趋势
+const (
+	hourlyRollupRetention = 90 * 24 * time.Hour
+	dailyRollupRetention  = 400 * 24 * time.Hour
+)
+
+// rollupBatchSize 每次从使用记录中读取的最大条数
+const rollupBatchSize = 10000
+
+// rollupDimensions 每条记录计入的维度
+var rollupDimensions = map[string]func(*UsageRecord) string{
+	RollupByKey:      GroupByKey,
+	RollupByAccount:  GroupByAccount,
+	RollupByModel:    GroupByModel,
+	RollupByProvider: GroupByProvider,
+	RollupByPool:     GroupByPool,
+}
+
+// GroupByModel 按实际使用的模型分组
+func GroupByModel(record *UsageRecord) string {
+	return record.Model
+}
+
+// GroupByProvider 按上游提供商分组
+func GroupByProvider(record *UsageRecord) string {
+	return string(record.Provider)
+}
+
+// GroupByPool 按上游账号池分组，不属于账号池的记录不计入
+func GroupByPool(record *UsageRecord) string {
+	return record.Pool
+}
+
+// RollupBucket 一个时间桶内某个维度取值的用量合计
+type RollupBucket struct {
+	Start        time.Time `json:"start"` // 桶的起始时间（UTC，整点或零点）
+	Dimension    string    `json:"dimension"`
+	ID           string    `json:"id"`
+	Requests     int64     `json:"requests"`
+	Errors       int64     `json:"errors"`
+	InputTokens  int64     `json:"input_tokens"`
+	OutputTokens int64     `json:"output_tokens"`
+	CostUSD      float64   `json:"cost_usd"`
+	LatencyMsSum int64     `json:"latency_ms_sum"` // 除以 Requests 得到平均延迟
+
+	// ErrorClasses 按上游错误分类统计的错误数，按提供商汇总时可以看出哪一类错误在增加
+	ErrorClasses map[types.ErrorClass]int64 `json:"error_classes,omitempty"`
+
+	// 成功请求的延迟分布和流式请求的输出速度，用于计算分位数
+	Latency              LatencyHistogram `json:"-"`
+	FirstTokenLatency    LatencyHistogram `json:"-"` // 只计入流式请求
+	TokensPerSecondSum   float64          `json:"tokens_per_second_sum"`
+	TokensPerSecondCount int64            `json:"tokens_per_second_count"` // 除 TokensPerSecondSum 得到平均输出速度
+}
+
+// RollupMetrics 由时间桶计算的成功率、延迟分位数和输出速度
+type RollupMetrics struct {
+	SuccessRate       float64         `json:"success_rate"`
+	Latency           *LatencySummary `json:"latency_ms,omitempty"`             // 成功请求的总延迟
+	FirstTokenLatency *LatencySummary `json:"first_token_latency_ms,omitempty"` // 成功的流式请求的首token延迟
+	TokensPerSecond   float64         `json:"tokens_per_second"`                // 流式请求的平均输出速度
+}
+
+// Merge 把另一个时间桶的用量加到当前时间桶，用于计算窗口合计
+func (b *RollupBucket) Merge(other *RollupBucket) {
+	b.Requests += other.Requests
+	b.Errors += other.Errors
+	b.InputTokens += other.InputTokens
+	b.OutputTokens += other.OutputTokens
+	b.CostUSD += other.CostUSD
+	b.LatencyMsSum += other.LatencyMsSum
+	for class, count := range other.ErrorClasses {
+		b.addErrorClass(class, count)
+	}
+	b.Latency.Merge(&other.Latency)
+	b.FirstTokenLatency.Merge(&other.FirstTokenLatency)
+	b.TokensPerSecondSum += other.TokensPerSecondSum
+	b.TokensPerSecondCount += other.TokensPerSecondCount
+}
+
+// addErrorClass 累加某个错误分类的错误数
+func (b *RollupBucket) addErrorClass(class types.ErrorClass, count int64) {
+	if b.ErrorClasses == nil {
+		b.ErrorClasses = make(map[types.ErrorClass]int64)
+	}
+	b.ErrorClasses[class] += count
+}
+
+// Metrics 计算时间桶的成功率、延迟分位数和平均输出速度
+func (b *RollupBucket) Metrics() RollupMetrics {
+	var metrics RollupMetrics
+	if b.Requests > 0 {
+		metrics.SuccessRate = float64(b.Requests-b.Errors) / float64(b.Requests)
+	}
+	metrics.Latency = b.Latency.Summary()
+	metrics.FirstTokenLatency = b.FirstTokenLatency.Summary()
+	if b.TokensPerSecondCount > 0 {
+		metrics.TokensPerSecond = b.TokensPerSecondSum / float64(b.TokensPerSecondCount)
+	}
+	return metrics
+}
+
+// rollupKey 时间桶的索引
+type rollupKey struct {
+	start     int64
+	dimension string
+	id        string
+}
+
+// Rollups 后台任务定期把新的使用记录按小时和天汇总（每个Key、账号、模型、提供商、账号池），统计查询读取汇总而不扫描原始记录。
+// 汇总在原始记录被淘汰后仍然保留，但只保存在内存中：重启后由 Recorder 中仍保留的使用记录重新计算，更早的历史会丢失
+type Rollups struct {
+	recorder *Recorder
+	interval time.Duration
+	cursor   int64 // 下一条待汇总记录的游标
+	hourly   map[rollupKey]*RollupBucket
+	daily    map[rollupKey]*RollupBucket
+	stopCh   chan struct{}
+	mutex    sync.Mutex
+}
+
+// NewRollups 创建汇总任务，已有的使用记录在第一次汇总时计入
+func NewRollups(recorder *Recorder, interval time.Duration) *Rollups {
+	if interval <= 0 {
+		interval = time.Minute
+	}
+	return &Rollups{
+		recorder: recorder,
+		interval: interval,
+		hourly:   make(map[rollupKey]*RollupBucket),
+		daily:    make(map[rollupKey]*RollupBucket),
+	}
+}
+
+// Start 启动后台汇总
+func (r *Rollups) Start() {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	if r.stopCh != nil {
+		return
+	}
+	r.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(r.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				r.Roll(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(r.stopCh)
+}
+
+// Stop 停止后台汇总
+func (r *Rollups) Stop() {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	if r.stopCh != nil {
+		close(r.stopCh)
+		r.stopCh = nil
+	}
+}
+
+// Roll 汇总上次以来写入的使用记录并清理超过保留时长的时间桶，返回本次汇总的记录数
+func (r *Rollups) Roll(now time.Time) int {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	return r.rollLocked(now)
+}
+
+// rollLocked 汇总新记录（调用方持有锁）
+func (r *Rollups) rollLocked(now time.Time) int {
+	rolled := 0
+	for {
+		records, next := r.recorder.ReadFrom(r.cursor, rollupBatchSize)
+		r.cursor = next
+		for i := range records {
+			r.add(&records[i])
+		}
+		rolled += len(records)
+		if len(records) < rollupBatchSize {
+			break
+		}
+	}
+
+	r.prune(r.hourly, now.Add(-hourlyRollupRetention))
+	r.prune(r.daily, now.Add(-dailyRollupRetention))
+	return rolled
+}
+
+// add 把一条记录计入每个维度的小时桶和天桶
+func (r *Rollups) add(record *UsageRecord) {
+	hour := record.Timestamp.UTC().Truncate(time.Hour).Unix()
+	day := utcDay(record.Timestamp).Unix()
+	for dimension, groupBy := range rollupDimensions {
+		id := groupBy(record)
+		if id == "" {
+			continue
+		}
+		addToBucket(r.hourly, rollupKey{start: hour, dimension: dimension, id: id}, record)
+		addToBucket(r.daily, rollupKey{start: day, dimension: dimension, id: id}, record)
+	}
+}
+
+// addToBucket 累加记录到时间桶，不存在时创建
+func addToBucket(buckets map[rollupKey]*RollupBucket, key rollupKey, record *UsageRecord) {
+	bucket, exists := buckets[key]
+	if !exists {
+		bucket = &RollupBucket{Start: time.Unix(key.start, 0).UTC(), Dimension: key.dimension, ID: key.id}
+		buckets[key] = bucket
+	}
+	bucket.Requests++
+	if !record.Success {
+		bucket.Errors++
+		if record.ErrorClass != "" {
+			bucket.addErrorClass(record.ErrorClass, 1)
+		}
+	}
+	bucket.InputTokens += int64(record.InputTokens)
+	bucket.OutputTokens += int64(record.OutputTokens)
+	bucket.CostUSD += record.CostUSD
+	bucket.LatencyMsSum += record.LatencyMs
+	if !record.Success {
+		return
+	}
+	bucket.Latency.Add(record.LatencyMs)
+	if record.Stream && record.FirstTokenLatencyMs > 0 {
+		bucket.FirstTokenLatency.Add(record.FirstTokenLatencyMs)
+	}
+	if record.Stream && record.TokensPerSecond > 0 {
+		bucket.TokensPerSecondSum += record.TokensPerSecond
+		bucket.TokensPerSecondCount++
+	}
+}
+
+// prune 删除起始时间早于cutoff的时间桶
+func (r *Rollups) prune(buckets map[rollupKey]*RollupBucket, cutoff time.Time) {
+	for key := range buckets {
+		if key.start < cutoff.Unix() {
+			delete(buckets, key)
+		}
+	}
+}
+
+// RollupQuery 汇总查询条件，ID为空时返回该维度的所有取值
+type RollupQuery struct {
+	Granularity string // RollupHourly 或 RollupDaily
+	Dimension   string // RollupByKey、RollupByAccount、RollupByModel、RollupByProvider 或 RollupByPool
+	ID          string
+	Since       time.Time // 返回与 [Since, Until) 有重叠的时间桶，Until 为零值时不限制
+	Until       time.Time
+}
+
+// Query 查询时间桶（返回副本），按起始时间和ID排序。查询前先汇总尚未处理的新记录，结果不会滞后于原始记录
+func (r *Rollups) Query(query RollupQuery, now time.Time) []RollupBucket {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	r.rollLocked(now)
+
+	buckets := r.hourly
+	since := query.Since.UTC().Truncate(time.Hour)
+	if query.Granularity == RollupDaily {
+		buckets = r.daily
+		since = utcDay(query.Since)
+	}
+
+	result := make([]RollupBucket, 0)
+	for key, bucket := range buckets {
+		if key.dimension != query.Dimension || (query.ID != "" && key.id != query.ID) {
+			continue
+		}
+		if bucket.Start.Before(since) || (!query.Until.IsZero() && !bucket.Start.Before(query.Until)) {
+			continue
+		}
+		copied := *bucket
+		copied.Latency = bucket.Latency.clone()
+		copied.FirstTokenLatency = bucket.FirstTokenLatency.clone()
+		copied.ErrorClasses = nil
+		for class, count := range bucket.ErrorClasses {
+			copied.addErrorClass(class, count)
+		}
+		result = append(result, copied)
+	}
+	sort.Slice(result, func(i, j int) bool {
+		if !result[i].Start.Equal(result[j].Start) {
+			return result[i].Start.Before(result[j].Start)
+		}
+		return result[i].ID < result[j].ID
+	})
+	return result
+}
@@ -0,0 +1,108 @@
+package stats
+
+import (
+	"math"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestRollups(t *testing.T) {
+	now := time.Date(2024, 6, 3, 12, 30, 0, 0, time.UTC)
+	recorder := NewRecorder(5)
+	recorder.Record(UsageRecord{Timestamp: now.Add(-26 * time.Hour), GatewayKeyID: "k1", UpstreamID: "u1", Pool: "openai/acme", Model: "gpt-4o", Success: true, InputTokens: 100, CostUSD: 0.1, LatencyMs: 200})
+	recorder.Record(UsageRecord{Timestamp: now.Add(-10 * time.Minute), GatewayKeyID: "k1", UpstreamID: "u1", Pool: "openai/acme", Model: "gpt-4o", Success: false, LatencyMs: 100})
+	recorder.Record(UsageRecord{Timestamp: now.Add(-5 * time.Minute), GatewayKeyID: "k2", Model: "claude-3-5-sonnet", Success: true, OutputTokens: 20})
+
+	rollups := NewRollups(recorder, time.Minute)
+	if rolled := rollups.Roll(now); rolled != 3 {
+		t.Fatalf("Roll() = %d, want 3", rolled)
+	}
+	if rolled := rollups.Roll(now); rolled != 0 {
+		t.Errorf("second Roll() = %d, want 0", rolled)
+	}
+
+	hourly := rollups.Query(RollupQuery{Granularity: RollupHourly, Dimension: RollupByKey, Since: now.Add(-time.Hour)}, now)
+	if len(hourly) != 2 || hourly[0].ID != "k1" || hourly[1].ID != "k2" {
+		t.Fatalf("hourly key buckets = %+v", hourly)
+	}
+	if hourly[0].Requests != 1 || hourly[0].Errors != 1 || !hourly[0].Start.Equal(now.Truncate(time.Hour)) {
+		t.Errorf("k1 hourly bucket = %+v", hourly[0])
+	}
+
+	daily := rollups.Query(RollupQuery{Granularity: RollupDaily, Dimension: RollupByAccount, ID: "u1", Since: now.AddDate(0, 0, -7)}, now)
+	if len(daily) != 2 || daily[0].Requests != 1 || daily[0].InputTokens != 100 || daily[0].LatencyMsSum != 200 {
+		t.Errorf("u1 daily buckets = %+v", daily)
+	}
+
+	// 只有属于账号池的记录计入账号池维度
+	pools := rollups.Query(RollupQuery{Granularity: RollupDaily, Dimension: RollupByPool, Since: now.AddDate(0, 0, -7)}, now)
+	if len(pools) != 2 || pools[0].ID != "openai/acme" || pools[0].Requests+pools[1].Requests != 2 {
+		t.Errorf("pool daily buckets = %+v", pools)
+	}
+
+	// 原始记录被淘汰后汇总仍然保留，查询时先汇总新记录
+	for i := 0; i < 10; i++ {
+		recorder.Record(UsageRecord{Timestamp: now, GatewayKeyID: "k3", Model: "gpt-4o", Success: true})
+	}
+	models := rollups.Query(RollupQuery{Granularity: RollupDaily, Dimension: RollupByModel, ID: "gpt-4o", Since: now.AddDate(0, 0, -7)}, now)
+	total := int64(0)
+	for _, bucket := range models {
+		total += bucket.Requests
+	}
+	if total < 3 || len(recorder.Query(Filter{GatewayKeyID: "k1"})) != 0 {
+		t.Errorf("gpt-4o requests = %d, want history kept after eviction", total)
+	}
+
+	// 超过保留时长的时间桶被清理
+	rollups.Roll(now.Add(hourlyRollupRetention + 48*time.Hour))
+	if buckets := rollups.Query(RollupQuery{Granularity: RollupHourly, Dimension: RollupByKey}, now); len(buckets) != 0 {
+		t.Errorf("got %d hourly buckets after retention, want 0", len(buckets))
+	}
+}
+
+func TestRollups_Metrics(t *testing.T) {
+	now := time.Date(2024, 6, 3, 12, 30, 0, 0, time.UTC)
+	recorder := NewRecorder(10)
+	for _, latency := range []int64{100, 200, 300, 400} {
+		recorder.Record(UsageRecord{Timestamp: now, Provider: "openai", Model: "gpt-4o", Success: true, LatencyMs: latency})
+	}
+	recorder.Record(UsageRecord{Timestamp: now, Provider: "openai", Model: "gpt-4o", Success: true, Stream: true, LatencyMs: 2000, FirstTokenLatencyMs: 300, TokensPerSecond: 40})
+	// 非流式请求的输出速度不计入平均值
+	recorder.Record(UsageRecord{Timestamp: now, Provider: "openai", Model: "gpt-4o", Success: true, LatencyMs: 600, TokensPerSecond: 100})
+	recorder.Record(UsageRecord{Timestamp: now, Provider: "openai", Model: "gpt-4o", Success: false, ErrorClass: types.ErrorClassQuota, LatencyMs: 30000})
+
+	rollups := NewRollups(recorder, time.Minute)
+	buckets := rollups.Query(RollupQuery{Granularity: RollupHourly, Dimension: RollupByProvider, ID: "openai", Since: now.Add(-time.Hour)}, now)
+	if len(buckets) != 1 {
+		t.Fatalf("provider buckets = %+v", buckets)
+	}
+
+	// 窗口合计与单个时间桶相同，失败请求不计入延迟分布
+	var total RollupBucket
+	total.Merge(&buckets[0])
+	metrics := total.Metrics()
+	if math.Abs(metrics.SuccessRate-6.0/7) > 1e-9 || total.Requests != 7 {
+		t.Errorf("success rate = %v, requests = %d", metrics.SuccessRate, total.Requests)
+	}
+	if metrics.Latency == nil || metrics.Latency.Avg != 600 || metrics.Latency.P99 > 3000 {
+		t.Errorf("latency = %+v", metrics.Latency)
+	}
+	if metrics.FirstTokenLatency == nil || metrics.FirstTokenLatency.Avg != 300 || metrics.TokensPerSecond != 40 {
+		t.Errorf("streaming metrics = %+v, %v", metrics.FirstTokenLatency, metrics.TokensPerSecond)
+	}
+	if total.ErrorClasses[types.ErrorClassQuota] != 1 || len(total.ErrorClasses) != 1 {
+		t.Errorf("error classes = %v, want quota only", total.ErrorClasses)
+	}
+
+	// 修改查询结果不影响汇总
+	buckets[0].Latency.Counts[0]++
+	if again := rollups.Query(RollupQuery{Granularity: RollupHourly, Dimension: RollupByProvider, ID: "openai", Since: now.Add(-time.Hour)}, now); again[0].Latency.Counts[0] != 0 {
+		t.Errorf("query result shares histogram with the rollup")
+	}
+	buckets[0].ErrorClasses[types.ErrorClassQuota]++
+	if again := rollups.Query(RollupQuery{Granularity: RollupHourly, Dimension: RollupByProvider, ID: "openai", Since: now.Add(-time.Hour)}, now); again[0].ErrorClasses[types.ErrorClassQuota] != 1 {
+		t.Errorf("query result shares error classes with the rollup")
+	}
+}
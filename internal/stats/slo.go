@@ -0,0 +1,248 @@
+package stats
+
+import (
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// SLOReport 单个维度（Gateway Key 或上游账号）的 SLO 达成情况
+type SLOReport struct {
+	ID                   string  `json:"id"`
+	TotalRequests        int64   `json:"total_requests"`
+	SuccessfulRequests   int64   `json:"successful_requests"`
+	Satisfied            int64   `json:"satisfied"`
+	Tolerating           int64   `json:"tolerating"`
+	Frustrated           int64   `json:"frustrated"`
+	Apdex                float64 `json:"apdex"`
+	Availability         float64 `json:"availability"`
+	LatencyAttainment    float64 `json:"latency_attainment"`     // 成功且在阈值T内的请求占比
+	ErrorBudgetRemaining float64 `json:"error_budget_remaining"` // 1 表示未消耗，<=0 表示已耗尽
+	BurnRate             float64 `json:"burn_rate"`              // 短窗口内的错误预算燃烧率
+	Alerting             bool    `json:"alerting"`
+}
+
+// SLOAlert 错误预算燃烧过快的告警
+type SLOAlert struct {
+	Dimension string    `json:"dimension"` // key, account
+	ID        string    `json:"id"`
+	BurnRate  float64   `json:"burn_rate"`
+	Threshold float64   `json:"threshold"`
+	FiredAt   time.Time `json:"fired_at"`
+}
+
+// clientRejections 网关因请求本身（权限、作用域、参数、配额、内容审核等）拒绝的错误类型。
+// 这些请求不是服务故障，不计入SLO；上游错误和网关自身的故障计入可用性
+var clientRejections = map[string]bool{
+	"scope_forbidden":     true,
+	"path_forbidden":      true,
+	"path_not_found":      true,
+	"routing_denied":      true,
+	"tools_not_supported": true,
+	"invalid_priority":    true,
+	"invalid_timeout":     true,
+	"invalid_messages":    true, // 消息规范化失败
+	"invalid_image":       true,
+	"model_not_found":     true,
+	"quota_exceeded":      true,
+	"moderation_blocked":  true,
+}
+
+// countsTowardSLO 使用记录是否计入SLO（网关拒绝的客户端请求不计入）
+func countsTowardSLO(record *UsageRecord) bool {
+	return record.Success || !clientRejections[record.ErrorType]
+}
+
+// ComputeSLOReports 按分组函数计算每个分组的 SLO 报告，空分组ID和网关拒绝的客户端请求会被忽略
+func ComputeSLOReports(records []UsageRecord, cfg types.SLOConfig, now time.Time, groupBy func(*UsageRecord) string) []*SLOReport {
+	threshold := int64(cfg.LatencyThresholdMs)
+	burnSince := now.Add(-time.Duration(cfg.BurnRateWindowMinutes) * time.Minute)
+
+	reports := make(map[string]*SLOReport)
+	shortTotal := make(map[string]int64)
+	shortErrors := make(map[string]int64)
+
+	for i := range records {
+		record := &records[i]
+		id := groupBy(record)
+		if id == "" || !countsTowardSLO(record) {
+			continue
+		}
+
+		report, exists := reports[id]
+		if !exists {
+			report = &SLOReport{ID: id}
+			reports[id] = report
+		}
+
+		report.TotalRequests++
+		switch {
+		case !record.Success:
+			report.Frustrated++
+		case record.LatencyMs <= threshold:
+			report.SuccessfulRequests++
+			report.Satisfied++
+		case record.LatencyMs <= 4*threshold:
+			report.SuccessfulRequests++
+			report.Tolerating++
+		default:
+			report.SuccessfulRequests++
+			report.Frustrated++
+		}
+
+		if !record.Timestamp.Before(burnSince) {
+			shortTotal[id]++
+			if !record.Success {
+				shortErrors[id]++
+			}
+		}
+	}
+
+	allowedErrorRate := 1 - cfg.AvailabilityTarget
+	result := make([]*SLOReport, 0, len(reports))
+	for id, report := range reports {
+		total := float64(report.TotalRequests)
+		failures := float64(report.TotalRequests - report.SuccessfulRequests)
+
+		report.Apdex = (float64(report.Satisfied) + float64(report.Tolerating)/2) / total
+		report.Availability = float64(report.SuccessfulRequests) / total
+		report.LatencyAttainment = float64(report.Satisfied) / total
+
+		if allowedErrorRate > 0 {
+			report.ErrorBudgetRemaining = 1 - failures/(allowedErrorRate*total)
+			if shortTotal[id] > 0 {
+				shortErrorRate := float64(shortErrors[id]) / float64(shortTotal[id])
+				report.BurnRate = shortErrorRate / allowedErrorRate
+			}
+		}
+		report.Alerting = cfg.BurnRateAlertThreshold > 0 && report.BurnRate >= cfg.BurnRateAlertThreshold
+
+		result = append(result, report)
+	}
+
+	sort.Slice(result, func(i, j int) bool {
+		return result[i].ID < result[j].ID
+	})
+	return result
+}
+
+// GroupByKey 按 Gateway Key 分组
+func GroupByKey(record *UsageRecord) string {
+	return record.GatewayKeyID
+}
+
+// GroupByAccount 按上游账号分组
+func GroupByAccount(record *UsageRecord) string {
+	return record.UpstreamID
+}
+
+// SLOMonitor 定期评估错误预算燃烧率并在超过阈值时告警
+type SLOMonitor struct {
+	recorder *Recorder
+	settings func() *types.SLOConfig // 每次评估时读取，修改或重新加载配置后立即生效
+	interval time.Duration
+	onAlert  func(SLOAlert) // 新触发的告警（如发送通知），可为nil
+	alerting map[string]bool
+	stopCh   chan struct{}
+	mutex    sync.Mutex
+}
+
+// NewSLOMonitor 创建SLO监控器，settings 返回当前的SLO配置
+func NewSLOMonitor(recorder *Recorder, settings func() *types.SLOConfig, interval time.Duration) *SLOMonitor {
+	if interval <= 0 {
+		interval = time.Minute
+	}
+	return &SLOMonitor{
+		recorder: recorder,
+		settings: settings,
+		interval: interval,
+		alerting: make(map[string]bool),
+	}
+}
+
+// SetAlertHandler 设置新触发告警时的处理函数，需在 Start 之前调用
+func (m *SLOMonitor) SetAlertHandler(handler func(SLOAlert)) {
+	m.onAlert = handler
+}
+
+// Start 启动后台评估
+func (m *SLOMonitor) Start() {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.stopCh != nil {
+		return
+	}
+	m.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(m.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				m.Evaluate(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(m.stopCh)
+}
+
+// Stop 停止后台评估
+func (m *SLOMonitor) Stop() {
+	m.mutex.Lock()
+	defer m.mutex.Unlock()
+
+	if m.stopCh != nil {
+		close(m.stopCh)
+		m.stopCh = nil
+	}
+}
+
+// Evaluate 评估当前燃烧率，返回本次新触发的告警
+func (m *SLOMonitor) Evaluate(now time.Time) []SLOAlert {
+	cfg := *m.settings()
+	records := m.recorder.Query(Filter{
+		Since: now.Add(-time.Duration(cfg.WindowHours) * time.Hour),
+	})
+
+	dimensions := map[string]func(*UsageRecord) string{
+		"key":     GroupByKey,
+		"account": GroupByAccount,
+	}
+
+	m.mutex.Lock()
+	var fired []SLOAlert
+	for dimension, groupBy := range dimensions {
+		for _, report := range ComputeSLOReports(records, cfg, now, groupBy) {
+			alertKey := dimension + ":" + report.ID
+			if report.Alerting && !m.alerting[alertKey] {
+				alert := SLOAlert{
+					Dimension: dimension,
+					ID:        report.ID,
+					BurnRate:  report.BurnRate,
+					Threshold: cfg.BurnRateAlertThreshold,
+					FiredAt:   now,
+				}
+				fired = append(fired, alert)
+				logger.Warn("SLO错误预算燃烧过快: %s=%s 燃烧率 %.1f (阈值 %.1f)", dimension, report.ID, report.BurnRate, cfg.BurnRateAlertThreshold)
+			} else if !report.Alerting && m.alerting[alertKey] {
+				logger.Info("SLO燃烧率已恢复: %s=%s 燃烧率 %.1f", dimension, report.ID, report.BurnRate)
+			}
+			m.alerting[alertKey] = report.Alerting
+		}
+	}
+	m.mutex.Unlock()
+
+	if m.onAlert != nil {
+		for _, alert := range fired {
+			m.onAlert(alert)
+		}
+	}
+	return fired
+}
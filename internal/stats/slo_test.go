@@ -0,0 +1,137 @@
+package stats
+
+import (
+	"math"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestComputeSLOReports(t *testing.T) {
+	now := time.Now()
+	cfg := types.SLOConfig{
+		LatencyThresholdMs:     1000,
+		AvailabilityTarget:     0.9,
+		WindowHours:            24,
+		BurnRateWindowMinutes:  60,
+		BurnRateAlertThreshold: 2,
+	}
+
+	// key-a: 满意、可容忍、慢请求、失败各一个；空Key的记录会被忽略
+	records := []UsageRecord{
+		{GatewayKeyID: "key-a", Timestamp: now.Add(-2 * time.Hour), Success: true, LatencyMs: 500},
+		{GatewayKeyID: "key-a", Timestamp: now.Add(-2 * time.Hour), Success: true, LatencyMs: 3000},
+		{GatewayKeyID: "key-a", Timestamp: now.Add(-10 * time.Minute), Success: true, LatencyMs: 5000},
+		{GatewayKeyID: "key-a", Timestamp: now.Add(-10 * time.Minute), Success: false, LatencyMs: 100},
+		{GatewayKeyID: "key-b", Timestamp: now.Add(-5 * time.Minute), Success: true, LatencyMs: 100},
+		{GatewayKeyID: "", Timestamp: now, Success: false},
+	}
+
+	reports := ComputeSLOReports(records, cfg, now, GroupByKey)
+	if len(reports) != 2 {
+		t.Fatalf("len(reports) = %d, want 2", len(reports))
+	}
+
+	a := reports[0]
+	if a.ID != "key-a" {
+		t.Fatalf("reports[0].ID = %s, want key-a", a.ID)
+	}
+	if a.Satisfied != 1 || a.Tolerating != 1 || a.Frustrated != 2 {
+		t.Errorf("buckets = %d/%d/%d, want 1/1/2", a.Satisfied, a.Tolerating, a.Frustrated)
+	}
+	if math.Abs(a.Apdex-0.375) > 1e-9 {
+		t.Errorf("Apdex = %v, want 0.375", a.Apdex)
+	}
+	if math.Abs(a.Availability-0.75) > 1e-9 {
+		t.Errorf("Availability = %v, want 0.75", a.Availability)
+	}
+	// 允许错误率10%，4个请求中1个失败 => 预算消耗 1/(0.1*4) = 2.5
+	if math.Abs(a.ErrorBudgetRemaining-(-1.5)) > 1e-9 {
+		t.Errorf("ErrorBudgetRemaining = %v, want -1.5", a.ErrorBudgetRemaining)
+	}
+	// 短窗口内2个请求1个失败 => 错误率50%，燃烧率 5
+	if math.Abs(a.BurnRate-5) > 1e-9 {
+		t.Errorf("BurnRate = %v, want 5", a.BurnRate)
+	}
+	if !a.Alerting {
+		t.Error("key-a should be alerting")
+	}
+
+	b := reports[1]
+	if b.Apdex != 1 || b.Alerting || b.ErrorBudgetRemaining != 1 {
+		t.Errorf("key-b report = %+v, want healthy", b)
+	}
+}
+
+func TestComputeSLOReports_IgnoresClientRejections(t *testing.T) {
+	now := time.Now()
+	cfg := types.SLOConfig{LatencyThresholdMs: 1000, AvailabilityTarget: 0.9, BurnRateWindowMinutes: 60, BurnRateAlertThreshold: 2}
+
+	// 网关拒绝的客户端请求不计入，上游错误和网关自身的故障计入
+	records := []UsageRecord{
+		{GatewayKeyID: "key-a", Timestamp: now, Success: true, LatencyMs: 100},
+		{GatewayKeyID: "key-a", Timestamp: now, Success: false, ErrorType: "scope_forbidden"},
+		{GatewayKeyID: "key-a", Timestamp: now, Success: false, ErrorType: "tools_not_supported"},
+		{GatewayKeyID: "key-a", Timestamp: now, Success: false, ErrorType: "quota_exceeded"},
+		{GatewayKeyID: "key-a", Timestamp: now, Success: false, ErrorType: "invalid_messages"},
+		{GatewayKeyID: "key-a", Timestamp: now, Success: false, ErrorType: "path_forbidden"},
+		{GatewayKeyID: "key-b", Timestamp: now, Success: true, LatencyMs: 100},
+		{GatewayKeyID: "key-b", Timestamp: now, Success: false, ErrorType: "upstream_error"},
+		{GatewayKeyID: "key-b", Timestamp: now, Success: false, ErrorType: "upstream_timeout"},
+		{GatewayKeyID: "key-b", Timestamp: now, Success: false, ErrorType: "no_upstream_available"},
+		{GatewayKeyID: "key-c", Timestamp: now, Success: false, ErrorType: "routing_denied"},
+	}
+
+	reports := ComputeSLOReports(records, cfg, now, GroupByKey)
+	if len(reports) != 2 {
+		t.Fatalf("len(reports) = %d, want 2 (key-c only has rejected requests)", len(reports))
+	}
+	if a := reports[0]; a.TotalRequests != 1 || a.Availability != 1 || a.Alerting {
+		t.Errorf("key-a report = %+v, want only the successful request counted", a)
+	}
+	if b := reports[1]; b.TotalRequests != 4 || math.Abs(b.Availability-0.25) > 1e-9 || !b.Alerting {
+		t.Errorf("key-b report = %+v, want upstream and gateway failures counted", b)
+	}
+}
+
+func TestSLOMonitor_AlertHandler(t *testing.T) {
+	now := time.Now()
+	cfg := &types.SLOConfig{LatencyThresholdMs: 1000, AvailabilityTarget: 0.9, WindowHours: 1, BurnRateWindowMinutes: 60, BurnRateAlertThreshold: 2}
+	recorder := NewRecorder(0)
+	recorder.Record(UsageRecord{GatewayKeyID: "key-a", UpstreamID: "up-1", Timestamp: now, Success: false, ErrorType: "upstream_error"})
+
+	var alerts []SLOAlert
+	monitor := NewSLOMonitor(recorder, func() *types.SLOConfig { return cfg }, time.Minute)
+	monitor.SetAlertHandler(func(alert SLOAlert) { alerts = append(alerts, alert) })
+
+	if fired := monitor.Evaluate(now); len(fired) != 2 || len(alerts) != 2 {
+		t.Fatalf("Evaluate() fired %d alerts, handler got %d, want 2 (key and account)", len(fired), len(alerts))
+	}
+	for _, alert := range alerts {
+		if math.Abs(alert.BurnRate-10) > 1e-9 || alert.Threshold != 2 || !alert.FiredAt.Equal(now) {
+			t.Errorf("alert = %+v, want burn rate 10 over threshold 2", alert)
+		}
+	}
+
+	// 持续告警时不重复通知
+	if fired := monitor.Evaluate(now.Add(time.Minute)); len(fired) != 0 || len(alerts) != 2 {
+		t.Errorf("Evaluate() fired %d alerts again while still alerting", len(fired))
+	}
+}
+
+func TestRecorderTrimsOldestRecords(t *testing.T) {
+	recorder := NewRecorder(10)
+	for i := 0; i < 25; i++ {
+		recorder.Record(UsageRecord{RequestID: string(rune('a' + i))})
+	}
+
+	if recorder.Len() > 10 {
+		t.Fatalf("Len() = %d, want <= 10", recorder.Len())
+	}
+
+	records := recorder.Query(Filter{})
+	if records[len(records)-1].RequestID != string(rune('a'+24)) {
+		t.Errorf("newest record lost after trimming")
+	}
+}
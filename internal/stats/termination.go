@@ -0,0 +1,60 @@
+package stats
+
+// 流式请求的结束原因
+const (
+	TerminationCompleted     = "completed"             // 上游正常结束，客户端收到完整响应
+	TerminationClientAbort   = "client_abort"          // 客户端在流结束前断开连接
+	TerminationUpstreamError = "upstream_error"        // 上游返回错误或连接中断
+	TerminationTimeout       = "timeout"               // 等待或读取上游响应超时
+	TerminationShutdown      = "cancelled_on_shutdown" // 网关关闭时被中断
+)
+
+// terminationOrder 结束原因在统计结果中的顺序
+var terminationOrder = []string{
+	TerminationCompleted,
+	TerminationClientAbort,
+	TerminationUpstreamError,
+	TerminationTimeout,
+	TerminationShutdown,
+}
+
+// TerminationStats 单个结束原因的流式请求统计
+type TerminationStats struct {
+	Reason       string  `json:"reason"`
+	Requests     int     `json:"requests"`
+	Share        float64 `json:"share"` // 占流式请求总数的比例
+	OutputTokens int64   `json:"output_tokens"`
+	CostUSD      float64 `json:"cost_usd"`
+}
+
+// ComputeTerminationStats 按结束原因汇总流式请求，没有结束原因的记录（非流式请求）不计入，
+// 所有原因都会出现在结果中，便于对比客户端中断与上游故障
+func ComputeTerminationStats(records []UsageRecord) []*TerminationStats {
+	byReason := make(map[string]*TerminationStats, len(terminationOrder))
+	result := make([]*TerminationStats, 0, len(terminationOrder))
+	for _, reason := range terminationOrder {
+		entry := &TerminationStats{Reason: reason}
+		byReason[reason] = entry
+		result = append(result, entry)
+	}
+
+	total := 0
+	for i := range records {
+		record := &records[i]
+		entry, exists := byReason[record.TerminationReason]
+		if !exists {
+			continue
+		}
+		total++
+		entry.Requests++
+		entry.OutputTokens += int64(record.OutputTokens)
+		entry.CostUSD += record.CostUSD
+	}
+
+	if total > 0 {
+		for _, entry := range result {
+			entry.Share = float64(entry.Requests) / float64(total)
+		}
+	}
+	return result
+}
@@ -0,0 +1,34 @@
+package stats
+
+import "testing"
+
+func TestComputeTerminationStats(t *testing.T) {
+	records := []UsageRecord{
+		{Stream: true, TerminationReason: TerminationCompleted, OutputTokens: 100, CostUSD: 0.1},
+		{Stream: true, TerminationReason: TerminationCompleted, OutputTokens: 50, CostUSD: 0.05},
+		{Stream: true, TerminationReason: TerminationClientAbort, OutputTokens: 20},
+		{Stream: true, TerminationReason: TerminationUpstreamError},
+		{Stream: false, Success: true, OutputTokens: 999}, // 非流式请求不计入
+	}
+
+	result := ComputeTerminationStats(records)
+	if len(result) != len(terminationOrder) {
+		t.Fatalf("got %d reasons, want %d", len(result), len(terminationOrder))
+	}
+	for i, entry := range result {
+		if entry.Reason != terminationOrder[i] {
+			t.Errorf("result[%d].Reason = %s, want %s", i, entry.Reason, terminationOrder[i])
+		}
+	}
+
+	completed := result[0]
+	if completed.Requests != 2 || completed.Share != 0.5 || completed.OutputTokens != 150 {
+		t.Errorf("completed = %+v", completed)
+	}
+	if abort := result[1]; abort.Requests != 1 || abort.Share != 0.25 || abort.OutputTokens != 20 {
+		t.Errorf("client_abort = %+v", abort)
+	}
+	if shutdown := result[4]; shutdown.Requests != 0 || shutdown.Share != 0 {
+		t.Errorf("cancelled_on_shutdown = %+v, want zero", shutdown)
+	}
+}
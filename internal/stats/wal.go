@@ -0,0 +1,329 @@
+package stats
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	walFile                 = "usage.wal"
+	defaultWALFlushInterval = time.Second
+	walBatchSize            = 256 // 缓冲的记录达到这个数量时不等定时器立即写入
+)
+
+// WALStatus 使用记录写前日志的状态
+type WALStatus struct {
+	Path          string    `json:"path"`
+	Pending       int       `json:"pending"`    // 已记录但还没有写入磁盘的记录数（重启会丢失的积压）
+	Entries       int       `json:"entries"`    // 日志文件中的记录数
+	SizeBytes     int64     `json:"size_bytes"` // 日志文件大小
+	Replayed      int       `json:"replayed"`   // 启动时从日志恢复的记录数
+	Dropped       int       `json:"dropped"`    // 积压超过上限而丢弃的记录数
+	LastFlushAt   time.Time `json:"last_flush_at,omitempty"`
+	LastCompactAt time.Time `json:"last_compact_at,omitempty"`
+	LastError     string    `json:"last_error,omitempty"` // 最近一次写入失败的原因，写入成功后清空
+}
+
+// WAL 使用记录的本地追加写日志：Recorder 写入的记录先进入内存缓冲，由后台任务批量追加到磁盘，
+// 启动时重放到 Recorder，使使用统计和配额计数在重启后保留。文件记录数超过容量的两倍时压缩到最近的 maxEntries 条。
+// 磁盘写入持续失败时缓冲最多保留 maxEntries 条，超出时丢弃最早的记录
+type WAL struct {
+	path       string
+	interval   time.Duration
+	maxEntries int
+
+	pending     []UsageRecord
+	entries     int
+	size        int64
+	replayed    int
+	dropped     int
+	lastLogged  int // 上次告警时的丢弃数
+	lastFlush   time.Time
+	lastCompact time.Time
+	lastError   string
+	wakeCh      chan struct{}
+	stopCh      chan struct{}
+	mutex       sync.Mutex // 保护缓冲和状态
+	fileMutex   sync.Mutex // 串行化文件的追加和压缩
+}
+
+// NewWAL 创建使用记录日志，dir 为空时使用 ~/.llm-gateway/usage；maxEntries<=0 时与 Recorder 默认容量一致
+func NewWAL(config *types.UsageWALConfig, maxEntries int) *WAL {
+	dir := config.Dir
+	if dir == "" {
+		homeDir, err := os.UserHomeDir()
+		if err != nil {
+			dir = filepath.Join(".llm-gateway", "usage")
+		} else {
+			dir = filepath.Join(homeDir, ".llm-gateway", "usage")
+		}
+	}
+	interval := time.Duration(config.FlushIntervalSeconds) * time.Second
+	if interval <= 0 {
+		interval = defaultWALFlushInterval
+	}
+	if maxEntries <= 0 {
+		maxEntries = defaultMaxRecords
+	}
+	return &WAL{
+		path:       filepath.Join(dir, walFile),
+		interval:   interval,
+		maxEntries: maxEntries,
+		wakeCh:     make(chan struct{}, 1),
+	}
+}
+
+// Replay 把日志中的记录按顺序写入 Recorder，返回恢复的记录数。应在订阅 Recorder 之前调用，
+// 否则恢复的记录会被再次追加到日志。写入中断产生的不完整行被跳过
+func (w *WAL) Replay(recorder *Recorder) (int, error) {
+	w.fileMutex.Lock()
+	defer w.fileMutex.Unlock()
+
+	records, size, err := w.read()
+	if err != nil {
+		return 0, err
+	}
+	for _, record := range records {
+		recorder.Record(record)
+	}
+
+	w.mutex.Lock()
+	defer w.mutex.Unlock()
+	w.entries = len(records)
+	w.size = size
+	w.replayed = len(records)
+	return len(records), nil
+}
+
+// Append 把记录放入写入缓冲，用作 Recorder 的订阅回调
+func (w *WAL) Append(record UsageRecord) {
+	w.mutex.Lock()
+	w.pending = append(w.pending, record)
+	w.trimPendingUnsafe()
+	full := len(w.pending) >= walBatchSize
+	w.mutex.Unlock()
+
+	if full {
+		select {
+		case w.wakeCh <- struct{}{}:
+		default:
+		}
+	}
+}
+
+// Flush 把缓冲的记录追加到磁盘并同步，写入失败时记录保留在缓冲中等待下次写入
+func (w *WAL) Flush() error {
+	w.fileMutex.Lock()
+	defer w.fileMutex.Unlock()
+
+	w.mutex.Lock()
+	batch := w.pending
+	w.pending = nil
+	entries := w.entries
+	w.mutex.Unlock()
+
+	if len(batch) > 0 {
+		written, err := w.appendBatch(batch)
+		w.mutex.Lock()
+		if err != nil {
+			w.pending = append(batch, w.pending...)
+			w.trimPendingUnsafe()
+			w.lastError = err.Error()
+			dropped := w.dropped - w.lastLogged
+			w.lastLogged = w.dropped
+			w.mutex.Unlock()
+			if dropped > 0 {
+				logger.Warn("使用记录日志积压超过 %d 条，已丢弃 %d 条最早的记录", w.maxEntries, dropped)
+			}
+			return err
+		}
+		w.entries += len(batch)
+		w.size += written
+		w.lastFlush = time.Now()
+		w.lastError = ""
+		entries = w.entries
+		w.mutex.Unlock()
+	}
+
+	if entries > 2*w.maxEntries {
+		return w.compact()
+	}
+	return nil
+}
+
+// trimPendingUnsafe 缓冲超过 maxEntries 条时丢弃最早的记录，这些记录即使写入也会在压缩时被删除（调用方持有锁）
+func (w *WAL) trimPendingUnsafe() {
+	if excess := len(w.pending) - w.maxEntries; excess > 0 {
+		w.pending = w.pending[excess:]
+		w.dropped += excess
+	}
+}
+
+// appendBatch 把一批记录追加到日志文件，返回写入的字节数（调用方持有文件锁）
+func (w *WAL) appendBatch(batch []UsageRecord) (int64, error) {
+	var buffer bytes.Buffer
+	for i := range batch {
+		data, err := json.Marshal(&batch[i])
+		if err != nil {
+			return 0, fmt.Errorf("序列化使用记录失败: %w", err)
+		}
+		buffer.Write(data)
+		buffer.WriteByte('\n')
+	}
+
+	if err := os.MkdirAll(filepath.Dir(w.path), 0700); err != nil {
+		return 0, fmt.Errorf("创建使用记录目录失败: %w", err)
+	}
+	file, err := os.OpenFile(w.path, os.O_CREATE|os.O_WRONLY|os.O_APPEND, 0600)
+	if err != nil {
+		return 0, fmt.Errorf("打开使用记录日志失败: %w", err)
+	}
+	defer func() { _ = file.Close() }()
+
+	if _, err := file.Write(buffer.Bytes()); err != nil {
+		return 0, fmt.Errorf("写入使用记录日志失败: %w", err)
+	}
+	if err := file.Sync(); err != nil {
+		return 0, fmt.Errorf("同步使用记录日志失败: %w", err)
+	}
+	return int64(buffer.Len()), nil
+}
+
+// compact 只保留最近的 maxEntries 条记录，先写临时文件再替换（调用方持有文件锁）
+func (w *WAL) compact() error {
+	records, _, err := w.read()
+	if err != nil {
+		return err
+	}
+	if len(records) > w.maxEntries {
+		records = records[len(records)-w.maxEntries:]
+	}
+
+	var buffer bytes.Buffer
+	for i := range records {
+		data, err := json.Marshal(&records[i])
+		if err != nil {
+			return fmt.Errorf("序列化使用记录失败: %w", err)
+		}
+		buffer.Write(data)
+		buffer.WriteByte('\n')
+	}
+
+	tmpPath := w.path + ".tmp"
+	if err := os.WriteFile(tmpPath, buffer.Bytes(), 0600); err != nil {
+		return fmt.Errorf("写入使用记录日志失败: %w", err)
+	}
+	if err := os.Rename(tmpPath, w.path); err != nil {
+		return fmt.Errorf("替换使用记录日志失败: %w", err)
+	}
+
+	w.mutex.Lock()
+	defer w.mutex.Unlock()
+	w.entries = len(records)
+	w.size = int64(buffer.Len())
+	w.lastCompact = time.Now()
+	return nil
+}
+
+// read 读取日志中的全部记录和文件大小，文件不存在时为空（调用方持有文件锁）
+func (w *WAL) read() ([]UsageRecord, int64, error) {
+	file, err := os.Open(w.path)
+	if err != nil {
+		if os.IsNotExist(err) {
+			return nil, 0, nil
+		}
+		return nil, 0, fmt.Errorf("读取使用记录日志失败: %w", err)
+	}
+	defer func() { _ = file.Close() }()
+
+	var records []UsageRecord
+	var size int64
+	scanner := bufio.NewScanner(file)
+	scanner.Buffer(make([]byte, 64*1024), 16*1024*1024)
+	for scanner.Scan() {
+		size += int64(len(scanner.Bytes())) + 1
+		var record UsageRecord
+		if err := json.Unmarshal(scanner.Bytes(), &record); err != nil {
+			continue // 跳过写入中断产生的不完整行
+		}
+		records = append(records, record)
+	}
+	if err := scanner.Err(); err != nil {
+		return nil, 0, fmt.Errorf("读取使用记录日志失败: %w", err)
+	}
+	return records, size, nil
+}
+
+// Status 返回日志的积压和文件状态
+func (w *WAL) Status() WALStatus {
+	w.mutex.Lock()
+	defer w.mutex.Unlock()
+	return WALStatus{
+		Path:          w.path,
+		Pending:       len(w.pending),
+		Entries:       w.entries,
+		SizeBytes:     w.size,
+		Replayed:      w.replayed,
+		Dropped:       w.dropped,
+		LastFlushAt:   w.lastFlush,
+		LastCompactAt: w.lastCompact,
+		LastError:     w.lastError,
+	}
+}
+
+// Start 启动后台写入，按 flush_interval_seconds 或缓冲满时把记录写入磁盘
+func (w *WAL) Start() {
+	if w == nil {
+		return
+	}
+	w.mutex.Lock()
+	defer w.mutex.Unlock()
+
+	if w.stopCh != nil {
+		return
+	}
+	w.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(w.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+			case <-w.wakeCh:
+			case <-stopCh:
+				return
+			}
+			if err := w.Flush(); err != nil {
+				logger.Warn("写入使用记录日志失败: %v", err)
+			}
+		}
+	}(w.stopCh)
+}
+
+// Stop 停止后台写入，并把剩余的缓冲写入磁盘
+func (w *WAL) Stop() {
+	if w == nil {
+		return
+	}
+	w.mutex.Lock()
+	if w.stopCh != nil {
+		close(w.stopCh)
+		w.stopCh = nil
+	}
+	w.mutex.Unlock()
+
+	if err := w.Flush(); err != nil {
+		logger.Warn("写入使用记录日志失败: %v", err)
+	}
+}
@@ -0,0 +1,102 @@
+package stats
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestWAL_ReplayAfterRestart(t *testing.T) {
+	config := &types.UsageWALConfig{Enabled: true, Dir: t.TempDir()}
+	start := time.Date(2024, 6, 1, 0, 0, 0, 0, time.UTC)
+
+	wal := NewWAL(config, 10)
+	recorder := NewRecorder(10)
+	recorder.Subscribe(wal.Append)
+	for i := 0; i < 3; i++ {
+		recorder.Record(UsageRecord{RequestID: string(rune('a' + i)), Timestamp: start.Add(time.Duration(i) * time.Minute), CostUSD: 0.5})
+	}
+	if status := wal.Status(); status.Pending != 3 || status.Entries != 0 {
+		t.Fatalf("status before flush = %+v, want 3 pending", status)
+	}
+	if err := wal.Flush(); err != nil {
+		t.Fatalf("Flush() error = %v", err)
+	}
+	if status := wal.Status(); status.Pending != 0 || status.Entries != 3 || status.SizeBytes == 0 {
+		t.Fatalf("status after flush = %+v, want 3 entries on disk", status)
+	}
+
+	// 模拟写入中断留下的不完整行
+	file, err := os.OpenFile(wal.Status().Path, os.O_WRONLY|os.O_APPEND, 0600)
+	if err != nil {
+		t.Fatal(err)
+	}
+	_, _ = file.WriteString(`{"request_id":"d","timest`)
+	_ = file.Close()
+
+	restarted := NewRecorder(10)
+	replayed, err := NewWAL(config, 10).Replay(restarted)
+	if err != nil {
+		t.Fatalf("Replay() error = %v", err)
+	}
+	records := restarted.Query(Filter{})
+	if replayed != 3 || len(records) != 3 || records[0].RequestID != "a" || records[2].CostUSD != 0.5 {
+		t.Errorf("replayed %d records %v, want a..c", replayed, records)
+	}
+}
+
+func TestWAL_Compact(t *testing.T) {
+	wal := NewWAL(&types.UsageWALConfig{Enabled: true, Dir: t.TempDir()}, 2)
+	for i := 0; i < 5; i++ {
+		wal.Append(UsageRecord{RequestID: string(rune('a' + i))})
+	}
+
+	// 超过容量的两倍时只保留最近的记录
+	if err := wal.Flush(); err != nil {
+		t.Fatalf("Flush() error = %v", err)
+	}
+	status := wal.Status()
+	if status.Entries != 2 || status.LastCompactAt.IsZero() {
+		t.Fatalf("status = %+v, want 2 entries after compaction", status)
+	}
+
+	recorder := NewRecorder(10)
+	if _, err := wal.Replay(recorder); err != nil {
+		t.Fatalf("Replay() error = %v", err)
+	}
+	records := recorder.Query(Filter{})
+	if len(records) != 2 || records[0].RequestID != "d" || records[1].RequestID != "e" {
+		t.Errorf("records = %v, want d and e", records)
+	}
+}
+
+func TestWAL_PendingCappedWhileWriteFails(t *testing.T) {
+	// 用普通文件占用日志目录的位置，使写入一直失败
+	blocker := filepath.Join(t.TempDir(), "blocker")
+	if err := os.WriteFile(blocker, nil, 0600); err != nil {
+		t.Fatal(err)
+	}
+	wal := NewWAL(&types.UsageWALConfig{Enabled: true, Dir: filepath.Join(blocker, "usage")}, 3)
+
+	for i := 0; i < 5; i++ {
+		wal.Append(UsageRecord{RequestID: string(rune('a' + i))})
+	}
+	if err := wal.Flush(); err == nil {
+		t.Fatal("Flush() error = nil, want a write error")
+	}
+	wal.Append(UsageRecord{RequestID: "f"})
+
+	status := wal.Status()
+	if status.Pending != 3 || status.Dropped != 3 || status.LastError == "" {
+		t.Fatalf("status = %+v, want 3 pending and 3 dropped", status)
+	}
+	wal.mutex.Lock()
+	oldest := wal.pending[0].RequestID
+	wal.mutex.Unlock()
+	if oldest != "d" {
+		t.Errorf("oldest pending record = %q, want d", oldest)
+	}
+}
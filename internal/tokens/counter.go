@@ -0,0 +1,163 @@
+package tokens
+
+import (
+	"encoding/json"
+	"math"
+	"unicode"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// imageTokens 图片内容块按固定token数估算（约为OpenAI高清图片一个512像素分块加基础开销）
+const imageTokens = 765
+
+// Counter 按提供商的分词方式估算token数
+type Counter interface {
+	Count(text string) int                      // 文本的token数
+	CountRequest(req *types.UnifiedRequest) int // 请求的输入token数
+}
+
+// approxCounter 按提供商分词器的特点近似计数：英文单词、数字分组、标点、CJK字符分别计算，
+// 比按字节数除以4更接近真实分词结果，尤其是代码和中日韩文本。没有内置BPE词表，结果仍是估算值
+type approxCounter struct {
+	wordChars       float64 // 一个token平均覆盖的连续字母数
+	digitChars      float64 // 一个token覆盖的连续数字数
+	cjkPerRune      float64 // 每个中日韩字符的token数
+	messageOverhead int     // 每条消息的格式开销（角色、分隔符）
+	requestOverhead int     // 每个请求的固定开销（回复起始标记等）
+}
+
+// 各提供商分词器的近似参数
+var (
+	// OpenAI cl100k/o200k：常见英文单词一个token，数字最多3位一组
+	openAICounter = &approxCounter{wordChars: 6, digitChars: 3, cjkPerRune: 1, messageOverhead: 4, requestOverhead: 3}
+
+	// Anthropic：分词粒度比OpenAI略细，中日韩文本的token数更多
+	anthropicCounter = &approxCounter{wordChars: 5, digitChars: 3, cjkPerRune: 1.3, messageOverhead: 5, requestOverhead: 5}
+
+	// Gemini、Qwen等：词表对中日韩文本更友好
+	defaultCounter = &approxCounter{wordChars: 6, digitChars: 3, cjkPerRune: 0.8, messageOverhead: 4, requestOverhead: 3}
+)
+
+// ForProvider 返回提供商对应的token计数器
+func ForProvider(provider types.Provider) Counter {
+	switch provider {
+	case types.ProviderOpenAI, types.ProviderAzure:
+		return openAICounter
+	case types.ProviderAnthropic, types.ProviderBedrock:
+		return anthropicCounter
+	default:
+		return defaultCounter
+	}
+}
+
+// Count 估算文本的token数
+func (c *approxCounter) Count(text string) int {
+	total := 0.0
+	runes := []rune(text)
+	for i := 0; i < len(runes); {
+		r := runes[i]
+		switch {
+		case isCJK(r):
+			total += c.cjkPerRune
+			i++
+		case r == '\n':
+			// 连续换行合并为一个token
+			for i < len(runes) && runes[i] == '\n' {
+				i++
+			}
+			total++
+		case unicode.IsSpace(r):
+			// 空格通常并入后面的单词
+			i++
+		case unicode.IsDigit(r):
+			n := runLength(runes, i, unicode.IsDigit)
+			total += math.Ceil(float64(n) / c.digitChars)
+			i += n
+		case unicode.IsLetter(r):
+			n := runLength(runes, i, func(r rune) bool { return unicode.IsLetter(r) && !isCJK(r) })
+			chars := c.wordChars
+			if r > unicode.MaxASCII {
+				chars /= 2 // 非拉丁字母的词表覆盖率较低
+			}
+			total += math.Ceil(float64(n) / chars)
+			i += n
+		default:
+			// 连续的相同标点（如 ``` 或 ===）通常两个合并为一个token
+			n := runLength(runes, i, func(next rune) bool { return next == r })
+			total += math.Ceil(float64(n) / 2)
+			i += n
+		}
+	}
+	return int(math.Ceil(total))
+}
+
+// CountRequest 估算请求的输入token数：消息文本、工具调用、工具定义和图片，加上消息与请求的格式开销
+func (c *approxCounter) CountRequest(req *types.UnifiedRequest) int {
+	total := c.requestOverhead
+	for _, message := range req.Messages {
+		total += c.messageOverhead + c.countContent(message.Content)
+		for _, call := range message.ToolCalls {
+			total += c.countJSON(call)
+		}
+	}
+	for _, tool := range req.Tools {
+		total += c.countJSON(tool)
+	}
+	return total
+}
+
+// countContent 估算消息内容：字符串，或文本/图片/工具调用/工具结果内容块数组
+func (c *approxCounter) countContent(content interface{}) int {
+	switch content := content.(type) {
+	case string:
+		return c.Count(content)
+	case []interface{}:
+		total := 0
+		for _, item := range content {
+			block, ok := item.(map[string]interface{})
+			if !ok {
+				continue
+			}
+			switch block["type"] {
+			case "text":
+				text, _ := block["text"].(string)
+				total += c.Count(text)
+			case "image", "image_url":
+				total += imageTokens
+			case "tool_result":
+				total += c.countContent(block["content"])
+			default:
+				total += c.countJSON(block)
+			}
+		}
+		return total
+	case nil:
+		return 0
+	default:
+		return c.countJSON(content)
+	}
+}
+
+// countJSON 按JSON文本估算结构化内容（工具定义、工具调用参数等）
+func (c *approxCounter) countJSON(value interface{}) int {
+	data, err := json.Marshal(value)
+	if err != nil {
+		return 0
+	}
+	return c.Count(string(data))
+}
+
+// runLength 返回从 start 开始连续满足 match 的字符数
+func runLength(runes []rune, start int, match func(rune) bool) int {
+	n := 0
+	for start+n < len(runes) && match(runes[start+n]) {
+		n++
+	}
+	return n
+}
+
+// isCJK 是否为中日韩文字（汉字、假名、谚文）
+func isCJK(r rune) bool {
+	return unicode.Is(unicode.Han, r) || unicode.Is(unicode.Hiragana, r) || unicode.Is(unicode.Katakana, r) || unicode.Is(unicode.Hangul, r)
+}
@@ -0,0 +1,64 @@
+package tokens
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestCounter_Count(t *testing.T) {
+	counter := ForProvider(types.ProviderOpenAI)
+
+	tests := []struct {
+		name string
+		text string
+		want int
+	}{
+		{name: "empty", text: "", want: 0},
+		{name: "short words", text: "Hello world, how are you?", want: 7},
+		{name: "long word splits", text: "internationalization", want: 4},
+		{name: "digits in groups of three", text: "1234567", want: 3},
+		{name: "CJK per character", text: "你好世界", want: 4},
+		{name: "repeated punctuation merges", text: "```", want: 2},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := counter.Count(tt.text); got != tt.want {
+				t.Errorf("Count(%q) = %d, want %d", tt.text, got, tt.want)
+			}
+		})
+	}
+
+	// CJK文本按字符计数，远多于按字节数除以4
+	chinese := strings.Repeat("上下文窗口", 20)
+	if got := counter.Count(chinese); got != 100 {
+		t.Errorf("Count(chinese) = %d, want 100 (len/4 would give %d)", got, len(chinese)/4)
+	}
+	if anthropic := ForProvider(types.ProviderAnthropic).Count(chinese); anthropic <= 100 {
+		t.Errorf("Anthropic count = %d, want more tokens than OpenAI for CJK text", anthropic)
+	}
+}
+
+func TestCounter_CountRequest(t *testing.T) {
+	counter := ForProvider(types.ProviderOpenAI)
+	req := &types.UnifiedRequest{
+		Messages: []types.Message{
+			{Role: "system", Content: "Be brief."},
+			{Role: "user", Content: []interface{}{
+				map[string]interface{}{"type": "text", "text": "Describe this"},
+				map[string]interface{}{"type": "image_url", "image_url": map[string]interface{}{"url": "data:image/png;base64,AAAA"}},
+			}},
+		},
+		Tools: []map[string]interface{}{
+			{"type": "function", "function": map[string]interface{}{"name": "lookup"}},
+		},
+	}
+
+	// 请求开销3 + 两条消息各4 + "Be brief."(3) + "Describe this"(3) + 图片765 + 工具定义
+	got := counter.CountRequest(req)
+	base := 3 + 4 + 4 + 3 + 3 + imageTokens
+	if got <= base || got > base+40 {
+		t.Errorf("CountRequest() = %d, want slightly above %d", got, base)
+	}
+}
@@ -0,0 +1,334 @@
+package transform
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"regexp"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/internal/audit"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 转换器类型
+const (
+	TypeSystemPrompt = "system_prompt" // 注入系统提示词（请求）
+	TypeStripFields  = "strip_fields"  // 删除请求参数（请求）
+	TypeMaskPII      = "mask_pii"      // 替换邮箱、银行卡号、电话号码（请求和/或响应）
+	TypeRedact       = "redact"        // 按正则表达式替换文本（请求和/或响应）
+)
+
+// defaultReplacement redact 转换器未配置替换文本时使用的值
+const defaultReplacement = "[REDACTED]"
+
+// responseTextFields 响应中按文本处理的字段（各格式的消息文本和流式增量），其他字段（ID、工具参数等）保持不变
+var responseTextFields = map[string]bool{"text": true, "content": true}
+
+// Pipeline 按配置顺序执行的请求/响应转换器，为nil时不做任何处理
+type Pipeline struct {
+	transformers []*transformer
+}
+
+// transformer 编译后的单个转换器
+type transformer struct {
+	name         string
+	kind         string
+	models       []string
+	keys         map[string]bool
+	prompt       string
+	appendPrompt bool
+	fields       []string
+	patterns     []*regexp.Regexp
+	replacement  string
+	request      bool
+	response     bool
+}
+
+// New 编译转换器配置，没有配置时返回nil
+func New(configs []types.TransformConfig) (*Pipeline, error) {
+	if len(configs) == 0 {
+		return nil, nil
+	}
+
+	pipeline := &Pipeline{}
+	for _, config := range configs {
+		t := &transformer{
+			name:         config.Name,
+			kind:         config.Type,
+			models:       config.Models,
+			prompt:       config.Prompt,
+			appendPrompt: config.Position == "append",
+			fields:       config.Fields,
+			replacement:  config.Replacement,
+			request:      config.Apply != "response",
+			response:     config.Apply != "request",
+		}
+		if len(config.Keys) > 0 {
+			t.keys = make(map[string]bool, len(config.Keys))
+			for _, key := range config.Keys {
+				t.keys[key] = true
+			}
+		}
+
+		switch config.Type {
+		case TypeSystemPrompt, TypeStripFields:
+			t.response = false
+		case TypeMaskPII:
+		case TypeRedact:
+			if t.replacement == "" {
+				t.replacement = defaultReplacement
+			}
+			for _, pattern := range config.Patterns {
+				re, err := regexp.Compile(pattern)
+				if err != nil {
+					return nil, fmt.Errorf("转换器 %s: 无效的正则表达式 %q: %w", config.Name, pattern, err)
+				}
+				t.patterns = append(t.patterns, re)
+			}
+		default:
+			return nil, fmt.Errorf("转换器 %s: 无效的类型: %s", config.Name, config.Type)
+		}
+		pipeline.transformers = append(pipeline.transformers, t)
+	}
+	return pipeline, nil
+}
+
+// matches 检查转换器是否处理该模型和Key的请求
+func (t *transformer) matches(model, keyID string) bool {
+	if t.keys != nil && !t.keys[keyID] {
+		return false
+	}
+	if len(t.models) == 0 {
+		return true
+	}
+	for _, pattern := range t.models {
+		if pattern == model || (strings.HasSuffix(pattern, "*") && strings.HasPrefix(model, strings.TrimSuffix(pattern, "*"))) {
+			return true
+		}
+	}
+	return false
+}
+
+// rewriteText 对文本执行 mask_pii 或 redact 替换
+func (t *transformer) rewriteText(text string) string {
+	if t.kind == TypeMaskPII {
+		return audit.MaskPII(text)
+	}
+	for _, re := range t.patterns {
+		text = re.ReplaceAllString(text, t.replacement)
+	}
+	return text
+}
+
+// ApplyRequest 按顺序对请求执行命中的请求转换器，返回执行了的转换器名称
+func (p *Pipeline) ApplyRequest(request *types.UnifiedRequest, keyID string) []string {
+	if p == nil {
+		return nil
+	}
+
+	var applied []string
+	for _, t := range p.transformers {
+		if !t.request || !t.matches(request.Model, keyID) {
+			continue
+		}
+		switch t.kind {
+		case TypeSystemPrompt:
+			injectSystemPrompt(request, t.prompt, t.appendPrompt)
+		case TypeStripFields:
+			stripFields(request, t.fields)
+		default:
+			rewriteRequestText(request, t.rewriteText)
+		}
+		applied = append(applied, t.name)
+	}
+	return applied
+}
+
+// ResponseFilter 返回请求命中的响应转换器，没有命中时返回nil
+func (p *Pipeline) ResponseFilter(model, keyID string) *ResponseFilter {
+	if p == nil {
+		return nil
+	}
+
+	var matched []*transformer
+	for _, t := range p.transformers {
+		if t.response && t.matches(model, keyID) {
+			matched = append(matched, t)
+		}
+	}
+	if len(matched) == 0 {
+		return nil
+	}
+	return &ResponseFilter{transformers: matched}
+}
+
+// injectSystemPrompt 把提示词加到系统消息的开头或末尾，没有系统消息时新增一条。
+// Anthropic 格式的请求同时修改原始 system 字段（转换回 Anthropic 格式时优先使用）
+func injectSystemPrompt(request *types.UnifiedRequest, prompt string, appendPrompt bool) {
+	join := func(text string) string {
+		if text == "" {
+			return prompt
+		}
+		if appendPrompt {
+			return text + "\n\n" + prompt
+		}
+		return prompt + "\n\n" + text
+	}
+
+	injected := false
+	for i := range request.Messages {
+		if request.Messages[i].Role != "system" {
+			continue
+		}
+		if text, ok := request.Messages[i].Content.(string); ok {
+			request.Messages[i].Content = join(text)
+			injected = true
+		}
+		break
+	}
+	if !injected {
+		request.Messages = append([]types.Message{{Role: "system", Content: prompt}}, request.Messages...)
+	}
+
+	if request.OriginalSystem != nil {
+		block := types.SystemBlock{Type: "text", Text: prompt}
+		blocks := append([]types.SystemBlock{}, request.OriginalSystem.ToArray()...)
+		if appendPrompt {
+			blocks = append(blocks, block)
+		} else {
+			blocks = append([]types.SystemBlock{block}, blocks...)
+		}
+		system := &types.SystemField{}
+		system.SetArray(blocks)
+		request.OriginalSystem = system
+	}
+}
+
+// stripFields 删除请求参数：已解析的参数恢复为未设置，其他参数从额外参数中删除
+func stripFields(request *types.UnifiedRequest, fields []string) {
+	for _, field := range fields {
+		switch field {
+		case "temperature":
+			request.Temperature = 0
+		case "top_p":
+			request.TopP = nil
+		case "tools":
+			request.Tools = nil
+		case "tool_choice":
+			request.ToolChoice = nil
+		case "metadata":
+			request.OriginalMetadata = nil
+		default:
+			delete(request.ExtraParams, field)
+		}
+	}
+}
+
+// rewriteRequestText 替换请求中所有消息和原始 system 字段的文本
+func rewriteRequestText(request *types.UnifiedRequest, rewrite func(string) string) {
+	for i := range request.Messages {
+		request.Messages[i].Content = rewriteContent(request.Messages[i].Content, rewrite)
+	}
+	if request.OriginalSystem != nil {
+		blocks := append([]types.SystemBlock{}, request.OriginalSystem.ToArray()...)
+		for i := range blocks {
+			blocks[i].Text = rewrite(blocks[i].Text)
+		}
+		system := &types.SystemField{}
+		system.SetArray(blocks)
+		request.OriginalSystem = system
+	}
+}
+
+// rewriteContent 替换消息内容中的文本：字符串内容，或内容块数组中的 text 字段
+func rewriteContent(content interface{}, rewrite func(string) string) interface{} {
+	switch v := content.(type) {
+	case string:
+		return rewrite(v)
+	case []interface{}:
+		for _, item := range v {
+			if block, ok := item.(map[string]interface{}); ok {
+				if text, ok := block["text"].(string); ok {
+					block["text"] = rewrite(text)
+				}
+			}
+		}
+		return v
+	default:
+		return content
+	}
+}
+
+// ResponseFilter 对返回给客户端的响应执行请求命中的响应转换器。
+// 流式响应按事件处理，跨越多个事件的文本不会被匹配
+type ResponseFilter struct {
+	transformers []*transformer
+}
+
+// Body 处理JSON响应体（或单个流式事件的数据），不是JSON或没有修改时原样返回
+func (f *ResponseFilter) Body(body []byte) []byte {
+	var value interface{}
+	if err := json.Unmarshal(body, &value); err != nil {
+		return body
+	}
+	if !f.rewriteValue(value) {
+		return body
+	}
+	rewritten, err := json.Marshal(value)
+	if err != nil {
+		return body
+	}
+	return rewritten
+}
+
+// SSELine 处理透传的SSE行中的 data 内容，没有修改时返回nil
+func (f *ResponseFilter) SSELine(line []byte) []byte {
+	content := bytes.TrimRight(line, "\r\n")
+	if !bytes.HasPrefix(content, []byte("data:")) {
+		return nil
+	}
+	data := bytes.TrimSpace(content[len("data:"):])
+	rewritten := f.Body(data)
+	if bytes.Equal(rewritten, data) {
+		return nil
+	}
+
+	result := append([]byte("data: "), rewritten...)
+	return append(result, line[len(content):]...)
+}
+
+// rewriteValue 递归替换JSON值中文本字段的内容，返回是否有修改
+func (f *ResponseFilter) rewriteValue(value interface{}) bool {
+	changed := false
+	switch v := value.(type) {
+	case map[string]interface{}:
+		for key, item := range v {
+			if text, ok := item.(string); ok && responseTextFields[key] {
+				if rewritten := f.rewriteText(text); rewritten != text {
+					v[key] = rewritten
+					changed = true
+				}
+				continue
+			}
+			if f.rewriteValue(item) {
+				changed = true
+			}
+		}
+	case []interface{}:
+		for _, item := range v {
+			if f.rewriteValue(item) {
+				changed = true
+			}
+		}
+	}
+	return changed
+}
+
+// rewriteText 依次执行所有响应转换器
+func (f *ResponseFilter) rewriteText(text string) string {
+	for _, t := range f.transformers {
+		text = t.rewriteText(text)
+	}
+	return text
+}
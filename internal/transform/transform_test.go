@@ -0,0 +1,119 @@
+package transform
+
+import (
+	"encoding/json"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestPipeline_ApplyRequest(t *testing.T) {
+	pipeline, err := New([]types.TransformConfig{
+		{Name: "policy", Type: TypeSystemPrompt, Prompt: "Follow the company policy."},
+		{Name: "footer", Type: TypeSystemPrompt, Prompt: "Answer in English.", Position: "append", Models: []string{"gpt-*"}},
+		{Name: "no-sampling", Type: TypeStripFields, Fields: []string{"temperature", "logprobs"}, Keys: []string{"key-1"}},
+		{Name: "pii", Type: TypeMaskPII, Apply: "request"},
+	})
+	if err != nil {
+		t.Fatalf("New() error = %v", err)
+	}
+
+	request := &types.UnifiedRequest{
+		Model:       "gpt-4o",
+		Temperature: 0.7,
+		Messages: []types.Message{
+			{Role: "system", Content: "You are helpful."},
+			{Role: "user", Content: []interface{}{map[string]interface{}{"type": "text", "text": "mail me at jane@example.com"}}},
+		},
+		ExtraParams: map[string]json.RawMessage{"logprobs": json.RawMessage("true")},
+	}
+	applied := pipeline.ApplyRequest(request, "key-1")
+	if strings.Join(applied, ",") != "policy,footer,no-sampling,pii" {
+		t.Errorf("applied = %v", applied)
+	}
+	if system := request.Messages[0].Content; system != "Follow the company policy.\n\nYou are helpful.\n\nAnswer in English." {
+		t.Errorf("system prompt = %q", system)
+	}
+	if request.Temperature != 0 || len(request.ExtraParams) != 0 {
+		t.Errorf("fields not stripped: temperature=%v extra=%v", request.Temperature, request.ExtraParams)
+	}
+	block := request.Messages[1].Content.([]interface{})[0].(map[string]interface{})
+	if block["text"] != "mail me at [EMAIL]" {
+		t.Errorf("user text = %v", block["text"])
+	}
+
+	// 模型和Key不匹配的转换器被跳过，没有系统消息时新增一条
+	request = &types.UnifiedRequest{
+		Model:          "claude-sonnet-4",
+		Messages:       []types.Message{{Role: "user", Content: "hi"}},
+		OriginalSystem: &types.SystemField{},
+	}
+	request.OriginalSystem.SetArray([]types.SystemBlock{{Type: "text", Text: "Be brief."}})
+	applied = pipeline.ApplyRequest(request, "key-2")
+	if strings.Join(applied, ",") != "policy,pii" {
+		t.Errorf("applied = %v", applied)
+	}
+	if request.Messages[0].Role != "system" || request.Messages[0].Content != "Follow the company policy." {
+		t.Errorf("messages = %+v", request.Messages)
+	}
+	// Anthropic 的原始 system 字段同步注入
+	if blocks := request.OriginalSystem.ToArray(); len(blocks) != 2 || blocks[0].Text != "Follow the company policy." {
+		t.Errorf("original system = %+v", blocks)
+	}
+
+	var nilPipeline *Pipeline
+	if applied := nilPipeline.ApplyRequest(request, ""); applied != nil {
+		t.Errorf("nil pipeline applied = %v", applied)
+	}
+}
+
+func TestResponseFilter(t *testing.T) {
+	pipeline, err := New([]types.TransformConfig{
+		{Name: "prompt", Type: TypeSystemPrompt, Prompt: "x"},
+		{Name: "secrets", Type: TypeRedact, Patterns: []string{`PRJ-\d+`}},
+		{Name: "pii", Type: TypeMaskPII, Apply: "response", Models: []string{"claude-*"}},
+	})
+	if err != nil {
+		t.Fatalf("New() error = %v", err)
+	}
+
+	filter := pipeline.ResponseFilter("claude-sonnet-4", "")
+	body := filter.Body([]byte(`{"id":"PRJ-1","content":[{"type":"text","text":"see PRJ-42, ask bob@example.com"}]}`))
+	if string(body) != `{"content":[{"text":"see [REDACTED], ask [EMAIL]","type":"text"}],"id":"PRJ-1"}` {
+		t.Errorf("Body() = %s", body)
+	}
+	unchanged := []byte(`{"choices":[{"message":{"content":"all good"}}]}`)
+	if body := filter.Body(unchanged); string(body) != string(unchanged) {
+		t.Errorf("Body() rewrote an unchanged response: %s", body)
+	}
+
+	// 透传的SSE行保留行尾，不需要修改时返回nil
+	line := filter.SSELine([]byte("data: {\"delta\":{\"text\":\"PRJ-7\"}}\n"))
+	if string(line) != "data: {\"delta\":{\"text\":\"[REDACTED]\"}}\n" {
+		t.Errorf("SSELine() = %q", line)
+	}
+	for _, line := range []string{"event: content_block_delta\n", "data: [DONE]\n", "data: {\"text\":\"ok\"}\n"} {
+		if rewritten := filter.SSELine([]byte(line)); rewritten != nil {
+			t.Errorf("SSELine(%q) = %q, want nil", line, rewritten)
+		}
+	}
+
+	// 只有请求转换器命中时不需要响应处理
+	pipeline, _ = New([]types.TransformConfig{{Name: "prompt", Type: TypeSystemPrompt, Prompt: "x"}})
+	if filter := pipeline.ResponseFilter("gpt-4o", ""); filter != nil {
+		t.Error("ResponseFilter() should be nil without response transformers")
+	}
+}
+
+func TestNew_InvalidConfig(t *testing.T) {
+	if _, err := New([]types.TransformConfig{{Name: "bad", Type: TypeRedact, Patterns: []string{"("}}}); err == nil {
+		t.Error("expected an error for an invalid pattern")
+	}
+	if _, err := New([]types.TransformConfig{{Name: "bad", Type: "lowercase"}}); err == nil {
+		t.Error("expected an error for an unknown type")
+	}
+	if pipeline, err := New(nil); pipeline != nil || err != nil {
+		t.Errorf("New(nil) = %v, %v", pipeline, err)
+	}
+}
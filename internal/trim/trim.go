@@ -0,0 +1,179 @@
+package trim
+
+import (
+	"regexp"
+	"strconv"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/internal/tokens"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// 删减的默认参数
+const (
+	defaultKeepRecent  = 2
+	defaultTargetRatio = 0.7
+)
+
+// limitMargin 按上游报告的上限删减时额外留出的比例，抵消估算token数的误差
+const limitMargin = 0.9
+
+// contextErrorMarkers 各提供商上下文窗口超限错误中的特征文本（小写）
+var contextErrorMarkers = []string{
+	"context_length_exceeded",              // OpenAI 错误代码
+	"maximum context length",               // OpenAI
+	"context window",                       // 多个 OpenAI 兼容后端
+	"prompt is too long",                   // Anthropic
+	"input is too long",                    // Bedrock
+	"exceeds the maximum number of tokens", // Gemini
+}
+
+// limitPatterns 从错误信息中取出上限和实际token数，limit/actual 为对应的分组序号
+var limitPatterns = []struct {
+	pattern       *regexp.Regexp
+	limit, actual int
+}{
+	{regexp.MustCompile(`maximum context length is (\d+) tokens.*?(?:resulted in|requested) (\d+) tokens`), 1, 2},
+	{regexp.MustCompile(`prompt is too long: (\d+) tokens > (\d+) maximum`), 2, 1},
+	{regexp.MustCompile(`input token count \((\d+)\) exceeds the maximum number of tokens allowed \((\d+)\)`), 2, 1},
+}
+
+// IsContextWindowError 判断上游是否因为请求超出模型的上下文窗口而拒绝（400或413，且错误信息符合已知格式）
+func IsContextWindowError(statusCode int, body string) bool {
+	if statusCode != 400 && statusCode != 413 {
+		return false
+	}
+	body = strings.ToLower(body)
+	for _, marker := range contextErrorMarkers {
+		if strings.Contains(body, marker) {
+			return true
+		}
+	}
+	return false
+}
+
+// parseLimit 从错误信息中取出上下文窗口上限和请求的token数，取不到时返回0
+func parseLimit(body string) (limit, actual int) {
+	body = strings.ToLower(body)
+	for _, p := range limitPatterns {
+		match := p.pattern.FindStringSubmatch(body)
+		if match == nil {
+			continue
+		}
+		limit, _ = strconv.Atoi(match[p.limit])
+		actual, _ = strconv.Atoi(match[p.actual])
+		if limit > 0 && actual > limit {
+			return limit, actual
+		}
+	}
+	return 0, 0
+}
+
+// Trimmer 上游报告上下文窗口超限时删减请求中最早的对话消息：保留系统消息和最近的消息，
+// 从最早的消息开始删除，直到估算的token数降到目标以下。删除后对话仍以 user 消息开始，
+// 工具调用和对应的结果一起删除
+type Trimmer struct {
+	config *types.ContextTrimConfig
+}
+
+// New 创建删减器，config 为全局配置中的 proxy.context_trim（修改后下次请求生效）
+func New(config *types.ContextTrimConfig) *Trimmer {
+	return &Trimmer{config: config}
+}
+
+// Enabled 是否开启了超限后删减重试
+func (t *Trimmer) Enabled() bool {
+	return t != nil && t.config.Enabled
+}
+
+// Apply 按上游的错误信息删减请求消息，返回删除的消息数；未开启、无法删减到目标以下时不修改请求并返回0
+func (t *Trimmer) Apply(request *types.UnifiedRequest, provider types.Provider, errorBody string) int {
+	if !t.Enabled() {
+		return 0
+	}
+
+	keepRecent := t.config.KeepRecentMessages
+	if keepRecent <= 0 {
+		keepRecent = defaultKeepRecent
+	}
+	counter := tokens.ForProvider(provider)
+	current := counter.CountRequest(request)
+
+	// 目标token数：上游报告了上限时按比例缩减，否则缩减到配置的比例
+	var target int
+	if limit, actual := parseLimit(errorBody); limit > 0 {
+		target = int(float64(current) * float64(limit) / float64(actual) * limitMargin)
+	} else {
+		ratio := t.config.TargetRatio
+		if ratio <= 0 {
+			ratio = defaultTargetRatio
+		}
+		target = int(float64(current) * ratio)
+	}
+
+	messages := request.Messages
+	for {
+		next, ok := dropOldest(messages, keepRecent)
+		if !ok {
+			// 已经删到只剩必须保留的消息，仍然超出目标时不重试
+			return 0
+		}
+		messages = next
+
+		trimmed := *request
+		trimmed.Messages = messages
+		if counter.CountRequest(&trimmed) <= target {
+			break
+		}
+	}
+
+	removed := len(request.Messages) - len(messages)
+	request.Messages = messages
+	return removed
+}
+
+// dropOldest 删除最早的一条非系统消息，以及随后直到下一条 user 消息之前的消息（如对应的助手回复和工具结果），
+// 剩余的非系统消息少于 keepRecent 时返回false
+func dropOldest(messages []types.Message, keepRecent int) ([]types.Message, bool) {
+	first := -1
+	conversation := 0
+	for i, msg := range messages {
+		if msg.Role == "system" {
+			continue
+		}
+		if first < 0 {
+			first = i
+		}
+		conversation++
+	}
+	if first < 0 {
+		return nil, false
+	}
+
+	end := first + 1
+	for end < len(messages) && messages[end].Role != "system" && (messages[end].Role != "user" || isToolResult(messages[end])) {
+		end++
+	}
+	if conversation-(end-first) < keepRecent || end >= len(messages) {
+		return nil, false
+	}
+
+	result := make([]types.Message, 0, len(messages)-(end-first))
+	result = append(result, messages[:first]...)
+	return append(result, messages[end:]...), true
+}
+
+// isToolResult 是否为 Anthropic 格式的工具结果消息（role 为 user，内容为 tool_result 块），
+// 对应的 tool_use 被删除后它不能作为对话的第一条消息
+func isToolResult(msg types.Message) bool {
+	blocks, ok := msg.Content.([]interface{})
+	if !ok {
+		return false
+	}
+	for _, block := range blocks {
+		if b, ok := block.(map[string]interface{}); ok && b["type"] == "tool_result" {
+			return true
+		}
+	}
+	return false
+}
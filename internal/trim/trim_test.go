@@ -0,0 +1,103 @@
+package trim
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestIsContextWindowError(t *testing.T) {
+	tests := []struct {
+		name   string
+		status int
+		body   string
+		want   bool
+	}{
+		{"openai", 400, `{"error":{"message":"This model's maximum context length is 8192 tokens. However, your messages resulted in 9000 tokens.","code":"context_length_exceeded"}}`, true},
+		{"anthropic", 400, `{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}`, true},
+		{"gemini", 400, `{"error":{"message":"The input token count (1200000) exceeds the maximum number of tokens allowed (1048576)."}}`, true},
+		{"payload too large", 413, `{"error":{"message":"Input is too long for requested model."}}`, true},
+		{"other bad request", 400, `{"error":{"message":"invalid temperature"}}`, false},
+		{"rate limited", 429, `{"error":{"message":"maximum context length"}}`, false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := IsContextWindowError(tt.status, tt.body); got != tt.want {
+				t.Errorf("IsContextWindowError() = %v, want %v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseLimit(t *testing.T) {
+	if limit, actual := parseLimit("This model's maximum context length is 8192 tokens. However, your messages resulted in 9000 tokens."); limit != 8192 || actual != 9000 {
+		t.Errorf("OpenAI limit = %d/%d", limit, actual)
+	}
+	if limit, actual := parseLimit("prompt is too long: 210000 tokens > 200000 maximum"); limit != 200000 || actual != 210000 {
+		t.Errorf("Anthropic limit = %d/%d", limit, actual)
+	}
+	if limit, _ := parseLimit("context window exceeded"); limit != 0 {
+		t.Errorf("limit = %d, want 0 without numbers", limit)
+	}
+}
+
+func conversation(turns int) []types.Message {
+	messages := []types.Message{{Role: "system", Content: "Be brief."}}
+	for i := 0; i < turns; i++ {
+		messages = append(messages,
+			types.Message{Role: "user", Content: strings.Repeat("question ", 50)},
+			types.Message{Role: "assistant", Content: strings.Repeat("answer ", 50)},
+		)
+	}
+	return append(messages, types.Message{Role: "user", Content: "last question"})
+}
+
+func TestTrimmer_Apply(t *testing.T) {
+	config := &types.ContextTrimConfig{}
+	trimmer := New(config)
+	request := &types.UnifiedRequest{Model: "gpt-4o", Messages: conversation(4)}
+
+	// 未开启时不修改请求
+	if removed := trimmer.Apply(request, types.ProviderOpenAI, ""); removed != 0 || len(request.Messages) != 10 {
+		t.Fatalf("disabled Apply() = %d, messages = %d", removed, len(request.Messages))
+	}
+
+	config.Enabled = true
+	removed := trimmer.Apply(request, types.ProviderOpenAI, "context window exceeded")
+	if removed == 0 || removed%2 != 0 {
+		t.Fatalf("Apply() removed %d messages, want whole turns", removed)
+	}
+	if request.Messages[0].Role != "system" || request.Messages[1].Role != "user" {
+		t.Errorf("trimmed conversation starts with %s, %s", request.Messages[0].Role, request.Messages[1].Role)
+	}
+	if last := request.Messages[len(request.Messages)-1]; last.Content != "last question" {
+		t.Errorf("last message = %v, want it kept", last.Content)
+	}
+}
+
+func TestTrimmer_ApplyKeepsRecent(t *testing.T) {
+	trimmer := New(&types.ContextTrimConfig{Enabled: true, KeepRecentMessages: 3})
+	request := &types.UnifiedRequest{Model: "gpt-4o", Messages: conversation(1)}
+
+	// 只有3条非系统消息，删除后不足 keep_recent_messages，不重试
+	if removed := trimmer.Apply(request, types.ProviderOpenAI, "context window exceeded"); removed != 0 || len(request.Messages) != 4 {
+		t.Errorf("Apply() = %d, messages = %d", removed, len(request.Messages))
+	}
+}
+
+func TestDropOldest_ToolResults(t *testing.T) {
+	messages := []types.Message{
+		{Role: "user", Content: "look it up"},
+		{Role: "assistant", Content: []interface{}{map[string]interface{}{"type": "tool_use", "id": "t1"}}},
+		{Role: "user", Content: []interface{}{map[string]interface{}{"type": "tool_result", "tool_use_id": "t1"}}},
+		{Role: "assistant", Content: "found it"},
+		{Role: "user", Content: "thanks"},
+	}
+
+	// 工具结果和对应的调用一起删除
+	trimmed, ok := dropOldest(messages, 1)
+	if !ok || len(trimmed) != 1 || trimmed[0].Content != "thanks" {
+		t.Errorf("dropOldest() = %v, %v", trimmed, ok)
+	}
+}
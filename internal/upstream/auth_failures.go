@@ -0,0 +1,117 @@
+package upstream
+
+import (
+	"errors"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// defaultAuthFailureThreshold 未配置时连续多少次401/403后自动停用账号
+const defaultAuthFailureThreshold = 5
+
+// maxDisabledReasonBytes 停用原因中保留的上游错误长度
+const maxDisabledReasonBytes = 256
+
+// CredentialError 上游以401/403拒绝了账号凭证的错误
+type CredentialError interface {
+	CredentialRejected() bool
+}
+
+// AutoDisableHook 账号因凭证连续被拒绝而被自动停用后调用（用于告警和审计）
+type AutoDisableHook func(account *types.UpstreamAccount, failures int, now time.Time)
+
+// authFailures 统计每个账号连续被上游以401/403拒绝的次数，成功请求时清零
+type authFailures struct {
+	threshold int // 小于等于0时不自动停用
+	counts    map[string]int
+	hook      AutoDisableHook
+	mutex     sync.Mutex
+}
+
+// newAuthFailures 创建使用默认阈值的统计
+func newAuthFailures() *authFailures {
+	return &authFailures{
+		threshold: defaultAuthFailureThreshold,
+		counts:    make(map[string]int),
+	}
+}
+
+// ConfigureAutoDisable 设置自动停用的阈值（0使用默认值，负数表示不自动停用）和停用后的回调
+func (m *UpstreamManager) ConfigureAutoDisable(threshold int, hook AutoDisableHook) {
+	m.authFailures.mutex.Lock()
+	defer m.authFailures.mutex.Unlock()
+
+	switch {
+	case threshold == 0:
+		m.authFailures.threshold = defaultAuthFailureThreshold
+	case threshold < 0:
+		m.authFailures.threshold = 0
+	default:
+		m.authFailures.threshold = threshold
+	}
+	m.authFailures.hook = hook
+}
+
+// RecordAuthFailure 记录代理请求的失败：凭证被拒绝时累加连续次数，达到阈值时停用账号（持久化到配置文件，
+// 立即不再参与路由）。其他错误（限流、超时等）不影响计数
+func (m *UpstreamManager) RecordAuthFailure(upstreamID string, err error, now time.Time) {
+	var credErr CredentialError
+	if !errors.As(err, &credErr) || !credErr.CredentialRejected() {
+		return
+	}
+
+	guard := m.authFailures
+	guard.mutex.Lock()
+	if guard.threshold <= 0 {
+		guard.mutex.Unlock()
+		return
+	}
+	guard.counts[upstreamID]++
+	failures := guard.counts[upstreamID]
+	if failures < guard.threshold {
+		guard.mutex.Unlock()
+		return
+	}
+	delete(guard.counts, upstreamID)
+	hook := guard.hook
+	guard.mutex.Unlock()
+
+	reason := fmt.Sprintf("auto-disabled after %d consecutive credential rejections: %s", failures, utils.Excerpt([]byte(err.Error()), maxDisabledReasonBytes))
+	disabled := false
+	updateErr := m.configMgr.UpdateUpstreamAccount(upstreamID, func(account *types.UpstreamAccount) error {
+		if account.Status != "active" {
+			return nil // 已被其他请求或管理员停用
+		}
+		account.Status = "disabled"
+		account.DisabledReason = reason
+		account.UpdatedAt = now
+		disabled = true
+		return nil
+	})
+	if updateErr != nil {
+		logger.Error("自动停用上游账号 %s 失败: %v", upstreamID, updateErr)
+		return
+	}
+	if !disabled {
+		return
+	}
+
+	logger.Warn("上游账号 %s 连续 %d 次凭证被拒绝，已自动停用", upstreamID, failures)
+	if hook != nil {
+		if account, err := m.configMgr.GetUpstreamAccount(upstreamID); err == nil {
+			hook(account, failures, now)
+		}
+	}
+}
+
+// RecordAuthSuccess 记录成功请求，清零账号的连续凭证拒绝次数
+func (m *UpstreamManager) RecordAuthSuccess(upstreamID string) {
+	m.authFailures.mutex.Lock()
+	defer m.authFailures.mutex.Unlock()
+	delete(m.authFailures.counts, upstreamID)
+}
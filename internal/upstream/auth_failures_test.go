@@ -0,0 +1,79 @@
+package upstream
+
+import (
+	"errors"
+	"fmt"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// statusError 模拟代理层带状态码的上游错误
+type statusError int
+
+func (e statusError) Error() string {
+	return fmt.Sprintf("upstream API error: status=%d", int(e))
+}
+
+func (e statusError) CredentialRejected() bool {
+	return e == 401 || e == 403
+}
+
+func TestUpstreamManager_AutoDisable(t *testing.T) {
+	configMgr := NewMockUpstreamConfigManager()
+	_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{ID: "revoked", Provider: types.ProviderAnthropic, Status: "active"})
+	mgr := NewUpstreamManager(configMgr)
+
+	var disabled []string
+	mgr.ConfigureAutoDisable(3, func(account *types.UpstreamAccount, failures int, now time.Time) {
+		disabled = append(disabled, fmt.Sprintf("%s:%d", account.ID, failures))
+	})
+
+	now := time.Now()
+	// 成功请求清零，其他错误不计数
+	mgr.RecordAuthFailure("revoked", statusError(401), now)
+	mgr.RecordAuthFailure("revoked", statusError(403), now)
+	mgr.RecordAuthSuccess("revoked")
+	mgr.RecordAuthFailure("revoked", statusError(401), now)
+	mgr.RecordAuthFailure("revoked", statusError(429), now)
+	mgr.RecordAuthFailure("revoked", errors.New("timeout"), now)
+	mgr.RecordAuthFailure("revoked", fmt.Errorf("wrapped: %w", statusError(401)), now)
+	if len(disabled) != 0 || len(configMgr.ListActiveUpstreamAccounts(types.ProviderAnthropic)) != 1 {
+		t.Fatalf("account disabled before reaching the threshold: %v", disabled)
+	}
+
+	mgr.RecordAuthFailure("revoked", statusError(403), now)
+	account, _ := configMgr.GetUpstreamAccount("revoked")
+	if account.Status != "disabled" || !strings.Contains(account.DisabledReason, "3 consecutive") {
+		t.Errorf("account = %s (%q), want disabled with reason", account.Status, account.DisabledReason)
+	}
+	if len(disabled) != 1 || disabled[0] != "revoked:3" {
+		t.Errorf("hook calls = %v, want [revoked:3]", disabled)
+	}
+	if len(configMgr.ListActiveUpstreamAccounts(types.ProviderAnthropic)) != 0 {
+		t.Error("disabled account is still routable")
+	}
+
+	// 重新启用后清除原因并重新计数
+	if err := mgr.UpdateAccountStatus("revoked", "active"); err != nil {
+		t.Fatalf("UpdateAccountStatus() error = %v", err)
+	}
+	if account.DisabledReason != "" {
+		t.Errorf("DisabledReason = %q after re-enabling", account.DisabledReason)
+	}
+	mgr.RecordAuthFailure("revoked", statusError(401), now)
+	if account.Status != "active" {
+		t.Error("re-enabled account was disabled by a single failure")
+	}
+
+	// 负数阈值关闭自动停用
+	mgr.ConfigureAutoDisable(-1, nil)
+	for i := 0; i < 10; i++ {
+		mgr.RecordAuthFailure("revoked", statusError(401), now)
+	}
+	if account.Status != "active" {
+		t.Error("account disabled with auto-disable turned off")
+	}
+}
@@ -0,0 +1,218 @@
+package upstream
+
+import (
+	"bufio"
+	"encoding/binary"
+	"encoding/json"
+	"fmt"
+	"hash/crc32"
+	"io"
+	"net/http"
+	"net/url"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/sigv4"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+const (
+	bedrockAnthropicVersion = "bedrock-2023-05-31"
+	bedrockSigningService   = "bedrock"
+	maxEventStreamMessage   = 16 * 1024 * 1024 // 单条 event stream 消息的上限，超过视为数据损坏
+)
+
+// bedrockBodyFields Bedrock 上 Anthropic 模型接受的请求体字段，其余字段（model、stream、metadata等）会被拒绝
+var bedrockBodyFields = map[string]bool{
+	"messages": true, "system": true, "max_tokens": true, "temperature": true, "top_p": true, "top_k": true,
+	"stop_sequences": true, "tools": true, "tool_choice": true, "thinking": true, "anthropic_beta": true,
+}
+
+// bedrockRequestPath 把 Anthropic 路径改写为 Bedrock 的 InvokeModel 路径，模型ID放在路径中：
+// /model/{模型ID}/invoke，流式请求为 /model/{模型ID}/invoke-with-response-stream
+func bedrockRequestPath(account *types.UpstreamAccount, _ string, request *types.UnifiedRequest) string {
+	path := "/model/" + url.PathEscape(account.Deployment(request.Model)) + "/invoke"
+	if request.Stream != nil && *request.Stream {
+		path += "-with-response-stream"
+	}
+	return path
+}
+
+// bedrockRequestBody 把 Anthropic 请求体改为 Bedrock 格式：模型和流式由路径决定，
+// 删除 Bedrock 不接受的字段并设置 anthropic_version
+func bedrockRequestBody(body []byte) ([]byte, error) {
+	var fields map[string]json.RawMessage
+	if err := json.Unmarshal(body, &fields); err != nil {
+		return nil, fmt.Errorf("解析Bedrock请求体失败: %w", err)
+	}
+	for name := range fields {
+		if !bedrockBodyFields[name] {
+			delete(fields, name)
+		}
+	}
+	fields["anthropic_version"] = json.RawMessage(`"` + bedrockAnthropicVersion + `"`)
+	return json.Marshal(fields)
+}
+
+// signBedrockRequest 使用账号的AWS凭证对请求做 SigV4 签名，需在设置完其他请求头之后调用
+func signBedrockRequest(account *types.UpstreamAccount, req *http.Request, body []byte, now time.Time) error {
+	credentials := account.AWS
+	if credentials == nil || credentials.AccessKeyID == "" || credentials.SecretAccessKey == "" || credentials.Region == "" {
+		return fmt.Errorf("bedrock 账号 %s 缺少AWS凭证或区域", account.ID)
+	}
+	signer := &sigv4.Signer{
+		AccessKeyID:     credentials.AccessKeyID,
+		SecretAccessKey: credentials.SecretAccessKey,
+		SessionToken:    credentials.SessionToken,
+		Region:          credentials.Region,
+		Service:         bedrockSigningService,
+	}
+	signer.SignRequest(req, utils.SHA256HexBytes(body), now)
+	return nil
+}
+
+// eventStreamReader 把 AWS event stream（application/vnd.amazon.eventstream）转换为 Anthropic SSE：
+// chunk 事件的 bytes 字段是 Base64 编码的 Anthropic 流式事件，异常消息转换为 error 事件
+type eventStreamReader struct {
+	source  io.ReadCloser
+	reader  *bufio.Reader
+	pending []byte
+	err     error
+}
+
+// newEventStreamReader 包装 Bedrock 的流式响应体
+func newEventStreamReader(body io.ReadCloser) io.ReadCloser {
+	return &eventStreamReader{source: body, reader: bufio.NewReader(body)}
+}
+
+// Read 读取转换后的SSE数据
+func (r *eventStreamReader) Read(p []byte) (int, error) {
+	for len(r.pending) == 0 {
+		if r.err != nil {
+			return 0, r.err
+		}
+		r.pending, r.err = r.next()
+	}
+	n := copy(p, r.pending)
+	r.pending = r.pending[n:]
+	return n, nil
+}
+
+// Close 关闭上游响应体
+func (r *eventStreamReader) Close() error {
+	return r.source.Close()
+}
+
+// next 读取并校验一条消息，返回对应的SSE事件；不需要转发的消息返回nil
+func (r *eventStreamReader) next() ([]byte, error) {
+	// 前导：总长度、头部长度（大端uint32）和前导的CRC32
+	prelude := make([]byte, 12)
+	if _, err := io.ReadFull(r.reader, prelude); err != nil {
+		if err == io.ErrUnexpectedEOF {
+			return nil, fmt.Errorf("event stream 消息不完整")
+		}
+		return nil, err
+	}
+	totalLength := binary.BigEndian.Uint32(prelude[0:4])
+	headersLength := binary.BigEndian.Uint32(prelude[4:8])
+	if crc32.ChecksumIEEE(prelude[:8]) != binary.BigEndian.Uint32(prelude[8:12]) {
+		return nil, fmt.Errorf("event stream 前导校验失败")
+	}
+	if totalLength < 16 || headersLength > totalLength-16 || totalLength > maxEventStreamMessage {
+		return nil, fmt.Errorf("无效的 event stream 消息长度: %d", totalLength)
+	}
+
+	message := make([]byte, totalLength)
+	copy(message, prelude)
+	if _, err := io.ReadFull(r.reader, message[12:]); err != nil {
+		return nil, fmt.Errorf("event stream 消息不完整: %w", err)
+	}
+	if crc32.ChecksumIEEE(message[:totalLength-4]) != binary.BigEndian.Uint32(message[totalLength-4:]) {
+		return nil, fmt.Errorf("event stream 消息校验失败")
+	}
+
+	headers, err := parseEventHeaders(message[12 : 12+headersLength])
+	if err != nil {
+		return nil, err
+	}
+	return eventToSSE(headers, message[12+headersLength:totalLength-4])
+}
+
+// parseEventHeaders 解析消息头部，只保留字符串类型的值
+func parseEventHeaders(data []byte) (map[string]string, error) {
+	// 各类型值的固定长度，-1 表示带2字节长度前缀
+	sizes := map[byte]int{0: 0, 1: 0, 2: 1, 3: 2, 4: 4, 5: 8, 6: -1, 7: -1, 8: 8, 9: 16}
+
+	headers := make(map[string]string)
+	for len(data) > 0 {
+		nameLength := int(data[0])
+		if len(data) < 1+nameLength+1 {
+			return nil, fmt.Errorf("无效的 event stream 头部")
+		}
+		name := string(data[1 : 1+nameLength])
+		valueType := data[1+nameLength]
+		data = data[2+nameLength:]
+
+		size, ok := sizes[valueType]
+		if !ok {
+			return nil, fmt.Errorf("未知的 event stream 头部类型: %d", valueType)
+		}
+		if size < 0 {
+			if len(data) < 2 {
+				return nil, fmt.Errorf("无效的 event stream 头部")
+			}
+			size = int(binary.BigEndian.Uint16(data[:2]))
+			data = data[2:]
+		}
+		if len(data) < size {
+			return nil, fmt.Errorf("无效的 event stream 头部")
+		}
+		if valueType == 7 {
+			headers[name] = string(data[:size])
+		}
+		data = data[size:]
+	}
+	return headers, nil
+}
+
+// eventToSSE 把一条消息转换为SSE事件：chunk 事件解码为 Anthropic 事件，异常转换为 error 事件
+func eventToSSE(headers map[string]string, payload []byte) ([]byte, error) {
+	switch headers[":message-type"] {
+	case "event":
+		if headers[":event-type"] != "chunk" {
+			return nil, nil
+		}
+		var chunk struct {
+			Bytes []byte `json:"bytes"` // Base64 编码的 Anthropic 事件
+		}
+		if err := json.Unmarshal(payload, &chunk); err != nil {
+			return nil, fmt.Errorf("解析Bedrock流式事件失败: %w", err)
+		}
+		var event struct {
+			Type string `json:"type"`
+		}
+		if err := json.Unmarshal(chunk.Bytes, &event); err != nil {
+			return nil, fmt.Errorf("解析Bedrock流式事件失败: %w", err)
+		}
+		return []byte("event: " + event.Type + "\ndata: " + string(chunk.Bytes) + "\n\n"), nil
+	case "exception", "error":
+		errorType := headers[":exception-type"]
+		if errorType == "" {
+			errorType = headers[":error-code"]
+		}
+		message := headers[":error-message"]
+		var body struct {
+			Message string `json:"message"`
+		}
+		if json.Unmarshal(payload, &body) == nil && body.Message != "" {
+			message = body.Message
+		}
+		data, _ := json.Marshal(map[string]interface{}{
+			"type":  "error",
+			"error": map[string]string{"type": errorType, "message": message},
+		})
+		return []byte("event: error\ndata: " + string(data) + "\n\n"), nil
+	default:
+		return nil, nil
+	}
+}
@@ -0,0 +1,124 @@
+package upstream
+
+import (
+	"bytes"
+	"encoding/binary"
+	"encoding/json"
+	"hash/crc32"
+	"io"
+	"net/http"
+	"strings"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// eventFrame 按 AWS event stream 格式编码一条消息（头部值均为字符串）
+func eventFrame(headers map[string]string, payload []byte) []byte {
+	var encoded bytes.Buffer
+	for name, value := range headers {
+		encoded.WriteByte(byte(len(name)))
+		encoded.WriteString(name)
+		encoded.WriteByte(7)
+		_ = binary.Write(&encoded, binary.BigEndian, uint16(len(value)))
+		encoded.WriteString(value)
+	}
+
+	total := 16 + encoded.Len() + len(payload)
+	message := make([]byte, 12, total)
+	binary.BigEndian.PutUint32(message[0:4], uint32(total))
+	binary.BigEndian.PutUint32(message[4:8], uint32(encoded.Len()))
+	binary.BigEndian.PutUint32(message[8:12], crc32.ChecksumIEEE(message[:8]))
+	message = append(message, encoded.Bytes()...)
+	message = append(message, payload...)
+	return binary.BigEndian.AppendUint32(message, crc32.ChecksumIEEE(message))
+}
+
+// chunkFrame 把 Anthropic 流式事件包装为 Bedrock 的 chunk 消息
+func chunkFrame(event string) []byte {
+	payload, _ := json.Marshal(map[string][]byte{"bytes": []byte(event)})
+	return eventFrame(map[string]string{":message-type": "event", ":event-type": "chunk", ":content-type": "application/json"}, payload)
+}
+
+func TestUpstreamManager_BedrockRequest(t *testing.T) {
+	mgr := NewUpstreamManager(NewMockUpstreamConfigManager())
+	account := &types.UpstreamAccount{
+		ID:          "bedrock",
+		Provider:    types.ProviderBedrock,
+		Type:        types.UpstreamTypeAPIKey,
+		AWS:         &types.AWSCredentials{AccessKeyID: "AKIDEXAMPLE", SecretAccessKey: "secret", Region: "us-west-2"},
+		Deployments: map[string]string{"claude-3-5-sonnet": "anthropic.claude-3-5-sonnet-20241022-v2:0"},
+	}
+
+	// 模型ID放在路径中，流式请求使用 invoke-with-response-stream
+	stream := true
+	got := mgr.UpstreamURL(account, "/v1/messages", &types.UnifiedRequest{Model: "claude-3-5-sonnet", Stream: &stream})
+	if want := "https://bedrock-runtime.us-west-2.amazonaws.com/model/anthropic.claude-3-5-sonnet-20241022-v2:0/invoke-with-response-stream"; got != want {
+		t.Errorf("UpstreamURL() = %s, want %s", got, want)
+	}
+	if got := mgr.UpstreamURL(account, "/v1/messages", &types.UnifiedRequest{Model: "claude-3-haiku"}); !strings.HasSuffix(got, "/model/claude-3-haiku/invoke") {
+		t.Errorf("UpstreamURL() for an unmapped model = %s", got)
+	}
+
+	body, err := mgr.UpstreamBody(account, []byte(`{"model":"claude-3-5-sonnet","stream":true,"max_tokens":64,"messages":[{"role":"user","content":"hi"}]}`))
+	if err != nil {
+		t.Fatalf("UpstreamBody() error = %v", err)
+	}
+	var fields map[string]interface{}
+	if err := json.Unmarshal(body, &fields); err != nil {
+		t.Fatal(err)
+	}
+	if fields["anthropic_version"] != bedrockAnthropicVersion || fields["model"] != nil || fields["stream"] != nil || fields["max_tokens"] != float64(64) {
+		t.Errorf("UpstreamBody() = %s", body)
+	}
+
+	if headers := mgr.APIKeyHeaders(account); len(headers) != 0 {
+		t.Errorf("bedrock headers = %v, want none before signing", headers)
+	}
+	req, _ := http.NewRequest(http.MethodPost, got, bytes.NewReader(body))
+	req.Header.Set("Content-Type", "application/json")
+	if err := mgr.SignRequest(account, req, body); err != nil {
+		t.Fatalf("SignRequest() error = %v", err)
+	}
+	authorization := req.Header.Get("Authorization")
+	if !strings.HasPrefix(authorization, "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/") ||
+		!strings.Contains(authorization, "/us-west-2/bedrock/aws4_request") ||
+		!strings.Contains(authorization, "SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date,") {
+		t.Errorf("Authorization = %s", authorization)
+	}
+
+	account.AWS = nil
+	if err := mgr.SignRequest(account, req, body); err == nil {
+		t.Error("SignRequest() should fail without AWS credentials")
+	}
+}
+
+func TestEventStreamReader(t *testing.T) {
+	var stream bytes.Buffer
+	stream.Write(chunkFrame(`{"type":"message_start","message":{"id":"msg_1"}}`))
+	stream.Write(eventFrame(map[string]string{":message-type": "event", ":event-type": "metadata"}, []byte(`{}`)))
+	stream.Write(chunkFrame(`{"type":"content_block_delta","delta":{"type":"text_delta","text":"hi"}}`))
+	stream.Write(eventFrame(map[string]string{":message-type": "exception", ":exception-type": "throttlingException"}, []byte(`{"message":"Too many requests"}`)))
+
+	data, err := io.ReadAll(newEventStreamReader(io.NopCloser(&stream)))
+	if err != nil {
+		t.Fatalf("ReadAll() error = %v", err)
+	}
+	want := "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n" +
+		"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"hi\"}}\n\n" +
+		"event: error\ndata: {\"error\":{\"message\":\"Too many requests\",\"type\":\"throttlingException\"},\"type\":\"error\"}\n\n"
+	if string(data) != want {
+		t.Errorf("converted stream = %q, want %q", data, want)
+	}
+
+	// 校验和不匹配说明数据损坏，不能转发
+	corrupted := chunkFrame(`{"type":"ping"}`)
+	corrupted[len(corrupted)-6] ^= 0xff
+	if _, err := io.ReadAll(newEventStreamReader(io.NopCloser(bytes.NewReader(corrupted)))); err == nil {
+		t.Error("a frame with a bad checksum should fail")
+	}
+	truncated := chunkFrame(`{"type":"ping"}`)
+	if _, err := io.ReadAll(newEventStreamReader(io.NopCloser(bytes.NewReader(truncated[:len(truncated)-3])))); err == nil {
+		t.Error("a truncated frame should fail")
+	}
+}
@@ -0,0 +1,407 @@
+package upstream
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// 熔断器状态
+const (
+	BreakerClosed   = "closed"    // 正常放行
+	BreakerOpen     = "open"      // 连续失败过多，暂停路由到该账号
+	BreakerHalfOpen = "half_open" // 打开时间已过，放行试探请求
+)
+
+const (
+	defaultBreakerFailureThreshold = 5                // 未配置时连续失败多少次后打开熔断器
+	defaultBreakerOpenDuration     = 30 * time.Second // 未配置时打开多久后进入半开状态
+	breakerHistorySize             = 200              // 每个账号保留的状态转换记录数
+	breakerHistoryFile             = "breaker_history.json"
+	maxBreakerReasonBytes          = 256
+)
+
+// BreakerTransition 熔断器的一次状态转换
+type BreakerTransition struct {
+	UpstreamID string    `json:"upstream_id"`
+	From       string    `json:"from"`
+	To         string    `json:"to"`
+	Failures   int       `json:"failures"`         // 转换时的连续失败次数
+	Reason     string    `json:"reason,omitempty"` // 触发转换的错误摘要
+	Timestamp  time.Time `json:"timestamp"`
+}
+
+// ClientError 由客户端请求本身导致的错误（如400），不计入熔断器的失败次数
+type ClientError interface {
+	ClientError() bool
+}
+
+// BreakerStatus 账号熔断器的当前状态和生效的参数
+type BreakerStatus struct {
+	State            string     `json:"state"`
+	Failures         int        `json:"consecutive_failures"`
+	OpenedAt         *time.Time `json:"opened_at,omitempty"`
+	Forced           bool       `json:"forced"` // 手动打开，重置前不会自动进入半开状态
+	FailureThreshold int        `json:"failure_threshold"`
+	OpenSeconds      int        `json:"open_seconds"`
+}
+
+// breakerState 单个账号的熔断器状态
+type breakerState struct {
+	state    string
+	failures int
+	openedAt time.Time
+	forced   bool
+}
+
+// CircuitBreakers 每个上游账号一个熔断器：连续失败达到阈值时打开，打开一段时间后半开放行试探请求，
+// 试探成功则关闭、失败则重新打开。阈值和打开时间每次使用时读取（账号覆盖优先于全局参数），修改后立即生效。
+// 状态在内存中（设置共享存储后与其他实例同步打开和关闭），状态转换记录持久化到 breaker_history.json
+type CircuitBreakers struct {
+	states    map[string]*breakerState
+	history   map[string][]BreakerTransition     // 账号ID -> 状态转换，按时间从旧到新
+	path      string                             // 为空时不持久化
+	global    func() *types.CircuitBreakerConfig // 读取全局参数，为nil或返回nil时使用默认值
+	overrides func(upstreamID string) *types.CircuitBreakerConfig
+	store     BreakerStore         // 多实例共享状态的存储，为nil时只在本实例生效
+	applied   map[string]time.Time // 每个账号已应用或写入的共享状态的更新时间
+	mutex     sync.Mutex
+
+	saveMutex sync.Mutex // 保证按顺序写文件，最后写入的总是最新的记录
+}
+
+// newCircuitBreakers 创建熔断器集合（未持久化）
+func newCircuitBreakers() *CircuitBreakers {
+	return &CircuitBreakers{
+		states:  make(map[string]*breakerState),
+		history: make(map[string][]BreakerTransition),
+		applied: make(map[string]time.Time),
+	}
+}
+
+// LoadHistory 从目录加载状态转换记录，之后的转换都会写入该目录；dir 为空时使用 ~/.llm-gateway/health
+func (b *CircuitBreakers) LoadHistory(dir string) error {
+	path := filepath.Join(healthDir(dir), breakerHistoryFile)
+
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	b.path = path
+
+	data, err := os.ReadFile(path)
+	if os.IsNotExist(err) {
+		return nil
+	}
+	if err != nil {
+		return fmt.Errorf("读取熔断记录失败: %w", err)
+	}
+
+	history := make(map[string][]BreakerTransition)
+	if err := json.Unmarshal(data, &history); err != nil {
+		return fmt.Errorf("解析熔断记录失败: %w", err)
+	}
+	b.history = history
+	return nil
+}
+
+// Configure 设置读取全局熔断器参数的方式，每次使用时读取，修改或重新加载配置后立即生效
+func (b *CircuitBreakers) Configure(global func() *types.CircuitBreakerConfig) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	b.global = global
+}
+
+// Settings 返回账号生效的失败阈值和打开时间：账号覆盖优先，其次是全局参数，都未设置时使用默认值
+func (b *CircuitBreakers) Settings(upstreamID string) (int, time.Duration) {
+	b.mutex.Lock()
+	globalSource, overrides := b.global, b.overrides
+	b.mutex.Unlock()
+
+	var global *types.CircuitBreakerConfig
+	if globalSource != nil {
+		global = globalSource()
+	}
+
+	threshold, openSeconds := 0, 0
+	if overrides != nil {
+		if override := overrides(upstreamID); override != nil {
+			threshold, openSeconds = override.FailureThreshold, override.OpenSeconds
+		}
+	}
+	if global != nil {
+		if threshold == 0 {
+			threshold = global.FailureThreshold
+		}
+		if openSeconds == 0 {
+			openSeconds = global.OpenSeconds
+		}
+	}
+
+	if threshold <= 0 {
+		threshold = defaultBreakerFailureThreshold
+	}
+	openDuration := defaultBreakerOpenDuration
+	if openSeconds > 0 {
+		openDuration = time.Duration(openSeconds) * time.Second
+	}
+	return threshold, openDuration
+}
+
+// Allow 判断是否可以路由到账号：打开状态下超过打开时间时转为半开并放行，手动打开的熔断器在重置前一直拒绝
+func (b *CircuitBreakers) Allow(upstreamID string, now time.Time) bool {
+	b.mutex.Lock()
+	state := b.stateLocked(upstreamID)
+	if state.state != BreakerOpen {
+		b.mutex.Unlock()
+		return true
+	}
+	if state.forced {
+		b.mutex.Unlock()
+		return false
+	}
+	openedAt := state.openedAt
+	b.mutex.Unlock()
+
+	if _, openDuration := b.Settings(upstreamID); now.Sub(openedAt) < openDuration {
+		return false
+	}
+
+	b.mutex.Lock()
+	// 读取参数期间可能已被其他请求转为半开，或被重新打开、手动打开
+	if state.state != BreakerOpen {
+		b.mutex.Unlock()
+		return true
+	}
+	if state.forced || !state.openedAt.Equal(openedAt) {
+		b.mutex.Unlock()
+		return false
+	}
+	b.transitionLocked(upstreamID, state, BreakerHalfOpen, "", now)
+	b.mutex.Unlock()
+
+	b.save()
+	return true
+}
+
+// HalfOpenAt 返回打开的熔断器转为半开、放行试探请求的时间；未打开或手动打开时返回 ok=false
+func (b *CircuitBreakers) HalfOpenAt(upstreamID string) (time.Time, bool) {
+	b.mutex.Lock()
+	state, exists := b.states[upstreamID]
+	if !exists || state.state != BreakerOpen || state.forced {
+		b.mutex.Unlock()
+		return time.Time{}, false
+	}
+	openedAt := state.openedAt
+	b.mutex.Unlock()
+
+	_, openDuration := b.Settings(upstreamID)
+	return openedAt.Add(openDuration), true
+}
+
+// RecordSuccess 记录成功请求，重置连续失败次数，半开或打开状态下关闭熔断器（手动打开的除外）
+func (b *CircuitBreakers) RecordSuccess(upstreamID string, now time.Time) {
+	b.mutex.Lock()
+	state := b.stateLocked(upstreamID)
+	state.failures = 0
+	if state.state == BreakerClosed || state.forced {
+		b.mutex.Unlock()
+		return
+	}
+	b.transitionLocked(upstreamID, state, BreakerClosed, "", now)
+	shared := b.sharedLocked(upstreamID, state, now)
+	b.mutex.Unlock()
+
+	b.save()
+	b.publish(upstreamID, shared)
+}
+
+// RecordFailure 记录失败请求：连续失败达到阈值或半开试探失败时打开熔断器。客户端错误不计入
+func (b *CircuitBreakers) RecordFailure(upstreamID string, err error, now time.Time) {
+	var clientErr ClientError
+	if errors.As(err, &clientErr) && clientErr.ClientError() {
+		return
+	}
+
+	threshold, _ := b.Settings(upstreamID)
+
+	b.mutex.Lock()
+	state := b.stateLocked(upstreamID)
+	state.failures++
+	if state.state == BreakerOpen || (state.state == BreakerClosed && state.failures < threshold) {
+		b.mutex.Unlock()
+		return
+	}
+
+	reason := ""
+	if err != nil {
+		reason = utils.TruncateUTF8(err.Error(), maxBreakerReasonBytes)
+	}
+	failures := state.failures
+	state.openedAt = now
+	b.transitionLocked(upstreamID, state, BreakerOpen, reason, now)
+	shared := b.sharedLocked(upstreamID, state, now)
+	b.mutex.Unlock()
+
+	logger.Warn("上游账号 %s 熔断器打开（连续失败%d次）: %s", upstreamID, failures, reason)
+	b.save()
+	b.publish(upstreamID, shared)
+}
+
+// State 返回账号熔断器的当前状态和连续失败次数
+func (b *CircuitBreakers) State(upstreamID string) (string, int) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	state, exists := b.states[upstreamID]
+	if !exists {
+		return BreakerClosed, 0
+	}
+	return state.state, state.failures
+}
+
+// Status 返回账号熔断器的当前状态和生效的参数
+func (b *CircuitBreakers) Status(upstreamID string) BreakerStatus {
+	threshold, openDuration := b.Settings(upstreamID)
+	status := BreakerStatus{
+		State:            BreakerClosed,
+		FailureThreshold: threshold,
+		OpenSeconds:      int(openDuration / time.Second),
+	}
+
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	if state, exists := b.states[upstreamID]; exists {
+		status.State = state.state
+		status.Failures = state.failures
+		status.Forced = state.forced
+		if state.state != BreakerClosed {
+			openedAt := state.openedAt
+			status.OpenedAt = &openedAt
+		}
+	}
+	return status
+}
+
+// Reset 手动关闭熔断器并清零连续失败次数（共享状态时同时关闭其他实例的熔断器）
+func (b *CircuitBreakers) Reset(upstreamID, reason string, now time.Time) {
+	b.mutex.Lock()
+	state := b.stateLocked(upstreamID)
+	state.failures = 0
+	state.forced = false
+	if state.state == BreakerClosed {
+		shared := b.sharedLocked(upstreamID, state, now)
+		b.mutex.Unlock()
+		b.publish(upstreamID, shared)
+		return
+	}
+	b.transitionLocked(upstreamID, state, BreakerClosed, reason, now)
+	shared := b.sharedLocked(upstreamID, state, now)
+	b.mutex.Unlock()
+
+	logger.Info("上游账号 %s 熔断器已手动重置: %s", upstreamID, reason)
+	b.save()
+	b.publish(upstreamID, shared)
+}
+
+// Trip 手动打开熔断器，重置前不会自动进入半开状态，用于临时摘除账号
+func (b *CircuitBreakers) Trip(upstreamID, reason string, now time.Time) {
+	b.mutex.Lock()
+	state := b.stateLocked(upstreamID)
+	state.forced = true
+	state.openedAt = now
+	if state.state == BreakerOpen {
+		shared := b.sharedLocked(upstreamID, state, now)
+		b.mutex.Unlock()
+		b.publish(upstreamID, shared)
+		return
+	}
+	b.transitionLocked(upstreamID, state, BreakerOpen, reason, now)
+	shared := b.sharedLocked(upstreamID, state, now)
+	b.mutex.Unlock()
+
+	logger.Warn("上游账号 %s 熔断器已手动打开: %s", upstreamID, reason)
+	b.save()
+	b.publish(upstreamID, shared)
+}
+
+// History 返回账号最近的状态转换，从新到旧，limit<=0 时返回全部
+func (b *CircuitBreakers) History(upstreamID string, limit int) []BreakerTransition {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	history := b.history[upstreamID]
+	if limit <= 0 || limit > len(history) {
+		limit = len(history)
+	}
+	list := make([]BreakerTransition, 0, limit)
+	for i := len(history) - 1; i >= 0 && len(list) < limit; i-- {
+		list = append(list, history[i])
+	}
+	return list
+}
+
+// stateLocked 返回账号的熔断器状态，不存在时创建（调用方持有锁）
+func (b *CircuitBreakers) stateLocked(upstreamID string) *breakerState {
+	state, exists := b.states[upstreamID]
+	if !exists {
+		state = &breakerState{state: BreakerClosed}
+		b.states[upstreamID] = state
+	}
+	return state
+}
+
+// transitionLocked 转换状态并追加记录（调用方持有锁）
+func (b *CircuitBreakers) transitionLocked(upstreamID string, state *breakerState, to, reason string, now time.Time) {
+	transition := BreakerTransition{
+		UpstreamID: upstreamID,
+		From:       state.state,
+		To:         to,
+		Failures:   state.failures,
+		Reason:     reason,
+		Timestamp:  now,
+	}
+	state.state = to
+
+	history := append(b.history[upstreamID], transition)
+	if excess := len(history) - breakerHistorySize; excess > 0 {
+		history = append([]BreakerTransition(nil), history[excess:]...)
+	}
+	b.history[upstreamID] = history
+}
+
+// save 写入熔断记录文件（未加载过记录时不持久化），失败只记录日志
+func (b *CircuitBreakers) save() {
+	b.saveMutex.Lock()
+	defer b.saveMutex.Unlock()
+
+	b.mutex.Lock()
+	path := b.path
+	var data []byte
+	var err error
+	if path != "" {
+		data, err = json.Marshal(b.history)
+	}
+	b.mutex.Unlock()
+	if path == "" {
+		return
+	}
+	if err != nil {
+		logger.Warn("序列化熔断记录失败: %v", err)
+		return
+	}
+
+	if err := os.MkdirAll(filepath.Dir(path), 0700); err != nil {
+		logger.Warn("创建熔断记录目录失败: %v", err)
+		return
+	}
+	if err := os.WriteFile(path, data, 0600); err != nil {
+		logger.Warn("写入熔断记录失败: %v", err)
+	}
+}
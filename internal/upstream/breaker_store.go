@@ -0,0 +1,228 @@
+package upstream
+
+import (
+	"encoding/json"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/redis"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+)
+
+// defaultBreakerSyncInterval 未配置时从共享存储同步熔断器状态的间隔
+const defaultBreakerSyncInterval = 2 * time.Second
+
+// SharedBreakerState 多个网关实例共享的熔断器状态，只共享打开和关闭（半开是各实例自己的试探）
+type SharedBreakerState struct {
+	State     string    `json:"state"` // BreakerOpen 或 BreakerClosed
+	OpenedAt  time.Time `json:"opened_at,omitempty"`
+	Forced    bool      `json:"forced,omitempty"`
+	UpdatedAt time.Time `json:"updated_at"`
+}
+
+// BreakerStore 共享熔断器状态的存储，每个账号保存最近一次打开或关闭
+type BreakerStore interface {
+	Publish(upstreamID string, state SharedBreakerState) error
+	Load() (map[string]SharedBreakerState, error)
+}
+
+// SetStore 设置共享存储：之后本实例的打开和关闭都会写入存储，Sync 读取其他实例的变化
+func (b *CircuitBreakers) SetStore(store BreakerStore) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	b.store = store
+}
+
+// Sync 读取共享存储，把其他实例更新的打开（包括手动打开）和关闭应用到本实例
+func (b *CircuitBreakers) Sync(now time.Time) error {
+	b.mutex.Lock()
+	store := b.store
+	b.mutex.Unlock()
+	if store == nil {
+		return nil
+	}
+
+	states, err := store.Load()
+	if err != nil {
+		return err
+	}
+
+	changed := false
+	b.mutex.Lock()
+	for upstreamID, shared := range states {
+		if !shared.UpdatedAt.After(b.applied[upstreamID]) {
+			continue
+		}
+		b.applied[upstreamID] = shared.UpdatedAt
+
+		state := b.stateLocked(upstreamID)
+		switch shared.State {
+		case BreakerOpen:
+			wasOpen := state.state == BreakerOpen
+			state.openedAt = shared.OpenedAt
+			state.forced = shared.Forced
+			if !wasOpen {
+				b.transitionLocked(upstreamID, state, BreakerOpen, "opened on another gateway instance", now)
+				changed = true
+			}
+		case BreakerClosed:
+			state.failures = 0
+			state.forced = false
+			if state.state != BreakerClosed {
+				b.transitionLocked(upstreamID, state, BreakerClosed, "closed on another gateway instance", now)
+				changed = true
+			}
+		}
+	}
+	b.mutex.Unlock()
+
+	if changed {
+		b.save()
+	}
+	return nil
+}
+
+// sharedLocked 本实例的打开或关闭对应的共享状态（调用方持有锁）
+func (b *CircuitBreakers) sharedLocked(upstreamID string, state *breakerState, now time.Time) SharedBreakerState {
+	b.applied[upstreamID] = now
+	return SharedBreakerState{State: state.state, OpenedAt: state.openedAt, Forced: state.forced, UpdatedAt: now}
+}
+
+// publish 写入共享存储，未设置存储时不做任何事；失败只记录日志，其他实例在下次变化时同步
+func (b *CircuitBreakers) publish(upstreamID string, shared SharedBreakerState) {
+	b.mutex.Lock()
+	store := b.store
+	b.mutex.Unlock()
+	if store == nil {
+		return
+	}
+
+	if err := store.Publish(upstreamID, shared); err != nil {
+		logger.Warn("上游账号 %s 的熔断器状态未能共享: %v", upstreamID, err)
+	}
+}
+
+// RedisBreakerStore 熔断器状态保存在 Redis 哈希中（账号ID -> JSON）
+type RedisBreakerStore struct {
+	client *redis.Client
+}
+
+// NewRedisBreakerStore 创建 Redis 熔断器状态存储
+func NewRedisBreakerStore(client *redis.Client) *RedisBreakerStore {
+	return &RedisBreakerStore{client: client}
+}
+
+// Publish 写入账号的共享状态
+func (s *RedisBreakerStore) Publish(upstreamID string, state SharedBreakerState) error {
+	data, err := json.Marshal(state)
+	if err != nil {
+		return err
+	}
+	_, err = s.client.Do("HSET", s.client.Key("breakers"), upstreamID, data)
+	return err
+}
+
+// Load 读取所有账号的共享状态，跳过无法解析的记录
+func (s *RedisBreakerStore) Load() (map[string]SharedBreakerState, error) {
+	reply, err := s.client.Do("HGETALL", s.client.Key("breakers"))
+	if err != nil {
+		return nil, err
+	}
+	items, ok := reply.([]interface{})
+	if !ok || len(items)%2 != 0 {
+		return nil, fmt.Errorf("无效的熔断器状态回复: %T", reply)
+	}
+
+	states := make(map[string]SharedBreakerState, len(items)/2)
+	for i := 0; i < len(items); i += 2 {
+		upstreamID, _ := items[i].(string)
+		data, _ := items[i+1].(string)
+		var state SharedBreakerState
+		if upstreamID == "" || json.Unmarshal([]byte(data), &state) != nil {
+			continue
+		}
+		states[upstreamID] = state
+	}
+	return states, nil
+}
+
+// BreakerSync 后台定期从共享存储同步熔断器和提供商过载退避状态
+type BreakerSync struct {
+	breakers  *CircuitBreakers
+	overloads *ProviderBackoff // 为nil时只同步熔断器
+	interval  time.Duration
+	failing   bool // 上次同步失败，恢复前不重复记录日志
+	stopCh    chan struct{}
+	mutex     sync.Mutex
+}
+
+// NewBreakerSync 创建同步任务，interval<=0 时使用默认值2秒
+func NewBreakerSync(breakers *CircuitBreakers, overloads *ProviderBackoff, interval time.Duration) *BreakerSync {
+	if interval <= 0 {
+		interval = defaultBreakerSyncInterval
+	}
+	return &BreakerSync{breakers: breakers, overloads: overloads, interval: interval}
+}
+
+// Start 启动后台同步
+func (s *BreakerSync) Start() {
+	if s == nil {
+		return
+	}
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		return
+	}
+	s.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(s.interval)
+		defer ticker.Stop()
+
+		for {
+			select {
+			case <-ticker.C:
+				s.sync(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(s.stopCh)
+}
+
+// Stop 停止后台同步
+func (s *BreakerSync) Stop() {
+	if s == nil {
+		return
+	}
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		close(s.stopCh)
+		s.stopCh = nil
+	}
+}
+
+// sync 同步一次，失败和恢复时各记录一次日志
+func (s *BreakerSync) sync(now time.Time) {
+	err := s.breakers.Sync(now)
+	if s.overloads != nil {
+		if overloadErr := s.overloads.Sync(); err == nil {
+			err = overloadErr
+		}
+	}
+
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+	switch {
+	case err != nil && !s.failing:
+		logger.Warn("同步共享熔断器状态失败: %v", err)
+	case err == nil && s.failing:
+		logger.Info("共享熔断器状态同步已恢复")
+	}
+	s.failing = err != nil
+}
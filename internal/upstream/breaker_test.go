@@ -0,0 +1,178 @@
+package upstream
+
+import (
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+type clientError struct{}
+
+func (clientError) Error() string     { return "bad request" }
+func (clientError) ClientError() bool { return true }
+
+func TestCircuitBreakers_Transitions(t *testing.T) {
+	dir := t.TempDir()
+	breakers := newCircuitBreakers()
+	if err := breakers.LoadHistory(dir); err != nil {
+		t.Fatalf("LoadHistory() error = %v", err)
+	}
+
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	upstreamErr := errors.New("upstream API error: status=503")
+
+	// 客户端错误不计入，连续失败达到阈值时打开
+	breakers.RecordFailure("up-1", clientError{}, now)
+	for i := 0; i < defaultBreakerFailureThreshold-1; i++ {
+		breakers.RecordFailure("up-1", upstreamErr, now)
+	}
+	if state, failures := breakers.State("up-1"); state != BreakerClosed || failures != defaultBreakerFailureThreshold-1 {
+		t.Fatalf("State() = %s, %d before threshold", state, failures)
+	}
+	breakers.RecordFailure("up-1", upstreamErr, now)
+	if state, _ := breakers.State("up-1"); state != BreakerOpen || breakers.Allow("up-1", now.Add(time.Second)) {
+		t.Fatalf("breaker should be open, state = %s", state)
+	}
+
+	// 打开时间过后半开放行，试探失败重新打开，试探成功关闭
+	if !breakers.Allow("up-1", now.Add(defaultBreakerOpenDuration)) {
+		t.Fatal("breaker should allow a trial request after the open duration")
+	}
+	breakers.RecordFailure("up-1", upstreamErr, now.Add(defaultBreakerOpenDuration))
+	later := now.Add(2 * defaultBreakerOpenDuration)
+	if !breakers.Allow("up-1", later) {
+		t.Fatal("breaker should half-open again")
+	}
+	breakers.RecordSuccess("up-1", later)
+
+	history := breakers.History("up-1", 0)
+	want := []string{BreakerClosed, BreakerHalfOpen, BreakerOpen, BreakerHalfOpen, BreakerOpen}
+	if len(history) != len(want) {
+		t.Fatalf("History() = %+v", history)
+	}
+	for i, to := range want {
+		if history[i].To != to {
+			t.Errorf("history[%d].To = %s, want %s", i, history[i].To, to)
+		}
+	}
+	if opened := history[len(history)-1]; opened.Reason != upstreamErr.Error() || opened.Failures != defaultBreakerFailureThreshold {
+		t.Errorf("open transition = %+v", opened)
+	}
+
+	// 重启后从文件恢复记录，状态从关闭开始
+	restarted := newCircuitBreakers()
+	if err := restarted.LoadHistory(dir); err != nil {
+		t.Fatalf("LoadHistory() error = %v", err)
+	}
+	if got := restarted.History("up-1", 2); len(got) != 2 || got[0].To != BreakerClosed {
+		t.Errorf("History() after restart = %+v", got)
+	}
+	if state, _ := restarted.State("up-1"); state != BreakerClosed {
+		t.Errorf("State() after restart = %s", state)
+	}
+}
+
+func TestCircuitBreakers_SettingsAndManualControl(t *testing.T) {
+	breakers := newCircuitBreakers()
+	global := &types.CircuitBreakerConfig{FailureThreshold: 2}
+	overrides := map[string]*types.CircuitBreakerConfig{"up-strict": {FailureThreshold: 1, OpenSeconds: 5}}
+	breakers.Configure(func() *types.CircuitBreakerConfig { return global })
+	breakers.overrides = func(upstreamID string) *types.CircuitBreakerConfig { return overrides[upstreamID] }
+
+	if threshold, open := breakers.Settings("up-1"); threshold != 2 || open != defaultBreakerOpenDuration {
+		t.Errorf("Settings(up-1) = %d, %v, want global threshold and default open duration", threshold, open)
+	}
+	if threshold, open := breakers.Settings("up-strict"); threshold != 1 || open != 5*time.Second {
+		t.Errorf("Settings(up-strict) = %d, %v, want the account override", threshold, open)
+	}
+
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+	upstreamErr := errors.New("upstream API error: status=503")
+	breakers.RecordFailure("up-strict", upstreamErr, now)
+	if state, _ := breakers.State("up-strict"); state != BreakerOpen {
+		t.Fatalf("State(up-strict) = %s after one failure, want open", state)
+	}
+	if !breakers.Allow("up-strict", now.Add(5*time.Second)) {
+		t.Error("override open duration was not applied")
+	}
+
+	// 修改全局参数立即生效
+	global.FailureThreshold = 3
+	breakers.RecordFailure("up-1", upstreamErr, now)
+	breakers.RecordFailure("up-1", upstreamErr, now)
+	if state, failures := breakers.State("up-1"); state != BreakerClosed || failures != 2 {
+		t.Errorf("State(up-1) = %s, %d, want closed below the updated threshold", state, failures)
+	}
+
+	// 手动打开后不会自动半开，成功请求也不会关闭，重置后恢复
+	breakers.Trip("up-1", "maintenance", now)
+	breakers.RecordSuccess("up-1", now)
+	if breakers.Allow("up-1", now.Add(time.Hour)) {
+		t.Error("manually tripped breaker allowed a request")
+	}
+	if status := breakers.Status("up-1"); status.State != BreakerOpen || !status.Forced || status.FailureThreshold != 3 {
+		t.Errorf("Status() = %+v", status)
+	}
+	breakers.Reset("up-1", "maintenance done", now)
+	if status := breakers.Status("up-1"); status.State != BreakerClosed || status.Forced || status.Failures != 0 || !breakers.Allow("up-1", now) {
+		t.Errorf("Status() after reset = %+v", status)
+	}
+	if history := breakers.History("up-1", 1); len(history) != 1 || history[0].Reason != "maintenance done" {
+		t.Errorf("History() = %+v", history)
+	}
+}
+
+// memoryBreakerStore 多个实例共用的内存存储
+type memoryBreakerStore struct {
+	states map[string]SharedBreakerState
+}
+
+func (s *memoryBreakerStore) Publish(upstreamID string, state SharedBreakerState) error {
+	s.states[upstreamID] = state
+	return nil
+}
+
+func (s *memoryBreakerStore) Load() (map[string]SharedBreakerState, error) {
+	states := make(map[string]SharedBreakerState, len(s.states))
+	for id, state := range s.states {
+		states[id] = state
+	}
+	return states, nil
+}
+
+func TestCircuitBreakers_SharedStore(t *testing.T) {
+	store := &memoryBreakerStore{states: make(map[string]SharedBreakerState)}
+	a, b := newCircuitBreakers(), newCircuitBreakers()
+	a.SetStore(store)
+	b.SetStore(store)
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+
+	// 一个实例打开的熔断器同步到其他实例，打开时间沿用原实例的
+	for i := 0; i < defaultBreakerFailureThreshold; i++ {
+		a.RecordFailure("up-1", errors.New("status=503"), now)
+	}
+	if err := b.Sync(now.Add(time.Second)); err != nil {
+		t.Fatalf("Sync() error = %v", err)
+	}
+	if b.Allow("up-1", now.Add(time.Second)) || !b.Allow("up-1", now.Add(defaultBreakerOpenDuration)) {
+		t.Error("synced breaker should stay open for the original open duration, then half-open")
+	}
+
+	// 手动打开和重置同样共享，已应用的状态不重复应用
+	a.Trip("up-2", "maintenance", now)
+	_ = b.Sync(now)
+	if status := b.Status("up-2"); status.State != BreakerOpen || !status.Forced {
+		t.Errorf("up-2 status on b = %+v, want forced open", status)
+	}
+	b.Reset("up-2", "done", now.Add(time.Minute))
+	_ = a.Sync(now.Add(time.Minute))
+	_ = a.Sync(now.Add(2 * time.Minute))
+	if state, _ := a.State("up-2"); state != BreakerClosed || !a.Allow("up-2", now.Add(time.Minute)) {
+		t.Errorf("up-2 state on a = %s, want closed after reset on b", state)
+	}
+	if history := a.History("up-2", 0); len(history) != 2 || history[0].Reason != "closed on another gateway instance" {
+		t.Errorf("up-2 history on a = %+v", history)
+	}
+}
@@ -0,0 +1,44 @@
+package upstream
+
+import (
+	"net/http"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// StatusOverloaded Anthropic 在整体容量不足时返回的非标准状态码
+const StatusOverloaded = 529
+
+// quotaMarkers 错误体中表示余额或配额耗尽的片段：部分提供商以400/403返回（如 Anthropic 的余额不足、OpenAI 的 insufficient_quota）
+var quotaMarkers = []string{"insufficient_quota", "quota_exceeded", "credit balance", "billing_hard_limit", "resource_exhausted"}
+
+// ClassifyStatus 按上游的状态码和错误体归入错误分类。错误体中的过载和配额标记优先于状态码，
+// 因为部分渠道以其他状态码报告这两类错误
+func ClassifyStatus(statusCode int, body string) types.ErrorClass {
+	lower := strings.ToLower(body)
+	switch {
+	case statusCode == StatusOverloaded || (statusCode >= 500 && strings.Contains(lower, "overloaded_error")):
+		return types.ErrorClassOverloaded
+	case statusCode == http.StatusTooManyRequests || statusCode == http.StatusPaymentRequired || containsAny(lower, quotaMarkers):
+		return types.ErrorClassQuota
+	case statusCode == http.StatusUnauthorized || statusCode == http.StatusForbidden:
+		return types.ErrorClassAuth
+	case statusCode == http.StatusRequestTimeout || statusCode == http.StatusGatewayTimeout:
+		return types.ErrorClassTimeout
+	case statusCode >= 500:
+		return types.ErrorClassServer
+	default:
+		return types.ErrorClassInvalidRequest
+	}
+}
+
+// containsAny 判断文本是否包含任一片段
+func containsAny(text string, markers []string) bool {
+	for _, marker := range markers {
+		if strings.Contains(text, marker) {
+			return true
+		}
+	}
+	return false
+}
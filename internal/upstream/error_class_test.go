@@ -0,0 +1,34 @@
+package upstream
+
+import (
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestClassifyStatus(t *testing.T) {
+	tests := []struct {
+		status int
+		body   string
+		want   types.ErrorClass
+	}{
+		{401, `{"error":{"type":"authentication_error"}}`, types.ErrorClassAuth},
+		{403, `{"error":{"type":"permission_error"}}`, types.ErrorClassAuth},
+		{429, `{"error":{"type":"rate_limit_error"}}`, types.ErrorClassQuota},
+		{429, `{"error":{"code":"insufficient_quota"}}`, types.ErrorClassQuota},
+		{400, `{"error":{"message":"Your credit balance is too low"}}`, types.ErrorClassQuota},
+		{529, `{"error":{"type":"overloaded_error"}}`, types.ErrorClassOverloaded},
+		{503, `{"error":{"type":"overloaded_error"}}`, types.ErrorClassOverloaded},
+		{400, `{"error":{"type":"invalid_request_error"}}`, types.ErrorClassInvalidRequest},
+		{404, `{"error":{"message":"model not found"}}`, types.ErrorClassInvalidRequest},
+		{504, "", types.ErrorClassTimeout},
+		{500, `{"error":{"type":"api_error"}}`, types.ErrorClassServer},
+		{502, "", types.ErrorClassServer},
+	}
+
+	for _, tt := range tests {
+		if got := ClassifyStatus(tt.status, tt.body); got != tt.want {
+			t.Errorf("ClassifyStatus(%d, %q) = %s, want %s", tt.status, tt.body, got, tt.want)
+		}
+	}
+}
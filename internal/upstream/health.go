@@ -0,0 +1,424 @@
+package upstream
+
+import (
+	"context"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
+)
+
+// maxHealthErrorBodyBytes 探测失败时保留的响应体长度
+const maxHealthErrorBodyBytes = 512
+
+// maxHealthModelsBodyBytes 解析模型列表时读取的最大响应体长度
+const maxHealthModelsBodyBytes = 4 << 20
+
+// HealthResult 一次健康探测的结果
+type HealthResult struct {
+	UpstreamID string         `json:"upstream_id"`
+	Name       string         `json:"name"`
+	Provider   types.Provider `json:"provider"`
+	Healthy    bool           `json:"healthy"`
+	Probed     bool           `json:"probed"` // 是否实际请求了上游（提供商未配置探测接口时只检查凭证）
+	StatusCode int            `json:"status_code,omitempty"`
+	LatencyMs  int64          `json:"latency_ms"`
+	Error      string         `json:"error,omitempty"`
+	Canary     string         `json:"canary,omitempty"` // 由合成探针产生时为探针名称
+	Models     []string       `json:"models,omitempty"` // 提供商支持模型发现时，后端返回的模型列表
+	CheckedAt  time.Time      `json:"checked_at"`
+}
+
+// CredentialRejected 上游以401/403拒绝了凭证（区别于网络错误、限流等暂时性失败）
+func (r *HealthResult) CredentialRejected() bool {
+	return r.StatusCode == http.StatusUnauthorized || r.StatusCode == http.StatusForbidden
+}
+
+// defaultHealthCheckParallel 未配置批量探测并发数时的默认值
+const defaultHealthCheckParallel = 8
+
+// HealthService 通过向上游发送轻量请求（模型列表）检查账号的真实可用性，
+// 最近一次结果保存在账号上，每次结果同时写入探测历史
+type HealthService struct {
+	upstreamMgr *UpstreamManager
+	settings    func() *types.HealthCheckConfig // 每次探测时读取并发上限
+	timeout     time.Duration
+	history     *healthHistory
+	clients     map[types.Provider]*http.Client // 每个提供商独立的探测客户端，与代理数据面的连接池分开
+	mutex       sync.Mutex
+}
+
+// NewHealthService 创建健康探测服务，settings 返回当前的健康检查配置（超时和历史目录在创建时确定）；
+// 加载持久化的探测历史
+func NewHealthService(upstreamMgr *UpstreamManager, settings func() *types.HealthCheckConfig) *HealthService {
+	config := settings()
+	timeout := time.Duration(config.TimeoutSeconds) * time.Second
+	if timeout <= 0 {
+		timeout = 10 * time.Second
+	}
+
+	history := newHealthHistory(config.HistoryDir, config.HistorySize)
+	if err := history.load(); err != nil {
+		logger.Warn("加载健康探测历史失败: %v", err)
+	}
+
+	service := &HealthService{
+		upstreamMgr: upstreamMgr,
+		settings:    settings,
+		timeout:     timeout,
+		history:     history,
+		clients:     make(map[types.Provider]*http.Client),
+	}
+	service.restoreState()
+	return service
+}
+
+// restoreState 用探测历史中每个账号最新的结果恢复健康状态（探测结果不写配置文件，重启后从历史恢复）
+func (s *HealthService) restoreState() {
+	for _, account := range s.upstreamMgr.ListAccounts() {
+		latest := s.history.list(account.ID, 1)
+		if len(latest) == 0 {
+			continue
+		}
+		if account.LastHealthCheck != nil && !latest[0].CheckedAt.After(*account.LastHealthCheck) {
+			continue
+		}
+		if err := s.upstreamMgr.RecordHealthCheck(latest[0]); err != nil {
+			logger.Warn("恢复账号 %s 的健康状态失败: %v", account.ID, err)
+		}
+	}
+}
+
+// clientFor 返回提供商的探测客户端（首次使用时创建）。
+// 连接池很小且各阶段超时不超过探测超时，慢探测不会占用代理请求的连接，代理流量也不会拖慢探测
+func (s *HealthService) clientFor(provider types.Provider) *http.Client {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if client, exists := s.clients[provider]; exists {
+		return client
+	}
+
+	client := &http.Client{
+		Timeout: s.timeout,
+		Transport: &http.Transport{
+			Proxy:                 http.ProxyFromEnvironment, // 自动读取HTTP_PROXY/HTTPS_PROXY环境变量
+			DialContext:           (&net.Dialer{Timeout: s.timeout}).DialContext,
+			TLSHandshakeTimeout:   s.timeout,
+			ResponseHeaderTimeout: s.timeout,
+			MaxIdleConnsPerHost:   2,
+			MaxConnsPerHost:       4,
+			IdleConnTimeout:       30 * time.Second,
+		},
+	}
+	s.clients[provider] = client
+	return client
+}
+
+// Check 探测单个账号并保存结果
+func (s *HealthService) Check(upstreamID string) (*HealthResult, error) {
+	result, err := s.check(upstreamID)
+	if result != nil {
+		s.saveHistory()
+	}
+	return result, err
+}
+
+// check 探测单个账号，结果保存到账号并加入探测历史（不写历史文件）
+func (s *HealthService) check(upstreamID string) (*HealthResult, error) {
+	account, err := s.upstreamMgr.GetAccount(upstreamID)
+	if err != nil {
+		return nil, err
+	}
+
+	result := s.probe(account)
+	s.history.add(result)
+	if err := s.upstreamMgr.RecordHealthCheck(result); err != nil {
+		return result, fmt.Errorf("保存健康检查结果失败: %w", err)
+	}
+	return result, nil
+}
+
+// CheckMany 并发探测多个账号，结果顺序与ids一致，不存在的账号会被跳过
+func (s *HealthService) CheckMany(ids []string) []*HealthResult {
+	results := make([]*HealthResult, len(ids))
+	s.CheckEach(context.Background(), ids, func(i int, result *HealthResult) {
+		results[i] = result
+	})
+
+	checked := make([]*HealthResult, 0, len(results))
+	for _, result := range results {
+		if result != nil {
+			checked = append(checked, result)
+		}
+	}
+	return checked
+}
+
+// CheckEach 以不超过 max_parallel 的并发探测多个账号，每完成一个就以其在ids中的下标回调fn（回调串行执行），
+// 不存在的账号会被跳过；ctx 取消后不再开始新的探测。全部完成后写入探测历史文件
+func (s *HealthService) CheckEach(ctx context.Context, ids []string, fn func(i int, result *HealthResult)) {
+	parallel := s.settings().MaxParallel
+	if parallel <= 0 {
+		parallel = defaultHealthCheckParallel
+	}
+	if parallel > len(ids) {
+		parallel = len(ids)
+	}
+
+	next := make(chan int)
+	go func() {
+		defer close(next)
+		for i := range ids {
+			if ctx.Err() != nil {
+				return
+			}
+			select {
+			case next <- i:
+			case <-ctx.Done():
+				return
+			}
+		}
+	}()
+
+	var wg sync.WaitGroup
+	var callbackMutex sync.Mutex
+	for worker := 0; worker < parallel; worker++ {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for i := range next {
+				result, err := s.check(ids[i])
+				if result == nil && err != nil {
+					continue
+				}
+				callbackMutex.Lock()
+				fn(i, result)
+				callbackMutex.Unlock()
+			}
+		}()
+	}
+	wg.Wait()
+
+	s.saveHistory()
+}
+
+// Record 记录外部产生的健康信号（如合成探针结果）：保存到账号并写入探测历史
+func (s *HealthService) Record(result *HealthResult) {
+	s.history.add(result)
+	if err := s.upstreamMgr.RecordHealthCheck(result); err != nil {
+		logger.Warn("保存健康检查结果失败: %v", err)
+	}
+	s.saveHistory()
+}
+
+// History 返回账号最近的探测结果，从新到旧，limit<=0 时返回全部
+func (s *HealthService) History(upstreamID string, limit int) []*HealthResult {
+	return s.history.list(upstreamID, limit)
+}
+
+// saveHistory 写入探测历史文件，失败只记录日志
+func (s *HealthService) saveHistory() {
+	if err := s.history.save(); err != nil {
+		logger.Warn("保存健康探测历史失败: %v", err)
+	}
+}
+
+// CheckAll 探测所有未禁用的账号
+func (s *HealthService) CheckAll() []*HealthResult {
+	return s.CheckMany(s.EnabledIDs())
+}
+
+// EnabledIDs 返回所有未禁用账号的ID，即 CheckAll 探测的范围
+func (s *HealthService) EnabledIDs() []string {
+	var ids []string
+	for _, account := range s.upstreamMgr.ListAccounts() {
+		if account.Status != "disabled" {
+			ids = append(ids, account.ID)
+		}
+	}
+	return ids
+}
+
+// Verify 在保存前验证API Key账号的凭证（账号无需已保存，不保存结果）；OAuth账号在授权后才有凭证，返回nil
+func (s *HealthService) Verify(account *types.UpstreamAccount) *HealthResult {
+	if account.Type != types.UpstreamTypeAPIKey {
+		return nil
+	}
+	return s.send(account, s.upstreamMgr.APIKeyHeaders(account))
+}
+
+// probe 对账号执行一次探测（不保存结果）
+func (s *HealthService) probe(account *types.UpstreamAccount) *HealthResult {
+	// 获取认证头部（OAuth账号会在必要时刷新token），失败说明凭证不可用
+	authHeaders, err := s.upstreamMgr.GetAuthHeaders(account.ID)
+	if err != nil {
+		return &HealthResult{UpstreamID: account.ID, Name: account.Name, Provider: account.Provider, Error: err.Error(), CheckedAt: time.Now()}
+	}
+	return s.send(account, authHeaders)
+}
+
+// send 使用给定的认证头部向提供商的探测接口发送请求
+func (s *HealthService) send(account *types.UpstreamAccount, authHeaders map[string]string) *HealthResult {
+	result := &HealthResult{
+		UpstreamID: account.ID,
+		Name:       account.Name,
+		Provider:   account.Provider,
+		CheckedAt:  time.Now(),
+	}
+
+	spec, ok := s.upstreamMgr.Providers().Get(account.Provider)
+	if !ok || spec.HealthPath == "" {
+		result.Healthy = true
+		return result
+	}
+
+	req, err := http.NewRequest(http.MethodGet, s.upstreamMgr.GetBaseURL(account)+spec.HealthPath, nil)
+	if err != nil {
+		result.Error = err.Error()
+		return result
+	}
+
+	if account.Provider == types.ProviderAnthropic {
+		req.Header.Set("User-Agent", "claude-cli/1.0.56 (external, cli)")
+	} else {
+		req.Header.Set("User-Agent", "LLM-Gateway/1.0")
+	}
+	for key, value := range authHeaders {
+		req.Header.Set(key, value)
+	}
+
+	result.Probed = true
+	start := time.Now()
+	resp, err := s.clientFor(account.Provider).Do(req)
+	result.LatencyMs = time.Since(start).Milliseconds()
+	if err != nil {
+		result.Error = err.Error()
+		return result
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	result.StatusCode = resp.StatusCode
+	if resp.StatusCode >= 200 && resp.StatusCode < 300 {
+		if spec.ParseModels == nil {
+			result.Healthy = true
+			_, _ = io.Copy(io.Discard, resp.Body)
+			return result
+		}
+
+		// 探测接口即模型列表，顺便记录后端当前提供的模型；解析失败说明后端不是预期的接口（如 base_url 多了 /v1）
+		body, err := io.ReadAll(io.LimitReader(resp.Body, maxHealthModelsBodyBytes))
+		if err == nil {
+			result.Models, err = spec.ParseModels(body)
+		}
+		if err != nil {
+			result.Error = err.Error()
+			return result
+		}
+		result.Healthy = true
+		return result
+	}
+
+	body, _ := io.ReadAll(io.LimitReader(resp.Body, maxHealthErrorBodyBytes))
+	result.Error = fmt.Sprintf("status %d: %s", resp.StatusCode, utils.TruncateUTF8(string(body), maxHealthErrorBodyBytes))
+	return result
+}
+
+// defaultHealthCheckInterval 未配置探测间隔时的默认值
+const defaultHealthCheckInterval = 5 * time.Minute
+
+// HealthScheduler 定期探测所有活跃账号，结果写入账号健康状态，
+// 健康优先路由据此在客户端请求到达前排除不健康的账号
+type HealthScheduler struct {
+	service  *HealthService
+	settings func() *types.HealthCheckConfig
+	stopCh   chan struct{}
+	mutex    sync.Mutex
+}
+
+// NewHealthScheduler 创建健康探测调度器，settings 返回当前的健康检查配置
+func NewHealthScheduler(service *HealthService, settings func() *types.HealthCheckConfig) *HealthScheduler {
+	return &HealthScheduler{
+		service:  service,
+		settings: settings,
+	}
+}
+
+// interval 当前探测间隔，每轮重新读取配置；返回0表示已关闭
+func (s *HealthScheduler) interval() time.Duration {
+	seconds := s.settings().IntervalSeconds
+	if seconds < 0 {
+		return 0
+	}
+	if seconds == 0 {
+		return defaultHealthCheckInterval
+	}
+	return time.Duration(seconds) * time.Second
+}
+
+// Start 启动后台探测，探测间隔为负数时不启动
+func (s *HealthScheduler) Start() {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil || s.interval() == 0 {
+		return
+	}
+	s.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		timer := time.NewTimer(s.interval())
+		defer timer.Stop()
+
+		for {
+			select {
+			case <-timer.C:
+				// 运行期间关闭探测时只等待配置恢复
+				interval := s.interval()
+				if interval > 0 {
+					s.RunOnce()
+				} else {
+					interval = defaultHealthCheckInterval
+				}
+				timer.Reset(interval)
+			case <-stopCh:
+				return
+			}
+		}
+	}(s.stopCh)
+}
+
+// Stop 停止后台探测
+func (s *HealthScheduler) Stop() {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		close(s.stopCh)
+		s.stopCh = nil
+	}
+}
+
+// RunOnce 探测所有活跃账号一次
+func (s *HealthScheduler) RunOnce() []*HealthResult {
+	var ids []string
+	for _, account := range s.service.upstreamMgr.ListAccounts() {
+		if account.Status == "active" {
+			ids = append(ids, account.ID)
+		}
+	}
+
+	results := s.service.CheckMany(ids)
+	for _, result := range results {
+		if !result.Healthy {
+			logger.Warn("上游账号健康检查失败: %s (%s): %s", result.UpstreamID, result.Name, result.Error)
+		}
+	}
+	return results
+}
@@ -0,0 +1,113 @@
+package upstream
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+)
+
+const (
+	defaultHealthHistorySize = 100
+	healthHistoryFile        = "history.json"
+)
+
+// healthHistory 每个账号最近的探测结果，保存在目录下的 history.json，重启后保留
+type healthHistory struct {
+	path    string
+	size    int
+	results map[string][]*HealthResult // 账号ID -> 探测结果，按时间从旧到新
+	mutex   sync.Mutex
+}
+
+// newHealthHistory 创建探测历史，dir 为空时使用 ~/.llm-gateway/health，size<=0 时使用默认值
+func newHealthHistory(dir string, size int) *healthHistory {
+	if size <= 0 {
+		size = defaultHealthHistorySize
+	}
+	return &healthHistory{
+		path:    filepath.Join(healthDir(dir), healthHistoryFile),
+		size:    size,
+		results: make(map[string][]*HealthResult),
+	}
+}
+
+// healthDir 探测历史与熔断记录所在目录，dir 为空时使用 ~/.llm-gateway/health
+func healthDir(dir string) string {
+	if dir != "" {
+		return dir
+	}
+	homeDir, err := os.UserHomeDir()
+	if err != nil {
+		return filepath.Join(".llm-gateway", "health")
+	}
+	return filepath.Join(homeDir, ".llm-gateway", "health")
+}
+
+// load 从文件加载探测历史，文件不存在时为空
+func (h *healthHistory) load() error {
+	data, err := os.ReadFile(h.path)
+	if os.IsNotExist(err) {
+		return nil
+	}
+	if err != nil {
+		return fmt.Errorf("读取探测历史失败: %w", err)
+	}
+
+	results := make(map[string][]*HealthResult)
+	if err := json.Unmarshal(data, &results); err != nil {
+		return fmt.Errorf("解析探测历史失败: %w", err)
+	}
+
+	h.mutex.Lock()
+	defer h.mutex.Unlock()
+	h.results = results
+	return nil
+}
+
+// save 将探测历史写入文件
+func (h *healthHistory) save() error {
+	h.mutex.Lock()
+	data, err := json.Marshal(h.results)
+	h.mutex.Unlock()
+	if err != nil {
+		return fmt.Errorf("序列化探测历史失败: %w", err)
+	}
+
+	if err := os.MkdirAll(filepath.Dir(h.path), 0700); err != nil {
+		return fmt.Errorf("创建探测历史目录失败: %w", err)
+	}
+	if err := os.WriteFile(h.path, data, 0600); err != nil {
+		return fmt.Errorf("写入探测历史失败: %w", err)
+	}
+	return nil
+}
+
+// add 追加一条探测结果，超过容量时淘汰该账号最旧的记录（不写文件）
+func (h *healthHistory) add(result *HealthResult) {
+	h.mutex.Lock()
+	defer h.mutex.Unlock()
+
+	results := append(h.results[result.UpstreamID], result)
+	if excess := len(results) - h.size; excess > 0 {
+		results = append([]*HealthResult(nil), results[excess:]...)
+	}
+	h.results[result.UpstreamID] = results
+}
+
+// list 返回账号最近的探测结果，从新到旧，limit<=0 时返回全部
+func (h *healthHistory) list(upstreamID string, limit int) []*HealthResult {
+	h.mutex.Lock()
+	defer h.mutex.Unlock()
+
+	results := h.results[upstreamID]
+	if limit <= 0 || limit > len(results) {
+		limit = len(results)
+	}
+	list := make([]*HealthResult, 0, limit)
+	for i := len(results) - 1; i >= 0 && len(list) < limit; i-- {
+		list = append(list, results[i])
+	}
+	return list
+}
@@ -0,0 +1,339 @@
+package upstream
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// healthSettings 返回固定的健康检查配置
+func healthSettings(config *types.HealthCheckConfig) func() *types.HealthCheckConfig {
+	return func() *types.HealthCheckConfig { return config }
+}
+
+func TestHealthService_Check(t *testing.T) {
+	upstreamServer := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.Method != http.MethodGet || r.URL.Path != "/v1/models" {
+			t.Errorf("unexpected probe request: %s %s", r.Method, r.URL.Path)
+		}
+		if r.Header.Get("Authorization") != "Bearer sk-good" {
+			w.WriteHeader(http.StatusUnauthorized)
+			_, _ = w.Write([]byte(`{"error":"invalid api key"}`))
+			return
+		}
+		_, _ = w.Write([]byte(`{"data":[]}`))
+	}))
+	defer upstreamServer.Close()
+
+	configMgr := NewMockUpstreamConfigManager()
+	for id, key := range map[string]string{"good": "sk-good", "bad": "sk-bad"} {
+		_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+			ID:       id,
+			Type:     types.UpstreamTypeAPIKey,
+			Provider: types.ProviderOpenAI,
+			BaseURL:  upstreamServer.URL,
+			APIKey:   key,
+			Status:   "active",
+		})
+	}
+	service := NewHealthService(NewUpstreamManager(configMgr), healthSettings(&types.HealthCheckConfig{HistoryDir: t.TempDir()}))
+
+	results := service.CheckMany([]string{"good", "missing", "bad"})
+	if len(results) != 2 {
+		t.Fatalf("len(results) = %d, want 2", len(results))
+	}
+
+	good := results[0]
+	if good.UpstreamID != "good" || !good.Healthy || !good.Probed || good.StatusCode != http.StatusOK {
+		t.Errorf("good result = %+v", good)
+	}
+
+	bad := results[1]
+	if bad.Healthy || bad.StatusCode != http.StatusUnauthorized || !strings.Contains(bad.Error, "invalid api key") {
+		t.Errorf("bad result = %+v", bad)
+	}
+
+	// 结果记录到账号
+	account, _ := configMgr.GetUpstreamAccount("bad")
+	if account.HealthStatus != "unhealthy" || account.LastHealthCheck == nil || account.HealthError != bad.Error {
+		t.Errorf("health result not recorded: %+v", account)
+	}
+	account, _ = configMgr.GetUpstreamAccount("good")
+	if account.HealthStatus != "healthy" || account.HealthError != "" {
+		t.Errorf("health result not recorded: %+v", account)
+	}
+}
+
+func TestHealthService_CheckEach(t *testing.T) {
+	var mutex sync.Mutex
+	inFlight, maxInFlight := 0, 0
+	upstreamServer := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		mutex.Lock()
+		inFlight++
+		if inFlight > maxInFlight {
+			maxInFlight = inFlight
+		}
+		mutex.Unlock()
+
+		time.Sleep(20 * time.Millisecond)
+		_, _ = w.Write([]byte(`{"data":[]}`))
+
+		mutex.Lock()
+		inFlight--
+		mutex.Unlock()
+	}))
+	defer upstreamServer.Close()
+
+	configMgr := NewMockUpstreamConfigManager()
+	var ids []string
+	for i := 0; i < 6; i++ {
+		id := fmt.Sprintf("up-%d", i)
+		ids = append(ids, id)
+		_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+			ID:       id,
+			Type:     types.UpstreamTypeAPIKey,
+			Provider: types.ProviderOpenAI,
+			BaseURL:  upstreamServer.URL,
+			APIKey:   "sk-" + id,
+			Status:   "active",
+		})
+	}
+	config := &types.HealthCheckConfig{MaxParallel: 2, HistorySize: 2, HistoryDir: t.TempDir()}
+	service := NewHealthService(NewUpstreamManager(configMgr), healthSettings(config))
+
+	seen := make(map[int]bool)
+	service.CheckEach(context.Background(), ids, func(i int, result *HealthResult) {
+		if result.UpstreamID != ids[i] || !result.Healthy {
+			t.Errorf("result %d = %+v", i, result)
+		}
+		seen[i] = true
+	})
+	if len(seen) != len(ids) {
+		t.Fatalf("callbacks = %d, want %d", len(seen), len(ids))
+	}
+	if maxInFlight > 2 {
+		t.Errorf("max concurrent probes = %d, want <= 2", maxInFlight)
+	}
+
+	// 每次探测都写入历史，超过 history_size 时淘汰最旧的记录
+	for i := 0; i < 2; i++ {
+		if _, err := service.Check("up-0"); err != nil {
+			t.Fatalf("Check() error = %v", err)
+		}
+	}
+	history := service.History("up-0", 0)
+	if len(history) != 2 || history[0].CheckedAt.Before(history[1].CheckedAt) {
+		t.Errorf("History() = %+v, want 2 results newest first", history)
+	}
+
+	// 重启后从文件恢复，账号的健康状态用最新的探测结果恢复
+	restartedMgr := NewMockUpstreamConfigManager()
+	_ = restartedMgr.CreateUpstreamAccount(&types.UpstreamAccount{ID: "up-5", Provider: types.ProviderOpenAI, Status: "active"})
+	restarted := NewHealthService(NewUpstreamManager(restartedMgr), healthSettings(config))
+	if got := restarted.History("up-5", 1); len(got) != 1 || got[0].UpstreamID != "up-5" {
+		t.Errorf("History() after restart = %+v", got)
+	}
+	if account, _ := restartedMgr.GetUpstreamAccount("up-5"); account.HealthStatus != "healthy" || account.LastHealthCheck == nil {
+		t.Errorf("health state after restart = %+v", account)
+	}
+
+	// 已取消的ctx不再开始探测
+	ctx, cancel := context.WithCancel(context.Background())
+	cancel()
+	probed := 0
+	service.CheckEach(ctx, ids, func(int, *HealthResult) { probed++ })
+	if probed != 0 {
+		t.Errorf("cancelled CheckEach probed %d accounts", probed)
+	}
+}
+
+func TestHealthService_DiscoverModels(t *testing.T) {
+	upstreamServer := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.Header.Get("Authorization") != "" {
+			t.Errorf("account without api_key sent Authorization: %s", r.Header.Get("Authorization"))
+		}
+		switch r.URL.Path {
+		case "/v1/models":
+			_, _ = w.Write([]byte(`{"object":"list","data":[{"id":"qwen2.5:7b"},{"id":"llama3.1:8b"}]}`))
+		default:
+			_, _ = w.Write([]byte(`<html>not an api</html>`))
+		}
+	}))
+	defer upstreamServer.Close()
+
+	configMgr := NewMockUpstreamConfigManager()
+	for id, baseURL := range map[string]string{"ollama": upstreamServer.URL, "wrong-path": upstreamServer.URL + "/ui"} {
+		_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+			ID:       id,
+			Type:     types.UpstreamTypeAPIKey,
+			Provider: types.ProviderOpenAICompatible,
+			BaseURL:  baseURL,
+			Status:   "active",
+		})
+	}
+	mgr := NewUpstreamManager(configMgr)
+	service := NewHealthService(mgr, healthSettings(&types.HealthCheckConfig{HistoryDir: t.TempDir()}))
+
+	result, err := service.Check("ollama")
+	if err != nil {
+		t.Fatalf("Check() error = %v", err)
+	}
+	if !result.Healthy || strings.Join(result.Models, ",") != "llama3.1:8b,qwen2.5:7b" {
+		t.Errorf("result = %+v", result)
+	}
+	account, _ := configMgr.GetUpstreamAccount("ollama")
+	if !account.ServesModel("qwen2.5:7b") || account.ServesModel("gpt-4o") {
+		t.Errorf("discovered models not persisted: %v", account.Models)
+	}
+
+	// 响应不是模型列表说明 base_url 配置错误
+	result, _ = service.Check("wrong-path")
+	if result.Healthy || result.Models != nil {
+		t.Errorf("non-JSON model list should be unhealthy: %+v", result)
+	}
+
+	if got := strings.Join(mgr.DiscoveredModels(), ","); got != "llama3.1:8b,qwen2.5:7b" {
+		t.Errorf("DiscoveredModels() = %s", got)
+	}
+}
+
+func TestHealthService_CredentialOnlyProvider(t *testing.T) {
+	configMgr := NewMockUpstreamConfigManager()
+	_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+		ID:       "azure",
+		Type:     types.UpstreamTypeAPIKey,
+		Provider: types.ProviderAzure,
+		APIKey:   "key",
+		Status:   "active",
+	})
+	_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+		ID:       "oauth-no-token",
+		Type:     types.UpstreamTypeOAuth,
+		Provider: types.ProviderAnthropic,
+		Status:   "active",
+	})
+	service := NewHealthService(NewUpstreamManager(configMgr), healthSettings(&types.HealthCheckConfig{HistoryDir: t.TempDir()}))
+
+	result, err := service.Check("azure")
+	if err != nil {
+		t.Fatalf("Check() error = %v", err)
+	}
+	if !result.Healthy || result.Probed {
+		t.Errorf("provider without health path should only check credentials: %+v", result)
+	}
+
+	result, err = service.Check("oauth-no-token")
+	if err != nil {
+		t.Fatalf("Check() error = %v", err)
+	}
+	if result.Healthy || result.Probed || result.Error == "" {
+		t.Errorf("OAuth account without token should be unhealthy: %+v", result)
+	}
+}
+
+func TestHealthScheduler_RunOnce(t *testing.T) {
+	probed := make(chan string, 4)
+	upstreamServer := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		probed <- r.Header.Get("Authorization")
+		w.WriteHeader(http.StatusServiceUnavailable)
+	}))
+	defer upstreamServer.Close()
+
+	configMgr := NewMockUpstreamConfigManager()
+	for id, status := range map[string]string{"active": "active", "disabled": "disabled"} {
+		_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+			ID:           id,
+			Type:         types.UpstreamTypeAPIKey,
+			Provider:     types.ProviderOpenAI,
+			BaseURL:      upstreamServer.URL,
+			APIKey:       "sk-" + id,
+			Status:       status,
+			HealthStatus: "healthy",
+		})
+	}
+
+	config := &types.HealthCheckConfig{IntervalSeconds: -1}
+	scheduler := NewHealthScheduler(NewHealthService(NewUpstreamManager(configMgr), healthSettings(&types.HealthCheckConfig{HistoryDir: t.TempDir()})), healthSettings(config))
+
+	// 负数间隔表示关闭，不启动后台任务
+	scheduler.Start()
+	if scheduler.stopCh != nil {
+		t.Fatal("scheduler should not start when interval is negative")
+	}
+
+	results := scheduler.RunOnce()
+	if len(results) != 1 || results[0].UpstreamID != "active" {
+		t.Fatalf("RunOnce() should only probe active accounts, got %+v", results)
+	}
+	if auth := <-probed; auth != "Bearer sk-active" {
+		t.Errorf("probed account auth = %s", auth)
+	}
+
+	// 探测失败的账号会被标记为unhealthy，健康优先路由会跳过它
+	account, _ := configMgr.GetUpstreamAccount("active")
+	if account.HealthStatus != "unhealthy" {
+		t.Errorf("HealthStatus = %s, want unhealthy", account.HealthStatus)
+	}
+	account, _ = configMgr.GetUpstreamAccount("disabled")
+	if account.HealthStatus != "healthy" {
+		t.Errorf("disabled account should not be probed")
+	}
+}
+
+func TestHealthService_ClientPerProvider(t *testing.T) {
+	service := NewHealthService(NewUpstreamManager(NewMockUpstreamConfigManager()), healthSettings(&types.HealthCheckConfig{TimeoutSeconds: 3, HistoryDir: t.TempDir()}))
+
+	openai := service.clientFor(types.ProviderOpenAI)
+	if openai != service.clientFor(types.ProviderOpenAI) {
+		t.Error("the same provider should reuse its probe client")
+	}
+	if openai == service.clientFor(types.ProviderAnthropic) {
+		t.Error("each provider should get its own probe client")
+	}
+
+	transport, ok := openai.Transport.(*http.Transport)
+	if !ok || openai.Timeout != 3*time.Second || transport.ResponseHeaderTimeout != 3*time.Second || transport.MaxConnsPerHost == 0 {
+		t.Errorf("probe client should use the probe timeout and a bounded pool: %+v", openai)
+	}
+}
+
+func TestHealthService_Verify(t *testing.T) {
+	upstreamServer := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		switch r.Header.Get("Authorization") {
+		case "Bearer sk-good":
+			_, _ = w.Write([]byte(`{"data":[]}`))
+		case "Bearer sk-busy":
+			w.WriteHeader(http.StatusTooManyRequests)
+		default:
+			w.WriteHeader(http.StatusUnauthorized)
+		}
+	}))
+	defer upstreamServer.Close()
+
+	service := NewHealthService(NewUpstreamManager(NewMockUpstreamConfigManager()), healthSettings(&types.HealthCheckConfig{HistoryDir: t.TempDir()}))
+	account := func(key string) *types.UpstreamAccount {
+		return &types.UpstreamAccount{Type: types.UpstreamTypeAPIKey, Provider: types.ProviderOpenAI, BaseURL: upstreamServer.URL, APIKey: key}
+	}
+
+	// 账号无需已保存
+	if result := service.Verify(account("sk-good")); !result.Healthy || result.CredentialRejected() {
+		t.Errorf("valid key: %+v", result)
+	}
+	if result := service.Verify(account("sk-typo")); result.Healthy || !result.CredentialRejected() {
+		t.Errorf("invalid key should be rejected: %+v", result)
+	}
+	if result := service.Verify(account("sk-busy")); result.Healthy || result.CredentialRejected() {
+		t.Errorf("rate limited key is unhealthy but not rejected: %+v", result)
+	}
+
+	if result := service.Verify(&types.UpstreamAccount{Type: types.UpstreamTypeOAuth, Provider: types.ProviderAnthropic}); result != nil {
+		t.Errorf("OAuth account has nothing to verify: %+v", result)
+	}
+}
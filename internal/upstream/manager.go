@@ -2,7 +2,12 @@ package upstream
 
 import (
 	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"sort"
 	"strings"
+	"sync"
 	"time"
 
 	"github.com/iBreaker/llm-gateway/pkg/types"
@@ -15,19 +20,66 @@ type ConfigManager interface {
 	ListUpstreamAccounts() []*types.UpstreamAccount
 	ListActiveUpstreamAccounts(provider types.Provider) []*types.UpstreamAccount
 	UpdateUpstreamAccount(accountID string, updater func(*types.UpstreamAccount) error) error
+	UpdateUpstreamAccountState(accountID string, updater func(*types.UpstreamAccount) error) error
 	DeleteUpstreamAccount(accountID string) error
 }
 
 // UpstreamManager 上游账号业务管理器
 type UpstreamManager struct {
-	configMgr ConfigManager
+	configMgr    ConfigManager
+	providers    *ProviderRegistry
+	breakers     *CircuitBreakers
+	rateLimits   *RateLimits
+	overloads    *ProviderBackoff
+	authFailures *authFailures
+
+	// refreshLocks 每个账号一把刷新锁，避免请求路径和后台任务同时使用同一个refresh token
+	refreshLocks map[string]*sync.Mutex
+	refreshMutex sync.Mutex
 }
 
 // NewUpstreamManager 创建新的上游账号管理器
 func NewUpstreamManager(configMgr ConfigManager) *UpstreamManager {
-	return &UpstreamManager{
-		configMgr: configMgr,
+	m := &UpstreamManager{
+		configMgr:    configMgr,
+		providers:    NewProviderRegistry(),
+		breakers:     newCircuitBreakers(),
+		rateLimits:   newRateLimits(),
+		overloads:    newProviderBackoff(),
+		authFailures: newAuthFailures(),
+		refreshLocks: make(map[string]*sync.Mutex),
 	}
+	m.breakers.overrides = m.breakerOverride
+	return m
+}
+
+// breakerOverride 返回账号配置的熔断器参数覆盖，未配置或账号不存在时为nil
+func (m *UpstreamManager) breakerOverride(upstreamID string) *types.CircuitBreakerConfig {
+	account, err := m.configMgr.GetUpstreamAccount(upstreamID)
+	if err != nil {
+		return nil
+	}
+	return account.CircuitBreaker
+}
+
+// Providers 获取提供商注册表
+func (m *UpstreamManager) Providers() *ProviderRegistry {
+	return m.providers
+}
+
+// Breakers 返回账号熔断器
+func (m *UpstreamManager) Breakers() *CircuitBreakers {
+	return m.breakers
+}
+
+// RateLimits 返回上游报告的账号剩余额度
+func (m *UpstreamManager) RateLimits() *RateLimits {
+	return m.rateLimits
+}
+
+// Overloads 返回按提供商记录的过载退避状态
+func (m *UpstreamManager) Overloads() *ProviderBackoff {
+	return m.overloads
 }
 
 // AddAccount 添加上游账号（业务逻辑）
@@ -83,6 +135,35 @@ func (m *UpstreamManager) ListActiveAccounts(provider types.Provider) []*types.U
 	return m.configMgr.ListActiveUpstreamAccounts(provider)
 }
 
+// ListAvailableAccounts 列出某个组织的Key可以使用的活跃账号：未归属组织的共用账号和该组织自己的账号。
+// orgID 为空（Key不属于任何组织）时只返回共用账号
+func (m *UpstreamManager) ListAvailableAccounts(provider types.Provider, orgID string) []*types.UpstreamAccount {
+	active := m.configMgr.ListActiveUpstreamAccounts(provider)
+	available := make([]*types.UpstreamAccount, 0, len(active))
+	for _, account := range active {
+		if account.AvailableTo(orgID) {
+			available = append(available, account)
+		}
+	}
+	return available
+}
+
+// DiscoveredModels 返回活跃的 OpenAI 兼容账号从后端发现的模型（去重并排序）
+func (m *UpstreamManager) DiscoveredModels() []string {
+	seen := make(map[string]bool)
+	var models []string
+	for _, account := range m.ListActiveAccounts(types.ProviderOpenAICompatible) {
+		for _, model := range account.Models {
+			if !seen[model] {
+				seen[model] = true
+				models = append(models, model)
+			}
+		}
+	}
+	sort.Strings(models)
+	return models
+}
+
 // DeleteAccount 删除上游账号
 func (m *UpstreamManager) DeleteAccount(upstreamID string) error {
 	return m.configMgr.DeleteUpstreamAccount(upstreamID)
@@ -90,8 +171,14 @@ func (m *UpstreamManager) DeleteAccount(upstreamID string) error {
 
 // UpdateAccountStatus 更新上游账号状态（业务逻辑）
 func (m *UpstreamManager) UpdateAccountStatus(upstreamID string, status string) error {
+	if status == "active" {
+		m.RecordAuthSuccess(upstreamID)
+	}
 	return m.configMgr.UpdateUpstreamAccount(upstreamID, func(account *types.UpstreamAccount) error {
 		account.Status = status
+		if status == "active" {
+			account.DisabledReason = ""
+		}
 		account.UpdatedAt = time.Now()
 		return nil
 	})
@@ -114,6 +201,32 @@ func (m *UpstreamManager) UpdateAccountHealth(upstreamID string, healthy bool) e
 	})
 }
 
+// RecordHealthCheck 记录健康探测结果（业务逻辑）。
+// 结果只保存在内存中（历史由 HealthService 写入探测历史文件），不会每次探测都重写配置文件
+func (m *UpstreamManager) RecordHealthCheck(result *HealthResult) error {
+	return m.configMgr.UpdateUpstreamAccountState(result.UpstreamID, func(account *types.UpstreamAccount) error {
+		ApplyHealthResult(account, result)
+		return nil
+	})
+}
+
+// ApplyHealthResult 将探测结果写入账号的健康状态字段
+func ApplyHealthResult(account *types.UpstreamAccount, result *HealthResult) {
+	checkedAt := result.CheckedAt
+	account.LastHealthCheck = &checkedAt
+
+	if result.Healthy {
+		account.HealthStatus = "healthy"
+	} else {
+		account.HealthStatus = "unhealthy"
+	}
+	account.HealthLatencyMs = result.LatencyMs
+	account.HealthError = result.Error
+	if result.Models != nil {
+		account.Models = result.Models
+	}
+}
+
 // RecordSuccess 记录成功请求（业务逻辑）
 func (m *UpstreamManager) RecordSuccess(upstreamID string, latency time.Duration, tokensUsed int64) error {
 	return m.configMgr.UpdateUpstreamAccount(upstreamID, func(account *types.UpstreamAccount) error {
@@ -215,30 +328,23 @@ func (m *UpstreamManager) GetAuthHeaders(upstreamID string) (map[string]string,
 	}
 	headers := make(map[string]string)
 
+	// 认证头部由提供商注册表中的实现构建，未注册的提供商使用Bearer认证
+	spec, hasSpec := m.providers.Get(account.Provider)
+
 	switch account.Type {
 	case types.UpstreamTypeAPIKey:
-		switch account.Provider {
-		case types.ProviderAnthropic:
-			headers["x-api-key"] = account.APIKey
-			headers["anthropic-version"] = "2023-06-01"
-			// Claude Code必需的beta标识
-			headers["anthropic-beta"] = "claude-code-20250219,oauth-2025-04-20,interleaved-thinking-2025-05-14,fine-grained-tool-streaming-2025-05-14"
-		case types.ProviderOpenAI:
-			headers["Authorization"] = "Bearer " + account.APIKey
-		default:
-			headers["Authorization"] = "Bearer " + account.APIKey
-		}
+		return m.APIKeyHeaders(account), nil
 
 	case types.UpstreamTypeOAuth:
 		if account.AccessToken == "" {
 			return nil, fmt.Errorf("OAuth account missing access token")
 		}
 
-		// 1. 提前5分钟刷新token，避免在请求过程中过期
+		// 1. 提前刷新token，避免在请求过程中过期（后台刷新任务通常已提前完成）
 		needRefresh := false
 		if account.ExpiresAt != nil {
 			timeUntilExpiry := time.Until(*account.ExpiresAt)
-			if timeUntilExpiry < 5*time.Minute {
+			if timeUntilExpiry < requestRefreshLeadTime {
 				needRefresh = true
 			}
 		} else {
@@ -266,19 +372,14 @@ func (m *UpstreamManager) GetAuthHeaders(upstreamID string) (map[string]string,
 		// OAuth总是使用Bearer认证
 		headers["Authorization"] = "Bearer " + account.AccessToken
 
-		// Anthropic OAuth需要特殊处理
-		if account.Provider == types.ProviderAnthropic {
-			// 设置API版本头部
-			headers["anthropic-version"] = "2023-06-01"
-			// 设置OAuth特有的beta标志
-			headers["anthropic-beta"] = "claude-code-20250219,oauth-2025-04-20,interleaved-thinking-2025-05-14,fine-grained-tool-streaming-2025-05-14"
+		// 提供商特有的OAuth头部（如Anthropic的beta标志、DashScope头部）
+		if hasSpec && spec.OAuthHeaders != nil {
+			for key, value := range spec.OAuthHeaders(account) {
+				headers[key] = value
+			}
 		}
-
-		// Qwen DashScope OAuth需要特殊处理
-		if account.Provider == types.ProviderQwen {
-			// DashScope 特殊头部
-			headers["X-DashScope-CacheControl"] = "enable"
-			headers["X-DashScope-UserAgent"] = "LLM-Gateway/1.0"
+		if hasSpec && spec.VersionHeader != "" && account.APIVersion != "" {
+			headers[spec.VersionHeader] = account.APIVersion
 		}
 
 	default:
@@ -288,6 +389,22 @@ func (m *UpstreamManager) GetAuthHeaders(upstreamID string) (map[string]string,
 	return headers, nil
 }
 
+// APIKeyHeaders 构建API Key账号的认证头部（账号无需已保存），未注册的提供商使用Bearer认证
+func (m *UpstreamManager) APIKeyHeaders(account *types.UpstreamAccount) map[string]string {
+	headers := make(map[string]string)
+	if spec, ok := m.providers.Get(account.Provider); ok {
+		for key, value := range spec.APIKeyHeaders(account) {
+			headers[key] = value
+		}
+		if spec.VersionHeader != "" && account.APIVersion != "" {
+			headers[spec.VersionHeader] = account.APIVersion
+		}
+	} else {
+		headers["Authorization"] = "Bearer " + account.APIKey
+	}
+	return headers
+}
+
 // autoRefreshToken 自动刷新OAuth token（业务逻辑）
 func (m *UpstreamManager) autoRefreshToken(account *types.UpstreamAccount) error {
 	// 1. 检查是否有refresh token
@@ -298,7 +415,47 @@ func (m *UpstreamManager) autoRefreshToken(account *types.UpstreamAccount) error
 	// 2. 创建OAuth管理器实例并调用刷新方法
 	// RefreshToken内部会调用UpdateOAuthTokens更新配置中的数据
 	oauthMgr := NewOAuthManager(m)
-	return oauthMgr.RefreshToken(account.ID)
+	return m.RefreshOAuthToken(account.ID, requestRefreshLeadTime, oauthMgr.RefreshToken)
+}
+
+// NeedsTokenRefresh 判断OAuth账号是否需要刷新token：有refresh token，且没有过期时间或将在leadTime内过期
+func NeedsTokenRefresh(account *types.UpstreamAccount, now time.Time, leadTime time.Duration) bool {
+	if account.Type != types.UpstreamTypeOAuth || account.RefreshToken == "" {
+		return false
+	}
+	if account.ExpiresAt == nil {
+		return true
+	}
+	return account.ExpiresAt.Sub(now) < leadTime
+}
+
+// RefreshOAuthToken 持有账号的刷新锁后调用refresh；等锁期间token已被其他请求或后台任务刷新时直接返回
+func (m *UpstreamManager) RefreshOAuthToken(upstreamID string, leadTime time.Duration, refresh func(upstreamID string) error) error {
+	lock := m.refreshLock(upstreamID)
+	lock.Lock()
+	defer lock.Unlock()
+
+	account, err := m.configMgr.GetUpstreamAccount(upstreamID)
+	if err != nil {
+		return err
+	}
+	if !NeedsTokenRefresh(account, time.Now(), leadTime) {
+		return nil
+	}
+	return refresh(upstreamID)
+}
+
+// refreshLock 获取账号的刷新锁
+func (m *UpstreamManager) refreshLock(upstreamID string) *sync.Mutex {
+	m.refreshMutex.Lock()
+	defer m.refreshMutex.Unlock()
+
+	lock, exists := m.refreshLocks[upstreamID]
+	if !exists {
+		lock = &sync.Mutex{}
+		m.refreshLocks[upstreamID] = lock
+	}
+	return lock
 }
 
 // GetBaseURL 获取上游账号的BaseURL
@@ -319,47 +476,104 @@ func (m *UpstreamManager) GetBaseURL(account *types.UpstreamAccount) string {
 		}
 	}
 
-	// 3. 根据提供商返回默认BaseURL
-	return m.getDefaultBaseURL(account.Provider)
+	// 3. 根据提供商返回默认BaseURL，按区域划分端点的提供商（如Bedrock）替换其中的 {region}
+	baseURL := m.getDefaultBaseURL(account.Provider)
+	if account.AWS != nil && account.AWS.Region != "" {
+		baseURL = strings.ReplaceAll(baseURL, "{region}", account.AWS.Region)
+	}
+	return baseURL
+}
+
+// SupportsAPIVersion 检查提供商是否支持按账号固定API版本
+func (m *UpstreamManager) SupportsAPIVersion(provider types.Provider) bool {
+	spec, ok := m.providers.Get(provider)
+	return ok && (spec.VersionHeader != "" || spec.VersionQuery != "")
+}
+
+// VersionedURL 为通过查询参数传递版本（如Azure的api-version）的提供商设置该参数，
+// 使用账号固定的版本或提供商的默认版本，覆盖URL中已有的值
+func (m *UpstreamManager) VersionedURL(account *types.UpstreamAccount, rawURL string) string {
+	spec, ok := m.providers.Get(account.Provider)
+	if !ok || spec.VersionQuery == "" {
+		return rawURL
+	}
+	version := account.APIVersion
+	if version == "" {
+		version = spec.DefaultVersion
+	}
+	if version == "" {
+		return rawURL
+	}
+	parsed, err := url.Parse(rawURL)
+	if err != nil {
+		return rawURL
+	}
+	query := parsed.Query()
+	query.Set(spec.VersionQuery, version)
+	parsed.RawQuery = query.Encode()
+	return parsed.String()
+}
+
+// UpstreamURL 构建上游请求地址：BaseURL 加上提供商的实际路径（Azure 为模型对应的部署路径，
+// Bedrock 为模型的 InvokeModel 路径），并设置API版本查询参数
+func (m *UpstreamManager) UpstreamURL(account *types.UpstreamAccount, path string, request *types.UnifiedRequest) string {
+	if spec, ok := m.providers.Get(account.Provider); ok && spec.RequestPath != nil {
+		path = spec.RequestPath(account, path, request)
+	}
+	return m.VersionedURL(account, m.GetBaseURL(account)+path)
+}
+
+// UpstreamBody 按提供商的要求调整转换后的请求体，没有特殊要求时原样返回
+func (m *UpstreamManager) UpstreamBody(account *types.UpstreamAccount, body []byte) ([]byte, error) {
+	if spec, ok := m.providers.Get(account.Provider); ok && spec.RequestBody != nil {
+		return spec.RequestBody(body)
+	}
+	return body, nil
+}
+
+// SignRequest 对需要签名的提供商（如Bedrock）签名请求，必须在设置完所有请求头之后调用
+func (m *UpstreamManager) SignRequest(account *types.UpstreamAccount, req *http.Request, body []byte) error {
+	if spec, ok := m.providers.Get(account.Provider); ok && spec.SignRequest != nil {
+		return spec.SignRequest(account, req, body, time.Now())
+	}
+	return nil
+}
+
+// StreamBody 把提供商自己编码的流式响应转换为SSE，第二个返回值表示是否做了转换（此时响应不是 text/event-stream）
+func (m *UpstreamManager) StreamBody(account *types.UpstreamAccount, body io.ReadCloser) (io.ReadCloser, bool) {
+	if spec, ok := m.providers.Get(account.Provider); ok && spec.StreamBody != nil {
+		return spec.StreamBody(body), true
+	}
+	return body, false
 }
 
 // getDefaultBaseURL 获取提供商的默认BaseURL
 func (m *UpstreamManager) getDefaultBaseURL(provider types.Provider) string {
-	switch provider {
-	case types.ProviderAnthropic:
-		return "https://api.anthropic.com"
-	case types.ProviderOpenAI:
-		return "https://api.openai.com"
-	case types.ProviderGoogle:
-		return "https://generativelanguage.googleapis.com"
-	case types.ProviderAzure:
-		return "https://your-resource.openai.azure.com" // 需要配置
-	case types.ProviderQwen:
-		return "https://dashscope.aliyuncs.com/compatible-mode/v1"
-	default:
-		return "https://api.anthropic.com"
+	if spec, ok := m.providers.Get(provider); ok && spec.DefaultBaseURL != "" {
+		return spec.DefaultBaseURL
 	}
+	return "https://api.anthropic.com"
 }
 
 // StartAnthropicOAuth 启动Anthropic OAuth授权流程
 func (m *UpstreamManager) StartAnthropicOAuth(upstreamID string) (*AnthropicOAuthResult, error) {
 	oauthMgr := NewOAuthManager(m)
-	
+
 	account, err := m.configMgr.GetUpstreamAccount(upstreamID)
 	if err != nil {
 		return nil, err
 	}
-	
+
 	if account.Provider != types.ProviderAnthropic {
 		return nil, fmt.Errorf("account is not an Anthropic provider")
 	}
-	
+
 	// 使用通用的StartOAuthFlow方法，它会根据provider分发
 	authURL, err := oauthMgr.StartOAuthFlow(upstreamID)
 	if err != nil {
 		return nil, err
 	}
-	
+
 	return &AnthropicOAuthResult{
 		AuthURL: authURL,
 	}, nil
@@ -368,25 +582,25 @@ func (m *UpstreamManager) StartAnthropicOAuth(upstreamID string) (*AnthropicOAut
 // StartQwenOAuth 启动Qwen OAuth设备流程
 func (m *UpstreamManager) StartQwenOAuth(upstreamID string) (*QwenOAuthResult, error) {
 	oauthMgr := NewOAuthManager(m)
-	
+
 	account, err := m.configMgr.GetUpstreamAccount(upstreamID)
 	if err != nil {
 		return nil, err
 	}
-	
+
 	if account.Provider != types.ProviderQwen {
 		return nil, fmt.Errorf("account is not a Qwen provider")
 	}
-	
+
 	// 获取Qwen配置
 	config := oauthMgr.GetQwenConfig()
-	
+
 	// 生成PKCE参数
 	codeVerifier, codeChallenge, err := GeneratePKCE()
 	if err != nil {
 		return nil, fmt.Errorf("生成PKCE参数失败: %w", err)
 	}
-	
+
 	// 构建设备授权请求
 	deviceReq := map[string]string{
 		"client_id":             config.ClientID,
@@ -394,20 +608,20 @@ func (m *UpstreamManager) StartQwenOAuth(upstreamID string) (*QwenOAuthResult, e
 		"code_challenge":        codeChallenge,
 		"code_challenge_method": "S256",
 	}
-	
+
 	// 发送设备授权请求
 	deviceResp, err := oauthMgr.RequestQwenDeviceCode(config.DeviceAuthURL, deviceReq)
 	if err != nil {
 		return nil, fmt.Errorf("请求设备授权码失败: %w", err)
 	}
-	
+
 	// 存储device_code和code_verifier用于后续轮询
 	verifierData := fmt.Sprintf("%s|%s", deviceResp.DeviceCode, codeVerifier)
 	oauthMgr.StorePKCEVerifier(upstreamID, verifierData)
-	
+
 	// 启动自动轮询
 	oauthMgr.PollQwenToken(upstreamID, deviceResp.DeviceCode, codeVerifier, deviceResp.Interval, deviceResp.ExpiresIn)
-	
+
 	return &QwenOAuthResult{
 		DeviceCode:      deviceResp.DeviceCode,
 		UserCode:        deviceResp.UserCode,
@@ -429,20 +643,20 @@ func (m *UpstreamManager) GetOAuthStatus(upstreamID string) (string, error) {
 	if err != nil {
 		return "error", err
 	}
-	
+
 	if account.Type != types.UpstreamTypeOAuth {
 		return "not_oauth", nil
 	}
-	
+
 	if account.AccessToken == "" {
 		return "not_authorized", nil
 	}
-	
+
 	// 检查token是否过期
 	if account.ExpiresAt != nil && time.Now().After(*account.ExpiresAt) {
 		return "expired", nil
 	}
-	
+
 	return "authorized", nil
 }
 
@@ -58,6 +58,10 @@ func (m *MockUpstreamConfigManager) UpdateUpstreamAccount(accountID string, upda
 	return updater(account)
 }
 
+func (m *MockUpstreamConfigManager) UpdateUpstreamAccountState(accountID string, updater func(*types.UpstreamAccount) error) error {
+	return m.UpdateUpstreamAccount(accountID, updater)
+}
+
 func (m *MockUpstreamConfigManager) DeleteUpstreamAccount(accountID string) error {
 	_, exists := m.accounts[accountID]
 	if !exists {
@@ -195,6 +199,35 @@ func TestUpstreamManager_ListActiveAccounts(t *testing.T) {
 	}
 }
 
+func TestUpstreamManager_ListAvailableAccounts(t *testing.T) {
+	configMgr := NewMockUpstreamConfigManager()
+	for _, account := range []*types.UpstreamAccount{
+		{ID: "shared", Provider: types.ProviderAnthropic, Status: "active"},
+		{ID: "ml-1", Provider: types.ProviderAnthropic, Status: "active", OrgID: "org-ml"},
+		{ID: "web-1", Provider: types.ProviderAnthropic, Status: "active", OrgID: "org-web"},
+	} {
+		_ = configMgr.CreateUpstreamAccount(account)
+	}
+	mgr := NewUpstreamManager(configMgr)
+
+	ids := func(accounts []*types.UpstreamAccount) map[string]bool {
+		result := make(map[string]bool)
+		for _, account := range accounts {
+			result[account.ID] = true
+		}
+		return result
+	}
+
+	// 组织的Key可以使用共用账号和本组织的账号，不能使用其他组织的账号
+	if got := ids(mgr.ListAvailableAccounts(types.ProviderAnthropic, "org-ml")); len(got) != 2 || !got["shared"] || !got["ml-1"] {
+		t.Errorf("ListAvailableAccounts(org-ml) = %v, want shared and ml-1", got)
+	}
+	// 不属于组织的Key只能使用共用账号
+	if got := ids(mgr.ListAvailableAccounts(types.ProviderAnthropic, "")); len(got) != 1 || !got["shared"] {
+		t.Errorf("ListAvailableAccounts(\"\") = %v, want shared", got)
+	}
+}
+
 func TestUpstreamManager_UpdateAccountStatus(t *testing.T) {
 	configMgr := NewMockUpstreamConfigManager()
 	mgr := NewUpstreamManager(configMgr)
@@ -15,8 +15,12 @@ import (
 
 	"github.com/iBreaker/llm-gateway/pkg/logger"
 	"github.com/iBreaker/llm-gateway/pkg/types"
+	"github.com/iBreaker/llm-gateway/pkg/utils"
 )
 
+// maxLogBodyBytes 日志和错误信息中保留的响应体长度
+const maxLogBodyBytes = 2048
+
 // OAuthManager OAuth管理器
 type OAuthManager struct {
 	upstreamMgr   *UpstreamManager
@@ -683,8 +687,8 @@ func (m *OAuthManager) exchangeCodeForToken(tokenURL string, tokenReq map[string
 	logger.Debug("Token响应状态码: %d", resp.StatusCode)
 
 	if resp.StatusCode != http.StatusOK {
-		logger.Debug("Token请求失败，响应内容: %s", string(body))
-		return nil, fmt.Errorf("token请求失败，状态码: %d, 响应: %s", resp.StatusCode, string(body))
+		logger.Debug("Token请求失败，响应内容: %s", utils.Excerpt(body, maxLogBodyBytes))
+		return nil, fmt.Errorf("token请求失败，状态码: %d, 响应: %s", resp.StatusCode, utils.Excerpt(body, maxLogBodyBytes))
 	}
 
 	// 脱敏处理响应内容中的敏感信息并打印
@@ -0,0 +1,270 @@
+package upstream
+
+import (
+	"encoding/json"
+	"fmt"
+	"math/rand"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/internal/redis"
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+const (
+	defaultOverloadBase = 5 * time.Second  // 未配置时第一次过载的退避时长
+	defaultOverloadMax  = 60 * time.Second // 未配置时退避时长的上限
+)
+
+// OverloadState 提供商的过载退避状态，多个网关实例通过共享存储同步
+type OverloadState struct {
+	Until     time.Time `json:"until"`   // 退避窗口的结束时间
+	Strikes   int       `json:"strikes"` // 连续过载次数（中间没有成功请求），窗口按它翻倍
+	UpdatedAt time.Time `json:"updated_at"`
+}
+
+// OverloadStore 共享过载退避状态的存储，每个提供商保存最近一次变化
+type OverloadStore interface {
+	Publish(provider types.Provider, state OverloadState) error
+	Load() (map[types.Provider]OverloadState, error)
+}
+
+// ProviderBackoff 按提供商记录上游过载（529 overloaded_error）。过载说明的是提供商整体的容量不足，
+// 换同一提供商的其他账号通常也会过载，因此退避窗口按提供商计算：窗口内路由优先选择其他提供商，
+// 没有其他选择时直接告诉客户端过载和重试时间，而不是继续加重上游的负担
+type ProviderBackoff struct {
+	enabled bool
+	base    time.Duration
+	max     time.Duration
+	states  map[types.Provider]*OverloadState
+	store   OverloadStore // 多实例共享状态的存储，为nil时只在本实例生效
+	jitter  func() float64
+	mutex   sync.Mutex
+}
+
+// newProviderBackoff 创建未启用的过载退避记录，Configure 后生效
+func newProviderBackoff() *ProviderBackoff {
+	return &ProviderBackoff{
+		base:   defaultOverloadBase,
+		max:    defaultOverloadMax,
+		states: make(map[types.Provider]*OverloadState),
+		jitter: rand.Float64,
+	}
+}
+
+// Configure 应用过载退避配置，未启用时 Record 不做任何事
+func (b *ProviderBackoff) Configure(config *types.OverloadBackoffConfig) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	b.enabled = config.Enabled
+	b.base = defaultOverloadBase
+	if config.BaseSeconds > 0 {
+		b.base = time.Duration(config.BaseSeconds) * time.Second
+	}
+	b.max = defaultOverloadMax
+	if config.MaxSeconds > 0 {
+		b.max = time.Duration(config.MaxSeconds) * time.Second
+	}
+	if b.max < b.base {
+		b.max = b.base
+	}
+}
+
+// Enabled 返回是否启用了过载退避
+func (b *ProviderBackoff) Enabled() bool {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	return b.enabled
+}
+
+// Record 记录提供商的一次过载，返回退避窗口的结束时间；未启用时返回零值。
+// 窗口为 base*2^(连续次数-1)（不超过max）的一半到全长之间的随机值，上游给出的 Retry-After 更长时以它为准。
+// 窗口内再次过载（如同一时刻的并发请求都收到529）属于同一次过载，不增加连续次数，只有更长的 Retry-After 能延长窗口
+func (b *ProviderBackoff) Record(provider types.Provider, retryAfter time.Duration, now time.Time) time.Time {
+	b.mutex.Lock()
+	if !b.enabled {
+		b.mutex.Unlock()
+		return time.Time{}
+	}
+
+	state := b.states[provider]
+	if state == nil {
+		state = &OverloadState{}
+		b.states[provider] = state
+	}
+	if now.Before(state.Until) {
+		if !now.Add(retryAfter).After(state.Until) {
+			until := state.Until
+			b.mutex.Unlock()
+			return until
+		}
+		state.Until = now.Add(retryAfter)
+		state.UpdatedAt = now
+		shared := *state
+		b.mutex.Unlock()
+
+		b.publish(provider, shared)
+		return shared.Until
+	}
+	state.Strikes++
+
+	window := b.base
+	for i := 1; i < state.Strikes && window < b.max; i++ {
+		window *= 2
+	}
+	if window > b.max {
+		window = b.max
+	}
+	window = window/2 + time.Duration(b.jitter()*float64(window/2))
+	if retryAfter > window {
+		window = retryAfter
+	}
+	state.Until = now.Add(window)
+	state.UpdatedAt = now
+	shared := *state
+	b.mutex.Unlock()
+
+	logger.Warn("提供商 %s 过载（连续第%d次），退避到 %s", provider, shared.Strikes, shared.Until.Format(time.RFC3339))
+	b.publish(provider, shared)
+	return shared.Until
+}
+
+// Clear 提供商的请求成功后清零连续过载次数，下次过载重新从 base 开始退避
+func (b *ProviderBackoff) Clear(provider types.Provider, now time.Time) {
+	b.mutex.Lock()
+	state := b.states[provider]
+	if state == nil || state.Strikes == 0 {
+		b.mutex.Unlock()
+		return
+	}
+	state.Strikes = 0
+	state.UpdatedAt = now
+	shared := *state
+	b.mutex.Unlock()
+
+	b.publish(provider, shared)
+}
+
+// Until 返回提供商当前退避窗口的结束时间，不在退避中时返回 ok=false
+func (b *ProviderBackoff) Until(provider types.Provider, now time.Time) (time.Time, bool) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	if !b.enabled {
+		return time.Time{}, false
+	}
+	state := b.states[provider]
+	if state == nil || !now.Before(state.Until) {
+		return time.Time{}, false
+	}
+	return state.Until, true
+}
+
+// Active 返回当前处于退避中的提供商及窗口的结束时间
+func (b *ProviderBackoff) Active(now time.Time) map[types.Provider]time.Time {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	active := make(map[types.Provider]time.Time)
+	if !b.enabled {
+		return active
+	}
+	for provider, state := range b.states {
+		if now.Before(state.Until) {
+			active[provider] = state.Until
+		}
+	}
+	return active
+}
+
+// SetStore 设置共享存储：之后本实例的过载和恢复都会写入存储，Sync 读取其他实例的变化
+func (b *ProviderBackoff) SetStore(store OverloadStore) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	b.store = store
+}
+
+// Sync 读取共享存储，应用其他实例更新的退避状态
+func (b *ProviderBackoff) Sync() error {
+	b.mutex.Lock()
+	store := b.store
+	b.mutex.Unlock()
+	if store == nil {
+		return nil
+	}
+
+	states, err := store.Load()
+	if err != nil {
+		return err
+	}
+
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+	for provider, shared := range states {
+		state := b.states[provider]
+		if state != nil && !shared.UpdatedAt.After(state.UpdatedAt) {
+			continue
+		}
+		shared := shared
+		b.states[provider] = &shared
+	}
+	return nil
+}
+
+// publish 写入共享存储，未设置存储时不做任何事；失败只记录日志，其他实例在下次变化时同步
+func (b *ProviderBackoff) publish(provider types.Provider, state OverloadState) {
+	b.mutex.Lock()
+	store := b.store
+	b.mutex.Unlock()
+	if store == nil {
+		return
+	}
+
+	if err := store.Publish(provider, state); err != nil {
+		logger.Warn("提供商 %s 的过载退避状态未能共享: %v", provider, err)
+	}
+}
+
+// RedisOverloadStore 过载退避状态保存在 Redis 哈希中（提供商 -> JSON）
+type RedisOverloadStore struct {
+	client *redis.Client
+}
+
+// NewRedisOverloadStore 创建 Redis 过载退避状态存储
+func NewRedisOverloadStore(client *redis.Client) *RedisOverloadStore {
+	return &RedisOverloadStore{client: client}
+}
+
+// Publish 写入提供商的共享状态
+func (s *RedisOverloadStore) Publish(provider types.Provider, state OverloadState) error {
+	data, err := json.Marshal(state)
+	if err != nil {
+		return err
+	}
+	_, err = s.client.Do("HSET", s.client.Key("overloads"), string(provider), data)
+	return err
+}
+
+// Load 读取所有提供商的共享状态，跳过无法解析的记录
+func (s *RedisOverloadStore) Load() (map[types.Provider]OverloadState, error) {
+	reply, err := s.client.Do("HGETALL", s.client.Key("overloads"))
+	if err != nil {
+		return nil, err
+	}
+	items, ok := reply.([]interface{})
+	if !ok || len(items)%2 != 0 {
+		return nil, fmt.Errorf("无效的过载退避状态回复: %T", reply)
+	}
+
+	states := make(map[types.Provider]OverloadState, len(items)/2)
+	for i := 0; i < len(items); i += 2 {
+		provider, _ := items[i].(string)
+		data, _ := items[i+1].(string)
+		var state OverloadState
+		if provider == "" || json.Unmarshal([]byte(data), &state) != nil {
+			continue
+		}
+		states[types.Provider(provider)] = state
+	}
+	return states, nil
+}
@@ -0,0 +1,123 @@
+package upstream
+
+import (
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// memoryOverloadStore 内存中的共享存储，模拟多个实例共用的 Redis
+type memoryOverloadStore map[types.Provider]OverloadState
+
+func (s memoryOverloadStore) Publish(provider types.Provider, state OverloadState) error {
+	s[provider] = state
+	return nil
+}
+
+func (s memoryOverloadStore) Load() (map[types.Provider]OverloadState, error) {
+	return s, nil
+}
+
+func TestProviderBackoff_Windows(t *testing.T) {
+	backoff := newProviderBackoff()
+	now := time.Date(2024, 5, 1, 12, 0, 0, 0, time.UTC)
+
+	// 未启用时不进入退避
+	if until := backoff.Record(types.ProviderAnthropic, 0, now); !until.IsZero() {
+		t.Fatalf("Record() while disabled = %v, want zero", until)
+	}
+
+	backoff.Configure(&types.OverloadBackoffConfig{Enabled: true, BaseSeconds: 4, MaxSeconds: 10})
+	backoff.jitter = func() float64 { return 1 } // 取窗口全长
+
+	// 每个窗口结束后再次过载时窗口翻倍：4s、8s，之后不超过上限10s
+	at := now
+	for i, want := range []time.Duration{4 * time.Second, 8 * time.Second, 10 * time.Second, 10 * time.Second} {
+		until := backoff.Record(types.ProviderAnthropic, 0, at)
+		if until != at.Add(want) {
+			t.Errorf("strike %d: until = %v, want %v", i+1, until.Sub(at), want)
+		}
+		at = until
+	}
+	if _, ok := backoff.Until(types.ProviderAnthropic, at.Add(-time.Second)); !ok {
+		t.Error("Until() = false inside the window")
+	}
+	if _, ok := backoff.Until(types.ProviderOpenAI, now); ok {
+		t.Error("other providers should not be backing off")
+	}
+
+	// 成功后从 base 重新开始；窗口内只有更长的 Retry-After 能延长窗口
+	backoff.Clear(types.ProviderAnthropic, at)
+	backoff.jitter = func() float64 { return 0 } // 取窗口的一半
+	later := at.Add(20 * time.Second)
+	if until := backoff.Record(types.ProviderAnthropic, 0, later); until != later.Add(2*time.Second) {
+		t.Errorf("after Clear: until = %v, want 2s", until.Sub(later))
+	}
+	if until := backoff.Record(types.ProviderAnthropic, time.Second, later); until != later.Add(2*time.Second) {
+		t.Errorf("with a shorter Retry-After: until = %v, want the 2s window kept", until.Sub(later))
+	}
+	if until := backoff.Record(types.ProviderAnthropic, 30*time.Second, later); until != later.Add(30*time.Second) {
+		t.Errorf("with Retry-After: until = %v, want 30s", until.Sub(later))
+	}
+	if _, ok := backoff.Until(types.ProviderAnthropic, later.Add(31*time.Second)); ok {
+		t.Error("Until() = true after the window ended")
+	}
+}
+
+func TestProviderBackoff_ConcurrentRecords(t *testing.T) {
+	backoff := newProviderBackoff()
+	backoff.Configure(&types.OverloadBackoffConfig{Enabled: true, BaseSeconds: 4, MaxSeconds: 60})
+	backoff.jitter = func() float64 { return 1 }
+	now := time.Date(2024, 5, 1, 12, 0, 0, 0, time.UTC)
+
+	// 同一次过载中并发请求收到的529只算一次，窗口不会直接升到上限
+	var wg sync.WaitGroup
+	for i := 0; i < 20; i++ {
+		wg.Add(1)
+		go func(i int) {
+			defer wg.Done()
+			backoff.Record(types.ProviderAnthropic, 0, now.Add(time.Duration(i)*time.Millisecond))
+		}(i)
+	}
+	wg.Wait()
+
+	until, ok := backoff.Until(types.ProviderAnthropic, now)
+	if !ok || until.Sub(now) > 4*time.Second+20*time.Millisecond {
+		t.Errorf("until = %v after one overload event, want about the 4s base window", until.Sub(now))
+	}
+	if strikes := backoff.states[types.ProviderAnthropic].Strikes; strikes != 1 {
+		t.Errorf("strikes = %d, want 1", strikes)
+	}
+}
+
+func TestProviderBackoff_Shared(t *testing.T) {
+	store := memoryOverloadStore{}
+	config := &types.OverloadBackoffConfig{Enabled: true}
+	first, second := newProviderBackoff(), newProviderBackoff()
+	first.Configure(config)
+	second.Configure(config)
+	first.SetStore(store)
+	second.SetStore(store)
+
+	now := time.Now()
+	until := first.Record(types.ProviderAnthropic, 0, now)
+	if err := second.Sync(); err != nil {
+		t.Fatalf("Sync() error = %v", err)
+	}
+	if got, ok := second.Until(types.ProviderAnthropic, now); !ok || !got.Equal(until) {
+		t.Errorf("second instance Until() = %v, %v, want %v", got, ok, until)
+	}
+	if active := second.Active(now); len(active) != 1 {
+		t.Errorf("Active() = %v, want anthropic only", active)
+	}
+
+	// 较旧的共享状态不覆盖本实例更新的状态
+	second.Clear(types.ProviderAnthropic, now.Add(time.Second))
+	store[types.ProviderAnthropic] = OverloadState{Until: now.Add(time.Hour), Strikes: 9, UpdatedAt: now}
+	_ = second.Sync()
+	if got, _ := second.Until(types.ProviderAnthropic, now); got.Equal(now.Add(time.Hour)) {
+		t.Error("stale shared state overwrote a newer local state")
+	}
+}
@@ -0,0 +1,281 @@
+package upstream
+
+import (
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// anthropicBetaFlags Claude Code必需的beta标识
+const anthropicBetaFlags = "claude-code-20250219,oauth-2025-04-20,interleaved-thinking-2025-05-14,fine-grained-tool-streaming-2025-05-14"
+
+// ProviderSpec 提供商实现：默认BaseURL与认证头部构建
+type ProviderSpec struct {
+	Provider       types.Provider
+	DefaultBaseURL string
+
+	// APIKeyHeaders 构建API Key账号的认证头部
+	APIKeyHeaders func(account *types.UpstreamAccount) map[string]string
+
+	// OAuthHeaders 构建OAuth账号在Bearer之外的附加头部（可选）
+	OAuthHeaders func(account *types.UpstreamAccount) map[string]string
+
+	// HealthPath 健康探测使用的轻量GET接口（相对BaseURL，通常为模型列表），为空时只检查凭证
+	HealthPath string
+
+	// ParseModels 从探测成功的响应体中解析后端提供的模型（如自托管的 OpenAI 兼容后端），为空时不发现模型
+	ParseModels func(body []byte) ([]string, error)
+
+	// VersionHeader/VersionQuery 账号固定API版本（api_version）时设置的请求头或查询参数，都为空表示不支持固定版本
+	VersionHeader string
+	VersionQuery  string
+
+	// DefaultVersion 通过查询参数传递版本的提供商在账号没有固定版本时使用的版本
+	DefaultVersion string
+
+	// RequestPath 把转换器给出的上游路径改写为提供商的实际路径（如Azure的部署路径），为空时原样使用
+	RequestPath func(account *types.UpstreamAccount, path string, request *types.UnifiedRequest) string
+
+	// RequestBody 调整转换后的请求体以符合提供商的要求（如Bedrock去掉model字段），为空时原样使用
+	RequestBody func(body []byte) ([]byte, error)
+
+	// SignRequest 对完整的请求签名（如Bedrock的SigV4），在设置完所有请求头后调用，为空时不签名
+	SignRequest func(account *types.UpstreamAccount, req *http.Request, body []byte, now time.Time) error
+
+	// StreamBody 把提供商自己的流式编码转换为SSE（如Bedrock的event stream），为空表示上游直接返回SSE
+	StreamBody func(body io.ReadCloser) io.ReadCloser
+}
+
+// ProviderStatus 提供商状态
+type ProviderStatus struct {
+	Provider       types.Provider `json:"provider"`
+	Enabled        bool           `json:"enabled"`
+	DefaultBaseURL string         `json:"default_base_url"`
+}
+
+// ProviderRegistry 线程安全的提供商注册表，支持运行时注册新提供商和启用/禁用
+type ProviderRegistry struct {
+	mutex    sync.RWMutex
+	specs    map[types.Provider]*ProviderSpec
+	disabled map[types.Provider]bool
+}
+
+// NewProviderRegistry 创建注册表并注册内置提供商
+func NewProviderRegistry() *ProviderRegistry {
+	registry := &ProviderRegistry{
+		specs:    make(map[types.Provider]*ProviderSpec),
+		disabled: make(map[types.Provider]bool),
+	}
+	for _, spec := range builtinProviderSpecs() {
+		_ = registry.Register(spec)
+	}
+	return registry
+}
+
+// Register 注册（或替换）提供商实现
+func (r *ProviderRegistry) Register(spec *ProviderSpec) error {
+	if spec == nil || spec.Provider == "" {
+		return fmt.Errorf("提供商名称不能为空")
+	}
+	if spec.APIKeyHeaders == nil {
+		return fmt.Errorf("提供商 %s 缺少认证头部构建函数", spec.Provider)
+	}
+
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	r.specs[spec.Provider] = spec
+	return nil
+}
+
+// Get 获取提供商实现
+func (r *ProviderRegistry) Get(provider types.Provider) (*ProviderSpec, bool) {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+	spec, ok := r.specs[provider]
+	return spec, ok
+}
+
+// SetEnabled 启用或禁用提供商
+func (r *ProviderRegistry) SetEnabled(provider types.Provider, enabled bool) error {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	if _, ok := r.specs[provider]; !ok {
+		return fmt.Errorf("未注册的提供商: %s", provider)
+	}
+	if enabled {
+		delete(r.disabled, provider)
+	} else {
+		r.disabled[provider] = true
+	}
+	return nil
+}
+
+// IsEnabled 检查提供商是否已注册且启用
+func (r *ProviderRegistry) IsEnabled(provider types.Provider) bool {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+	_, registered := r.specs[provider]
+	return registered && !r.disabled[provider]
+}
+
+// ApplySettings 应用配置文件中的提供商设置
+func (r *ProviderRegistry) ApplySettings(settings map[types.Provider]types.ProviderSettings) {
+	for provider, setting := range settings {
+		if setting.Enabled != nil {
+			_ = r.SetEnabled(provider, *setting.Enabled)
+		}
+	}
+}
+
+// List 列出所有已注册提供商的状态（按名称排序）
+func (r *ProviderRegistry) List() []ProviderStatus {
+	r.mutex.RLock()
+	defer r.mutex.RUnlock()
+
+	statuses := make([]ProviderStatus, 0, len(r.specs))
+	for provider, spec := range r.specs {
+		statuses = append(statuses, ProviderStatus{
+			Provider:       provider,
+			Enabled:        !r.disabled[provider],
+			DefaultBaseURL: spec.DefaultBaseURL,
+		})
+	}
+	sort.Slice(statuses, func(i, j int) bool {
+		return statuses[i].Provider < statuses[j].Provider
+	})
+	return statuses
+}
+
+// bearerHeaders 通用的Bearer认证头部
+func bearerHeaders(account *types.UpstreamAccount) map[string]string {
+	return map[string]string{
+		"Authorization": "Bearer " + account.APIKey,
+	}
+}
+
+// optionalBearerHeaders 配置了API Key时使用Bearer认证，本地后端（如Ollama）通常不需要认证
+func optionalBearerHeaders(account *types.UpstreamAccount) map[string]string {
+	if account.APIKey == "" {
+		return map[string]string{}
+	}
+	return bearerHeaders(account)
+}
+
+// parseOpenAIModels 解析 OpenAI 格式的模型列表 {"data": [{"id": ...}]}
+func parseOpenAIModels(body []byte) ([]string, error) {
+	var list struct {
+		Data []struct {
+			ID string `json:"id"`
+		} `json:"data"`
+	}
+	if err := json.Unmarshal(body, &list); err != nil {
+		return nil, fmt.Errorf("解析模型列表失败: %w", err)
+	}
+
+	models := make([]string, 0, len(list.Data))
+	for _, model := range list.Data {
+		if model.ID != "" {
+			models = append(models, model.ID)
+		}
+	}
+	sort.Strings(models)
+	return models, nil
+}
+
+// azureRequestPath 把 OpenAI 路径改写为 Azure OpenAI 的部署路径：
+// /v1/chat/completions -> /openai/deployments/{部署名}/chat/completions
+func azureRequestPath(account *types.UpstreamAccount, path string, request *types.UnifiedRequest) string {
+	return "/openai/deployments/" + url.PathEscape(account.Deployment(request.Model)) + strings.TrimPrefix(path, "/v1")
+}
+
+// builtinProviderSpecs 内置提供商实现
+func builtinProviderSpecs() []*ProviderSpec {
+	return []*ProviderSpec{
+		{
+			Provider:       types.ProviderAnthropic,
+			DefaultBaseURL: "https://api.anthropic.com",
+			HealthPath:     "/v1/models",
+			VersionHeader:  "anthropic-version",
+			APIKeyHeaders: func(account *types.UpstreamAccount) map[string]string {
+				return map[string]string{
+					"x-api-key":         account.APIKey,
+					"anthropic-version": "2023-06-01",
+					"anthropic-beta":    anthropicBetaFlags,
+				}
+			},
+			OAuthHeaders: func(account *types.UpstreamAccount) map[string]string {
+				return map[string]string{
+					"anthropic-version": "2023-06-01",
+					"anthropic-beta":    anthropicBetaFlags,
+				}
+			},
+		},
+		{
+			Provider:       types.ProviderOpenAI,
+			DefaultBaseURL: "https://api.openai.com",
+			APIKeyHeaders:  bearerHeaders,
+			HealthPath:     "/v1/models",
+		},
+		{
+			Provider:       types.ProviderGoogle,
+			DefaultBaseURL: "https://generativelanguage.googleapis.com",
+			HealthPath:     "/v1beta/models",
+			APIKeyHeaders: func(account *types.UpstreamAccount) map[string]string {
+				return map[string]string{
+					"x-goog-api-key": account.APIKey,
+				}
+			},
+		},
+		{
+			Provider:       types.ProviderAzure,
+			DefaultBaseURL: "https://your-resource.openai.azure.com", // 需要配置
+			VersionQuery:   "api-version",
+			DefaultVersion: "2024-10-21",
+			RequestPath:    azureRequestPath,
+			APIKeyHeaders: func(account *types.UpstreamAccount) map[string]string {
+				return map[string]string{
+					"api-key": account.APIKey,
+				}
+			},
+		},
+		{
+			Provider:       types.ProviderBedrock,
+			DefaultBaseURL: "https://bedrock-runtime.{region}.amazonaws.com", // {region} 取账号的AWS区域
+			RequestPath:    bedrockRequestPath,
+			RequestBody:    bedrockRequestBody,
+			SignRequest:    signBedrockRequest,
+			StreamBody:     newEventStreamReader,
+			APIKeyHeaders: func(account *types.UpstreamAccount) map[string]string {
+				return map[string]string{} // 认证由 SigV4 签名完成
+			},
+		},
+		{
+			Provider:       types.ProviderOpenAICompatible,
+			DefaultBaseURL: "", // 必须在账号上配置 base_url（不含 /v1）
+			APIKeyHeaders:  optionalBearerHeaders,
+			HealthPath:     "/v1/models",
+			ParseModels:    parseOpenAIModels,
+		},
+		{
+			Provider:       types.ProviderQwen,
+			DefaultBaseURL: "https://dashscope.aliyuncs.com/compatible-mode/v1",
+			APIKeyHeaders:  bearerHeaders,
+			HealthPath:     "/models", // BaseURL已包含/v1
+			OAuthHeaders: func(account *types.UpstreamAccount) map[string]string {
+				return map[string]string{
+					"X-DashScope-CacheControl": "enable",
+					"X-DashScope-UserAgent":    "LLM-Gateway/1.0",
+				}
+			},
+		},
+	}
+}
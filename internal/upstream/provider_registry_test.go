@@ -0,0 +1,142 @@
+package upstream
+
+import (
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestProviderRegistry_EnableDisable(t *testing.T) {
+	registry := NewProviderRegistry()
+
+	if !registry.IsEnabled(types.ProviderAnthropic) {
+		t.Fatal("builtin provider should be enabled by default")
+	}
+
+	if err := registry.SetEnabled(types.ProviderAnthropic, false); err != nil {
+		t.Fatalf("SetEnabled() error = %v", err)
+	}
+	if registry.IsEnabled(types.ProviderAnthropic) {
+		t.Error("provider should be disabled")
+	}
+
+	if err := registry.SetEnabled("unknown", false); err == nil {
+		t.Error("SetEnabled() should fail for unregistered provider")
+	}
+
+	enabled := true
+	registry.ApplySettings(map[types.Provider]types.ProviderSettings{
+		types.ProviderAnthropic: {Enabled: &enabled},
+	})
+	if !registry.IsEnabled(types.ProviderAnthropic) {
+		t.Error("ApplySettings() should re-enable provider")
+	}
+}
+
+func TestProviderRegistry_RegisterCustomProvider(t *testing.T) {
+	configMgr := NewMockUpstreamConfigManager()
+	mgr := NewUpstreamManager(configMgr)
+
+	err := mgr.Providers().Register(&ProviderSpec{
+		Provider:       "deepseek",
+		DefaultBaseURL: "https://api.deepseek.com",
+		APIKeyHeaders: func(account *types.UpstreamAccount) map[string]string {
+			return map[string]string{"Authorization": "Bearer " + account.APIKey, "X-Custom": "1"}
+		},
+	})
+	if err != nil {
+		t.Fatalf("Register() error = %v", err)
+	}
+
+	account := &types.UpstreamAccount{
+		Name:     "deepseek",
+		Type:     types.UpstreamTypeAPIKey,
+		Provider: "deepseek",
+		APIKey:   "sk-test",
+	}
+	if err := mgr.AddAccount(account); err != nil {
+		t.Fatalf("AddAccount() error = %v", err)
+	}
+
+	headers, err := mgr.GetAuthHeaders(account.ID)
+	if err != nil {
+		t.Fatalf("GetAuthHeaders() error = %v", err)
+	}
+	if headers["X-Custom"] != "1" || headers["Authorization"] != "Bearer sk-test" {
+		t.Errorf("unexpected headers: %v", headers)
+	}
+	if baseURL := mgr.GetBaseURL(account); baseURL != "https://api.deepseek.com" {
+		t.Errorf("GetBaseURL() = %s", baseURL)
+	}
+}
+
+func TestUpstreamManager_APIVersionPinning(t *testing.T) {
+	mgr := NewUpstreamManager(NewMockUpstreamConfigManager())
+
+	anthropic := &types.UpstreamAccount{
+		Name:       "anthropic-pinned",
+		Type:       types.UpstreamTypeAPIKey,
+		Provider:   types.ProviderAnthropic,
+		APIKey:     "sk-ant-test",
+		APIVersion: "2024-10-01",
+	}
+	if err := mgr.AddAccount(anthropic); err != nil {
+		t.Fatalf("AddAccount() error = %v", err)
+	}
+	headers, err := mgr.GetAuthHeaders(anthropic.ID)
+	if err != nil {
+		t.Fatalf("GetAuthHeaders() error = %v", err)
+	}
+	if headers["anthropic-version"] != "2024-10-01" {
+		t.Errorf("anthropic-version = %q, want pinned 2024-10-01", headers["anthropic-version"])
+	}
+	if headers := mgr.APIKeyHeaders(&types.UpstreamAccount{Provider: types.ProviderAnthropic}); headers["anthropic-version"] != "2023-06-01" {
+		t.Errorf("unpinned anthropic-version = %q, want default", headers["anthropic-version"])
+	}
+
+	azure := &types.UpstreamAccount{Provider: types.ProviderAzure, APIVersion: "2024-10-21"}
+	got := mgr.VersionedURL(azure, "https://example.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-02-01")
+	if want := "https://example.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"; got != want {
+		t.Errorf("VersionedURL() = %s, want %s", got, want)
+	}
+	if url := "https://api.anthropic.com/v1/messages"; mgr.VersionedURL(anthropic, url) != url {
+		t.Error("VersionedURL() should not change providers that pin the version by header")
+	}
+
+	if !mgr.SupportsAPIVersion(types.ProviderAnthropic) || !mgr.SupportsAPIVersion(types.ProviderAzure) {
+		t.Error("anthropic and azure should support version pinning")
+	}
+	if mgr.SupportsAPIVersion(types.ProviderGoogle) {
+		t.Error("google should not support version pinning")
+	}
+}
+
+func TestUpstreamManager_AzureDeploymentURL(t *testing.T) {
+	mgr := NewUpstreamManager(NewMockUpstreamConfigManager())
+	azure := &types.UpstreamAccount{
+		Provider:    types.ProviderAzure,
+		APIKey:      "azure-key",
+		BaseURL:     "https://example.openai.azure.com",
+		Deployments: map[string]string{"gpt-4o": "prod-gpt4o"},
+	}
+
+	// 映射的模型使用部署名，未固定版本时使用默认 api-version
+	got := mgr.UpstreamURL(azure, "/v1/chat/completions", &types.UnifiedRequest{Model: "gpt-4o"})
+	if want := "https://example.openai.azure.com/openai/deployments/prod-gpt4o/chat/completions?api-version=2024-10-21"; got != want {
+		t.Errorf("UpstreamURL() = %s, want %s", got, want)
+	}
+	azure.APIVersion = "2025-01-01-preview"
+	got = mgr.UpstreamURL(azure, "/v1/chat/completions", &types.UnifiedRequest{Model: "gpt-4o-mini"})
+	if want := "https://example.openai.azure.com/openai/deployments/gpt-4o-mini/chat/completions?api-version=2025-01-01-preview"; got != want {
+		t.Errorf("UpstreamURL() = %s, want %s", got, want)
+	}
+
+	if headers := mgr.APIKeyHeaders(azure); headers["api-key"] != "azure-key" || headers["Authorization"] != "" {
+		t.Errorf("azure headers = %v, want only api-key", headers)
+	}
+
+	openai := &types.UpstreamAccount{Provider: types.ProviderOpenAI}
+	if got := mgr.UpstreamURL(openai, "/v1/chat/completions", &types.UnifiedRequest{Model: "gpt-4o"}); got != "https://api.openai.com/v1/chat/completions" {
+		t.Errorf("UpstreamURL(openai) = %s", got)
+	}
+}
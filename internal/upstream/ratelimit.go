@@ -0,0 +1,190 @@
+package upstream
+
+import (
+	"net/http"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+const (
+	rateLimitLowWatermark = 0.05             // 剩余额度低于上限的这个比例时视为接近耗尽
+	rateLimitStaleAfter   = time.Minute      // 没有重置时间的记录在多久之后失效
+	rateLimitMaxRetry     = 10 * time.Minute // 429 的 Retry-After 最多按多久计算
+)
+
+// RateLimitState 上游在响应头中报告的账号剩余额度，-1 表示上游没有报告该项
+type RateLimitState struct {
+	RequestsLimit     int64     `json:"requests_limit"`
+	RequestsRemaining int64     `json:"requests_remaining"`
+	TokensLimit       int64     `json:"tokens_limit"`
+	TokensRemaining   int64     `json:"tokens_remaining"`
+	ResetAt           time.Time `json:"reset_at,omitempty"`  // 最早恢复额度的时间，未知时为零值
+	Throttled         bool      `json:"throttled,omitempty"` // 上游返回了429
+	UpdatedAt         time.Time `json:"updated_at"`
+}
+
+// Exhausted 判断额度是否接近耗尽：上游返回了429，剩余请求数或token数为0，或低于上限的5%
+func (s *RateLimitState) Exhausted() bool {
+	return s.Throttled || lowRemaining(s.RequestsRemaining, s.RequestsLimit) || lowRemaining(s.TokensRemaining, s.TokensLimit)
+}
+
+// expired 判断记录是否已经过时：已经到了重置时间，或没有重置时间且长时间没有更新
+func (s *RateLimitState) expired(now time.Time) bool {
+	if !s.ResetAt.IsZero() {
+		return !now.Before(s.ResetAt)
+	}
+	return now.Sub(s.UpdatedAt) > rateLimitStaleAfter
+}
+
+// lowRemaining 判断一项剩余额度是否接近耗尽，上游没有报告时为false
+func lowRemaining(remaining, limit int64) bool {
+	if remaining < 0 {
+		return false
+	}
+	if remaining == 0 {
+		return true
+	}
+	return limit > 0 && float64(remaining) < float64(limit)*rateLimitLowWatermark
+}
+
+// RateLimits 记录每个上游账号最近一次响应报告的剩余额度，路由据此提前避开快要被限流的账号，
+// 而不是等到收到429再切换。状态只在内存中
+type RateLimits struct {
+	states map[string]*RateLimitState
+	mutex  sync.Mutex
+}
+
+// newRateLimits 创建空的额度记录
+func newRateLimits() *RateLimits {
+	return &RateLimits{states: make(map[string]*RateLimitState)}
+}
+
+// Record 从上游响应头更新账号的剩余额度，响应头中没有额度信息时保持原有记录。
+// 429 响应总是记为额度耗尽，有 Retry-After 时以它作为恢复时间
+func (r *RateLimits) Record(upstreamID string, statusCode int, header http.Header, now time.Time) {
+	state, ok := ParseRateLimitHeaders(header, now)
+	if statusCode == http.StatusTooManyRequests {
+		if !ok {
+			state = &RateLimitState{RequestsLimit: -1, RequestsRemaining: -1, TokensLimit: -1, TokensRemaining: -1, UpdatedAt: now}
+		}
+		state.Throttled = true
+		if wait, ok := parseRetryAfter(header.Get("Retry-After")); ok {
+			state.ResetAt = now.Add(wait)
+		}
+	} else if !ok {
+		return
+	}
+
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+	r.states[upstreamID] = state
+}
+
+// Exhausted 判断账号当前是否接近额度耗尽，没有记录或记录已过时返回false
+func (r *RateLimits) Exhausted(upstreamID string, now time.Time) bool {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	state := r.states[upstreamID]
+	return state != nil && !state.expired(now) && state.Exhausted()
+}
+
+// Get 返回账号最近一次报告的剩余额度，没有记录或记录已过时返回nil
+func (r *RateLimits) Get(upstreamID string, now time.Time) *RateLimitState {
+	r.mutex.Lock()
+	defer r.mutex.Unlock()
+
+	state := r.states[upstreamID]
+	if state == nil || state.expired(now) {
+		return nil
+	}
+	copied := *state
+	return &copied
+}
+
+// ParseRateLimitHeaders 解析上游的限流响应头：Anthropic 的 anthropic-ratelimit-*（重置时间为RFC3339），
+// OpenAI 的 x-ratelimit-*（重置时间为 "6m0s" 这样的时长）。没有任何额度信息时返回false
+func ParseRateLimitHeaders(header http.Header, now time.Time) (*RateLimitState, bool) {
+	state := &RateLimitState{
+		RequestsLimit:     headerInt(header, "anthropic-ratelimit-requests-limit", "x-ratelimit-limit-requests"),
+		RequestsRemaining: headerInt(header, "anthropic-ratelimit-requests-remaining", "x-ratelimit-remaining-requests"),
+		TokensLimit:       headerInt(header, "anthropic-ratelimit-tokens-limit", "x-ratelimit-limit-tokens"),
+		TokensRemaining:   headerInt(header, "anthropic-ratelimit-tokens-remaining", "x-ratelimit-remaining-tokens"),
+		UpdatedAt:         now,
+	}
+	if state.RequestsRemaining < 0 && state.TokensRemaining < 0 {
+		return nil, false
+	}
+
+	// 只关心耗尽的那一项什么时候恢复；都没有耗尽时取较早的重置时间
+	var resets []time.Time
+	for _, item := range []struct {
+		remaining, limit  int64
+		anthropic, openai string
+	}{
+		{state.RequestsRemaining, state.RequestsLimit, "anthropic-ratelimit-requests-reset", "x-ratelimit-reset-requests"},
+		{state.TokensRemaining, state.TokensLimit, "anthropic-ratelimit-tokens-reset", "x-ratelimit-reset-tokens"},
+	} {
+		reset, ok := parseReset(header, item.anthropic, item.openai, now)
+		if !ok {
+			continue
+		}
+		if lowRemaining(item.remaining, item.limit) {
+			if reset.After(state.ResetAt) {
+				state.ResetAt = reset
+			}
+			continue
+		}
+		resets = append(resets, reset)
+	}
+	if state.ResetAt.IsZero() {
+		for _, reset := range resets {
+			if state.ResetAt.IsZero() || reset.Before(state.ResetAt) {
+				state.ResetAt = reset
+			}
+		}
+	}
+	return state, true
+}
+
+// headerInt 读取第一个存在的整数响应头，都不存在或无效时返回-1
+func headerInt(header http.Header, names ...string) int64 {
+	for _, name := range names {
+		if value := strings.TrimSpace(header.Get(name)); value != "" {
+			if n, err := strconv.ParseInt(value, 10, 64); err == nil && n >= 0 {
+				return n
+			}
+		}
+	}
+	return -1
+}
+
+// parseReset 读取额度重置时间：Anthropic 为RFC3339时间，OpenAI 为相对时长
+func parseReset(header http.Header, anthropicName, openaiName string, now time.Time) (time.Time, bool) {
+	if value := header.Get(anthropicName); value != "" {
+		if reset, err := time.Parse(time.RFC3339, value); err == nil {
+			return reset, true
+		}
+	}
+	if value := header.Get(openaiName); value != "" {
+		if wait, err := time.ParseDuration(value); err == nil && wait >= 0 {
+			return now.Add(wait), true
+		}
+	}
+	return time.Time{}, false
+}
+
+// parseRetryAfter 解析以秒为单位的 Retry-After，超过上限时按上限计算
+func parseRetryAfter(value string) (time.Duration, bool) {
+	seconds, err := strconv.Atoi(strings.TrimSpace(value))
+	if err != nil || seconds < 0 {
+		return 0, false
+	}
+	wait := time.Duration(seconds) * time.Second
+	if wait > rateLimitMaxRetry {
+		wait = rateLimitMaxRetry
+	}
+	return wait, true
+}
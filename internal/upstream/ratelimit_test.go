@@ -0,0 +1,82 @@
+package upstream
+
+import (
+	"net/http"
+	"testing"
+	"time"
+)
+
+func TestParseRateLimitHeaders(t *testing.T) {
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+
+	anthropic := http.Header{}
+	anthropic.Set("anthropic-ratelimit-requests-limit", "1000")
+	anthropic.Set("anthropic-ratelimit-requests-remaining", "999")
+	anthropic.Set("anthropic-ratelimit-requests-reset", "2024-03-01T12:00:30Z")
+	anthropic.Set("anthropic-ratelimit-tokens-limit", "100000")
+	anthropic.Set("anthropic-ratelimit-tokens-remaining", "2000")
+	anthropic.Set("anthropic-ratelimit-tokens-reset", "2024-03-01T12:00:45Z")
+
+	state, ok := ParseRateLimitHeaders(anthropic, now)
+	if !ok || state.RequestsRemaining != 999 || state.TokensLimit != 100000 {
+		t.Fatalf("ParseRateLimitHeaders(anthropic) = %+v, %v", state, ok)
+	}
+	// token额度低于5%，恢复时间取token的重置时间
+	if !state.Exhausted() || !state.ResetAt.Equal(now.Add(45*time.Second)) {
+		t.Errorf("anthropic state = %+v, want exhausted until tokens reset", state)
+	}
+
+	openai := http.Header{}
+	openai.Set("x-ratelimit-limit-requests", "500")
+	openai.Set("x-ratelimit-remaining-requests", "420")
+	openai.Set("x-ratelimit-reset-requests", "6m0s")
+	openai.Set("x-ratelimit-remaining-tokens", "90000")
+	openai.Set("x-ratelimit-reset-tokens", "20ms")
+
+	state, ok = ParseRateLimitHeaders(openai, now)
+	if !ok || state.TokensLimit != -1 || state.Exhausted() {
+		t.Fatalf("ParseRateLimitHeaders(openai) = %+v, %v", state, ok)
+	}
+	if !state.ResetAt.Equal(now.Add(20 * time.Millisecond)) {
+		t.Errorf("ResetAt = %v, want the earliest reset", state.ResetAt)
+	}
+
+	if _, ok := ParseRateLimitHeaders(http.Header{}, now); ok {
+		t.Error("ParseRateLimitHeaders() should report no state without rate limit headers")
+	}
+}
+
+func TestRateLimits_Exhausted(t *testing.T) {
+	limits := newRateLimits()
+	now := time.Date(2024, 3, 1, 12, 0, 0, 0, time.UTC)
+
+	header := http.Header{}
+	header.Set("x-ratelimit-limit-requests", "100")
+	header.Set("x-ratelimit-remaining-requests", "0")
+	header.Set("x-ratelimit-reset-requests", "10s")
+	limits.Record("up-1", http.StatusOK, header, now)
+	if !limits.Exhausted("up-1", now) || limits.Exhausted("up-1", now.Add(10*time.Second)) {
+		t.Error("up-1 should be exhausted until the reported reset")
+	}
+
+	// 没有额度头的响应不覆盖原有记录
+	limits.Record("up-1", http.StatusOK, http.Header{}, now)
+	if !limits.Exhausted("up-1", now) {
+		t.Error("a response without rate limit headers should keep the previous state")
+	}
+
+	// 429 总是记为耗尽，按 Retry-After 恢复
+	retry := http.Header{}
+	retry.Set("Retry-After", "5")
+	limits.Record("up-2", http.StatusTooManyRequests, retry, now)
+	if !limits.Exhausted("up-2", now.Add(4*time.Second)) || limits.Exhausted("up-2", now.Add(5*time.Second)) {
+		t.Error("up-2 should be exhausted until Retry-After")
+	}
+	if state := limits.Get("up-2", now); state == nil || !state.Throttled {
+		t.Errorf("Get(up-2) = %+v, want throttled", state)
+	}
+
+	if limits.Exhausted("unknown", now) || limits.Get("unknown", now) != nil {
+		t.Error("accounts without reports should not be exhausted")
+	}
+}
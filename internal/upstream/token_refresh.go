@@ -0,0 +1,130 @@
+package upstream
+
+import (
+	"sync"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/logger"
+)
+
+const (
+	// requestRefreshLeadTime 请求路径上提前刷新token的时间
+	requestRefreshLeadTime = 5 * time.Minute
+
+	// backgroundRefreshLeadTime 后台任务提前刷新token的时间，大于请求路径的提前量，
+	// 使token通常在请求需要之前就已刷新
+	backgroundRefreshLeadTime = 10 * time.Minute
+
+	// tokenRefreshRetryBackoff 刷新失败后等待多久再重试同一账号
+	tokenRefreshRetryBackoff = 5 * time.Minute
+)
+
+// TokenRefreshService 后台定期刷新即将过期的OAuth token，新token由OAuthManager持久化到配置
+type TokenRefreshService struct {
+	upstreamMgr *UpstreamManager
+	refresh     func(upstreamID string) error
+	interval    time.Duration
+
+	retryAfter map[string]time.Time // 刷新失败的账号在此时间之前不再重试
+	stopCh     chan struct{}
+	mutex      sync.Mutex
+}
+
+// NewTokenRefreshService 创建OAuth token后台刷新服务
+func NewTokenRefreshService(oauthMgr *OAuthManager, interval time.Duration) *TokenRefreshService {
+	if interval <= 0 {
+		interval = time.Minute
+	}
+	return &TokenRefreshService{
+		upstreamMgr: oauthMgr.upstreamMgr,
+		refresh:     oauthMgr.RefreshToken,
+		interval:    interval,
+		retryAfter:  make(map[string]time.Time),
+	}
+}
+
+// Start 启动后台刷新，启动时立即检查一次
+func (s *TokenRefreshService) Start() {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		return
+	}
+	s.stopCh = make(chan struct{})
+
+	go func(stopCh chan struct{}) {
+		ticker := time.NewTicker(s.interval)
+		defer ticker.Stop()
+
+		s.RefreshDue(time.Now())
+		for {
+			select {
+			case <-ticker.C:
+				s.RefreshDue(time.Now())
+			case <-stopCh:
+				return
+			}
+		}
+	}(s.stopCh)
+}
+
+// Stop 停止后台刷新
+func (s *TokenRefreshService) Stop() {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if s.stopCh != nil {
+		close(s.stopCh)
+		s.stopCh = nil
+	}
+}
+
+// RefreshDue 刷新所有即将过期的OAuth token，返回成功刷新的账号ID
+func (s *TokenRefreshService) RefreshDue(now time.Time) []string {
+	var refreshed []string
+	for _, account := range s.upstreamMgr.ListAccounts() {
+		if account.Status == "disabled" || !NeedsTokenRefresh(account, now, backgroundRefreshLeadTime) {
+			continue
+		}
+		if !s.shouldAttempt(account.ID, now) {
+			continue
+		}
+
+		if err := s.upstreamMgr.RefreshOAuthToken(account.ID, backgroundRefreshLeadTime, s.refresh); err != nil {
+			s.recordFailure(account.ID, now)
+			logger.Warn("后台刷新OAuth token失败: %s (%s, %s): %v", account.ID, account.Name, account.Provider, err)
+			continue
+		}
+
+		s.recordSuccess(account.ID)
+		refreshed = append(refreshed, account.ID)
+		logger.Info("后台刷新OAuth token成功: %s (%s)", account.ID, account.Name)
+	}
+	return refreshed
+}
+
+// shouldAttempt 检查账号是否处于失败退避期
+func (s *TokenRefreshService) shouldAttempt(upstreamID string, now time.Time) bool {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	retryAt, exists := s.retryAfter[upstreamID]
+	return !exists || !now.Before(retryAt)
+}
+
+// recordFailure 记录刷新失败，退避一段时间后再重试
+func (s *TokenRefreshService) recordFailure(upstreamID string, now time.Time) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	s.retryAfter[upstreamID] = now.Add(tokenRefreshRetryBackoff)
+}
+
+// recordSuccess 清除账号的失败退避
+func (s *TokenRefreshService) recordSuccess(upstreamID string) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	delete(s.retryAfter, upstreamID)
+}
@@ -0,0 +1,127 @@
+package upstream
+
+import (
+	"fmt"
+	"sort"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestNeedsTokenRefresh(t *testing.T) {
+	now := time.Now()
+	soon := now.Add(2 * time.Minute)
+	later := now.Add(time.Hour)
+
+	tests := []struct {
+		name    string
+		account *types.UpstreamAccount
+		want    bool
+	}{
+		{"expiring soon", &types.UpstreamAccount{Type: types.UpstreamTypeOAuth, RefreshToken: "rt", ExpiresAt: &soon}, true},
+		{"no expiry", &types.UpstreamAccount{Type: types.UpstreamTypeOAuth, RefreshToken: "rt"}, true},
+		{"still valid", &types.UpstreamAccount{Type: types.UpstreamTypeOAuth, RefreshToken: "rt", ExpiresAt: &later}, false},
+		{"no refresh token", &types.UpstreamAccount{Type: types.UpstreamTypeOAuth, ExpiresAt: &soon}, false},
+		{"api key", &types.UpstreamAccount{Type: types.UpstreamTypeAPIKey, RefreshToken: "rt"}, false},
+	}
+
+	for _, tt := range tests {
+		if got := NeedsTokenRefresh(tt.account, now, 10*time.Minute); got != tt.want {
+			t.Errorf("%s: NeedsTokenRefresh() = %v, want %v", tt.name, got, tt.want)
+		}
+	}
+}
+
+func TestTokenRefreshService_RefreshDue(t *testing.T) {
+	now := time.Now()
+	soon := now.Add(3 * time.Minute)
+	later := now.Add(time.Hour)
+
+	configMgr := NewMockUpstreamConfigManager()
+	accounts := []*types.UpstreamAccount{
+		{ID: "due", Provider: types.ProviderAnthropic, Status: "active", ExpiresAt: &soon},
+		{ID: "fresh", Provider: types.ProviderAnthropic, Status: "active", ExpiresAt: &later},
+		{ID: "disabled", Provider: types.ProviderQwen, Status: "disabled", ExpiresAt: &soon},
+		{ID: "broken", Provider: types.ProviderGoogle, Status: "active", ExpiresAt: &soon},
+	}
+	for _, account := range accounts {
+		account.Type = types.UpstreamTypeOAuth
+		account.AccessToken = "old"
+		account.RefreshToken = "rt"
+		_ = configMgr.CreateUpstreamAccount(account)
+	}
+
+	upstreamMgr := NewUpstreamManager(configMgr)
+	service := NewTokenRefreshService(NewOAuthManager(upstreamMgr), 0)
+	attempts := make(map[string]int)
+	service.refresh = func(upstreamID string) error {
+		attempts[upstreamID]++
+		if upstreamID == "broken" {
+			return fmt.Errorf("不支持的OAuth provider")
+		}
+		return upstreamMgr.UpdateOAuthTokens(upstreamID, "new", "rt2", now.Add(time.Hour))
+	}
+
+	refreshed := service.RefreshDue(now)
+	sort.Strings(refreshed)
+	if len(refreshed) != 1 || refreshed[0] != "due" {
+		t.Fatalf("RefreshDue() = %v, want [due]", refreshed)
+	}
+
+	account, _ := configMgr.GetUpstreamAccount("due")
+	if account.AccessToken != "new" || account.RefreshToken != "rt2" {
+		t.Errorf("refreshed token not persisted: %+v", account)
+	}
+	if attempts["fresh"] != 0 || attempts["disabled"] != 0 {
+		t.Errorf("unexpected refresh attempts: %v", attempts)
+	}
+
+	// 失败的账号在退避期内不重试，退避结束后再次尝试
+	service.RefreshDue(now.Add(time.Minute))
+	if attempts["broken"] != 1 || attempts["due"] != 1 {
+		t.Errorf("attempts during backoff = %v", attempts)
+	}
+	service.RefreshDue(now.Add(tokenRefreshRetryBackoff))
+	if attempts["broken"] != 2 {
+		t.Errorf("broken account should be retried after backoff, attempts = %v", attempts)
+	}
+}
+
+func TestRefreshOAuthToken_SingleFlight(t *testing.T) {
+	configMgr := NewMockUpstreamConfigManager()
+	_ = configMgr.CreateUpstreamAccount(&types.UpstreamAccount{
+		ID:           "oauth",
+		Type:         types.UpstreamTypeOAuth,
+		Provider:     types.ProviderAnthropic,
+		Status:       "active",
+		AccessToken:  "old",
+		RefreshToken: "rt",
+	})
+	upstreamMgr := NewUpstreamManager(configMgr)
+
+	var calls int
+	refresh := func(upstreamID string) error {
+		calls++
+		time.Sleep(10 * time.Millisecond)
+		return upstreamMgr.UpdateOAuthTokens(upstreamID, "new", "rt2", time.Now().Add(time.Hour))
+	}
+
+	// 并发请求同时发现token过期时只刷新一次，其余请求等待后直接使用新token
+	var wg sync.WaitGroup
+	for i := 0; i < 5; i++ {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			if err := upstreamMgr.RefreshOAuthToken("oauth", requestRefreshLeadTime, refresh); err != nil {
+				t.Errorf("RefreshOAuthToken() error = %v", err)
+			}
+		}()
+	}
+	wg.Wait()
+
+	if calls != 1 {
+		t.Errorf("refresh called %d times, want 1", calls)
+	}
+}
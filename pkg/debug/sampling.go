@@ -0,0 +1,137 @@
+package debug
+
+import (
+	"context"
+	"math/rand"
+	"net/http"
+	"sort"
+	"strings"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+// DefaultTraceHeader 默认的强制采样请求头
+const DefaultTraceHeader = "X-Gateway-Trace"
+
+// routeRate 路径前缀的采样率
+type routeRate struct {
+	prefix string
+	rate   float64
+}
+
+// Sampler 按请求路径决定是否记录请求跟踪
+type Sampler struct {
+	defaultRate float64
+	routes      []routeRate // 按前缀长度从长到短排列
+	forceHeader string
+	random      func() float64
+}
+
+// NewSampler 根据配置创建采样器，config 为nil时所有请求都采样
+func NewSampler(config *types.TraceConfig) *Sampler {
+	sampler := &Sampler{
+		defaultRate: 1,
+		forceHeader: DefaultTraceHeader,
+		random:      rand.Float64,
+	}
+	if config == nil {
+		return sampler
+	}
+
+	if config.SampleRate != nil {
+		sampler.defaultRate = *config.SampleRate
+	}
+	if config.ForceHeader != "" {
+		sampler.forceHeader = config.ForceHeader
+	}
+	for prefix, rate := range config.Routes {
+		sampler.routes = append(sampler.routes, routeRate{prefix: prefix, rate: rate})
+	}
+	sort.Slice(sampler.routes, func(i, j int) bool {
+		return len(sampler.routes[i].prefix) > len(sampler.routes[j].prefix)
+	})
+	return sampler
+}
+
+// Rate 返回路径的采样率（最长匹配前缀优先）
+func (s *Sampler) Rate(path string) float64 {
+	for _, route := range s.routes {
+		if strings.HasPrefix(path, route.prefix) {
+			return route.rate
+		}
+	}
+	return s.defaultRate
+}
+
+// Sample 按路径的采样率随机决定请求是否采样。强制采样请求头不在这里处理：
+// 它要等请求通过认证、确认Key有权限后才生效（见 ForceSample）
+func (s *Sampler) Sample(r *http.Request) bool {
+	rate := s.Rate(r.URL.Path)
+	switch {
+	case rate >= 1:
+		return true
+	case rate <= 0:
+		return false
+	default:
+		return s.random() < rate
+	}
+}
+
+// ForceRequested 判断请求是否带有强制采样请求头（值不为 0/false）
+func (s *Sampler) ForceRequested(r *http.Request) bool {
+	value := r.Header.Get(s.forceHeader)
+	return value != "" && value != "0" && !strings.EqualFold(value, "false")
+}
+
+// sampler 当前生效的采样器（由 mu 保护）
+var sampler = NewSampler(nil)
+
+// ConfigureSampling 设置请求跟踪的采样配置
+func ConfigureSampling(config *types.TraceConfig) {
+	next := NewSampler(config)
+	mu.Lock()
+	defer mu.Unlock()
+	sampler = next
+}
+
+// sampledKey 请求上下文中保存采样结果的键
+type sampledKey struct{}
+
+// MarkSampled 按采样率对请求做一次采样决定并保存到请求上下文（跟踪未启用时不采样）。
+// 上下文中保存的是指针，之后 ForceSample 修改的结果对外层的中间件同样可见
+func MarkSampled(r *http.Request) (*http.Request, *bool) {
+	sampled := new(bool)
+	if IsEnabled() {
+		mu.RLock()
+		current := sampler
+		mu.RUnlock()
+		*sampled = current.Sample(r)
+	}
+	return r.WithContext(context.WithValue(r.Context(), sampledKey{}, sampled)), sampled
+}
+
+// ForceSample 请求带有强制采样请求头时将其标记为采样，返回是否标记。
+// 只应在调用方确认请求者有权限（如Key有 trace 权限）之后调用；请求未经过 MarkSampled 时不做任何事
+func ForceSample(r *http.Request) bool {
+	sampled, ok := r.Context().Value(sampledKey{}).(*bool)
+	if !ok || !IsEnabled() {
+		return false
+	}
+	mu.RLock()
+	current := sampler
+	mu.RUnlock()
+	if !current.ForceRequested(r) {
+		return false
+	}
+	*sampled = true
+	return true
+}
+
+// Sampled 返回请求的采样结果，未经过 MarkSampled 的请求即时判断
+func Sampled(r *http.Request) bool {
+	if sampled, ok := r.Context().Value(sampledKey{}).(*bool); ok {
+		return *sampled
+	}
+	_, sampled := MarkSampled(r)
+	return *sampled
+}
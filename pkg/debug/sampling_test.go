@@ -0,0 +1,98 @@
+package debug
+
+import (
+	"net/http/httptest"
+	"testing"
+
+	"github.com/iBreaker/llm-gateway/pkg/types"
+)
+
+func TestSampler_Rate(t *testing.T) {
+	defaultRate := 0.05
+	sampler := NewSampler(&types.TraceConfig{
+		SampleRate: &defaultRate,
+		Routes: map[string]float64{
+			"/api/":                1,
+			"/api/v1/stats/":       0.5,
+			"/v1/chat/completions": 0,
+		},
+	})
+
+	cases := map[string]float64{
+		"/v1/messages":               0.05,
+		"/v1/chat/completions":       0,
+		"/api/v1/upstream":           1,
+		"/api/v1/stats/languages":    0.5,
+		"/health":                    0.05,
+		"/api/v1/stats":              1, // 不匹配 /api/v1/stats/
+		"/v1/chat/completions/extra": 0,
+	}
+	for path, want := range cases {
+		if got := sampler.Rate(path); got != want {
+			t.Errorf("Rate(%s) = %v, want %v", path, got, want)
+		}
+	}
+
+	if NewSampler(nil).Rate("/v1/messages") != 1 {
+		t.Error("sampler without config should sample everything")
+	}
+}
+
+func TestSampler_Sample(t *testing.T) {
+	zero := 0.0
+	sampler := NewSampler(&types.TraceConfig{SampleRate: &zero, Routes: map[string]float64{"/v1/": 0.3}})
+	sampler.random = func() float64 { return 0.2 }
+
+	if !sampler.Sample(httptest.NewRequest("POST", "/v1/messages", nil)) {
+		t.Error("0.2 < 0.3 should be sampled")
+	}
+	sampler.random = func() float64 { return 0.4 }
+	if sampler.Sample(httptest.NewRequest("POST", "/v1/messages", nil)) {
+		t.Error("0.4 >= 0.3 should not be sampled")
+	}
+	if sampler.Sample(httptest.NewRequest("GET", "/health", nil)) {
+		t.Error("rate 0 should never be sampled")
+	}
+
+	// 强制采样请求头不参与按采样率的决定，只在认证后由 ForceSample 生效
+	forced := httptest.NewRequest("GET", "/health", nil)
+	forced.Header.Set(DefaultTraceHeader, "1")
+	if sampler.Sample(forced) || !sampler.ForceRequested(forced) {
+		t.Error("trace header should be reported by ForceRequested, not applied by Sample")
+	}
+	forced.Header.Set(DefaultTraceHeader, "false")
+	if sampler.ForceRequested(forced) {
+		t.Error("trace header set to false should not request sampling")
+	}
+}
+
+func TestForceSample(t *testing.T) {
+	mu.Lock()
+	previous := enabled
+	enabled = true
+	mu.Unlock()
+	zero := 0.0
+	ConfigureSampling(&types.TraceConfig{SampleRate: &zero})
+	defer func() {
+		ConfigureSampling(nil)
+		mu.Lock()
+		enabled = previous
+		mu.Unlock()
+	}()
+
+	plain, sampled := MarkSampled(httptest.NewRequest("POST", "/v1/messages", nil))
+	if *sampled || ForceSample(plain) || Sampled(plain) {
+		t.Error("request without the trace header should not be sampled")
+	}
+
+	request := httptest.NewRequest("POST", "/v1/messages", nil)
+	request.Header.Set(DefaultTraceHeader, "1")
+	request, sampled = MarkSampled(request)
+	if *sampled {
+		t.Fatal("the trace header alone should not sample the request before authentication")
+	}
+	// 外层中间件持有的结果随 ForceSample 更新
+	if !ForceSample(request) || !*sampled || !Sampled(request) {
+		t.Error("ForceSample() should mark the request as sampled")
+	}
+}
@@ -1,9 +1,12 @@
 package logger
 
 import (
+	"encoding/json"
 	"fmt"
+	"io"
 	"log"
 	"os"
+	"sort"
 	"strings"
 	"time"
 )
@@ -18,9 +21,13 @@ const (
 	ErrorLevel
 )
 
+// Fields 结构化日志的附加字段，如 request_id、key_id
+type Fields map[string]interface{}
+
 // Logger 简单日志器
 type Logger struct {
 	level  LogLevel
+	json   bool // 每条日志输出为一行JSON，便于日志系统按字段检索
 	logger *log.Logger
 }
 
@@ -43,37 +50,86 @@ func SetDebugLevel() {
 	SetLevel(DebugLevel)
 }
 
-func (l *Logger) log(level LogLevel, prefix, format string, args ...interface{}) {
+// SetFormat 设置日志格式：json 时每条日志输出为一行JSON，其他值使用文本格式
+func SetFormat(format string) {
+	defaultLogger.json = format == "json"
+}
+
+// SetOutput 设置日志输出位置（默认标准输出）
+func SetOutput(w io.Writer) {
+	defaultLogger.logger.SetOutput(w)
+}
+
+func (l *Logger) log(level LogLevel, prefix string, fields Fields, format string, args ...interface{}) {
 	if level < l.level {
 		return
 	}
 
-	timestamp := time.Now().Format("15:04:05")
+	now := time.Now()
 	message := fmt.Sprintf(format, args...)
 
-	// 格式: [时间] [级别] 消息
-	fullMessage := fmt.Sprintf("[%s] [%s] %s", timestamp, prefix, message)
-	l.logger.Println(fullMessage)
+	// JSON格式: {"time": ..., "level": ..., "msg": ..., 附加字段...}
+	if l.json {
+		entry := make(map[string]interface{}, len(fields)+3)
+		for key, value := range fields {
+			entry[key] = value
+		}
+		entry["time"] = now.Format(time.RFC3339Nano)
+		entry["level"] = strings.ToLower(prefix)
+		entry["msg"] = message
+		if data, err := json.Marshal(entry); err == nil {
+			l.logger.Println(string(data))
+			return
+		}
+	}
+
+	// 文本格式: [时间] [级别] 消息 key=value...（字段按名称排序）
+	var builder strings.Builder
+	_, _ = fmt.Fprintf(&builder, "[%s] [%s] %s", now.Format("15:04:05"), prefix, message)
+	keys := make([]string, 0, len(fields))
+	for key := range fields {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+	for _, key := range keys {
+		_, _ = fmt.Fprintf(&builder, " %s=%v", key, fields[key])
+	}
+	l.logger.Println(builder.String())
 }
 
 // Debug 调试日志
 func Debug(format string, args ...interface{}) {
-	defaultLogger.log(DebugLevel, "DEBUG", format, args...)
+	defaultLogger.log(DebugLevel, "DEBUG", nil, format, args...)
 }
 
 // Info 信息日志
 func Info(format string, args ...interface{}) {
-	defaultLogger.log(InfoLevel, "INFO", format, args...)
+	defaultLogger.log(InfoLevel, "INFO", nil, format, args...)
 }
 
 // Warn 警告日志
 func Warn(format string, args ...interface{}) {
-	defaultLogger.log(WarnLevel, "WARN", format, args...)
+	defaultLogger.log(WarnLevel, "WARN", nil, format, args...)
 }
 
 // Error 错误日志
 func Error(format string, args ...interface{}) {
-	defaultLogger.log(ErrorLevel, "ERROR", format, args...)
+	defaultLogger.log(ErrorLevel, "ERROR", nil, format, args...)
+}
+
+// DebugWith 带结构化字段的调试日志
+func DebugWith(fields Fields, format string, args ...interface{}) {
+	defaultLogger.log(DebugLevel, "DEBUG", fields, format, args...)
+}
+
+// InfoWith 带结构化字段的信息日志
+func InfoWith(fields Fields, format string, args ...interface{}) {
+	defaultLogger.log(InfoLevel, "INFO", fields, format, args...)
+}
+
+// WarnWith 带结构化字段的警告日志
+func WarnWith(fields Fields, format string, args ...interface{}) {
+	defaultLogger.log(WarnLevel, "WARN", fields, format, args...)
 }
 
 // IsDebugEnabled 是否启用调试级别
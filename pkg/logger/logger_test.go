@@ -0,0 +1,50 @@
+package logger
+
+import (
+	"bytes"
+	"encoding/json"
+	"log"
+	"strings"
+	"testing"
+)
+
+// captureOutput 将默认日志器的输出重定向到缓冲区，测试结束后恢复
+func captureOutput(t *testing.T) *bytes.Buffer {
+	t.Helper()
+	saved := *defaultLogger
+	buffer := &bytes.Buffer{}
+	defaultLogger.logger = log.New(buffer, "", 0)
+	t.Cleanup(func() { *defaultLogger = saved })
+	return buffer
+}
+
+func TestInfoWith_Text(t *testing.T) {
+	buffer := captureOutput(t)
+	SetFormat("text")
+
+	InfoWith(Fields{"request_id": "req-1", "key_id": "gw_1"}, "request %s", "completed")
+
+	line := strings.TrimSpace(buffer.String())
+	if !strings.HasSuffix(line, "[INFO] request completed key_id=gw_1 request_id=req-1") {
+		t.Errorf("line = %q, want fields sorted by name after the message", line)
+	}
+}
+
+func TestInfoWith_JSON(t *testing.T) {
+	buffer := captureOutput(t)
+	SetFormat("json")
+
+	InfoWith(Fields{"request_id": "req-1", "status": 200}, "request completed")
+	Debug("below the level, not written")
+
+	var entry map[string]interface{}
+	if err := json.Unmarshal(buffer.Bytes(), &entry); err != nil {
+		t.Fatalf("output is not a single JSON line: %q (%v)", buffer.String(), err)
+	}
+	if entry["level"] != "info" || entry["msg"] != "request completed" || entry["request_id"] != "req-1" || entry["status"] != float64(200) {
+		t.Errorf("entry = %v", entry)
+	}
+	if _, ok := entry["time"]; !ok {
+		t.Error("entry should carry a time field")
+	}
+}
@@ -0,0 +1,41 @@
+package types
+
+import "time"
+
+// Announcement - 管理员发布的公告（维护窗口、新模型上线等）
+type Announcement struct {
+	ID          string     `json:"id" yaml:"id"`
+	Title       string     `json:"title" yaml:"title"`
+	Message     string     `json:"message" yaml:"message"`
+	Level       string     `json:"level" yaml:"level"` // info, warning, critical
+	Enabled     bool       `json:"enabled" yaml:"enabled"`
+	StartsAt    *time.Time `json:"starts_at,omitempty" yaml:"starts_at,omitempty"`
+	EndsAt      *time.Time `json:"ends_at,omitempty" yaml:"ends_at,omitempty"`
+	DismissedBy []string   `json:"dismissed_by,omitempty" yaml:"-"` // 已关闭该公告的用户（Web用户名或Gateway Key ID），保存在单独的文件中，不写入配置文件
+	CreatedAt   time.Time  `json:"created_at" yaml:"created_at"`
+	UpdatedAt   time.Time  `json:"updated_at" yaml:"updated_at"`
+}
+
+// IsActive 检查公告在指定时间是否处于展示期
+func (a *Announcement) IsActive(now time.Time) bool {
+	if !a.Enabled {
+		return false
+	}
+	if a.StartsAt != nil && now.Before(*a.StartsAt) {
+		return false
+	}
+	if a.EndsAt != nil && now.After(*a.EndsAt) {
+		return false
+	}
+	return true
+}
+
+// IsDismissedBy 检查指定用户是否已关闭该公告
+func (a *Announcement) IsDismissedBy(userID string) bool {
+	for _, id := range a.DismissedBy {
+		if id == userID {
+			return true
+		}
+	}
+	return false
+}
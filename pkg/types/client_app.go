@@ -0,0 +1,41 @@
+package types
+
+import (
+	"regexp"
+	"strings"
+)
+
+// ClientAppHeader 客户端应用标识请求头，格式为 "<名称>@<版本>"，如 billing-bot@1.4.2
+const ClientAppHeader = "X-Gateway-App"
+
+var (
+	clientAppNamePattern    = regexp.MustCompile(`^[A-Za-z0-9][A-Za-z0-9._-]{0,63}$`)
+	clientAppVersionPattern = regexp.MustCompile(`^[A-Za-z0-9][A-Za-z0-9._+-]{0,31}$`)
+)
+
+// ParseClientApp 解析 "<名称>@<版本>" 形式的客户端应用标识，格式无效时返回 ok=false
+func ParseClientApp(value string) (name, version string, ok bool) {
+	name, version, found := strings.Cut(value, "@")
+	if !found || !ValidClientAppName(name) || !clientAppVersionPattern.MatchString(version) {
+		return "", "", false
+	}
+	return name, version, true
+}
+
+// ValidClientAppName 检查应用名称格式：字母或数字开头，可含 . _ -，最长64个字符
+func ValidClientAppName(name string) bool {
+	return clientAppNamePattern.MatchString(name)
+}
+
+// AppAllowed 检查Key是否允许该应用：未登记应用时不限制，登记了时需在列表中
+func (k *GatewayAPIKey) AppAllowed(name string) bool {
+	if len(k.Apps) == 0 {
+		return true
+	}
+	for _, app := range k.Apps {
+		if app == name {
+			return true
+		}
+	}
+	return false
+}
@@ -2,13 +2,29 @@ package types
 
 // Config - 全局配置
 type Config struct {
-	Server           ServerConfig      `yaml:"server"`
-	Proxy            ProxyConfig       `yaml:"proxy"`
-	GatewayKeys      []GatewayAPIKey   `yaml:"gateway_keys"`
-	UpstreamAccounts []UpstreamAccount `yaml:"upstream_accounts"`
-	ModelRoutes      ModelRouteConfig  `yaml:"model_routes"`
-	Logging          LoggingConfig     `yaml:"logging"`
-	Environment      EnvironmentConfig `yaml:"environment"`
+	Server           ServerConfig                  `yaml:"server"`
+	Proxy            ProxyConfig                   `yaml:"proxy"`
+	GatewayKeys      []GatewayAPIKey               `yaml:"gateway_keys"`
+	UpstreamAccounts []UpstreamAccount             `yaml:"upstream_accounts"`
+	Organizations    []Organization                `yaml:"organizations,omitempty"`
+	ModelRoutes      ModelRouteConfig              `yaml:"model_routes"`
+	RoutingRules     []RoutingRule                 `yaml:"routing_rules,omitempty"`
+	Routing          RoutingConfig                 `yaml:"routing"`
+	Providers        map[Provider]ProviderSettings `yaml:"providers,omitempty"`
+	Announcements    []Announcement                `yaml:"announcements,omitempty"`
+	SLO              SLOConfig                     `yaml:"slo"`
+	Hygiene          HygieneConfig                 `yaml:"hygiene"`
+	HealthCheck      HealthCheckConfig             `yaml:"health_check"`
+	Canaries         CanaryConfig                  `yaml:"canaries"`
+	Audit            AuditConfig                   `yaml:"audit"`
+	Backup           BackupConfig                  `yaml:"backup"`
+	UsageWAL         UsageWALConfig                `yaml:"usage_wal"`
+	SharedState      SharedStateConfig             `yaml:"shared_state"`
+	Notifications    NotificationConfig            `yaml:"notifications"`
+	Pricing          PricingConfig                 `yaml:"pricing"`
+	Logging          LoggingConfig                 `yaml:"logging"`
+	Environment      EnvironmentConfig             `yaml:"environment"`
+	Runtime          RuntimeConfig                 `yaml:"runtime"`
 }
 
 // ServerConfig - 服务器配置
@@ -17,14 +33,54 @@ type ServerConfig struct {
 	Port    int       `yaml:"port"`
 	Timeout int       `yaml:"timeout_seconds"`
 	Web     WebConfig `yaml:"web"`
+
+	// DrainTimeoutSeconds 关闭时等待进行中的请求（包括流式响应）结束的最长时间，0使用默认值30秒
+	DrainTimeoutSeconds int `yaml:"drain_timeout_seconds"`
+
+	// ConfigWatchSeconds 检查配置文件是否被外部修改、修改后自动重新加载的间隔，0表示不检查
+	// （仍可通过 POST /api/v1/config/reload 手动重新加载）
+	ConfigWatchSeconds int `yaml:"config_watch_seconds"`
+
+	// CORSAllowedOrigins 允许跨域的来源，未配置时使用运行环境配置档的默认值
+	CORSAllowedOrigins []string `yaml:"cors_allowed_origins,omitempty"`
+
+	// Profiling 运行时性能剖析端点（/api/v1/debug/pprof/*，需要Web登录），默认关闭
+	Profiling ProfilingConfig `yaml:"profiling"`
+}
+
+// ProfilingConfig - 运行时性能剖析配置
+type ProfilingConfig struct {
+	Enabled       bool `yaml:"enabled"`
+	MaxCPUSeconds int  `yaml:"max_cpu_seconds"` // 单次CPU剖析的最长时间，0使用默认值60
 }
 
 // WebConfig - Web 管理界面配置
 type WebConfig struct {
 	Enabled  bool   `yaml:"enabled"`
-	Password string `yaml:"password"`
+	Password string `yaml:"password" json:"-"` // 内置 admin 用户的密码
+
+	// Users 其他Web用户及其角色（admin / operator / viewer）
+	Users []WebUser `yaml:"users,omitempty"`
+
+	// ServiceAccounts 自动化程序的服务账号，用 client_id/client_secret 换取短期访问令牌
+	ServiceAccounts        []ServiceAccount `yaml:"service_accounts,omitempty"`
+	ServiceTokenSecret     string           `yaml:"service_token_secret,omitempty" json:"-"` // 令牌签名密钥，为空时每次启动随机生成；多实例共享令牌时需配置
+	ServiceTokenTTLSeconds int              `yaml:"service_token_ttl_seconds"`               // 令牌有效期，0表示默认900秒
+
+	// KeyCreation 非 admin 用户自助创建 Gateway Key 的设置
+	KeyCreation KeyCreationConfig `yaml:"key_creation"`
 }
 
+// KeyCreationConfig - 自助创建 Gateway Key 的设置（admin 创建的Key不受限制）
+type KeyCreationConfig struct {
+	AllowOperators  bool `json:"allow_operators" yaml:"allow_operators"`     // operator 可以自己创建Key
+	MaxKeysPerUser  int  `json:"max_keys_per_user" yaml:"max_keys_per_user"` // 每个用户最多创建的Key数量，0表示不限制；环境变量 MAX_KEYS_PER_USER 优先
+	RequireApproval bool `json:"require_approval" yaml:"require_approval"`   // 自助创建的Key处于停用状态，admin 批准后才能使用
+}
+
+// MaxRequestSizeBytes 未配置 proxy.max_request_bytes 时代理请求体的大小上限（32MB）
+const MaxRequestSizeBytes = 32 << 20
+
 // ProxyConfig - 代理配置
 type ProxyConfig struct {
 	RequestTimeout  int `yaml:"request_timeout_seconds"`   // 普通请求超时
@@ -33,6 +89,294 @@ type ProxyConfig struct {
 	TLSTimeout      int `yaml:"tls_timeout_seconds"`       // TLS握手超时
 	IdleConnTimeout int `yaml:"idle_conn_timeout_seconds"` // 空闲连接超时
 	ResponseTimeout int `yaml:"response_timeout_seconds"`  // 响应头超时
+
+	// UsageHeaders 在响应中返回 X-Gateway-Cost-USD / X-Gateway-Input-Tokens / X-Gateway-Output-Tokens，
+	// 流式响应则在结束后追加 gateway_usage 事件
+	UsageHeaders bool `yaml:"usage_headers"`
+
+	// MergeConsecutiveMessages 自动合并连续的同角色消息（部分SDK会产生，部分提供商会拒绝）
+	MergeConsecutiveMessages bool `yaml:"merge_consecutive_messages"`
+	MaxMessages              int  `yaml:"max_messages"` // 单个请求的消息数量上限，0表示不限制
+
+	// MaxRequestBytes 代理请求体的大小上限，超过时返回413，0使用默认值 MaxRequestSizeBytes
+	MaxRequestBytes int64 `yaml:"max_request_bytes"`
+
+	// PathRules 按提供商配置的路径访问规则，在选择上游账号之前检查
+	PathRules map[Provider]PathRules `yaml:"path_rules,omitempty"`
+
+	// MaxRetryAttempts 上游返回429/5xx或超时时切换到其他账号重试的最大次数，0使用默认值2，负数表示不重试
+	MaxRetryAttempts int `yaml:"max_retry_attempts"`
+
+	// ModelValidation 模型名校验模式：off（默认）、normalize（规范化大小写/别名/日期后缀）、strict（同时拒绝未知模型）
+	ModelValidation string `yaml:"model_validation"`
+
+	// ResponseCache 非流式响应的精确匹配缓存，请求带 X-LLM-Cache: true 时才使用
+	ResponseCache ResponseCacheConfig `yaml:"response_cache"`
+
+	// Params 客户端请求中网关未解析的额外参数（如 seed、top_k）的过滤规则
+	Params ParamFilterConfig `yaml:"params"`
+
+	// Queue 提供商的所有账号都达到并发上限时让请求排队等待，而不是直接返回429
+	Queue QueueConfig `yaml:"queue"`
+
+	// Transforms 按顺序执行的请求/响应转换器（注入系统提示词、删除参数、脱敏），在转发上游之前和返回客户端之前执行
+	Transforms []TransformConfig `yaml:"transforms,omitempty"`
+
+	// Moderation 转发上游之前的内容审核（关键词/正则规则和可选的审核模型），按Key选择审核策略
+	Moderation ModerationConfig `yaml:"moderation"`
+
+	// ContextTrim 上游因超出上下文窗口拒绝请求时，删除最早的对话消息后自动重试一次
+	ContextTrim ContextTrimConfig `yaml:"context_trim"`
+
+	// Sandbox 沙箱Key的请求由内置的模拟响应器应答，不访问真实上游
+	Sandbox SandboxConfig `yaml:"sandbox"`
+
+	// OverloadBackoff 上游报告过载（Anthropic 的529 overloaded_error）时整个提供商进入退避窗口
+	OverloadBackoff OverloadBackoffConfig `yaml:"overload_backoff"`
+}
+
+// OverloadBackoffConfig - 提供商过载退避配置。窗口从 base_seconds 开始，连续过载时翻倍直到 max_seconds，
+// 实际长度在窗口的一半到全长之间随机，避免所有实例和客户端同时重试
+type OverloadBackoffConfig struct {
+	Enabled     bool `yaml:"enabled"`
+	BaseSeconds int  `yaml:"base_seconds"` // 第一次过载的退避时长，0使用默认值5
+	MaxSeconds  int  `yaml:"max_seconds"`  // 退避时长上限，0使用默认值60
+}
+
+// SandboxConfig - 沙箱模拟响应的延迟配置
+type SandboxConfig struct {
+	LatencyMs       int `yaml:"latency_ms"`        // 返回响应（流式为首个事件）之前的模拟延迟，0使用默认值300，负数表示不延迟
+	ChunkIntervalMs int `yaml:"chunk_interval_ms"` // 流式响应相邻事件的间隔，0使用默认值30，负数表示不延迟
+}
+
+// ContextTrimConfig - 上下文超限删减配置
+type ContextTrimConfig struct {
+	Enabled            bool    `yaml:"enabled"`
+	KeepRecentMessages int     `yaml:"keep_recent_messages"` // 至少保留的最近非系统消息数，0使用默认值2
+	TargetRatio        float64 `yaml:"target_ratio"`         // 上游没有报告上限时删减到原token数的比例，0使用默认值0.7
+}
+
+// ModerationConfig - 内容审核配置，没有策略时不审核
+type ModerationConfig struct {
+	Policies []ModerationPolicyConfig `yaml:"policies,omitempty"`
+	Model    ModerationModelConfig    `yaml:"model"` // 策略开启 use_model 时调用的审核模型
+}
+
+// ModerationPolicyConfig - 内容审核策略，请求使用第一个包含其Key的策略，都不包含时使用没有配置 keys 的策略
+type ModerationPolicyConfig struct {
+	Name       string                 `yaml:"name"`
+	Keys       []string               `yaml:"keys,omitempty"` // 使用此策略的 Gateway Key ID，为空时作为默认策略
+	Rules      []ModerationRuleConfig `yaml:"rules,omitempty"`
+	UseModel   bool                   `yaml:"use_model"`            // 规则未命中时再调用审核模型
+	Categories []string               `yaml:"categories,omitempty"` // 审核模型标记了这些类别时拦截，为空时标记任何类别都拦截
+}
+
+// ModerationRuleConfig - 内容审核规则，命中任意关键词或正则表达式时拦截
+type ModerationRuleConfig struct {
+	Code      string   `yaml:"code"`                // 拦截时返回的策略代码
+	Blocklist []string `yaml:"blocklist,omitempty"` // 关键词（大小写不敏感）
+	Patterns  []string `yaml:"patterns,omitempty"`  // 正则表达式
+}
+
+// ModerationModelConfig - 审核模型配置（OpenAI moderations 接口格式）
+type ModerationModelConfig struct {
+	Endpoint       string `yaml:"endpoint"`         // 默认 https://api.openai.com/v1/moderations
+	APIKey         string `yaml:"api_key" json:"-"` // 作为 Bearer 令牌发送
+	Model          string `yaml:"model"`            // 默认 omni-moderation-latest
+	TimeoutSeconds int    `yaml:"timeout_seconds"`  // 0使用默认值5秒
+	FailOpen       bool   `yaml:"fail_open"`        // 审核模型调用失败时放行请求，默认拒绝
+}
+
+// TransformConfig - 请求/响应转换器配置
+type TransformConfig struct {
+	Name        string   `yaml:"name"`
+	Type        string   `yaml:"type"`                  // system_prompt、strip_fields、mask_pii 或 redact
+	Models      []string `yaml:"models,omitempty"`      // 只处理这些模型（支持末尾*通配），为空时处理所有模型
+	Keys        []string `yaml:"keys,omitempty"`        // 只处理这些 Gateway Key ID 的请求，为空时处理所有Key
+	Prompt      string   `yaml:"prompt,omitempty"`      // system_prompt：注入的系统提示词
+	Position    string   `yaml:"position,omitempty"`    // system_prompt：prepend（默认）或 append
+	Fields      []string `yaml:"fields,omitempty"`      // strip_fields：删除的请求参数
+	Patterns    []string `yaml:"patterns,omitempty"`    // redact：替换的正则表达式
+	Replacement string   `yaml:"replacement,omitempty"` // redact：替换文本，默认 [REDACTED]
+	Apply       string   `yaml:"apply,omitempty"`       // mask_pii、redact：request、response 或 both（默认）
+}
+
+// QueueConfig - 请求排队配置
+type QueueConfig struct {
+	Enabled        bool `yaml:"enabled"`
+	MaxSize        int  `yaml:"max_size"`         // 最多排队的请求数，0使用默认值100
+	MaxWaitSeconds int  `yaml:"max_wait_seconds"` // 最长排队时间，0使用默认值30
+}
+
+// ParamFilterConfig - 额外请求参数过滤配置
+type ParamFilterConfig struct {
+	Mode  string                `yaml:"mode"`            // allowlist（默认，只转发目标提供商允许的参数）或 passthrough（全部转发）
+	Allow map[Provider][]string `yaml:"allow,omitempty"` // 按提供商替换内置的允许列表
+}
+
+// ResponseCacheConfig - 响应缓存配置
+type ResponseCacheConfig struct {
+	Enabled    bool `yaml:"enabled"`
+	TTLSeconds int  `yaml:"ttl_seconds"` // 缓存有效期，0使用默认值300秒
+	MaxEntries int  `yaml:"max_entries"` // 最多缓存的响应数，超出时淘汰最久未使用的，0使用默认值1000
+}
+
+// ProviderSettings - 提供商设置（可在运行时修改，无需重启）
+type ProviderSettings struct {
+	Enabled *bool `yaml:"enabled,omitempty"` // 未设置时默认启用
+}
+
+// SLOConfig - SLO 目标配置（对每个 Gateway Key 和上游账号分别计算）
+type SLOConfig struct {
+	LatencyThresholdMs     int     `yaml:"latency_threshold_ms"`      // Apdex 满意阈值 T（毫秒），4T 以内为可容忍
+	AvailabilityTarget     float64 `yaml:"availability_target"`       // 可用性目标，如 0.99
+	WindowHours            int     `yaml:"window_hours"`              // SLO 与错误预算的计算窗口
+	BurnRateWindowMinutes  int     `yaml:"burn_rate_window_minutes"`  // 燃烧率的短窗口
+	BurnRateAlertThreshold float64 `yaml:"burn_rate_alert_threshold"` // 燃烧率超过该值时告警
+}
+
+// HygieneConfig - 闲置凭证检测配置（Gateway Key 和上游账号）
+type HygieneConfig struct {
+	IdleDays    int  `yaml:"idle_days"`    // 超过该天数未使用即标记为闲置
+	AutoDisable bool `yaml:"auto_disable"` // 闲置超过宽限期后自动禁用
+	GraceDays   int  `yaml:"grace_days"`   // 标记闲置到自动禁用之间的宽限期
+}
+
+// HealthCheckConfig - 上游账号健康探测配置
+type HealthCheckConfig struct {
+	TimeoutSeconds  int    `yaml:"timeout_seconds"`       // 单次探测请求的超时时间
+	IntervalSeconds int    `yaml:"interval_seconds"`      // 后台探测活跃账号的间隔，0使用默认值300，负数表示关闭
+	MaxParallel     int    `yaml:"max_parallel"`          // 批量探测的最大并发数，0使用默认值8
+	HistorySize     int    `yaml:"history_size"`          // 每个账号保留的探测历史条数，0使用默认值100
+	HistoryDir      string `yaml:"history_dir,omitempty"` // 探测历史目录，默认 ~/.llm-gateway/health
+
+	// AuthFailureThreshold 代理请求连续多少次被上游以401/403拒绝后自动停用账号，0使用默认值5，负数表示不自动停用
+	AuthFailureThreshold int `yaml:"auth_failure_threshold"`
+
+	// CircuitBreaker 账号熔断器的全局参数，账号可以通过自己的 circuit_breaker 覆盖
+	CircuitBreaker CircuitBreakerConfig `yaml:"circuit_breaker"`
+}
+
+// CircuitBreakerConfig - 熔断器参数，修改后立即生效
+type CircuitBreakerConfig struct {
+	FailureThreshold int `json:"failure_threshold" yaml:"failure_threshold"` // 连续失败多少次后打开熔断器，0使用默认值（全局5，账号覆盖时为全局值）
+	OpenSeconds      int `json:"open_seconds" yaml:"open_seconds"`           // 打开多久后进入半开状态放行试探请求，0使用默认值（全局30，账号覆盖时为全局值）
+}
+
+// RoutingConfig - 负载均衡配置
+type RoutingConfig struct {
+	Strategy  string          `yaml:"strategy"` // round_robin / random / health_first / fastest / least_connections，默认 health_first
+	Autopilot AutopilotConfig `yaml:"autopilot"`
+}
+
+// AutopilotConfig - 自动切换负载均衡策略：延迟异常时切换到 fastest，流量突增时切换到 least_connections，
+// 恢复后切回 routing.strategy
+type AutopilotConfig struct {
+	Enabled            bool    `yaml:"enabled"`
+	IntervalSeconds    int     `yaml:"interval_seconds"`     // 评估间隔，0使用默认值30
+	WindowMinutes      int     `yaml:"window_minutes"`       // 按最近多少分钟的请求判断，0使用默认值5
+	LatencyThresholdMs int     `yaml:"latency_threshold_ms"` // 平均延迟超过该值视为延迟异常，0使用默认值10000
+	SpikeFactor        float64 `yaml:"spike_factor"`         // 请求速率超过前一小时平均速率的倍数视为流量突增，0使用默认值3
+	MinHoldSeconds     int     `yaml:"min_hold_seconds"`     // 切换后至少保持多久才再次切换，0使用默认值300
+}
+
+// CanaryConfig - 合成探针配置：定期向各提供商发送很小的提示词并断言输出，
+// 发现返回200但内容为空或错误等静默质量下降
+type CanaryConfig struct {
+	Enabled         bool          `yaml:"enabled"`
+	IntervalSeconds int           `yaml:"interval_seconds"` // 运行间隔，0使用默认值900
+	TimeoutSeconds  int           `yaml:"timeout_seconds"`  // 单次请求超时，0使用默认值30
+	Checks          []CanaryCheck `yaml:"checks"`
+}
+
+// CanaryCheck - 单个合成探针
+type CanaryCheck struct {
+	Name           string   `yaml:"name" json:"name"`
+	Provider       Provider `yaml:"provider" json:"provider"`
+	Model          string   `yaml:"model" json:"model"`
+	Prompt         string   `yaml:"prompt" json:"prompt"`
+	MaxTokens      int      `yaml:"max_tokens,omitempty" json:"max_tokens,omitempty"`           // 0使用默认值32
+	ExpectContains string   `yaml:"expect_contains,omitempty" json:"expect_contains,omitempty"` // 输出需包含的子串（不区分大小写）
+	ExpectRegex    string   `yaml:"expect_regex,omitempty" json:"expect_regex,omitempty"`       // 输出需匹配的正则表达式
+	UpstreamIDs    []string `yaml:"upstream_ids,omitempty" json:"upstream_ids,omitempty"`       // 为空时探测该提供商的所有活跃账号
+}
+
+// AuditConfig - 请求/响应审计日志配置
+type AuditConfig struct {
+	Enabled       bool     `yaml:"enabled"`
+	Dir           string   `yaml:"dir,omitempty"`           // 审计日志目录，默认 ~/.llm-gateway/audit
+	BodyMode      string   `yaml:"body_mode"`               // full: 脱敏后保存内容（超长截断）；hash: 只保存SHA-256和长度
+	MaxBodyBytes  int      `yaml:"max_body_bytes"`          // full 模式下请求体和响应体各保留的最大字节数
+	RetentionDays int      `yaml:"retention_days"`          // 审计记录保留天数
+	KeyIDs        []string `yaml:"key_ids,omitempty"`       // 只审计这些Gateway Key的请求，为空时审计所有Key
+	RedactFields  []string `yaml:"redact_fields,omitempty"` // 内置凭证字段之外需要脱敏的JSON字段名
+	KeepPII       bool     `yaml:"keep_pii"`                // 保留文本中的邮箱、电话、卡号（默认替换）
+
+	// ObjectStore 配置后超过 max_body_bytes 的内容完整上传到对象存储，本地只保留截断的预览
+	ObjectStore *AuditObjectStoreConfig `yaml:"object_store,omitempty"`
+
+	// Dedup 按内容分块去重保存请求体/响应体，重复的系统提示词和对话前缀只保存一份
+	Dedup AuditDedupConfig `yaml:"dedup"`
+}
+
+// AuditDedupConfig - 审计内容的去重存储配置
+type AuditDedupConfig struct {
+	Enabled  bool `yaml:"enabled"`
+	MinBytes int  `yaml:"min_bytes"` // 短于此长度的内容直接内联保存，0使用默认值1024
+}
+
+// AuditObjectStoreConfig - 审计内容的对象存储分层配置
+type AuditObjectStoreConfig struct {
+	ObjectStoreConfig `yaml:",inline"`
+	Prefix            string `yaml:"prefix"`           // 对象键前缀，默认 audit/
+	ExpirationDays    int    `yaml:"expiration_days"`  // 启动时为前缀写入存储桶生命周期规则，0表示不设置
+	MaxObjectBytes    int    `yaml:"max_object_bytes"` // 单个对象保存的最大字节数，默认8MB
+	PresignSeconds    int    `yaml:"presign_seconds"`  // 查询接口返回的下载链接有效期，默认900秒
+}
+
+// ObjectStoreConfig - S3兼容对象存储配置（AWS S3、MinIO、Cloudflare R2 等）
+type ObjectStoreConfig struct {
+	Endpoint        string `yaml:"endpoint"` // 如 https://s3.us-east-1.amazonaws.com
+	Region          string `yaml:"region"`   // 默认 us-east-1
+	Bucket          string `yaml:"bucket"`
+	AccessKeyID     string `yaml:"access_key_id"`
+	SecretAccessKey string `yaml:"secret_access_key"`
+	PathStyle       bool   `yaml:"path_style"` // 使用 endpoint/bucket/key 形式的地址（MinIO 等需要开启）
+}
+
+// BackupConfig - 定时备份配置（网关数据导出到S3兼容对象存储）
+type BackupConfig struct {
+	Enabled       bool   `yaml:"enabled"`
+	IntervalHours int    `yaml:"interval_hours"` // 备份间隔，默认24
+	RetentionDays int    `yaml:"retention_days"` // 超过天数的备份被删除（最新一份始终保留），默认30
+	Prefix        string `yaml:"prefix"`         // 对象键前缀，默认 backups/
+	Passphrase    string `yaml:"passphrase"`     // 加密上游账号凭证的口令，未配置时读取 LLM_GATEWAY_BACKUP_PASSPHRASE
+
+	ObjectStore ObjectStoreConfig `yaml:"object_store"`
+}
+
+// UsageWALConfig - 使用记录持久化配置（追加写到本地日志，重启后恢复使用统计和配额计数）
+type UsageWALConfig struct {
+	Enabled              bool   `yaml:"enabled"`
+	Dir                  string `yaml:"dir,omitempty"`          // 日志目录，默认 ~/.llm-gateway/usage
+	FlushIntervalSeconds int    `yaml:"flush_interval_seconds"` // 缓冲的记录写入磁盘的间隔，默认1
+}
+
+// SharedStateConfig - 多实例部署时共享的状态：Gateway Key 的限流令牌桶和上游账号熔断器
+type SharedStateConfig struct {
+	Backend             string      `yaml:"backend"`               // memory（默认，每个进程独立）或 redis
+	Redis               RedisConfig `yaml:"redis"`                 // backend 为 redis 时使用
+	SyncIntervalSeconds int         `yaml:"sync_interval_seconds"` // 从 Redis 同步熔断器状态的间隔，0使用默认值2
+}
+
+// RedisConfig - Redis 连接配置
+type RedisConfig struct {
+	Address   string `yaml:"address"` // host:port
+	Password  string `yaml:"password,omitempty" json:"-"`
+	DB        int    `yaml:"db"`
+	TLS       bool   `yaml:"tls"`
+	KeyPrefix string `yaml:"key_prefix"` // 所有键的前缀，默认 llm-gateway:
+	TimeoutMs int    `yaml:"timeout_ms"` // 单个命令的超时时间，0使用默认值500
 }
 
 // LoggingConfig - 日志配置
@@ -40,6 +384,24 @@ type LoggingConfig struct {
 	Level  string `yaml:"level"`
 	Format string `yaml:"format"`
 	File   string `yaml:"file"`
+
+	// Trace 请求跟踪的按路由采样
+	Trace TraceConfig `yaml:"trace"`
+}
+
+// TraceConfig - 请求跟踪采样配置（调试模式或 enabled 时生效，跟踪文件写入 ~/.llm-gateway/debug）
+type TraceConfig struct {
+	Enabled     bool               `yaml:"enabled"`                // 非调试模式下也记录采样到的请求跟踪
+	SampleRate  *float64           `yaml:"sample_rate,omitempty"`  // 默认采样率（0-1），未设置时为1
+	Routes      map[string]float64 `yaml:"routes,omitempty"`       // 按路径前缀覆盖采样率，最长前缀优先
+	ForceHeader string             `yaml:"force_header,omitempty"` // 带该请求头（值不为 0/false）的请求始终采样，默认 X-Gateway-Trace
+}
+
+// RuntimeConfig - Go 运行时调优（server start 时生效），未设置的项沿用 GOMAXPROCS/GOGC/GOMEMLIMIT 环境变量或运行时默认值
+type RuntimeConfig struct {
+	MaxProcs      int  `yaml:"max_procs"`            // 同时执行Go代码的最大线程数（GOMAXPROCS），0表示不修改
+	GCPercent     *int `yaml:"gc_percent,omitempty"` // GC触发比例（GOGC），-1关闭GC
+	MemoryLimitMB int  `yaml:"memory_limit_mb"`      // 软内存上限（GOMEMLIMIT），0表示不修改
 }
 
 // EnvironmentConfig - 环境变量配置
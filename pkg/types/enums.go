@@ -1,14 +1,18 @@
 package types
 
+import "strings"
+
 // Provider 枚举 - LLM提供商
 type Provider string
 
 const (
-	ProviderAnthropic Provider = "anthropic"
-	ProviderOpenAI    Provider = "openai"
-	ProviderGoogle    Provider = "google"
-	ProviderAzure     Provider = "azure"
-	ProviderQwen      Provider = "qwen"
+	ProviderAnthropic        Provider = "anthropic"
+	ProviderOpenAI           Provider = "openai"
+	ProviderGoogle           Provider = "google"
+	ProviderAzure            Provider = "azure"
+	ProviderQwen             Provider = "qwen"
+	ProviderBedrock          Provider = "bedrock"
+	ProviderOpenAICompatible Provider = "openai-compatible" // 自托管的 OpenAI 兼容后端（vLLM、Ollama 等），必须配置 base_url
 )
 
 // Permission 枚举 - Gateway API Key权限
@@ -18,6 +22,7 @@ const (
 	PermissionRead  Permission = "read"
 	PermissionWrite Permission = "write"
 	PermissionAdmin Permission = "admin"
+	PermissionTrace Permission = "trace" // 可以用强制采样请求头记录请求的完整跟踪（admin 同样可以）
 )
 
 // UpstreamType 枚举 - 上游账号类型
@@ -27,3 +32,60 @@ const (
 	UpstreamTypeAPIKey UpstreamType = "api-key"
 	UpstreamTypeOAuth  UpstreamType = "oauth"
 )
+
+// RequestPriority 枚举 - 请求排队时的优先级（X-Gateway-Priority 头部）
+type RequestPriority string
+
+const (
+	PriorityCritical RequestPriority = "critical"
+	PriorityHigh     RequestPriority = "high"
+	PriorityNormal   RequestPriority = "normal"
+	PriorityLow      RequestPriority = "low"
+)
+
+// Rank 返回优先级的排序值，数字越小越优先；未知的优先级按 normal 处理
+func (p RequestPriority) Rank() int {
+	switch p {
+	case PriorityCritical:
+		return 0
+	case PriorityHigh:
+		return 1
+	case PriorityLow:
+		return 3
+	default:
+		return 2
+	}
+}
+
+// ParseRequestPriority 解析请求优先级（不区分大小写），为空时返回 normal
+func ParseRequestPriority(value string) (RequestPriority, bool) {
+	switch priority := RequestPriority(strings.ToLower(strings.TrimSpace(value))); priority {
+	case "":
+		return PriorityNormal, true
+	case PriorityCritical, PriorityHigh, PriorityNormal, PriorityLow:
+		return priority, true
+	}
+	return PriorityNormal, false
+}
+
+// ErrorClass 枚举 - 上游错误分类，记录在使用记录和汇总中，可按提供商配置告警规则
+type ErrorClass string
+
+const (
+	ErrorClassAuth           ErrorClass = "auth"            // 401/403：凭证无效、过期或权限不足
+	ErrorClassQuota          ErrorClass = "quota"           // 429、402 或余额/配额耗尽
+	ErrorClassOverloaded     ErrorClass = "overloaded"      // 529 或 overloaded_error：提供商整体容量不足
+	ErrorClassInvalidRequest ErrorClass = "invalid_request" // 其他4xx：请求内容被上游拒绝
+	ErrorClassServer         ErrorClass = "server"          // 其他5xx
+	ErrorClassNetwork        ErrorClass = "network"         // 连接失败或响应中途断开
+	ErrorClassTimeout        ErrorClass = "timeout"         // 等待或读取上游响应超时
+)
+
+// ParseErrorClass 解析错误分类（不区分大小写）
+func ParseErrorClass(value string) (ErrorClass, bool) {
+	switch class := ErrorClass(strings.ToLower(strings.TrimSpace(value))); class {
+	case ErrorClassAuth, ErrorClassQuota, ErrorClassOverloaded, ErrorClassInvalidRequest, ErrorClassServer, ErrorClassNetwork, ErrorClassTimeout:
+		return class, true
+	}
+	return "", false
+}
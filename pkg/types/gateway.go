@@ -8,13 +8,40 @@ type GatewayAPIKey struct {
 	Name        string           `json:"name" yaml:"name"`
 	KeyHash     string           `json:"key_hash" yaml:"key_hash"`
 	Permissions []Permission     `json:"permissions" yaml:"permissions"`
+	// 作用域，如 provider:anthropic、model:claude-3-*、endpoint:/v1/messages，为空时不限制
+	Scopes      []string         `json:"scopes,omitempty" yaml:"scopes,omitempty"`
+	// 登记的客户端应用名称（X-Gateway-App 头部的名称部分），为空时不校验
+	Apps        []string         `json:"apps,omitempty" yaml:"apps,omitempty"`
+	// 标签，用于路由规则的 key_tags 条件
+	Tags        []string         `json:"tags,omitempty" yaml:"tags,omitempty"`
+	// 所属组织，用量计入该组织，可以使用该组织的上游账号
+	OrgID       string           `json:"org_id,omitempty" yaml:"org_id,omitempty"`
 	Status      string           `json:"status" yaml:"status"` // active, disabled
 	RateLimit   *RateLimitConfig `json:"rate_limit,omitempty" yaml:"rate_limit,omitempty"`
+	Quota       *QuotaConfig     `json:"quota,omitempty" yaml:"quota,omitempty"`
 	ModelRoutes *ModelRouteConfig `json:"model_routes,omitempty" yaml:"model_routes,omitempty"`
+	PathRules   *PathRules       `json:"path_rules,omitempty" yaml:"path_rules,omitempty"`
 	Usage       *KeyUsageStats   `json:"usage,omitempty" yaml:"usage,omitempty"`
 	CreatedAt   time.Time        `json:"created_at" yaml:"created_at"`
 	UpdatedAt   time.Time        `json:"updated_at" yaml:"updated_at"`
 	ExpiresAt   *time.Time       `json:"expires_at,omitempty" yaml:"expires_at,omitempty"`
+
+	// 创建者（web:用户名 或 service:服务账号），用于自助创建的数量限制
+	CreatedBy       string          `json:"created_by,omitempty" yaml:"created_by,omitempty"`
+	// 自助创建后等待 admin 批准，批准前 Status 为 disabled
+	PendingApproval bool            `json:"pending_approval,omitempty" yaml:"pending_approval,omitempty"`
+	ApprovedBy      string          `json:"approved_by,omitempty" yaml:"approved_by,omitempty"`
+	ApprovedAt      *time.Time      `json:"approved_at,omitempty" yaml:"approved_at,omitempty"`
+	// 沙箱Key：请求由内置的模拟响应器应答，不访问真实上游，用于客户端开发联调
+	Sandbox         bool            `json:"sandbox,omitempty" yaml:"sandbox,omitempty"`
+	// 客户端通过 X-LLM-Timeout-Ms 可以指定的最长上游超时（毫秒），0表示不能超过全局超时
+	MaxTimeoutMs    int             `json:"max_timeout_ms,omitempty" yaml:"max_timeout_ms,omitempty"`
+	// 客户端通过 X-Gateway-Priority 可以请求的最高排队优先级，为空时最高为 normal
+	MaxPriority     RequestPriority `json:"max_priority,omitempty" yaml:"max_priority,omitempty"`
+	// 允许使用该Key的客户端地址（CIDR或单个IP，按TCP连接的对端地址判断），为空时不限制
+	AllowedIPs      []string        `json:"allowed_ips,omitempty" yaml:"allowed_ips,omitempty"`
+	// 允许的请求来源（如 https://app.example.com，取自 Origin 头部，没有时取 Referer），为空时不限制
+	AllowedOrigins  []string        `json:"allowed_origins,omitempty" yaml:"allowed_origins,omitempty"`
 }
 
 // RateLimitConfig - 限流配置
@@ -22,6 +49,15 @@ type RateLimitConfig struct {
 	RequestsPerMinute int `json:"requests_per_minute" yaml:"requests_per_minute"`
 	RequestsPerHour   int `json:"requests_per_hour" yaml:"requests_per_hour"`
 	RequestsPerDay    int `json:"requests_per_day" yaml:"requests_per_day"`
+	MaxConcurrent     int `json:"max_concurrent" yaml:"max_concurrent"` // 同时进行的请求数上限（流式请求持续到结束），0表示不限制
+}
+
+// QuotaConfig - 用量配额（按UTC自然日/自然月统计，0表示不限制）
+type QuotaConfig struct {
+	DailyTokens    int64   `json:"daily_tokens" yaml:"daily_tokens"`
+	MonthlyTokens  int64   `json:"monthly_tokens" yaml:"monthly_tokens"`
+	DailyCostUSD   float64 `json:"daily_cost_usd" yaml:"daily_cost_usd"`
+	MonthlyCostUSD float64 `json:"monthly_cost_usd" yaml:"monthly_cost_usd"`
 }
 
 
@@ -0,0 +1,65 @@
+package types
+
+import (
+	"net"
+	"net/url"
+	"strings"
+)
+
+// ParseIPRule 解析 allowed_ips 中的一项，可以是CIDR（如 10.0.0.0/8）或单个IP地址，格式无效时返回 ok=false
+func ParseIPRule(rule string) (*net.IPNet, bool) {
+	if _, network, err := net.ParseCIDR(rule); err == nil {
+		return network, true
+	}
+	ip := net.ParseIP(rule)
+	if ip == nil {
+		return nil, false
+	}
+	bits := 128
+	if ip4 := ip.To4(); ip4 != nil {
+		ip, bits = ip4, 32
+	}
+	return &net.IPNet{IP: ip, Mask: net.CIDRMask(bits, bits)}, true
+}
+
+// NormalizeOrigin 把来源规范为小写的 scheme://host[:port]，不是 http(s) 来源时返回 ok=false
+func NormalizeOrigin(origin string) (string, bool) {
+	parsed, err := url.Parse(strings.TrimSpace(origin))
+	if err != nil || (parsed.Scheme != "http" && parsed.Scheme != "https") || parsed.Host == "" {
+		return "", false
+	}
+	return strings.ToLower(parsed.Scheme + "://" + parsed.Host), true
+}
+
+// IPAllowed 检查客户端IP是否在Key的 allowed_ips 中：未配置时不限制
+func (k *GatewayAPIKey) IPAllowed(ip net.IP) bool {
+	if len(k.AllowedIPs) == 0 {
+		return true
+	}
+	if ip == nil {
+		return false
+	}
+	for _, rule := range k.AllowedIPs {
+		if network, ok := ParseIPRule(rule); ok && network.Contains(ip) {
+			return true
+		}
+	}
+	return false
+}
+
+// OriginAllowed 检查请求来源是否在Key的 allowed_origins 中：未配置时不限制，配置了时没有来源的请求也被拒绝
+func (k *GatewayAPIKey) OriginAllowed(origin string) bool {
+	if len(k.AllowedOrigins) == 0 {
+		return true
+	}
+	origin, ok := NormalizeOrigin(origin)
+	if !ok {
+		return false
+	}
+	for _, allowed := range k.AllowedOrigins {
+		if normalized, ok := NormalizeOrigin(allowed); ok && normalized == origin {
+			return true
+		}
+	}
+	return false
+}
@@ -237,7 +237,7 @@ func (config *ModelRouteConfig) CreateContextWithKey(originalModel string, gatew
 
 	// 构建合并的路由规则列表
 	var mergedRoutes []*ModelRoute
-	
+
 	// 1. 首先添加Key级别的路由规则（高优先级）
 	if gatewayKey != nil && gatewayKey.ModelRoutes != nil {
 		for i := range gatewayKey.ModelRoutes.Routes {
@@ -246,7 +246,7 @@ func (config *ModelRouteConfig) CreateContextWithKey(originalModel string, gatew
 			}
 		}
 	}
-	
+
 	// 2. 然后添加全局路由规则（低优先级）
 	if config != nil {
 		for i := range config.Routes {
@@ -255,7 +255,7 @@ func (config *ModelRouteConfig) CreateContextWithKey(originalModel string, gatew
 			}
 		}
 	}
-	
+
 	// 3. 在合并的规则中查找匹配的路由
 	for _, route := range mergedRoutes {
 		if route.Matches(originalModel) {
@@ -0,0 +1,34 @@
+package types
+
+// NotificationConfig - 告警通知配置：费用超过阈值、错误率突增、上游账号健康状态变化、配额接近用完时发送Webhook
+type NotificationConfig struct {
+	Enabled                bool             `json:"enabled" yaml:"enabled"`
+	Webhooks               []WebhookConfig  `json:"webhooks" yaml:"webhooks,omitempty"`
+	DailyCostUSD           float64          `json:"daily_cost_usd" yaml:"daily_cost_usd"`                           // 全局当日（UTC）费用阈值，0表示不告警
+	KeyDailyCostUSD        float64          `json:"key_daily_cost_usd" yaml:"key_daily_cost_usd"`                   // 单个Gateway Key当日（UTC）费用阈值，0表示不告警
+	ErrorRateThreshold     float64          `json:"error_rate_threshold" yaml:"error_rate_threshold"`               // 错误率阈值（0-1），0表示不告警
+	ErrorRateWindowMinutes int              `json:"error_rate_window_minutes" yaml:"error_rate_window_minutes"`     // 错误率统计窗口，0使用默认值15分钟
+	ErrorRateMinRequests   int              `json:"error_rate_min_requests" yaml:"error_rate_min_requests"`         // 窗口内请求数少于此值时不告警，0使用默认值20
+	HealthChanges          bool             `json:"health_changes" yaml:"health_changes"`                           // 上游账号健康状态变化时通知
+	QuotaWarningThresholds []float64        `json:"quota_warning_thresholds" yaml:"quota_warning_thresholds"`       // Key配额和上游账号限流额度的软告警阈值（占上限的比例），为空时使用 0.5、0.8、0.95
+	ErrorClassRules        []ErrorClassRule `json:"error_class_rules,omitempty" yaml:"error_class_rules,omitempty"` // 按提供商和上游错误分类的错误率告警规则
+	MaxAttempts            int              `json:"max_attempts" yaml:"max_attempts"`                               // 每次投递的最大尝试次数，0使用默认值3
+	Dir                    string           `json:"dir,omitempty" yaml:"dir,omitempty"`                             // 投递记录目录，默认 ~/.llm-gateway/notifications
+}
+
+// ErrorClassRule - 某一类上游错误在提供商的请求中占比过高时告警，如某个提供商的 quota 错误突增
+type ErrorClassRule struct {
+	Class         ErrorClass `json:"class" yaml:"class"`
+	Provider      Provider   `json:"provider,omitempty" yaml:"provider,omitempty"`             // 为空时对每个提供商分别计算
+	Threshold     float64    `json:"threshold" yaml:"threshold"`                               // 该分类的错误占提供商请求数的比例（0-1）
+	WindowMinutes int        `json:"window_minutes,omitempty" yaml:"window_minutes,omitempty"` // 统计窗口，0使用 error_rate_window_minutes
+	MinRequests   int        `json:"min_requests,omitempty" yaml:"min_requests,omitempty"`     // 窗口内提供商的请求数少于此值时不告警，0使用 error_rate_min_requests
+}
+
+// WebhookConfig - 通知的Webhook接收端
+type WebhookConfig struct {
+	Name   string   `json:"name" yaml:"name"`
+	URL    string   `json:"url" yaml:"url"`
+	Format string   `json:"format,omitempty" yaml:"format,omitempty"` // generic（默认，发送事件JSON）或 slack
+	Events []string `json:"events,omitempty" yaml:"events,omitempty"` // 订阅的事件类型，为空时订阅全部
+}
@@ -0,0 +1,23 @@
+package types
+
+import "time"
+
+// Organization - 组织（团队）：上游账号和Gateway API Key可以归属于组织，用量按组织汇总，
+// 成员是共同负责这些资源的Web用户
+type Organization struct {
+	ID        string    `json:"id" yaml:"id"`
+	Name      string    `json:"name" yaml:"name"`
+	Members   []string  `json:"members" yaml:"members,omitempty"` // 成员的Web用户名，admin 表示内置管理员
+	CreatedAt time.Time `json:"created_at" yaml:"created_at"`
+	UpdatedAt time.Time `json:"updated_at" yaml:"updated_at"`
+}
+
+// HasMember 检查用户是否是组织成员
+func (o *Organization) HasMember(username string) bool {
+	for _, member := range o.Members {
+		if member == username {
+			return true
+		}
+	}
+	return false
+}
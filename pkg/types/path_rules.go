@@ -0,0 +1,44 @@
+package types
+
+// PathRules - 代理路径访问规则（支持后缀通配符，如 /v1/files*）
+type PathRules struct {
+	// Allow 非空时只允许匹配的路径
+	Allow []string `json:"allow,omitempty" yaml:"allow,omitempty"`
+
+	// Deny 匹配的路径直接拒绝，优先于 Allow
+	Deny []string `json:"deny,omitempty" yaml:"deny,omitempty"`
+}
+
+// PathDecision 路径访问判定结果
+type PathDecision int
+
+const (
+	PathAllowed    PathDecision = iota // 允许访问
+	PathNotAllowed                     // 不在允许列表中（对外表现为404）
+	PathDenied                         // 命中拒绝列表（对外表现为403）
+)
+
+// Evaluate 判定路径是否允许访问
+func (rules *PathRules) Evaluate(path string) PathDecision {
+	if rules == nil {
+		return PathAllowed
+	}
+
+	for _, pattern := range rules.Deny {
+		if matchPattern(pattern, path) {
+			return PathDenied
+		}
+	}
+
+	if len(rules.Allow) == 0 {
+		return PathAllowed
+	}
+
+	for _, pattern := range rules.Allow {
+		if matchPattern(pattern, path) {
+			return PathAllowed
+		}
+	}
+
+	return PathNotAllowed
+}
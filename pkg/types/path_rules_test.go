@@ -0,0 +1,31 @@
+package types
+
+import "testing"
+
+func TestPathRules_Evaluate(t *testing.T) {
+	tests := []struct {
+		name  string
+		rules *PathRules
+		path  string
+		want  PathDecision
+	}{
+		{name: "nil rules", rules: nil, path: "/v1/files", want: PathAllowed},
+		{name: "empty rules", rules: &PathRules{}, path: "/v1/files", want: PathAllowed},
+		{name: "deny exact", rules: &PathRules{Deny: []string{"/v1/files"}}, path: "/v1/files", want: PathDenied},
+		{name: "deny exact does not match subpath", rules: &PathRules{Deny: []string{"/v1/files"}}, path: "/v1/files/abc", want: PathAllowed},
+		{name: "deny wildcard", rules: &PathRules{Deny: []string{"/v1/files*"}}, path: "/v1/files/abc/content", want: PathDenied},
+		{name: "deny other path", rules: &PathRules{Deny: []string{"/v1/files*"}}, path: "/v1/messages", want: PathAllowed},
+		{name: "allow listed", rules: &PathRules{Allow: []string{"/v1/chat/completions", "/v1/embeddings"}}, path: "/v1/embeddings", want: PathAllowed},
+		{name: "allow wildcard", rules: &PathRules{Allow: []string{"/v1/chat/*"}}, path: "/v1/chat/completions", want: PathAllowed},
+		{name: "not in allow list", rules: &PathRules{Allow: []string{"/v1/chat/completions"}}, path: "/v1/messages", want: PathNotAllowed},
+		{name: "deny wins over allow", rules: &PathRules{Allow: []string{"/v1/*"}, Deny: []string{"/v1/files*"}}, path: "/v1/files", want: PathDenied},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := tt.rules.Evaluate(tt.path); got != tt.want {
+				t.Errorf("Evaluate(%q) = %v, want %v", tt.path, got, tt.want)
+			}
+		})
+	}
+}
@@ -0,0 +1,16 @@
+package types
+
+// PricingConfig - 自定义模型价格，覆盖内置价格表（美元/百万token）
+type PricingConfig struct {
+	Models []ModelPricing `json:"models" yaml:"models,omitempty"`
+}
+
+// ModelPricing - 单个模型（按前缀匹配）的价格
+type ModelPricing struct {
+	Model      string   `json:"model" yaml:"model"`                           // 模型名前缀，最长前缀优先
+	Provider   Provider `json:"provider,omitempty" yaml:"provider,omitempty"` // 只对该提供商生效，为空时对所有提供商生效
+	Input      float64  `json:"input" yaml:"input"`
+	Output     float64  `json:"output" yaml:"output"`
+	CacheRead  float64  `json:"cache_read,omitempty" yaml:"cache_read,omitempty"`   // 缓存读取的输入token，0时按 input 计价
+	CacheWrite float64  `json:"cache_write,omitempty" yaml:"cache_write,omitempty"` // 写入缓存的输入token，0时按 input 计价
+}
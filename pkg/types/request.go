@@ -4,6 +4,7 @@ import (
 	"encoding/json"
 	"fmt"
 	"strings"
+	"time"
 )
 
 // UnifiedRequest - 统一的请求结构
@@ -21,6 +22,18 @@ type UnifiedRequest struct {
 	OriginalMetadata map[string]interface{}   `json:"-"` // 原始metadata字段
 	GatewayKeyID     string                   `json:"-"` // 发起请求的Gateway API Key ID
 	UpstreamID       string                   `json:"-"` // 选中的上游账号ID
+	RequestID        string                   `json:"-"` // 网关请求ID，作为 X-Request-Id 转发给上游
+	Timeout          time.Duration            `json:"-"` // 客户端指定的上游超时，0使用全局超时
+	Deadline         time.Time                `json:"-"` // 客户端指定超时时整个上游调用的截止时间，故障切换和重试共用
+	AcceptGzip       bool                     `json:"-"` // 客户端接受 gzip 且响应不被审计或缓存记录，可以原样转发上游的压缩响应体
+
+	// ExtraParams 转换器未解析的顶层参数，按目标提供商的允许列表过滤后原样转发（参与响应缓存键的计算）
+	ExtraParams map[string]json.RawMessage `json:"extra_params,omitempty"`
+}
+
+// HasTools 请求是否声明了工具（tools 或 tool_choice）
+func (r *UnifiedRequest) HasTools() bool {
+	return len(r.Tools) > 0 || r.ToolChoice != nil
 }
 
 // Message - 通用消息结构
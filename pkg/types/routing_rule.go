@@ -0,0 +1,294 @@
+package types
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+	"time"
+)
+
+// 路由规则的动作
+const (
+	RoutingActionRoute = "route" // 转发到指定的提供商和账号池（默认）
+	RoutingActionDeny  = "deny"  // 拒绝请求
+)
+
+// RoutingRule - 按模型、Key标签、时间段、估算token数和是否使用工具匹配请求的路由规则（优先于按模型名推断提供商）
+type RoutingRule struct {
+	ID          string    `json:"id" yaml:"id"`
+	Pattern     string    `json:"pattern" yaml:"pattern"` // 模型名，支持后缀通配符，如 gpt-4*
+	Provider    Provider  `json:"provider,omitempty" yaml:"provider,omitempty"`
+	UpstreamIDs []string  `json:"upstream_ids,omitempty" yaml:"upstream_ids,omitempty"` // 账号池，为空时使用该提供商的全部账号
+	Priority    int       `json:"priority" yaml:"priority"`                             // 数字越小优先级越高
+	Enabled     bool      `json:"enabled" yaml:"enabled"`
+	Description string    `json:"description,omitempty" yaml:"description,omitempty"`
+	CreatedAt   time.Time `json:"created_at" yaml:"created_at"`
+	UpdatedAt   time.Time `json:"updated_at" yaml:"updated_at"`
+
+	// 附加的匹配条件，为空时不限制
+	KeyTags        []string `json:"key_tags,omitempty" yaml:"key_tags,omitempty"`                 // 请求的Key带有其中任意一个标签
+	TimeOfDay      string   `json:"time_of_day,omitempty" yaml:"time_of_day,omitempty"`           // 时间段 HH:MM-HH:MM，结束早于开始时跨越午夜
+	Timezone       string   `json:"timezone,omitempty" yaml:"timezone,omitempty"`                 // time_of_day 的时区（IANA名称），默认UTC
+	MinInputTokens int      `json:"min_input_tokens,omitempty" yaml:"min_input_tokens,omitempty"` // 估算的输入token数下限
+	MaxInputTokens int      `json:"max_input_tokens,omitempty" yaml:"max_input_tokens,omitempty"` // 估算的输入token数上限
+	HasTools       *bool    `json:"has_tools,omitempty" yaml:"has_tools,omitempty"`               // true只匹配声明了工具的请求，false只匹配没有工具的请求
+
+	// 动作：route 转发到 Provider（可不设置，只调整排队优先级），deny 拒绝请求
+	Action        string          `json:"action,omitempty" yaml:"action,omitempty"`
+	QueuePriority RequestPriority `json:"queue_priority,omitempty" yaml:"queue_priority,omitempty"` // 覆盖请求的排队优先级
+
+	window *timeWindow // Prepare 解析好的 time_of_day 和 timezone
+}
+
+// timeWindow - 解析后的时间段，start/end 为一天中的分钟数
+type timeWindow struct {
+	start    int
+	end      int
+	location *time.Location
+}
+
+// contains 检查时间是否落在时间段内，结束早于开始时跨越午夜
+func (w *timeWindow) contains(t time.Time) bool {
+	local := t.In(w.location)
+	minute := local.Hour()*60 + local.Minute()
+	if w.start <= w.end {
+		return minute >= w.start && minute < w.end
+	}
+	return minute >= w.start || minute < w.end
+}
+
+// RoutingContext - 评估路由规则时的请求信息
+type RoutingContext struct {
+	Model       string
+	KeyTags     []string
+	InputTokens int       // 转发前估算的输入token数
+	Time        time.Time // 请求时间
+	HasTools    bool      // 请求声明了工具（tools 或 tool_choice）
+}
+
+// RoutingDecision - 路由规则的评估结果，每种动作取第一个设置了该动作的命中规则
+type RoutingDecision struct {
+	Deny      *RoutingRule   // 命中的拒绝规则，不为nil时请求被拒绝
+	Route     *RoutingRule   // 决定提供商和账号池的规则
+	Priority  *RoutingRule   // 决定排队优先级的规则
+	Fallbacks []*RoutingRule // Route 之后命中的、转发到其他提供商的规则，Route 的提供商过载时依次改用
+}
+
+// Matches 检查模型是否匹配此规则（大小写不敏感）
+func (rule *RoutingRule) Matches(model string) bool {
+	if !rule.Enabled {
+		return false
+	}
+	return matchPattern(strings.ToLower(rule.Pattern), strings.ToLower(model))
+}
+
+// HasRequestConditions 规则是否有模型以外的匹配条件
+func (rule *RoutingRule) HasRequestConditions() bool {
+	return len(rule.KeyTags) > 0 || rule.TimeOfDay != "" || rule.MinInputTokens > 0 || rule.MaxInputTokens > 0 || rule.HasTools != nil
+}
+
+// Denies 规则是否拒绝请求
+func (rule *RoutingRule) Denies() bool {
+	return rule.Action == RoutingActionDeny
+}
+
+// MatchesRequest 检查请求是否满足规则的所有条件
+func (rule *RoutingRule) MatchesRequest(ctx *RoutingContext) bool {
+	if !rule.Matches(ctx.Model) {
+		return false
+	}
+	if len(rule.KeyTags) > 0 && !hasAnyTag(ctx.KeyTags, rule.KeyTags) {
+		return false
+	}
+	if rule.MinInputTokens > 0 && ctx.InputTokens < rule.MinInputTokens {
+		return false
+	}
+	if rule.MaxInputTokens > 0 && ctx.InputTokens > rule.MaxInputTokens {
+		return false
+	}
+	if rule.HasTools != nil && *rule.HasTools != ctx.HasTools {
+		return false
+	}
+	if rule.TimeOfDay != "" {
+		window := rule.window
+		if window == nil {
+			// 没有经过 Prepare 的规则（如直接构造的规则）在匹配时解析
+			var err error
+			if window, err = rule.parseTimeWindow(); err != nil {
+				return false
+			}
+		}
+		return window.contains(ctx.Time)
+	}
+	return true
+}
+
+// Prepare 预先解析 time_of_day 和 timezone，之后匹配请求时不再解析和加载时区。
+// 加载配置和修改规则时调用；设置无效时不缓存，由 Validate 报告错误
+func (rule *RoutingRule) Prepare() {
+	rule.window = nil
+	if rule.TimeOfDay != "" {
+		rule.window, _ = rule.parseTimeWindow()
+	}
+}
+
+// parseTimeWindow 解析规则的时间段和时区，未设置时区时使用UTC
+func (rule *RoutingRule) parseTimeWindow() (*timeWindow, error) {
+	start, end, err := ParseTimeOfDay(rule.TimeOfDay)
+	if err != nil {
+		return nil, err
+	}
+	location := time.UTC
+	if rule.Timezone != "" {
+		if location, err = time.LoadLocation(rule.Timezone); err != nil {
+			return nil, fmt.Errorf("无效的时区: %s", rule.Timezone)
+		}
+	}
+	return &timeWindow{start: start, end: end, location: location}, nil
+}
+
+// Validate 检查规则的条件和动作是否有效
+func (rule *RoutingRule) Validate() error {
+	if rule.Pattern == "" {
+		return fmt.Errorf("模型匹配模式不能为空")
+	}
+	switch rule.Action {
+	case "", RoutingActionRoute:
+		if rule.Provider == "" && rule.QueuePriority == "" {
+			return fmt.Errorf("转发规则必须设置 provider 或 queue_priority")
+		}
+	case RoutingActionDeny:
+	default:
+		return fmt.Errorf("无效的动作: %s，必须是 route 或 deny", rule.Action)
+	}
+	if rule.QueuePriority != "" {
+		if _, ok := ParseRequestPriority(string(rule.QueuePriority)); !ok {
+			return fmt.Errorf("无效的排队优先级: %s", rule.QueuePriority)
+		}
+	}
+	if rule.TimeOfDay != "" {
+		if _, _, err := ParseTimeOfDay(rule.TimeOfDay); err != nil {
+			return err
+		}
+	}
+	if rule.Timezone != "" {
+		if _, err := time.LoadLocation(rule.Timezone); err != nil {
+			return fmt.Errorf("无效的时区: %s", rule.Timezone)
+		}
+	}
+	if rule.MinInputTokens < 0 || rule.MaxInputTokens < 0 || (rule.MaxInputTokens > 0 && rule.MinInputTokens > rule.MaxInputTokens) {
+		return fmt.Errorf("token数范围无效: min_input_tokens=%d, max_input_tokens=%d", rule.MinInputTokens, rule.MaxInputTokens)
+	}
+	for _, tag := range rule.KeyTags {
+		if tag == "" {
+			return fmt.Errorf("Key标签不能为空")
+		}
+	}
+	return nil
+}
+
+// InPool 检查账号是否属于规则的账号池，未配置账号池时总是返回true
+func (rule *RoutingRule) InPool(upstreamID string) bool {
+	if len(rule.UpstreamIDs) == 0 {
+		return true
+	}
+	for _, id := range rule.UpstreamIDs {
+		if id == upstreamID {
+			return true
+		}
+	}
+	return false
+}
+
+// ParseTimeOfDay 解析 HH:MM-HH:MM 形式的时间段，返回开始和结束时间（当天的分钟数）
+func ParseTimeOfDay(value string) (int, int, error) {
+	parts := strings.Split(value, "-")
+	if len(parts) != 2 {
+		return 0, 0, fmt.Errorf("无效的时间段: %s，格式应为 HH:MM-HH:MM", value)
+	}
+	var minutes [2]int
+	for i, part := range parts {
+		clock, err := time.Parse("15:04", strings.TrimSpace(part))
+		if err != nil {
+			return 0, 0, fmt.Errorf("无效的时间段: %s，格式应为 HH:MM-HH:MM", value)
+		}
+		minutes[i] = clock.Hour()*60 + clock.Minute()
+	}
+	if minutes[0] == minutes[1] {
+		return 0, 0, fmt.Errorf("时间段的开始和结束不能相同: %s", value)
+	}
+	return minutes[0], minutes[1], nil
+}
+
+// sortRoutingRules 按优先级排序规则的副本，优先级相同时保持原有顺序
+func sortRoutingRules(rules []*RoutingRule) []*RoutingRule {
+	sorted := make([]*RoutingRule, len(rules))
+	copy(sorted, rules)
+	sort.SliceStable(sorted, func(i, j int) bool {
+		return sorted[i].Priority < sorted[j].Priority
+	})
+	return sorted
+}
+
+// MatchRoutingRule 按优先级查找第一个匹配模型的转发规则，没有匹配时返回nil。
+// 只有模型条件的规则参与匹配，其他条件和拒绝动作需要完整的请求信息，由 EvaluateRoutingRules 处理
+func MatchRoutingRule(rules []*RoutingRule, model string) *RoutingRule {
+	for _, rule := range sortRoutingRules(rules) {
+		if rule.Provider == "" || rule.Denies() || rule.HasRequestConditions() {
+			continue
+		}
+		if rule.Matches(model) {
+			return rule
+		}
+	}
+	return nil
+}
+
+// EvaluateRoutingRules 按优先级评估所有规则：命中拒绝规则时停止评估，
+// 提供商和排队优先级分别取第一个设置了它们的命中规则，之后命中的其他提供商的规则作为备选
+func EvaluateRoutingRules(rules []*RoutingRule, ctx *RoutingContext) RoutingDecision {
+	var decision RoutingDecision
+	for _, rule := range sortRoutingRules(rules) {
+		if !rule.MatchesRequest(ctx) {
+			continue
+		}
+		if rule.Denies() {
+			decision.Deny = rule
+			return decision
+		}
+		if decision.Route == nil && rule.Provider != "" {
+			decision.Route = rule
+		} else if rule.Provider != "" && decision.fallbackProvider(rule.Provider) {
+			decision.Fallbacks = append(decision.Fallbacks, rule)
+		}
+		if decision.Priority == nil && rule.QueuePriority != "" {
+			decision.Priority = rule
+		}
+	}
+	return decision
+}
+
+// fallbackProvider 检查提供商是否还没有出现在 Route 和备选规则中
+func (d *RoutingDecision) fallbackProvider(provider Provider) bool {
+	if d.Route.Provider == provider {
+		return false
+	}
+	for _, rule := range d.Fallbacks {
+		if rule.Provider == provider {
+			return false
+		}
+	}
+	return true
+}
+
+// hasAnyTag 检查 tags 是否包含 wanted 中的任意一个
+func hasAnyTag(tags, wanted []string) bool {
+	for _, tag := range tags {
+		for _, w := range wanted {
+			if tag == w {
+				return true
+			}
+		}
+	}
+	return false
+}
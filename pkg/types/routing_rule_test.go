@@ -0,0 +1,41 @@
+package types
+
+import (
+	"testing"
+	"time"
+)
+
+func TestRoutingRule_TimeWindow(t *testing.T) {
+	// 东京 09:00-18:00 即 UTC 00:00-09:00
+	rule := &RoutingRule{Pattern: "*", Provider: ProviderOpenAI, Enabled: true, TimeOfDay: "09:00-18:00", Timezone: "Asia/Tokyo"}
+	inside := &RoutingContext{Model: "gpt-4o", Time: time.Date(2026, 1, 1, 3, 0, 0, 0, time.UTC)}
+	outside := &RoutingContext{Model: "gpt-4o", Time: time.Date(2026, 1, 1, 12, 0, 0, 0, time.UTC)}
+
+	// 未经过 Prepare 的规则在匹配时解析
+	if !rule.MatchesRequest(inside) || rule.MatchesRequest(outside) {
+		t.Error("unprepared rule should match only inside its time window")
+	}
+
+	// Prepare 后缓存解析结果，匹配时使用缓存的时区
+	rule.Prepare()
+	if rule.window == nil || rule.window.location.String() != "Asia/Tokyo" {
+		t.Fatalf("Prepare() window = %+v, want the Asia/Tokyo location", rule.window)
+	}
+	if !rule.MatchesRequest(inside) || rule.MatchesRequest(outside) {
+		t.Error("prepared rule should match only inside its time window")
+	}
+
+	// 跨越午夜的时间段
+	rule.TimeOfDay, rule.Timezone = "22:00-06:00", ""
+	rule.Prepare()
+	if !rule.MatchesRequest(&RoutingContext{Model: "gpt-4o", Time: time.Date(2026, 1, 1, 23, 0, 0, 0, time.UTC)}) || rule.MatchesRequest(outside) {
+		t.Error("overnight window should match at 23:00 UTC and not at 12:00 UTC")
+	}
+
+	// 无效的设置不缓存，也不匹配任何请求
+	rule.Timezone = "Mars/Base"
+	rule.Prepare()
+	if rule.window != nil || rule.MatchesRequest(inside) {
+		t.Error("rule with an invalid timezone should not be cached or match")
+	}
+}
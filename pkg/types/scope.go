@@ -0,0 +1,104 @@
+package types
+
+import (
+	"sort"
+	"strings"
+)
+
+// Gateway API Key作用域类型，作用域写作 "类型:值"，值支持后缀通配符（如 model:claude-3-*）
+const (
+	ScopeProvider = "provider" // 允许的提供商，如 provider:anthropic
+	ScopeModel    = "model"    // 允许的模型（实际发往上游的模型），如 model:gpt-4o-mini，可加提供商前缀，如 model:openai/*
+	ScopeEndpoint = "endpoint" // 允许的网关端点路径，如 endpoint:/v1/messages
+)
+
+// ParseScope 解析 "类型:值" 形式的作用域，类型未知、值为空或通配符不在末尾时返回 ok=false
+func ParseScope(scope string) (kind, pattern string, ok bool) {
+	kind, pattern, found := strings.Cut(scope, ":")
+	if !found || pattern == "" || strings.Contains(strings.TrimSuffix(pattern, "*"), "*") {
+		return "", "", false
+	}
+	switch kind {
+	case ScopeProvider, ScopeModel, ScopeEndpoint:
+		return kind, pattern, true
+	default:
+		return "", "", false
+	}
+}
+
+// ScopeAllows 检查Key的作用域是否允许某类型的值：未配置该类型的作用域时不限制，
+// 配置了时需匹配其中之一。模型作用域可能带提供商前缀，应使用 ModelScopes 检查
+func (k *GatewayAPIKey) ScopeAllows(kind, value string) bool {
+	restricted := false
+	for _, scope := range k.Scopes {
+		scopeKind, pattern, ok := ParseScope(scope)
+		if !ok || scopeKind != kind {
+			continue
+		}
+		restricted = true
+		if matchPattern(pattern, value) {
+			return true
+		}
+	}
+	return !restricted
+}
+
+// ModelScopes 编译后的模型作用域：精确值用集合查找，通配符按前缀长度查找，匹配开销与作用域数量无关。
+// 模型名和 "提供商/模型名" 都参与匹配，因此 model:openai/* 允许该提供商的所有模型
+type ModelScopes struct {
+	restricted    bool
+	exact         map[string]bool
+	prefixes      map[string]bool
+	prefixLengths []int // 出现过的前缀长度（升序），只查找这些长度的前缀
+}
+
+// CompileModelScopes 编译作用域列表中的模型作用域，忽略其他类型和无效的作用域
+func CompileModelScopes(scopes []string) *ModelScopes {
+	compiled := &ModelScopes{exact: make(map[string]bool), prefixes: make(map[string]bool)}
+	lengths := make(map[int]bool)
+	for _, scope := range scopes {
+		kind, pattern, ok := ParseScope(scope)
+		if !ok || kind != ScopeModel {
+			continue
+		}
+		compiled.restricted = true
+		if prefix, wildcard := strings.CutSuffix(pattern, "*"); wildcard {
+			compiled.prefixes[prefix] = true
+			lengths[len(prefix)] = true
+		} else {
+			compiled.exact[pattern] = true
+		}
+	}
+	for length := range lengths {
+		compiled.prefixLengths = append(compiled.prefixLengths, length)
+	}
+	sort.Ints(compiled.prefixLengths)
+	return compiled
+}
+
+// Allows 检查提供商的模型是否在作用域内，未配置模型作用域时不限制
+func (s *ModelScopes) Allows(provider Provider, model string) bool {
+	if !s.restricted {
+		return true
+	}
+	if model == "" {
+		return false
+	}
+	return s.match(model) || (provider != "" && s.match(string(provider)+"/"+model))
+}
+
+// match 检查值是否精确匹配或以某个通配符前缀开头
+func (s *ModelScopes) match(value string) bool {
+	if s.exact[value] {
+		return true
+	}
+	for _, length := range s.prefixLengths {
+		if length > len(value) {
+			break
+		}
+		if s.prefixes[value[:length]] {
+			return true
+		}
+	}
+	return false
+}
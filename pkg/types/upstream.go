@@ -1,6 +1,9 @@
 package types
 
-import "time"
+import (
+	"regexp"
+	"time"
+)
 
 // UpstreamAccount - 上游账号结构 (用于调用LLM服务)
 type UpstreamAccount struct {
@@ -20,8 +23,31 @@ type UpstreamAccount struct {
 	Usage           *UpstreamUsageStats `json:"usage,omitempty" yaml:"usage,omitempty"`
 	LastHealthCheck *time.Time          `json:"last_health_check,omitempty" yaml:"last_health_check,omitempty"`
 	HealthStatus    string              `json:"health_status,omitempty" yaml:"health_status,omitempty"`
+	HealthLatencyMs int64               `json:"health_latency_ms,omitempty" yaml:"health_latency_ms,omitempty"` // 最近一次健康探测的延迟
+	HealthError     string              `json:"health_error,omitempty" yaml:"health_error,omitempty"`           // 最近一次健康探测失败的原因
+	MaxConcurrent   int                 `json:"max_concurrent,omitempty" yaml:"max_concurrent,omitempty"`       // 同时转发到此账号的请求数上限，0表示不限制
+	APIVersion      string              `json:"api_version,omitempty" yaml:"api_version,omitempty"`             // 固定的上游API版本（如 anthropic-version），覆盖默认值
+	Weight          *int                `json:"weight,omitempty" yaml:"weight,omitempty"`                       // 同一优先级内的流量权重，未设置时为默认值100，0表示不分配流量
+	Priority        int                 `json:"priority,omitempty" yaml:"priority,omitempty"`                   // 数字越小越优先，更优先的账号都不可用时才使用
+	Deployments     map[string]string   `json:"deployments,omitempty" yaml:"deployments,omitempty"`             // 模型名到 Azure OpenAI 部署名或 Bedrock 模型ID的映射，未映射的模型直接使用模型名
+	AWS             *AWSCredentials     `json:"aws,omitempty" yaml:"aws,omitempty"`                             // Bedrock 账号的AWS凭证，用于 SigV4 签名
+	Models          []string            `json:"models,omitempty" yaml:"models,omitempty"`                       // OpenAI 兼容后端的 /v1/models 返回的模型，健康探测成功时更新
+	DisabledReason  string              `json:"disabled_reason,omitempty" yaml:"disabled_reason,omitempty"`     // 被自动停用的原因，重新启用时清空
+	OrgID           string              `json:"org_id,omitempty" yaml:"org_id,omitempty"`                       // 所属组织，为空时是所有Key共用的账号，否则只有同一组织的Key可以使用
+	Pool            string              `json:"pool,omitempty" yaml:"pool,omitempty"`                           // 账号池名称：同一提供商、同一账号池的账号（如同一组织的多个Key）作为一份容量，选中时在成员之间轮换
 	CreatedAt       time.Time           `json:"created_at" yaml:"created_at"`
 	UpdatedAt       time.Time           `json:"updated_at" yaml:"updated_at"`
+
+	// CircuitBreaker 覆盖 health_check.circuit_breaker 的熔断器参数，为nil时使用全局参数
+	CircuitBreaker *CircuitBreakerConfig `json:"circuit_breaker,omitempty" yaml:"circuit_breaker,omitempty"`
+}
+
+// AWSCredentials - AWS访问凭证
+type AWSCredentials struct {
+	AccessKeyID     string `json:"access_key_id" yaml:"access_key_id"`
+	SecretAccessKey string `json:"secret_access_key,omitempty" yaml:"secret_access_key"`
+	SessionToken    string `json:"session_token,omitempty" yaml:"session_token,omitempty"` // 临时凭证的会话令牌
+	Region          string `json:"region" yaml:"region"`
 }
 
 // UpstreamUsageStats - 上游账号使用统计
@@ -35,3 +61,55 @@ type UpstreamUsageStats struct {
 	AvgLatency         float64    `json:"avg_latency_ms" yaml:"avg_latency_ms"`
 	ErrorRate          float64    `json:"error_rate" yaml:"error_rate"`
 }
+
+const (
+	DefaultUpstreamWeight = 100   // 未设置权重的账号使用的路由权重
+	MaxUpstreamWeight     = 10000 // 账号路由权重的上限
+)
+
+// ServesModel 检查后端发现的模型列表是否包含该模型
+func (a *UpstreamAccount) ServesModel(model string) bool {
+	for _, served := range a.Models {
+		if served == model {
+			return true
+		}
+	}
+	return false
+}
+
+// AvailableTo 检查账号能否为某个组织的Key所用：未归属组织的账号对所有Key可用，归属组织的账号只对同一组织的Key可用
+func (a *UpstreamAccount) AvailableTo(orgID string) bool {
+	return a.OrgID == "" || a.OrgID == orgID
+}
+
+// PoolKey 返回账号所属账号池的唯一标识（提供商/账号池名称），未加入账号池时返回空字符串
+func (a *UpstreamAccount) PoolKey() string {
+	if a.Pool == "" {
+		return ""
+	}
+	return string(a.Provider) + "/" + a.Pool
+}
+
+// EffectiveWeight 返回账号的路由权重，未设置时为 DefaultUpstreamWeight；0 表示不分配流量（用于排空账号）
+func (a *UpstreamAccount) EffectiveWeight() int {
+	if a.Weight == nil {
+		return DefaultUpstreamWeight
+	}
+	return *a.Weight
+}
+
+// Deployment 返回模型在账号中的部署名（Azure OpenAI）或模型ID（Bedrock），未配置映射时使用模型名
+func (a *UpstreamAccount) Deployment(model string) string {
+	if deployment := a.Deployments[model]; deployment != "" {
+		return deployment
+	}
+	return model
+}
+
+// apiVersionPattern 上游API版本格式，如 2023-06-01、2024-10-21、2024-05-01-preview
+var apiVersionPattern = regexp.MustCompile(`^[A-Za-z0-9][A-Za-z0-9._-]{0,63}$`)
+
+// ValidAPIVersion 检查固定的上游API版本格式：字母或数字开头，可含 . _ -，最长64个字符
+func ValidAPIVersion(version string) bool {
+	return apiVersionPattern.MatchString(version)
+}
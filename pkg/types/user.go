@@ -0,0 +1,46 @@
+package types
+
+import "time"
+
+// Web管理界面角色，权限从高到低
+const (
+	RoleAdmin    = "admin"    // 全部权限：用户管理、系统设置、价格、上游账号与Key管理
+	RoleOperator = "operator" // 查看全部数据，执行健康探测、发送测试通知、管理公告
+	RoleViewer   = "viewer"   // 只读
+)
+
+// RoleRank 角色的权限等级，数值越大权限越高，未知角色为0
+func RoleRank(role string) int {
+	switch role {
+	case RoleAdmin:
+		return 3
+	case RoleOperator:
+		return 2
+	case RoleViewer:
+		return 1
+	default:
+		return 0
+	}
+}
+
+// WebUser - Web管理界面用户；server.web.password 对应内置的 admin 用户，不在此列表中
+type WebUser struct {
+	Username           string    `yaml:"username" json:"username"`
+	Role               string    `yaml:"role" json:"role"`
+	PasswordHash       string    `yaml:"password_hash" json:"-"` // PBKDF2-HMAC-SHA256(密码, salt)，早期版本为 SHA-256(salt + 密码)
+	Salt               string    `yaml:"salt" json:"-"`
+	// PasswordIterations 计算密码哈希的 PBKDF2 迭代次数，为0表示早期版本的 SHA-256 哈希（下次登录成功后升级）
+	PasswordIterations int       `yaml:"password_iterations,omitempty" json:"-"`
+	CreatedAt          time.Time `yaml:"created_at" json:"created_at"`
+}
+
+// ServiceAccount - 管理网关自身的自动化程序使用的非交互凭证：用 client_id/client_secret 换取短期访问令牌，
+// 与Web用户登录和数据面的 Gateway API Key 分开
+type ServiceAccount struct {
+	ClientID   string    `yaml:"client_id" json:"client_id"`
+	Name       string    `yaml:"name" json:"name"`
+	Role       string    `yaml:"role" json:"role"`
+	SecretHash string    `yaml:"secret_hash" json:"-"` // SHA-256(salt + client_secret)
+	Salt       string    `yaml:"salt" json:"-"`
+	CreatedAt  time.Time `yaml:"created_at" json:"created_at"`
+}
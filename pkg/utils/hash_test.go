@@ -0,0 +1,50 @@
+package utils
+
+import (
+	"encoding/hex"
+	"testing"
+)
+
+func TestSHA256Hex(t *testing.T) {
+	// FIPS 180-2 附录B.1 测试向量
+	want := "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+	if got := SHA256Hex("abc"); got != want {
+		t.Errorf("SHA256Hex(\"abc\") = %s, want %s", got, want)
+	}
+	if got := SHA256HexBytes([]byte("abc")); got != want {
+		t.Errorf("SHA256HexBytes(\"abc\") = %s, want %s", got, want)
+	}
+	// 空请求体的摘要（SigV4 中无请求体时的 x-amz-content-sha256）
+	if got := SHA256HexBytes(nil); got != "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855" {
+		t.Errorf("SHA256HexBytes(nil) = %s", got)
+	}
+}
+
+func TestPBKDF2SHA256(t *testing.T) {
+	// RFC 7914 第11节的 PBKDF2-HMAC-SHA256 测试向量
+	key := PBKDF2SHA256([]byte("passwd"), []byte("salt"), 1, 64)
+	want := "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc" +
+		"49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+	if got := hex.EncodeToString(key); got != want {
+		t.Errorf("PBKDF2SHA256() = %s, want %s", got, want)
+	}
+}
+
+func TestSecureEqual(t *testing.T) {
+	tests := []struct {
+		a, b string
+		want bool
+	}{
+		{a: "secret", b: "secret", want: true},
+		{a: "", b: "", want: true},
+		{a: "secret", b: "Secret", want: false},
+		{a: "secret", b: "secret-longer", want: false},
+		{a: "secret", b: "", want: false},
+	}
+
+	for _, tt := range tests {
+		if got := SecureEqual(tt.a, tt.b); got != tt.want {
+			t.Errorf("SecureEqual(%q, %q) = %v, want %v", tt.a, tt.b, got, tt.want)
+		}
+	}
+}
@@ -0,0 +1,32 @@
+package utils
+
+import (
+	"fmt"
+	"unicode/utf8"
+)
+
+// TruncateUTF8 按字节上限截断字符串，保证不会切断多字节UTF-8字符
+func TruncateUTF8(s string, maxBytes int) string {
+	if maxBytes <= 0 {
+		return ""
+	}
+	if len(s) <= maxBytes {
+		return s
+	}
+
+	// 从上限位置向前回退到字符起始字节
+	cut := maxBytes
+	for cut > 0 && !utf8.RuneStart(s[cut]) {
+		cut--
+	}
+	return s[:cut]
+}
+
+// Excerpt 截取请求/响应体片段用于日志和存储，超出部分以截断标记代替
+func Excerpt(data []byte, maxBytes int) string {
+	if len(data) <= maxBytes {
+		return string(data)
+	}
+	head := TruncateUTF8(string(data), maxBytes)
+	return fmt.Sprintf("%s...(truncated %d bytes)", head, len(data)-len(head))
+}
@@ -0,0 +1,66 @@
+package utils
+
+import (
+	"strings"
+	"testing"
+	"unicode/utf8"
+)
+
+func TestTruncateUTF8(t *testing.T) {
+	tests := []struct {
+		name     string
+		input    string
+		maxBytes int
+		want     string
+	}{
+		{name: "ASCII未超限", input: "hello", maxBytes: 10, want: "hello"},
+		{name: "ASCII截断", input: "hello world", maxBytes: 5, want: "hello"},
+		{name: "中文落在字符边界", input: "你好世界", maxBytes: 6, want: "你好"},
+		{name: "中文落在字符中间", input: "你好世界", maxBytes: 7, want: "你好"},
+		{name: "中文不足一个字符", input: "你好", maxBytes: 2, want: ""},
+		{name: "emoji不被切断", input: "ok😀😀", maxBytes: 5, want: "ok"},
+		{name: "emoji完整保留", input: "ok😀😀", maxBytes: 6, want: "ok😀"},
+		{name: "混合内容", input: "a中😀b", maxBytes: 8, want: "a中😀"},
+		{name: "零上限", input: "abc", maxBytes: 0, want: ""},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := TruncateUTF8(tt.input, tt.maxBytes)
+			if got != tt.want {
+				t.Errorf("TruncateUTF8(%q, %d) = %q, want %q", tt.input, tt.maxBytes, got, tt.want)
+			}
+			if !utf8.ValidString(got) {
+				t.Errorf("截断结果不是合法的UTF-8: %q", got)
+			}
+		})
+	}
+}
+
+func TestTruncateUTF8_AllOffsets(t *testing.T) {
+	input := "日本語テキスト👨‍👩‍👧 mixed 内容"
+	for i := 0; i <= len(input)+1; i++ {
+		got := TruncateUTF8(input, i)
+		if !utf8.ValidString(got) {
+			t.Fatalf("上限 %d 时截断结果不是合法的UTF-8: %q", i, got)
+		}
+		if len(got) > i {
+			t.Fatalf("上限 %d 时结果长度 %d 超出上限", i, len(got))
+		}
+		if !strings.HasPrefix(input, got) {
+			t.Fatalf("上限 %d 时结果不是原字符串前缀: %q", i, got)
+		}
+	}
+}
+
+func TestExcerpt(t *testing.T) {
+	if got := Excerpt([]byte("短内容"), 100); got != "短内容" {
+		t.Errorf("未超限时应原样返回, got %q", got)
+	}
+
+	got := Excerpt([]byte("错误信息：上游服务不可用"), 10)
+	want := "错误信...(truncated 27 bytes)"
+	if got != want {
+		t.Errorf("Excerpt() = %q, want %q", got, want)
+	}
+}
@@ -230,12 +230,39 @@ class LLMGatewayApp {
             ]);
             
             this.updateDashboardCounts(upstreamAccounts, apiKeys, config);
+            await this.loadAnnouncements();
         } catch (error) {
             console.error('Failed to load dashboard:', error);
             this.updateSystemStatus(false);
         }
     }
 
+    async loadAnnouncements() {
+        const banner = document.getElementById('announcement-banner');
+        if (!banner) {
+            return;
+        }
+
+        const response = await this.apiCall('/announcements?active=true');
+        const announcements = response.data || [];
+        banner.innerHTML = announcements.map(item => `
+            <div class="alert ${item.level === 'info' ? 'alert-info' : 'alert-warning'} announcement">
+                <strong>${this.escapeHtml(item.title)}</strong>
+                <span>${this.escapeHtml(item.message)}</span>
+                <button class="btn btn-secondary btn-small" onclick="app.dismissAnnouncement('${item.id}')">&times;</button>
+            </div>
+        `).join('');
+    }
+
+    async dismissAnnouncement(id) {
+        try {
+            await this.apiCall(`/announcements/${id}/dismiss`, 'POST');
+            await this.loadAnnouncements();
+        } catch (error) {
+            console.error('Failed to dismiss announcement:', error);
+        }
+    }
+
     updateSystemStatus(healthy) {
         const statusDot = document.getElementById('system-status');
         const statusText = document.getElementById('system-status-text');